@@ -1,6 +1,6 @@
 mod utils;
 
-use darklua_core::{Configuration, Options, Resources};
+use darklua_core::Configuration;
 use utils::set_panic_hook;
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 
@@ -24,29 +24,8 @@ pub fn process_code(code: &str, opt_config: JsValue) -> Result<String, JsValue>
             })?
     };
 
-    let resources = Resources::from_memory();
-    const LOCATION: &str = "file.lua";
-    resources.write(LOCATION, code).unwrap();
-
-    let worker_tree = darklua_core::process(
-        &resources,
-        Options::new(LOCATION).with_configuration(config),
-    )
-    .map_err(|err| err.to_string())?;
-
-    let errors = worker_tree.collect_errors();
-
-    if errors.is_empty() {
-        let lua_code = resources.get(LOCATION).unwrap();
-
-        Ok(lua_code)
-    } else {
-        let errors: Vec<_> = errors
-            .into_iter()
-            .map(|error| format!("-> {}", error))
-            .collect();
-        Err(format!("unable to process code:\n{}", errors.join("\n")).into())
-    }
+    darklua_core::process_code(code, &config)
+        .map_err(|error| format!("unable to process code:\n-> {}", error).into())
 }
 
 #[wasm_bindgen]
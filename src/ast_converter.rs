@@ -604,8 +604,12 @@ impl<'a> AstConverter<'a> {
                     )?;
                     let name = self.convert_function_name(statement.name())?;
 
-                    self.statements
-                        .push(builder.into_function_statement(name).into());
+                    let mut function = builder.into_function_statement(name);
+                    for attribute in statement.attributes() {
+                        function.push_attribute(self.convert_attribute(attribute)?);
+                    }
+
+                    self.statements.push(function.into());
                 }
                 ConvertWork::MakeFunctionCallStatement { call } => {
                     let call = self.make_function_call(call)?;
@@ -846,11 +850,12 @@ impl<'a> AstConverter<'a> {
                         local_token = Some(self.convert_token(statement.local_token())?);
                     }
 
-                    self.statements.push(
-                        builder
-                            .into_local_function_statement(name, local_token)
-                            .into(),
-                    );
+                    let mut function = builder.into_local_function_statement(name, local_token);
+                    for attribute in statement.attributes() {
+                        function.push_attribute(self.convert_attribute(attribute)?);
+                    }
+
+                    self.statements.push(function.into());
                 }
                 ConvertWork::MakeTypeFunctionStatement { statement } => {
                     let builder = self.convert_function_body_attributes(
@@ -1621,6 +1626,25 @@ impl<'a> AstConverter<'a> {
             ast::Stmt::TypeDeclaration(type_declaration) => {
                 self.convert_type_declaration(type_declaration, None);
             }
+            ast::Stmt::Goto(goto) => {
+                let mut goto_statement =
+                    GotoStatement::new(self.convert_token_to_identifier(goto.label_name())?);
+                if self.hold_token_data {
+                    goto_statement.set_token(self.convert_token(goto.goto_token())?);
+                }
+                self.statements.push(goto_statement.into());
+            }
+            ast::Stmt::Label(label) => {
+                let mut label_statement =
+                    LabelStatement::new(self.convert_token_to_identifier(label.name())?);
+                if self.hold_token_data {
+                    label_statement.set_tokens(LabelTokens {
+                        left_colons: self.convert_token(label.left_colons())?,
+                        right_colons: self.convert_token(label.right_colons())?,
+                    });
+                }
+                self.statements.push(label_statement.into());
+            }
             _ => {
                 return Err(ConvertError::Statement {
                     statement: statement.to_string(),
@@ -2423,6 +2447,7 @@ impl<'a> AstConverter<'a> {
         let trivia = match token.token_kind() {
             TokenKind::MultiLineComment => TriviaKind::Comment,
             TokenKind::SingleLineComment => TriviaKind::Comment,
+            TokenKind::Shebang => TriviaKind::Comment,
             TokenKind::Whitespace => TriviaKind::Whitespace,
             _ => return Err(ConvertError::UnexpectedTrivia(token.token_kind())),
         }
@@ -2446,6 +2471,17 @@ impl<'a> AstConverter<'a> {
         Ok(identifier)
     }
 
+    fn convert_attribute(
+        &self,
+        attribute: &ast::luau::LuauAttribute,
+    ) -> Result<Attribute, ConvertError> {
+        let mut new_attribute = Attribute::new(self.convert_token_to_identifier(attribute.name())?);
+        if self.hold_token_data {
+            new_attribute.set_token(self.convert_token(attribute.at_sign())?);
+        }
+        Ok(new_attribute)
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
     fn convert_typed_identifier(
         &mut self,
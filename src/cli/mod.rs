@@ -37,7 +37,7 @@ pub enum Command {
     ///
     /// Configure the code transformation using a configuration file.
     /// If no configuration is passed, darklua will attempt to read
-    /// `.darklua.json` or `darklua.json5` from the working directory.
+    /// `.darklua.json`, `.darklua.json5` or `.darklua.toml` from the working directory.
     Process(process::Options),
     /// Convert a data file [json, json5, yaml, toml] into a Lua file
     Convert(convert::Options),
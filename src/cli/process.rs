@@ -26,6 +26,18 @@ pub struct Options {
     /// Watch files and directories for changes and automatically re-run
     #[arg(long, short)]
     watch: bool,
+    /// Process files in parallel with the given number of threads (0 uses all the
+    /// available cores).
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Stop processing at the first file that fails, instead of processing every file
+    /// and reporting all the errors.
+    #[arg(long)]
+    fail_fast: bool,
+    /// Report up to 20 syntax errors of the files that can not be parsed, instead of only
+    /// the first one.
+    #[arg(long)]
+    recover_syntax_errors: bool,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -76,9 +88,19 @@ impl Options {
             process_options = process_options.with_generator_override(match format {
                 LuaFormat::Dense => GeneratorParameters::default_dense(),
                 LuaFormat::Readable => GeneratorParameters::default_readable(),
-                LuaFormat::RetainLines => GeneratorParameters::RetainLines,
+                LuaFormat::RetainLines => GeneratorParameters::default_retain_lines(),
             })
         }
+        if let Some(threads) = self.threads {
+            process_options = process_options.parallel(threads);
+        }
+        if self.fail_fast {
+            process_options = process_options.fail_fast();
+        }
+        if self.recover_syntax_errors {
+            process_options = process_options.recover_syntax_errors();
+        }
+
         process_options
     }
 }
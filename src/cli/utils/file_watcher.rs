@@ -17,7 +17,7 @@ use crate::cli::{error::CliError, process::Options as ProcessOptions, CommandRes
 use super::report_process;
 
 const FILE_WATCHING_DEBOUNCE_DURATION_MILLIS: u64 = 400;
-const DEFAULT_CONFIG_PATHS: [&str; 2] = [".darklua.json", ".darklua.json5"];
+const DEFAULT_CONFIG_PATHS: [&str; 3] = [".darklua.json", ".darklua.json5", ".darklua.toml"];
 
 enum WatcherSignal {
     Exit,
@@ -328,6 +328,9 @@ impl FileWatcher {
         if let Some(worker_tree) = self.worker_tree.as_ref() {
             let files: HashSet<_> = worker_tree
                 .iter_external_dependencies()
+                // dependencies can be missing files (like the candidates probed when
+                // resolving a require), which are created inside the watched directory
+                .filter(|path| path.is_file())
                 .map(ToOwned::to_owned)
                 .collect();
 
@@ -0,0 +1,269 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::nodes::{Block, Statement};
+use crate::utils::normalize_path;
+
+/// The modules that went into a bundle. The manifest is only collected when enabled with
+/// [`Options::collect_bundle_manifests`](crate::Options::collect_bundle_manifests) or when
+/// the `manifest_path` of the bundle configuration is set. It is available in the
+/// [`FileReport`](crate::FileReport) of the bundled file.
+///
+/// Paths are relative to the project location (the location of the configuration file).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BundleManifest {
+    entry: PathBuf,
+    dependencies: Vec<PathBuf>,
+    modules: Vec<BundledModule>,
+}
+
+impl BundleManifest {
+    /// The file where the bundling started.
+    pub fn entry(&self) -> &Path {
+        &self.entry
+    }
+
+    /// Iterates over the modules directly required by the entry file.
+    pub fn iter_dependencies(&self) -> impl Iterator<Item = &Path> {
+        self.dependencies.iter().map(AsRef::as_ref)
+    }
+
+    /// Iterates over the modules, sorted by their path.
+    pub fn iter_modules(&self) -> impl Iterator<Item = &BundledModule> {
+        self.modules.iter()
+    }
+
+    pub fn get_module(&self, path: impl AsRef<Path>) -> Option<&BundledModule> {
+        let path = path.as_ref();
+        self.modules.iter().find(|module| module.path == path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    /// Measures the size of each inlined module from its definition in the processed
+    /// block. Modules that cannot be found (for example when a rule renamed the modules
+    /// identifier) have no size after rules.
+    pub(crate) fn measure_modules(
+        &mut self,
+        block: &Block,
+        modules_identifier: &str,
+        generate: impl Fn(&Block) -> String,
+    ) {
+        let mut definitions = BTreeMap::new();
+
+        for statement in block.iter_statements() {
+            if let Statement::Do(do_statement) = statement {
+                for statement in do_statement.get_block().iter_statements() {
+                    if let Statement::Function(function) = statement {
+                        let name = function.get_name();
+                        let mut fields = name.get_field_names().iter();
+
+                        if let (Some(field), None) = (fields.next(), fields.next()) {
+                            if !name.has_method()
+                                && name.get_name().get_name() == modules_identifier
+                            {
+                                definitions.insert(field.get_name().as_str(), function.get_block());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for module in self.modules.iter_mut() {
+            module.size_after_rules = match &module.status {
+                BundledModuleStatus::Inlined { id } => definitions
+                    .get(id.as_str())
+                    .map(|module_block| generate(module_block).len()),
+                BundledModuleStatus::External => None,
+            };
+        }
+    }
+}
+
+/// A module required while bundling a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BundledModule {
+    path: PathBuf,
+    #[serde(flatten)]
+    status: BundledModuleStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size_before_rules: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size_after_rules: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    dependencies: Vec<PathBuf>,
+}
+
+impl BundledModule {
+    /// The path of the module file, or the string passed to the require call for
+    /// external modules.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn status(&self) -> &BundledModuleStatus {
+        &self.status
+    }
+
+    /// The id used to load the module in the bundle, when it is inlined.
+    pub fn id(&self) -> Option<&str> {
+        match &self.status {
+            BundledModuleStatus::Inlined { id } => Some(id),
+            BundledModuleStatus::External => None,
+        }
+    }
+
+    /// The size in bytes of the module file.
+    pub fn size_before_rules(&self) -> Option<usize> {
+        self.size_before_rules
+    }
+
+    /// The size in bytes of the generated code of the module, after the rules were
+    /// applied to the bundle.
+    pub fn size_after_rules(&self) -> Option<usize> {
+        self.size_after_rules
+    }
+
+    /// Iterates over the modules directly required by this module.
+    pub fn iter_dependencies(&self) -> impl Iterator<Item = &Path> {
+        self.dependencies.iter().map(AsRef::as_ref)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BundledModuleStatus {
+    /// The module is inlined in the bundle and loaded with the given id.
+    Inlined { id: String },
+    /// The module matches one of the `excludes` patterns, so its require call is kept.
+    External,
+}
+
+/// Collects the modules found by the bundler to build a [`BundleManifest`].
+#[derive(Debug)]
+pub(crate) struct BundleManifestBuilder {
+    project_location: PathBuf,
+    entry: PathBuf,
+    modules: BTreeMap<PathBuf, (BundledModuleStatus, Option<usize>)>,
+    dependencies: BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+    // the path of each module, from the first path used to require it
+    module_paths: HashMap<PathBuf, PathBuf>,
+    module_sizes: HashMap<PathBuf, usize>,
+}
+
+impl BundleManifestBuilder {
+    pub(crate) fn new(project_location: impl AsRef<Path>, entry: impl AsRef<Path>) -> Self {
+        let project_location = match normalize_path(project_location) {
+            location if location == Path::new(".") => PathBuf::new(),
+            location => location,
+        };
+        let mut builder = Self {
+            project_location,
+            entry: PathBuf::new(),
+            modules: Default::default(),
+            dependencies: Default::default(),
+            module_paths: Default::default(),
+            module_sizes: Default::default(),
+        };
+        builder.entry = builder.relative_path(entry);
+        builder
+    }
+
+    fn relative_path(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = normalize_path(path);
+        path.strip_prefix(&self.project_location)
+            .map(Path::to_path_buf)
+            .unwrap_or(path)
+    }
+
+    fn module_path(&mut self, module_key: &Path, path: &Path) -> PathBuf {
+        let relative_path = self.relative_path(path);
+        self.module_paths
+            .entry(module_key.to_path_buf())
+            .or_insert(relative_path)
+            .clone()
+    }
+
+    /// Adds a module identified by its canonical path, so that a module required with
+    /// different paths is listed once.
+    pub(crate) fn add_inlined_module(
+        &mut self,
+        module_key: &Path,
+        path: &Path,
+        id: impl Into<String>,
+    ) {
+        let path = self.module_path(module_key, path);
+        let size = self.module_sizes.get(&path).copied();
+        let status = BundledModuleStatus::Inlined { id: id.into() };
+        self.modules.insert(path, (status, size));
+    }
+
+    /// Records the size of the file read for a module.
+    pub(crate) fn add_module_size(&mut self, path: &Path, size: usize) {
+        let path = self.relative_path(path);
+        self.module_sizes.insert(path, size);
+    }
+
+    /// Adds a module that is not bundled, with the string passed to its require call.
+    pub(crate) fn add_external_module(&mut self, source: impl AsRef<Path>, require: &str) {
+        let source = self.relative_path(source);
+        let path = PathBuf::from(require);
+        self.dependencies
+            .entry(source)
+            .or_default()
+            .insert(path.clone());
+        self.modules
+            .entry(path)
+            .or_insert((BundledModuleStatus::External, None));
+    }
+
+    pub(crate) fn add_dependency(&mut self, source: &Path, module_key: &Path, module_path: &Path) {
+        let source = self.relative_path(source);
+        let module = self.module_path(module_key, module_path);
+        self.dependencies.entry(source).or_default().insert(module);
+    }
+
+    pub(crate) fn build(self) -> BundleManifest {
+        let Self {
+            entry,
+            modules,
+            mut dependencies,
+            ..
+        } = self;
+
+        let mut take_dependencies = |path: &Path| -> Vec<PathBuf> {
+            dependencies
+                .remove(path)
+                .map(|dependencies| dependencies.into_iter().collect())
+                .unwrap_or_default()
+        };
+
+        let entry_dependencies = take_dependencies(&entry);
+
+        let modules = modules
+            .into_iter()
+            .map(|(path, (status, size_before_rules))| BundledModule {
+                dependencies: take_dependencies(&path),
+                path,
+                status,
+                size_before_rules,
+                size_after_rules: None,
+            })
+            .collect();
+
+        BundleManifest {
+            entry,
+            dependencies: entry_dependencies,
+            modules,
+        }
+    }
+}
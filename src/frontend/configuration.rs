@@ -1,36 +1,89 @@
 use std::{
     collections::HashSet,
+    ffi::OsStr,
+    iter,
     path::{Path, PathBuf},
     str::FromStr,
 };
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
 
 use crate::{
     generator::{DenseLuaGenerator, LuaGenerator, ReadableLuaGenerator, TokenBasedLuaGenerator},
     nodes::Block,
     rules::{
         bundle::{BundleRequireMode, Bundler},
-        get_default_rules, Rule,
+        get_default_rules, RemoveAttributes, RemoveCompoundAssignment, RemoveContinue,
+        RemoveFloorDivision, RemoveIfExpression, RemoveInterpolatedString, RemoveTypes, Rule,
+    },
+    utils::{
+        deserialize_at_path, deserialize_indexed_list, normalize_path, split_error_path,
+        string_or_struct, with_error_path,
     },
     Parser,
 };
 
+use super::{
+    configuration_layer::{layer_rules, ConfigurationLayer},
+    file_filter::FileFilter,
+    DarkluaResult,
+};
+
 const DEFAULT_COLUMN_SPAN: usize = 80;
 
 fn get_default_column_span() -> usize {
     DEFAULT_COLUMN_SPAN
 }
 
+fn deserialize_generator<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<GeneratorParameters, D::Error> {
+    string_or_struct(deserializer)
+        .map_err(|err| de::Error::custom(with_error_path(err, "generator")))
+}
+
+fn deserialize_bundle<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<BundleConfiguration>, D::Error> {
+    deserialize_at_path(deserializer, "bundle")
+}
+
+fn deserialize_require_mode<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BundleRequireMode, D::Error> {
+    string_or_struct(deserializer)
+        .map_err(|err| de::Error::custom(with_error_path(err, "require_mode")))
+}
+
+fn deserialize_overrides<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<ConfigurationOverride>, D::Error> {
+    deserialize_indexed_list(deserializer, "overrides")
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Configuration {
-    #[serde(alias = "process", default = "get_default_rules")]
+    #[serde(
+        alias = "process",
+        default = "get_default_rules",
+        deserialize_with = "crate::rules::deserialize_rules"
+    )]
     rules: Vec<Box<dyn Rule>>,
-    #[serde(default, deserialize_with = "crate::utils::string_or_struct")]
+    #[serde(default, deserialize_with = "deserialize_generator")]
     generator: GeneratorParameters,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_bundle"
+    )]
     bundle: Option<BundleConfiguration>,
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_overrides"
+    )]
+    overrides: Vec<ConfigurationOverride>,
     #[serde(default, skip)]
     location: Option<PathBuf>,
 }
@@ -43,6 +96,7 @@ impl Configuration {
             rules: Vec::new(),
             generator: GeneratorParameters::default(),
             bundle: None,
+            overrides: Vec::new(),
             location: None,
         }
     }
@@ -70,6 +124,58 @@ impl Configuration {
         self
     }
 
+    /// Removes every rule with the given name.
+    pub fn with_rule_disabled(self, rule_name: impl Into<String>) -> Self {
+        self.with_layer(ConfigurationLayer::new().with_rule_disabled(rule_name))
+    }
+
+    /// Replaces the rule with the same name (and its properties) with the given rule. When
+    /// there is no rule with that name, the rule is appended.
+    pub fn with_rule_overridden(self, rule: impl Into<Box<dyn Rule>>) -> Self {
+        self.with_layer(ConfigurationLayer::new().with_rule_overridden(rule))
+    }
+
+    /// Applies the changes of a layer to the configuration.
+    pub fn with_layer(mut self, layer: ConfigurationLayer) -> Self {
+        let (rule_changes, generator, bundle) = layer.into_parts();
+
+        self.rules = layer_rules(std::mem::take(&mut self.rules), rule_changes);
+        if let Some(generator) = generator {
+            self.generator = generator;
+        }
+        if let Some(bundle) = bundle {
+            self.bundle = Some(bundle);
+        }
+        self
+    }
+
+    /// Adds an override that changes the rules applied to the files matching its pattern.
+    /// When multiple overrides match a file, the last one is used.
+    #[inline]
+    pub fn with_override(mut self, configuration_override: ConfigurationOverride) -> Self {
+        self.overrides.push(configuration_override);
+        self
+    }
+
+    /// Places the rules of the given target before the rules of the configuration. Rules
+    /// of the target with the same name as a rule of the configuration are not added, so
+    /// that the rule of the configuration is used instead.
+    pub fn with_target(mut self, target: LuaTarget) -> Self {
+        let mut rules: Vec<_> = target
+            .rules()
+            .into_iter()
+            .filter(|target_rule| {
+                !self
+                    .rules
+                    .iter()
+                    .any(|rule| rule.get_name() == target_rule.get_name())
+            })
+            .collect();
+        rules.append(&mut self.rules);
+        self.rules = rules;
+        self
+    }
+
     #[inline]
     pub fn with_location(mut self, location: impl Into<PathBuf>) -> Self {
         self.location = Some(location.into());
@@ -86,6 +192,42 @@ impl Configuration {
         self.rules.iter().map(AsRef::as_ref)
     }
 
+    /// Returns the rules to apply to the file at the given path, using the last override
+    /// matching the path relative to the configuration location.
+    pub(crate) fn rules_for<'a>(&'a self, path: &Path) -> Vec<&'a dyn Rule> {
+        if self.overrides.is_empty() {
+            return self.rules().collect();
+        }
+
+        let path = normalize_path(path);
+        let relative_path = self
+            .location
+            .as_ref()
+            .map(normalize_path)
+            .and_then(|location| path.strip_prefix(location).ok().map(Path::to_path_buf))
+            .unwrap_or(path);
+
+        for configuration_override in self.overrides.iter().rev() {
+            if configuration_override.filter.accepts(&relative_path) {
+                log::trace!(
+                    "use configuration override `{}` for `{}`",
+                    configuration_override.include,
+                    relative_path.display()
+                );
+
+                let override_rules = configuration_override.rules.iter().map(AsRef::as_ref);
+
+                return if configuration_override.merge {
+                    self.rules().chain(override_rules).collect()
+                } else {
+                    override_rules.collect()
+                };
+            }
+        }
+
+        self.rules().collect()
+    }
+
     #[inline]
     pub(crate) fn build_parser(&self) -> Parser {
         self.generator.build_parser()
@@ -103,7 +245,8 @@ impl Configuration {
                 bundle_config.require_mode().clone(),
                 bundle_config.excludes(),
             )
-            .with_modules_identifier(bundle_config.modules_identifier());
+            .with_modules_identifier(bundle_config.modules_identifier())
+            .with_type_exports_removed(bundle_config.remove_type_exports());
             Some(bundler)
         } else {
             None
@@ -111,8 +254,12 @@ impl Configuration {
     }
 
     #[inline]
-    pub(crate) fn rules_len(&self) -> usize {
-        self.rules.len()
+    pub(crate) fn has_bundle(&self) -> bool {
+        self.bundle.is_some()
+    }
+
+    pub(crate) fn bundle_configuration(&self) -> Option<&BundleConfiguration> {
+        self.bundle.as_ref()
     }
 
     #[inline]
@@ -121,12 +268,338 @@ impl Configuration {
     }
 }
 
+/// Deserializes a configuration from the content of a TOML file. When the configuration
+/// is invalid, the error message contains the path of the key where the error happened,
+/// with its line and column.
+#[cfg(test)]
+pub(crate) fn configuration_from_toml(content: &str) -> Result<Configuration, String> {
+    toml::from_str(content).map_err(|err| locate_toml_error(&err, content))
+}
+
+/// Deserializes a configuration from the value parsed from the content of a configuration
+/// file. When the configuration is invalid, the error message contains the path of the key
+/// where the error happened, with its line and column in the content.
+pub(crate) fn configuration_from_value(
+    path: &Path,
+    content: &str,
+    value: serde_json::Value,
+) -> Result<Configuration, String> {
+    serde_json::from_value(value).map_err(|err| {
+        let message = err.to_string();
+        if is_toml_file(path) {
+            locate_error_path(&message, |path| find_toml_path_location(content, path))
+        } else {
+            locate_json5_error(&message, content)
+        }
+    })
+}
+
+pub(crate) fn is_toml_file(path: &Path) -> bool {
+    path.extension() == Some(OsStr::new("toml"))
+}
+
+/// Adds the line and the column of the key that caused a TOML error to the error message,
+/// using the path found in the message or the span of the error.
+pub(crate) fn locate_toml_error(err: &toml::de::Error, content: &str) -> String {
+    if let (message, Some(path)) = split_error_path(err.message()) {
+        return match find_path_location(path, |path| find_toml_path_location(content, path)) {
+            Some((line, column)) => format_located_error(message, path, line, column),
+            None => err.message().to_owned(),
+        };
+    }
+
+    match err.span().and_then(|span| {
+        find_toml_key_path(content, span.start)
+            .map(|(path, line)| (path, line, get_column(content, span.start)))
+    }) {
+        Some((path, line, column)) => format_located_error(err.message(), &path, line, column),
+        None => err.to_string(),
+    }
+}
+
+/// Adds the line and the column of the value that caused a JSON5 configuration error to
+/// the error message, using the path found in the message.
+pub(crate) fn locate_json5_error(message: &str, content: &str) -> String {
+    locate_error_path(message, |path| find_json5_path_location(content, path))
+}
+
+fn locate_error_path(message: &str, find: impl Fn(&str) -> Option<(usize, usize)>) -> String {
+    if let (message_without_path, Some(path)) = split_error_path(message) {
+        if let Some((line, column)) = find_path_location(path, find) {
+            return format_located_error(message_without_path, path, line, column);
+        }
+    }
+    message.to_owned()
+}
+
+fn format_located_error(message: &str, path: &str, line: usize, column: usize) -> String {
+    format!(
+        "{} (at `{}`, line {}, column {})",
+        message, path, line, column
+    )
+}
+
+fn get_column(content: &str, offset: usize) -> usize {
+    content[..offset]
+        .rsplit('\n')
+        .next()
+        .map(|line| line.chars().count())
+        .unwrap_or(0)
+        + 1
+}
+
+/// Finds the location of a path, or of its closest parent when the path itself cannot be
+/// found (like an element of an inline array in a TOML document).
+fn find_path_location(
+    path: &str,
+    find: impl Fn(&str) -> Option<(usize, usize)>,
+) -> Option<(usize, usize)> {
+    let mut path = path;
+
+    loop {
+        if let Some(location) = find(path) {
+            return Some(location);
+        }
+
+        path = &path[..path.rfind(['.', '[']).filter(|index| *index > 0)?];
+    }
+}
+
+/// Finds the line and the column of the first key of a TOML document that has the
+/// given path.
+fn find_toml_path_location(content: &str, path: &str) -> Option<(usize, usize)> {
+    let mut line_start = 0;
+
+    for line in content.split_inclusive('\n') {
+        if let Some((line_path, line_number)) = find_toml_key_path(content, line_start) {
+            if line_path == path && !line.trim().is_empty() {
+                return Some((line_number, line.len() - line.trim_start().len() + 1));
+            }
+        }
+        line_start += line.len();
+    }
+
+    None
+}
+
+enum Json5Frame {
+    Object {
+        key: Option<String>,
+        expects_key: bool,
+    },
+    Array {
+        index: usize,
+        expects_value: bool,
+    },
+}
+
+fn format_json5_path(frames: &[Json5Frame]) -> String {
+    let mut path = String::new();
+
+    for frame in frames {
+        match frame {
+            Json5Frame::Object { key: Some(key), .. } => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+            }
+            Json5Frame::Object { key: None, .. } => {}
+            Json5Frame::Array { index, .. } => path.push_str(&format!("[{}]", index)),
+        }
+    }
+
+    path
+}
+
+/// Marks the start of a value and returns `true` if the value is the element of an array
+/// at the given path.
+fn start_json5_value(frames: &mut [Json5Frame], path: &str) -> bool {
+    if let Some(Json5Frame::Array { expects_value, .. }) = frames.last_mut() {
+        if *expects_value {
+            *expects_value = false;
+            return format_json5_path(frames) == path;
+        }
+    }
+    false
+}
+
+/// Finds the line and the column of the key (or of the array element) of a JSON5 document
+/// that has the given path, like `rules[2].current`.
+fn find_json5_path_location(content: &str, path: &str) -> Option<(usize, usize)> {
+    let mut frames = Vec::new();
+    let mut chars = content.char_indices().peekable();
+    let mut line = 1;
+    let mut line_start = 0;
+
+    while let Some((index, character)) = chars.next() {
+        let column = content[line_start..index].chars().count() + 1;
+
+        match character {
+            '\n' => {
+                line += 1;
+                line_start = index + 1;
+            }
+            '/' if matches!(chars.peek(), Some((_, '/'))) => {
+                while chars.next_if(|(_, next)| *next != '\n').is_some() {}
+            }
+            '/' if matches!(chars.peek(), Some((_, '*'))) => {
+                chars.next();
+                let mut previous = ' ';
+                for (index, next) in chars.by_ref() {
+                    if next == '\n' {
+                        line += 1;
+                        line_start = index + 1;
+                    } else if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            '{' | '[' => {
+                if start_json5_value(&mut frames, path) {
+                    return Some((line, column));
+                }
+                frames.push(if character == '{' {
+                    Json5Frame::Object {
+                        key: None,
+                        expects_key: true,
+                    }
+                } else {
+                    Json5Frame::Array {
+                        index: 0,
+                        expects_value: true,
+                    }
+                });
+            }
+            '}' | ']' => {
+                frames.pop();
+            }
+            ':' => {
+                if let Some(Json5Frame::Object { expects_key, .. }) = frames.last_mut() {
+                    *expects_key = false;
+                }
+            }
+            ',' => match frames.last_mut() {
+                Some(Json5Frame::Object { key, expects_key }) => {
+                    *key = None;
+                    *expects_key = true;
+                }
+                Some(Json5Frame::Array {
+                    index,
+                    expects_value,
+                }) => {
+                    *index += 1;
+                    *expects_value = true;
+                }
+                None => {}
+            },
+            _ if character.is_whitespace() => {}
+            _ => {
+                let word = if character == '"' || character == '\'' {
+                    let mut word = String::new();
+                    while let Some((_, next)) = chars.next() {
+                        match next {
+                            '\\' => {
+                                if let Some((_, escaped)) = chars.next() {
+                                    word.push(escaped);
+                                }
+                            }
+                            _ if next == character => break,
+                            _ => word.push(next),
+                        }
+                    }
+                    word
+                } else {
+                    let mut end = index + character.len_utf8();
+                    while let Some((next_index, next)) = chars
+                        .next_if(|(_, next)| !next.is_whitespace() && !",:[]{}/".contains(*next))
+                    {
+                        end = next_index + next.len_utf8();
+                    }
+                    content[index..end].to_owned()
+                };
+
+                if let Some(Json5Frame::Object {
+                    key,
+                    expects_key: true,
+                }) = frames.last_mut()
+                {
+                    *key = Some(word);
+                    if format_json5_path(&frames) == path {
+                        return Some((line, column));
+                    }
+                } else if start_json5_value(&mut frames, path) {
+                    return Some((line, column));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Finds the path of the key defined at the given offset of a TOML document, with the
+/// number of the line where it is defined. Array of tables are indexed, so the second
+/// `[[rules]]` table is `rules[1]`.
+fn find_toml_key_path(content: &str, offset: usize) -> Option<(String, usize)> {
+    let mut table = String::new();
+    let mut array_tables_count = std::collections::HashMap::<String, usize>::new();
+    let mut current_key: Option<String> = None;
+    let mut line_start = 0;
+
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim();
+
+        if let Some(name) = trimmed
+            .strip_prefix("[[")
+            .and_then(|rest| rest.split("]]").next())
+        {
+            let name = name.trim().to_owned();
+            let count = array_tables_count.entry(name.clone()).or_default();
+            table = format!("{}[{}]", name, count);
+            *count += 1;
+            current_key = None;
+        } else if let Some(name) = trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.split(']').next())
+        {
+            table = name.trim().to_owned();
+            current_key = None;
+        } else if let Some((key, _value)) = trimmed.split_once('=') {
+            let key = key.trim();
+            if !key.is_empty()
+                && key.chars().all(|character| {
+                    character.is_ascii_alphanumeric() || "_-.\"' ".contains(character)
+                })
+            {
+                current_key = Some(key.replace(['"', '\''], ""));
+            }
+        }
+
+        line_start += line.len();
+
+        if offset < line_start {
+            let path = match current_key {
+                Some(key) if table.is_empty() => key,
+                Some(key) => format!("{}.{}", table, key),
+                None if table.is_empty() => return None,
+                None => table,
+            };
+            return Some((path, index + 1));
+        }
+    }
+
+    None
+}
+
 impl Default for Configuration {
     fn default() -> Self {
         Self {
             rules: get_default_rules(),
             generator: Default::default(),
             bundle: None,
+            overrides: Vec::new(),
             location: None,
         }
     }
@@ -153,11 +626,68 @@ impl std::fmt::Debug for Configuration {
     }
 }
 
+/// A Lua runtime that the generated code should run on. In configuration files, the
+/// `target` field expands to the rules needed to convert Luau code for that runtime.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LuaTarget {
+    #[serde(rename = "lua51")]
+    Lua51,
+    #[serde(rename = "lua53")]
+    Lua53,
+    #[serde(rename = "luau")]
+    Luau,
+}
+
+impl LuaTarget {
+    /// Returns the rules that convert Luau code for this target, in the order they are
+    /// applied. In configuration files, these rules are placed before the rules of the
+    /// configuration.
+    pub fn rules(&self) -> Vec<Box<dyn Rule>> {
+        match self {
+            Self::Lua51 => vec![
+                Box::<RemoveTypes>::default(),
+                Box::<RemoveAttributes>::default(),
+                Box::<RemoveInterpolatedString>::default(),
+                Box::<RemoveIfExpression>::default(),
+                Box::<RemoveCompoundAssignment>::default(),
+                Box::<RemoveFloorDivision>::default(),
+                Box::<RemoveContinue>::default(),
+            ],
+            // Lua 5.3 supports the floor division operator
+            Self::Lua53 => vec![
+                Box::<RemoveTypes>::default(),
+                Box::<RemoveAttributes>::default(),
+                Box::<RemoveInterpolatedString>::default(),
+                Box::<RemoveIfExpression>::default(),
+                Box::<RemoveCompoundAssignment>::default(),
+                Box::<RemoveContinue>::default(),
+            ],
+            Self::Luau => Vec::new(),
+        }
+    }
+}
+
+impl FromStr for LuaTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "lua51" => Self::Lua51,
+            "lua53" => Self::Lua53,
+            "luau" => Self::Luau,
+            _ => return Err(format!("invalid target `{}`", s)),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields, rename_all = "snake_case", tag = "name")]
 pub enum GeneratorParameters {
     #[serde(alias = "retain-lines")]
-    RetainLines,
+    RetainLines {
+        #[serde(default, skip_serializing_if = "is_false")]
+        escape_unicode: bool,
+    },
     Dense {
         #[serde(default = "get_default_column_span")]
         column_span: usize,
@@ -165,16 +695,24 @@ pub enum GeneratorParameters {
     Readable {
         #[serde(default = "get_default_column_span")]
         column_span: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_line_length: Option<usize>,
     },
 }
 
 impl Default for GeneratorParameters {
     fn default() -> Self {
-        Self::RetainLines
+        Self::default_retain_lines()
     }
 }
 
 impl GeneratorParameters {
+    pub fn default_retain_lines() -> Self {
+        Self::RetainLines {
+            escape_unicode: false,
+        }
+    }
+
     pub fn default_dense() -> Self {
         Self::Dense {
             column_span: DEFAULT_COLUMN_SPAN,
@@ -184,13 +722,26 @@ impl GeneratorParameters {
     pub fn default_readable() -> Self {
         Self::Readable {
             column_span: DEFAULT_COLUMN_SPAN,
+            max_line_length: None,
+        }
+    }
+
+    pub fn dense(column_span: usize) -> Self {
+        Self::Dense { column_span }
+    }
+
+    pub fn readable(column_span: usize) -> Self {
+        Self::Readable {
+            column_span,
+            max_line_length: None,
         }
     }
 
     fn generate_lua(&self, block: &Block, code: &str) -> String {
         match self {
-            Self::RetainLines => {
-                let mut generator = TokenBasedLuaGenerator::new(code);
+            Self::RetainLines { escape_unicode } => {
+                let mut generator =
+                    TokenBasedLuaGenerator::new(code).with_escaped_unicode(*escape_unicode);
                 generator.write_block(block);
                 generator.into_string()
             }
@@ -199,8 +750,14 @@ impl GeneratorParameters {
                 generator.write_block(block);
                 generator.into_string()
             }
-            Self::Readable { column_span } => {
+            Self::Readable {
+                column_span,
+                max_line_length,
+            } => {
                 let mut generator = ReadableLuaGenerator::new(*column_span);
+                if let Some(max_line_length) = max_line_length {
+                    generator = generator.with_max_line_length(*max_line_length);
+                }
                 generator.write_block(block);
                 generator.into_string()
             }
@@ -209,7 +766,7 @@ impl GeneratorParameters {
 
     fn build_parser(&self) -> Parser {
         match self {
-            Self::RetainLines => Parser::default().preserve_tokens(),
+            Self::RetainLines { .. } => Parser::default().preserve_tokens(),
             Self::Dense { .. } | Self::Readable { .. } => Parser::default(),
         }
     }
@@ -221,13 +778,11 @@ impl FromStr for GeneratorParameters {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
             // keep "retain-lines" for back-compatibility
-            "retain_lines" | "retain-lines" => Self::RetainLines,
+            "retain_lines" | "retain-lines" => Self::default_retain_lines(),
             "dense" => Self::Dense {
                 column_span: DEFAULT_COLUMN_SPAN,
             },
-            "readable" => Self::Readable {
-                column_span: DEFAULT_COLUMN_SPAN,
-            },
+            "readable" => Self::default_readable(),
             _ => return Err(format!("invalid generator name `{}`", s)),
         })
     }
@@ -236,12 +791,20 @@ impl FromStr for GeneratorParameters {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields, rename_all = "snake_case")]
 pub struct BundleConfiguration {
-    #[serde(deserialize_with = "crate::utils::string_or_struct")]
+    #[serde(deserialize_with = "deserialize_require_mode")]
     require_mode: BundleRequireMode,
     #[serde(skip_serializing_if = "Option::is_none")]
     modules_identifier: Option<String>,
     #[serde(default, skip_serializing_if = "HashSet::is_empty")]
     excludes: HashSet<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    remove_type_exports: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    manifest_path: Option<PathBuf>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
 }
 
 impl BundleConfiguration {
@@ -250,6 +813,8 @@ impl BundleConfiguration {
             require_mode: require_mode.into(),
             modules_identifier: None,
             excludes: Default::default(),
+            remove_type_exports: false,
+            manifest_path: None,
         }
     }
 
@@ -263,6 +828,20 @@ impl BundleConfiguration {
         self
     }
 
+    /// Removes the `export` keyword from the type declarations of the bundled modules,
+    /// because exported types are only valid at the top level of a file.
+    pub fn with_type_exports_removed(mut self) -> Self {
+        self.remove_type_exports = true;
+        self
+    }
+
+    /// Writes the [`BundleManifest`](crate::BundleManifest) as a JSON file at the given
+    /// path, relative to the directory of the bundle output.
+    pub fn with_manifest_path(mut self, manifest_path: impl Into<PathBuf>) -> Self {
+        self.manifest_path = Some(manifest_path.into());
+        self
+    }
+
     pub(crate) fn require_mode(&self) -> &BundleRequireMode {
         &self.require_mode
     }
@@ -274,15 +853,356 @@ impl BundleConfiguration {
             .unwrap_or("__DARKLUA_BUNDLE_MODULES")
     }
 
+    pub(crate) fn remove_type_exports(&self) -> bool {
+        self.remove_type_exports
+    }
+
     pub(crate) fn excludes(&self) -> impl Iterator<Item = &str> {
         self.excludes.iter().map(AsRef::as_ref)
     }
+
+    pub(crate) fn manifest_path(&self) -> Option<&Path> {
+        self.manifest_path.as_deref()
+    }
+}
+
+/// Changes the rules applied to the files matching a gitignore-style pattern. The pattern
+/// is matched against the path of each file relative to the configuration location.
+#[derive(Serialize)]
+pub struct ConfigurationOverride {
+    include: String,
+    rules: Vec<Box<dyn Rule>>,
+    #[serde(skip_serializing_if = "is_false")]
+    merge: bool,
+    #[serde(skip)]
+    filter: FileFilter,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigurationOverrideDefinition {
+    include: String,
+    #[serde(default, deserialize_with = "crate::rules::deserialize_rules")]
+    rules: Vec<Box<dyn Rule>>,
+    #[serde(default)]
+    merge: bool,
+}
+
+impl<'de> Deserialize<'de> for ConfigurationOverride {
+    /// The pattern is compiled when the override is deserialized, so that an invalid
+    /// pattern is reported with the configuration errors.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let definition = ConfigurationOverrideDefinition::deserialize(deserializer)?;
+
+        let mut configuration_override =
+            Self::new(definition.include).map_err(de::Error::custom)?;
+        configuration_override.rules = definition.rules;
+        configuration_override.merge = definition.merge;
+
+        Ok(configuration_override)
+    }
+}
+
+impl ConfigurationOverride {
+    /// Creates an override for the files matching the given pattern. An error is returned
+    /// if the pattern is invalid.
+    pub fn new(include: impl Into<String>) -> DarkluaResult<Self> {
+        let include = include.into();
+        let filter = FileFilter::new(iter::once(include.as_str()), iter::empty())?;
+
+        Ok(Self {
+            include,
+            rules: Vec::new(),
+            merge: false,
+            filter,
+        })
+    }
+
+    pub fn with_rule(mut self, rule: impl Into<Box<dyn Rule>>) -> Self {
+        self.rules.push(rule.into());
+        self
+    }
+
+    /// Applies the rules of the override after the rules of the configuration, instead of
+    /// replacing them.
+    pub fn with_merged_rules(mut self) -> Self {
+        self.merge = true;
+        self
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    mod toml_configuration {
+        use super::*;
+
+        fn assert_same_configuration(json5_content: &str, toml_content: &str) {
+            let json5_config: Configuration = json5::from_str(json5_content).unwrap();
+            let toml_config = configuration_from_toml(toml_content).unwrap();
+
+            pretty_assertions::assert_eq!(
+                serde_json::to_value(&toml_config).unwrap(),
+                serde_json::to_value(&json5_config).unwrap()
+            );
+        }
+
+        #[test]
+        fn empty_configuration_uses_default_values() {
+            assert_same_configuration("{}", "");
+        }
+
+        #[test]
+        fn rules_and_generator() {
+            assert_same_configuration(
+                r#"{
+                    rules: [
+                        'remove_comments',
+                        { rule: 'inject_global_value', identifier: 'DEV', value: false },
+                        { rule: 'inject_global_value', identifier: 'COUNT', value: 8 },
+                        { rule: 'inject_global_value', identifier: 'RATIO', value: 0.5 },
+                        { rule: 'rename_variables', globals: ['$default', 'game'] },
+                    ],
+                    generator: { name: 'dense', column_span: 110 },
+                }"#,
+                r#"
+                    generator = { name = "dense", column_span = 110 }
+
+                    rules = [
+                        "remove_comments",
+                        { rule = "inject_global_value", identifier = "DEV", value = false },
+                        { rule = "inject_global_value", identifier = "COUNT", value = 8 },
+                        { rule = "inject_global_value", identifier = "RATIO", value = 0.5 },
+                        { rule = "rename_variables", globals = ["$default", "game"] },
+                    ]
+                "#,
+            );
+        }
+
+        #[test]
+        fn rules_as_array_of_tables() {
+            assert_same_configuration(
+                r#"{
+                    rules: [
+                        { rule: 'inject_global_value', identifier: 'COUNT', value: 8 },
+                        { rule: 'inject_global_value', identifier: 'RATIO', value: 0.5 },
+                        { rule: 'rename_variables', globals: ['$default', 'game'] },
+                    ],
+                    generator: 'readable',
+                }"#,
+                r#"
+                    generator = "readable"
+
+                    [[rules]]
+                    name = "inject_global_value"
+                    identifier = "COUNT"
+                    value = 8
+
+                    [[rules]]
+                    name = "inject_global_value"
+                    identifier = "RATIO"
+                    value = 0.5
+
+                    [[rules]]
+                    name = "rename_variables"
+                    globals = ["$default", "game"]
+                "#,
+            );
+        }
+
+        #[test]
+        fn bundle_with_nested_require_mode() {
+            assert_same_configuration(
+                r#"{
+                    rules: [],
+                    bundle: {
+                        require_mode: {
+                            name: 'path',
+                            module_folder_name: '__INIT__',
+                            sources: { pkg: './Packages' },
+                        },
+                        modules_identifier: '__M',
+                        excludes: ['@lune/**'],
+                    },
+                }"#,
+                r#"
+                    rules = []
+
+                    [bundle]
+                    modules_identifier = "__M"
+                    excludes = ["@lune/**"]
+
+                    [bundle.require_mode]
+                    name = "path"
+                    module_folder_name = "__INIT__"
+
+                    [bundle.require_mode.sources]
+                    pkg = "./Packages"
+                "#,
+            );
+        }
+
+        #[test]
+        fn error_contains_key_path() {
+            let error = configuration_from_toml("rules = []\n\n[bundle]\nrequire_mode = 'oops'")
+                .expect_err("deserialization should fail");
+
+            pretty_assertions::assert_eq!(
+                error,
+                "invalid require mode `oops` (at `bundle.require_mode`, line 4, column 1)"
+            );
+        }
+
+        #[test]
+        fn find_key_path_in_array_of_tables() {
+            let content = "[[rules]]\nname = 'a'\n\n[[rules]]\nname = 'b'\nvalue = 1\n";
+            let offset = content.find("value").unwrap();
+
+            pretty_assertions::assert_eq!(
+                find_toml_key_path(content, offset),
+                Some(("rules[1].value".to_owned(), 6))
+            );
+        }
+
+        #[test]
+        fn find_key_path_of_top_level_key() {
+            let content = "generator = 'oops'\n";
+
+            pretty_assertions::assert_eq!(
+                find_toml_key_path(content, 12),
+                Some(("generator".to_owned(), 1))
+            );
+        }
+
+        #[test]
+        fn find_key_path_inside_multiline_array() {
+            let content = "rules = [\n  { rule = 'a' },\n]\n";
+            let offset = content.find("{ rule").unwrap();
+
+            pretty_assertions::assert_eq!(
+                find_toml_key_path(content, offset),
+                Some(("rules".to_owned(), 2))
+            );
+        }
+    }
+
+    mod error_path {
+        use super::*;
+
+        fn deserialize_error(content: &str) -> String {
+            let error = json5::from_str::<Configuration>(content)
+                .err()
+                .expect("deserialization should fail");
+            locate_json5_error(&error.to_string(), content)
+        }
+
+        #[test]
+        fn unexpected_property_in_third_rule() {
+            pretty_assertions::assert_eq!(
+                deserialize_error(
+                    "{\n  rules: [\n    'remove_comments',\n    'remove_spaces',\n    { rule: 'convert_require', current: 'path', target: 'path', typo: true },\n  ],\n}"
+                ),
+                "unexpected field 'typo' in rule `convert_require` (at `rules[2].typo`, line 5, column 65)"
+            );
+        }
+
+        #[test]
+        fn invalid_field_of_nested_require_mode() {
+            pretty_assertions::assert_eq!(
+                deserialize_error(
+                    "{\n  rules: [\n    {\n      rule: 'convert_require',\n      current: { name: 'path', modul_folder_name: 'init' },\n      target: 'roblox',\n    },\n  ],\n}"
+                ),
+                "unexpected value for field 'current': unknown field `modul_folder_name`, expected one of `module_folder_name`, `sources`, `package_path` in rule `convert_require` (at `rules[0].current`, line 5, column 7)"
+            );
+        }
+
+        #[test]
+        fn unknown_rule_name_suggests_closest_name() {
+            pretty_assertions::assert_eq!(
+                deserialize_error("{ rules: ['remove_spaces', 'remove_coments'] }"),
+                "invalid rule name `remove_coments`, did you mean `remove_comments`? (at `rules[1]`, line 1, column 28)"
+            );
+        }
+
+        #[test]
+        fn unknown_rule_name_in_rule_object() {
+            pretty_assertions::assert_eq!(
+                deserialize_error("{\n  rules: [{ rule: 'rename_variable', globals: [] }],\n}"),
+                "invalid rule name `rename_variable`, did you mean `rename_variables`? (at `rules[0].rule`, line 2, column 13)"
+            );
+        }
+
+        #[test]
+        fn unknown_rule_name_without_close_name() {
+            pretty_assertions::assert_eq!(
+                deserialize_error("{ rules: ['minify'] }"),
+                "invalid rule name `minify` (at `rules[0]`, line 1, column 11)"
+            );
+        }
+
+        #[test]
+        fn missing_property_in_override_rule() {
+            pretty_assertions::assert_eq!(
+                deserialize_error(
+                    "{\n  overrides: [\n    { include: '*.lua', rules: [] },\n    { include: 'lib/', rules: ['remove_spaces', { rule: 'inject_global_value' }] },\n  ],\n}"
+                ),
+                "missing required field 'identifier' in rule `inject_global_value` (at `overrides[1].rules[1]`, line 4, column 48)"
+            );
+        }
+
+        #[test]
+        fn invalid_override_pattern() {
+            let error = deserialize_error("{ overrides: [{ include: 'lib/{a' }] }");
+
+            assert!(
+                error.starts_with("invalid file pattern `lib/{a`: "),
+                "unexpected error: {}",
+                error
+            );
+            assert!(
+                error.ends_with("(at `overrides[0]`, line 1, column 15)"),
+                "unexpected error: {}",
+                error
+            );
+        }
+
+        #[test]
+        fn find_json5_path_skips_comments_and_strings() {
+            let content = "{\n  // rules: [],\n  'generator': '{[',\n  /* rules */ rules: [\n    'a',\n  ],\n}";
+
+            pretty_assertions::assert_eq!(
+                find_json5_path_location(content, "rules[0]"),
+                Some((5, 5))
+            );
+        }
+
+        #[test]
+        fn find_path_location_of_closest_parent() {
+            let content = "rules = ['remove_comments', { rule = 'oops' }]\n";
+
+            pretty_assertions::assert_eq!(
+                find_path_location("rules[1].rule", |path| find_toml_path_location(
+                    content, path
+                )),
+                Some((1, 1))
+            );
+        }
+
+        #[test]
+        fn toml_error_in_array_of_tables() {
+            let error = configuration_from_toml(
+                "[[rules]]\nrule = 'remove_comments'\n\n[[rules]]\nrule = 'remove_spaces'\n\n[[rules]]\nrule = 'convert_require'\ncurrent = 'path'\ntarget = 'roblox'\ntypo = true\n",
+            )
+            .expect_err("deserialization should fail");
+
+            pretty_assertions::assert_eq!(
+                error,
+                "unexpected field 'typo' in rule `convert_require` (at `rules[2].typo`, line 11, column 1)"
+            );
+        }
+    }
+
     mod generator_parameters {
         use super::*;
 
@@ -291,7 +1211,10 @@ mod test {
             let config: Configuration =
                 json5::from_str("{ generator: { name: 'retain_lines' } }").unwrap();
 
-            pretty_assertions::assert_eq!(config.generator, GeneratorParameters::RetainLines);
+            pretty_assertions::assert_eq!(
+                config.generator,
+                GeneratorParameters::default_retain_lines()
+            );
         }
 
         #[test]
@@ -299,7 +1222,24 @@ mod test {
             let config: Configuration =
                 json5::from_str("{ generator: { name: 'retain-lines' } }").unwrap();
 
-            pretty_assertions::assert_eq!(config.generator, GeneratorParameters::RetainLines);
+            pretty_assertions::assert_eq!(
+                config.generator,
+                GeneratorParameters::default_retain_lines()
+            );
+        }
+
+        #[test]
+        fn deserialize_retain_lines_params_with_escape_unicode() {
+            let config: Configuration =
+                json5::from_str("{ generator: { name: 'retain_lines', escape_unicode: true } }")
+                    .unwrap();
+
+            pretty_assertions::assert_eq!(
+                config.generator,
+                GeneratorParameters::RetainLines {
+                    escape_unicode: true
+                }
+            );
         }
 
         #[test]
@@ -332,9 +1272,7 @@ mod test {
 
             pretty_assertions::assert_eq!(
                 config.generator,
-                GeneratorParameters::Readable {
-                    column_span: DEFAULT_COLUMN_SPAN
-                }
+                GeneratorParameters::default_readable()
             );
         }
 
@@ -343,9 +1281,21 @@ mod test {
             let config: Configuration =
                 json5::from_str("{ generator: { name: 'readable', column_span: 110 }}").unwrap();
 
+            pretty_assertions::assert_eq!(config.generator, GeneratorParameters::readable(110));
+        }
+
+        #[test]
+        fn deserialize_readable_params_with_max_line_length() {
+            let config: Configuration =
+                json5::from_str("{ generator: { name: 'readable', max_line_length: 100 }}")
+                    .unwrap();
+
             pretty_assertions::assert_eq!(
                 config.generator,
-                GeneratorParameters::Readable { column_span: 110 }
+                GeneratorParameters::Readable {
+                    column_span: DEFAULT_COLUMN_SPAN,
+                    max_line_length: Some(100),
+                }
             );
         }
 
@@ -353,7 +1303,10 @@ mod test {
         fn deserialize_retain_lines_params_as_string() {
             let config: Configuration = json5::from_str("{generator: 'retain_lines'}").unwrap();
 
-            pretty_assertions::assert_eq!(config.generator, GeneratorParameters::RetainLines);
+            pretty_assertions::assert_eq!(
+                config.generator,
+                GeneratorParameters::default_retain_lines()
+            );
         }
 
         #[test]
@@ -374,9 +1327,7 @@ mod test {
 
             pretty_assertions::assert_eq!(
                 config.generator,
-                GeneratorParameters::Readable {
-                    column_span: DEFAULT_COLUMN_SPAN
-                }
+                GeneratorParameters::default_readable()
             );
         }
 
@@ -386,7 +1337,7 @@ mod test {
 
             pretty_assertions::assert_eq!(
                 result.expect_err("deserialization should fail").to_string(),
-                "invalid generator name `oops`"
+                "invalid generator name `oops` (at `generator`)"
             );
         }
     }
@@ -479,7 +1430,181 @@ mod test {
 
             pretty_assertions::assert_eq!(
                 result.expect_err("deserialization should fail").to_string(),
-                "invalid require mode `oops`"
+                "invalid require mode `oops` (at `bundle.require_mode`)"
+            );
+        }
+    }
+
+    mod layers {
+        use super::*;
+        use crate::rules::RenameVariables;
+
+        const CONFIG: &str = r#"{
+            generator: "readable",
+            rules: [
+                "remove_comments",
+                { rule: "rename_variables", include_functions: true },
+                "remove_spaces",
+            ],
+        }"#;
+
+        fn load_configuration() -> Configuration {
+            json5::from_str(CONFIG).unwrap()
+        }
+
+        fn serialize_rules(configuration: &Configuration) -> serde_json::Value {
+            serde_json::to_value(&configuration.rules).unwrap()
+        }
+
+        #[test]
+        fn disable_rule() {
+            let configuration = load_configuration().with_rule_disabled("rename_variables");
+
+            pretty_assertions::assert_eq!(
+                serialize_rules(&configuration),
+                serde_json::json!(["remove_comments", "remove_spaces"])
+            );
+        }
+
+        #[test]
+        fn override_rule_replaces_its_properties() {
+            let configuration = load_configuration()
+                .with_rule_overridden(Box::<RenameVariables>::default() as Box<dyn Rule>);
+
+            pretty_assertions::assert_eq!(
+                serialize_rules(&configuration),
+                serde_json::json!(["remove_comments", "rename_variables", "remove_spaces"])
+            );
+        }
+
+        #[test]
+        fn override_missing_rule_appends_it() {
+            let configuration = load_configuration()
+                .with_rule_overridden(Box::<RemoveTypes>::default() as Box<dyn Rule>);
+
+            pretty_assertions::assert_eq!(
+                serialize_rules(&configuration),
+                serde_json::json!([
+                    "remove_comments",
+                    { "rule": "rename_variables", "include_functions": true },
+                    "remove_spaces",
+                    "remove_types",
+                ])
+            );
+        }
+
+        #[test]
+        fn layer_applies_changes_in_order_and_generator() {
+            let configuration = load_configuration().with_layer(
+                ConfigurationLayer::new()
+                    .with_rule_disabled("remove_spaces")
+                    .with_rule_overridden(Box::<RemoveTypes>::default() as Box<dyn Rule>)
+                    .with_rule_disabled("remove_comments")
+                    .with_generator(GeneratorParameters::default_retain_lines()),
+            );
+
+            pretty_assertions::assert_eq!(
+                serialize_rules(&configuration),
+                serde_json::json!([
+                    { "rule": "rename_variables", "include_functions": true },
+                    "remove_types",
+                ])
+            );
+            pretty_assertions::assert_eq!(
+                configuration.generator,
+                GeneratorParameters::default_retain_lines()
+            );
+        }
+
+        #[test]
+        fn empty_layer_keeps_configuration() {
+            let configuration = load_configuration().with_layer(ConfigurationLayer::new());
+
+            pretty_assertions::assert_eq!(
+                serialize_rules(&configuration),
+                serialize_rules(&load_configuration())
+            );
+            pretty_assertions::assert_eq!(
+                configuration.generator,
+                GeneratorParameters::default_readable()
+            );
+        }
+    }
+
+    mod targets {
+        use super::*;
+        use crate::rules::{RemoveComments, RuleConfiguration};
+
+        fn rule_names(configuration: &Configuration) -> Vec<&'static str> {
+            configuration.rules().map(|rule| rule.get_name()).collect()
+        }
+
+        #[test]
+        fn luau_target_has_no_rules() {
+            assert!(LuaTarget::Luau.rules().is_empty());
+        }
+
+        #[test]
+        fn lua51_target_removes_floor_division() {
+            assert!(LuaTarget::Lua51
+                .rules()
+                .iter()
+                .any(|rule| rule.get_name() == RemoveFloorDivision::default().get_name()));
+        }
+
+        #[test]
+        fn lua53_target_keeps_floor_division() {
+            assert!(!LuaTarget::Lua53
+                .rules()
+                .iter()
+                .any(|rule| rule.get_name() == RemoveFloorDivision::default().get_name()));
+        }
+
+        #[test]
+        fn with_target_places_rules_before_configuration_rules() {
+            let configuration = Configuration::empty()
+                .with_rule(Box::<RemoveComments>::default() as Box<dyn Rule>)
+                .with_target(LuaTarget::Lua53);
+
+            pretty_assertions::assert_eq!(
+                rule_names(&configuration),
+                vec![
+                    "remove_types",
+                    "remove_attributes",
+                    "remove_interpolated_string",
+                    "remove_if_expression",
+                    "remove_compound_assignment",
+                    "remove_continue",
+                    "remove_comments",
+                ]
+            );
+        }
+
+        #[test]
+        fn with_target_keeps_configuration_rule_with_same_name() {
+            let configuration = Configuration::empty()
+                .with_rule(Box::<RemoveContinue>::default() as Box<dyn Rule>)
+                .with_target(LuaTarget::Lua53);
+
+            pretty_assertions::assert_eq!(
+                rule_names(&configuration).last(),
+                Some(&"remove_continue")
+            );
+            pretty_assertions::assert_eq!(
+                rule_names(&configuration)
+                    .iter()
+                    .filter(|name| **name == "remove_continue")
+                    .count(),
+                1
+            );
+        }
+
+        #[test]
+        fn parse_target_from_string() {
+            pretty_assertions::assert_eq!("lua51".parse::<LuaTarget>(), Ok(LuaTarget::Lua51));
+            pretty_assertions::assert_eq!(
+                "lua52".parse::<LuaTarget>(),
+                Err("invalid target `lua52`".to_owned())
             );
         }
     }
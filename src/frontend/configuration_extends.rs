@@ -0,0 +1,202 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde_json::{Map, Value};
+
+use crate::{rules::get_default_rules, utils::normalize_path};
+
+use super::{
+    configuration::{
+        is_toml_file, locate_json5_error, locate_toml_error, Configuration, LuaTarget,
+    },
+    configuration_layer::{layer_rules, RuleChange},
+    resources::Resources,
+    DarkluaError, DarkluaResult,
+};
+
+const EXTENDS_FIELD: &str = "extends";
+const DISABLE_FIELD: &str = "disable";
+const RULES_FIELD: &str = "rules";
+const RULES_ALIAS_FIELD: &str = "process";
+const TARGET_FIELD: &str = "target";
+
+/// Parses the content of a configuration file into a generic value, using the extension
+/// of the file to find its format.
+pub(crate) fn parse_configuration_value(path: &Path, content: &str) -> DarkluaResult<Value> {
+    if is_toml_file(path) {
+        let value: toml::Value = toml::from_str(content).map_err(|err| {
+            DarkluaError::invalid_configuration_file(path)
+                .context(locate_toml_error(&err, content))
+                .with_source(Arc::new(err))
+        })?;
+        serde_json::to_value(value)
+            .map_err(|err| DarkluaError::invalid_configuration_file(path).context(err.to_string()))
+    } else {
+        json5::from_str(content).map_err(|err| {
+            DarkluaError::invalid_configuration_file(path)
+                .context(locate_json5_error(&err.to_string(), content))
+                .with_source(Arc::new(err))
+        })
+    }
+}
+
+/// Returns `true` if the configuration value has fields that need to be resolved with
+/// [`resolve_extends`] before it can be deserialized.
+pub(crate) fn needs_resolution(value: &Value) -> bool {
+    value.get(EXTENDS_FIELD).is_some() || value.get(TARGET_FIELD).is_some()
+}
+
+/// Reads the chain of configurations extended by the given configuration value and merges
+/// them into a single configuration.
+///
+/// Fields of the extending configuration override the fields of the base configuration,
+/// except for the rules, which are merged: a rule with the same name as an inherited rule
+/// overrides its properties, an entry like `{ disable: "rule_name" }` removes the inherited
+/// rules with that name, and other rules are appended.
+///
+/// The `target` field of a configuration is expanded before its rules are merged with
+/// the extended configuration: the rules of the configuration (or the default rules when
+/// they are not defined) are merged into the rules of the target with the same logic.
+///
+/// The base configurations are merged as values, so their paths are not rebased: like the
+/// paths of the extending configuration, they are resolved from the location of the
+/// configuration file that is loaded.
+pub(crate) fn resolve_extends(
+    resources: &Resources,
+    path: &Path,
+    value: Value,
+) -> DarkluaResult<Configuration> {
+    let mut chain = vec![normalize_path(path)];
+    let value = resolve(resources, value, &mut chain)?;
+
+    serde_json::from_value(value)
+        .map_err(|err| DarkluaError::invalid_configuration_file(path).context(err.to_string()))
+}
+
+fn resolve(
+    resources: &Resources,
+    mut value: Value,
+    chain: &mut Vec<PathBuf>,
+) -> DarkluaResult<Value> {
+    let current = chain.last().expect("chain should not be empty").clone();
+
+    let (extends, target) = match value.as_object_mut() {
+        Some(object) => (object.remove(EXTENDS_FIELD), object.remove(TARGET_FIELD)),
+        None => {
+            return Err(DarkluaError::invalid_configuration_file(&current)
+                .context("expected configuration to be an object"))
+        }
+    };
+
+    if let Some(target) = target {
+        value = expand_target(value, target, &current)?;
+    }
+
+    let extends = match extends {
+        Some(Value::String(extends)) => extends,
+        Some(_) => {
+            return Err(DarkluaError::invalid_configuration_file(&current)
+                .context("expected `extends` to be a path"))
+        }
+        None => return Ok(value),
+    };
+
+    let base_path = normalize_path(
+        current
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(extends),
+    );
+
+    if chain.contains(&base_path) {
+        chain.push(base_path);
+        return Err(DarkluaError::cyclic_configuration_extends(chain.clone()));
+    }
+
+    log::trace!(
+        "configuration `{}` extends `{}`",
+        current.display(),
+        base_path.display()
+    );
+
+    let base_content = resources.get(&base_path).map_err(|err| {
+        DarkluaError::from(err).context(format!("extended by `{}`", current.display()))
+    })?;
+    let base_value = parse_configuration_value(&base_path, &base_content)?;
+
+    chain.push(base_path);
+    let base_value = resolve(resources, base_value, chain)?;
+    chain.pop();
+
+    Ok(merge_configuration(base_value, value))
+}
+
+fn expand_target(value: Value, target: Value, path: &Path) -> DarkluaResult<Value> {
+    let target: LuaTarget = serde_json::from_value(target).map_err(|err| {
+        DarkluaError::invalid_configuration_file(path).context(format!("invalid `target`: {}", err))
+    })?;
+
+    log::trace!(
+        "expand target `{:?}` of configuration `{}`",
+        target,
+        path.display()
+    );
+
+    let mut object = into_configuration_object(value);
+    let rules = object
+        .remove(RULES_FIELD)
+        .unwrap_or_else(|| serde_json::to_value(get_default_rules()).unwrap_or_default());
+    let target_rules = serde_json::to_value(target.rules()).unwrap_or_default();
+    object.insert(RULES_FIELD.to_owned(), merge_rules(target_rules, rules));
+
+    Ok(Value::Object(object))
+}
+
+fn merge_configuration(base: Value, extension: Value) -> Value {
+    let mut base = into_configuration_object(base);
+
+    for (key, value) in into_configuration_object(extension) {
+        if key == RULES_FIELD {
+            let base_rules = base
+                .remove(RULES_FIELD)
+                .unwrap_or_else(|| serde_json::to_value(get_default_rules()).unwrap_or_default());
+            base.insert(key, merge_rules(base_rules, value));
+        } else {
+            base.insert(key, value);
+        }
+    }
+
+    Value::Object(base)
+}
+
+fn into_configuration_object(value: Value) -> Map<String, Value> {
+    let mut object = match value {
+        Value::Object(object) => object,
+        _ => Map::new(),
+    };
+
+    if let Some(rules) = object.remove(RULES_ALIAS_FIELD) {
+        object.insert(RULES_FIELD.to_owned(), rules);
+    }
+
+    object
+}
+
+fn merge_rules(base: Value, extension: Value) -> Value {
+    let (base, extension) = match (base, extension) {
+        (Value::Array(base), Value::Array(extension)) => (base, extension),
+        (_, extension) => return extension,
+    };
+
+    let changes =
+        extension.into_iter().map(
+            |rule| match rule.get(DISABLE_FIELD).and_then(Value::as_str) {
+                Some(disabled_name) => RuleChange::Disable(disabled_name.to_owned()),
+                None => RuleChange::Override(rule),
+            },
+        );
+
+    Value::Array(layer_rules(base, changes))
+}
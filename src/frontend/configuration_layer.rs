@@ -0,0 +1,272 @@
+use std::fmt;
+
+use serde_json::{Map, Value};
+
+use crate::rules::Rule;
+
+use super::configuration::{BundleConfiguration, GeneratorParameters};
+
+/// A change to the list of rules of a configuration.
+pub(crate) enum RuleChange<T> {
+    /// Removes every rule with this name.
+    Disable(String),
+    /// Overrides the first rule with the same name that was not already overridden, or
+    /// appends the rule when there is none.
+    Override(T),
+    /// Adds the rule after the other rules, even when a rule with the same name exists.
+    Append(T),
+}
+
+/// A rule of a list where rules can be disabled or overridden by name.
+pub(crate) trait LayeredRule: Sized {
+    fn layer_name(&self) -> Option<&str>;
+
+    fn override_with(self, rule: Self) -> Self;
+}
+
+impl LayeredRule for Box<dyn Rule> {
+    fn layer_name(&self) -> Option<&str> {
+        Some(self.get_name())
+    }
+
+    fn override_with(self, rule: Self) -> Self {
+        rule
+    }
+}
+
+impl LayeredRule for Value {
+    fn layer_name(&self) -> Option<&str> {
+        match self {
+            Value::String(name) => Some(name),
+            Value::Object(object) => object
+                .get("rule")
+                .or_else(|| object.get("name"))
+                .and_then(Value::as_str),
+            _ => None,
+        }
+    }
+
+    /// Rules written in configuration files only override the properties they define.
+    fn override_with(self, rule: Self) -> Self {
+        let properties = match rule {
+            Value::Object(properties) => properties,
+            _ => return self,
+        };
+
+        let mut inherited_rule = match self {
+            Value::Object(object) => object,
+            Value::String(name) => {
+                let mut object = Map::new();
+                object.insert("rule".to_owned(), Value::String(name));
+                object
+            }
+            _ => Map::new(),
+        };
+
+        for (key, value) in properties {
+            if key != "rule" && key != "name" {
+                inherited_rule.insert(key, value);
+            }
+        }
+
+        Value::Object(inherited_rule)
+    }
+}
+
+/// Applies changes to a list of rules, in order. This is used both by the `extends` field
+/// of configuration files and by [`ConfigurationLayer`], so that both behave the same way.
+pub(crate) fn layer_rules<T: LayeredRule>(
+    rules: Vec<T>,
+    changes: impl IntoIterator<Item = RuleChange<T>>,
+) -> Vec<T> {
+    // each rule is paired with a boolean that tells if it was already overridden, so that
+    // a rule that appears multiple times can be overridden once for each occurrence
+    let mut rules: Vec<(Option<T>, bool)> =
+        rules.into_iter().map(|rule| (Some(rule), false)).collect();
+
+    for change in changes {
+        match change {
+            RuleChange::Disable(disabled_name) => {
+                rules.retain(|(rule, _)| {
+                    rule.as_ref().and_then(LayeredRule::layer_name) != Some(disabled_name.as_str())
+                });
+            }
+            RuleChange::Override(rule) => {
+                let inherited_index = rule.layer_name().and_then(|name| {
+                    rules.iter().position(|(inherited_rule, overridden)| {
+                        !overridden
+                            && inherited_rule.as_ref().and_then(LayeredRule::layer_name)
+                                == Some(name)
+                    })
+                });
+
+                match inherited_index {
+                    Some(index) => {
+                        let inherited_rule = rules[index].0.take().expect("rule should exist");
+                        rules[index] = (Some(inherited_rule.override_with(rule)), true);
+                    }
+                    None => rules.push((Some(rule), true)),
+                }
+            }
+            RuleChange::Append(rule) => rules.push((Some(rule), false)),
+        }
+    }
+
+    rules.into_iter().filter_map(|(rule, _)| rule).collect()
+}
+
+/// Changes applied on top of a configuration, like the configuration loaded from a file by
+/// [`process`](crate::process) when given to
+/// [`Options::with_configuration_layer`](crate::Options::with_configuration_layer).
+///
+/// Rules are disabled and overridden by name, in the order the changes are added. An
+/// overridden rule replaces the first rule with the same name, or it is appended to the
+/// rules when there is no rule with that name.
+#[derive(Default)]
+pub struct ConfigurationLayer {
+    rule_changes: Vec<RuleChange<Box<dyn Rule>>>,
+    generator: Option<GeneratorParameters>,
+    bundle: Option<BundleConfiguration>,
+}
+
+impl ConfigurationLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes every rule with the given name.
+    pub fn with_rule_disabled(mut self, rule_name: impl Into<String>) -> Self {
+        self.rule_changes
+            .push(RuleChange::Disable(rule_name.into()));
+        self
+    }
+
+    /// Replaces the rule with the same name (and its properties) with the given rule.
+    pub fn with_rule_overridden(mut self, rule: impl Into<Box<dyn Rule>>) -> Self {
+        self.rule_changes.push(RuleChange::Override(rule.into()));
+        self
+    }
+
+    /// Adds the rule after the other rules, without replacing rules with the same name.
+    pub fn with_rule(mut self, rule: impl Into<Box<dyn Rule>>) -> Self {
+        self.rule_changes.push(RuleChange::Append(rule.into()));
+        self
+    }
+
+    pub fn with_generator(mut self, generator: impl Into<GeneratorParameters>) -> Self {
+        self.generator = Some(generator.into());
+        self
+    }
+
+    /// Replaces the bundle configuration, so that the files are bundled with it.
+    pub fn with_bundle_configuration(mut self, configuration: BundleConfiguration) -> Self {
+        self.bundle = Some(configuration);
+        self
+    }
+
+    pub(crate) fn into_parts(
+        self,
+    ) -> (
+        Vec<RuleChange<Box<dyn Rule>>>,
+        Option<GeneratorParameters>,
+        Option<BundleConfiguration>,
+    ) {
+        (self.rule_changes, self.generator, self.bundle)
+    }
+}
+
+impl fmt::Debug for ConfigurationLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigurationLayer")
+            .field(
+                "rule_changes",
+                &self
+                    .rule_changes
+                    .iter()
+                    .map(|change| match change {
+                        RuleChange::Disable(name) => format!("disable {}", name),
+                        RuleChange::Override(rule) => format!("override {}", rule.get_name()),
+                        RuleChange::Append(rule) => format!("append {}", rule.get_name()),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .field("generator", &self.generator)
+            .field("bundle", &self.bundle)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn override_value_rule_merges_properties() {
+        pretty_assertions::assert_eq!(
+            layer_rules(
+                vec![json!({ "rule": "rename_variables", "globals": ["$default"] })],
+                vec![RuleChange::Override(
+                    json!({ "rule": "rename_variables", "include_functions": true })
+                )],
+            ),
+            vec![json!({
+                "rule": "rename_variables",
+                "globals": ["$default"],
+                "include_functions": true,
+            })]
+        );
+    }
+
+    #[test]
+    fn override_each_occurrence_once() {
+        pretty_assertions::assert_eq!(
+            layer_rules(
+                vec![json!("remove_comments"), json!("remove_comments")],
+                vec![
+                    RuleChange::Override(json!({ "rule": "remove_comments", "a": 1 })),
+                    RuleChange::Override(json!({ "rule": "remove_comments", "b": 2 })),
+                    RuleChange::Override(json!("remove_comments")),
+                ],
+            ),
+            vec![
+                json!({ "rule": "remove_comments", "a": 1 }),
+                json!({ "rule": "remove_comments", "b": 2 }),
+                json!("remove_comments"),
+            ]
+        );
+    }
+
+    #[test]
+    fn disable_removes_every_occurrence() {
+        pretty_assertions::assert_eq!(
+            layer_rules(
+                vec![
+                    json!("remove_comments"),
+                    json!("remove_spaces"),
+                    json!({ "rule": "remove_comments" }),
+                ],
+                vec![RuleChange::Disable("remove_comments".to_owned())],
+            ),
+            vec![json!("remove_spaces")]
+        );
+    }
+
+    #[test]
+    fn append_keeps_rules_with_the_same_name() {
+        pretty_assertions::assert_eq!(
+            layer_rules(
+                vec![json!("remove_comments")],
+                vec![
+                    RuleChange::Append(json!({ "rule": "remove_comments", "a": 1 })),
+                    RuleChange::Override(json!({ "rule": "remove_comments", "b": 2 })),
+                ],
+            ),
+            vec![
+                json!({ "rule": "remove_comments", "b": 2 }),
+                json!({ "rule": "remove_comments", "a": 1 }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use std::{mem, path::PathBuf};
+
+use crate::rules::{bundle::BundleRequireMode, Rule, RuleProperties};
+
+use super::{
+    process, BundleConfiguration, Configuration, ConfigurationLayer, DarkluaError, DarkluaResult,
+    FileBudget, GeneratorParameters, Options, ProcessReport, Resources,
+};
+
+/// A builder to configure and run darklua from code, without writing a configuration file.
+///
+/// Rules, the generator and the bundle configuration are applied on top of a base
+/// configuration. The base is empty (no rules and the default generator), unless a
+/// configuration or a configuration file is given.
+///
+/// ```
+/// # use darklua_core::{
+/// #     rules::RemoveEmptyDo,
+/// #     Darklua, GeneratorParameters, Resources,
+/// # };
+/// # fn main() -> Result<(), darklua_core::DarkluaError> {
+/// let resources = Resources::from_memory();
+/// resources.write("src/init.lua", "do end return nil").unwrap();
+///
+/// let report = Darklua::new()
+///     .with_resources(resources.clone())
+///     .with_input("src")
+///     .with_output("dist")
+///     .with_rule(RemoveEmptyDo::default())
+///     .with_rule_named("remove_comments")?
+///     .with_generator(GeneratorParameters::dense(80))
+///     .run()?;
+///
+/// assert!(!report.has_errors());
+/// assert_eq!(resources.get("dist/init.lua").unwrap(), "return nil");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Darklua {
+    resources: Resources,
+    input: Option<PathBuf>,
+    output: Option<PathBuf>,
+    configuration: Option<Configuration>,
+    configuration_path: Option<PathBuf>,
+    layers: Vec<ConfigurationLayer>,
+    fail_fast: bool,
+    threads: Option<usize>,
+    file_budget: Option<FileBudget>,
+}
+
+impl Default for Darklua {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Darklua {
+    /// Creates a builder that reads and writes files on the file system. When the `fs`
+    /// feature is disabled, files are kept in memory.
+    pub fn new() -> Self {
+        Self {
+            resources: Self::default_resources(),
+            input: None,
+            output: None,
+            configuration: None,
+            configuration_path: None,
+            layers: Vec::new(),
+            fail_fast: false,
+            threads: None,
+            file_budget: None,
+        }
+    }
+
+    #[cfg(feature = "fs")]
+    fn default_resources() -> Resources {
+        Resources::from_file_system()
+    }
+
+    #[cfg(not(feature = "fs"))]
+    fn default_resources() -> Resources {
+        Resources::from_memory()
+    }
+
+    /// Sets the resources used to read and write files.
+    pub fn with_resources(mut self, resources: Resources) -> Self {
+        self.resources = resources;
+        self
+    }
+
+    /// Sets the file or directory to process.
+    pub fn with_input(mut self, input: impl Into<PathBuf>) -> Self {
+        self.input = Some(input.into());
+        self
+    }
+
+    /// Sets where the processed files are written. When not set, the input files are
+    /// overwritten.
+    pub fn with_output(mut self, output: impl Into<PathBuf>) -> Self {
+        self.output = Some(output.into());
+        self
+    }
+
+    /// Uses the given configuration as the base for the other changes of the builder.
+    pub fn with_configuration(mut self, configuration: Configuration) -> Self {
+        self.configuration = Some(configuration);
+        self
+    }
+
+    /// Uses the configuration file at the given path as the base for the other changes of
+    /// the builder.
+    pub fn with_configuration_at(mut self, path: impl Into<PathBuf>) -> Self {
+        self.configuration_path = Some(path.into());
+        self
+    }
+
+    /// Adds the rule after the other rules.
+    pub fn with_rule(self, rule: impl Into<Box<dyn Rule>>) -> Self {
+        self.edit_layer(|layer| layer.with_rule(rule))
+    }
+
+    /// Adds the rule with the given name and its default properties after the other rules.
+    pub fn with_rule_named(self, rule_name: &str) -> DarkluaResult<Self> {
+        let mut rule: Box<dyn Rule> = rule_name.parse().map_err(DarkluaError::invalid_options)?;
+
+        rule.configure(RuleProperties::new()).map_err(|err| {
+            DarkluaError::invalid_options(format!("{} in rule `{}`", err, rule_name))
+        })?;
+
+        Ok(self.with_rule(rule))
+    }
+
+    /// Removes every rule with the given name.
+    pub fn with_rule_disabled(self, rule_name: impl Into<String>) -> Self {
+        self.edit_layer(|layer| layer.with_rule_disabled(rule_name))
+    }
+
+    /// Replaces the rule with the same name (and its properties) with the given rule.
+    pub fn with_rule_overridden(self, rule: impl Into<Box<dyn Rule>>) -> Self {
+        self.edit_layer(|layer| layer.with_rule_overridden(rule))
+    }
+
+    pub fn with_generator(self, generator: impl Into<GeneratorParameters>) -> Self {
+        self.edit_layer(|layer| layer.with_generator(generator))
+    }
+
+    /// Bundles the input file, using the given require mode to find the required modules.
+    pub fn with_require_mode(self, require_mode: impl Into<BundleRequireMode>) -> Self {
+        self.with_bundle_configuration(BundleConfiguration::new(require_mode))
+    }
+
+    pub fn with_bundle_configuration(self, configuration: BundleConfiguration) -> Self {
+        self.edit_layer(|layer| layer.with_bundle_configuration(configuration))
+    }
+
+    /// Applies the layer after the changes already made with the builder.
+    pub fn with_layer(mut self, layer: ConfigurationLayer) -> Self {
+        self.layers.push(layer);
+        self.layers.push(ConfigurationLayer::new());
+        self
+    }
+
+    /// Stops processing files after the first error.
+    pub fn fail_fast(mut self) -> Self {
+        self.fail_fast = true;
+        self
+    }
+
+    /// Processes the files with the given number of threads.
+    pub fn parallel(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Abandons the files that exceed the given budget (see [`FileBudget`]).
+    pub fn with_file_budget(mut self, budget: FileBudget) -> Self {
+        self.file_budget = Some(budget);
+        self
+    }
+
+    fn edit_layer(mut self, edit: impl FnOnce(ConfigurationLayer) -> ConfigurationLayer) -> Self {
+        let layer = match self.layers.last_mut() {
+            Some(layer) => layer,
+            None => {
+                self.layers.push(ConfigurationLayer::new());
+                self.layers.last_mut().expect("layer should exist")
+            }
+        };
+
+        *layer = edit(mem::take(layer));
+        self
+    }
+
+    /// Validates the builder and processes the files.
+    pub fn run(self) -> DarkluaResult<ProcessReport> {
+        let input = self
+            .input
+            .ok_or_else(|| DarkluaError::invalid_options("an input path is required"))?;
+
+        let mut options = Options::new(input);
+
+        options = match (self.configuration, self.configuration_path) {
+            (Some(_), Some(path)) => {
+                return Err(DarkluaError::invalid_options(format!(
+                    "a configuration and a configuration file (`{}`) cannot be used together",
+                    path.display()
+                )))
+            }
+            (Some(configuration), None) => options.with_configuration(configuration),
+            (None, Some(path)) => options.with_configuration_at(path),
+            (None, None) => options.with_configuration(Configuration::empty()),
+        };
+
+        if let Some(output) = self.output {
+            options = options.with_output(output);
+        }
+        if self.fail_fast {
+            options = options.fail_fast();
+        }
+        if let Some(threads) = self.threads {
+            options = options.parallel(threads);
+        }
+        if let Some(budget) = self.file_budget {
+            options = options.with_file_budget(budget);
+        }
+
+        for layer in self.layers {
+            options = options.with_configuration_layer(layer);
+        }
+
+        Ok(process(&self.resources, options)?.report())
+    }
+}
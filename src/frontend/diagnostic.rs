@@ -0,0 +1,127 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// The category of a [`Diagnostic`]. The serialized names of these kinds are stable, so
+/// that tools can rely on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    /// A file could not be read or written.
+    Io,
+    /// A file could not be parsed.
+    Parse,
+    /// A rule failed to process a file.
+    Rule,
+    /// A configuration file is invalid.
+    Configuration,
+    /// A required module could not be found or loaded.
+    RequireResolution,
+    /// Files depend on each other in a way that can not be resolved.
+    Cycle,
+    /// Data could not be serialized or deserialized.
+    Data,
+    /// A file exceeded its processing budget.
+    Timeout,
+    /// A file is nested deeper than the processing limit.
+    DepthLimitExceeded,
+    /// Any other error.
+    Other,
+}
+
+/// The line and column (both starting at 1) where a [`Diagnostic`] is located.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiagnosticSpan {
+    line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+}
+
+impl DiagnosticSpan {
+    pub(crate) fn new(line: usize, column: Option<usize>) -> Self {
+        Self { line, column }
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+}
+
+/// A machine-readable description of a [`DarkluaError`](crate::DarkluaError), obtained
+/// with [`DarkluaError::to_diagnostic`](crate::DarkluaError::to_diagnostic). It can be
+/// serialized (to JSON for example) to be consumed by editors or CI tools.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    kind: DiagnosticKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    span: Option<DiagnosticSpan>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule: Option<String>,
+    message: String,
+    context: Vec<String>,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(kind: DiagnosticKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            path: None,
+            span: None,
+            rule: None,
+            message: message.into(),
+            context: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub(crate) fn with_span(mut self, span: Option<DiagnosticSpan>) -> Self {
+        self.span = span;
+        self
+    }
+
+    pub(crate) fn with_rule(mut self, rule: impl Into<String>) -> Self {
+        self.rule = Some(rule.into());
+        self
+    }
+
+    pub(crate) fn with_context(mut self, context: Vec<String>) -> Self {
+        self.context = context;
+        self
+    }
+
+    pub fn kind(&self) -> DiagnosticKind {
+        self.kind
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn span(&self) -> Option<&DiagnosticSpan> {
+        self.span.as_ref()
+    }
+
+    /// The name of the rule that produced the error, if any.
+    pub fn rule(&self) -> Option<&str> {
+        self.rule.as_deref()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Additional information about the error, in the order it was attached to the error.
+    pub fn iter_context(&self) -> impl Iterator<Item = &str> {
+        self.context.iter().map(String::as_str)
+    }
+}
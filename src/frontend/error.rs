@@ -2,20 +2,50 @@ use std::{
     borrow::Cow,
     cmp::Ordering,
     collections::HashSet,
+    error::Error,
     ffi::{OsStr, OsString},
     fmt::{self, Display},
+    io,
     path::PathBuf,
+    sync::Arc,
 };
 
-use crate::{process::LuaSerializerError, rules::Rule, ParserError};
+use crate::{
+    nodes::SourcePosition,
+    process::LuaSerializerError,
+    render_code_frame,
+    rules::{Rule, RuleErrorDetails},
+    ParserError,
+};
 
 use super::{
+    diagnostic::{Diagnostic, DiagnosticKind, DiagnosticSpan},
     resources::ResourceError,
     work_item::{WorkData, WorkItem, WorkStatus},
 };
 
 #[derive(Debug, Clone)]
-enum ErrorKind {
+struct SourceLocation {
+    line: usize,
+    column: Option<usize>,
+    code_frame: Option<String>,
+}
+
+impl SourceLocation {
+    fn new(position: SourcePosition, code: &str) -> Self {
+        let line = position.line();
+        let column = position.column(code);
+
+        Self {
+            line,
+            column,
+            code_frame: column.and_then(|column| render_code_frame(code, line, column)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ErrorData {
     Parser {
         path: PathBuf,
         error: ParserError,
@@ -23,12 +53,21 @@ enum ErrorKind {
     ResourceNotFound {
         path: PathBuf,
     },
+    InvalidUtf8 {
+        path: PathBuf,
+    },
     InvalidConfiguration {
         path: PathBuf,
     },
     MultipleConfigurationFound {
         paths: Vec<PathBuf>,
     },
+    InvalidOptions {
+        message: String,
+    },
+    CyclicConfigurationExtends {
+        chain: Vec<PathBuf>,
+    },
     IO {
         path: PathBuf,
         error: String,
@@ -36,11 +75,26 @@ enum ErrorKind {
     UncachedWork {
         path: PathBuf,
     },
+    RequiredWorkFailed {
+        path: PathBuf,
+        required: PathBuf,
+    },
+    Timeout {
+        path: PathBuf,
+        rule_name: String,
+        limit: String,
+    },
+    DepthLimitExceeded {
+        path: PathBuf,
+        limit: usize,
+    },
     RuleError {
         path: PathBuf,
         rule_name: String,
         rule_number: Option<usize>,
         error: String,
+        location: Option<SourceLocation>,
+        diagnostic_kind: DiagnosticKind,
     },
     CyclicWork {
         work: Vec<(WorkData, Vec<PathBuf>)>,
@@ -68,29 +122,116 @@ enum ErrorKind {
     },
 }
 
+/// The category of a [`DarkluaError`], to handle errors without matching on their
+/// message. New kinds may be added in the future.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// A file could not be found, read or written.
+    Io,
+    /// A file could not be parsed.
+    Parse,
+    /// A configuration file is invalid or could not be found.
+    Configuration,
+    /// A rule failed to process a file.
+    RuleExecution,
+    /// A required module could not be found or loaded.
+    RequireResolution,
+    /// Files or configuration files depend on each other in a cycle.
+    Cycle,
+    /// Data could not be serialized or deserialized.
+    Data,
+    /// A file exceeded the budget given with
+    /// [`Options::with_file_budget`](crate::Options::with_file_budget).
+    Timeout,
+    /// A file is nested deeper than the limit given with
+    /// [`Options::with_depth_limit`](crate::Options::with_depth_limit).
+    DepthLimitExceeded,
+    /// Any other error.
+    Other,
+}
+
 pub type DarkluaResult<T> = Result<T, DarkluaError>;
 
+type ErrorSource = Arc<dyn Error + Send + Sync>;
+
 #[derive(Debug, Clone)]
 pub struct DarkluaError {
-    kind: Box<ErrorKind>,
-    context: Option<Cow<'static, str>>,
+    kind: Box<ErrorData>,
+    context: Vec<Cow<'static, str>>,
+    source: Option<ErrorSource>,
 }
 
 impl DarkluaError {
-    fn new(kind: ErrorKind) -> Self {
+    fn new(kind: ErrorData) -> Self {
         Self {
             kind: kind.into(),
-            context: None,
+            context: Vec::new(),
+            source: None,
         }
     }
 
-    pub(crate) fn context(mut self, context: impl Into<Cow<'static, str>>) -> Self {
-        self.context = Some(context.into());
+    pub(crate) fn with_source(mut self, source: ErrorSource) -> Self {
+        self.source = Some(source);
         self
     }
 
+    /// Adds a message that describes what was happening when the error occurred. Only
+    /// the last context is displayed with the error, but every context can be obtained
+    /// with [`DarkluaError::contexts`].
+    pub fn context(mut self, context: impl Into<Cow<'static, str>>) -> Self {
+        self.context.push(context.into());
+        self
+    }
+
+    /// Iterates over the contexts added to the error, from the first one to the last one.
+    pub fn contexts(&self) -> impl Iterator<Item = &str> {
+        self.context.iter().map(AsRef::as_ref)
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match &*self.kind {
+            ErrorData::Parser { .. } => ErrorKind::Parse,
+            ErrorData::ResourceNotFound { .. }
+            | ErrorData::InvalidUtf8 { .. }
+            | ErrorData::IO { .. }
+            | ErrorData::OsStringConversion { .. } => ErrorKind::Io,
+            ErrorData::InvalidConfiguration { .. }
+            | ErrorData::MultipleConfigurationFound { .. }
+            | ErrorData::InvalidOptions { .. } => ErrorKind::Configuration,
+            ErrorData::CyclicConfigurationExtends { .. } | ErrorData::CyclicWork { .. } => {
+                ErrorKind::Cycle
+            }
+            ErrorData::RuleError {
+                diagnostic_kind, ..
+            } => match diagnostic_kind {
+                DiagnosticKind::RequireResolution => ErrorKind::RequireResolution,
+                _ => ErrorKind::RuleExecution,
+            },
+            ErrorData::InvalidResourcePath { .. } | ErrorData::InvalidResourceExtension { .. } => {
+                ErrorKind::RequireResolution
+            }
+            ErrorData::Deserialization { .. } | ErrorData::Serialization { .. } => ErrorKind::Data,
+            ErrorData::Timeout { .. } => ErrorKind::Timeout,
+            ErrorData::DepthLimitExceeded { .. } => ErrorKind::DepthLimitExceeded,
+            ErrorData::UncachedWork { .. }
+            | ErrorData::RequiredWorkFailed { .. }
+            | ErrorData::Custom { .. } => ErrorKind::Other,
+        }
+    }
+
+    pub(crate) fn required_work_failed(
+        path: impl Into<PathBuf>,
+        required: impl Into<PathBuf>,
+    ) -> Self {
+        Self::new(ErrorData::RequiredWorkFailed {
+            path: path.into(),
+            required: required.into(),
+        })
+    }
+
     pub(crate) fn parser_error(path: impl Into<PathBuf>, error: ParserError) -> Self {
-        Self::new(ErrorKind::Parser {
+        Self::new(ErrorData::Parser {
             path: path.into(),
             error,
         })
@@ -99,28 +240,61 @@ impl DarkluaError {
     pub(crate) fn multiple_configuration_found(
         configuration_files: impl Iterator<Item = PathBuf>,
     ) -> Self {
-        Self::new(ErrorKind::MultipleConfigurationFound {
+        Self::new(ErrorData::MultipleConfigurationFound {
             paths: configuration_files.collect(),
         })
     }
 
-    pub(crate) fn io_error(path: impl Into<PathBuf>, error: impl Into<String>) -> Self {
-        Self::new(ErrorKind::IO {
+    pub(crate) fn invalid_options(message: impl Into<String>) -> Self {
+        Self::new(ErrorData::InvalidOptions {
+            message: message.into(),
+        })
+    }
+
+    pub(crate) fn cyclic_configuration_extends(chain: impl Into<Vec<PathBuf>>) -> Self {
+        Self::new(ErrorData::CyclicConfigurationExtends {
+            chain: chain.into(),
+        })
+    }
+
+    pub(crate) fn io_error(path: impl Into<PathBuf>, error: impl Into<Arc<io::Error>>) -> Self {
+        let error = error.into();
+        Self::new(ErrorData::IO {
             path: path.into(),
-            error: error.into(),
+            error: error.to_string(),
         })
+        .with_source(error)
     }
 
     pub(crate) fn resource_not_found(path: impl Into<PathBuf>) -> Self {
-        Self::new(ErrorKind::ResourceNotFound { path: path.into() })
+        Self::new(ErrorData::ResourceNotFound { path: path.into() })
     }
 
     pub(crate) fn invalid_configuration_file(path: impl Into<PathBuf>) -> Self {
-        Self::new(ErrorKind::InvalidConfiguration { path: path.into() })
+        Self::new(ErrorData::InvalidConfiguration { path: path.into() })
     }
 
     pub(crate) fn uncached_work(path: impl Into<PathBuf>) -> Self {
-        Self::new(ErrorKind::UncachedWork { path: path.into() })
+        Self::new(ErrorData::UncachedWork { path: path.into() })
+    }
+
+    pub(crate) fn timeout(
+        path: impl Into<PathBuf>,
+        rule_name: impl Into<String>,
+        limit: impl Into<String>,
+    ) -> Self {
+        Self::new(ErrorData::Timeout {
+            path: path.into(),
+            rule_name: rule_name.into(),
+            limit: limit.into(),
+        })
+    }
+
+    pub(crate) fn depth_limit_exceeded(path: impl Into<PathBuf>, limit: usize) -> Self {
+        Self::new(ErrorData::DepthLimitExceeded {
+            path: path.into(),
+            limit,
+        })
     }
 
     pub(crate) fn rule_error(
@@ -129,11 +303,13 @@ impl DarkluaError {
         rule_index: usize,
         rule_error: impl Into<String>,
     ) -> Self {
-        Self::new(ErrorKind::RuleError {
+        Self::new(ErrorData::RuleError {
             path: path.into(),
             rule_name: rule.get_name().to_owned(),
             rule_number: Some(rule_index),
             error: rule_error.into(),
+            location: None,
+            diagnostic_kind: DiagnosticKind::Rule,
         })
     }
 
@@ -142,14 +318,173 @@ impl DarkluaError {
         rule: &dyn Rule,
         rule_error: impl Into<String>,
     ) -> Self {
-        Self::new(ErrorKind::RuleError {
+        Self::new(ErrorData::RuleError {
             path: path.into(),
             rule_name: rule.get_name().to_owned(),
             rule_number: None,
             error: rule_error.into(),
+            location: None,
+            diagnostic_kind: DiagnosticKind::Rule,
         })
     }
 
+    /// Attaches the details reported by a rule through its context. The code of the file is
+    /// used to render the line where the error happened.
+    pub(crate) fn with_rule_error_details(mut self, details: RuleErrorDetails, code: &str) -> Self {
+        if let ErrorData::RuleError {
+            location,
+            diagnostic_kind,
+            ..
+        } = &mut *self.kind
+        {
+            if let Some(position) = details.position {
+                *location = Some(SourceLocation::new(position, code));
+            }
+            if let Some(kind) = details.kind {
+                *diagnostic_kind = kind;
+            }
+        }
+        self
+    }
+
+    fn source_location(&self) -> Option<&SourceLocation> {
+        match &*self.kind {
+            ErrorData::RuleError { location, .. } => location.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The line where the error is located, when the error comes from a rule that
+    /// reported the node that caused it.
+    pub fn line(&self) -> Option<usize> {
+        self.source_location().map(|location| location.line)
+    }
+
+    /// The column (starting at 1) where the error is located, when the error comes from a
+    /// rule that reported the node that caused it.
+    pub fn column(&self) -> Option<usize> {
+        self.source_location().and_then(|location| location.column)
+    }
+
+    /// Converts the error into a [`Diagnostic`] that can be serialized for tools that need
+    /// a machine-readable description of the error.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let diagnostic = match &*self.kind {
+            ErrorData::Parser { path, error } => {
+                Diagnostic::new(DiagnosticKind::Parse, self.kind_message())
+                    .with_path(path)
+                    .with_span(
+                        error
+                            .line()
+                            .map(|line| DiagnosticSpan::new(line, error.column())),
+                    )
+            }
+            ErrorData::ResourceNotFound { path }
+            | ErrorData::InvalidUtf8 { path }
+            | ErrorData::IO { path, .. } => {
+                Diagnostic::new(DiagnosticKind::Io, self.kind_message()).with_path(path)
+            }
+            ErrorData::OsStringConversion { .. } => {
+                Diagnostic::new(DiagnosticKind::Io, self.kind_message())
+            }
+            ErrorData::InvalidConfiguration { path } => {
+                Diagnostic::new(DiagnosticKind::Configuration, self.kind_message()).with_path(path)
+            }
+            ErrorData::MultipleConfigurationFound { .. } | ErrorData::InvalidOptions { .. } => {
+                Diagnostic::new(DiagnosticKind::Configuration, self.kind_message())
+            }
+            ErrorData::CyclicConfigurationExtends { chain } => {
+                let diagnostic = Diagnostic::new(DiagnosticKind::Cycle, self.kind_message());
+                match chain.first() {
+                    Some(path) => diagnostic.with_path(path),
+                    None => diagnostic,
+                }
+            }
+            ErrorData::CyclicWork { .. } => {
+                Diagnostic::new(DiagnosticKind::Cycle, self.kind_message())
+            }
+            ErrorData::Timeout {
+                path, rule_name, ..
+            } => Diagnostic::new(DiagnosticKind::Timeout, self.kind_message())
+                .with_path(path)
+                .with_rule(rule_name.as_str()),
+            ErrorData::DepthLimitExceeded { path, .. } => {
+                Diagnostic::new(DiagnosticKind::DepthLimitExceeded, self.kind_message())
+                    .with_path(path)
+            }
+            ErrorData::UncachedWork { path } | ErrorData::RequiredWorkFailed { path, .. } => {
+                Diagnostic::new(DiagnosticKind::Other, self.kind_message()).with_path(path)
+            }
+            ErrorData::RuleError {
+                path,
+                rule_name,
+                error,
+                location,
+                diagnostic_kind,
+                ..
+            } => Diagnostic::new(*diagnostic_kind, error.as_str())
+                .with_path(path)
+                .with_span(
+                    location
+                        .as_ref()
+                        .map(|location| DiagnosticSpan::new(location.line, location.column)),
+                )
+                .with_rule(rule_name.as_str()),
+            ErrorData::Deserialization { .. } | ErrorData::Serialization { .. } => {
+                Diagnostic::new(DiagnosticKind::Data, self.kind_message())
+            }
+            ErrorData::InvalidResourcePath { .. } => {
+                Diagnostic::new(DiagnosticKind::RequireResolution, self.kind_message())
+            }
+            ErrorData::InvalidResourceExtension { location } => {
+                Diagnostic::new(DiagnosticKind::RequireResolution, self.kind_message())
+                    .with_path(location)
+            }
+            ErrorData::Custom { .. } => Diagnostic::new(DiagnosticKind::Other, self.kind_message()),
+        };
+
+        diagnostic.with_context(self.context.iter().map(ToString::to_string).collect())
+    }
+
+    /// Converts the error into one [`Diagnostic`] for each problem it contains. A parser
+    /// error that recovered multiple syntax errors produces one diagnostic per syntax
+    /// error, while every other error produces the same diagnostic as
+    /// [`to_diagnostic`](Self::to_diagnostic).
+    pub fn to_diagnostics(&self) -> Vec<Diagnostic> {
+        match &*self.kind {
+            ErrorData::Parser { path, error } if error.iter_syntax_errors().count() > 1 => error
+                .iter_syntax_errors()
+                .map(|syntax_error| {
+                    Diagnostic::new(
+                        DiagnosticKind::Parse,
+                        format!(
+                            "unable to parse `{}`: {}",
+                            path.display(),
+                            syntax_error.message()
+                        ),
+                    )
+                    .with_path(path)
+                    .with_span(Some(DiagnosticSpan::new(
+                        syntax_error.line(),
+                        Some(syntax_error.column()),
+                    )))
+                    .with_context(self.context.iter().map(ToString::to_string).collect())
+                })
+                .collect(),
+            _ => vec![self.to_diagnostic()],
+        }
+    }
+
+    /// The message of the error without its context.
+    fn kind_message(&self) -> String {
+        Self {
+            kind: self.kind.clone(),
+            context: Vec::new(),
+            source: None,
+        }
+        .to_string()
+    }
+
     pub(crate) fn cyclic_work(work_left: Vec<&WorkItem>) -> Self {
         let source_left: HashSet<PathBuf> = work_left
             .iter()
@@ -187,7 +522,7 @@ impl DarkluaError {
 
         required_work.sort_by_key(|(_, content)| content.len());
 
-        Self::new(ErrorKind::CyclicWork {
+        Self::new(ErrorData::CyclicWork {
             work: required_work,
         })
     }
@@ -196,26 +531,26 @@ impl DarkluaError {
         path: impl Into<String>,
         message: impl Into<String>,
     ) -> Self {
-        Self::new(ErrorKind::InvalidResourcePath {
+        Self::new(ErrorData::InvalidResourcePath {
             location: path.into(),
             message: message.into(),
         })
     }
 
     pub(crate) fn invalid_resource_extension(path: impl Into<PathBuf>) -> Self {
-        Self::new(ErrorKind::InvalidResourceExtension {
+        Self::new(ErrorData::InvalidResourceExtension {
             location: path.into(),
         })
     }
 
     pub(crate) fn os_string_conversion(os_string: impl Into<OsString>) -> Self {
-        Self::new(ErrorKind::OsStringConversion {
+        Self::new(ErrorData::OsStringConversion {
             os_string: os_string.into(),
         })
     }
 
     pub fn custom(message: impl Into<Cow<'static, str>>) -> Self {
-        Self::new(ErrorKind::Custom {
+        Self::new(ErrorData::Custom {
             message: message.into(),
         })
     }
@@ -225,6 +560,7 @@ impl From<ResourceError> for DarkluaError {
     fn from(err: ResourceError) -> Self {
         match err {
             ResourceError::NotFound(path) => DarkluaError::resource_not_found(path),
+            ResourceError::InvalidUtf8(path) => Self::new(ErrorData::InvalidUtf8 { path }),
             ResourceError::IO { path, error } => DarkluaError::io_error(path, error),
         }
     }
@@ -232,71 +568,84 @@ impl From<ResourceError> for DarkluaError {
 
 impl From<json5::Error> for DarkluaError {
     fn from(error: json5::Error) -> Self {
-        Self::new(ErrorKind::Deserialization {
+        Self::new(ErrorData::Deserialization {
             message: error.to_string(),
             data_type: "json",
         })
+        .with_source(Arc::new(error))
     }
 }
 
 impl From<serde_json::Error> for DarkluaError {
     fn from(error: serde_json::Error) -> Self {
-        Self::new(ErrorKind::Deserialization {
+        Self::new(ErrorData::Deserialization {
             message: error.to_string(),
             data_type: "json",
         })
+        .with_source(Arc::new(error))
     }
 }
 
 impl From<serde_yaml::Error> for DarkluaError {
     fn from(error: serde_yaml::Error) -> Self {
-        Self::new(ErrorKind::Deserialization {
+        Self::new(ErrorData::Deserialization {
             message: error.to_string(),
             data_type: "yaml",
         })
+        .with_source(Arc::new(error))
     }
 }
 
 impl From<toml::de::Error> for DarkluaError {
     fn from(error: toml::de::Error) -> Self {
-        Self::new(ErrorKind::Deserialization {
+        Self::new(ErrorData::Deserialization {
             message: error.to_string(),
             data_type: "toml",
         })
+        .with_source(Arc::new(error))
     }
 }
 
 impl From<toml::ser::Error> for DarkluaError {
     fn from(error: toml::ser::Error) -> Self {
-        Self::new(ErrorKind::Serialization {
+        Self::new(ErrorData::Serialization {
             message: error.to_string(),
             data_type: "toml",
         })
+        .with_source(Arc::new(error))
     }
 }
 
 impl From<LuaSerializerError> for DarkluaError {
     fn from(error: LuaSerializerError) -> Self {
-        Self::new(ErrorKind::Serialization {
+        Self::new(ErrorData::Serialization {
             message: error.to_string(),
             data_type: "lua",
         })
+        .with_source(Arc::new(error))
     }
 }
 
 impl Display for DarkluaError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &*self.kind {
-            ErrorKind::Parser { path, error } => {
+            ErrorData::Parser { path, error } => {
                 write!(f, "unable to parse `{}`: {}", path.display(), error)?;
             }
-            ErrorKind::ResourceNotFound { path } => {
+            ErrorData::ResourceNotFound { path } => {
                 write!(f, "unable to find `{}`", path.display())?;
             }
-            ErrorKind::InvalidConfiguration { path } => {
+            ErrorData::InvalidUtf8 { path } => {
+                write!(
+                    f,
+                    "unable to read `{}`: the file does not contain valid UTF-8",
+                    path.display()
+                )?;
+            }
+            ErrorData::InvalidConfiguration { path } => {
                 write!(f, "invalid configuration file at `{}`", path.display())?;
             }
-            ErrorKind::MultipleConfigurationFound { paths } => {
+            ErrorData::MultipleConfigurationFound { paths } => {
                 write!(
                     f,
                     "multiple default configuration file found: {}",
@@ -307,23 +656,78 @@ impl Display for DarkluaError {
                         .join(", ")
                 )?;
             }
-            ErrorKind::IO { path, error } => {
+            ErrorData::InvalidOptions { message } => {
+                write!(f, "invalid options: {}", message)?;
+            }
+            ErrorData::CyclicConfigurationExtends { chain } => {
+                write!(
+                    f,
+                    "cyclic configuration `extends` detected: {}",
+                    chain
+                        .iter()
+                        .map(|path| format!("`{}`", path.display()))
+                        .collect::<Vec<_>>()
+                        .join(" -> ")
+                )?;
+            }
+            ErrorData::IO { path, error } => {
                 write!(f, "IO error with `{}`: {}", path.display(), error)?;
             }
-            ErrorKind::UncachedWork { path } => {
+            ErrorData::UncachedWork { path } => {
                 write!(f, "attempt to obtain work at `{}`", path.display())?;
             }
-            ErrorKind::RuleError {
+            ErrorData::RequiredWorkFailed { path, required } => {
+                write!(
+                    f,
+                    "unable to process `{}` because it requires `{}`, which failed",
+                    path.display(),
+                    required.display()
+                )?;
+            }
+            ErrorData::Timeout {
+                path,
+                rule_name,
+                limit,
+            } => {
+                write!(
+                    f,
+                    "unable to process `{}`: the file exceeded its budget ({}) while applying rule `{}`",
+                    path.display(),
+                    limit,
+                    rule_name
+                )?;
+            }
+            ErrorData::DepthLimitExceeded { path, limit } => {
+                write!(
+                    f,
+                    "unable to process `{}`: the code is nested deeper than the limit of {} levels",
+                    path.display(),
+                    limit
+                )?;
+            }
+            ErrorData::RuleError {
                 path,
                 rule_name,
                 rule_number,
                 error,
+                location,
+                ..
             } => {
+                let path = match location {
+                    Some(SourceLocation {
+                        line,
+                        column: Some(column),
+                        ..
+                    }) => format!("{}:{}:{}", path.display(), line, column),
+                    Some(SourceLocation { line, .. }) => format!("{}:{}", path.display(), line),
+                    None => path.display().to_string(),
+                };
+
                 if let Some(rule_number) = rule_number {
                     write!(
                         f,
                         "error processing `{}` ({} [#{}]):{}{}",
-                        path.display(),
+                        path,
                         rule_name,
                         rule_number,
                         if error.contains('\n') { '\n' } else { ' ' },
@@ -333,14 +737,21 @@ impl Display for DarkluaError {
                     write!(
                         f,
                         "error processing `{}` ({}):{}{}",
-                        path.display(),
+                        path,
                         rule_name,
                         if error.contains('\n') { '\n' } else { ' ' },
                         error,
                     )?;
                 }
+
+                if let Some(code_frame) = location
+                    .as_ref()
+                    .and_then(|location| location.code_frame.as_ref())
+                {
+                    write!(f, "\n{}", code_frame)?;
+                }
             }
-            ErrorKind::CyclicWork { work } => {
+            ErrorData::CyclicWork { work } => {
                 const MAX_PRINTED_WORK: usize = 12;
                 const MAX_REQUIRED_PATH: usize = 20;
 
@@ -374,20 +785,20 @@ impl Display for DarkluaError {
                     }
                 )?;
             }
-            ErrorKind::Deserialization { message, data_type } => {
+            ErrorData::Deserialization { message, data_type } => {
                 write!(f, "unable to read {} data: {}", data_type, message)?;
             }
-            ErrorKind::Serialization { message, data_type } => {
+            ErrorData::Serialization { message, data_type } => {
                 write!(f, "unable to serialize {} data: {}", data_type, message)?;
             }
-            ErrorKind::InvalidResourcePath { location, message } => {
+            ErrorData::InvalidResourcePath { location, message } => {
                 write!(
                     f,
                     "unable to require resource at `{}`: {}",
                     location, message
                 )?;
             }
-            ErrorKind::InvalidResourceExtension { location } => {
+            ErrorData::InvalidResourceExtension { location } => {
                 if let Some(extension) = location.extension().map(OsStr::to_string_lossy) {
                     write!(
                         f,
@@ -403,22 +814,34 @@ impl Display for DarkluaError {
                     )?;
                 }
             }
-            ErrorKind::OsStringConversion { os_string } => {
+            ErrorData::OsStringConversion { os_string } => {
                 write!(
                     f,
                     "unable to convert operating system string (`{}`) into a utf-8 string",
                     os_string.to_string_lossy(),
                 )?;
             }
-            ErrorKind::Custom { message } => {
+            ErrorData::Custom { message } => {
                 write!(f, "{}", message)?;
             }
         };
 
-        if let Some(context) = &self.context {
+        if let Some(context) = self.context.last() {
             write!(f, " ({})", context)?;
         }
 
         Ok(())
     }
 }
+
+impl Error for DarkluaError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &*self.kind {
+            ErrorData::Parser { error, .. } => Some(error),
+            _ => self
+                .source
+                .as_deref()
+                .map(|source| source as &(dyn Error + 'static)),
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use std::path::{Path, PathBuf};
+
+use wax::{Glob, Pattern};
+
+use crate::utils::normalize_path;
+
+use super::{DarkluaError, DarkluaResult, Resources};
+
+pub(crate) const DARKLUA_IGNORE_FILE_NAME: &str = ".darkluaignore";
+pub(crate) const GIT_IGNORE_FILE_NAME: &str = ".gitignore";
+
+/// Selects the files found under an input directory, using patterns matched against the
+/// path of each file relative to that directory.
+///
+/// Patterns follow the gitignore conventions: a pattern without a `/` matches a file name
+/// at any depth, a leading `/` anchors a pattern to the input directory, a trailing `/`
+/// matches everything inside a directory and `**` matches any number of directories.
+/// Exclusions are applied in order and the last matching pattern wins, so a pattern
+/// starting with `!` includes again files excluded by a previous pattern.
+#[derive(Debug, Default)]
+pub(crate) struct FileFilter {
+    includes: Vec<Glob<'static>>,
+    excludes: Vec<(Glob<'static>, bool)>,
+}
+
+impl FileFilter {
+    pub(crate) fn new<'a>(
+        includes: impl Iterator<Item = &'a str>,
+        excludes: impl Iterator<Item = &'a str>,
+    ) -> DarkluaResult<Self> {
+        Ok(Self {
+            includes: includes
+                .map(|pattern| build_glob(pattern).map(|(glob, _)| glob))
+                .collect::<DarkluaResult<_>>()?,
+            excludes: excludes
+                .map(|pattern| {
+                    let (glob, negated) = build_glob(pattern)?;
+                    Ok((glob, !negated))
+                })
+                .collect::<DarkluaResult<_>>()?,
+        })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.includes.is_empty() && self.excludes.is_empty()
+    }
+
+    /// Returns `true` if the given path (relative to the input directory) matches one of
+    /// the include patterns. Those files are processed even if an ignore file ignores them.
+    pub(crate) fn is_explicitly_included(&self, relative_path: &Path) -> bool {
+        self.includes
+            .iter()
+            .any(|glob| glob.is_match(relative_path))
+    }
+
+    /// Returns `true` if a file at the given path (relative to the input directory) should
+    /// be processed.
+    pub(crate) fn accepts(&self, relative_path: &Path) -> bool {
+        if !self.includes.is_empty()
+            && !self
+                .includes
+                .iter()
+                .any(|glob| glob.is_match(relative_path))
+        {
+            return false;
+        }
+
+        self.excludes
+            .iter()
+            .rev()
+            .find(|(glob, _)| glob.is_match(relative_path))
+            .map(|(_, excluded)| !excluded)
+            .unwrap_or(true)
+    }
+}
+
+/// The patterns of the ignore files found at the root of a project (`.darkluaignore`, and
+/// `.gitignore` when enabled), matched against paths relative to that root.
+///
+/// Patterns use the same conventions as [`FileFilter`]. Like in `.gitignore` files, a
+/// pattern that does not end with a `/` also matches everything inside a directory with
+/// that name, lines starting with `#` are comments and a leading `\` escapes a `#` or a
+/// `!` at the start of a pattern.
+#[derive(Debug, Default)]
+pub(crate) struct IgnoreFiles {
+    root: PathBuf,
+    patterns: Vec<(Glob<'static>, bool)>,
+}
+
+impl IgnoreFiles {
+    /// Reads the ignore files found in the given directory. The `.darkluaignore` patterns
+    /// are applied after the `.gitignore` patterns, so they can include again files
+    /// ignored by git.
+    pub(crate) fn read(
+        resources: &Resources,
+        root: impl AsRef<Path>,
+        respect_gitignore: bool,
+    ) -> DarkluaResult<Self> {
+        let root = normalize_path(root);
+        let mut ignore_files = Self {
+            root: root.clone(),
+            patterns: Vec::new(),
+        };
+
+        let file_names = if respect_gitignore {
+            vec![GIT_IGNORE_FILE_NAME, DARKLUA_IGNORE_FILE_NAME]
+        } else {
+            vec![DARKLUA_IGNORE_FILE_NAME]
+        };
+
+        for file_name in file_names {
+            let path = root.join(file_name);
+            if !resources.is_file(&path)? {
+                continue;
+            }
+
+            log::debug!("using ignore file `{}`", path.display());
+
+            let content = resources.get(&path)?;
+            ignore_files
+                .add_patterns(&content)
+                .map_err(|err| err.context(format!("in ignore file `{}`", path.display())))?;
+        }
+
+        Ok(ignore_files)
+    }
+
+    fn add_patterns(&mut self, content: &str) -> DarkluaResult<()> {
+        for (pattern, negated) in content.lines().filter_map(parse_ignore_line) {
+            self.patterns
+                .push((build_pattern_glob(pattern, pattern)?, !negated));
+
+            if !pattern.ends_with('/') && !pattern.ends_with("**") {
+                let directory_pattern = format!("{}/", pattern);
+                self.patterns
+                    .push((build_pattern_glob(pattern, &directory_pattern)?, !negated));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Returns `true` if the last pattern matching the given path ignores it.
+    pub(crate) fn is_ignored(&self, path: &Path) -> bool {
+        let path = normalize_path(path);
+        let path = path.strip_prefix(&self.root).unwrap_or(&path);
+
+        self.patterns
+            .iter()
+            .rev()
+            .find(|(glob, _)| glob.is_match(path))
+            .map(|(_, ignored)| *ignored)
+            .unwrap_or(false)
+    }
+}
+
+/// Extracts the pattern of a line from an ignore file and tells if the pattern is negated.
+fn parse_ignore_line(line: &str) -> Option<(&str, bool)> {
+    let line = line.trim_end();
+
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (negated, pattern) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (
+            false,
+            line.strip_prefix('\\')
+                .filter(|rest| rest.starts_with('#') || rest.starts_with('!'))
+                .unwrap_or(line),
+        ),
+    };
+
+    if pattern.is_empty() {
+        None
+    } else {
+        Some((pattern, negated))
+    }
+}
+
+/// Converts a gitignore-style pattern into a glob and tells if the pattern was negated.
+fn build_glob(pattern: &str) -> DarkluaResult<(Glob<'static>, bool)> {
+    match pattern.strip_prefix('!') {
+        Some(rest) => Ok((build_pattern_glob(pattern, rest)?, true)),
+        None => Ok((build_pattern_glob(pattern, pattern)?, false)),
+    }
+}
+
+/// Converts a gitignore-style pattern (without its negation) into a glob. The original
+/// pattern is only used in error messages.
+fn build_pattern_glob(pattern: &str, glob_pattern: &str) -> DarkluaResult<Glob<'static>> {
+    let (anchored, glob_pattern) = match glob_pattern.strip_prefix('/') {
+        Some(rest) => (true, rest),
+        None => (false, glob_pattern),
+    };
+
+    let mut glob_pattern = glob_pattern.to_owned();
+    if glob_pattern.ends_with('/') {
+        glob_pattern.push_str("**");
+    }
+    if !anchored && !glob_pattern.trim_end_matches("/**").contains('/') {
+        glob_pattern.insert_str(0, "**/");
+    }
+
+    Glob::new(&glob_pattern)
+        .map(Glob::into_owned)
+        .map_err(|err| DarkluaError::custom(format!("invalid file pattern `{}`: {}", pattern, err)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn filter(includes: &[&str], excludes: &[&str]) -> FileFilter {
+        FileFilter::new(includes.iter().copied(), excludes.iter().copied()).unwrap()
+    }
+
+    #[test]
+    fn empty_filter_accepts_everything() {
+        let filter = filter(&[], &[]);
+
+        assert!(filter.is_empty());
+        assert!(filter.accepts(Path::new("src/main.lua")));
+    }
+
+    #[test]
+    fn exclude_nested_directory() {
+        let filter = filter(&[], &["**/node_modules/**"]);
+
+        assert!(!filter.accepts(Path::new("node_modules/lib/init.lua")));
+        assert!(!filter.accepts(Path::new("src/node_modules/lib/init.lua")));
+        assert!(filter.accepts(Path::new("src/main.lua")));
+    }
+
+    #[test]
+    fn exclude_file_name_at_any_depth() {
+        let filter = filter(&[], &["*.spec.luau"]);
+
+        assert!(!filter.accepts(Path::new("main.spec.luau")));
+        assert!(!filter.accepts(Path::new("src/utils/main.spec.luau")));
+        assert!(filter.accepts(Path::new("src/main.luau")));
+    }
+
+    #[test]
+    fn exclude_anchored_directory() {
+        let filter = filter(&[], &["/generated/"]);
+
+        assert!(!filter.accepts(Path::new("generated/types.lua")));
+        assert!(filter.accepts(Path::new("src/generated/types.lua")));
+    }
+
+    #[test]
+    fn later_pattern_includes_excluded_file() {
+        let filter = filter(&[], &["generated/", "!generated/keep.lua"]);
+
+        assert!(!filter.accepts(Path::new("generated/types.lua")));
+        assert!(filter.accepts(Path::new("generated/keep.lua")));
+    }
+
+    #[test]
+    fn later_pattern_excludes_included_file() {
+        let filter = filter(&[], &["!generated/keep.lua", "generated/"]);
+
+        assert!(!filter.accepts(Path::new("generated/keep.lua")));
+    }
+
+    #[test]
+    fn include_only_matching_files() {
+        let filter = filter(&["src/**/*.luau"], &[]);
+
+        assert!(filter.accepts(Path::new("src/main.luau")));
+        assert!(filter.accepts(Path::new("src/utils/init.luau")));
+        assert!(!filter.accepts(Path::new("src/main.lua")));
+        assert!(!filter.accepts(Path::new("scripts/build.luau")));
+    }
+
+    #[test]
+    fn invalid_pattern_is_an_error() {
+        let error = FileFilter::new(std::iter::empty(), vec!["src/{a"].into_iter()).unwrap_err();
+
+        assert!(error
+            .to_string()
+            .starts_with("invalid file pattern `src/{a`"));
+    }
+
+    fn ignore_files(content: &str) -> IgnoreFiles {
+        let mut ignore_files = IgnoreFiles::default();
+        ignore_files.add_patterns(content).unwrap();
+        ignore_files
+    }
+
+    #[test]
+    fn ignore_pattern_without_slash_matches_directory() {
+        let ignore_files = ignore_files("Packages");
+
+        assert!(ignore_files.is_ignored(Path::new("Packages/_Index/lib/init.lua")));
+        assert!(ignore_files.is_ignored(Path::new("src/Packages/init.lua")));
+        assert!(!ignore_files.is_ignored(Path::new("src/main.lua")));
+    }
+
+    #[test]
+    fn ignore_directory_only_pattern() {
+        let ignore_files = ignore_files("build/");
+
+        assert!(ignore_files.is_ignored(Path::new("build/main.lua")));
+        assert!(!ignore_files.is_ignored(Path::new("src/build.lua")));
+    }
+
+    #[test]
+    fn ignore_anchored_pattern() {
+        let ignore_files = ignore_files("/out");
+
+        assert!(ignore_files.is_ignored(Path::new("out/main.lua")));
+        assert!(!ignore_files.is_ignored(Path::new("src/out/main.lua")));
+    }
+
+    #[test]
+    fn ignore_negated_pattern_includes_file_again() {
+        let ignore_files = ignore_files("build/\n!build/keep.lua");
+
+        assert!(ignore_files.is_ignored(Path::new("build/main.lua")));
+        assert!(!ignore_files.is_ignored(Path::new("build/keep.lua")));
+    }
+
+    #[test]
+    fn ignore_comments_and_empty_lines() {
+        let ignore_files = ignore_files("# generated files\n\n  \n");
+
+        assert!(ignore_files.is_empty());
+    }
+
+    #[test]
+    fn ignore_escaped_pattern() {
+        let ignore_files = ignore_files("\\#main.lua");
+
+        assert!(ignore_files.is_ignored(Path::new("src/#main.lua")));
+        assert!(!ignore_files.is_ignored(Path::new("src/main.lua")));
+    }
+
+    #[test]
+    fn ignore_paths_relative_to_root() {
+        let mut ignore_files = ignore_files("/out");
+        ignore_files.root = PathBuf::from("project");
+
+        assert!(ignore_files.is_ignored(Path::new("project/out/main.lua")));
+        assert!(!ignore_files.is_ignored(Path::new("project/src/main.lua")));
+    }
+}
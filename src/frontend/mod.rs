@@ -1,29 +1,61 @@
+mod bundle_manifest;
 mod configuration;
+mod configuration_extends;
+mod configuration_layer;
+mod darklua;
+mod diagnostic;
 mod error;
+mod file_filter;
+mod module_graph;
 mod options;
+mod process_cache;
+mod process_report;
 mod resources;
+mod rule_directives;
 mod utils;
 mod work_cache;
 mod work_item;
 mod worker;
 mod worker_tree;
 
-pub use configuration::{BundleConfiguration, Configuration, GeneratorParameters};
-pub use error::{DarkluaError, DarkluaResult};
-pub use options::Options;
-pub use resources::Resources;
+pub(crate) use bundle_manifest::BundleManifestBuilder;
+pub use bundle_manifest::{BundleManifest, BundledModule, BundledModuleStatus};
+pub use configuration::{
+    BundleConfiguration, Configuration, ConfigurationOverride, GeneratorParameters, LuaTarget,
+};
+pub use configuration_layer::ConfigurationLayer;
+pub use darklua::Darklua;
+pub use diagnostic::{Diagnostic, DiagnosticKind, DiagnosticSpan};
+pub use error::{DarkluaError, DarkluaResult, ErrorKind};
+pub use module_graph::{ModuleEdge, ModuleGraph, ModuleNode, RequireLocation};
+pub use options::{ErrorMode, FileBudget, Options};
+pub(crate) use process_report::count_modifications;
+pub use process_report::{
+    AppliedRule, FileReport, FileStatus, ProcessReport, RuleDuration, RuleNoteValue, RuleTiming,
+};
+#[cfg(feature = "fs")]
+pub use resources::FileSystemBackend;
+pub use resources::{MemoryBackend, ResourceBackend, ResourceError, Resources};
 use serde::Serialize;
 use work_item::WorkItem;
 use worker::Worker;
 pub use worker_tree::WorkerTree;
 
+use std::path::Path;
+
 use crate::{
     generator::{DenseLuaGenerator, LuaGenerator},
     nodes::{Block, ReturnStatement},
     process::to_expression,
-    utils::normalize_path,
+    rules::{ContextBuilder, Rule},
+    utils::{
+        estimate_code_depth, exceeds_depth_limit, normalize_path, run_with_stack_size,
+        DEFAULT_DEPTH_LIMIT, DEFAULT_STACK_SIZE,
+    },
 };
 
+const DEFAULT_VIRTUAL_PATH: &str = "file.lua";
+
 /// Convert serializable data into a Lua module
 pub fn convert_data(value: impl Serialize) -> Result<String, DarkluaError> {
     let expression = to_expression(&value).map_err(DarkluaError::from)?;
@@ -36,7 +68,11 @@ pub fn convert_data(value: impl Serialize) -> Result<String, DarkluaError> {
     Ok(generator.into_string())
 }
 
-pub fn process(resources: &Resources, options: Options) -> DarkluaResult<WorkerTree> {
+pub fn process(resources: &Resources, mut options: Options) -> DarkluaResult<WorkerTree> {
+    if let Some(code) = options.take_input_code() {
+        return process_input_code(resources, options, code);
+    }
+
     let mut worker_tree = WorkerTree::default();
 
     worker_tree.collect_work(resources, &options)?;
@@ -44,3 +80,132 @@ pub fn process(resources: &Resources, options: Options) -> DarkluaResult<WorkerT
 
     Ok(worker_tree)
 }
+
+/// Processes the code given with [`Options::from_code`]. The code is placed on top of the
+/// resources with an overlay, so that writes are kept in memory.
+fn process_input_code(
+    resources: &Resources,
+    options: Options,
+    code: String,
+) -> DarkluaResult<WorkerTree> {
+    let input = normalize_path(options.input());
+    let resources = Resources::overlay(resources.clone(), vec![(input.clone(), code)]);
+
+    let mut worker_tree = WorkerTree::default();
+
+    worker_tree.collect_work(&resources, &options)?;
+    worker_tree.process(&resources, options)?;
+    worker_tree.read_output_code(&resources, &input);
+
+    Ok(worker_tree)
+}
+
+/// Processes a single piece of code with the given configuration and returns the generated
+/// code. Everything happens in memory: this is the recommended entry point for playgrounds,
+/// editor plugins or any tool that transforms code that does not come from a file.
+///
+/// The code is processed as if it came from a file named `file.lua`. Use
+/// [`process_code_at`] to provide a different path to rules that depend on it. Rules that
+/// read other files (like the bundler) only have access to an empty set of resources, so
+/// requires can't be resolved.
+///
+/// Code nested deeper than 30,000 levels fails with
+/// [`ErrorKind::DepthLimitExceeded`] instead of overflowing the stack.
+///
+/// ```
+/// # use darklua_core::{process_code, Configuration, GeneratorParameters};
+/// let configuration = Configuration::empty().with_generator(GeneratorParameters::default_dense());
+///
+/// let code = process_code("return    true", &configuration).unwrap();
+///
+/// assert_eq!(code, "return true");
+/// ```
+pub fn process_code(code: &str, configuration: &Configuration) -> DarkluaResult<String> {
+    process_code_at(code, DEFAULT_VIRTUAL_PATH, configuration)
+}
+
+/// Processes a single piece of code like [`process_code`], as if it came from a file at
+/// the given path.
+pub fn process_code_at(
+    code: &str,
+    path: impl AsRef<Path>,
+    configuration: &Configuration,
+) -> DarkluaResult<String> {
+    let path = path.as_ref();
+    let stack_size = if cfg!(target_arch = "wasm32") {
+        None
+    } else {
+        Some(DEFAULT_STACK_SIZE)
+    };
+
+    run_with_stack_size(stack_size, || {
+        process_code_on_current_thread(code, path, configuration)
+    })
+}
+
+fn process_code_on_current_thread(
+    code: &str,
+    path: &Path,
+    configuration: &Configuration,
+) -> DarkluaResult<String> {
+    let path = normalize_path(path);
+    let resources = Resources::from_memory();
+
+    let create_context = || {
+        let builder = ContextBuilder::new(&path, &resources, code);
+        if let Some(project_location) = configuration.location() {
+            builder.with_project_location(project_location)
+        } else {
+            builder
+        }
+    };
+
+    let mut parser = configuration.build_parser();
+    if rule_directives::may_contain_directives(code) {
+        parser = parser.preserve_tokens();
+    }
+
+    if estimate_code_depth(code) > DEFAULT_DEPTH_LIMIT {
+        return Err(DarkluaError::depth_limit_exceeded(
+            &path,
+            DEFAULT_DEPTH_LIMIT,
+        ));
+    }
+
+    let mut block = parser
+        .parse(code)
+        .map_err(|parser_error| DarkluaError::parser_error(&path, parser_error))?;
+
+    let directives = rule_directives::RuleDirectives::read(&mut block, code, &path);
+
+    if let Some(bundler) = configuration.bundle() {
+        let context = create_context().build();
+        bundler
+            .process(&mut block, &context)
+            .map_err(|rule_error| DarkluaError::orphan_rule_error(&path, &bundler, rule_error))?;
+    }
+
+    if exceeds_depth_limit(&mut block, DEFAULT_DEPTH_LIMIT) {
+        return Err(DarkluaError::depth_limit_exceeded(
+            &path,
+            DEFAULT_DEPTH_LIMIT,
+        ));
+    }
+
+    for (index, rule) in configuration.rules_for(&path).into_iter().enumerate() {
+        let context = create_context().build();
+        let disabled_statements = directives.disable_statements(rule.get_name(), &mut block);
+        let result = rule.process(&mut block, &context);
+        disabled_statements.restore(&mut block);
+        result.map_err(|rule_error| DarkluaError::rule_error(&path, rule, index, rule_error))?;
+    }
+
+    if exceeds_depth_limit(&mut block, DEFAULT_DEPTH_LIMIT) {
+        return Err(DarkluaError::depth_limit_exceeded(
+            &path,
+            DEFAULT_DEPTH_LIMIT,
+        ));
+    }
+
+    Ok(configuration.generate_lua(&block, code))
+}
@@ -0,0 +1,125 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::nodes::SourcePosition;
+use crate::utils::normalize_path;
+
+/// The require calls found while processing files, as a list of edges from the requiring
+/// file to the required module. The graph is only collected when enabled with
+/// [`Options::collect_module_graph`](crate::Options::collect_module_graph).
+///
+/// Requires are found by the `bundle` rule and the `convert_require` rule. The files
+/// restored from the process cache are not processed, so their requires are missing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ModuleGraph {
+    edges: Vec<ModuleEdge>,
+}
+
+impl ModuleGraph {
+    pub(crate) fn new(mut edges: Vec<ModuleEdge>) -> Self {
+        edges.sort();
+        edges.dedup();
+        Self { edges }
+    }
+
+    /// Iterates over the edges, sorted by the requiring file and by the required module.
+    pub fn iter_edges(&self) -> impl Iterator<Item = &ModuleEdge> {
+        self.edges.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+}
+
+/// A require call from a file to a module.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ModuleEdge {
+    source: PathBuf,
+    target: ModuleNode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<RequireLocation>,
+}
+
+impl ModuleEdge {
+    pub(crate) fn new(
+        source: impl AsRef<Path>,
+        target: ModuleNode,
+        location: Option<RequireLocation>,
+    ) -> Self {
+        Self {
+            source: normalize_path(source),
+            target,
+            location,
+        }
+    }
+
+    /// The file that contains the require call.
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    pub fn target(&self) -> &ModuleNode {
+        &self.target
+    }
+
+    /// The location of the require call in the source file. It is only available when
+    /// the code is parsed with its tokens (like with the `retain_lines` generator).
+    pub fn location(&self) -> Option<&RequireLocation> {
+        self.location.as_ref()
+    }
+}
+
+/// The module required by a require call.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ModuleNode {
+    /// A file that was found.
+    File { path: PathBuf },
+    /// A require that could not be resolved, with the string passed to the require call.
+    Unresolved { require: String },
+}
+
+impl ModuleNode {
+    pub(crate) fn file(path: impl AsRef<Path>) -> Self {
+        Self::File {
+            path: normalize_path(path),
+        }
+    }
+
+    pub(crate) fn unresolved(require: impl Into<String>) -> Self {
+        Self::Unresolved {
+            require: require.into(),
+        }
+    }
+}
+
+/// The line and the column (both starting at 1) of a require call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct RequireLocation {
+    line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+}
+
+impl RequireLocation {
+    pub(crate) fn new(position: SourcePosition, code: &str) -> Self {
+        Self {
+            line: position.line(),
+            column: position.column(code),
+        }
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+}
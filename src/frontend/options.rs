@@ -1,6 +1,75 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
 
 use super::configuration::{Configuration, GeneratorParameters};
+use super::configuration_layer::ConfigurationLayer;
+use super::resources::DEFAULT_MAX_WALK_DEPTH;
+use crate::utils::{DEFAULT_DEPTH_LIMIT, DEFAULT_STACK_SIZE};
+
+/// How [`process`](crate::process) handles the files that fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorMode {
+    /// Stops processing at the first file that fails.
+    FailFast,
+    /// Processes every file and collects all the errors. The outputs of the files that
+    /// succeeded are still written.
+    Continue,
+}
+
+impl Default for ErrorMode {
+    fn default() -> Self {
+        Self::Continue
+    }
+}
+
+/// Limits the work done on each file. Once a file exceeds its budget, the rule that was
+/// running stops visiting the file and the file fails with an error of kind
+/// [`ErrorKind::Timeout`](crate::ErrorKind::Timeout). The other files are still processed,
+/// unless [`ErrorMode::FailFast`] is used.
+///
+/// Steps are counted by the visitors before each statement, so rules that do not use
+/// the visitors are only stopped after they return.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileBudget {
+    timeout: Option<Duration>,
+    steps: Option<u64>,
+}
+
+impl FileBudget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the time spent applying rules on a file.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Limits the number of statements visited by all the rules applied on a file.
+    pub fn with_steps(mut self, steps: u64) -> Self {
+        self.steps = Some(steps);
+        self
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub fn steps(&self) -> Option<u64> {
+        self.steps
+    }
+
+    pub(crate) fn is_exceeded(&self, duration: Duration, steps: u64) -> bool {
+        self.timeout.map_or(false, |timeout| duration > timeout)
+            || self.steps.map_or(false, |limit| steps > limit)
+    }
+}
 
 #[derive(Debug)]
 pub struct Options {
@@ -8,8 +77,26 @@ pub struct Options {
     config_path: Option<PathBuf>,
     config: Option<Configuration>,
     config_generator_override: Option<GeneratorParameters>,
+    config_layers: Vec<ConfigurationLayer>,
     output: Option<PathBuf>,
-    fail_fast: bool,
+    error_mode: ErrorMode,
+    threads: usize,
+    stack_size: usize,
+    depth_limit: usize,
+    cache_directory: Option<PathBuf>,
+    includes: Vec<String>,
+    excludes: Vec<String>,
+    max_directory_depth: usize,
+    respect_gitignore: bool,
+    output_extensions: BTreeMap<String, String>,
+    measure_rule_timings: bool,
+    collect_module_graph: bool,
+    collect_applied_rules: bool,
+    collect_bundle_manifests: bool,
+    always_write_outputs: bool,
+    recover_syntax_errors: bool,
+    file_budget: Option<FileBudget>,
+    input_code: Option<String>,
 }
 
 impl Options {
@@ -19,11 +106,41 @@ impl Options {
             config_path: None,
             config: None,
             output: None,
-            fail_fast: false,
+            error_mode: ErrorMode::default(),
             config_generator_override: None,
+            config_layers: Vec::new(),
+            threads: 1,
+            stack_size: DEFAULT_STACK_SIZE,
+            depth_limit: DEFAULT_DEPTH_LIMIT,
+            cache_directory: None,
+            includes: Vec::new(),
+            excludes: Vec::new(),
+            max_directory_depth: DEFAULT_MAX_WALK_DEPTH,
+            respect_gitignore: false,
+            output_extensions: BTreeMap::new(),
+            measure_rule_timings: false,
+            collect_module_graph: false,
+            collect_applied_rules: false,
+            collect_bundle_manifests: false,
+            always_write_outputs: false,
+            recover_syntax_errors: false,
+            file_budget: None,
+            input_code: None,
         }
     }
 
+    /// Processes the given code as the only input, as if it was the content of a file at
+    /// the given virtual path. Other files (like required modules or configuration files)
+    /// are still read from the resources, so requires are resolved from the virtual path.
+    ///
+    /// Nothing is written to the resources: the generated code is obtained with
+    /// [`WorkerTree::output_code`](crate::WorkerTree::output_code).
+    pub fn from_code(virtual_path: impl Into<PathBuf>, code: impl Into<String>) -> Self {
+        let mut options = Self::new(virtual_path);
+        options.input_code = Some(code.into());
+        options
+    }
+
     pub fn with_configuration_at(mut self, config: impl Into<PathBuf>) -> Self {
         self.config_path = Some(config.into());
         self
@@ -39,8 +156,173 @@ impl Options {
         self
     }
 
-    pub fn fail_fast(mut self) -> Self {
-        self.fail_fast = true;
+    /// Stops processing at the first file that fails (see [`ErrorMode::FailFast`]).
+    pub fn fail_fast(self) -> Self {
+        self.with_error_mode(ErrorMode::FailFast)
+    }
+
+    /// Sets how files that fail are handled. By default, every file is processed and all
+    /// errors are collected (see [`ErrorMode::Continue`]).
+    pub fn with_error_mode(mut self, error_mode: ErrorMode) -> Self {
+        self.error_mode = error_mode;
+        self
+    }
+
+    /// Processes files in parallel using the given number of threads. When `threads` is zero,
+    /// the number of threads is the available parallelism of the machine. The results are
+    /// identical to processing files one by one.
+    ///
+    /// Files are processed serially when a bundle configuration is used.
+    pub fn parallel(mut self, threads: usize) -> Self {
+        self.threads = if threads == 0 {
+            thread::available_parallelism()
+                .map(NonZeroUsize::get)
+                .unwrap_or(1)
+        } else {
+            threads
+        };
+        self
+    }
+
+    /// Sets the size in bytes of the stack of the threads that process files. The parser,
+    /// the rules and the generators are recursive, so the stack limits how deeply code
+    /// can be nested before darklua overflows its stack. The default size (256 MiB) fits
+    /// tens of thousands of nested expressions or blocks. When the size is zero, files are
+    /// processed on the calling thread.
+    pub fn with_stack_size(mut self, size: usize) -> Self {
+        self.stack_size = size;
+        self
+    }
+
+    /// Sets how deeply the blocks and expressions of a file can be nested. Files nested
+    /// deeper fail with [`ErrorKind::DepthLimitExceeded`](crate::ErrorKind::DepthLimitExceeded)
+    /// before they are parsed, transformed or generated, instead of overflowing the stack.
+    /// The default limit (30,000 levels) fits the default stack size, so a larger limit
+    /// should come with a larger stack (see [`with_stack_size`](Self::with_stack_size)).
+    /// When the limit is zero, the depth of the files is not checked.
+    pub fn with_depth_limit(mut self, limit: usize) -> Self {
+        self.depth_limit = limit;
+        self
+    }
+
+    /// Stores the output of each processed file in the given directory. On the next runs,
+    /// files are not processed again if their content, the content of the files they depend
+    /// on (like bundled modules), the configuration and the version of darklua did not
+    /// change. The cached output is written directly instead.
+    ///
+    /// Files processed in place are never cached.
+    pub fn with_cache_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.cache_directory = Some(directory.into());
+        self
+    }
+
+    /// When the input is a directory, only processes the files that match one of the given
+    /// patterns. Patterns are matched against the path of each file relative to the input
+    /// directory, using the same conventions as `.gitignore` files.
+    pub fn with_includes<I: IntoIterator<Item = S>, S: Into<String>>(mut self, globs: I) -> Self {
+        self.includes.extend(globs.into_iter().map(Into::into));
+        self
+    }
+
+    /// When the input is a directory, skips the files that match the given patterns.
+    /// Patterns follow the conventions of `.gitignore` files: the last matching pattern
+    /// wins and a pattern starting with `!` includes again the files excluded by a previous
+    /// pattern.
+    ///
+    /// Excluded files are not processed, but they can still be required by other files.
+    /// Files are also excluded by the `.darkluaignore` file found next to the
+    /// configuration file (or in the current directory), unless they match one of the
+    /// patterns given to [`Options::with_includes`].
+    pub fn with_excludes<I: IntoIterator<Item = S>, S: Into<String>>(mut self, globs: I) -> Self {
+        self.excludes.extend(globs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Also skips the files ignored by the `.gitignore` file found next to the
+    /// `.darkluaignore` file. Patterns of the `.darkluaignore` file are applied after the
+    /// `.gitignore` patterns.
+    pub fn respect_gitignore(mut self) -> Self {
+        self.respect_gitignore = true;
+        self
+    }
+
+    /// When the input is a directory, does not look for files in directories nested
+    /// deeper than the given depth below it (64 by default). Directories skipped because
+    /// of this limit are logged as warnings.
+    pub fn with_max_directory_depth(mut self, depth: usize) -> Self {
+        self.max_directory_depth = depth;
+        self
+    }
+
+    /// Writes the files with the given input extension (like `luau`) using another extension
+    /// (like `lua`). Require calls to paths with the input extension are updated to use the
+    /// output extension, so that they keep pointing to the renamed files.
+    ///
+    /// It applies to the output paths computed from an output directory: it has no effect
+    /// when files are processed in place or when the output is a file.
+    pub fn with_output_extension(
+        mut self,
+        input_extension: impl AsRef<str>,
+        output_extension: impl AsRef<str>,
+    ) -> Self {
+        self.output_extensions.insert(
+            input_extension.as_ref().trim_start_matches('.').to_owned(),
+            output_extension.as_ref().trim_start_matches('.').to_owned(),
+        );
+        self
+    }
+
+    /// Measures the time spent by each rule on each file. The durations are available in
+    /// the [`ProcessReport`](crate::ProcessReport), with the total of each rule across all
+    /// files.
+    pub fn measure_rule_timings(mut self) -> Self {
+        self.measure_rule_timings = true;
+        self
+    }
+
+    /// Collects the require calls found while bundling and while converting requires into
+    /// a [`ModuleGraph`](crate::ModuleGraph), available in the
+    /// [`ProcessReport`](crate::ProcessReport).
+    pub fn collect_module_graph(mut self) -> Self {
+        self.collect_module_graph = true;
+        self
+    }
+
+    /// Records which rules modified each file, with the number of modifications and the
+    /// notes attached by some rules, in the [`FileReport`](crate::FileReport) of each file.
+    pub fn collect_applied_rules(mut self) -> Self {
+        self.collect_applied_rules = true;
+        self
+    }
+
+    /// Records the modules inlined in each bundle into a
+    /// [`BundleManifest`](crate::BundleManifest), available in the
+    /// [`FileReport`](crate::FileReport) of the bundled file.
+    pub fn collect_bundle_manifests(mut self) -> Self {
+        self.collect_bundle_manifests = true;
+        self
+    }
+
+    /// Writes every output file, even when it already contains the generated code. By
+    /// default, those files are not written again so that their modification time does
+    /// not change, and they are reported as [`FileStatus::Unchanged`](crate::FileStatus::Unchanged).
+    pub fn always_write_outputs(mut self) -> Self {
+        self.always_write_outputs = true;
+        self
+    }
+
+    /// Reports up to 20 syntax errors of a file that can not be parsed, instead of only the
+    /// first one (see [`Parser::recover_errors`](crate::Parser::recover_errors)).
+    /// Each syntax error is a separate [`Diagnostic`](crate::Diagnostic) in
+    /// [`WorkerTree::diagnostics`](crate::WorkerTree::diagnostics).
+    pub fn recover_syntax_errors(mut self) -> Self {
+        self.recover_syntax_errors = true;
+        self
+    }
+
+    /// Abandons the files that exceed the given budget (see [`FileBudget`]).
+    pub fn with_file_budget(mut self, budget: FileBudget) -> Self {
+        self.file_budget = Some(budget);
         self
     }
 
@@ -49,6 +331,14 @@ impl Options {
         self
     }
 
+    /// Applies the changes of a layer on top of the configuration, after it is loaded from
+    /// its file. Layers are applied in the order they are added, before the generator
+    /// override.
+    pub fn with_configuration_layer(mut self, layer: ConfigurationLayer) -> Self {
+        self.config_layers.push(layer);
+        self
+    }
+
     pub fn input(&self) -> &Path {
         &self.input
     }
@@ -57,8 +347,105 @@ impl Options {
         self.output.as_ref().map(AsRef::as_ref)
     }
 
+    pub fn should_measure_rule_timings(&self) -> bool {
+        self.measure_rule_timings
+    }
+
+    pub fn should_collect_module_graph(&self) -> bool {
+        self.collect_module_graph
+    }
+
+    pub fn should_collect_applied_rules(&self) -> bool {
+        self.collect_applied_rules
+    }
+
+    pub fn should_collect_bundle_manifests(&self) -> bool {
+        self.collect_bundle_manifests
+    }
+
+    pub fn should_always_write_outputs(&self) -> bool {
+        self.always_write_outputs
+    }
+
+    pub fn should_recover_syntax_errors(&self) -> bool {
+        self.recover_syntax_errors
+    }
+
+    pub fn file_budget(&self) -> Option<&FileBudget> {
+        self.file_budget.as_ref()
+    }
+
+    pub fn error_mode(&self) -> ErrorMode {
+        self.error_mode
+    }
+
     pub fn should_fail_fast(&self) -> bool {
-        self.fail_fast
+        self.error_mode == ErrorMode::FailFast
+    }
+
+    /// The number of threads used to process files.
+    pub fn threads(&self) -> usize {
+        if cfg!(target_arch = "wasm32") {
+            1
+        } else {
+            self.threads
+        }
+    }
+
+    /// The stack size of the threads that process files, or `None` if files are processed
+    /// on the calling thread.
+    pub fn stack_size(&self) -> Option<usize> {
+        if cfg!(target_arch = "wasm32") || self.stack_size == 0 {
+            None
+        } else {
+            Some(self.stack_size)
+        }
+    }
+
+    /// The maximum nesting depth of the files, or `None` if the depth is not checked.
+    pub fn depth_limit(&self) -> Option<usize> {
+        if self.depth_limit == 0 {
+            None
+        } else {
+            Some(self.depth_limit)
+        }
+    }
+
+    pub fn cache_directory(&self) -> Option<&Path> {
+        self.cache_directory.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn iter_includes(&self) -> impl Iterator<Item = &str> {
+        self.includes.iter().map(String::as_str)
+    }
+
+    pub fn iter_excludes(&self) -> impl Iterator<Item = &str> {
+        self.excludes.iter().map(String::as_str)
+    }
+
+    pub fn max_directory_depth(&self) -> usize {
+        self.max_directory_depth
+    }
+
+    pub fn should_respect_gitignore(&self) -> bool {
+        self.respect_gitignore
+    }
+
+    pub(crate) fn output_extensions(&self) -> &BTreeMap<String, String> {
+        &self.output_extensions
+    }
+
+    /// Replaces the extension of an output path according to the output extensions.
+    pub(crate) fn remap_output_extension(&self, path: PathBuf) -> PathBuf {
+        let new_extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| self.output_extensions.get(extension));
+
+        match new_extension {
+            Some(new_extension) => path.with_extension(new_extension),
+            None => path,
+        }
     }
 
     pub fn configuration_path(&self) -> Option<&Path> {
@@ -69,7 +456,15 @@ impl Options {
         self.config_generator_override.as_ref()
     }
 
+    pub(crate) fn take_input_code(&mut self) -> Option<String> {
+        self.input_code.take()
+    }
+
     pub fn take_configuration(&mut self) -> Option<Configuration> {
         self.config.take()
     }
+
+    pub(crate) fn take_configuration_layers(&mut self) -> Vec<ConfigurationLayer> {
+        std::mem::take(&mut self.config_layers)
+    }
 }
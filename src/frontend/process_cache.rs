@@ -0,0 +1,437 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3::xxh3_64;
+
+use super::{configuration::Configuration, resources::Resources, DarkluaError, DarkluaResult};
+
+const DARKLUA_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A cache stored in a directory that remembers the output of each processed file, so
+/// that files that did not change since the last run are not processed again.
+///
+/// An entry is only valid when it was produced by the same version of darklua, with the
+/// same configuration, and when the source and every file it depends on (like the modules
+/// inlined by the bundler) still have the same content.
+#[derive(Debug, Clone)]
+pub(crate) struct ProcessCache {
+    directory: PathBuf,
+    configuration_hash: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct FileState {
+    fingerprint: Option<u64>,
+    content_hash: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    key: u64,
+    source: FileState,
+    /// The generated code, or `None` when it is identical to the source.
+    output: Option<String>,
+    /// The state of each file the output depends on, or `None` for the files that did not
+    /// exist (like the candidates probed when resolving a require).
+    dependencies: Vec<(PathBuf, Option<FileState>)>,
+}
+
+#[derive(Debug)]
+pub(crate) struct CachedOutput {
+    output: Option<String>,
+    dependencies: Vec<PathBuf>,
+}
+
+impl CachedOutput {
+    /// Returns the generated code, or `None` if the generated code is the same as the source.
+    pub(crate) fn take_output(&mut self) -> Option<String> {
+        self.output.take()
+    }
+
+    pub(crate) fn into_dependencies(self) -> impl Iterator<Item = PathBuf> {
+        self.dependencies.into_iter()
+    }
+}
+
+impl ProcessCache {
+    pub(crate) fn new(
+        directory: impl Into<PathBuf>,
+        configuration: &Configuration,
+        output_extensions: &BTreeMap<String, String>,
+    ) -> Self {
+        let mut data = DARKLUA_VERSION.as_bytes().to_vec();
+        data.push(0);
+        data.extend(serde_json::to_vec(configuration).ok().unwrap_or_default());
+        data.push(0);
+        // the output extensions change the content of the generated code
+        data.extend(
+            serde_json::to_vec(output_extensions)
+                .ok()
+                .unwrap_or_default(),
+        );
+        data.push(0);
+        if let Some(location) = configuration.location() {
+            data.extend(location.to_string_lossy().as_bytes());
+        }
+
+        Self {
+            directory: directory.into(),
+            configuration_hash: xxh3_64(&data),
+        }
+    }
+
+    /// Returns the cached output of the given work if it is still valid.
+    pub(crate) fn lookup(
+        &self,
+        resources: &Resources,
+        source: &Path,
+        output: &Path,
+    ) -> DarkluaResult<Option<CachedOutput>> {
+        let entry_path = self.entry_path(source);
+
+        if !resources.is_file(&entry_path)? {
+            log::trace!("no cache entry found for `{}`", source.display());
+            return Ok(None);
+        }
+
+        let entry: CacheEntry =
+            serde_json::from_str(&resources.get(&entry_path)?).map_err(|err| {
+                DarkluaError::custom(format!(
+                    "invalid cache entry at `{}`: {}",
+                    entry_path.display(),
+                    err
+                ))
+            })?;
+
+        if entry.key != self.key(source, output) {
+            log::trace!(
+                "cache entry for `{}` was created with a different configuration",
+                source.display()
+            );
+            return Ok(None);
+        }
+
+        if !is_unchanged(resources, source, &entry.source)? {
+            log::trace!("`{}` changed since it was cached", source.display());
+            return Ok(None);
+        }
+
+        for (dependency, state) in entry.dependencies.iter() {
+            let unchanged = match state {
+                Some(state) => is_unchanged(resources, dependency, state)?,
+                None => !resources.is_file(dependency)?,
+            };
+
+            if !unchanged {
+                log::trace!(
+                    "dependency `{}` of `{}` changed since it was cached",
+                    dependency.display(),
+                    source.display()
+                );
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(CachedOutput {
+            output: entry.output,
+            dependencies: entry
+                .dependencies
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect(),
+        }))
+    }
+
+    /// Stores the generated code for the given work.
+    pub(crate) fn store<'b>(
+        &self,
+        resources: &Resources,
+        source: &Path,
+        output: &Path,
+        content: &str,
+        generated_code: &str,
+        dependencies: impl Iterator<Item = &'b Path>,
+    ) -> DarkluaResult<()> {
+        let mut dependencies = dependencies
+            .map(|path| {
+                let state = if resources.is_file(path)? {
+                    let content = resources.get(path)?;
+                    Some(FileState::new(resources, path, &content)?)
+                } else {
+                    None
+                };
+                Ok((path.to_path_buf(), state))
+            })
+            .collect::<DarkluaResult<Vec<_>>>()?;
+        dependencies.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let entry = CacheEntry {
+            key: self.key(source, output),
+            source: FileState::new(resources, source, content)?,
+            output: if generated_code == content {
+                None
+            } else {
+                Some(generated_code.to_owned())
+            },
+            dependencies,
+        };
+
+        let entry_content = serde_json::to_string(&entry).map_err(|err| {
+            DarkluaError::custom(format!(
+                "unable to serialize cache entry for `{}`: {}",
+                source.display(),
+                err
+            ))
+        })?;
+
+        resources.write(self.entry_path(source), &entry_content)?;
+
+        Ok(())
+    }
+
+    fn key(&self, source: &Path, output: &Path) -> u64 {
+        let mut data = self.configuration_hash.to_le_bytes().to_vec();
+        data.extend(source.to_string_lossy().as_bytes());
+        data.push(0);
+        data.extend(output.to_string_lossy().as_bytes());
+        xxh3_64(&data)
+    }
+
+    fn entry_path(&self, source: &Path) -> PathBuf {
+        let name = xxh3_64(source.to_string_lossy().as_bytes());
+        self.directory.join(format!("{:016x}.json", name))
+    }
+}
+
+impl FileState {
+    fn new(resources: &Resources, path: &Path, content: &str) -> DarkluaResult<Self> {
+        Ok(Self {
+            fingerprint: resources.fingerprint(path)?,
+            content_hash: xxh3_64(content.as_bytes()),
+        })
+    }
+}
+
+/// Compares the fingerprint of the file first so that its content is only read when the
+/// fingerprint is not available or when it changed.
+fn is_unchanged(resources: &Resources, path: &Path, state: &FileState) -> DarkluaResult<bool> {
+    if !resources.is_file(path)? {
+        return Ok(false);
+    }
+
+    let fingerprint = resources.fingerprint(path)?;
+
+    if fingerprint.is_some() && fingerprint == state.fingerprint {
+        Ok(true)
+    } else {
+        let content = resources.get(path)?;
+        Ok(xxh3_64(content.as_bytes()) == state.content_hash)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::{process, Options};
+
+    const MAIN: &str = "src/main.lua";
+    const VALUE: &str = "src/value.lua";
+    const OUTPUT: &str = "out/main.lua";
+    const CACHE: &str = "cache";
+
+    fn bundle_configuration() -> Configuration {
+        json5::from_str("{ rules: [], bundle: { require_mode: 'path' } }").unwrap()
+    }
+
+    fn setup() -> (Resources, Arc<Mutex<Vec<PathBuf>>>) {
+        let (resources, reads) = Resources::from_memory().spy_reads();
+        resources
+            .write(MAIN, "local value = require('./value') return value")
+            .unwrap();
+        resources.write(VALUE, "return 1").unwrap();
+        (resources, reads)
+    }
+
+    fn run(resources: &Resources, configuration: Configuration) {
+        process(
+            resources,
+            Options::new(MAIN)
+                .with_output(OUTPUT)
+                .with_configuration(configuration)
+                .with_cache_directory(CACHE),
+        )
+        .unwrap()
+        .result()
+        .unwrap();
+    }
+
+    fn take_reads(reads: &Arc<Mutex<Vec<PathBuf>>>) -> Vec<PathBuf> {
+        reads
+            .lock()
+            .unwrap()
+            .drain(..)
+            .filter(|path| path.starts_with("src"))
+            .collect()
+    }
+
+    #[test]
+    fn first_run_reads_all_files() {
+        let (resources, reads) = setup();
+
+        run(&resources, bundle_configuration());
+
+        let files = take_reads(&reads);
+        assert!(files.contains(&PathBuf::from(MAIN)));
+        assert!(files.contains(&PathBuf::from(VALUE)));
+    }
+
+    #[test]
+    fn second_run_does_not_read_unchanged_files() {
+        let (resources, reads) = setup();
+
+        run(&resources, bundle_configuration());
+        let output = resources.get(OUTPUT).unwrap();
+        resources.remove(OUTPUT).unwrap();
+        take_reads(&reads);
+
+        run(&resources, bundle_configuration());
+
+        assert_eq!(take_reads(&reads), Vec::<PathBuf>::new());
+        assert_eq!(resources.get(OUTPUT).unwrap(), output);
+    }
+
+    #[test]
+    fn changed_bundled_module_invalidates_entry() {
+        let (resources, reads) = setup();
+
+        run(&resources, bundle_configuration());
+        let output = resources.get(OUTPUT).unwrap();
+        resources.write(VALUE, "return 2").unwrap();
+        take_reads(&reads);
+
+        run(&resources, bundle_configuration());
+
+        let files = take_reads(&reads);
+        assert!(files.contains(&PathBuf::from(MAIN)));
+        assert_ne!(resources.get(OUTPUT).unwrap(), output);
+    }
+
+    #[test]
+    fn changed_luaurc_invalidates_entry() {
+        let (resources, reads) = setup();
+        resources.write(MAIN, "return require('@value')").unwrap();
+        resources.write("src/other.lua", "return 2").unwrap();
+        resources
+            .write(".luaurc", r#"{ "aliases": { "value": "src/value.lua" } }"#)
+            .unwrap();
+
+        run(&resources, bundle_configuration());
+        let output = resources.get(OUTPUT).unwrap();
+        resources
+            .write(".luaurc", r#"{ "aliases": { "value": "src/other.lua" } }"#)
+            .unwrap();
+        take_reads(&reads);
+
+        run(&resources, bundle_configuration());
+
+        assert!(take_reads(&reads).contains(&PathBuf::from(MAIN)));
+        assert_ne!(resources.get(OUTPUT).unwrap(), output);
+    }
+
+    #[test]
+    fn added_require_candidate_invalidates_entry() {
+        let (resources, reads) = setup();
+        resources.remove(VALUE).unwrap();
+        resources.write("src/value/init.lua", "return 1").unwrap();
+
+        run(&resources, bundle_configuration());
+        let output = resources.get(OUTPUT).unwrap();
+        // `value.luau` is a candidate that comes before `value/init.lua`
+        resources.write("src/value.luau", "return 2").unwrap();
+        take_reads(&reads);
+
+        run(&resources, bundle_configuration());
+
+        let files = take_reads(&reads);
+        assert!(files.contains(&PathBuf::from(MAIN)));
+        assert!(files.contains(&PathBuf::from("src/value.luau")));
+        assert_ne!(resources.get(OUTPUT).unwrap(), output);
+    }
+
+    #[test]
+    fn changed_source_invalidates_entry() {
+        let (resources, reads) = setup();
+
+        run(&resources, bundle_configuration());
+        let output = resources.get(OUTPUT).unwrap();
+        resources
+            .write(MAIN, "return require('./value') + 1")
+            .unwrap();
+        take_reads(&reads);
+
+        run(&resources, bundle_configuration());
+
+        assert!(take_reads(&reads).contains(&PathBuf::from(MAIN)));
+        assert_ne!(resources.get(OUTPUT).unwrap(), output);
+    }
+
+    #[test]
+    fn changed_configuration_invalidates_entry() {
+        let (resources, reads) = setup();
+
+        run(&resources, bundle_configuration());
+        take_reads(&reads);
+
+        run(
+            &resources,
+            json5::from_str("{ rules: ['remove_spaces'], bundle: { require_mode: 'path' } }")
+                .unwrap(),
+        );
+
+        assert!(take_reads(&reads).contains(&PathBuf::from(MAIN)));
+    }
+
+    #[test]
+    fn unchanged_output_is_stored_as_marker() {
+        let (resources, _reads) = setup();
+        let source = "return 1";
+        resources.write("src/init.lua", source).unwrap();
+
+        process(
+            &resources,
+            Options::new("src/init.lua")
+                .with_output("out/init.lua")
+                .with_configuration(
+                    Configuration::empty()
+                        .with_generator(crate::GeneratorParameters::default_retain_lines()),
+                )
+                .with_cache_directory(CACHE),
+        )
+        .unwrap()
+        .result()
+        .unwrap();
+
+        let cache = ProcessCache::new(
+            CACHE,
+            &Configuration::empty()
+                .with_generator(crate::GeneratorParameters::default_retain_lines()),
+            &BTreeMap::new(),
+        );
+        let mut cached = cache
+            .lookup(
+                &resources,
+                Path::new("src/init.lua"),
+                Path::new("out/init.lua"),
+            )
+            .unwrap()
+            .expect("cache entry should exist");
+
+        assert_eq!(cached.take_output(), None);
+        assert_eq!(resources.get("out/init.lua").unwrap(), source);
+    }
+}
@@ -0,0 +1,380 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Serialize, Serializer};
+
+use crate::nodes::Block;
+
+use super::{BundleManifest, DarkluaError, ModuleGraph};
+
+/// A summary of what happened to each file during a call to [`process`](crate::process).
+/// Use [`WorkerTree::report`](crate::WorkerTree::report) to obtain it.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProcessReport {
+    files: Vec<FileReport>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    rule_timings: Vec<RuleTiming>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    module_graph: Option<ModuleGraph>,
+}
+
+impl ProcessReport {
+    pub(crate) fn new(mut files: Vec<FileReport>) -> Self {
+        files.sort_by(|a, b| a.source.cmp(&b.source));
+
+        let mut rule_timings: Vec<RuleTiming> = Vec::new();
+        for rule_duration in files.iter().flat_map(|file| file.rule_durations.iter()) {
+            match rule_timings
+                .iter_mut()
+                .find(|timing| timing.rule == rule_duration.rule)
+            {
+                Some(timing) => {
+                    timing.duration += rule_duration.duration;
+                    timing.files += 1;
+                }
+                None => rule_timings.push(RuleTiming {
+                    rule: rule_duration.rule.clone(),
+                    duration: rule_duration.duration,
+                    files: 1,
+                }),
+            }
+        }
+
+        Self {
+            files,
+            rule_timings,
+            module_graph: None,
+        }
+    }
+
+    pub(crate) fn with_module_graph(mut self, module_graph: ModuleGraph) -> Self {
+        self.module_graph = Some(module_graph);
+        self
+    }
+
+    /// Iterates over the report of each input file, sorted by their source path.
+    pub fn iter_files(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter()
+    }
+
+    /// Returns the report of the given input file.
+    pub fn get(&self, source: impl AsRef<Path>) -> Option<&FileReport> {
+        let source = source.as_ref();
+        self.files.iter().find(|file| file.source == source)
+    }
+
+    /// Iterates over the output paths where new content was written.
+    pub fn iter_written(&self) -> impl Iterator<Item = &Path> {
+        self.files
+            .iter()
+            .filter(|file| matches!(file.status, FileStatus::Written))
+            .map(FileReport::output)
+    }
+
+    /// Iterates over the report of each file that was processed successfully.
+    pub fn iter_successes(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(|file| file.status.is_success())
+    }
+
+    /// Iterates over the errors of the files that failed.
+    pub fn iter_errors(&self) -> impl Iterator<Item = &DarkluaError> {
+        self.files.iter().filter_map(|file| match &file.status {
+            FileStatus::Failed { error } => Some(error),
+            _ => None,
+        })
+    }
+
+    /// Iterates over the total time spent by each rule across all files. Timings are only
+    /// measured when enabled with [`Options::measure_rule_timings`](crate::Options::measure_rule_timings).
+    pub fn iter_rule_timings(&self) -> impl Iterator<Item = &RuleTiming> {
+        self.rule_timings.iter()
+    }
+
+    /// The require calls found while processing the files. The graph is only collected
+    /// when enabled with [`Options::collect_module_graph`](crate::Options::collect_module_graph).
+    pub fn module_graph(&self) -> Option<&ModuleGraph> {
+        self.module_graph.as_ref()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.iter_errors().next().is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// The result of processing one input file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReport {
+    source: PathBuf,
+    output: PathBuf,
+    #[serde(flatten)]
+    status: FileStatus,
+    artifacts: Vec<PathBuf>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    rule_durations: Vec<RuleDuration>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    applied_rules: Vec<AppliedRule>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bundle_manifest: Option<BundleManifest>,
+}
+
+impl FileReport {
+    pub(crate) fn new(
+        source: impl Into<PathBuf>,
+        output: impl Into<PathBuf>,
+        status: FileStatus,
+        artifacts: Vec<PathBuf>,
+    ) -> Self {
+        Self {
+            source: source.into(),
+            output: output.into(),
+            status,
+            artifacts,
+            rule_durations: Vec::new(),
+            applied_rules: Vec::new(),
+            bundle_manifest: None,
+        }
+    }
+
+    pub(crate) fn with_rule_durations<'a>(
+        mut self,
+        durations: impl Iterator<Item = (&'a str, Duration)>,
+    ) -> Self {
+        self.rule_durations = durations
+            .map(|(rule, duration)| RuleDuration {
+                rule: rule.to_owned(),
+                duration,
+            })
+            .collect();
+        self
+    }
+
+    pub(crate) fn with_applied_rules(mut self, applied_rules: Vec<AppliedRule>) -> Self {
+        self.applied_rules = applied_rules;
+        self
+    }
+
+    pub(crate) fn with_bundle_manifest(mut self, bundle_manifest: Option<BundleManifest>) -> Self {
+        self.bundle_manifest = bundle_manifest;
+        self
+    }
+
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    pub fn output(&self) -> &Path {
+        &self.output
+    }
+
+    pub fn status(&self) -> &FileStatus {
+        &self.status
+    }
+
+    /// Iterates over the additional files produced while processing the file.
+    pub fn iter_artifacts(&self) -> impl Iterator<Item = &Path> {
+        self.artifacts.iter().map(AsRef::as_ref)
+    }
+
+    /// Iterates over the time spent by each rule on this file.
+    pub fn iter_rule_durations(&self) -> impl Iterator<Item = &RuleDuration> {
+        self.rule_durations.iter()
+    }
+
+    /// Iterates over the rules that modified this file, in the order they were applied.
+    /// Applied rules are only collected when enabled with
+    /// [`Options::collect_applied_rules`](crate::Options::collect_applied_rules).
+    pub fn iter_applied_rules(&self) -> impl Iterator<Item = &AppliedRule> {
+        self.applied_rules.iter()
+    }
+
+    /// The modules inlined in the file when it was bundled. The manifest is only collected
+    /// when enabled with [`Options::collect_bundle_manifests`](crate::Options::collect_bundle_manifests)
+    /// or when the `manifest_path` of the bundle configuration is set.
+    pub fn bundle_manifest(&self) -> Option<&BundleManifest> {
+        self.bundle_manifest.as_ref()
+    }
+}
+
+/// The time spent by a rule on a file. A rule applied multiple times accumulates its
+/// durations.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleDuration {
+    rule: String,
+    #[serde(rename = "duration_ms", serialize_with = "serialize_duration")]
+    duration: Duration,
+}
+
+impl RuleDuration {
+    pub fn rule(&self) -> &str {
+        &self.rule
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+/// The total time spent by a rule across all files.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleTiming {
+    rule: String,
+    #[serde(rename = "duration_ms", serialize_with = "serialize_duration")]
+    duration: Duration,
+    files: usize,
+}
+
+impl RuleTiming {
+    pub fn rule(&self) -> &str {
+        &self.rule
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// The number of files processed by the rule.
+    pub fn files(&self) -> usize {
+        self.files
+    }
+}
+
+/// A rule that modified a file. A rule applied multiple times has one entry for each time
+/// it modified the file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AppliedRule {
+    rule: String,
+    modifications: usize,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    notes: BTreeMap<String, RuleNoteValue>,
+}
+
+impl AppliedRule {
+    pub(crate) fn new(
+        rule: impl Into<String>,
+        modifications: usize,
+        notes: BTreeMap<String, RuleNoteValue>,
+    ) -> Self {
+        Self {
+            rule: rule.into(),
+            modifications,
+            notes,
+        }
+    }
+
+    pub fn rule(&self) -> &str {
+        &self.rule
+    }
+
+    /// The number of statements of the file that were added, removed or changed by the
+    /// rule, or 1 when only the tokens of the file changed (like removing comments).
+    pub fn modifications(&self) -> usize {
+        self.modifications
+    }
+
+    /// Returns a note attached by the rule, like the number of requires converted by the
+    /// `convert_require` rule.
+    pub fn get_note(&self, name: &str) -> Option<&RuleNoteValue> {
+        self.notes.get(name)
+    }
+
+    /// Iterates over the notes attached by the rule, sorted by their name.
+    pub fn iter_notes(&self) -> impl Iterator<Item = (&str, &RuleNoteValue)> {
+        self.notes
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+}
+
+/// A short note attached by a rule to describe what it did to a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum RuleNoteValue {
+    Count(usize),
+    Names(Vec<String>),
+}
+
+impl From<usize> for RuleNoteValue {
+    fn from(count: usize) -> Self {
+        Self::Count(count)
+    }
+}
+
+impl From<Vec<String>> for RuleNoteValue {
+    fn from(names: Vec<String>) -> Self {
+        Self::Names(names)
+    }
+}
+
+/// Counts the statements that differ between two versions of a block. Statements that are
+/// identical at the start or at the end of both blocks are not counted, so inserting or
+/// removing a statement only counts once.
+pub(crate) fn count_modifications(original: &Block, modified: &Block) -> usize {
+    let original_statements: Vec<_> = original.iter_statements().collect();
+    let modified_statements: Vec<_> = modified.iter_statements().collect();
+
+    let common_prefix = original_statements
+        .iter()
+        .zip(modified_statements.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let common_suffix = original_statements[common_prefix..]
+        .iter()
+        .rev()
+        .zip(modified_statements[common_prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let changed_statements =
+        original_statements.len().max(modified_statements.len()) - common_prefix - common_suffix;
+    let changed_last_statement =
+        usize::from(original.get_last_statement() != modified.get_last_statement());
+
+    match changed_statements + changed_last_statement {
+        0 if original != modified => 1,
+        modifications => modifications,
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum FileStatus {
+    /// The output was written with new content.
+    Written,
+    /// The output already contained the generated code, so it was not written again.
+    Unchanged,
+    /// The file was not processed.
+    Skipped { reason: String },
+    /// Processing the file failed.
+    Failed {
+        #[serde(serialize_with = "serialize_error")]
+        error: DarkluaError,
+    },
+}
+
+impl FileStatus {
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Written | Self::Unchanged)
+    }
+}
+
+fn serialize_duration<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+fn serialize_error<S: Serializer>(error: &DarkluaError, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(error)
+}
@@ -1,233 +1,641 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap, HashSet},
     ffi::OsStr,
-    fs::{self, File},
-    io::{self, BufWriter, ErrorKind as IOErrorKind, Write},
-    iter,
+    fmt, io,
     path::{Path, PathBuf},
     sync::{Arc, Mutex},
 };
+#[cfg(feature = "fs")]
+use std::{
+    fs::{self, File},
+    io::{BufWriter, ErrorKind as IOErrorKind, Write},
+    time::UNIX_EPOCH,
+};
+
+use xxhash_rust::xxh3::xxh3_64;
 
 use crate::utils::normalize_path;
 
+/// A storage for the files processed by darklua. Implement this trait to process files
+/// that are not on the file system or in memory (for example, files from a virtual file
+/// system or a database), then create [`Resources`] from it with
+/// [`Resources::from_backend`].
+///
+/// Every file access made while processing (reading the configuration, locating required
+/// modules, reading `.luaurc` files or inlining data files) goes through this trait.
+pub trait ResourceBackend: fmt::Debug + Send + Sync {
+    fn exists(&self, location: &Path) -> Result<bool, ResourceError>;
+
+    fn is_directory(&self, location: &Path) -> Result<bool, ResourceError>;
+
+    fn is_file(&self, location: &Path) -> Result<bool, ResourceError>;
+
+    /// Reads the content of a file. Files that do not contain valid UTF-8 (like binary
+    /// files) should return [`ResourceError::InvalidUtf8`], so that they can be skipped
+    /// when they are found in a directory.
+    fn read(&self, location: &Path) -> Result<String, ResourceError>;
+
+    /// Writes the content to the file, creating the parent directories when needed.
+    fn write(&self, location: &Path, content: &str) -> Result<(), ResourceError>;
+
+    /// Removes a file or a directory and its content. Nothing happens when the location
+    /// does not exist.
+    fn remove(&self, location: &Path) -> Result<(), ResourceError>;
+
+    /// Returns the paths of the files and directories directly inside a directory.
+    fn read_directory(&self, location: &Path) -> Result<Vec<PathBuf>, ResourceError>;
+
+    /// Returns a path that identifies a file or a directory, so that a location reached
+    /// through different paths (like symbolic links) is visited only once when walking
+    /// directories. The default implementation normalizes the path.
+    fn canonicalize(&self, location: &Path) -> Result<PathBuf, ResourceError> {
+        Ok(normalize_path(location))
+    }
+
+    /// Returns a value that changes when the content of a file changes. It is used by
+    /// the process cache to avoid reading unchanged files. The default implementation
+    /// hashes the content of the file.
+    fn fingerprint(&self, location: &Path) -> Result<Option<u64>, ResourceError> {
+        self.read(location)
+            .map(|content| Some(xxh3_64(content.as_bytes())))
+    }
+}
+
+/// A [`ResourceBackend`] that uses the file system. It is only available with the `fs`
+/// feature (enabled by default).
+#[cfg(feature = "fs")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileSystemBackend;
+
+#[cfg(feature = "fs")]
+impl ResourceBackend for FileSystemBackend {
+    fn exists(&self, location: &Path) -> Result<bool, ResourceError> {
+        Ok(location.exists())
+    }
+
+    fn is_directory(&self, location: &Path) -> Result<bool, ResourceError> {
+        Ok(self.exists(location)? && location.is_dir())
+    }
+
+    fn is_file(&self, location: &Path) -> Result<bool, ResourceError> {
+        Ok(self.exists(location)? && location.is_file())
+    }
+
+    fn read(&self, location: &Path) -> Result<String, ResourceError> {
+        let content = fs::read(location).map_err(|err| match err.kind() {
+            IOErrorKind::NotFound => ResourceError::not_found(location),
+            _ => ResourceError::io_error(location, err),
+        })?;
+
+        String::from_utf8(content).map_err(|_| ResourceError::invalid_utf8(location))
+    }
+
+    fn write(&self, location: &Path, content: &str) -> Result<(), ResourceError> {
+        if let Some(parent) = location.parent() {
+            fs::create_dir_all(parent).map_err(|err| ResourceError::io_error(parent, err))?;
+        };
+
+        let file = File::create(location).map_err(|err| ResourceError::io_error(location, err))?;
+
+        let mut file = BufWriter::new(file);
+        file.write_all(content.as_bytes())
+            .map_err(|err| ResourceError::io_error(location, err))
+    }
+
+    fn remove(&self, location: &Path) -> Result<(), ResourceError> {
+        if !self.exists(location)? {
+            Ok(())
+        } else if self.is_file(location)? {
+            fs::remove_file(location).map_err(|err| ResourceError::io_error(location, err))
+        } else if self.is_directory(location)? {
+            fs::remove_dir_all(location).map_err(|err| ResourceError::io_error(location, err))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn read_directory(&self, location: &Path) -> Result<Vec<PathBuf>, ResourceError> {
+        location
+            .read_dir()
+            .and_then(|entries| {
+                entries
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect()
+            })
+            .map_err(|err| ResourceError::io_error(location, err))
+    }
+
+    fn canonicalize(&self, location: &Path) -> Result<PathBuf, ResourceError> {
+        location.canonicalize().map_err(|err| match err.kind() {
+            IOErrorKind::NotFound => ResourceError::not_found(location),
+            _ => ResourceError::io_error(location, err),
+        })
+    }
+
+    fn fingerprint(&self, location: &Path) -> Result<Option<u64>, ResourceError> {
+        let metadata = location.metadata().map_err(|err| match err.kind() {
+            IOErrorKind::NotFound => ResourceError::not_found(location),
+            _ => ResourceError::io_error(location, err),
+        })?;
+
+        Ok(metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|modified| {
+                let mut data = metadata.len().to_le_bytes().to_vec();
+                data.extend_from_slice(&modified.as_nanos().to_le_bytes());
+                xxh3_64(&data)
+            }))
+    }
+}
+
+/// A [`ResourceBackend`] that keeps files in memory. Paths are normalized, so `./a.lua`
+/// and `a.lua` are the same file.
+#[derive(Debug, Default)]
+pub struct MemoryBackend {
+    data: Mutex<HashMap<PathBuf, String>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ResourceBackend for MemoryBackend {
+    fn exists(&self, location: &Path) -> Result<bool, ResourceError> {
+        Ok(self
+            .data
+            .lock()
+            .unwrap()
+            .contains_key(&normalize_path(location)))
+    }
+
+    fn is_directory(&self, location: &Path) -> Result<bool, ResourceError> {
+        let data = self.data.lock().unwrap();
+        let location = normalize_path(location);
+
+        Ok(data
+            .iter()
+            .any(|(path, _content)| path != &location && path.starts_with(&location)))
+    }
+
+    fn is_file(&self, location: &Path) -> Result<bool, ResourceError> {
+        let data = self.data.lock().unwrap();
+        let location = normalize_path(location);
+
+        Ok(data.contains_key(&location))
+    }
+
+    fn read(&self, location: &Path) -> Result<String, ResourceError> {
+        let data = self.data.lock().unwrap();
+        let location = normalize_path(location);
+
+        data.get(&location)
+            .map(String::from)
+            .ok_or_else(|| ResourceError::not_found(location))
+    }
+
+    fn write(&self, location: &Path, content: &str) -> Result<(), ResourceError> {
+        let mut data = self.data.lock().unwrap();
+        data.insert(normalize_path(location), content.to_string());
+        Ok(())
+    }
+
+    fn remove(&self, location: &Path) -> Result<(), ResourceError> {
+        if self.is_file(location)? {
+            let mut data = self.data.lock().unwrap();
+            data.remove(&normalize_path(location));
+        } else if self.is_directory(location)? {
+            let mut data = self.data.lock().unwrap();
+            let location = normalize_path(location);
+            data.retain(|path, _| !path.starts_with(&location));
+        }
+
+        Ok(())
+    }
+
+    fn read_directory(&self, location: &Path) -> Result<Vec<PathBuf>, ResourceError> {
+        let data = self.data.lock().unwrap();
+        let location = normalize_path(location);
+
+        let entries: BTreeSet<_> = data
+            .keys()
+            .filter(|path| *path != &location)
+            .filter_map(|path| path.strip_prefix(&location).ok())
+            .filter_map(|relative_path| relative_path.components().next())
+            .map(|component| location.join(component))
+            .collect();
+
+        Ok(entries.into_iter().collect())
+    }
+}
+
+/// The maximum number of nested directories walked below an input directory.
+pub(crate) const DEFAULT_MAX_WALK_DEPTH: usize = 64;
+
+/// Finds every file under a location. Directories are identified by their canonical path,
+/// so that symbolic link loops are cut and directories reached through multiple links are
+/// walked once. Files reached through multiple paths are returned once, using the smallest
+/// path.
+fn walk_backend(backend: &dyn ResourceBackend, location: &Path, max_depth: usize) -> Vec<PathBuf> {
+    let mut visited_directories: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut files: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut pending = vec![(location.to_path_buf(), 0)];
+
+    while let Some((path, depth)) = pending.pop() {
+        let canonical = match backend.canonicalize(&path) {
+            Ok(canonical) => canonical,
+            Err(err) => {
+                log::warn!("unable to resolve `{}`: {}", path.display(), err);
+                continue;
+            }
+        };
+
+        match backend.is_file(&path) {
+            Ok(true) => {
+                match files.get_mut(&canonical) {
+                    Some(known_path) => {
+                        log::debug!(
+                            "`{}` and `{}` lead to the same file",
+                            known_path.display(),
+                            path.display()
+                        );
+                        if path < *known_path {
+                            *known_path = path;
+                        }
+                    }
+                    None => {
+                        files.insert(canonical, path);
+                    }
+                }
+                continue;
+            }
+            Ok(false) => {}
+            Err(err) => {
+                log::warn!("unable to read `{}`: {}", path.display(), err);
+                continue;
+            }
+        }
+
+        match backend.is_directory(&path) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(err) => {
+                log::warn!("unable to read directory `{}`: {}", path.display(), err);
+                continue;
+            }
+        }
+
+        if let Some(visited_path) = visited_directories.get(&canonical) {
+            if path.starts_with(visited_path) {
+                log::warn!(
+                    "skip directory `{}` because it leads back to `{}` (directory loop)",
+                    path.display(),
+                    visited_path.display()
+                );
+            } else {
+                log::debug!(
+                    "skip directory `{}` because it was already walked as `{}`",
+                    path.display(),
+                    visited_path.display()
+                );
+            }
+            continue;
+        }
+
+        if depth > max_depth {
+            log::warn!(
+                "skip directory `{}` because it is nested deeper than {} directories",
+                path.display(),
+                max_depth
+            );
+            continue;
+        }
+
+        visited_directories.insert(canonical, path.clone());
+
+        match backend.read_directory(&path) {
+            Ok(mut entries) => {
+                entries.sort();
+                pending.extend(entries.into_iter().rev().map(|entry| (entry, depth + 1)));
+            }
+            Err(err) => {
+                log::warn!("unable to read directory `{}`: {}", path.display(), err);
+            }
+        }
+    }
+
+    let mut files: Vec<_> = files.into_values().collect();
+    files.sort();
+    files
+}
+
 #[derive(Debug, Clone)]
 enum Source {
-    FileSystem,
-    Memory(Arc<Mutex<HashMap<PathBuf, String>>>),
+    Backend(Arc<dyn ResourceBackend>),
+    Overlay {
+        base: Box<Source>,
+        layer: Arc<Mutex<OverlayLayer>>,
+    },
+}
+
+#[derive(Debug, Default)]
+struct OverlayLayer {
+    files: HashMap<PathBuf, String>,
+    written: HashSet<PathBuf>,
+    removed: HashSet<PathBuf>,
+}
+
+impl OverlayLayer {
+    fn is_directory(&self, location: &Path) -> bool {
+        self.files
+            .keys()
+            .any(|path| path != location && path.starts_with(location))
+    }
+
+    fn is_removed(&self, location: &Path) -> bool {
+        self.removed
+            .iter()
+            .any(|removed| location.starts_with(removed))
+    }
 }
 
 impl Source {
     pub fn exists(&self, location: &Path) -> ResourceResult<bool> {
         match self {
-            Self::FileSystem => Ok(location.exists()),
-            Self::Memory(data) => Ok(data.lock().unwrap().contains_key(&normalize_path(location))),
+            Self::Backend(backend) => backend.exists(location),
+            Self::Overlay { base, layer } => {
+                let normalized = normalize_path(location);
+                let layer = layer.lock().unwrap();
+
+                if layer.files.contains_key(&normalized) || layer.is_directory(&normalized) {
+                    Ok(true)
+                } else if layer.is_removed(&normalized) {
+                    Ok(false)
+                } else {
+                    base.exists(location)
+                }
+            }
         }
     }
 
     pub fn is_directory(&self, location: &Path) -> ResourceResult<bool> {
-        let is_directory = match self {
-            Source::FileSystem => self.exists(location)? && location.is_dir(),
-            Source::Memory(data) => {
-                let data = data.lock().unwrap();
-                let location = normalize_path(location);
-
-                data.iter()
-                    .any(|(path, _content)| path != &location && path.starts_with(&location))
+        match self {
+            Self::Backend(backend) => backend.is_directory(location),
+            Self::Overlay { base, layer } => {
+                let normalized = normalize_path(location);
+                let layer = layer.lock().unwrap();
+
+                if layer.is_directory(&normalized) {
+                    Ok(true)
+                } else if layer.files.contains_key(&normalized) || layer.is_removed(&normalized) {
+                    Ok(false)
+                } else {
+                    base.is_directory(location)
+                }
             }
-        };
-        Ok(is_directory)
+        }
     }
 
     pub fn is_file(&self, location: &Path) -> ResourceResult<bool> {
-        let is_file = match self {
-            Source::FileSystem => self.exists(location)? && location.is_file(),
-            Source::Memory(data) => {
-                let data = data.lock().unwrap();
-                let location = normalize_path(location);
-
-                data.contains_key(&location)
+        match self {
+            Self::Backend(backend) => backend.is_file(location),
+            Self::Overlay { base, layer } => {
+                let normalized = normalize_path(location);
+                let layer = layer.lock().unwrap();
+
+                if layer.files.contains_key(&normalized) {
+                    Ok(true)
+                } else if layer.is_directory(&normalized) || layer.is_removed(&normalized) {
+                    Ok(false)
+                } else {
+                    base.is_file(location)
+                }
             }
-        };
-        Ok(is_file)
+        }
     }
 
     pub fn get(&self, location: &Path) -> ResourceResult<String> {
         match self {
-            Self::FileSystem => fs::read_to_string(location).map_err(|err| match err.kind() {
-                IOErrorKind::NotFound => ResourceError::not_found(location),
-                _ => ResourceError::io_error(location, err),
-            }),
-            Self::Memory(data) => {
-                let data = data.lock().unwrap();
-                let location = normalize_path(location);
-
-                data.get(&location)
-                    .map(String::from)
-                    .ok_or_else(|| ResourceError::not_found(location))
+            Self::Backend(backend) => backend.read(location),
+            Self::Overlay { base, layer } => {
+                let normalized = normalize_path(location);
+                let layer = layer.lock().unwrap();
+
+                if let Some(content) = layer.files.get(&normalized) {
+                    Ok(content.to_owned())
+                } else if layer.is_removed(&normalized) {
+                    Err(ResourceError::not_found(normalized))
+                } else {
+                    base.get(location)
+                }
             }
         }
     }
 
-    pub fn write(&self, location: &Path, content: &str) -> ResourceResult<()> {
+    pub fn fingerprint(&self, location: &Path) -> ResourceResult<Option<u64>> {
         match self {
-            Self::FileSystem => {
-                if let Some(parent) = location.parent() {
-                    fs::create_dir_all(parent)
-                        .map_err(|err| ResourceError::io_error(parent, err))?;
-                };
-
-                let file =
-                    File::create(location).map_err(|err| ResourceError::io_error(location, err))?;
-
-                let mut file = BufWriter::new(file);
-                file.write_all(content.as_bytes())
-                    .map_err(|err| ResourceError::io_error(location, err))
+            Self::Backend(backend) => backend.fingerprint(location),
+            Self::Overlay { base, layer } => {
+                let normalized = normalize_path(location);
+                let layer = layer.lock().unwrap();
+
+                if let Some(content) = layer.files.get(&normalized) {
+                    Ok(Some(xxh3_64(content.as_bytes())))
+                } else if layer.is_removed(&normalized) {
+                    Err(ResourceError::not_found(normalized))
+                } else {
+                    base.fingerprint(location)
+                }
             }
-            Self::Memory(data) => {
-                let mut data = data.lock().unwrap();
-                data.insert(normalize_path(location), content.to_string());
-                Ok(())
+        }
+    }
+
+    pub fn canonicalize(&self, location: &Path) -> ResourceResult<PathBuf> {
+        match self {
+            Self::Backend(backend) => backend.canonicalize(location),
+            Self::Overlay { base, layer } => {
+                let normalized = normalize_path(location);
+                let layer = layer.lock().unwrap();
+
+                if layer.files.contains_key(&normalized) || layer.is_directory(&normalized) {
+                    Ok(normalized)
+                } else if layer.is_removed(&normalized) {
+                    Err(ResourceError::not_found(normalized))
+                } else {
+                    base.canonicalize(location)
+                }
             }
         }
     }
 
-    pub fn walk(&self, location: &Path) -> impl Iterator<Item = PathBuf> {
+    pub fn write(&self, location: &Path, content: &str) -> ResourceResult<()> {
         match self {
-            Self::FileSystem => Box::new(walk_file_system(location.to_path_buf()))
-                as Box<dyn Iterator<Item = PathBuf>>,
-            Self::Memory(data) => {
-                let data = data.lock().unwrap();
+            Self::Backend(backend) => backend.write(location, content),
+            Self::Overlay { layer, .. } => {
+                let mut layer = layer.lock().unwrap();
                 let location = normalize_path(location);
-                let mut paths: Vec<_> = data.keys().map(normalize_path).collect();
-                paths.retain(|path| path.starts_with(&location));
 
-                Box::new(paths.into_iter())
+                layer.removed.remove(&location);
+                layer.written.insert(location.clone());
+                layer.files.insert(location, content.to_string());
+                Ok(())
             }
         }
     }
 
-    fn remove(&self, location: &Path) -> Result<(), ResourceError> {
+    pub fn walk(&self, location: &Path, max_depth: usize) -> Box<dyn Iterator<Item = PathBuf>> {
         match self {
-            Self::FileSystem => {
-                if !self.exists(location)? {
-                    Ok(())
-                } else if self.is_file(location)? {
-                    fs::remove_file(location).map_err(|err| ResourceError::io_error(location, err))
-                } else if self.is_directory(location)? {
-                    fs::remove_dir_all(location)
-                        .map_err(|err| ResourceError::io_error(location, err))
-                } else {
-                    Ok(())
-                }
+            Self::Backend(backend) => {
+                Box::new(walk_backend(backend.as_ref(), location, max_depth).into_iter())
             }
-            Self::Memory(data) => {
-                if self.is_file(location)? {
-                    let mut data = data.lock().unwrap();
-                    data.remove(&normalize_path(location));
-                } else if self.is_directory(location)? {
-                    let mut data = data.lock().unwrap();
-                    let location = normalize_path(location);
-                    data.retain(|path, _| !path.starts_with(&location));
+            Self::Overlay { base, layer } => {
+                let layer = layer.lock().unwrap();
+                let location = normalize_path(location);
+
+                let mut paths: Vec<_> = layer
+                    .files
+                    .keys()
+                    .filter(|path| path.starts_with(&location))
+                    .cloned()
+                    .collect();
+
+                let mut known_paths: HashSet<_> = paths.iter().cloned().collect();
+
+                for path in base.walk(&location, max_depth) {
+                    let normalized = normalize_path(&path);
+                    if !layer.is_removed(&normalized) && known_paths.insert(normalized) {
+                        paths.push(path);
+                    }
                 }
 
-                Ok(())
+                Box::new(paths.into_iter())
             }
         }
     }
-}
 
-fn walk_file_system(location: PathBuf) -> impl Iterator<Item = PathBuf> {
-    let mut unknown_paths = vec![location];
-    let mut file_paths = Vec::new();
-    let mut dir_entries = Vec::new();
-
-    iter::from_fn(move || loop {
-        if let Some(location) = unknown_paths.pop() {
-            match location.metadata() {
-                Ok(metadata) => {
-                    if metadata.is_file() {
-                        file_paths.push(location.to_path_buf());
-                    } else if metadata.is_dir() {
-                        dir_entries.push(location.to_path_buf());
-                    } else if metadata.is_symlink() {
-                        log::warn!("unexpected symlink `{}` not followed", location.display());
-                    } else {
-                        log::warn!(
-                            concat!(
-                                "path `{}` points to an unexpected location that is not a ",
-                                "file, not a directory and not a symlink"
-                            ),
-                            location.display()
-                        );
-                    };
-                }
-                Err(err) => {
-                    log::warn!(
-                        "unable to read metadata from file `{}`: {}",
-                        location.display(),
-                        err
-                    );
-                }
-            }
-        } else if let Some(dir_location) = dir_entries.pop() {
-            match dir_location.read_dir() {
-                Ok(read_dir) => {
-                    for entry in read_dir {
-                        match entry {
-                            Ok(entry) => {
-                                unknown_paths.push(entry.path());
-                            }
-                            Err(err) => {
-                                log::warn!(
-                                    "unable to read directory entry `{}`: {}",
-                                    dir_location.display(),
-                                    err
-                                );
-                            }
-                        }
-                    }
-                }
-                Err(err) => {
-                    log::warn!(
-                        "unable to read directory `{}`: {}",
-                        dir_location.display(),
-                        err
-                    );
-                }
+    fn remove(&self, location: &Path) -> Result<(), ResourceError> {
+        match self {
+            Self::Backend(backend) => backend.remove(location),
+            Self::Overlay { layer, .. } => {
+                let mut layer = layer.lock().unwrap();
+                let location = normalize_path(location);
+
+                layer.files.retain(|path, _| !path.starts_with(&location));
+                layer.written.retain(|path| !path.starts_with(&location));
+                layer.removed.insert(location);
+
+                Ok(())
             }
-        } else if let Some(path) = file_paths.pop() {
-            break Some(path);
-        } else {
-            break None;
         }
-    })
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Resources {
     source: Source,
+    #[cfg(test)]
+    read_log: Option<Arc<Mutex<Vec<PathBuf>>>>,
 }
 
 impl Resources {
-    pub fn from_file_system() -> Self {
+    fn new(source: Source) -> Self {
         Self {
-            source: Source::FileSystem,
+            source,
+            #[cfg(test)]
+            read_log: None,
         }
     }
 
+    #[cfg(feature = "fs")]
+    pub fn from_file_system() -> Self {
+        Self::from_backend(FileSystemBackend)
+    }
+
     pub fn from_memory() -> Self {
-        Self {
-            source: Source::Memory(Default::default()),
+        Self::from_backend(MemoryBackend::new())
+    }
+
+    /// Creates resources that access files through a custom [`ResourceBackend`].
+    pub fn from_backend(backend: impl ResourceBackend + 'static) -> Self {
+        Self::new(Source::Backend(Arc::new(backend)))
+    }
+
+    /// Records the path of each file read with [`Resources::get`] in the returned list.
+    #[cfg(test)]
+    pub(crate) fn spy_reads(mut self) -> (Self, Arc<Mutex<Vec<PathBuf>>>) {
+        let read_log = Arc::new(Mutex::new(Vec::new()));
+        self.read_log = Some(Arc::clone(&read_log));
+        (self, read_log)
+    }
+
+    /// Creates resources that read files from `base`, except for the given files that
+    /// replace or add content on top of it (for example, the unsaved buffers of an editor).
+    /// Files written or removed through these resources only modify the overlay, so `base`
+    /// is never changed. The written files can be obtained with
+    /// [`Resources::overlay_writes`].
+    pub fn overlay<I, P, S>(base: Resources, files: I) -> Self
+    where
+        I: IntoIterator<Item = (P, S)>,
+        P: AsRef<Path>,
+        S: Into<String>,
+    {
+        let layer = OverlayLayer {
+            files: files
+                .into_iter()
+                .map(|(path, content)| (normalize_path(path.as_ref()), content.into()))
+                .collect(),
+            ..Default::default()
+        };
+
+        Self::new(Source::Overlay {
+            base: Box::new(base.source),
+            layer: Arc::new(Mutex::new(layer)),
+        })
+    }
+
+    /// Returns the files written to overlay resources with their content. For other
+    /// kinds of resources, this map is always empty.
+    pub fn overlay_writes(&self) -> HashMap<PathBuf, String> {
+        match &self.source {
+            Source::Overlay { layer, .. } => {
+                let layer = layer.lock().unwrap();
+                layer
+                    .written
+                    .iter()
+                    .filter_map(|path| {
+                        layer
+                            .files
+                            .get(path)
+                            .map(|content| (path.clone(), content.clone()))
+                    })
+                    .collect()
+            }
+            Source::Backend(_) => HashMap::new(),
         }
     }
 
     pub fn collect_work(&self, location: impl AsRef<Path>) -> impl Iterator<Item = PathBuf> {
-        self.source.walk(location.as_ref()).filter(|path| {
-            matches!(
-                path.extension().and_then(OsStr::to_str),
-                Some("lua") | Some("luau")
-            )
-        })
+        self.collect_work_with_max_depth(location, DEFAULT_MAX_WALK_DEPTH)
+    }
+
+    /// Finds the Lua files under a location without walking directories nested deeper
+    /// than `max_depth` below it.
+    pub(crate) fn collect_work_with_max_depth(
+        &self,
+        location: impl AsRef<Path>,
+        max_depth: usize,
+    ) -> impl Iterator<Item = PathBuf> {
+        self.source
+            .walk(location.as_ref(), max_depth)
+            .filter(|path| {
+                matches!(
+                    path.extension().and_then(OsStr::to_str),
+                    Some("lua") | Some("luau")
+                )
+            })
     }
 
     pub fn exists(&self, location: impl AsRef<Path>) -> ResourceResult<bool> {
@@ -243,9 +651,30 @@ impl Resources {
     }
 
     pub fn get(&self, location: impl AsRef<Path>) -> ResourceResult<String> {
+        #[cfg(test)]
+        if let Some(read_log) = &self.read_log {
+            read_log
+                .lock()
+                .unwrap()
+                .push(normalize_path(location.as_ref()));
+        }
+
         self.source.get(location.as_ref())
     }
 
+    /// Returns a value that changes when the content of a file changes. For files on the
+    /// file system, the value is computed from the metadata of the file so that it is
+    /// not necessary to read it.
+    pub(crate) fn fingerprint(&self, location: impl AsRef<Path>) -> ResourceResult<Option<u64>> {
+        self.source.fingerprint(location.as_ref())
+    }
+
+    /// Returns a path that identifies the resource, whatever path was used to reach it.
+    /// For files on the file system, symbolic links are resolved.
+    pub(crate) fn canonicalize(&self, location: impl AsRef<Path>) -> ResourceResult<PathBuf> {
+        self.source.canonicalize(location.as_ref())
+    }
+
     pub fn write(&self, location: impl AsRef<Path>, content: &str) -> ResourceResult<()> {
         self.source.write(location.as_ref(), content)
     }
@@ -254,26 +683,84 @@ impl Resources {
         self.source.remove(location.as_ref())
     }
 
+    /// Finds every file under a location. Directory loops (like symbolic links to a
+    /// parent directory) are skipped and files reached through multiple paths are
+    /// returned once.
     pub fn walk(&self, location: impl AsRef<Path>) -> impl Iterator<Item = PathBuf> {
-        self.source.walk(location.as_ref())
+        self.source.walk(location.as_ref(), DEFAULT_MAX_WALK_DEPTH)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub enum ResourceError {
     NotFound(PathBuf),
-    IO { path: PathBuf, error: String },
+    /// The file content is not valid UTF-8.
+    InvalidUtf8(PathBuf),
+    IO {
+        path: PathBuf,
+        error: Arc<io::Error>,
+    },
 }
 
 impl ResourceError {
-    pub(crate) fn not_found(path: impl Into<PathBuf>) -> Self {
+    pub fn not_found(path: impl Into<PathBuf>) -> Self {
         Self::NotFound(path.into())
     }
 
-    pub(crate) fn io_error(path: impl Into<PathBuf>, error: io::Error) -> Self {
+    pub fn invalid_utf8(path: impl Into<PathBuf>) -> Self {
+        Self::InvalidUtf8(path.into())
+    }
+
+    pub fn io_error(path: impl Into<PathBuf>, error: io::Error) -> Self {
         Self::IO {
             path: path.into(),
-            error: error.to_string(),
+            error: Arc::new(error),
+        }
+    }
+}
+
+impl PartialEq for ResourceError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::NotFound(path), Self::NotFound(other_path))
+            | (Self::InvalidUtf8(path), Self::InvalidUtf8(other_path)) => path == other_path,
+            (
+                Self::IO { path, error },
+                Self::IO {
+                    path: other_path,
+                    error: other_error,
+                },
+            ) => {
+                path == other_path
+                    && error.kind() == other_error.kind()
+                    && error.to_string() == other_error.to_string()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ResourceError {}
+
+impl fmt::Display for ResourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(path) => write!(f, "unable to find `{}`", path.display()),
+            Self::InvalidUtf8(path) => write!(
+                f,
+                "unable to read `{}`: the file does not contain valid UTF-8",
+                path.display()
+            ),
+            Self::IO { path, error } => write!(f, "IO error with `{}`: {}", path.display(), error),
+        }
+    }
+}
+
+impl std::error::Error for ResourceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::NotFound(_) | Self::InvalidUtf8(_) => None,
+            Self::IO { error, .. } => Some(&**error),
         }
     }
 }
@@ -357,4 +844,122 @@ mod test {
             );
         }
     }
+
+    mod overlay {
+        use std::iter::FromIterator;
+
+        use super::*;
+
+        const OVERLAY_CONTENT: &str = "return false";
+
+        fn new() -> (Resources, Resources) {
+            let base = Resources::from_memory();
+            base.write(any_path(), ANY_CONTENT).unwrap();
+            base.write("src/base.lua", ANY_CONTENT).unwrap();
+            let overlay =
+                Resources::overlay(base.clone(), vec![("src/overlay.lua", OVERLAY_CONTENT)]);
+            (base, overlay)
+        }
+
+        #[test]
+        fn read_base_file() {
+            let (_, resources) = new();
+
+            assert_eq!(resources.get(any_path()), Ok(ANY_CONTENT.to_string()));
+        }
+
+        #[test]
+        fn read_overlay_file() {
+            let (_, resources) = new();
+
+            assert_eq!(
+                resources.get("src/overlay.lua"),
+                Ok(OVERLAY_CONTENT.to_string())
+            );
+        }
+
+        #[test]
+        fn overlay_file_replaces_base_file() {
+            let base = Resources::from_memory();
+            base.write(any_path(), ANY_CONTENT).unwrap();
+            let resources = Resources::overlay(base, vec![(any_path(), OVERLAY_CONTENT)]);
+
+            assert_eq!(resources.get(any_path()), Ok(OVERLAY_CONTENT.to_string()));
+        }
+
+        #[test]
+        fn files_from_both_layers_exist() {
+            let (_, resources) = new();
+
+            assert_eq!(resources.exists(any_path()), Ok(true));
+            assert_eq!(resources.exists("src/overlay.lua"), Ok(true));
+            assert_eq!(resources.is_file("src/overlay.lua"), Ok(true));
+            assert_eq!(resources.is_directory("src"), Ok(true));
+            assert_eq!(resources.exists("src/missing.lua"), Ok(false));
+        }
+
+        #[test]
+        fn write_does_not_modify_base() {
+            let (base, resources) = new();
+
+            resources.write(any_path(), OVERLAY_CONTENT).unwrap();
+
+            assert_eq!(resources.get(any_path()), Ok(OVERLAY_CONTENT.to_string()));
+            assert_eq!(base.get(any_path()), Ok(ANY_CONTENT.to_string()));
+        }
+
+        #[test]
+        fn overlay_writes_contains_written_files_only() {
+            let (_, resources) = new();
+
+            resources.write("out/test.lua", OVERLAY_CONTENT).unwrap();
+
+            assert_eq!(
+                resources.overlay_writes(),
+                HashMap::from_iter(vec![(
+                    PathBuf::from("out/test.lua"),
+                    OVERLAY_CONTENT.to_string()
+                )])
+            );
+        }
+
+        #[test]
+        fn removed_base_file_does_not_exist() {
+            let (base, resources) = new();
+
+            resources.remove(any_path()).unwrap();
+
+            assert_eq!(resources.exists(any_path()), Ok(false));
+            assert_eq!(base.exists(any_path()), Ok(true));
+        }
+
+        #[test]
+        fn removed_directory_can_be_written_again() {
+            let (_, resources) = new();
+
+            resources.remove("src").unwrap();
+            resources.write("src/new.lua", OVERLAY_CONTENT).unwrap();
+
+            assert_eq!(resources.exists("src/base.lua"), Ok(false));
+            assert_eq!(resources.exists("src/overlay.lua"), Ok(false));
+            assert_eq!(resources.exists("src/new.lua"), Ok(true));
+        }
+
+        #[test]
+        fn collect_work_merges_both_layers() {
+            let (_, resources) = new();
+            resources.write("src/base.lua", OVERLAY_CONTENT).unwrap();
+
+            let mut work = Vec::from_iter(resources.collect_work("src"));
+            work.sort();
+
+            assert_eq!(
+                work,
+                vec![
+                    PathBuf::from("src/base.lua"),
+                    PathBuf::from("src/overlay.lua")
+                ]
+            );
+        }
+    }
 }
@@ -0,0 +1,863 @@
+//! Reads the comments that disable rules for some statements of a file:
+//!
+//! - `-- darklua-disable-next-line rule_name, other_rule` disables the rules for the
+//!   statement that starts on the next line
+//! - `-- darklua-disable rule_name` and `-- darklua-enable rule_name` disable the rules for
+//!   the statements that start between the two comments
+//!
+//! The `all` keyword can be used instead of the rule names to disable every rule. Before
+//! running a rule, the statements where it is disabled are replaced with placeholders, and
+//! they are put back once the rule is done. A placeholder declares, reads and writes the
+//! same variables as its statement, so that the rule still sees how these variables are
+//! used. If the rule renames them, the restored statement is renamed too.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::nodes::{
+    AssignStatement, Block, CompoundAssignStatement, Expression, FunctionCall, FunctionExpression,
+    FunctionStatement, Identifier, LocalAssignStatement, LocalFunctionStatement, Prefix, Statement,
+    TupleArguments, TypeField, Variable,
+};
+use crate::process::{DefaultVisitor, NodeProcessor, NodeVisitor, Scope, ScopeVisitor};
+use crate::rules::get_all_rule_names;
+
+const DISABLE_NEXT_LINE_DIRECTIVE: &str = "darklua-disable-next-line";
+const DISABLE_DIRECTIVE: &str = "darklua-disable";
+const ENABLE_DIRECTIVE: &str = "darklua-enable";
+const ALL_RULES: &str = "all";
+const DISABLED_STATEMENT_PREFIX: &str = "__DARKLUA_DISABLED_STATEMENT_";
+
+/// Returns `true` if the code may contain directives. Directives are read from the
+/// comments attached to the tokens, so these files must be parsed with their tokens.
+pub(crate) fn may_contain_directives(code: &str) -> bool {
+    code.contains(DISABLE_DIRECTIVE)
+}
+
+/// The lines (both included) where a rule is disabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LineRange {
+    start: usize,
+    end: usize,
+}
+
+impl LineRange {
+    fn contains(&self, line: usize) -> bool {
+        self.start <= line && line <= self.end
+    }
+}
+
+#[derive(Debug)]
+struct DirectiveComment {
+    text: String,
+    offset: usize,
+    length: usize,
+}
+
+/// The lines where each rule is disabled in a file.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RuleDirectives {
+    // the disabled lines for each rule name (or for `all`)
+    disabled_lines: HashMap<String, Vec<LineRange>>,
+    // the offset where each line of the file starts, to find the line of a token even after
+    // the bundler shifted the line numbers
+    line_starts: Vec<usize>,
+    // the offsets where the statements parsed from this file start, so that statements
+    // coming from another source (like bundled modules) are never disabled
+    statement_offsets: HashSet<usize>,
+}
+
+impl RuleDirectives {
+    /// Reads the directives from the comments of the block. Directives with unknown rule
+    /// names are ignored and logged as warnings.
+    pub(crate) fn read(block: &mut Block, code: &str, path: &Path) -> Self {
+        if !may_contain_directives(code) {
+            return Self::default();
+        }
+
+        let mut comments = Vec::new();
+
+        block.for_each_deep_token(|token| {
+            for comment in token
+                .iter_leading_comments(code)
+                .chain(token.iter_trailing_comments(code))
+            {
+                let text = comment.text().trim();
+
+                if !text.starts_with(DISABLE_DIRECTIVE) && !text.starts_with(ENABLE_DIRECTIVE) {
+                    continue;
+                }
+
+                if let Some(offset) = comment.position().and_then(|position| position.offset()) {
+                    comments.push(DirectiveComment {
+                        text: text.to_owned(),
+                        offset,
+                        length: comment.content().len(),
+                    });
+                }
+            }
+        });
+
+        comments.sort_by_key(|comment| comment.offset);
+
+        let mut offsets = StatementOffsets::default();
+        DefaultVisitor::visit_block(block, &mut offsets);
+
+        let mut directives = Self {
+            disabled_lines: HashMap::new(),
+            line_starts: get_line_starts(code),
+            statement_offsets: offsets.offsets,
+        };
+        let known_rules = get_all_rule_names();
+
+        // the line where each region starts, with the number of nested regions
+        let mut open_regions: HashMap<String, (usize, usize)> = HashMap::new();
+
+        for comment in comments {
+            let mut parts = comment.text.splitn(2, char::is_whitespace);
+            let directive = parts.next().unwrap_or_default();
+
+            if !matches!(
+                directive,
+                DISABLE_NEXT_LINE_DIRECTIVE | DISABLE_DIRECTIVE | ENABLE_DIRECTIVE
+            ) {
+                continue;
+            }
+
+            let rule_names: Vec<_> = parts
+                .next()
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .collect();
+
+            let line = directives.line_at(comment.offset);
+            let end_line = directives.line_at(comment.offset + comment.length.saturating_sub(1));
+            let location = format_location(path, code, line, comment.offset);
+
+            if rule_names.is_empty() {
+                log::warn!(
+                    "`{}` expects rule names or `{}` (at `{}`)",
+                    directive,
+                    ALL_RULES,
+                    location
+                );
+                continue;
+            }
+
+            for name in rule_names {
+                if name != ALL_RULES && !known_rules.iter().any(|known| *known == name) {
+                    log::warn!(
+                        "unknown rule `{}` in `{}` (at `{}`)",
+                        name,
+                        directive,
+                        location
+                    );
+                    continue;
+                }
+
+                match directive {
+                    DISABLE_NEXT_LINE_DIRECTIVE => {
+                        directives.disable_lines(name, end_line + 1, end_line + 1);
+                    }
+                    DISABLE_DIRECTIVE => {
+                        open_regions
+                            .entry(name.to_owned())
+                            .or_insert((end_line, 0))
+                            .1 += 1;
+                    }
+                    _ => {
+                        let closed = match open_regions.get_mut(name) {
+                            Some(region) => {
+                                region.1 -= 1;
+                                region.1 == 0
+                            }
+                            None => {
+                                log::warn!(
+                                    "`{} {}` does not close a `{}` comment (at `{}`)",
+                                    ENABLE_DIRECTIVE,
+                                    name,
+                                    DISABLE_DIRECTIVE,
+                                    location
+                                );
+                                continue;
+                            }
+                        };
+
+                        if closed {
+                            if let Some((start, _)) = open_regions.remove(name) {
+                                directives.disable_lines(name, start + 1, line.saturating_sub(1));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // regions that are never closed continue until the end of the file
+        for (name, (start, _)) in open_regions {
+            directives.disable_lines(&name, start + 1, usize::MAX);
+        }
+
+        directives
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.disabled_lines.is_empty()
+    }
+
+    fn disable_lines(&mut self, name: &str, start: usize, end: usize) {
+        if start <= end {
+            self.disabled_lines
+                .entry(name.to_owned())
+                .or_default()
+                .push(LineRange { start, end });
+        }
+    }
+
+    fn line_at(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(index) => index + 1,
+            Err(index) => index,
+        }
+    }
+
+    /// Replaces the statements where the rule is disabled with placeholders, so that the
+    /// rule does not process them. Only the statements parsed from this file can be
+    /// disabled. They must be put back with [`DisabledStatements::restore`]
+    /// after the rule is applied.
+    pub(crate) fn disable_statements(
+        &self,
+        rule_name: &str,
+        block: &mut Block,
+    ) -> DisabledStatements {
+        let ranges: Vec<LineRange> = self
+            .disabled_lines
+            .get(rule_name)
+            .into_iter()
+            .chain(self.disabled_lines.get(ALL_RULES))
+            .flatten()
+            .copied()
+            .collect();
+
+        if ranges.is_empty() {
+            return DisabledStatements::default();
+        }
+
+        let mut detacher = StatementDetacher {
+            directives: self,
+            ranges,
+            statements: Vec::new(),
+        };
+        DefaultVisitor::visit_block(block, &mut detacher);
+
+        DisabledStatements {
+            statements: detacher.statements,
+        }
+    }
+}
+
+fn get_line_starts(code: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(code.match_indices('\n').map(|(index, _)| index + 1))
+        .collect()
+}
+
+fn format_location(path: &Path, code: &str, line: usize, offset: usize) -> String {
+    let line_start = code
+        .get(..offset)
+        .and_then(|before| before.rfind('\n'))
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    let column = code
+        .get(line_start..offset)
+        .map(|before| before.chars().count() + 1)
+        .unwrap_or(1);
+
+    format!("{}:{}:{}", path.display(), line, column)
+}
+
+fn get_placeholder_index(call: &FunctionCall) -> Option<usize> {
+    if call.get_method().is_some() {
+        return None;
+    }
+
+    match call.get_prefix() {
+        Prefix::Identifier(identifier) => identifier
+            .get_name()
+            .strip_prefix(DISABLED_STATEMENT_PREFIX)?
+            .parse()
+            .ok(),
+        _ => None,
+    }
+}
+
+fn get_single_call<'a>(
+    mut values: impl Iterator<Item = &'a Expression>,
+) -> Option<&'a FunctionCall> {
+    match (values.next(), values.next()) {
+        (Some(Expression::Call(call)), None) => Some(call.as_ref()),
+        _ => None,
+    }
+}
+
+/// Finds the placeholder call of a statement, which can be used directly as a statement, or
+/// as the only value of an assignment when the disabled statement declares or writes
+/// variables.
+fn find_placeholder(statement: &Statement) -> Option<(usize, &FunctionCall)> {
+    let call = match statement {
+        Statement::Call(call) => call,
+        Statement::LocalAssign(local_assign) => get_single_call(local_assign.iter_values())?,
+        Statement::Assign(assign) => get_single_call(assign.iter_values())?,
+        _ => return None,
+    };
+
+    get_placeholder_index(call).map(|index| (index, call))
+}
+
+#[derive(Default)]
+struct StatementOffsets {
+    offsets: HashSet<usize>,
+}
+
+impl NodeProcessor for StatementOffsets {
+    fn process_statement(&mut self, statement: &mut Statement) {
+        if let Some(offset) = statement
+            .start_position()
+            .and_then(|position| position.offset())
+        {
+            self.offsets.insert(offset);
+        }
+    }
+}
+
+/// The variables that a statement declares, and the variables from outside the statement
+/// that it reads or writes, in the order where they are found.
+#[derive(Debug, Clone, Default)]
+struct Footprint {
+    declared: Vec<String>,
+    reads: Vec<String>,
+    writes: Vec<String>,
+}
+
+impl Footprint {
+    fn from_statement(statement: &mut Statement) -> Self {
+        let mut collector = FootprintCollector::default();
+        collector.push();
+        ScopeVisitor::visit_statement(statement, &mut collector);
+        collector.pop();
+        collector.footprint
+    }
+
+    /// Creates a placeholder with the same footprint:
+    ///
+    /// - `local d1, d2 = placeholder(r1, r2, function() w1, w2 = nil end)` when the statement
+    ///   declares variables
+    /// - `w1, w2 = placeholder(r1, r2)` when the statement writes variables
+    /// - `placeholder(r1, r2)` otherwise
+    fn create_placeholder(&self, index: usize) -> Statement {
+        let mut arguments = TupleArguments::new(
+            self.reads
+                .iter()
+                .map(|name| Expression::identifier(name.as_str()))
+                .collect(),
+        );
+
+        if !self.declared.is_empty() && !self.writes.is_empty() {
+            arguments = arguments.with_argument(FunctionExpression::from_block(
+                self.create_writes(Expression::nil()),
+            ));
+        }
+
+        let call = FunctionCall::from_name(format!("{}{}", DISABLED_STATEMENT_PREFIX, index))
+            .with_arguments(arguments);
+
+        if !self.declared.is_empty() {
+            LocalAssignStatement::new(
+                self.declared
+                    .iter()
+                    .map(String::as_str)
+                    .map(Into::into)
+                    .collect(),
+                vec![call.into()],
+            )
+            .into()
+        } else if !self.writes.is_empty() {
+            self.create_writes(call.into())
+        } else {
+            call.into()
+        }
+    }
+
+    fn create_writes(&self, value: Expression) -> Statement {
+        AssignStatement::new(self.writes.iter().map(Variable::new).collect(), vec![value]).into()
+    }
+
+    /// Reads the names that the rule gave to the variables of the placeholder.
+    fn read_renames(&self, placeholder: &Statement, call: &FunctionCall) -> Renames {
+        let mut renames = Renames::default();
+
+        let arguments: Vec<_> = call
+            .get_arguments()
+            .iter_values()
+            .filter_map(|argument| argument.as_expression())
+            .collect();
+
+        for (name, argument) in self.reads.iter().zip(arguments.iter().copied()) {
+            if let Expression::Identifier(identifier) = argument {
+                renames.add_outer(name, identifier.get_name());
+            }
+        }
+
+        let written = match placeholder {
+            Statement::Assign(assign) => Some(assign),
+            Statement::LocalAssign(_) => match arguments.last().copied() {
+                Some(Expression::Function(function)) if arguments.len() > self.reads.len() => {
+                    match function.get_block().first_statement() {
+                        Some(Statement::Assign(assign)) => Some(assign),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+
+        if let Some(assign) = written {
+            if assign.variables_len() == self.writes.len() {
+                for (name, variable) in self.writes.iter().zip(assign.iter_variables()) {
+                    if let Variable::Identifier(identifier) = variable {
+                        renames.add_outer(name, identifier.get_name());
+                    }
+                }
+            }
+        }
+
+        if let Statement::LocalAssign(local_assign) = placeholder {
+            if local_assign.variables_len() == self.declared.len() {
+                for (name, variable) in self.declared.iter().zip(local_assign.iter_variables()) {
+                    renames.add_declared(name, variable.get_name());
+                }
+            }
+        }
+
+        renames
+    }
+}
+
+fn push_unique(names: &mut Vec<String>, name: &str) {
+    if !names.iter().any(|existing| existing == name) {
+        names.push(name.to_owned());
+    }
+}
+
+/// Finds the footprint of a statement. The scope pushed around the statement receives the
+/// variables that it declares.
+#[derive(Default)]
+struct FootprintCollector {
+    scopes: Vec<HashSet<String>>,
+    pending_write: bool,
+    compound_assignment: bool,
+    footprint: Footprint,
+}
+
+impl FootprintCollector {
+    fn declare(&mut self, name: &str) {
+        if self.scopes.len() == 1 {
+            push_unique(&mut self.footprint.declared, name);
+        }
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_owned());
+        }
+    }
+
+    fn reference(&mut self, name: &str, read: bool, write: bool) {
+        if self.scopes.iter().any(|scope| scope.contains(name)) {
+            return;
+        }
+        if read {
+            push_unique(&mut self.footprint.reads, name);
+        }
+        if write {
+            push_unique(&mut self.footprint.writes, name);
+        }
+    }
+}
+
+impl Scope for FootprintCollector {
+    fn push(&mut self) {
+        self.scopes.push(HashSet::new());
+    }
+
+    fn pop(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn insert(&mut self, identifier: &mut String) {
+        self.declare(identifier);
+    }
+
+    fn insert_self(&mut self) {
+        self.declare("self");
+    }
+
+    fn insert_local(&mut self, identifier: &mut String, _value: Option<&mut Expression>) {
+        self.declare(identifier);
+    }
+
+    fn insert_local_function(&mut self, function: &mut LocalFunctionStatement) {
+        self.declare(function.get_name());
+    }
+}
+
+impl NodeProcessor for FootprintCollector {
+    fn process_variable(&mut self, variable: &mut Variable) {
+        let compound_assignment = std::mem::take(&mut self.compound_assignment);
+
+        if let Variable::Identifier(identifier) = variable {
+            self.reference(identifier.get_name(), compound_assignment, true);
+            self.pending_write = true;
+        }
+    }
+
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        if !std::mem::take(&mut self.pending_write) {
+            self.reference(identifier.get_name(), true, false);
+        }
+    }
+
+    fn process_type_field(&mut self, type_field: &mut TypeField) {
+        self.reference(type_field.get_namespace().get_name(), true, false);
+    }
+
+    fn process_compound_assign_statement(&mut self, _: &mut CompoundAssignStatement) {
+        self.compound_assignment = true;
+    }
+
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        let name = function.get_name();
+        if name.get_field_names().is_empty() && !name.has_method() {
+            self.reference(name.get_name().get_name(), false, true);
+            self.pending_write = true;
+        }
+    }
+}
+/// The names given by a rule to the variables of a placeholder, for the variables declared
+/// by the statement and for the variables from outside the statement.
+#[derive(Debug, Default)]
+struct Renames {
+    declared: HashMap<String, String>,
+    outer: HashMap<String, String>,
+}
+
+impl Renames {
+    fn add_declared(&mut self, name: &str, new_name: &str) {
+        if name != new_name {
+            self.declared
+                .entry(name.to_owned())
+                .or_insert_with(|| new_name.to_owned());
+        }
+    }
+
+    fn add_outer(&mut self, name: &str, new_name: &str) {
+        if name != new_name {
+            self.outer
+                .entry(name.to_owned())
+                .or_insert_with(|| new_name.to_owned());
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.declared.is_empty() && self.outer.is_empty()
+    }
+}
+
+/// Applies the renames of a placeholder to its statement. Like the [`FootprintCollector`],
+/// the scope pushed around the statement receives the variables that it declares.
+struct StatementRenamer<'a> {
+    renames: &'a Renames,
+    scopes: Vec<HashSet<String>>,
+}
+
+impl<'a> StatementRenamer<'a> {
+    fn new(renames: &'a Renames) -> Self {
+        Self {
+            renames,
+            scopes: Vec::new(),
+        }
+    }
+
+    fn get_new_name(&self, name: &str) -> Option<&'a String> {
+        match self.scopes.iter().rposition(|scope| scope.contains(name)) {
+            Some(0) => self.renames.declared.get(name),
+            Some(_) => None,
+            None => self.renames.outer.get(name),
+        }
+    }
+
+    fn rename(&self, identifier: &mut Identifier) {
+        if let Some(new_name) = self.get_new_name(identifier.get_name()) {
+            identifier.set_name(new_name.as_str());
+        }
+    }
+
+    fn declare(&mut self, name: &str) -> Option<&'a String> {
+        let is_statement_scope = self.scopes.len() == 1;
+
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_owned());
+        }
+
+        if is_statement_scope {
+            self.renames.declared.get(name)
+        } else {
+            None
+        }
+    }
+}
+
+impl Scope for StatementRenamer<'_> {
+    fn push(&mut self) {
+        self.scopes.push(HashSet::new());
+    }
+
+    fn pop(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn insert(&mut self, identifier: &mut String) {
+        if let Some(new_name) = self.declare(identifier) {
+            *identifier = new_name.clone();
+        }
+    }
+
+    fn insert_self(&mut self) {
+        self.declare("self");
+    }
+
+    fn insert_local(&mut self, identifier: &mut String, _value: Option<&mut Expression>) {
+        self.insert(identifier);
+    }
+
+    fn insert_local_function(&mut self, function: &mut LocalFunctionStatement) {
+        let name = function.get_name().to_owned();
+        if let Some(new_name) = self.declare(&name) {
+            function.mutate_identifier().set_name(new_name.as_str());
+        }
+    }
+}
+
+impl NodeProcessor for StatementRenamer<'_> {
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        self.rename(identifier);
+    }
+
+    fn process_type_field(&mut self, type_field: &mut TypeField) {
+        self.rename(type_field.mutate_namespace());
+    }
+}
+
+#[derive(Debug)]
+struct DisabledStatement {
+    statement: Statement,
+    footprint: Footprint,
+}
+
+impl DisabledStatement {
+    fn restore(&self, placeholder: &Statement, call: &FunctionCall) -> Statement {
+        let mut statement = self.statement.clone();
+        let renames = self.footprint.read_renames(placeholder, call);
+
+        if !renames.is_empty() {
+            let mut renamer = StatementRenamer::new(&renames);
+            renamer.push();
+            ScopeVisitor::visit_statement(&mut statement, &mut renamer);
+            renamer.pop();
+        }
+
+        statement
+    }
+}
+
+struct StatementDetacher<'a> {
+    directives: &'a RuleDirectives,
+    ranges: Vec<LineRange>,
+    statements: Vec<DisabledStatement>,
+}
+
+impl StatementDetacher<'_> {
+    fn is_disabled(&self, statement: &Statement) -> bool {
+        statement
+            .start_position()
+            .and_then(|position| position.offset())
+            .filter(|offset| self.directives.statement_offsets.contains(offset))
+            .map(|offset| self.directives.line_at(offset))
+            .map_or(false, |line| {
+                self.ranges.iter().any(|range| range.contains(line))
+            })
+    }
+}
+
+impl NodeProcessor for StatementDetacher<'_> {
+    fn process_block(&mut self, block: &mut Block) {
+        for statement in block.iter_mut_statements() {
+            if self.is_disabled(statement) {
+                let footprint = Footprint::from_statement(statement);
+                let placeholder = footprint.create_placeholder(self.statements.len());
+
+                self.statements.push(DisabledStatement {
+                    statement: std::mem::replace(statement, placeholder),
+                    footprint,
+                });
+            }
+        }
+    }
+}
+
+/// The statements removed from a block while a rule is disabled for them.
+#[derive(Debug, Default)]
+pub(crate) struct DisabledStatements {
+    statements: Vec<DisabledStatement>,
+}
+
+impl DisabledStatements {
+    /// Puts back the statements in place of their placeholders. If the rule duplicated a
+    /// placeholder, each copy is replaced with the statement, and if the rule removed it,
+    /// the statement is dropped.
+    pub(crate) fn restore(self, block: &mut Block) {
+        if self.statements.is_empty() {
+            return;
+        }
+
+        let mut restorer = StatementRestorer {
+            statements: self.statements,
+        };
+        DefaultVisitor::visit_block(block, &mut restorer);
+    }
+}
+
+struct StatementRestorer {
+    statements: Vec<DisabledStatement>,
+}
+
+impl StatementRestorer {
+    fn restore_placeholder(&self, statement: &Statement) -> Option<Statement> {
+        let (index, call) = find_placeholder(statement)?;
+
+        self.statements
+            .get(index)
+            .map(|disabled| disabled.restore(statement, call))
+    }
+}
+
+impl NodeProcessor for StatementRestorer {
+    fn process_block(&mut self, block: &mut Block) {
+        for statement in block.iter_mut_statements() {
+            if let Some(restored) = self.restore_placeholder(statement) {
+                *statement = restored;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{generator::LuaGenerator, generator::TokenBasedLuaGenerator, Parser};
+
+    fn read_directives(code: &str) -> (Block, RuleDirectives) {
+        let mut block = Parser::default()
+            .preserve_tokens()
+            .parse(code)
+            .expect("unable to parse code");
+        let directives = RuleDirectives::read(&mut block, code, Path::new("src/test.lua"));
+        (block, directives)
+    }
+
+    fn generate(block: &Block, code: &str) -> String {
+        let mut generator = TokenBasedLuaGenerator::new(code);
+        generator.write_block(block);
+        generator.into_string()
+    }
+
+    #[test]
+    fn code_without_directives_is_empty() {
+        let (_, directives) = read_directives("local a = 1\nreturn a\n");
+
+        assert!(directives.is_empty());
+    }
+
+    #[test]
+    fn unknown_rule_is_ignored() {
+        let (_, directives) = read_directives("-- darklua-disable-next-line not_a_rule\ndo end\n");
+
+        assert!(directives.is_empty());
+    }
+
+    #[test]
+    fn enable_without_disable_is_ignored() {
+        let (_, directives) =
+            read_directives("do end\n-- darklua-enable remove_empty_do\ndo end\n");
+
+        assert!(directives.is_empty());
+    }
+
+    #[test]
+    fn disabled_statement_is_restored() {
+        let code = "local a = 1\n-- darklua-disable-next-line all\nlocal b = 2\n";
+        let (mut block, directives) = read_directives(code);
+
+        let disabled = directives.disable_statements("remove_empty_do", &mut block);
+
+        assert_eq!(disabled.statements.len(), 1);
+
+        disabled.restore(&mut block);
+
+        pretty_assertions::assert_eq!(generate(&block, code), code);
+    }
+
+    #[test]
+    fn removed_placeholder_drops_the_statement() {
+        let code = "-- darklua-disable-next-line all\nlocal b = 2\n";
+        let (mut block, directives) = read_directives(code);
+
+        let disabled = directives.disable_statements("remove_empty_do", &mut block);
+        block.clear();
+        disabled.restore(&mut block);
+
+        assert!(block.is_empty());
+    }
+
+    #[test]
+    fn placeholder_has_the_footprint_of_the_statement() {
+        let code = "-- darklua-disable-next-line all\nlocal function f(n) count = count + n return f, print end\n";
+        let (mut block, directives) = read_directives(code);
+
+        let _disabled = directives.disable_statements("remove_empty_do", &mut block);
+
+        let expected = Parser::default()
+            .parse("local f = __DARKLUA_DISABLED_STATEMENT_0(count, print, function() count = nil end)")
+            .expect("unable to parse code");
+
+        pretty_assertions::assert_eq!(block.first_statement(), expected.first_statement());
+    }
+
+    #[test]
+    fn statements_from_another_source_are_not_disabled() {
+        let code = "-- darklua-disable-next-line all\ndo end\n";
+        let (mut block, directives) = read_directives(code);
+
+        // the module statement starts at an offset that is on the disabled line of the file
+        let module_code = format!("{}do end\n", " ".repeat(36));
+        let module = Parser::default()
+            .preserve_tokens()
+            .parse(&module_code)
+            .expect("unable to parse module");
+        block.push_statement(module.first_statement().unwrap().clone());
+
+        let disabled = directives.disable_statements("remove_empty_do", &mut block);
+
+        assert_eq!(disabled.statements.len(), 1);
+    }
+}
@@ -6,7 +6,7 @@ use std::{
 
 use elsa::FrozenMap;
 
-use crate::{nodes::Block, DarkluaError, Parser, Resources};
+use crate::{nodes::Block, utils::LuauConfigurationCache, DarkluaError, Parser, Resources};
 
 use super::DarkluaResult;
 
@@ -14,6 +14,7 @@ pub struct WorkCache<'a> {
     resources: &'a Resources,
     input_to_block: FrozenMap<PathBuf, Box<Block>>,
     input_to_output: HashMap<PathBuf, PathBuf>,
+    luau_configurations: LuauConfigurationCache,
 }
 
 impl Clone for WorkCache<'_> {
@@ -22,6 +23,7 @@ impl Clone for WorkCache<'_> {
             resources: self.resources,
             input_to_block: Default::default(),
             input_to_output: self.input_to_output.clone(),
+            luau_configurations: self.luau_configurations.clone(),
         }
     }
 }
@@ -41,6 +43,7 @@ impl<'a> WorkCache<'a> {
             resources,
             input_to_block: Default::default(),
             input_to_output: Default::default(),
+            luau_configurations: Default::default(),
         }
     }
 
@@ -52,6 +55,16 @@ impl<'a> WorkCache<'a> {
         self.input_to_output.insert(source.into(), output.into());
     }
 
+    pub fn merge(&mut self, other: WorkCache) {
+        self.input_to_output.extend(other.input_to_output);
+    }
+
+    /// The `.luaurc` configurations found during this process run, shared with the forks
+    /// of the worker.
+    pub(crate) fn luau_configurations(&self) -> &LuauConfigurationCache {
+        &self.luau_configurations
+    }
+
     pub fn contains(&self, source: impl AsRef<Path>) -> bool {
         self.input_to_output.contains_key(source.as_ref())
     }
@@ -1,11 +1,15 @@
 use std::{
     collections::HashSet,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 use crate::{nodes::Block, utils::Timer};
 
-use super::{DarkluaError, DarkluaResult};
+use super::{
+    rule_directives::RuleDirectives, AppliedRule, BundleManifest, DarkluaError, DarkluaResult,
+    ModuleEdge,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct Progress {
@@ -13,6 +17,7 @@ pub(crate) struct Progress {
     next_rule: usize,
     required: Vec<PathBuf>,
     duration: Timer,
+    steps: u64,
 }
 
 impl Progress {
@@ -22,6 +27,7 @@ impl Progress {
             next_rule: 0,
             required: Vec::new(),
             duration: Timer::now(),
+            steps: 0,
         }
     }
 
@@ -48,19 +54,30 @@ impl Progress {
     pub(crate) fn duration(&mut self) -> &mut Timer {
         &mut self.duration
     }
+
+    /// The number of steps consumed by the rules applied so far (see [`FileBudget`](super::FileBudget)).
+    pub(crate) fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub(crate) fn add_steps(&mut self, steps: u64) {
+        self.steps += steps;
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct WorkProgress {
     pub(crate) content: String,
     pub(crate) progress: Progress,
+    pub(crate) directives: RuleDirectives,
 }
 
 impl WorkProgress {
-    pub(crate) fn new(content: String, block: Block) -> Self {
+    pub(crate) fn new(content: String, block: Block, directives: RuleDirectives) -> Self {
         Self {
             content,
             progress: Progress::new(block),
+            directives,
         }
     }
 
@@ -122,11 +139,56 @@ impl WorkData {
     }
 }
 
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RuleDurations {
+    durations: Vec<(String, Duration)>,
+}
+
+impl RuleDurations {
+    /// Adds the duration to the time spent by the rule, so that a rule applied multiple
+    /// times accumulates its durations.
+    pub(crate) fn record(&mut self, rule_name: &str, duration: Duration) {
+        match self
+            .durations
+            .iter_mut()
+            .find(|(name, _)| name == rule_name)
+        {
+            Some((_, total)) => *total += duration,
+            None => self.durations.push((rule_name.to_owned(), duration)),
+        }
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&str, Duration)> {
+        self.durations
+            .iter()
+            .map(|(name, duration)| (name.as_str(), *duration))
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.durations.clear();
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct WorkItem {
     pub(crate) data: WorkData,
     pub(crate) status: WorkStatus,
     pub(crate) external_file_dependencies: HashSet<PathBuf>,
+    pub(crate) artifacts: Vec<PathBuf>,
+    /// Set when the output was already containing the generated code.
+    pub(crate) unchanged_output: bool,
+    /// The time spent by each rule, when rule timings are measured.
+    pub(crate) rule_durations: RuleDurations,
+    /// The require calls found by the rules, when the module graph is collected.
+    pub(crate) module_edges: Vec<ModuleEdge>,
+    /// The rules that modified the file, when the applied rules are collected.
+    pub(crate) applied_rules: Vec<AppliedRule>,
+    /// The modules inlined in the file, when it is bundled and the manifest is collected.
+    pub(crate) bundle_manifest: Option<BundleManifest>,
+    /// Set when the file was found in the input directory instead of being the input.
+    pub(crate) collected: bool,
+    /// The reason why the file was not processed, when it was skipped.
+    pub(crate) skip_reason: Option<String>,
 }
 
 impl WorkItem {
@@ -138,6 +200,14 @@ impl WorkItem {
             },
             status: Default::default(),
             external_file_dependencies: Default::default(),
+            artifacts: Vec::new(),
+            unchanged_output: false,
+            rule_durations: Default::default(),
+            module_edges: Vec::new(),
+            applied_rules: Vec::new(),
+            bundle_manifest: None,
+            collected: false,
+            skip_reason: None,
         }
     }
 
@@ -160,5 +230,12 @@ impl WorkItem {
     pub(crate) fn reset(&mut self) {
         self.status = WorkStatus::NotStarted;
         self.external_file_dependencies.clear();
+        self.artifacts.clear();
+        self.unchanged_output = false;
+        self.rule_durations.clear();
+        self.module_edges.clear();
+        self.applied_rules.clear();
+        self.bundle_manifest = None;
+        self.skip_reason = None;
     }
 }
@@ -1,29 +1,47 @@
-use std::path::Path;
+use std::{collections::BTreeMap, path::Path, sync::Arc};
 
 use super::{
-    configuration::Configuration,
-    resources::Resources,
+    configuration::{configuration_from_value, BundleConfiguration, Configuration},
+    configuration_extends::{needs_resolution, parse_configuration_value, resolve_extends},
+    count_modifications,
+    process_cache::ProcessCache,
+    resources::{ResourceError, Resources},
+    rule_directives::{may_contain_directives, RuleDirectives},
     utils::maybe_plural,
     work_cache::WorkCache,
     work_item::{WorkItem, WorkProgress, WorkStatus},
-    DarkluaError, DarkluaResult, Options,
+    AppliedRule, DarkluaError, DarkluaResult, FileBudget, Options,
 };
 
 use crate::{
     nodes::Block,
-    rules::{bundle::Bundler, ContextBuilder, Rule, RuleConfiguration},
-    utils::{normalize_path, Timer},
+    process::track_budget,
+    rules::{
+        bundle::Bundler, require::remap_require_extensions, Context, ContextBuilder, Rule,
+        RuleConfiguration,
+    },
+    utils::{estimate_code_depth, exceeds_depth_limit, normalize_path, Timer},
     GeneratorParameters,
 };
 
-const DEFAULT_CONFIG_PATHS: [&str; 2] = [".darklua.json", ".darklua.json5"];
+const DEFAULT_CONFIG_PATHS: [&str; 3] = [".darklua.json", ".darklua.json5", ".darklua.toml"];
 
 #[derive(Debug)]
 pub(crate) struct Worker<'a> {
     resources: &'a Resources,
     cache: WorkCache<'a>,
-    configuration: Configuration,
+    configuration: Arc<Configuration>,
     cached_bundler: Option<Bundler>,
+    process_cache: Option<ProcessCache>,
+    output_extensions: BTreeMap<String, String>,
+    measure_rule_timings: bool,
+    collect_module_graph: bool,
+    collect_applied_rules: bool,
+    collect_bundle_manifests: bool,
+    always_write_outputs: bool,
+    recover_syntax_errors: bool,
+    file_budget: Option<FileBudget>,
+    depth_limit: Option<usize>,
 }
 
 impl<'a> Worker<'a> {
@@ -31,16 +49,27 @@ impl<'a> Worker<'a> {
         Self {
             resources,
             cache: WorkCache::new(resources),
-            configuration: Configuration::default(),
+            configuration: Default::default(),
             cached_bundler: None,
+            process_cache: None,
+            output_extensions: BTreeMap::new(),
+            measure_rule_timings: false,
+            collect_module_graph: false,
+            collect_applied_rules: false,
+            collect_bundle_manifests: false,
+            always_write_outputs: false,
+            recover_syntax_errors: false,
+            file_budget: None,
+            depth_limit: None,
         }
     }
 
     pub(crate) fn setup_worker(&mut self, options: &mut Options) -> DarkluaResult<()> {
         let configuration_setup_timer = Timer::now();
+        let mut configuration = Configuration::default();
 
         if let Some(config) = options.take_configuration() {
-            self.configuration = config;
+            configuration = config;
             if let Some(config_path) = options.configuration_path() {
                 log::warn!(
                     concat!(
@@ -54,7 +83,7 @@ impl<'a> Worker<'a> {
             }
         } else if let Some(config) = options.configuration_path() {
             if self.resources.exists(config)? {
-                self.configuration = self.read_configuration(config)?;
+                configuration = self.read_configuration(config)?;
                 log::info!("using configuration file `{}`", config.display());
             } else {
                 return Err(DarkluaError::resource_not_found(config)
@@ -74,7 +103,7 @@ impl<'a> Worker<'a> {
                 }
                 1 => {
                     let configuration_file_path = configuration_files.first().unwrap();
-                    self.configuration = self.read_configuration(configuration_file_path)?;
+                    configuration = self.read_configuration(configuration_file_path)?;
                     log::info!(
                         "using configuration file `{}`",
                         configuration_file_path.display()
@@ -88,18 +117,37 @@ impl<'a> Worker<'a> {
             }
         };
 
+        for layer in options.take_configuration_layers() {
+            log::trace!("apply configuration layer {:?}", layer);
+            configuration = configuration.with_layer(layer);
+        }
+
         if let Some(generator) = options.generator_override() {
             log::trace!(
                 "override with {} generator",
                 match generator {
-                    GeneratorParameters::RetainLines => "`retain_lines`".to_owned(),
+                    GeneratorParameters::RetainLines { escape_unicode } =>
+                        if *escape_unicode {
+                            "`retain_lines` (escape unicode)".to_owned()
+                        } else {
+                            "`retain_lines`".to_owned()
+                        },
                     GeneratorParameters::Dense { column_span } =>
                         format!("dense ({})", column_span),
-                    GeneratorParameters::Readable { column_span } =>
-                        format!("readable ({})", column_span),
+                    GeneratorParameters::Readable {
+                        column_span,
+                        max_line_length: None,
+                    } => format!("readable ({})", column_span),
+                    GeneratorParameters::Readable {
+                        column_span,
+                        max_line_length: Some(max_line_length),
+                    } => format!(
+                        "readable ({}, max line length {})",
+                        column_span, max_line_length
+                    ),
                 }
             );
-            self.configuration.set_generator(generator.clone());
+            configuration.set_generator(generator.clone());
         }
 
         log::trace!(
@@ -108,14 +156,55 @@ impl<'a> Worker<'a> {
         );
         log::debug!(
             "using configuration: {}",
-            json5::to_string(&self.configuration).unwrap_or_else(|err| {
+            json5::to_string(&configuration).unwrap_or_else(|err| {
                 format!("? (unable to serialize configuration: {})", err)
             })
         );
 
+        self.process_cache = options.cache_directory().map(|directory| {
+            log::debug!("using process cache at `{}`", directory.display());
+            ProcessCache::new(directory, &configuration, options.output_extensions())
+        });
+        self.output_extensions = options.output_extensions().clone();
+        self.measure_rule_timings = options.should_measure_rule_timings();
+        self.collect_module_graph = options.should_collect_module_graph();
+        self.collect_applied_rules = options.should_collect_applied_rules();
+        self.collect_bundle_manifests = options.should_collect_bundle_manifests();
+        self.always_write_outputs = options.should_always_write_outputs();
+        self.recover_syntax_errors = options.should_recover_syntax_errors();
+        self.file_budget = options.file_budget().copied();
+        self.depth_limit = options.depth_limit();
+        self.configuration = Arc::new(configuration);
+
         Ok(())
     }
 
+    /// Creates a worker that shares the configuration and the known outputs of this worker,
+    /// so that it can process files on another thread.
+    pub(crate) fn fork(&self) -> Self {
+        Self {
+            resources: self.resources,
+            cache: self.cache.clone(),
+            configuration: Arc::clone(&self.configuration),
+            cached_bundler: None,
+            process_cache: self.process_cache.clone(),
+            output_extensions: self.output_extensions.clone(),
+            measure_rule_timings: self.measure_rule_timings,
+            collect_module_graph: self.collect_module_graph,
+            collect_applied_rules: self.collect_applied_rules,
+            collect_bundle_manifests: self.collect_bundle_manifests,
+            always_write_outputs: self.always_write_outputs,
+            recover_syntax_errors: self.recover_syntax_errors,
+            file_budget: self.file_budget,
+            depth_limit: self.depth_limit,
+        }
+    }
+
+    /// Collects the outputs produced by a forked worker.
+    pub(crate) fn join(&mut self, fork: Self) {
+        self.cache.merge(fork.cache);
+    }
+
     pub(crate) fn configuration(&self) -> &Configuration {
         &self.configuration
     }
@@ -123,14 +212,46 @@ impl<'a> Worker<'a> {
     pub(crate) fn advance_work(&mut self, work_item: &mut WorkItem) -> DarkluaResult<()> {
         match &work_item.status {
             WorkStatus::NotStarted => {
-                let source_display = work_item.source().display();
+                if self.restore_cached_output(work_item)? {
+                    return Ok(());
+                }
 
-                let content = self.resources.get(work_item.source())?;
+                let content = match self.resources.get(work_item.source()) {
+                    Ok(content) => content,
+                    Err(ResourceError::InvalidUtf8(_)) if work_item.collected => {
+                        let reason = "the file does not contain valid UTF-8".to_owned();
+                        log::warn!("skip `{}`: {}", work_item.source().display(), reason);
+                        work_item.skip_reason = Some(reason);
+                        work_item.status = WorkStatus::done();
+                        return Ok(());
+                    }
+                    Err(err) => return Err(err.into()),
+                };
+
+                let source_display = work_item.source().display();
 
-                let parser = self.configuration.build_parser();
+                let mut parser = self.configuration.build_parser();
+                if self.recover_syntax_errors {
+                    parser = parser.recover_errors();
+                }
+                // the directives are read from the comments, which are only kept with tokens
+                if may_contain_directives(&content) {
+                    parser = parser.preserve_tokens();
+                }
 
                 log::debug!("beginning work on `{}`", source_display);
 
+                // the parser is recursive, so code nested too deeply is rejected before
+                // it is parsed
+                if let Some(limit) = self.depth_limit {
+                    if estimate_code_depth(&content) > limit {
+                        return Err(DarkluaError::depth_limit_exceeded(
+                            work_item.source(),
+                            limit,
+                        ));
+                    }
+                }
+
                 let parser_timer = Timer::now();
 
                 let mut block = parser.parse(&content).map_err(|parser_error| {
@@ -140,9 +261,22 @@ impl<'a> Worker<'a> {
                 let parser_time = parser_timer.duration_label();
                 log::debug!("parsed `{}` in {}", source_display, parser_time);
 
+                let directives = RuleDirectives::read(&mut block, &content, work_item.source());
+
                 self.bundle(work_item, &mut block, &content)?;
 
-                work_item.status = WorkProgress::new(content, block).into();
+                // the estimate of the depth does not count every kind of block, so the
+                // parsed code is checked again before the rules visit it
+                if let Some(limit) = self.depth_limit {
+                    if exceeds_depth_limit(&mut block, limit) {
+                        return Err(DarkluaError::depth_limit_exceeded(
+                            work_item.source(),
+                            limit,
+                        ));
+                    }
+                }
+
+                work_item.status = WorkProgress::new(content, block, directives).into();
 
                 self.apply_rules(work_item)
             }
@@ -153,21 +287,27 @@ impl<'a> Worker<'a> {
 
     fn read_configuration(&self, config: &Path) -> DarkluaResult<Configuration> {
         let config_content = self.resources.get(config)?;
-        json5::from_str(&config_content)
-            .map_err(|err| {
-                DarkluaError::invalid_configuration_file(config).context(err.to_string())
-            })
-            .map(|configuration: Configuration| {
-                configuration.with_location({
-                    config.parent().unwrap_or_else(|| {
-                        log::warn!(
-                            "unexpected configuration path `{}` (unable to extract parent path)",
-                            config.display()
-                        );
-                        config
-                    })
+
+        let value = parse_configuration_value(config, &config_content)?;
+
+        let configuration = if needs_resolution(&value) {
+            resolve_extends(self.resources, config, value)
+        } else {
+            configuration_from_value(config, &config_content, value)
+                .map_err(|err| DarkluaError::invalid_configuration_file(config).context(err))
+        };
+
+        configuration.map(|configuration: Configuration| {
+            configuration.with_location({
+                config.parent().unwrap_or_else(|| {
+                    log::warn!(
+                        "unexpected configuration path `{}` (unable to extract parent path)",
+                        config.display()
+                    );
+                    config
                 })
             })
+        })
     }
 
     fn apply_rules(&mut self, work_item: &mut WorkItem) -> DarkluaResult<()> {
@@ -183,12 +323,9 @@ impl<'a> Worker<'a> {
 
         progress.duration().start();
 
-        for (index, rule) in self
-            .configuration
-            .rules()
-            .enumerate()
-            .skip(progress.next_rule())
-        {
+        let rules = self.configuration.rules_for(work_item.data.source());
+
+        for (index, rule) in rules.iter().copied().enumerate().skip(progress.next_rule()) {
             let mut context_builder =
                 self.create_rule_context(work_item.data.source(), &work_progress.content);
             log::trace!(
@@ -255,13 +392,42 @@ impl<'a> Worker<'a> {
             }
 
             let context = context_builder.build();
+            let elapsed = progress.duration().duration();
+            let steps = progress.steps();
             let block = progress.mutate_block();
+            let original_block = self.collect_applied_rules.then(|| block.clone());
             let rule_timer = Timer::now();
 
             let source = work_item.data.source();
 
-            let rule_result = rule.process(block, &context).map_err(|rule_error| {
-                let error = DarkluaError::rule_error(source, rule, index, rule_error);
+            let directives = &work_progress.directives;
+            let disabled_statements = (!directives.is_empty())
+                .then(|| directives.disable_statements(rule.get_name(), block));
+
+            let (rule_result, budget_usage) = match self.file_budget.as_ref() {
+                Some(budget) => {
+                    let (rule_result, usage) = track_budget(
+                        budget
+                            .timeout()
+                            .map(|timeout| timeout.saturating_sub(elapsed)),
+                        budget.steps().map(|limit| limit.saturating_sub(steps)),
+                        || rule.process(block, &context),
+                    );
+                    (rule_result, Some(usage))
+                }
+                None => (rule.process(block, &context), None),
+            };
+
+            if let Some(disabled_statements) = disabled_statements {
+                disabled_statements.restore(block);
+            }
+
+            let rule_result = rule_result.map_err(|rule_error| {
+                let details = context.take_error_details(&rule_error);
+                let mut error = DarkluaError::rule_error(source, rule, index, rule_error);
+                if let Some(details) = details {
+                    error = error.with_rule_error_details(details, &work_progress.content);
+                }
 
                 log::trace!(
                     "[{}] rule `{}` errored: {}",
@@ -273,10 +439,42 @@ impl<'a> Worker<'a> {
                 error
             });
 
+            if self.measure_rule_timings {
+                work_item
+                    .rule_durations
+                    .record(rule.get_name(), rule_timer.duration());
+            }
+
+            if let Some(original_block) = original_block {
+                record_applied_rule(
+                    &mut work_item.applied_rules,
+                    rule,
+                    &original_block,
+                    block,
+                    &context,
+                );
+            }
+
+            work_item.module_edges.extend(context.take_module_edges());
             work_item
                 .external_file_dependencies
                 .extend(context.into_dependencies());
 
+            if let (Some(budget), Some(usage)) = (self.file_budget.as_ref(), budget_usage) {
+                progress.add_steps(usage.steps());
+
+                let elapsed = progress.duration().duration();
+                if usage.is_exhausted() || budget.is_exceeded(elapsed, progress.steps()) {
+                    let error = DarkluaError::timeout(
+                        source,
+                        rule.get_name(),
+                        describe_exceeded_budget(budget, progress.steps()),
+                    );
+                    log::trace!("[{}] {}", source_display, error);
+                    return Err(error);
+                }
+            }
+
             rule_result?;
 
             let rule_duration = rule_timer.duration_label();
@@ -289,7 +487,7 @@ impl<'a> Worker<'a> {
         }
 
         let rule_time = progress.duration().duration_label();
-        let total_rules = self.configuration.rules_len();
+        let total_rules = rules.len();
         log::debug!(
             "{} rule{} applied in {} for `{}`",
             total_rules,
@@ -298,9 +496,29 @@ impl<'a> Worker<'a> {
             source_display,
         );
 
+        remap_require_extensions(progress.mutate_block(), &self.output_extensions);
+
+        // rules can nest the code deeper, so it is checked again before it is generated
+        if let Some(limit) = self.depth_limit {
+            if exceeds_depth_limit(progress.mutate_block(), limit) {
+                return Err(DarkluaError::depth_limit_exceeded(
+                    work_item.data.source(),
+                    limit,
+                ));
+            }
+        }
+
         log::trace!("begin generating code for `{}`", source_display);
 
-        if cfg!(test) || (cfg!(debug_assertions) && log::log_enabled!(log::Level::Trace)) {
+        let previous_output = if work_item.data.is_in_place() {
+            Some(work_progress.content.clone())
+        } else {
+            self.resources.get(work_item.data.output()).ok()
+        };
+
+        let write_debugging_view =
+            cfg!(test) || (cfg!(debug_assertions) && log::log_enabled!(log::Level::Trace));
+        if write_debugging_view {
             log::trace!(
                 "generate AST debugging view at `{}`",
                 work_item.data.output().display()
@@ -322,7 +540,73 @@ impl<'a> Worker<'a> {
             generator_time,
         );
 
-        self.resources.write(work_item.data.output(), &lua_code)?;
+        work_item.unchanged_output = previous_output.as_ref() == Some(&lua_code);
+
+        // the debugging view replaced the previous output, so it must be written again
+        if write_debugging_view {
+            self.resources.write(work_item.data.output(), &lua_code)?;
+        } else {
+            self.write_output(
+                work_item.data.output(),
+                work_item.unchanged_output,
+                &lua_code,
+            )?;
+        }
+
+        if let Some(manifest) = work_item.bundle_manifest.as_mut() {
+            if let Some(bundle_configuration) = self.configuration.bundle_configuration() {
+                let configuration = &self.configuration;
+                let content = &work_progress.content;
+                manifest.measure_modules(
+                    progress.block(),
+                    bundle_configuration.modules_identifier(),
+                    |module_block| configuration.generate_lua(module_block, content),
+                );
+            }
+        }
+
+        if let Some(manifest_path) = self.bundle_manifest_path() {
+            if let Some(manifest) = work_item.bundle_manifest.as_ref() {
+                let manifest_path = work_item
+                    .data
+                    .output()
+                    .parent()
+                    .map(|directory| directory.join(manifest_path))
+                    .unwrap_or_else(|| manifest_path.to_path_buf());
+
+                let content = serde_json::to_string_pretty(manifest).map_err(|err| {
+                    DarkluaError::custom(format!("unable to serialize bundle manifest: {}", err))
+                })?;
+
+                let unchanged = self.resources.get(&manifest_path).ok().as_ref() == Some(&content);
+                self.write_output(&manifest_path, unchanged, &content)?;
+                work_item.artifacts.push(manifest_path);
+            }
+        }
+
+        if let Some(process_cache) = self.process_cache.as_ref() {
+            // rules that use the output of other files are not cached, because their
+            // result depends on more than the content of the files they read. Bundles
+            // with a manifest are not cached either, because the manifest is not restored
+            if !work_item.data.is_in_place()
+                && work_progress.required_content().next().is_none()
+                && work_item.bundle_manifest.is_none()
+            {
+                if let Err(err) = process_cache.store(
+                    self.resources,
+                    work_item.data.source(),
+                    work_item.data.output(),
+                    &work_progress.content,
+                    &lua_code,
+                    work_item
+                        .external_file_dependencies
+                        .iter()
+                        .map(AsRef::as_ref),
+                ) {
+                    log::warn!("unable to cache output of `{}`: {}", source_display, err);
+                }
+            }
+        }
 
         self.cache
             .link_source_to_output(normalized_source, work_item.data.output());
@@ -331,12 +615,97 @@ impl<'a> Worker<'a> {
         Ok(())
     }
 
+    /// Writes the cached output of the work item if the process cache has a valid entry
+    /// for it, and returns true if the work is done.
+    fn restore_cached_output(&mut self, work_item: &mut WorkItem) -> DarkluaResult<bool> {
+        let process_cache = match self.process_cache.as_ref() {
+            Some(process_cache)
+                if !work_item.data.is_in_place() && !self.is_collecting_bundle_manifest() =>
+            {
+                process_cache
+            }
+            _ => return Ok(false),
+        };
+
+        let lookup_result =
+            process_cache.lookup(self.resources, work_item.source(), work_item.data.output());
+
+        let mut cached = match lookup_result {
+            Ok(Some(cached)) => cached,
+            Ok(None) => return Ok(false),
+            Err(err) => {
+                log::warn!(
+                    "unable to read process cache for `{}`: {}",
+                    work_item.source().display(),
+                    err
+                );
+                return Ok(false);
+            }
+        };
+
+        let output = match cached.take_output() {
+            Some(output) => output,
+            None => self.resources.get(work_item.source())?,
+        };
+
+        work_item.unchanged_output = self
+            .resources
+            .get(work_item.data.output())
+            .map(|previous_output| previous_output == output)
+            .unwrap_or_default();
+
+        self.write_output(work_item.data.output(), work_item.unchanged_output, &output)?;
+
+        log::debug!("reuse cached output for `{}`", work_item.source().display());
+
+        work_item
+            .external_file_dependencies
+            .extend(cached.into_dependencies());
+
+        self.cache
+            .link_source_to_output(normalize_path(work_item.source()), work_item.data.output());
+
+        work_item.status = WorkStatus::done();
+        Ok(true)
+    }
+
+    fn bundle_manifest_path(&self) -> Option<&Path> {
+        self.configuration
+            .bundle_configuration()
+            .and_then(BundleConfiguration::manifest_path)
+    }
+
+    fn is_collecting_bundle_manifest(&self) -> bool {
+        self.configuration.has_bundle()
+            && (self.collect_bundle_manifests || self.bundle_manifest_path().is_some())
+    }
+
+    /// Writes an output file, unless it already contains the same content so that its
+    /// modification time does not change.
+    fn write_output(&self, path: &Path, unchanged: bool, content: &str) -> DarkluaResult<()> {
+        if unchanged && !self.always_write_outputs {
+            log::debug!("skip writing unchanged output `{}`", path.display());
+            return Ok(());
+        }
+
+        self.resources.write(path, content)?;
+        Ok(())
+    }
+
     fn create_rule_context<'block, 'src>(
         &self,
         source: &Path,
         original_code: &'src str,
     ) -> ContextBuilder<'block, 'a, 'src> {
-        let builder = ContextBuilder::new(normalize_path(source), self.resources, original_code);
+        let mut builder =
+            ContextBuilder::new(normalize_path(source), self.resources, original_code)
+                .with_luau_configuration_cache(self.cache.luau_configurations().clone());
+        if self.collect_module_graph {
+            builder = builder.collect_module_graph();
+        }
+        if self.collect_applied_rules {
+            builder = builder.collect_rule_notes();
+        }
         if let Some(project_location) = self.configuration.location() {
             builder.with_project_location(project_location)
         } else {
@@ -362,14 +731,22 @@ impl<'a> Worker<'a> {
 
         log::debug!("beginning bundling from `{}`", work_item.source().display());
 
+        let original_block = self.collect_applied_rules.then(|| block.clone());
         let bundle_timer = Timer::now();
 
-        let context = self
-            .create_rule_context(work_item.source(), original_code)
-            .build();
+        let mut context_builder = self.create_rule_context(work_item.source(), original_code);
+        if self.is_collecting_bundle_manifest() {
+            context_builder = context_builder.collect_bundle_manifest();
+        }
+        let context = context_builder.build();
 
         let rule_result = bundler.process(block, &context).map_err(|rule_error| {
-            let error = DarkluaError::orphan_rule_error(work_item.source(), bundler, rule_error);
+            let details = context.take_error_details(&rule_error);
+            let mut error =
+                DarkluaError::orphan_rule_error(work_item.source(), bundler, rule_error);
+            if let Some(details) = details {
+                error = error.with_rule_error_details(details, original_code);
+            }
 
             log::trace!(
                 "[{}] rule `{}` errored: {}",
@@ -381,6 +758,24 @@ impl<'a> Worker<'a> {
             error
         });
 
+        if self.measure_rule_timings {
+            work_item
+                .rule_durations
+                .record(bundler.get_name(), bundle_timer.duration());
+        }
+
+        if let Some(original_block) = original_block {
+            record_applied_rule(
+                &mut work_item.applied_rules,
+                bundler,
+                &original_block,
+                block,
+                &context,
+            );
+        }
+
+        work_item.module_edges.extend(context.take_module_edges());
+        work_item.bundle_manifest = context.take_bundle_manifest();
         work_item
             .external_file_dependencies
             .extend(context.into_dependencies());
@@ -397,3 +792,28 @@ impl<'a> Worker<'a> {
         Ok(())
     }
 }
+
+fn describe_exceeded_budget(budget: &FileBudget, steps: u64) -> String {
+    match budget.timeout() {
+        Some(timeout) if budget.steps().map_or(true, |limit| steps <= limit) => {
+            format!("timeout of {:?}", timeout)
+        }
+        _ => format!("more than {} steps", budget.steps().unwrap_or_default()),
+    }
+}
+
+/// Records the rule when it modified the block, with the notes it attached to the context.
+fn record_applied_rule(
+    applied_rules: &mut Vec<AppliedRule>,
+    rule: &dyn Rule,
+    original_block: &Block,
+    block: &Block,
+    context: &Context,
+) {
+    let notes = context.take_rule_notes();
+    let modifications = count_modifications(original_block, block);
+
+    if modifications > 0 {
+        applied_rules.push(AppliedRule::new(rule.get_name(), modifications, notes));
+    }
+}
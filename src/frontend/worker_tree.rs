@@ -1,20 +1,32 @@
 use std::{
     collections::{HashMap, HashSet},
+    panic,
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, PoisonError,
+    },
+    thread,
 };
 
-use petgraph::{algo::toposort, graph::NodeIndex, stable_graph::StableDiGraph, visit::Dfs};
+use petgraph::{
+    algo::toposort, graph::NodeIndex, stable_graph::StableDiGraph, visit::Dfs, Direction,
+};
 use xxhash_rust::xxh3::xxh3_64;
 
 use crate::{
     frontend::utils::maybe_plural,
-    utils::{clear_luau_configuration_cache, Timer},
+    utils::{run_with_stack_size, spawn_scoped_with_stack_size, Timer},
     DarkluaError,
 };
 
 use super::{
-    normalize_path, work_item::WorkStatus, Configuration, DarkluaResult, Options, Resources,
-    WorkItem, Worker,
+    diagnostic::Diagnostic,
+    file_filter::{FileFilter, IgnoreFiles},
+    normalize_path,
+    process_report::{FileReport, FileStatus, ProcessReport},
+    work_item::WorkStatus,
+    Configuration, DarkluaResult, ModuleGraph, Options, Resources, WorkItem, Worker,
 };
 
 #[derive(Debug, Default)]
@@ -24,6 +36,8 @@ pub struct WorkerTree {
     external_dependencies: HashMap<PathBuf, HashSet<NodeIndex>>,
     remove_files: Vec<PathBuf>,
     last_configuration_hash: Option<u64>,
+    output_code: Option<String>,
+    collect_module_graph: bool,
 }
 
 impl WorkerTree {
@@ -31,6 +45,16 @@ impl WorkerTree {
         log::trace!("start collecting work");
         let collect_work_timer = Timer::now();
 
+        let filter = FileFilter::new(options.iter_includes(), options.iter_excludes())?;
+        let ignore_files = IgnoreFiles::read(
+            resources,
+            options
+                .configuration_path()
+                .and_then(Path::parent)
+                .unwrap_or_else(|| Path::new("")),
+            options.should_respect_gitignore(),
+        )?;
+
         if let Some(output) = options.output().map(Path::to_path_buf) {
             if resources.is_file(options.input())? {
                 if resources.is_directory(&output)? {
@@ -41,7 +65,10 @@ impl WorkerTree {
                         ))
                     })?;
 
-                    self.add_source_if_missing(options.input(), Some(output.join(file_name)));
+                    self.add_source_if_missing(
+                        options.input(),
+                        Some(options.remap_output_extension(output.join(file_name))),
+                    );
                 } else if resources.is_file(&output)? || output.extension().is_some() {
                     self.add_source_if_missing(options.input(), Some(output));
                 } else {
@@ -52,12 +79,17 @@ impl WorkerTree {
                         ))
                     })?;
 
-                    self.add_source_if_missing(options.input(), Some(output.join(file_name)));
+                    self.add_source_if_missing(
+                        options.input(),
+                        Some(options.remap_output_extension(output.join(file_name))),
+                    );
                 }
             } else {
                 let input = options.input().to_path_buf();
 
-                for source in resources.collect_work(&input) {
+                for source in
+                    resources.collect_work_with_max_depth(&input, options.max_directory_depth())
+                {
                     let source = normalize_path(source);
 
                     let relative_path = source.strip_prefix(&input).map_err(|err| {
@@ -69,15 +101,33 @@ impl WorkerTree {
                         ))
                     })?;
 
-                    let output_path = Some(output.join(relative_path));
-                    self.add_source_if_missing(source, output_path);
+                    if !accepts_collected_file(&filter, &ignore_files, &source, relative_path) {
+                        log::trace!("skip excluded file `{}`", source.display());
+                        continue;
+                    }
+
+                    let output_path =
+                        Some(options.remap_output_extension(output.join(relative_path)));
+                    self.add_collected_source_if_missing(source, output_path);
                 }
             }
         } else {
             let input = options.input().to_path_buf();
 
-            for source in resources.collect_work(input) {
-                self.add_source_if_missing(source, None);
+            for source in
+                resources.collect_work_with_max_depth(&input, options.max_directory_depth())
+            {
+                if !filter.is_empty() || !ignore_files.is_empty() {
+                    let source = normalize_path(&source);
+                    let relative_path = source.strip_prefix(&input).unwrap_or(&source);
+
+                    if !accepts_collected_file(&filter, &ignore_files, &source, relative_path) {
+                        log::trace!("skip excluded file `{}`", source.display());
+                        continue;
+                    }
+                }
+
+                self.add_collected_source_if_missing(source, None);
             }
         }
 
@@ -87,8 +137,6 @@ impl WorkerTree {
     }
 
     pub fn process(&mut self, resources: &Resources, mut options: Options) -> DarkluaResult<()> {
-        clear_luau_configuration_cache();
-
         if !self.remove_files.is_empty() {
             let remove_count = self.remove_files.len();
             log::debug!(
@@ -104,6 +152,8 @@ impl WorkerTree {
             }
         }
 
+        self.collect_module_graph = options.should_collect_module_graph();
+
         let mut worker = Worker::new(resources);
         worker.setup_worker(&mut options)?;
 
@@ -122,106 +172,44 @@ impl WorkerTree {
             return Ok(());
         }
 
-        let work_timer = Timer::now();
-
-        'work_loop: loop {
-            let mut add_edges = Vec::new();
-
-            match toposort(&self.graph, None) {
-                Ok(node_indexes) => {
-                    let mut done_count = 0;
-
-                    for node_index in node_indexes {
-                        let work_item = self
-                            .graph
-                            .node_weight_mut(node_index)
-                            .expect("node index should exist");
-
-                        if !work_item.status.is_done() {
-                            match worker.advance_work(work_item) {
-                                Ok(()) => match &work_item.status {
-                                    WorkStatus::Done(result) => {
-                                        done_count += 1;
-                                        if result.is_ok() {
-                                            log::info!(
-                                                "successfully processed `{}`",
-                                                work_item.source().display()
-                                            );
-                                        }
-                                    }
-                                    WorkStatus::InProgress(progress) => {
-                                        for content in progress.required_content() {
-                                            if let Some(content_node_index) =
-                                                self.node_map.get(content)
-                                            {
-                                                add_edges.push((*content_node_index, node_index));
-                                            }
-                                        }
-                                        log::trace!(
-                                            "work on `{}` has not completed",
-                                            work_item.source().display()
-                                        );
-                                    }
-                                    WorkStatus::NotStarted => {}
-                                },
-                                Err(err) => {
-                                    log::error!(
-                                        "an error happened while processing {}: {}",
-                                        work_item.source().display(),
-                                        err
-                                    );
-                                    work_item.status = WorkStatus::err(err);
-                                    done_count += 1;
-                                    if options.should_fail_fast() {
-                                        log::debug!(
-                                            "dropping all work because the fail-fast option is enabled"
-                                        );
-                                        break 'work_loop;
-                                    }
-                                }
-                            }
-                        }
+        let threads = if worker.configuration().has_bundle() {
+            if options.threads() > 1 {
+                log::debug!("process files serially because bundling is enabled");
+            }
+            1
+        } else {
+            options.threads()
+        };
 
-                        for path in work_item.external_file_dependencies.iter() {
-                            let container = self
-                                .external_dependencies
-                                .entry(path.to_path_buf())
-                                .or_default();
+        let work_timer = Timer::now();
 
-                            if !container.contains(&node_index) {
-                                log::trace!(
-                                    "link external dependency {} to {}",
-                                    path.display(),
-                                    work_item.source().display()
-                                );
-                                container.insert(node_index);
-                            }
-                        }
-                    }
+        let stack_size = options.stack_size();
+        let fail_fast = options.should_fail_fast();
 
-                    log::debug!("process batch of tasks ({}/{})", done_count, total_not_done);
+        if threads > 1 {
+            self.advance_all_work(&mut worker, threads, stack_size, fail_fast, total_not_done)?;
+        } else {
+            // every file is processed on the same thread, so that the thread with the large
+            // stack is only spawned once
+            run_with_stack_size(stack_size, || {
+                self.advance_all_work(&mut worker, threads, stack_size, fail_fast, total_not_done)
+            })?;
+        }
 
-                    if done_count == total_not_done {
-                        break;
-                    }
-                }
-                Err(_cycle_err) => {
-                    return Err(DarkluaError::cyclic_work(
-                        self.graph
-                            .node_weights()
-                            .filter(|item| !item.status.is_done())
-                            .collect(),
-                    ));
-                }
-            }
+        log::info!("executed work in {}", work_timer.duration_label());
 
-            for (from, to) in add_edges {
-                self.graph.add_edge(from, to, ());
+        if options.should_measure_rule_timings() && log::log_enabled!(log::Level::Debug) {
+            for timing in self.report().iter_rule_timings() {
+                log::debug!(
+                    "rule `{}` ran on {} file{} in {}",
+                    timing.rule(),
+                    timing.files(),
+                    maybe_plural(timing.files()),
+                    durationfmt::to_string(timing.duration())
+                );
             }
         }
 
-        log::info!("executed work in {}", work_timer.duration_label());
-
         Ok(())
     }
 
@@ -234,10 +222,91 @@ impl WorkerTree {
         }
     }
 
+    /// Creates a report with the status of each file. Errors are included with the file
+    /// that caused them, so the report is complete even when some files failed.
+    pub fn report(&self) -> ProcessReport {
+        let report = ProcessReport::new(
+            self.graph
+                .node_weights()
+                .map(|work_item| {
+                    let status = match &work_item.status {
+                        WorkStatus::NotStarted => FileStatus::Skipped {
+                            reason: "processing stopped before the file was started".to_owned(),
+                        },
+                        WorkStatus::InProgress(_) => FileStatus::Skipped {
+                            reason: "processing stopped while the file was waiting for other files"
+                                .to_owned(),
+                        },
+                        WorkStatus::Done(Ok(())) => {
+                            if let Some(reason) = &work_item.skip_reason {
+                                FileStatus::Skipped {
+                                    reason: reason.clone(),
+                                }
+                            } else if work_item.unchanged_output {
+                                FileStatus::Unchanged
+                            } else {
+                                FileStatus::Written
+                            }
+                        }
+                        WorkStatus::Done(Err(error)) => FileStatus::Failed {
+                            error: error.clone(),
+                        },
+                    };
+
+                    FileReport::new(
+                        work_item.data.source(),
+                        work_item.data.output(),
+                        status,
+                        work_item.artifacts.clone(),
+                    )
+                    .with_rule_durations(work_item.rule_durations.iter())
+                    .with_applied_rules(work_item.applied_rules.clone())
+                    .with_bundle_manifest(work_item.bundle_manifest.clone())
+                })
+                .collect(),
+        );
+
+        if self.collect_module_graph {
+            report.with_module_graph(ModuleGraph::new(
+                self.graph
+                    .node_weights()
+                    .flat_map(|work_item| work_item.module_edges.iter().cloned())
+                    .collect(),
+            ))
+        } else {
+            report
+        }
+    }
+
+    /// Returns the generated code of the input given with
+    /// [`Options::from_code`](crate::Options::from_code), if it was processed successfully.
+    pub fn output_code(&self) -> Option<&str> {
+        self.output_code.as_deref()
+    }
+
+    pub(crate) fn read_output_code(&mut self, resources: &Resources, source: &Path) {
+        let output = self
+            .node_map
+            .get(source)
+            .and_then(|node_index| self.graph.node_weight(*node_index))
+            .filter(|work_item| matches!(work_item.status, WorkStatus::Done(Ok(()))))
+            .map(|work_item| work_item.data.output().to_path_buf());
+
+        self.output_code = output.and_then(|output| resources.get(output).ok());
+    }
+
     pub fn collect_errors(&self) -> Vec<&DarkluaError> {
         self.iter_errors().collect()
     }
 
+    /// Returns a serializable description of each error, for tools that need to consume
+    /// errors in a machine-readable format.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.iter_errors()
+            .flat_map(DarkluaError::to_diagnostics)
+            .collect()
+    }
+
     fn iter_errors(&self) -> impl Iterator<Item = &DarkluaError> {
         self.graph
             .node_weights()
@@ -372,13 +441,28 @@ impl WorkerTree {
         }
     }
 
-    fn insert_source(&mut self, path: PathBuf, output: Option<PathBuf>) {
+    /// Adds a file found while walking the input directory. Unlike the input file, these
+    /// files are skipped when their content is not valid UTF-8.
+    fn add_collected_source_if_missing(&mut self, path: impl AsRef<Path>, output: Option<PathBuf>) {
+        let path = normalize_path(path.as_ref());
+
+        if !self.node_map.contains_key(&path) {
+            let node_index = self.insert_source(path, output);
+            self.graph
+                .node_weight_mut(node_index)
+                .expect("node index should exist")
+                .collected = true;
+        }
+    }
+
+    fn insert_source(&mut self, path: PathBuf, output: Option<PathBuf>) -> NodeIndex {
         let node_index = self.graph.add_node(if let Some(output) = output {
             WorkItem::new(path.clone(), output)
         } else {
             WorkItem::new_in_place(path.clone())
         });
         self.node_map.insert(path, node_index);
+        node_index
     }
 
     fn restart_work(&mut self, node_index: NodeIndex) {
@@ -400,6 +484,237 @@ impl WorkerTree {
         }
     }
 
+    /// Work that needs the content of a failed work can not complete, so it is marked as
+    /// failed too. Otherwise, it would wait forever for the content.
+    fn fail_work_requiring_failed_work(&mut self) {
+        let failed_work: Vec<_> = self
+            .graph
+            .node_indices()
+            .filter(|node_index| !self.graph[*node_index].status.is_done())
+            .filter_map(|node_index| {
+                self.graph
+                    .neighbors_directed(node_index, Direction::Incoming)
+                    .find(|required_index| {
+                        matches!(self.graph[*required_index].status, WorkStatus::Done(Err(_)))
+                    })
+                    .map(|required_index| {
+                        (
+                            node_index,
+                            self.graph[required_index].source().to_path_buf(),
+                        )
+                    })
+            })
+            .collect();
+
+        for (node_index, required) in failed_work {
+            let work_item = &mut self.graph[node_index];
+            let error = DarkluaError::required_work_failed(work_item.source(), required);
+            log::error!("{}", error);
+            work_item.status = WorkStatus::err(error);
+        }
+    }
+
+    /// Returns true if the work item is not done and all the work items it depends on
+    /// are done.
+    fn is_ready(&self, node_index: NodeIndex) -> bool {
+        let is_done = |index| {
+            self.graph
+                .node_weight(index)
+                .map(|work_item| work_item.status.is_done())
+                .unwrap_or(true)
+        };
+
+        !is_done(node_index)
+            && self
+                .graph
+                .neighbors_directed(node_index, Direction::Incoming)
+                .all(is_done)
+    }
+
+    /// Advances the work until every file is done. With a single thread, the files are
+    /// processed on the calling thread.
+    fn advance_all_work(
+        &mut self,
+        worker: &mut Worker,
+        threads: usize,
+        stack_size: Option<usize>,
+        fail_fast: bool,
+        total_not_done: usize,
+    ) -> DarkluaResult<()> {
+        'work_loop: loop {
+            let mut add_edges = Vec::new();
+
+            self.fail_work_requiring_failed_work();
+
+            match toposort(&self.graph, None) {
+                Ok(node_indexes) => {
+                    let (batch, mut failed) = if threads > 1 {
+                        let ready: Vec<_> = node_indexes
+                            .into_iter()
+                            .filter(|node_index| self.is_ready(*node_index))
+                            .collect();
+                        let failed =
+                            self.advance_work_in_parallel(worker, &ready, threads, stack_size);
+                        (ready, failed)
+                    } else {
+                        (node_indexes, false)
+                    };
+
+                    for node_index in batch {
+                        let work_item = self
+                            .graph
+                            .node_weight_mut(node_index)
+                            .expect("node index should exist");
+
+                        if threads <= 1 && !work_item.status.is_done() {
+                            failed = !advance_work_item(worker, work_item);
+
+                            if failed && fail_fast {
+                                break;
+                            }
+                        }
+
+                        if let WorkStatus::InProgress(progress) = &work_item.status {
+                            for content in progress.required_content() {
+                                if let Some(content_node_index) = self.node_map.get(content) {
+                                    add_edges.push((*content_node_index, node_index));
+                                }
+                            }
+                        }
+
+                        for path in work_item.external_file_dependencies.iter() {
+                            let container = self
+                                .external_dependencies
+                                .entry(path.to_path_buf())
+                                .or_default();
+
+                            if !container.contains(&node_index) {
+                                log::trace!(
+                                    "link external dependency {} to {}",
+                                    path.display(),
+                                    work_item.source().display()
+                                );
+                                container.insert(node_index);
+                            }
+                        }
+                    }
+
+                    if failed && fail_fast {
+                        log::debug!("dropping all work because the fail-fast option is enabled");
+                        break 'work_loop;
+                    }
+
+                    let not_done = self
+                        .graph
+                        .node_weights()
+                        .filter(|work_item| !work_item.status.is_done())
+                        .count();
+
+                    log::debug!(
+                        "process batch of tasks ({}/{})",
+                        total_not_done - not_done,
+                        total_not_done
+                    );
+
+                    if not_done == 0 {
+                        break;
+                    }
+                }
+                Err(_cycle_err) => {
+                    return Err(DarkluaError::cyclic_work(
+                        self.graph
+                            .node_weights()
+                            .filter(|item| !item.status.is_done())
+                            .collect(),
+                    ));
+                }
+            }
+
+            for (from, to) in add_edges {
+                self.graph.add_edge(from, to, ());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Advances the given work items using multiple threads. Each thread takes the next
+    /// work item that is not started by another thread. Returns true if any work item failed.
+    fn advance_work_in_parallel(
+        &mut self,
+        worker: &mut Worker,
+        node_indexes: &[NodeIndex],
+        threads: usize,
+        stack_size: Option<usize>,
+    ) -> bool {
+        let sources: HashSet<_> = node_indexes
+            .iter()
+            .filter_map(|node_index| self.graph.node_weight(*node_index))
+            .map(|work_item| work_item.source().to_path_buf())
+            .collect();
+
+        let work_items: Vec<&mut WorkItem> = self
+            .graph
+            .node_weights_mut()
+            .filter(|work_item| sources.contains(work_item.source()))
+            .collect();
+
+        let threads = threads.min(work_items.len());
+        let queue = Mutex::new(work_items.into_iter());
+        let failed = AtomicBool::new(false);
+
+        log::trace!(
+            "advance {} work item{} on {} thread{}",
+            sources.len(),
+            maybe_plural(sources.len()),
+            threads,
+            maybe_plural(threads)
+        );
+
+        let forks: Vec<_> = (0..threads).map(|_| worker.fork()).collect();
+
+        let forks = thread::scope(|scope| {
+            let handles: Vec<_> = forks
+                .into_iter()
+                .map(|mut fork| {
+                    let queue = &queue;
+                    let failed = &failed;
+                    spawn_scoped_with_stack_size(scope, stack_size, move || {
+                        loop {
+                            let next_item =
+                                queue.lock().unwrap_or_else(PoisonError::into_inner).next();
+
+                            match next_item {
+                                Some(work_item) => {
+                                    if !advance_work_item(&mut fork, work_item) {
+                                        failed.store(true, Ordering::Relaxed);
+                                    }
+                                }
+                                None => break,
+                            }
+                        }
+                        fork
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|payload| panic::resume_unwind(payload))
+                })
+                .collect::<Vec<_>>()
+        });
+
+        for fork in forks {
+            worker.join(fork);
+        }
+
+        failed.into_inner()
+    }
+
     fn has_configuration_changed(&mut self, config: &Configuration) -> bool {
         let input = serde_json::to_vec(config).ok().unwrap_or_default();
 
@@ -412,3 +727,47 @@ impl WorkerTree {
             .unwrap_or_default()
     }
 }
+
+/// Tells if a file found in the input directory should be processed. Files matching an
+/// include pattern are processed even when an ignore file ignores them.
+fn accepts_collected_file(
+    filter: &FileFilter,
+    ignore_files: &IgnoreFiles,
+    source: &Path,
+    relative_path: &Path,
+) -> bool {
+    filter.accepts(relative_path)
+        && (!ignore_files.is_ignored(source) || filter.is_explicitly_included(relative_path))
+}
+
+/// Advances the work item and returns false if it failed.
+fn advance_work_item(worker: &mut Worker, work_item: &mut WorkItem) -> bool {
+    match worker.advance_work(work_item) {
+        Ok(()) => {
+            match &work_item.status {
+                WorkStatus::Done(result) => {
+                    if result.is_ok() && work_item.skip_reason.is_none() {
+                        log::info!("successfully processed `{}`", work_item.source().display());
+                    }
+                }
+                WorkStatus::InProgress(_) => {
+                    log::trace!(
+                        "work on `{}` has not completed",
+                        work_item.source().display()
+                    );
+                }
+                WorkStatus::NotStarted => {}
+            }
+            true
+        }
+        Err(err) => {
+            log::error!(
+                "an error happened while processing {}: {}",
+                work_item.source().display(),
+                err
+            );
+            work_item.status = WorkStatus::err(err);
+            false
+        }
+    }
+}
@@ -26,15 +26,15 @@ impl DenseLuaGenerator {
     /// Appends a string to the current content of the DenseLuaGenerator. A space may be added
     /// depending of the last character of the current content and the first character pushed.
     fn push_str(&mut self, content: &str) {
-        if let Some(next_char) = content.chars().next() {
-            self.push_space_if_needed(next_char, content.len());
+        if !content.is_empty() {
+            self.push_space_if_needed(content);
             self.raw_push_str(content);
         }
     }
 
     /// Same as the `push_str` function, but for a single character.
     fn push_char(&mut self, character: char) {
-        self.push_space_if_needed(character, 1);
+        self.push_space_if_needed(character.encode_utf8(&mut [0; 4]));
 
         self.output.push(character);
         self.current_line_length += 1;
@@ -82,13 +82,13 @@ impl DenseLuaGenerator {
         }
     }
 
-    fn push_space_if_needed(&mut self, next_character: char, pushed_length: usize) {
+    fn push_space_if_needed(&mut self, next_content: &str) {
         if self.current_line_length >= self.column_span {
             self.push_new_line();
         } else {
-            let total_length = self.current_line_length + pushed_length;
+            let total_length = self.current_line_length + next_content.len();
 
-            if self.needs_space(next_character) {
+            if self.needs_space(next_content) {
                 if total_length + 1 > self.column_span {
                     self.push_new_line();
                 } else {
@@ -118,12 +118,15 @@ impl DenseLuaGenerator {
         self.current_line_length + length <= self.column_span
     }
 
+    /// Returns `true` if the last pushed token and the next content would be read as
+    /// different tokens without a space between them.
     #[inline]
-    fn needs_space(&self, next_character: char) -> bool {
-        if let Some(previous) = self.output.chars().last() {
-            utils::should_break_with_space(previous, next_character)
-        } else {
-            false
+    fn needs_space(&self, next_content: &str) -> bool {
+        match self.output.chars().last() {
+            Some(previous) if !previous.is_whitespace() => {
+                utils::tokens_need_separator(self.get_last_push_str(), next_content)
+            }
+            _ => false,
         }
     }
 
@@ -146,41 +149,6 @@ impl DenseLuaGenerator {
         self.current_line_length += 1;
     }
 
-    /// This function only insert a space or a new line if the given predicate returns true. In
-    /// the other case, the string is added to the current generator content.
-    fn push_str_and_break_if<F>(&mut self, content: &str, predicate: F)
-    where
-        F: Fn(&str) -> bool,
-    {
-        if predicate(self.get_last_push_str()) {
-            if self.fits_on_current_line(1 + content.len()) {
-                self.push_space();
-            } else {
-                self.push_new_line();
-            }
-        } else if !self.fits_on_current_line(content.len()) {
-            self.push_new_line();
-        }
-        self.raw_push_str(content);
-    }
-
-    /// Same as `push_str_and_break_if` but for a single character
-    fn push_char_and_break_if<F>(&mut self, content: char, predicate: F)
-    where
-        F: Fn(&str) -> bool,
-    {
-        if predicate(self.get_last_push_str()) {
-            if self.fits_on_current_line(2) {
-                self.push_space();
-            } else {
-                self.push_new_line();
-            }
-        } else if !self.fits_on_current_line(1) {
-            self.push_new_line();
-        }
-        self.raw_push_char(content);
-    }
-
     fn get_last_push_str(&self) -> &str {
         self.output
             .get((self.output.len() - self.last_push_length)..)
@@ -250,6 +218,12 @@ impl DenseLuaGenerator {
         self.push_char(')');
     }
 
+    fn write_attributes<'a>(&mut self, attributes: impl Iterator<Item = &'a nodes::Attribute>) {
+        for attribute in attributes {
+            self.push_str(&format!("@{}", attribute.get_name().get_name()));
+        }
+    }
+
     fn write_function_generics(&mut self, generics: &nodes::GenericParameters) {
         if generics.is_empty() {
             return;
@@ -295,9 +269,7 @@ impl LuaGenerator for DenseLuaGenerator {
             self.write_statement(statement);
 
             if let Some(next_statement) = statements.peek() {
-                if utils::starts_with_parenthese(next_statement)
-                    && utils::ends_with_prefix(statement)
-                {
+                if utils::needs_semicolon(statement, next_statement) {
                     self.push_char(';');
                 }
             }
@@ -320,7 +292,7 @@ impl LuaGenerator for DenseLuaGenerator {
             }
         });
 
-        self.push_char_and_break_if('=', utils::break_equal);
+        self.push_char('=');
 
         let last_value_index = assign.values_len().saturating_sub(1);
 
@@ -398,6 +370,7 @@ impl LuaGenerator for DenseLuaGenerator {
     }
 
     fn write_function_statement(&mut self, function: &nodes::FunctionStatement) {
+        self.write_attributes(function.iter_attributes());
         self.push_str("function");
         let name = function.get_name();
 
@@ -477,7 +450,7 @@ impl LuaGenerator for DenseLuaGenerator {
         });
 
         if assign.has_values() {
-            self.push_char_and_break_if('=', utils::break_equal);
+            self.push_char('=');
 
             let last_value_index = assign.values_len() - 1;
 
@@ -500,6 +473,7 @@ impl LuaGenerator for DenseLuaGenerator {
     }
 
     fn write_local_function(&mut self, function: &nodes::LocalFunctionStatement) {
+        self.write_attributes(function.iter_attributes());
         self.push_str("local function");
         self.push_str(function.get_name());
 
@@ -597,7 +571,7 @@ impl LuaGenerator for DenseLuaGenerator {
 
         self.write_typed_identifier(numeric_for.get_identifier());
 
-        self.push_char_and_break_if('=', utils::break_equal);
+        self.push_char('=');
 
         self.write_expression(numeric_for.get_start());
         self.push_char(',');
@@ -619,6 +593,17 @@ impl LuaGenerator for DenseLuaGenerator {
         }
     }
 
+    fn write_goto_statement(&mut self, goto: &nodes::GotoStatement) {
+        self.push_str("goto");
+        self.write_identifier(goto.get_label());
+    }
+
+    fn write_label_statement(&mut self, label: &nodes::LabelStatement) {
+        self.push_str("::");
+        self.write_identifier(label.get_name());
+        self.push_str("::");
+    }
+
     fn write_repeat_statement(&mut self, repeat: &nodes::RepeatStatement) {
         self.push_str("repeat");
 
@@ -695,7 +680,7 @@ impl LuaGenerator for DenseLuaGenerator {
             self.push_char('>');
         }
 
-        self.push_char_and_break_if('=', utils::break_equal);
+        self.push_char('=');
         self.write_type(statement.get_type());
     }
 
@@ -712,12 +697,10 @@ impl LuaGenerator for DenseLuaGenerator {
     }
 
     fn write_variable_arguments_expression(&mut self, _token: &Option<nodes::Token>) {
-        self.push_str_and_break_if("...", utils::break_variable_arguments);
+        self.push_str("...");
     }
 
     fn write_binary_expression(&mut self, binary: &nodes::BinaryExpression) {
-        use nodes::BinaryOperator;
-
         let operator = binary.operator();
         let left = binary.left();
         let right = binary.right();
@@ -728,10 +711,7 @@ impl LuaGenerator for DenseLuaGenerator {
             self.write_expression(left);
         }
 
-        match operator {
-            BinaryOperator::Concat => self.push_str_and_break_if("..", utils::break_concat),
-            _ => self.push_str(operator.to_str()),
-        }
+        self.push_str(operator.to_str());
 
         if operator.right_needs_parentheses(right) {
             self.write_expression_in_parentheses(right);
@@ -745,7 +725,7 @@ impl LuaGenerator for DenseLuaGenerator {
 
         match unary.operator() {
             Length => self.push_char('#'),
-            Minus => self.push_char_and_break_if('-', utils::break_minus),
+            Minus => self.push_char('-'),
             Not => self.push_str("not"),
         }
 
@@ -760,6 +740,7 @@ impl LuaGenerator for DenseLuaGenerator {
     }
 
     fn write_function(&mut self, function: &nodes::FunctionExpression) {
+        self.write_attributes(function.iter_attributes());
         self.push_str("function");
 
         if let Some(generics) = function.get_generic_parameters() {
@@ -893,6 +874,10 @@ impl LuaGenerator for DenseLuaGenerator {
                 } else {
                     let mut result = format!("{}", float);
 
+                    if number.has_decimal_point() && float.fract() == 0.0 {
+                        result.push_str(".0");
+                    }
+
                     if let Some(exponent) = number.get_exponent() {
                         let exponent_char = number
                             .is_uppercase()
@@ -954,12 +939,7 @@ impl LuaGenerator for DenseLuaGenerator {
     }
 
     fn write_string(&mut self, string: &nodes::StringExpression) {
-        let result = utils::write_string(string.get_value());
-        if result.starts_with('[') {
-            self.push_str_and_break_if(&result, utils::break_long_string);
-        } else {
-            self.push_str(&result);
-        }
+        self.push_str(&utils::write_string(string.get_value()));
     }
 
     fn write_interpolated_string(
@@ -1046,12 +1026,7 @@ impl LuaGenerator for DenseLuaGenerator {
     }
 
     fn write_string_type(&mut self, string_type: &nodes::StringType) {
-        let result = utils::write_string(string_type.get_value());
-        if result.starts_with('[') {
-            self.push_str_and_break_if(&result, utils::break_long_string);
-        } else {
-            self.push_str(&result);
-        }
+        self.push_str(&utils::write_string(string_type.get_value()));
     }
 
     fn write_array_type(&mut self, array: &nodes::ArrayType) {
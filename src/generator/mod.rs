@@ -12,6 +12,25 @@ pub use token_based::TokenBasedLuaGenerator;
 
 use crate::nodes;
 
+/// Generates readable Lua code from a block, to display it in error messages or test
+/// failures. Returns `None` if the code could not be generated.
+///
+/// ```
+/// # use darklua_core::generator::generate_for_diagnostics;
+/// # use darklua_core::Parser;
+/// let block = Parser::default().parse("return  1").unwrap();
+///
+/// assert_eq!(generate_for_diagnostics(&block).unwrap().trim_end(), "return 1");
+/// ```
+pub fn generate_for_diagnostics(block: &nodes::Block) -> Option<String> {
+    std::panic::catch_unwind(|| {
+        let mut generator = ReadableLuaGenerator::default();
+        generator.write_block(block);
+        generator.into_string()
+    })
+    .ok()
+}
+
 /// A trait to let its implementation define how the Lua code is generated. See
 /// [ReadableLuaGenerator](struct.ReadableLuaGenerator.html) and
 /// [DenseLuaGenerator](struct.DenseLuaGenerator.html) for implementations.
@@ -30,7 +49,9 @@ pub trait LuaGenerator {
             CompoundAssign(statement) => self.write_compound_assign(statement),
             Function(statement) => self.write_function_statement(statement),
             GenericFor(statement) => self.write_generic_for(statement),
+            Goto(statement) => self.write_goto_statement(statement),
             If(statement) => self.write_if_statement(statement),
+            Label(statement) => self.write_label_statement(statement),
             LocalAssign(statement) => self.write_local_assign(statement),
             LocalFunction(statement) => self.write_local_function(statement),
             TypeFunction(statement) => self.write_type_function(statement),
@@ -46,7 +67,9 @@ pub trait LuaGenerator {
     fn write_do_statement(&mut self, do_statement: &nodes::DoStatement);
     fn write_compound_assign(&mut self, assign: &nodes::CompoundAssignStatement);
     fn write_generic_for(&mut self, generic_for: &nodes::GenericForStatement);
+    fn write_goto_statement(&mut self, goto: &nodes::GotoStatement);
     fn write_if_statement(&mut self, if_statement: &nodes::IfStatement);
+    fn write_label_statement(&mut self, label: &nodes::LabelStatement);
     fn write_function_statement(&mut self, function: &nodes::FunctionStatement);
     fn write_last_statement(&mut self, statement: &nodes::LastStatement);
     fn write_local_assign(&mut self, assign: &nodes::LocalAssignStatement);
@@ -308,6 +331,86 @@ mod test {
         };
     }
 
+    macro_rules! test_float_round_trip {
+        (
+            $generator:expr => (
+                $($name:ident => $value:expr),+,
+            )
+        ) => {
+            $(
+                #[test]
+                fn $name() {
+                    let value: f64 = $value;
+
+                    let mut generator = $generator;
+                    generator.write_expression(&$crate::nodes::Expression::from(value));
+                    let code = format!("return {}", generator.into_string());
+
+                    let block = $crate::Parser::default()
+                        .parse(&code)
+                        .unwrap_or_else(|err| panic!("unable to parse `{}`: {:?}", code, err));
+
+                    let expression = match block.get_last_statement() {
+                        Some($crate::nodes::LastStatement::Return(statement)) => statement
+                            .iter_expressions()
+                            .next()
+                            .expect("return statement should have an expression"),
+                        _ => panic!("expected a return statement in `{}`", code),
+                    };
+
+                    match $crate::process::Evaluator::default().evaluate(expression) {
+                        $crate::process::LuaValue::Number(number) => assert_eq!(
+                            number.to_bits(),
+                            value.to_bits(),
+                            "`{}` is read as {:e} instead of {:e}",
+                            code,
+                            number,
+                            value
+                        ),
+                        other => panic!("`{}` is read as {:?}", code, other),
+                    }
+                }
+            )*
+        };
+    }
+
+    macro_rules! test_string_round_trip {
+        (
+            $generator:expr => (
+                $($name:ident => $value:expr),+,
+            )
+        ) => {
+            $(
+                #[test]
+                fn $name() {
+                    let value: String = $value.into();
+
+                    let mut generator = $generator;
+                    generator.write_expression(
+                        &$crate::nodes::StringExpression::from_value(value.as_str()).into(),
+                    );
+                    let code = format!("return {}", generator.into_string());
+
+                    let block = $crate::Parser::default()
+                        .parse(&code)
+                        .unwrap_or_else(|err| panic!("unable to parse `{}`: {:?}", code, err));
+
+                    match block.get_last_statement() {
+                        Some($crate::nodes::LastStatement::Return(statement)) => {
+                            match statement.iter_expressions().next() {
+                                Some($crate::nodes::Expression::String(string)) => {
+                                    assert_eq!(string.get_value(), value, "read from `{}`", code)
+                                }
+                                _ => panic!("expected a string in `{}`", code),
+                            }
+                        }
+                        _ => panic!("expected a return statement in `{}`", code),
+                    }
+                }
+            )*
+        };
+    }
+
     macro_rules! blocks_consistency {
         (
             $generator:expr => (
@@ -403,6 +506,24 @@ mod $mod_name {
             concat_variable_arguments_with_number => "return ... ..1",
             double_unary_minus => "return - -10",
             binary_minus_with_unary_minus => "return 100- -10",
+            local_call_followed_by_parenthese_call => "local a = f(); (g or h)()",
+            local_call_followed_by_parenthese_call_on_next_line => "local a = f()\n;(g or h)()",
+            call_followed_by_parenthese_call => "f(); (g)()",
+            assign_followed_by_parenthese_assign => "a = b; (c).d = 1",
+            compound_assign_followed_by_parenthese_compound_assign => "a += b; (c).d += 1",
+            repeat_followed_by_parenthese_call => "repeat until a; (f)()",
+            unary_followed_by_parenthese_call => "local a = -b; (f)()",
+            if_expression_followed_by_parenthese_call => "local a = if b then c else d; (f)()",
+            binary_followed_by_parenthese_call => "local a = b + c; (f)()",
+            type_function => "type function Pair(a, b) return types.newtable() end",
+            type_function_with_body => "type function Keys(t)\n\tlocal result = {}\n\tfor key in t:properties() do\n\t\ttable.insert(result, key)\n\tend\n\treturn types.unionof(table.unpack(result))\nend",
+            export_type_function => "export type function Same(t) return t end",
+            function_with_attribute => "@native function foo() end",
+            method_with_attribute => "@native function foo.bar:baz() end",
+            local_function_with_attribute => "@checked local function foo() end",
+            local_function_with_multiple_attributes => "@native @checked local function foo() end",
+            attributes_on_separate_line => "@native\nlocal function foo() end",
+            nested_function_with_attribute => "local function foo() @native local function bar() end return bar end",
         ));
     }
 
@@ -413,12 +534,56 @@ mod $mod_name {
             zero => "0",
             one => "1",
             integer => "123",
+            whole_float => "1.0",
             hex_number => "0x12",
             hex_number_with_letter => "0x12a",
             hex_with_exponent => "0x12p4",
         ));
     }
 
+    mod float_round_trip {
+        use super::*;
+
+        test_float_round_trip!($generator => (
+            below_two_power_53 => 9007199254740991.0,
+            two_power_53 => 9007199254740992.0,
+            above_two_power_53 => 9007199254740994.0,
+            above_two_power_53_with_exponent => 9007199254740994.0e5,
+            seventeen_significant_digits => 0.30000000000000004,
+            small_value_with_seventeen_digits => 1.4514526454261344e-7,
+            large_value_with_seventeen_digits => 123456789012345680000.0,
+            negative_small_value => -1.2345678901234567e-50,
+            ten_power_23 => 1e23,
+            smallest_subnormal => f64::from_bits(1),
+            largest_subnormal => f64::from_bits(0x000F_FFFF_FFFF_FFFF),
+            smallest_normal => f64::MIN_POSITIVE,
+            largest_value => f64::MAX,
+            negative_zero => -0.0,
+        ));
+    }
+
+    mod string_round_trip {
+        use super::*;
+
+        test_string_round_trip!($generator => (
+            empty => "",
+            null_character => "\0",
+            null_character_followed_by_digits => "\0123",
+            control_characters_followed_by_digits => "\u{1}1\u{1F}9\u{7F}0",
+            single_quote => "'",
+            double_quote => "\"",
+            both_quotes => "'\"'\"",
+            backslashes => "\\ \\z \\n",
+            closing_brackets => "]]",
+            closing_brackets_with_equals => "]=] ]==]",
+            carriage_return => "a\r\nb",
+            unicode => "é 😀 \u{10FFFF}",
+            long_text_with_closing_brackets => "line ]] ]=]\n".repeat(10),
+            long_text_starting_with_new_line => "\nline\n".repeat(10),
+            long_text_with_both_quotes => "'\"".repeat(40),
+        ));
+    }
+
     mod binary {
         use super::*;
 
@@ -1122,6 +1287,265 @@ mod $mod_name {
         };
     }
 
+    mod dense_token_adjacency {
+        use super::*;
+        use crate::nodes::*;
+
+        const BINARY_OPERATORS: [BinaryOperator; 16] = [
+            BinaryOperator::And,
+            BinaryOperator::Or,
+            BinaryOperator::Equal,
+            BinaryOperator::NotEqual,
+            BinaryOperator::LowerThan,
+            BinaryOperator::LowerOrEqualThan,
+            BinaryOperator::GreaterThan,
+            BinaryOperator::GreaterOrEqualThan,
+            BinaryOperator::Plus,
+            BinaryOperator::Minus,
+            BinaryOperator::Asterisk,
+            BinaryOperator::Slash,
+            BinaryOperator::DoubleSlash,
+            BinaryOperator::Percent,
+            BinaryOperator::Caret,
+            BinaryOperator::Concat,
+        ];
+
+        const UNARY_OPERATORS: [UnaryOperator; 3] = [
+            UnaryOperator::Minus,
+            UnaryOperator::Not,
+            UnaryOperator::Length,
+        ];
+
+        /// Expressions that start or end with characters that can be read differently when
+        /// they touch the previous or the next token.
+        fn operands() -> Vec<Expression> {
+            vec![
+                Expression::identifier("a"),
+                Expression::identifier("_1"),
+                DecimalNumber::new(1.0).into(),
+                DecimalNumber::new(-1.0).into(),
+                DecimalNumber::new(0.5).into(),
+                DecimalNumber::new(-0.5).into(),
+                HexNumber::new(0xF, false).into(),
+                Expression::variable_arguments(),
+                StringExpression::from_value("a").into(),
+                StringExpression::from_value("line ]]\n".repeat(6)).into(),
+                UnaryExpression::new(UnaryOperator::Minus, DecimalNumber::new(-1.0)).into(),
+                UnaryExpression::new(UnaryOperator::Minus, Expression::identifier("a")).into(),
+                IndexExpression::new(
+                    Expression::identifier("t"),
+                    StringExpression::from_value("[[b]]".repeat(10)),
+                )
+                .into(),
+                TableExpression::default().into(),
+                true.into(),
+            ]
+        }
+
+        fn crafted_expressions() -> Vec<Expression> {
+            let operands = operands();
+            let mut expressions = operands.clone();
+
+            for operand in operands.iter() {
+                for operator in UNARY_OPERATORS.iter() {
+                    expressions.push(UnaryExpression::new(*operator, operand.clone()).into());
+                }
+                expressions.push(
+                    IndexExpression::new(Expression::identifier("t"), operand.clone()).into(),
+                );
+            }
+
+            for left in operands.iter() {
+                for operator in BINARY_OPERATORS.iter() {
+                    for right in operands.iter() {
+                        expressions.push(
+                            BinaryExpression::new(*operator, left.clone(), right.clone()).into(),
+                        );
+                    }
+                }
+            }
+
+            expressions
+        }
+
+        fn generate_return(mut generator: impl LuaGenerator, expression: &Expression) -> String {
+            generator.write_expression(expression);
+            format!("return {}", generator.into_string())
+        }
+
+        #[test]
+        fn dense_code_is_read_like_readable_code() {
+            let parser = crate::Parser::default();
+
+            for expression in crafted_expressions() {
+                let dense_code = generate_return(DenseLuaGenerator::default(), &expression);
+                let readable_code = generate_return(ReadableLuaGenerator::default(), &expression);
+
+                let dense_block = parser
+                    .parse(&dense_code)
+                    .unwrap_or_else(|err| panic!("unable to parse `{}`: {:?}", dense_code, err));
+                let readable_block = parser
+                    .parse(&readable_code)
+                    .unwrap_or_else(|err| panic!("unable to parse `{}`: {:?}", readable_code, err));
+
+                pretty_assertions::assert_eq!(
+                    dense_block,
+                    readable_block,
+                    "`{}` is not read like `{}`",
+                    dense_code,
+                    readable_code
+                );
+            }
+        }
+
+        #[test]
+        fn dense_code_with_narrow_column_span_is_read_like_readable_code() {
+            let parser = crate::Parser::default();
+
+            for expression in crafted_expressions() {
+                let dense_code = generate_return(DenseLuaGenerator::new(1), &expression);
+                let readable_code = generate_return(ReadableLuaGenerator::default(), &expression);
+
+                pretty_assertions::assert_eq!(
+                    parser.parse(&dense_code).ok(),
+                    parser.parse(&readable_code).ok(),
+                    "`{}` is not read like `{}`",
+                    dense_code,
+                    readable_code
+                );
+            }
+        }
+
+        #[test]
+        fn minus_followed_by_negative_number() {
+            let expression =
+                UnaryExpression::new(UnaryOperator::Minus, DecimalNumber::new(-1.0)).into();
+
+            assert_eq!(
+                generate_return(DenseLuaGenerator::default(), &expression),
+                "return - -1"
+            );
+        }
+
+        #[test]
+        fn index_with_long_string_containing_brackets() {
+            let expression = IndexExpression::new(
+                Expression::identifier("t"),
+                StringExpression::from_value("line ]]\n".repeat(6)),
+            )
+            .into();
+
+            assert!(generate_return(DenseLuaGenerator::default(), &expression)
+                .starts_with("return t[ [=["));
+        }
+
+        blocks_consistency!(DenseLuaGenerator::default() => (
+            typed_local_with_generic_type => "local a: Array<number> = b",
+            typed_local_with_nested_generic_type => "local a: Map<string, Array<number>> = b",
+            floor_division_of_negative_number => "return a // -1",
+            concat_hex_number => "return 0xF .. 1",
+            variable_arguments_after_concat => "return a .. ...",
+            keyword_after_number => "return 1 and 2 or 0xF",
+        ));
+    }
+
+    mod readable_max_line_length {
+        use super::*;
+        use crate::Parser;
+
+        const TABLE: &str =
+            "local config = {name = value, version = 1, enabled = true, tags = {first, second}}";
+
+        fn generate(code: &str, max_line_length: usize) -> String {
+            let block = Parser::default().parse(code).expect("unable to parse code");
+            let mut generator =
+                ReadableLuaGenerator::default().with_max_line_length(max_line_length);
+            generator.write_block(&block);
+            generator.into_string()
+        }
+
+        #[test]
+        fn table_fits_on_a_single_line() {
+            pretty_assertions::assert_eq!(generate(TABLE, 120), format!("{}\n", TABLE));
+        }
+
+        #[test]
+        fn table_is_broken_across_lines() {
+            pretty_assertions::assert_eq!(
+                generate(TABLE, 40),
+                "local config = {\n    name = value,\n    version = 1,\n    enabled = true,\n    tags = {first, second},\n}\n"
+            );
+        }
+
+        #[test]
+        fn table_is_never_broken_with_zero_length() {
+            pretty_assertions::assert_eq!(generate(TABLE, 0), format!("{}\n", TABLE));
+        }
+
+        #[test]
+        fn table_length_includes_indentation() {
+            pretty_assertions::assert_eq!(
+                generate("local t = {a, b, c}", 20),
+                "local t = {a, b, c}\n"
+            );
+            pretty_assertions::assert_eq!(
+                generate("do local t = {a, b, c} end", 20),
+                "do\n    local t = {\n        a,\n        b,\n        c,\n    }\nend\n"
+            );
+        }
+
+        #[test]
+        fn call_arguments_fit_on_a_single_line() {
+            let code = "print(alpha, beta, gamma, delta, epsilon, zeta, eta)";
+
+            pretty_assertions::assert_eq!(generate(code, 120), format!("{}\n", code));
+        }
+
+        #[test]
+        fn call_arguments_are_broken_across_lines() {
+            pretty_assertions::assert_eq!(
+                generate("print(alpha, beta, gamma, delta, epsilon, zeta, eta)", 40),
+                "print(\n    alpha,\n    beta,\n    gamma,\n    delta,\n    epsilon,\n    zeta,\n    eta\n)\n"
+            );
+        }
+
+        #[test]
+        fn callback_argument_is_not_broken() {
+            let code = "call(function()\n    return value\nend)\n";
+
+            pretty_assertions::assert_eq!(generate(code, 40), code);
+        }
+
+        #[test]
+        fn binary_chain_is_broken_across_lines() {
+            pretty_assertions::assert_eq!(
+                generate(
+                    "local message = prefix .. separator .. name .. suffix .. extension",
+                    40
+                ),
+                "local message = prefix\n    .. separator\n    .. name\n    .. suffix\n    .. extension\n"
+            );
+        }
+
+        #[test]
+        fn binary_chain_with_other_operators() {
+            pretty_assertions::assert_eq!(
+                generate(
+                    "return first_condition and (a or b) and second_condition and not done",
+                    40
+                ),
+                "return first_condition\n    and (a or b)\n    and second_condition\n    and not done\n"
+            );
+        }
+
+        #[test]
+        fn binary_chain_fits_on_a_single_line() {
+            let code = "local message = prefix .. separator .. name .. suffix .. extension";
+
+            pretty_assertions::assert_eq!(generate(code, 100), format!("{}\n", code));
+        }
+    }
+
     snapshot_generator!(dense, DenseLuaGenerator::default());
     snapshot_generator!(readable, ReadableLuaGenerator::default());
     snapshot_generator!(token_based, TokenBasedLuaGenerator::new(""));
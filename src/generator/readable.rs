@@ -9,7 +9,9 @@ enum StatementType {
     CompoundAssign,
     Function,
     GenericFor,
+    Goto,
     If,
+    Label,
     LocalAssign,
     LocalFunction,
     TypeFunction,
@@ -33,7 +35,9 @@ impl From<&nodes::Statement> for StatementType {
             CompoundAssign(_) => Self::CompoundAssign,
             Function(_) => Self::Function,
             GenericFor(_) => Self::GenericFor,
+            Goto(_) => Self::Goto,
             If(_) => Self::If,
+            Label(_) => Self::Label,
             LocalAssign(_) => Self::LocalAssign,
             LocalFunction(_) => Self::LocalFunction,
             TypeFunction(_) => Self::TypeFunction,
@@ -62,6 +66,7 @@ impl From<&nodes::LastStatement> for StatementType {
 #[derive(Debug, Clone)]
 pub struct ReadableLuaGenerator {
     column_span: usize,
+    max_line_length: Option<usize>,
     indentation: usize,
     current_line_length: usize,
     current_indentation: usize,
@@ -74,6 +79,7 @@ impl ReadableLuaGenerator {
     pub fn new(column_span: usize) -> Self {
         Self {
             column_span,
+            max_line_length: None,
             indentation: 4,
             current_line_length: 0,
             current_indentation: 0,
@@ -83,6 +89,47 @@ impl ReadableLuaGenerator {
         }
     }
 
+    /// Breaks table constructors, function call arguments and chains of binary expressions
+    /// across multiple lines when they do not fit in the given number of characters,
+    /// including the indentation. A length of `0` keeps them on a single line.
+    pub fn with_max_line_length(mut self, max_line_length: usize) -> Self {
+        self.max_line_length = Some(max_line_length);
+        self
+    }
+
+    /// Measures the content written by the callback without breaking it, when a maximum
+    /// line length is set.
+    fn measure_line<F>(&self, write: F) -> Option<LineMeasure>
+    where
+        F: FnOnce(&mut Self),
+    {
+        let max_line_length = self.max_line_length?;
+
+        if max_line_length == 0 {
+            return Some(LineMeasure {
+                fits: true,
+                is_multiline: false,
+            });
+        }
+
+        let mut generator = Self::new(usize::MAX).with_max_line_length(0);
+        generator.push_can_add_new_line(false);
+        write(&mut generator);
+        let content = generator.into_string();
+
+        let line_length = if self.current_line_length == 0 {
+            self.indentation * self.current_indentation
+        } else {
+            self.current_line_length
+        };
+        let first_line = content.lines().next().unwrap_or_default();
+
+        Some(LineMeasure {
+            fits: line_length + first_line.chars().count() <= max_line_length,
+            is_multiline: content.contains('\n'),
+        })
+    }
+
     #[inline]
     fn can_add_new_line(&self) -> bool {
         self.can_add_new_line_stack.last().copied().unwrap_or(true)
@@ -412,6 +459,47 @@ impl ReadableLuaGenerator {
         self.push_char(')');
     }
 
+    fn write_binary_operand(&mut self, operand: &nodes::Expression, needs_parentheses: bool) {
+        if needs_parentheses {
+            self.write_expression_in_parentheses(operand);
+        } else {
+            self.write_expression(operand);
+        }
+    }
+
+    /// Writes each operand of a chain of binary expressions with the same operator on its
+    /// own line, with the operator at the start of the line.
+    fn write_binary_chain(&mut self, binary: &nodes::BinaryExpression) {
+        let operator = binary.operator();
+        let mut operands = Vec::new();
+        flatten_binary_chain(binary, &mut operands);
+
+        let mut operands = operands.into_iter();
+
+        if let Some((operand, needs_parentheses)) = operands.next() {
+            self.write_binary_operand(operand, needs_parentheses);
+        }
+
+        self.push_indentation();
+
+        for (operand, needs_parentheses) in operands {
+            self.push_new_line();
+            self.write_indentation();
+            self.raw_push_str(operator.to_str());
+            self.raw_push_char(' ');
+            self.write_binary_operand(operand, needs_parentheses);
+        }
+
+        self.pop_indentation();
+    }
+
+    fn write_attributes<'a>(&mut self, attributes: impl Iterator<Item = &'a nodes::Attribute>) {
+        for attribute in attributes {
+            self.push_str(&format!("@{}", attribute.get_name().get_name()));
+            self.push_space();
+        }
+    }
+
     fn write_function_generics(&mut self, generics: &nodes::GenericParameters) {
         if generics.is_empty() {
             return;
@@ -440,6 +528,46 @@ impl ReadableLuaGenerator {
     }
 }
 
+/// The result of measuring some content against the maximum line length. Content that
+/// already spans multiple lines (like a function with a body) only has its first line
+/// measured.
+#[derive(Debug, Clone, Copy)]
+struct LineMeasure {
+    fits: bool,
+    is_multiline: bool,
+}
+
+/// Collects the operands of nested binary expressions that use the same operator and are
+/// written without parentheses, with a flag telling if the operand needs parentheses.
+fn flatten_binary_chain<'a>(
+    binary: &'a nodes::BinaryExpression,
+    operands: &mut Vec<(&'a nodes::Expression, bool)>,
+) {
+    let operator = binary.operator();
+
+    let left = binary.left();
+    let left_needs_parentheses = operator.left_needs_parentheses(left);
+    match left {
+        nodes::Expression::Binary(left_binary)
+            if left_binary.operator() == operator && !left_needs_parentheses =>
+        {
+            flatten_binary_chain(left_binary, operands);
+        }
+        _ => operands.push((left, left_needs_parentheses)),
+    }
+
+    let right = binary.right();
+    let right_needs_parentheses = operator.right_needs_parentheses(right);
+    match right {
+        nodes::Expression::Binary(right_binary)
+            if right_binary.operator() == operator && !right_needs_parentheses =>
+        {
+            flatten_binary_chain(right_binary, operands);
+        }
+        _ => operands.push((right, right_needs_parentheses)),
+    }
+}
+
 impl Default for ReadableLuaGenerator {
     fn default() -> Self {
         Self::new(80)
@@ -461,9 +589,7 @@ impl LuaGenerator for ReadableLuaGenerator {
             self.write_statement(statement);
 
             if let Some(next_statement) = statements.peek() {
-                if utils::starts_with_parenthese(next_statement)
-                    && utils::ends_with_prefix(statement)
-                {
+                if utils::needs_semicolon(statement, next_statement) {
                     self.push_char(';');
                 }
 
@@ -596,6 +722,7 @@ impl LuaGenerator for ReadableLuaGenerator {
     }
 
     fn write_local_function(&mut self, function: &nodes::LocalFunctionStatement) {
+        self.write_attributes(function.iter_attributes());
         self.push_str("local function ");
         self.raw_push_str(function.get_name());
 
@@ -795,6 +922,7 @@ impl LuaGenerator for ReadableLuaGenerator {
     }
 
     fn write_function_statement(&mut self, function: &nodes::FunctionStatement) {
+        self.write_attributes(function.iter_attributes());
         self.push_str("function ");
         let name = function.get_name();
 
@@ -849,6 +977,17 @@ impl LuaGenerator for ReadableLuaGenerator {
         }
     }
 
+    fn write_goto_statement(&mut self, goto: &nodes::GotoStatement) {
+        self.push_str("goto");
+        self.write_identifier(goto.get_label());
+    }
+
+    fn write_label_statement(&mut self, label: &nodes::LabelStatement) {
+        self.push_str("::");
+        self.write_identifier(label.get_name());
+        self.raw_push_str("::");
+    }
+
     fn write_repeat_statement(&mut self, repeat: &nodes::RepeatStatement) {
         self.push_str("repeat");
 
@@ -958,6 +1097,13 @@ impl LuaGenerator for ReadableLuaGenerator {
     }
 
     fn write_binary_expression(&mut self, binary: &nodes::BinaryExpression) {
+        let measure = self.measure_line(|generator| generator.write_binary_expression(binary));
+
+        if measure.map_or(false, |measure| !measure.fits) {
+            self.write_binary_chain(binary);
+            return;
+        }
+
         let operator = binary.operator();
         let left = binary.left();
         let right = binary.right();
@@ -999,6 +1145,7 @@ impl LuaGenerator for ReadableLuaGenerator {
     }
 
     fn write_function(&mut self, function: &nodes::FunctionExpression) {
+        self.write_attributes(function.iter_attributes());
         self.push_str("function");
 
         if let Some(generics) = function.get_generic_parameters() {
@@ -1045,6 +1192,37 @@ impl LuaGenerator for ReadableLuaGenerator {
     }
 
     fn write_tuple_arguments(&mut self, arguments: &nodes::TupleArguments) {
+        let measure = if arguments.is_empty() {
+            None
+        } else {
+            self.measure_line(|generator| generator.write_tuple_arguments(arguments))
+        };
+
+        if measure.map_or(false, |measure| !measure.fits) {
+            self.raw_push_char('(');
+            self.push_indentation();
+
+            let last_index = arguments.len().saturating_sub(1);
+            arguments
+                .iter_values()
+                .enumerate()
+                .for_each(|(index, expression)| {
+                    self.push_new_line();
+                    self.write_indentation();
+                    self.write_expression(expression);
+
+                    if index != last_index {
+                        self.raw_push_char(',');
+                    }
+                });
+
+            self.pop_indentation();
+            self.push_new_line();
+            self.write_indentation();
+            self.raw_push_char(')');
+            return;
+        }
+
         self.raw_push_char('(');
 
         let last_index = arguments.len().saturating_sub(1);
@@ -1124,16 +1302,28 @@ impl LuaGenerator for ReadableLuaGenerator {
     }
 
     fn write_table(&mut self, table: &nodes::TableExpression) {
-        self.push_char('{');
-
         let entries = table.get_entries();
         let table_len = entries.len();
 
+        // tables with an entry that spans multiple lines are always broken
+        let fits_within_max_line_length = if table_len == 0 {
+            None
+        } else {
+            self.measure_line(|generator| generator.write_table(table))
+                .map(|measure| measure.fits && !measure.is_multiline)
+        };
+
+        self.push_char('{');
+
         if table_len == 0 {
             self.raw_push_char('}');
         } else {
-            let column_space = self.column_span.saturating_sub(self.current_line_length);
-            if self.table_fits_on_line(entries, column_space) {
+            let fits_on_line = fits_within_max_line_length.unwrap_or_else(|| {
+                let column_space = self.column_span.saturating_sub(self.current_line_length);
+                self.table_fits_on_line(entries, column_space)
+            });
+
+            if fits_on_line {
                 let last_index = table_len.saturating_sub(1);
 
                 entries.iter().enumerate().for_each(|(index, entry)| {
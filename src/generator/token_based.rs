@@ -7,12 +7,23 @@ use crate::{
 
 /// This implementation of [LuaGenerator](trait.LuaGenerator.html) outputs the
 /// AST nodes from the tokens associated with each of them.
+///
+/// When the code was parsed with [`Parser::preserve_tokens`](crate::Parser::preserve_tokens)
+/// and no node was modified, the generated code is identical to the original code. Nodes
+/// without tokens (like the nodes created by rules) are written with the spaces needed to
+/// separate them from the other tokens, and new lines are added so that the following tokens
+/// stay on their original line. Strings with characters outside of ASCII are also rewritten
+/// when [`with_escaped_unicode`](Self::with_escaped_unicode) is enabled.
 #[derive(Debug, Clone)]
 pub struct TokenBasedLuaGenerator<'a> {
     original_code: &'a str,
     output: String,
     currently_commenting: bool,
     current_line: usize,
+    escape_unicode: bool,
+    // the offset in the original code where the last written token or trivia ends, when
+    // nothing else was written after it
+    original_end: Option<usize>,
 }
 
 impl<'a> TokenBasedLuaGenerator<'a> {
@@ -22,17 +33,50 @@ impl<'a> TokenBasedLuaGenerator<'a> {
             output: String::new(),
             currently_commenting: false,
             current_line: 1,
+            escape_unicode: false,
+            original_end: None,
+        }
+    }
+
+    /// Writes the characters outside of ASCII in strings as escape sequences of their
+    /// bytes, including strings written from their original tokens.
+    pub fn with_escaped_unicode(mut self, escape_unicode: bool) -> Self {
+        self.escape_unicode = escape_unicode;
+        self
+    }
+
+    fn write_string_token(&mut self, token: &Token, value: &str) {
+        if self.escape_unicode {
+            if let Some(escaped) =
+                utils::escape_unicode_string(token.read(self.original_code), value)
+            {
+                let mut new_token = token.clone();
+                new_token.replace_with_content(escaped);
+                self.write_token(&new_token);
+                return;
+            }
+        }
+        self.write_token(token);
+    }
+
+    fn write_string_value(&mut self, value: &str) {
+        if self.escape_unicode {
+            self.write_symbol(&utils::write_ascii_string(value));
+        } else {
+            self.write_symbol(&utils::write_string(value));
         }
     }
 
     fn push_str(&mut self, string: &str) {
         self.current_line += utils::count_new_lines(string);
         self.output.push_str(string);
+        self.original_end = None;
     }
 
     fn write_trivia(&mut self, trivia: &Trivia) {
         let content = trivia.read(self.original_code);
         self.push_str(content);
+        self.original_end = trivia.end_offset();
 
         match trivia.kind() {
             TriviaKind::Comment => {
@@ -61,6 +105,14 @@ impl<'a> TokenBasedLuaGenerator<'a> {
         let content = token.read(self.original_code);
 
         if !content.is_empty() {
+            // tokens that were next to each other in the original code do not need
+            // to be separated, since the original code was read correctly
+            let follows_original_code = self.original_end.is_some()
+                && token
+                    .start_position()
+                    .and_then(|position| position.offset())
+                    == self.original_end;
+
             if self.currently_commenting {
                 self.uncomment();
             }
@@ -72,7 +124,7 @@ impl<'a> TokenBasedLuaGenerator<'a> {
                 }
             }
 
-            if space_check {
+            if space_check && !follows_original_code {
                 if let Some(next_character) = content.chars().next() {
                     if self.needs_space(next_character) {
                         self.output.push(' ');
@@ -81,6 +133,7 @@ impl<'a> TokenBasedLuaGenerator<'a> {
             }
 
             self.push_str(content);
+            self.original_end = token.end_offset();
         }
 
         for trivia in token.iter_trailing_trivia() {
@@ -97,9 +150,7 @@ impl<'a> TokenBasedLuaGenerator<'a> {
             if let Some(semicolon) = tokens.semicolons.get(index).unwrap_or(&None) {
                 self.write_token(semicolon);
             } else if let Some((_, next_statement)) = iterator.peek() {
-                if utils::starts_with_parenthese(next_statement)
-                    && utils::ends_with_prefix(statement)
-                {
+                if utils::needs_semicolon(statement, next_statement) {
                     self.write_symbol(";");
                 }
             };
@@ -107,6 +158,10 @@ impl<'a> TokenBasedLuaGenerator<'a> {
 
         if let Some(statement) = block.get_last_statement() {
             self.write_last_statement(statement);
+
+            if let Some(semicolon) = &tokens.last_semicolon {
+                self.write_token(semicolon);
+            }
         }
 
         if let Some(token) = &tokens.final_token {
@@ -671,6 +726,12 @@ impl<'a> TokenBasedLuaGenerator<'a> {
         self.write_token(&tokens.end);
     }
 
+    fn write_label_with_tokens(&mut self, label: &LabelStatement, tokens: &LabelTokens) {
+        self.write_token(&tokens.left_colons);
+        self.write_identifier(label.get_name());
+        self.write_token(&tokens.right_colons);
+    }
+
     fn write_repeat_with_tokens(&mut self, repeat: &RepeatStatement, tokens: &RepeatTokens) {
         self.write_token(&tokens.repeat);
         self.write_block(repeat.get_block());
@@ -1031,7 +1092,7 @@ impl<'a> TokenBasedLuaGenerator<'a> {
             self.write_generic_type_pack(generic_type_pack);
 
             if (i + type_variables_len) < last_index {
-                if let Some(comma) = tokens.commas.get(i) {
+                if let Some(comma) = tokens.commas.get(i + type_variables_len) {
                     self.write_token(comma);
                 } else {
                     self.write_symbol(",");
@@ -1157,6 +1218,16 @@ impl<'a> TokenBasedLuaGenerator<'a> {
             match segment {
                 InterpolationSegment::String(string_segment) => {
                     if let Some(token) = string_segment.get_token() {
+                        if self.escape_unicode {
+                            let content = token.read(self.original_code);
+                            if !content.is_ascii() {
+                                let mut new_token = token.clone();
+                                new_token
+                                    .replace_with_content(utils::escape_unicode_bytes(content));
+                                self.write_token(&new_token);
+                                continue;
+                            }
+                        }
                         self.write_token(token);
                     } else {
                         self.write_symbol(&utils::write_interpolated_string_segment(string_segment))
@@ -1442,6 +1513,13 @@ impl<'a> TokenBasedLuaGenerator<'a> {
         }
     }
 
+    fn generate_label_tokens(&self, _label: &LabelStatement) -> LabelTokens {
+        LabelTokens {
+            left_colons: Token::from_content("::"),
+            right_colons: Token::from_content("::"),
+        }
+    }
+
     fn generate_repeat_tokens(&self, _repeat: &RepeatStatement) -> RepeatTokens {
         RepeatTokens {
             repeat: Token::from_content("repeat"),
@@ -1735,6 +1813,17 @@ impl<'a> TokenBasedLuaGenerator<'a> {
         self.push_str(symbol);
     }
 
+    fn write_attributes<'b>(&mut self, attributes: impl Iterator<Item = &'b Attribute>) {
+        for attribute in attributes {
+            if let Some(token) = attribute.get_token() {
+                self.write_token(token);
+            } else {
+                self.write_symbol("@");
+            }
+            self.write_identifier(attribute.get_name());
+        }
+    }
+
     fn write_typed_identifier(&mut self, typed_identifier: &TypedIdentifier) {
         if let Some(token) = typed_identifier.get_token() {
             let name_in_token = token.read(self.original_code);
@@ -1863,6 +1952,8 @@ impl LuaGenerator for TokenBasedLuaGenerator<'_> {
     }
 
     fn write_function_statement(&mut self, function: &FunctionStatement) {
+        self.write_attributes(function.iter_attributes());
+
         if let Some(tokens) = function.get_tokens() {
             self.write_function_statement_with_tokens(function, tokens);
         } else {
@@ -1911,6 +2002,8 @@ impl LuaGenerator for TokenBasedLuaGenerator<'_> {
     }
 
     fn write_local_function(&mut self, function: &LocalFunctionStatement) {
+        self.write_attributes(function.iter_attributes());
+
         if let Some(tokens) = function.get_tokens() {
             self.write_local_function_with_tokens(function, tokens);
         } else {
@@ -1954,6 +2047,23 @@ impl LuaGenerator for TokenBasedLuaGenerator<'_> {
         }
     }
 
+    fn write_goto_statement(&mut self, goto: &GotoStatement) {
+        if let Some(token) = goto.get_token() {
+            self.write_token(token);
+        } else {
+            self.write_symbol("goto");
+        }
+        self.write_identifier(goto.get_label());
+    }
+
+    fn write_label_statement(&mut self, label: &LabelStatement) {
+        if let Some(tokens) = label.get_tokens() {
+            self.write_label_with_tokens(label, tokens);
+        } else {
+            self.write_label_with_tokens(label, &self.generate_label_tokens(label));
+        }
+    }
+
     fn write_repeat_statement(&mut self, repeat: &RepeatStatement) {
         if let Some(tokens) = repeat.get_tokens() {
             self.write_repeat_with_tokens(repeat, tokens);
@@ -2063,6 +2173,8 @@ impl LuaGenerator for TokenBasedLuaGenerator<'_> {
     }
 
     fn write_function(&mut self, function: &FunctionExpression) {
+        self.write_attributes(function.iter_attributes());
+
         if let Some(tokens) = function.get_tokens() {
             self.write_function_with_tokens(function, tokens);
         } else {
@@ -2160,9 +2272,9 @@ impl LuaGenerator for TokenBasedLuaGenerator<'_> {
 
     fn write_string(&mut self, string: &StringExpression) {
         if let Some(token) = string.get_token() {
-            self.write_token(token);
+            self.write_string_token(token, string.get_value());
         } else {
-            self.write_symbol(&utils::write_string(string.get_value()));
+            self.write_string_value(string.get_value());
         }
     }
 
@@ -2264,9 +2376,9 @@ impl LuaGenerator for TokenBasedLuaGenerator<'_> {
 
     fn write_string_type(&mut self, string_type: &StringType) {
         if let Some(token) = string_type.get_token() {
-            self.write_token(token);
+            self.write_string_token(token, string_type.get_value());
         } else {
-            self.write_symbol(&utils::write_string(string_type.get_value()));
+            self.write_string_value(string_type.get_value());
         }
     }
 
@@ -2361,13 +2473,21 @@ impl LuaGenerator for TokenBasedLuaGenerator<'_> {
     }
 
     fn write_variadic_type_pack(&mut self, variadic_type_pack: &VariadicTypePack) {
-        self.push_str("...");
+        if let Some(token) = variadic_type_pack.get_token() {
+            self.write_token(token);
+        } else {
+            self.push_str("...");
+        }
         self.write_type(variadic_type_pack.get_type());
     }
 
     fn write_generic_type_pack(&mut self, generic_type_pack: &GenericTypePack) {
         self.write_identifier(generic_type_pack.get_name());
-        self.push_str("...");
+        if let Some(token) = generic_type_pack.get_token() {
+            self.write_token_options(token, false);
+        } else {
+            self.push_str("...");
+        }
     }
 }
 
@@ -2474,12 +2594,25 @@ mod test {
         empty_repeat => "repeat until false",
         repeat_break_immediately => "repeat break until false",
         empty_while => "while true do end",
+        goto_label => "goto done\n::done::\n",
+        label_after_assign => "local value = a;\n::done::",
+        goto_continue_label => "for i = 1, 3 do\n\tif i == 2 then goto continue end\n\t:: continue ::\nend",
         while_break_immediately => "while true do\n\tbreak\nend",
+        empty_type_function => "type function Empty()\nend",
+        type_function_with_parameters => "type function Pair( a, b )\n\treturn types.newtable()\nend\n",
+        export_type_function => "export type function Same(t) return t end -- identity",
+        function_with_attribute => "@native function foo() end",
+        local_function_with_attributes => "@native  @checked\nlocal function foo() end",
+        attribute_with_comment => "--[[ fast ]] @native --[[ checked ]] local function foo() end",
+        function_with_generic_type_packs => "function f<T, --[[t]] U..., V ...>(...: U...): V...\nend",
+        type_with_variadic_type_packs => "type Callback = (... number) -> ... string",
 
         // last statements
         break_with_comment => "break -- exit loop",
         continue_with_comment => "continue -- skip to next iteration",
         empty_return => "return\n",
+        return_with_semicolon => "return 1; -- done",
+        break_with_semicolon => "while true do break; end",
 
         // expressions
         return_true => "return true",
@@ -10,22 +10,103 @@ const QUOTED_STRING_MAX_LENGTH: usize = 60;
 const LONG_STRING_MIN_LENGTH: usize = 20;
 const FORCE_LONG_STRING_NEW_LINE_THRESHOLD: usize = 6;
 
+/// The start or the end of a token, used to find tokens that can not be written next to
+/// each other.
+#[derive(Debug, Clone, Copy)]
+enum TokenEdge {
+    /// A letter, a digit or an underscore: identifiers, keywords and numbers continue
+    /// with those characters.
+    Word,
+    Digit,
+    /// The end of a number, which continues with any `.` that follows it.
+    Number,
+    Char(char),
+}
+
+impl TokenEdge {
+    fn matches_end(self, token: &str, last_character: char) -> bool {
+        match self {
+            Self::Word => is_word_character(last_character),
+            Self::Digit => last_character.is_ascii_digit(),
+            Self::Number => is_number(token),
+            Self::Char(character) => last_character == character,
+        }
+    }
+
+    fn matches_start(self, first_character: char) -> bool {
+        match self {
+            Self::Word => is_word_character(first_character),
+            Self::Digit | Self::Number => first_character.is_ascii_digit(),
+            Self::Char(character) => first_character == character,
+        }
+    }
+}
+
+/// Each pair is the end of a token and the start of the next token that would be read as
+/// different tokens when they are written without a space between them.
+const TOKEN_ADJACENCY_RULES: [(TokenEdge, TokenEdge); 14] = [
+    // `local a`, `return 1`, `a and b`
+    (TokenEdge::Word, TokenEdge::Word),
+    // `1 ..` and `0xF ...` would be malformed numbers
+    (TokenEdge::Number, TokenEdge::Char('.')),
+    // `- -a` and `- -1` would start a comment
+    (TokenEdge::Char('-'), TokenEdge::Char('-')),
+    // `.. ...` would be read as `...` followed by `..`
+    (TokenEdge::Char('.'), TokenEdge::Char('.')),
+    // `.. 1`
+    (TokenEdge::Char('.'), TokenEdge::Digit),
+    // `a[ [[b]]]` and `a[ [=[b]=]]` would start a long string
+    (TokenEdge::Char('['), TokenEdge::Char('[')),
+    (TokenEdge::Char('['), TokenEdge::Char('=')),
+    (TokenEdge::Char(']'), TokenEdge::Char(']')),
+    // `local a: T<U> = b` would be read as `>=`
+    (TokenEdge::Char('>'), TokenEdge::Char('=')),
+    (TokenEdge::Char('<'), TokenEdge::Char('=')),
+    (TokenEdge::Char('='), TokenEdge::Char('=')),
+    (TokenEdge::Char('~'), TokenEdge::Char('=')),
+    // `::` is a type cast and `//` a floor division
+    (TokenEdge::Char(':'), TokenEdge::Char(':')),
+    (TokenEdge::Char('/'), TokenEdge::Char('/')),
+];
+
 #[inline]
-pub fn should_break_with_space(ending_character: char, next_character: char) -> bool {
-    match ending_character {
-        '0'..='9' => matches!(next_character, '0'..='9' | 'A'..='Z' | 'a'..='z' | '_' | '.'),
-        'A'..='Z' | 'a'..='z' | '_' => {
-            next_character.is_ascii_alphanumeric() || next_character == '_'
+fn is_word_character(character: char) -> bool {
+    character.is_ascii_alphanumeric() || character == '_'
+}
+
+fn is_number(token: &str) -> bool {
+    let mut characters = token.chars();
+    match characters.next() {
+        Some('0'..='9') => true,
+        Some('.') => matches!(characters.next(), Some('0'..='9')),
+        _ => false,
+    }
+}
+
+/// Returns `true` if a space is needed between two tokens so that they are not read as
+/// different tokens.
+pub fn tokens_need_separator(previous_token: &str, next_token: &str) -> bool {
+    match (previous_token.chars().last(), next_token.chars().next()) {
+        (Some(last_character), Some(first_character)) => {
+            TOKEN_ADJACENCY_RULES.iter().any(|(end, start)| {
+                end.matches_end(previous_token, last_character)
+                    && start.matches_start(first_character)
+            })
         }
-        '>' => next_character == '=',
-        '-' => next_character == '-',
-        '[' => next_character == '[',
-        ']' => next_character == ']',
-        '.' => matches!(next_character, '.' | '0'..='9'),
         _ => false,
     }
 }
 
+/// Same as [`tokens_need_separator`], when only the last character of the previous token
+/// and the first character of the next token are known.
+#[inline]
+pub fn should_break_with_space(ending_character: char, next_character: char) -> bool {
+    tokens_need_separator(
+        ending_character.encode_utf8(&mut [0; 4]),
+        next_character.encode_utf8(&mut [0; 4]),
+    )
+}
+
 pub fn break_long_string(last_str: &str) -> bool {
     if let Some(last_char) = last_str.chars().last() {
         last_char == '['
@@ -52,24 +133,6 @@ pub fn break_minus(last_string: &str) -> bool {
     }
 }
 
-pub fn break_equal(last_string: &str) -> bool {
-    if let Some(last_char) = last_string.chars().last() {
-        last_char == '>'
-    } else {
-        false
-    }
-}
-
-pub fn break_concat(last_string: &str) -> bool {
-    if let Some('.') = last_string.chars().last() {
-        true
-    } else if let Some(first_char) = last_string.chars().next() {
-        first_char == '.' || first_char.is_ascii_digit()
-    } else {
-        false
-    }
-}
-
 pub fn ends_with_prefix(statement: &Statement) -> bool {
     match statement {
         Statement::Assign(assign) => {
@@ -93,6 +156,27 @@ pub fn ends_with_prefix(statement: &Statement) -> bool {
     }
 }
 
+/// Returns `true` if a semicolon is needed between two statements so that the code
+/// is not parsed differently, like a call that would otherwise be parsed as part of the
+/// previous statement, or a label that would be parsed as a type assertion.
+pub fn needs_semicolon(statement: &Statement, next_statement: &Statement) -> bool {
+    match next_statement {
+        Statement::Label(_) => ends_with_expression(statement),
+        _ => starts_with_parenthese(next_statement) && ends_with_prefix(statement),
+    }
+}
+
+fn ends_with_expression(statement: &Statement) -> bool {
+    match statement {
+        Statement::Assign(_)
+        | Statement::CompoundAssign(_)
+        | Statement::Call(_)
+        | Statement::Repeat(_) => true,
+        Statement::LocalAssign(assign) => assign.values_len() != 0,
+        _ => false,
+    }
+}
+
 pub fn starts_with_table(mut expression: &Expression) -> Option<&TableExpression> {
     loop {
         match expression {
@@ -205,8 +289,13 @@ pub fn write_number(number: &NumberExpression) -> String {
                 format!("({}1/0)", if float.is_sign_negative() { "-" } else { "" })
             } else {
                 format!(
-                    "{}{}",
+                    "{}{}{}",
                     float,
+                    if number.has_decimal_point() && float.fract() == 0.0 {
+                        ".0"
+                    } else {
+                        ""
+                    },
                     number
                         .get_exponent()
                         .map(|exponent| {
@@ -325,14 +414,16 @@ pub fn write_interpolated_string_segment(segment: &StringSegment) -> String {
 
     result.reserve(value.len());
 
-    for character in value.chars() {
+    let mut characters = value.chars().peekable();
+
+    while let Some(character) = characters.next() {
         match character {
             '`' | '{' => {
                 result.push('\\');
                 result.push(character);
             }
             _ if needs_escaping(character) => {
-                result.push_str(&escape(character));
+                push_escape(&mut result, escape(character), characters.peek());
             }
             _ => {
                 result.push(character);
@@ -343,6 +434,60 @@ pub fn write_interpolated_string_segment(segment: &StringSegment) -> String {
     result
 }
 
+/// Writes a string that only contains ASCII characters: characters outside of ASCII are
+/// written as decimal escapes of their UTF-8 bytes.
+pub fn write_ascii_string(value: &str) -> String {
+    if value.is_ascii() {
+        write_string(value)
+    } else {
+        write_quoted_with(value, |character| {
+            if character.is_ascii() {
+                escape(character)
+            } else {
+                escape_bytes(character)
+            }
+        })
+    }
+}
+
+/// Rewrites the original code of a string so that it only contains ASCII characters,
+/// or returns `None` if it already does. Long strings do not have escape sequences, so
+/// they are converted to quoted strings.
+pub fn escape_unicode_string(string_code: &str, value: &str) -> Option<String> {
+    if string_code.is_ascii() {
+        None
+    } else if string_code.starts_with('[') {
+        Some(write_ascii_string(value))
+    } else {
+        Some(escape_unicode_bytes(string_code))
+    }
+}
+
+/// Replaces the characters outside of ASCII with decimal escapes of their UTF-8 bytes.
+/// Since those escapes always have three digits, they can be placed anywhere in the code
+/// of a quoted string.
+pub fn escape_unicode_bytes(string_code: &str) -> String {
+    let mut escaped = String::with_capacity(string_code.len());
+
+    for character in string_code.chars() {
+        if character.is_ascii() {
+            escaped.push(character);
+        } else {
+            escaped.push_str(&escape_bytes(character));
+        }
+    }
+
+    escaped
+}
+
+fn escape_bytes(character: char) -> String {
+    character
+        .encode_utf8(&mut [0; 4])
+        .bytes()
+        .map(|byte| format!("\\{:03}", byte))
+        .collect()
+}
+
 fn write_long_bracket(value: &str) -> String {
     let mut i: usize = value.ends_with(']').into();
     let mut equals = "=".repeat(i);
@@ -358,19 +503,26 @@ fn write_long_bracket(value: &str) -> String {
     format!("[{}[{}{}]{}]", equals, needs_extra_new_line, value, equals)
 }
 
+#[inline]
 fn write_quoted(value: &str) -> String {
+    write_quoted_with(value, escape)
+}
+
+fn write_quoted_with(value: &str, escape: impl Fn(char) -> String) -> String {
     let mut quoted = String::new();
     quoted.reserve(value.len() + 2);
 
     let quote_symbol = get_quote_symbol(value);
     quoted.push(quote_symbol);
 
-    for character in value.chars() {
+    let mut characters = value.chars().peekable();
+
+    while let Some(character) = characters.next() {
         if character == quote_symbol {
             quoted.push('\\');
             quoted.push(quote_symbol);
         } else if needs_escaping(character) {
-            quoted.push_str(&escape(character));
+            push_escape(&mut quoted, escape(character), characters.peek());
         } else {
             quoted.push(character);
         }
@@ -381,6 +533,21 @@ fn write_quoted(value: &str) -> String {
     quoted
 }
 
+/// Pushes an escape sequence, with a decimal escape padded to three digits when it is
+/// followed by a digit, since the digit would otherwise be read as part of the escape.
+fn push_escape(output: &mut String, escape: String, next_character: Option<&char>) {
+    let is_short_decimal_escape = escape.len() < 4
+        && escape[1..]
+            .chars()
+            .all(|character| character.is_ascii_digit());
+
+    if is_short_decimal_escape && next_character.map_or(false, char::is_ascii_digit) {
+        output.push_str(&format!("\\{:0>3}", &escape[1..]));
+    } else {
+        output.push_str(&escape);
+    }
+}
+
 fn get_quote_symbol(value: &str) -> char {
     if value.contains('"') {
         '\''
@@ -428,6 +595,9 @@ mod test {
             single_quote("'") => "\"'\"",
             double_quote("\"") => "'\"'",
             null("\0") => "'\\0'",
+            null_followed_by_digit("\01") => "'\\0001'",
+            escape_followed_by_digit("\u{1B}9") => "'\\0279'",
+            escape_followed_by_letter("\u{1B}a") => "'\\27a'",
             escape("\u{1B}") => "'\\27'",
             extended_ascii("\u{C3}") => "'\\u{c3}'",
             unicode("\u{25C1}") => "'\\u{25c1}'",
@@ -446,4 +616,91 @@ mod test {
                 => "'\\nooof\\nooof\\nooof\\nooof\\nooof\\nooof\\nooof\\nooof\\noof\\u{10ffff}'",
         );
     }
+
+    mod write_ascii_string {
+        use super::*;
+
+        macro_rules! test_output {
+            ($($name:ident($input:expr) => $value:literal),* $(,)?) => {
+                $(
+                    #[test]
+                    fn $name() {
+                        assert_eq!($value, write_ascii_string(&$input));
+                    }
+                )*
+            };
+        }
+
+        test_output!(
+            ascii("abc") => "'abc'",
+            accent("é") => "'\\195\\169'",
+            emoji_between_digits("1😀2") => "'1\\240\\159\\152\\1282'",
+            long_text_with_accent("café ]] ".repeat(8)) => "'caf\\195\\169 ]] caf\\195\\169 ]] caf\\195\\169 ]] caf\\195\\169 ]] caf\\195\\169 ]] caf\\195\\169 ]] caf\\195\\169 ]] caf\\195\\169 ]] '",
+        );
+    }
+
+    mod escape_unicode_string {
+        use super::*;
+
+        macro_rules! test_output {
+            ($($name:ident($code:literal, $value:literal) => $expected:expr),* $(,)?) => {
+                $(
+                    #[test]
+                    fn $name() {
+                        assert_eq!(
+                            $expected.map(str::to_owned),
+                            escape_unicode_string($code, $value)
+                        );
+                    }
+                )*
+            };
+        }
+
+        test_output!(
+            ascii_string("'abc\\255'", "abc\u{FF}") => None,
+            quoted_emoji("'a😀'", "a😀") => Some("'a\\240\\159\\152\\128'"),
+            emoji_before_digit("\"😀1\"", "😀1") => Some("\"\\240\\159\\152\\1281\""),
+            emoji_after_escaped_backslash("'\\\\😀'", "\\😀") => Some("'\\\\\\240\\159\\152\\128'"),
+            long_string("[[é]]", "é") => Some("'\\195\\169'"),
+        );
+    }
+
+    mod tokens_need_separator {
+        use super::*;
+
+        macro_rules! test_separator {
+            ($($name:ident($previous:literal, $next:literal) => $value:literal),* $(,)?) => {
+                $(
+                    #[test]
+                    fn $name() {
+                        assert_eq!($value, tokens_need_separator($previous, $next));
+                    }
+                )*
+            };
+        }
+
+        test_separator!(
+            identifier_and_keyword("a", "and") => true,
+            number_and_identifier("1", "a") => true,
+            number_and_concat("1", "..") => true,
+            hex_number_and_concat("0xf", "..") => true,
+            identifier_ending_with_digit_and_concat("a1", "..") => false,
+            minus_and_negative_number("-", "-1") => true,
+            minus_and_minus("-", "-") => true,
+            concat_and_variable_arguments("..", "...") => true,
+            concat_and_number("..", "1") => true,
+            open_bracket_and_long_string("[", "[[a]]") => true,
+            open_bracket_and_long_string_with_equals("[", "[=[a]=]") => true,
+            closing_bracket_and_closing_bracket("]", "]") => true,
+            generic_closing_and_equal(">", "=") => true,
+            lower_than_and_equal("<", "=") => true,
+            equal_and_equal("=", "=") => true,
+            colon_and_colon(":", ":") => true,
+            slash_and_slash("/", "/") => true,
+            equal_and_minus("=", "-") => false,
+            minus_and_number("-", "1") => false,
+            parenthese_and_identifier(")", "a") => false,
+            empty_token("", "a") => false,
+        );
+    }
 }
@@ -7,10 +7,17 @@ pub mod nodes;
 mod parser;
 pub mod process;
 pub mod rules;
+pub mod test_utils;
 mod utils;
 
+#[cfg(feature = "fs")]
+pub use frontend::FileSystemBackend;
 pub use frontend::{
-    convert_data, process, BundleConfiguration, Configuration, DarkluaError, GeneratorParameters,
-    Options, Resources, WorkerTree,
+    convert_data, process, process_code, process_code_at, AppliedRule, BundleConfiguration,
+    BundleManifest, BundledModule, BundledModuleStatus, Configuration, ConfigurationLayer,
+    ConfigurationOverride, Darklua, DarkluaError, Diagnostic, DiagnosticKind, DiagnosticSpan,
+    ErrorKind, ErrorMode, FileBudget, FileReport, FileStatus, GeneratorParameters, LuaTarget,
+    MemoryBackend, ModuleEdge, ModuleGraph, ModuleNode, Options, ProcessReport, RequireLocation,
+    ResourceBackend, ResourceError, Resources, RuleDuration, RuleNoteValue, RuleTiming, WorkerTree,
 };
-pub use parser::{Parser, ParserError};
+pub use parser::{render_code_frame, Parser, ParserError, SyntaxError};
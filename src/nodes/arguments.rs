@@ -75,7 +75,25 @@ impl TupleArguments {
         self.values.iter_mut()
     }
 
+    #[inline]
+    pub fn last_value(&self) -> Option<&Expression> {
+        self.values.last()
+    }
+
+    /// Removes the last argument and the comma placed before it.
+    pub fn pop_value(&mut self) -> Option<Expression> {
+        let value = self.values.pop();
+        if let Some(tokens) = &mut self.tokens {
+            tokens.commas.truncate(self.values.len().saturating_sub(1));
+        }
+        value
+    }
+
     super::impl_token_fns!(iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
 
 impl From<Arguments> for TupleArguments {
@@ -88,6 +106,12 @@ impl From<Arguments> for TupleArguments {
     }
 }
 
+impl From<Vec<Expression>> for TupleArguments {
+    fn from(values: Vec<Expression>) -> Self {
+        Self::new(values)
+    }
+}
+
 impl iter::FromIterator<Expression> for TupleArguments {
     fn from_iter<T: IntoIterator<Item = Expression>>(iter: T) -> Self {
         Self {
@@ -97,6 +121,42 @@ impl iter::FromIterator<Expression> for TupleArguments {
     }
 }
 
+/// A borrowed argument of a call, from any form of [`Arguments`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArgumentValue<'a> {
+    Expression(&'a Expression),
+    String(&'a StringExpression),
+    Table(&'a TableExpression),
+}
+
+impl<'a> ArgumentValue<'a> {
+    /// Returns the string passed as this argument, either with the string call syntax
+    /// (`f"value"`) or as a string expression (`f("value")`).
+    pub fn as_string(&self) -> Option<&'a StringExpression> {
+        match *self {
+            Self::Expression(Expression::String(string)) | Self::String(string) => Some(string),
+            Self::Expression(_) | Self::Table(_) => None,
+        }
+    }
+
+    /// Returns the table passed as this argument, either with the table call syntax
+    /// (`f{}`) or as a table expression (`f({})`).
+    pub fn as_table(&self) -> Option<&'a TableExpression> {
+        match *self {
+            Self::Expression(Expression::Table(table)) | Self::Table(table) => Some(table),
+            Self::Expression(_) | Self::String(_) => None,
+        }
+    }
+
+    /// Returns the expression of an argument written inside parentheses.
+    pub fn as_expression(&self) -> Option<&'a Expression> {
+        match *self {
+            Self::Expression(expression) => Some(expression),
+            Self::String(_) | Self::Table(_) => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Arguments {
     Tuple(TupleArguments),
@@ -117,6 +177,36 @@ impl Arguments {
         TupleArguments::from(self).with_argument(argument).into()
     }
 
+    /// Returns the number of arguments, which is always one for the string and table call
+    /// syntax.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Tuple(tuple) => tuple.len(),
+            Self::String(_) | Self::Table(_) => 1,
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over the arguments the same way for the three forms of arguments:
+    /// `f(a, b)`, `f"value"` and `f{}`.
+    pub fn iter_values(&self) -> impl Iterator<Item = ArgumentValue<'_>> {
+        let (tuple_values, single_value) = match self {
+            Self::Tuple(tuple) => (Some(tuple.iter_values()), None),
+            Self::String(string) => (None, Some(ArgumentValue::String(string))),
+            Self::Table(table) => (None, Some(ArgumentValue::Table(table))),
+        };
+
+        tuple_values
+            .into_iter()
+            .flatten()
+            .map(ArgumentValue::Expression)
+            .chain(single_value)
+    }
+
     pub fn clear_comments(&mut self) {
         match self {
             Arguments::Tuple(tuple) => tuple.clear_comments(),
@@ -138,9 +228,9 @@ impl Arguments {
         }
     }
 
-    pub(crate) fn shift_token_line(&mut self, amount: usize) {
+    pub(crate) fn for_each_token(&mut self, callback: &mut dyn FnMut(&mut crate::nodes::Token)) {
         match self {
-            Arguments::Tuple(tuple) => tuple.shift_token_line(amount),
+            Arguments::Tuple(tuple) => tuple.for_each_token(callback),
             Arguments::String(_) | Arguments::Table(_) => {}
         }
     }
@@ -165,6 +255,12 @@ impl From<TupleArguments> for Arguments {
     }
 }
 
+impl From<Vec<Expression>> for Arguments {
+    fn from(values: Vec<Expression>) -> Self {
+        Self::Tuple(TupleArguments::new(values))
+    }
+}
+
 impl From<TableExpression> for Arguments {
     fn from(table: TableExpression) -> Self {
         Self::Table(table)
@@ -176,3 +272,130 @@ impl From<StringExpression> for Arguments {
         Self::String(string)
     }
 }
+
+impl iter::FromIterator<Expression> for Arguments {
+    fn from_iter<T: IntoIterator<Item = Expression>>(iter: T) -> Self {
+        Self::Tuple(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::{
+        nodes::{Block, FunctionCall, Statement, TableEntry},
+        Parser,
+    };
+
+    fn parse_call(code: &str) -> FunctionCall {
+        let block: Block = Parser::default()
+            .parse(code)
+            .unwrap_or_else(|err| panic!("unable to parse `{}`: {:?}", code, err));
+
+        match block.iter_statements().next() {
+            Some(Statement::Call(call)) => call.clone(),
+            _ => panic!("expected a call statement in `{}`", code),
+        }
+    }
+
+    #[test]
+    fn build_require_call() {
+        pretty_assertions::assert_eq!(
+            FunctionCall::from_name("require").with_argument(StringExpression::from_value("./a")),
+            parse_call("require('./a')")
+        );
+    }
+
+    #[test]
+    fn build_call_with_multiple_arguments() {
+        pretty_assertions::assert_eq!(
+            FunctionCall::from_name("setmetatable").with_arguments(vec![
+                Expression::identifier("t"),
+                Expression::identifier("mt"),
+            ]),
+            parse_call("setmetatable(t, mt)")
+        );
+    }
+
+    #[test]
+    fn build_call_with_string_syntax() {
+        pretty_assertions::assert_eq!(
+            FunctionCall::from_name("require").with_arguments(StringExpression::from_value("a")),
+            parse_call("require 'a'")
+        );
+    }
+
+    #[test]
+    fn build_method_call() {
+        pretty_assertions::assert_eq!(
+            FunctionCall::from_method("object", "method").with_argument(true),
+            parse_call("object:method(true)")
+        );
+    }
+
+    #[test]
+    fn build_arguments_from_iterator() {
+        let arguments: Arguments = vec![Expression::from(true), Expression::nil()]
+            .into_iter()
+            .collect();
+
+        pretty_assertions::assert_eq!(
+            FunctionCall::from_name("f").with_arguments(arguments),
+            parse_call("f(true, nil)")
+        );
+    }
+
+    #[test]
+    fn iter_values_of_tuple_arguments() {
+        let call = parse_call("f('a', {}, b)");
+        let values: Vec<_> = call.get_arguments().iter_values().collect();
+
+        assert_eq!(values.len(), 3);
+        assert_eq!(
+            values[0].as_string().map(StringExpression::get_value),
+            Some("a")
+        );
+        assert_eq!(values[1].as_table(), Some(&TableExpression::default()));
+        assert_eq!(
+            values[2].as_expression(),
+            Some(&Expression::identifier("b"))
+        );
+        assert_eq!(call.get_arguments().len(), 3);
+    }
+
+    #[test]
+    fn iter_values_of_string_arguments() {
+        let call = parse_call("f 'a'");
+        let values: Vec<_> = call.get_arguments().iter_values().collect();
+
+        assert_eq!(values.len(), 1);
+        assert_eq!(
+            values[0].as_string().map(StringExpression::get_value),
+            Some("a")
+        );
+        assert_eq!(values[0].as_expression(), None);
+        assert_eq!(call.get_arguments().len(), 1);
+    }
+
+    #[test]
+    fn iter_values_of_table_arguments() {
+        let call = parse_call("f { true }");
+        let values: Vec<_> = call.get_arguments().iter_values().collect();
+
+        assert_eq!(values.len(), 1);
+        assert_eq!(
+            values[0].as_table(),
+            Some(&TableExpression::new(vec![TableEntry::Value(true.into())]))
+        );
+        assert_eq!(values[0].as_string(), None);
+    }
+
+    #[test]
+    fn iter_values_of_empty_arguments() {
+        let call = parse_call("f()");
+
+        assert_eq!(call.get_arguments().iter_values().count(), 0);
+        assert!(call.get_arguments().is_empty());
+    }
+}
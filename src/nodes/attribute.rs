@@ -0,0 +1,59 @@
+use crate::nodes::{Identifier, Token};
+
+/// A Luau attribute placed before a function, like `@native` or `@checked`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attribute {
+    name: Identifier,
+    token: Option<Token>,
+}
+
+impl Attribute {
+    pub fn new(name: impl Into<Identifier>) -> Self {
+        Self {
+            name: name.into(),
+            token: None,
+        }
+    }
+
+    /// Sets the token of the `@` symbol.
+    pub fn with_token(mut self, token: Token) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    #[inline]
+    pub fn set_token(&mut self, token: Token) {
+        self.token = Some(token);
+    }
+
+    #[inline]
+    pub fn get_token(&self) -> Option<&Token> {
+        self.token.as_ref()
+    }
+
+    #[inline]
+    pub fn get_name(&self) -> &Identifier {
+        &self.name
+    }
+
+    #[inline]
+    pub fn mutate_name(&mut self) -> &mut Identifier {
+        &mut self.name
+    }
+
+    super::impl_token_fns!(
+        target = [name]
+        iter = [token]
+    );
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+        self.name.clear_tokens();
+    }
+}
+
+impl<IntoIdentifier: Into<Identifier>> From<IntoIdentifier> for Attribute {
+    fn from(name: IntoIdentifier) -> Self {
+        Self::new(name)
+    }
+}
@@ -1,4 +1,6 @@
-use crate::nodes::{LastStatement, ReturnStatement, Statement, Token};
+use crate::generator::{LuaGenerator, ReadableLuaGenerator};
+use crate::nodes::{LastStatement, ReturnStatement, SourcePosition, Statement, Token};
+use crate::Parser;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct BlockTokens {
@@ -167,6 +169,20 @@ impl Block {
         self.statements.iter_mut()
     }
 
+    /// Returns the position of the statement at the given index. The index equal to the
+    /// number of statements refers to the last statement.
+    pub fn get_statement_position(&self, index: usize) -> Option<SourcePosition> {
+        if index == self.statements.len() {
+            self.last_statement
+                .as_ref()
+                .and_then(LastStatement::start_position)
+        } else {
+            self.statements
+                .get(index)
+                .and_then(Statement::start_position)
+        }
+    }
+
     #[inline]
     pub fn first_statement(&self) -> Option<&Statement> {
         self.statements.first()
@@ -222,7 +238,42 @@ impl Block {
         }
     }
 
+    /// Compares two blocks without looking at their tokens. Whitespaces, comments and
+    /// positions are ignored, but the structure, identifiers, literals and operators of
+    /// both blocks must be the same.
+    ///
+    /// ```
+    /// # use darklua_core::Parser;
+    /// let parser = Parser::default().preserve_tokens();
+    ///
+    /// let block = parser.parse("local a = 1 + 2").unwrap();
+    /// let formatted = parser.parse("local a=1+2 -- sum").unwrap();
+    /// let different = parser.parse("local a = 1 + 3").unwrap();
+    ///
+    /// assert_ne!(block, formatted);
+    /// assert!(block.eq_ignore_tokens(&formatted));
+    /// assert!(!block.eq_ignore_tokens(&different));
+    /// ```
+    pub fn eq_ignore_tokens(&self, other: &Block) -> bool {
+        match (self.without_tokens(), other.without_tokens()) {
+            (Some(block), Some(other_block)) => block == other_block,
+            _ => false,
+        }
+    }
+
+    fn without_tokens(&self) -> Option<Block> {
+        // the readable generator does not use tokens, so parsing its output back
+        // (without preserving tokens) gives a block with the same nodes
+        let mut generator = ReadableLuaGenerator::default();
+        generator.write_block(self);
+        Parser::default().parse(&generator.into_string()).ok()
+    }
+
     super::impl_token_fns!(iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
 
 impl Default for Block {
@@ -270,6 +321,42 @@ mod test {
         statements.into_iter().next().unwrap()
     }
 
+    #[test]
+    fn eq_ignore_tokens_with_different_formatting() {
+        let block = parse_block_with_tokens("local function f(a, b)\n\treturn a + b\nend\nf(1, 2)");
+        let other =
+            parse_block_with_tokens("-- add\nlocal   function f( a,b ) return a+b end f(1,2) ; ");
+
+        assert_ne!(block, other);
+        assert!(block.eq_ignore_tokens(&other));
+    }
+
+    #[test]
+    fn eq_ignore_tokens_with_and_without_tokens() {
+        let code = "if condition then print('ok') end";
+        let block = parse_block_with_tokens(code);
+        let other = Parser::default().parse(code).unwrap();
+
+        assert!(block.eq_ignore_tokens(&other));
+        assert!(other.eq_ignore_tokens(&block));
+    }
+
+    #[test]
+    fn eq_ignore_tokens_with_different_literal() {
+        let block = parse_block_with_tokens("return 'hello', 1");
+        let other = parse_block_with_tokens("return 'hello', 2");
+
+        assert!(!block.eq_ignore_tokens(&other));
+    }
+
+    #[test]
+    fn eq_ignore_tokens_with_different_operator() {
+        let block = parse_block_with_tokens("return a + b");
+        let other = parse_block_with_tokens("return a - b");
+
+        assert!(!block.eq_ignore_tokens(&other));
+    }
+
     #[test]
     fn default_block_is_empty() {
         let block = Block::default();
@@ -512,4 +599,85 @@ mod test {
             })
         );
     }
+
+    const POSITION_FIXTURE: &str = r#"local value = 1
+
+  print(value)
+
+if value then
+    value.field = true
+end
+return value"#;
+
+    #[test]
+    fn get_statement_position_of_statements() {
+        let block = parse_block_with_tokens(POSITION_FIXTURE);
+
+        let expected = [(1, 0, 1), (3, 19, 3), (5, 33, 1)];
+
+        for (index, &(line, offset, column)) in expected.iter().enumerate() {
+            let position = block.get_statement_position(index).unwrap();
+
+            assert_eq!(position.line(), line);
+            assert_eq!(position.offset(), Some(offset));
+            assert_eq!(position.column(POSITION_FIXTURE), Some(column));
+        }
+    }
+
+    #[test]
+    fn get_statement_position_of_last_statement() {
+        let block = parse_block_with_tokens(POSITION_FIXTURE);
+
+        let position = block.get_statement_position(3).unwrap();
+
+        assert_eq!(position.line(), 8);
+        assert_eq!(position.offset(), Some(74));
+        assert_eq!(position.column(POSITION_FIXTURE), Some(1));
+    }
+
+    #[test]
+    fn get_statement_position_of_nested_statement() {
+        let block = parse_block_with_tokens(POSITION_FIXTURE);
+
+        let if_statement = match block.iter_statements().nth(2) {
+            Some(Statement::If(if_statement)) => if_statement,
+            _ => panic!("third statement should be an if statement"),
+        };
+        let branch_block = if_statement.get_branches()[0].get_block();
+
+        let position = branch_block.get_statement_position(0).unwrap();
+
+        assert_eq!(position.line(), 6);
+        assert_eq!(position.column(POSITION_FIXTURE), Some(5));
+    }
+
+    #[test]
+    fn get_statement_position_out_of_bounds() {
+        let block = parse_block_with_tokens(POSITION_FIXTURE);
+
+        assert_eq!(block.get_statement_position(4), None);
+    }
+
+    #[test]
+    fn get_statement_position_without_tokens() {
+        let block = Parser::default().parse(POSITION_FIXTURE).unwrap();
+
+        assert_eq!(block.get_statement_position(0), None);
+    }
+
+    #[test]
+    fn get_statement_position_keeps_line_after_replacing_referenced_tokens() {
+        let mut block = parse_block_with_tokens(POSITION_FIXTURE);
+        match block.first_mut_statement() {
+            Some(Statement::LocalAssign(local_assign)) => {
+                local_assign.replace_referenced_tokens(POSITION_FIXTURE)
+            }
+            _ => panic!("first statement should be a local assignment"),
+        }
+
+        let position = block.get_statement_position(0).unwrap();
+
+        assert_eq!(position.line(), 1);
+        assert_eq!(position.offset(), None);
+    }
 }
@@ -0,0 +1,259 @@
+//! A collection of functions to create nodes without writing out every constructor.
+//!
+//! All the nodes created by these functions do not have any tokens, so they can be
+//! used with any of the generators.
+//!
+//! ```
+//! use darklua_core::nodes::builder::*;
+//! use darklua_core::Parser;
+//!
+//! let block = block([
+//!     local_assign(["x"], [call("require").with_string_argument("y")]),
+//! ]);
+//!
+//! let expected = Parser::default().parse("local x = require('y')").unwrap();
+//!
+//! assert_eq!(block, expected);
+//! ```
+
+use crate::nodes::{
+    AssignStatement, BinaryExpression, BinaryOperator, Block, Expression, FieldExpression,
+    FunctionCall, FunctionExpression, Identifier, IfStatement, IndexExpression,
+    LocalAssignStatement, LocalFunctionStatement, Prefix, ReturnStatement, Statement,
+    StringExpression, TypedIdentifier, Variable,
+};
+
+/// Creates an identifier.
+pub fn identifier(name: impl Into<String>) -> Identifier {
+    Identifier::new(name)
+}
+
+/// Creates a string expression from its value (without quotes).
+pub fn string(value: impl Into<String>) -> StringExpression {
+    StringExpression::from_value(value)
+}
+
+/// Creates a function call without any arguments. Arguments can be added with
+/// [`FunctionCall::with_argument`] or [`FunctionCall::with_string_argument`].
+pub fn call(prefix: impl Into<Prefix>) -> FunctionCall {
+    FunctionCall::from_prefix(prefix)
+}
+
+/// Creates a method call (`prefix:method()`) without any arguments.
+pub fn method_call(prefix: impl Into<Prefix>, method: impl Into<Identifier>) -> FunctionCall {
+    FunctionCall::from_prefix(prefix).with_method(method)
+}
+
+/// Creates a field expression (`prefix.field`).
+pub fn field(prefix: impl Into<Prefix>, field: impl Into<Identifier>) -> FieldExpression {
+    FieldExpression::new(prefix, field)
+}
+
+/// Creates an index expression (`prefix[key]`).
+pub fn index(prefix: impl Into<Prefix>, key: impl Into<Expression>) -> IndexExpression {
+    IndexExpression::new(prefix, key)
+}
+
+/// Creates a binary expression.
+pub fn binary(
+    left: impl Into<Expression>,
+    operator: BinaryOperator,
+    right: impl Into<Expression>,
+) -> BinaryExpression {
+    BinaryExpression::new(operator, left, right)
+}
+
+/// Creates a function expression from its parameters and its block.
+pub fn function<P, B>(parameters: P, block: B) -> FunctionExpression
+where
+    P: IntoIterator,
+    P::Item: Into<TypedIdentifier>,
+    B: Into<Block>,
+{
+    FunctionExpression::from_block(block)
+        .with_parameters(parameters.into_iter().map(Into::into).collect())
+}
+
+/// Creates a block from a list of statements.
+pub fn block<S>(statements: S) -> Block
+where
+    S: IntoIterator,
+    S::Item: Into<Statement>,
+{
+    Block::new(statements.into_iter().map(Into::into).collect(), None)
+}
+
+/// Creates a local assignment (`local a, b = c, d`).
+pub fn local_assign<V, E>(variables: V, values: E) -> LocalAssignStatement
+where
+    V: IntoIterator,
+    V::Item: Into<TypedIdentifier>,
+    E: IntoIterator,
+    E::Item: Into<Expression>,
+{
+    LocalAssignStatement::new(
+        variables.into_iter().map(Into::into).collect(),
+        values.into_iter().map(Into::into).collect(),
+    )
+}
+
+/// Creates an assignment (`a, b = c, d`).
+pub fn assign<V, E>(variables: V, values: E) -> AssignStatement
+where
+    V: IntoIterator,
+    V::Item: Into<Variable>,
+    E: IntoIterator,
+    E::Item: Into<Expression>,
+{
+    AssignStatement::new(
+        variables.into_iter().map(Into::into).collect(),
+        values.into_iter().map(Into::into).collect(),
+    )
+}
+
+/// Creates a local function statement from its name, its parameters and its block.
+pub fn local_function<P, B>(
+    name: impl Into<Identifier>,
+    parameters: P,
+    block: B,
+) -> LocalFunctionStatement
+where
+    P: IntoIterator,
+    P::Item: Into<TypedIdentifier>,
+    B: Into<Block>,
+{
+    LocalFunctionStatement::new(
+        name,
+        block.into(),
+        parameters.into_iter().map(Into::into).collect(),
+        false,
+    )
+}
+
+/// Creates a return statement.
+pub fn return_statement<E>(values: E) -> ReturnStatement
+where
+    E: IntoIterator,
+    E::Item: Into<Expression>,
+{
+    ReturnStatement::new(values.into_iter().map(Into::into).collect())
+}
+
+/// Starts building an if statement. Call [`IfStatementBuilder::then_block`] to get
+/// the statement.
+pub fn if_stmt(condition: impl Into<Expression>) -> IfStatementBuilder {
+    IfStatementBuilder {
+        condition: condition.into(),
+    }
+}
+
+/// The value returned by [`if_stmt`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IfStatementBuilder {
+    condition: Expression,
+}
+
+impl IfStatementBuilder {
+    /// Creates the if statement with the given block. Other branches can be added
+    /// using [`IfStatement::with_new_branch`] and [`IfStatement::with_else_block`].
+    pub fn then_block(self, block: impl Into<Block>) -> IfStatement {
+        IfStatement::create(self.condition, block)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::nodes::TableExpression;
+    use crate::Parser;
+
+    fn parse(code: &str) -> Block {
+        Parser::default()
+            .parse(code)
+            .unwrap_or_else(|error| panic!("could not parse `{}`: {:?}", code, error))
+    }
+
+    #[test]
+    fn local_assign_require() {
+        assert_eq!(
+            block([local_assign(
+                ["x"],
+                [call("require").with_string_argument("y")]
+            )]),
+            parse("local x = require('y')"),
+        );
+    }
+
+    #[test]
+    fn assign_field() {
+        assert_eq!(
+            block([assign([field(identifier("a"), "b")], [true])]),
+            parse("a.b = true"),
+        );
+    }
+
+    #[test]
+    fn method_call_statement() {
+        assert_eq!(
+            block([method_call(identifier("object"), "run").with_argument(false)]),
+            parse("object:run(false)"),
+        );
+    }
+
+    #[test]
+    fn if_statement_with_else() {
+        assert_eq!(
+            block([if_stmt(identifier("condition"))
+                .then_block(call("print").with_string_argument("a"))
+                .with_else_block(call("print"))]),
+            parse("if condition then print('a') else print() end"),
+        );
+    }
+
+    #[test]
+    fn small_module() {
+        let module = block([
+            Statement::from(local_assign(["module"], [TableExpression::default()])),
+            local_function(
+                "helper",
+                ["value"],
+                return_statement([binary(
+                    identifier("value"),
+                    BinaryOperator::Concat,
+                    string("!"),
+                )]),
+            )
+            .into(),
+            assign(
+                [field(identifier("module"), "run")],
+                [function(
+                    ["name"],
+                    block([call("print").with_argument(
+                        call("helper").with_argument(index(identifier("name"), string("key"))),
+                    )]),
+                )],
+            )
+            .into(),
+        ])
+        .with_last_statement(return_statement([identifier("module")]));
+
+        assert_eq!(
+            module,
+            parse(
+                r#"
+local module = {}
+
+local function helper(value)
+    return value .. "!"
+end
+
+module.run = function(name)
+    print(helper(name["key"]))
+end
+
+return module
+"#
+            ),
+        );
+    }
+}
@@ -0,0 +1,443 @@
+use std::borrow::Cow;
+
+use crate::nodes::*;
+use crate::process::{
+    processors::{shift_token_line_processor, ClearTokensProcessor, ForEachTokenProcessor},
+    DefaultVisitor, NodeVisitor,
+};
+
+macro_rules! impl_deep_token_fns {
+    ( $( $node:ty => $visit:ident ),* $(,)? ) => {
+        $(
+            impl $node {
+                /// Removes the tokens of this node and of all its children, so that the
+                /// node is identical to one created without parsing any code.
+                pub fn clear_all_tokens(&mut self) {
+                    let mut processor = ClearTokensProcessor::default();
+                    DefaultVisitor::$visit(self, &mut processor);
+                }
+
+                /// Adds the given number of lines to the line numbers of the tokens of this
+                /// node and of all its children.
+                pub fn shift_token_lines(&mut self, lines: usize) {
+                    if lines != 0 {
+                        let mut processor = shift_token_line_processor(lines);
+                        DefaultVisitor::$visit(self, &mut processor);
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_deep_token_fns!(
+    Block => visit_block,
+    Statement => visit_statement,
+    LastStatement => visit_last_statement,
+    AssignStatement => visit_assign_statement,
+    CompoundAssignStatement => visit_compound_assign,
+    DoStatement => visit_do_statement,
+    FunctionCall => visit_function_call,
+    FunctionStatement => visit_function_statement,
+    GenericForStatement => visit_generic_for,
+    GotoStatement => visit_goto_statement,
+    IfStatement => visit_if_statement,
+    LabelStatement => visit_label_statement,
+    LocalAssignStatement => visit_local_assign,
+    LocalFunctionStatement => visit_local_function,
+    TypeFunctionStatement => visit_type_function,
+    ExportTypeFunctionStatement => visit_export_type_function,
+    NumericForStatement => visit_numeric_for,
+    RepeatStatement => visit_repeat_statement,
+    WhileStatement => visit_while_statement,
+    TypeDeclarationStatement => visit_type_declaration,
+    Variable => visit_variable,
+    Prefix => visit_prefix_expression,
+    Expression => visit_expression,
+    BinaryExpression => visit_binary_expression,
+    FieldExpression => visit_field_expression,
+    FunctionExpression => visit_function_expression,
+    Identifier => visit_identifier,
+    IfExpression => visit_if_expression,
+    IndexExpression => visit_index_expression,
+    NumberExpression => visit_number_expression,
+    ParentheseExpression => visit_parenthese_expression,
+    StringExpression => visit_string_expression,
+    InterpolatedStringExpression => visit_interpolated_string_expression,
+    TableExpression => visit_table,
+    UnaryExpression => visit_unary_expression,
+    TypeCastExpression => visit_type_cast_expression,
+    Type => visit_type,
+    TypeName => visit_type_name,
+    TypeField => visit_type_field,
+    StringType => visit_string_type,
+    ArrayType => visit_array_type,
+    TableType => visit_table_type,
+    ExpressionType => visit_expression_type,
+    ParentheseType => visit_parenthese_type,
+    FunctionType => visit_function_type,
+    OptionalType => visit_optional_type,
+    IntersectionType => visit_intersection_type,
+    UnionType => visit_union_type,
+    TypePack => visit_type_pack,
+    GenericTypePack => visit_generic_type_pack,
+    VariadicTypePack => visit_variadic_type_pack,
+    FunctionReturnType => visit_function_return_type,
+    FunctionVariadicType => visit_function_variadic_type,
+    VariadicArgumentType => visit_variadic_argument_type,
+);
+
+impl Arguments {
+    /// Removes the tokens of the arguments and of all their children.
+    pub fn clear_all_tokens(&mut self) {
+        if let Arguments::Tuple(tuple) = self {
+            tuple.clear_tokens();
+        }
+        let mut processor = ClearTokensProcessor::default();
+        DefaultVisitor::visit_arguments(self, &mut processor);
+    }
+
+    /// Adds the given number of lines to the line numbers of the tokens of the arguments
+    /// and of all their children.
+    pub fn shift_token_lines(&mut self, lines: usize) {
+        if lines != 0 {
+            self.for_each_token(&mut |token| token.shift_token_line(lines));
+            let mut processor = shift_token_line_processor(lines);
+            DefaultVisitor::visit_arguments(self, &mut processor);
+        }
+    }
+}
+
+/// Calls `edit` with the first token (the token with the smallest offset) among the
+/// tokens given by `for_each_token`. Returns `false` when there are no tokens with a
+/// position.
+fn edit_first_deep_token(
+    mut for_each_token: impl FnMut(&mut dyn FnMut(&mut Token)),
+    edit: impl FnOnce(&mut Token),
+) -> bool {
+    let mut first_offset = None;
+
+    for_each_token(&mut |token: &mut Token| {
+        if let Some(offset) = token
+            .start_position()
+            .and_then(|position| position.offset())
+        {
+            first_offset = Some(first_offset.map_or(offset, |first: usize| first.min(offset)));
+        }
+    });
+
+    let first_offset = match first_offset {
+        Some(offset) => offset,
+        None => return false,
+    };
+
+    let mut edit = Some(edit);
+
+    for_each_token(&mut |token: &mut Token| {
+        if token
+            .start_position()
+            .and_then(|position| position.offset())
+            == Some(first_offset)
+        {
+            if let Some(edit) = edit.take() {
+                edit(token);
+            }
+        }
+    });
+
+    true
+}
+
+fn function_body_tokens() -> FunctionBodyTokens {
+    FunctionBodyTokens {
+        function: Token::from_content("function"),
+        opening_parenthese: Token::from_content("("),
+        closing_parenthese: Token::from_content(")"),
+        end: Token::from_content("end"),
+        parameter_commas: Vec::new(),
+        variable_arguments: None,
+        variable_arguments_colon: None,
+        return_type_colon: None,
+    }
+}
+
+impl Block {
+    /// Calls the function with every token of the block and of all its nodes.
+    pub(crate) fn for_each_deep_token(&mut self, callback: impl FnMut(&mut Token)) {
+        let mut processor = ForEachTokenProcessor::new(callback);
+        DefaultVisitor::visit_block(self, &mut processor);
+    }
+
+    /// Removes the trivia written before the first token of the block. At the top of a
+    /// file, it contains the shebang or the directive comments (like `--!strict`), which
+    /// rules inserting statements at the top of the file move with
+    /// [`Statement::prepend_leading_header`].
+    pub(crate) fn take_leading_header(&mut self) -> Vec<Trivia> {
+        let mut header = Vec::new();
+
+        edit_first_deep_token(
+            |callback| self.for_each_deep_token(callback),
+            |token| header = token.take_leading_trivia(),
+        );
+
+        header
+    }
+}
+
+impl Statement {
+    /// Calls the function with every token of the statement and of all its nodes.
+    pub(crate) fn for_each_deep_token(&mut self, callback: impl FnMut(&mut Token)) {
+        let mut processor = ForEachTokenProcessor::new(callback);
+        DefaultVisitor::visit_statement(self, &mut processor);
+    }
+
+    /// Calls the function with the first token of the statement, which owns the comments
+    /// written on the lines before the statement. Returns `false` (without calling the
+    /// function) when the statement does not have tokens from the parsed code.
+    pub fn edit_first_token(&mut self, edit: impl FnOnce(&mut Token)) -> bool {
+        let first_offset = match self.start_position().and_then(|position| position.offset()) {
+            Some(offset) => offset,
+            None => return false,
+        };
+
+        let mut edit = Some(edit);
+
+        self.for_each_deep_token(|token| {
+            if edit.is_some()
+                && token
+                    .start_position()
+                    .and_then(|position| position.offset())
+                    == Some(first_offset)
+            {
+                if let Some(edit) = edit.take() {
+                    edit(token);
+                }
+            }
+        });
+
+        edit.is_none()
+    }
+
+    /// Removes the trivia written before the first token of the statement. See
+    /// [`Block::take_leading_header`].
+    pub(crate) fn take_leading_header(&mut self) -> Vec<Trivia> {
+        let mut header = Vec::new();
+        self.edit_first_token(|token| header = token.take_leading_trivia());
+        header
+    }
+
+    /// Writes the trivia removed by [`Block::take_leading_header`] before the first token
+    /// of the statement. The tokens needed to write the header are created when a local
+    /// assignment or a local function does not have tokens. Returns `false` when the
+    /// statement has no token that can hold the header.
+    pub(crate) fn prepend_leading_header(&mut self, header: Vec<Trivia>) -> bool {
+        if header.is_empty() {
+            return true;
+        }
+
+        match self {
+            Self::LocalAssign(assign) if assign.get_tokens().is_none() => {
+                let mut local = Token::from_content("local");
+                local.prepend_leading_trivia(header);
+
+                assign.set_tokens(LocalAssignTokens {
+                    local,
+                    equal: None,
+                    variable_commas: Vec::new(),
+                    value_commas: Vec::new(),
+                });
+                true
+            }
+            Self::LocalFunction(function) if function.get_tokens().is_none() => {
+                let mut local = Token::from_content("local");
+                local.prepend_leading_trivia(header);
+
+                function.set_tokens(LocalFunctionTokens {
+                    local,
+                    function_body: function_body_tokens(),
+                });
+                true
+            }
+            _ => edit_first_deep_token(
+                |callback| self.for_each_deep_token(callback),
+                |token| token.prepend_leading_trivia(header),
+            ),
+        }
+    }
+
+    /// Adds a comment on the line before the statement, using the same rules as
+    /// [`Token::push_leading_comment`]. Returns `false` when the statement does not have
+    /// tokens from the parsed code.
+    pub fn push_leading_comment<IntoCowStr: Into<Cow<'static, str>>>(
+        &mut self,
+        content: IntoCowStr,
+    ) -> bool {
+        let content = content.into();
+        self.edit_first_token(|token| token.push_leading_comment(content))
+    }
+}
+
+impl TypedIdentifier {
+    /// Removes the tokens of the identifier and of its type.
+    pub fn clear_all_tokens(&mut self) {
+        self.clear_tokens();
+        if let Some(r#type) = self.mutate_type() {
+            r#type.clear_all_tokens();
+        }
+    }
+
+    /// Adds the given number of lines to the line numbers of the tokens of the identifier
+    /// and of its type.
+    pub fn shift_token_lines(&mut self, lines: usize) {
+        self.for_each_token(&mut |token| token.shift_token_line(lines));
+        if let Some(r#type) = self.mutate_type() {
+            r#type.shift_token_lines(lines);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generator::{LuaGenerator, TokenBasedLuaGenerator};
+    use crate::nodes::builder;
+    use crate::rules::RemoveWhitespacesProcessor;
+    use crate::Parser;
+
+    const CODE: &str = r#"
+type Map<K, V = string, T... = ...any> = { [K]: V, name: string, ["literal"]: (T...) -> () }
+export type Callback = (value: number, ...string) -> (boolean?, typeof(x))
+
+@native
+local function process<T>(value: T, ...: number): number
+    local a: number, b = 1 + 2 * 3, -value
+    a += #"abc" .. `count: {a}, next: {b}!`
+    local t = { 1, key = true, [a] = nil, ... }
+    for i = 1, 10, 2 do
+        if i == 2 then break elseif i > 8 then continue else end
+    end
+    for key, item: any in pairs(t) do print(key, item) end
+    while false do end
+    repeat local x = (a :: number) until x
+    do end
+    function t.field.name:method(...) return if a then b else nil end
+    t:method'string'
+    t.value[a] = function() return 0x1F, 0b101, 1e3 end
+    return a
+end
+
+type function Pair(a, b) return types.newtable() end
+export type function Same(t) return t end
+"#;
+
+    fn parse_with_tokens(code: &str) -> Block {
+        Parser::default()
+            .preserve_tokens()
+            .parse(code)
+            .unwrap_or_else(|error| panic!("could not parse `{}`: {}", code, error))
+    }
+
+    fn parse(code: &str) -> Block {
+        Parser::default()
+            .parse(code)
+            .unwrap_or_else(|error| panic!("could not parse `{}`: {}", code, error))
+    }
+
+    fn generate(block: &Block, code: &str) -> String {
+        let mut generator = TokenBasedLuaGenerator::new(code);
+        generator.write_block(block);
+        generator.into_string()
+    }
+
+    #[test]
+    fn clear_all_tokens_of_block() {
+        let mut block = parse_with_tokens(CODE);
+
+        block.clear_all_tokens();
+
+        pretty_assertions::assert_eq!(block, parse(CODE));
+    }
+
+    #[test]
+    fn clear_all_tokens_matches_built_block() {
+        let mut block = parse_with_tokens("local x = require('y')\nreturn x");
+
+        block.clear_all_tokens();
+
+        pretty_assertions::assert_eq!(
+            block,
+            builder::block([builder::local_assign(
+                ["x"],
+                [builder::call("require").with_string_argument("y")]
+            )])
+            .with_last_statement(builder::return_statement([builder::identifier("x")]))
+        );
+    }
+
+    #[test]
+    fn clear_all_tokens_of_expression() {
+        let code = "return { a = function(b: number) return b end }";
+        let mut block = parse_with_tokens(code);
+
+        block
+            .mutate_last_statement()
+            .and_then(|statement| match statement {
+                LastStatement::Return(statement) => statement.iter_mut_expressions().next(),
+                _ => None,
+            })
+            .expect("return statement should have a value")
+            .clear_all_tokens();
+
+        // only the block and the return statement still have their tokens
+        block.clear_tokens();
+        if let Some(LastStatement::Return(statement)) = block.mutate_last_statement() {
+            statement.clear_tokens();
+        }
+
+        pretty_assertions::assert_eq!(block, parse(code));
+    }
+
+    #[test]
+    fn shift_token_lines_of_block() {
+        let code = "local a = 1\nreturn a";
+        let mut block = parse_with_tokens(code);
+
+        block.shift_token_lines(2);
+
+        pretty_assertions::assert_eq!(generate(&block, code), "\n\nlocal a = 1\nreturn a");
+    }
+
+    #[test]
+    fn shift_token_lines_of_statement() {
+        let code = "local a = 1\nlocal b = 2\nreturn a";
+        let mut block = parse_with_tokens(code);
+
+        block
+            .iter_mut_statements()
+            .nth(1)
+            .expect("block should have two statements")
+            .shift_token_lines(1);
+
+        pretty_assertions::assert_eq!(
+            generate(&block, code),
+            "local a = 1\n\nlocal b = 2\nreturn a"
+        );
+    }
+
+    #[test]
+    fn shift_token_lines_of_every_node() {
+        // without whitespaces, the generator can only place tokens on the correct line
+        // by using their line number
+        let parse_without_spaces = || {
+            let mut block = parse_with_tokens(CODE);
+            DefaultVisitor::visit_block(&mut block, &mut RemoveWhitespacesProcessor::default());
+            block
+        };
+        let expected = generate(&parse_without_spaces(), CODE);
+        let mut block = parse_without_spaces();
+
+        block.shift_token_lines(3);
+
+        pretty_assertions::assert_eq!(generate(&block, CODE), format!("\n\n\n{}", expected));
+    }
+}
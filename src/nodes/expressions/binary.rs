@@ -277,6 +277,10 @@ impl BinaryExpression {
     }
 
     super::impl_token_fns!(iter = [token]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+    }
 }
 
 #[cfg(test)]
@@ -52,4 +52,9 @@ impl FieldExpression {
         target = [field]
         iter = [token]
     );
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+        self.field.clear_tokens();
+    }
 }
@@ -1,6 +1,6 @@
 use crate::nodes::{
-    Block, FunctionBodyTokens, FunctionReturnType, FunctionVariadicType, GenericParameters,
-    TypedIdentifier,
+    Attribute, Block, FunctionBodyTokens, FunctionReturnType, FunctionVariadicType,
+    GenericParameters, TypedIdentifier,
 };
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -11,6 +11,7 @@ pub struct FunctionExpression {
     variadic_type: Option<FunctionVariadicType>,
     return_type: Option<FunctionReturnType>,
     generic_parameters: Option<GenericParameters>,
+    attributes: Vec<Attribute>,
     tokens: Option<Box<FunctionBodyTokens>>,
 }
 
@@ -23,6 +24,7 @@ impl FunctionExpression {
             variadic_type: None,
             return_type: None,
             generic_parameters: None,
+            attributes: Vec::new(),
             tokens: None,
         }
     }
@@ -35,6 +37,7 @@ impl FunctionExpression {
             variadic_type: None,
             return_type: None,
             generic_parameters: None,
+            attributes: Vec::new(),
             tokens: None,
         }
     }
@@ -127,6 +130,41 @@ impl FunctionExpression {
         self.generic_parameters.as_ref()
     }
 
+    pub fn with_attribute(mut self, attribute: impl Into<Attribute>) -> Self {
+        self.attributes.push(attribute.into());
+        self
+    }
+
+    #[inline]
+    pub fn push_attribute(&mut self, attribute: impl Into<Attribute>) {
+        self.attributes.push(attribute.into());
+    }
+
+    #[inline]
+    pub fn iter_attributes(&self) -> impl Iterator<Item = &Attribute> {
+        self.attributes.iter()
+    }
+
+    #[inline]
+    pub fn iter_mut_attributes(&mut self) -> impl Iterator<Item = &mut Attribute> {
+        self.attributes.iter_mut()
+    }
+
+    #[inline]
+    pub fn mutate_attributes(&mut self) -> &mut Vec<Attribute> {
+        &mut self.attributes
+    }
+
+    #[inline]
+    pub fn has_attributes(&self) -> bool {
+        !self.attributes.is_empty()
+    }
+
+    #[inline]
+    pub fn clear_attributes(&mut self) {
+        self.attributes.clear();
+    }
+
     #[inline]
     pub fn is_generic(&self) -> bool {
         self.generic_parameters.is_some()
@@ -204,5 +242,18 @@ impl FunctionExpression {
         }
     }
 
-    super::impl_token_fns!(iter = [parameters, generic_parameters, tokens]);
+    super::impl_token_fns!(iter = [parameters, generic_parameters, attributes, tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        for parameter in self.parameters.iter_mut() {
+            parameter.clear_tokens();
+        }
+        if let Some(generic_parameters) = &mut self.generic_parameters {
+            generic_parameters.clear_tokens();
+        }
+        for attribute in self.attributes.iter_mut() {
+            attribute.clear_tokens();
+        }
+    }
 }
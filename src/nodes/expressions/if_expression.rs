@@ -123,6 +123,13 @@ impl IfExpression {
     }
 
     super::impl_token_fns!(iter = [tokens, branches]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        for branch in self.branches.iter_mut() {
+            branch.clear_tokens();
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -176,6 +183,10 @@ impl ElseIfExpressionBranch {
     }
 
     super::impl_token_fns!(iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
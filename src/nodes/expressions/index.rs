@@ -62,4 +62,8 @@ impl IndexExpression {
     }
 
     super::impl_token_fns!(iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
@@ -55,6 +55,10 @@ impl StringSegment {
     }
 
     super::impl_token_fns!(iter = [token]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -93,6 +97,10 @@ impl ValueSegment {
     }
 
     super::impl_token_fns!(iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -133,10 +141,17 @@ impl InterpolationSegment {
         }
     }
 
-    pub(crate) fn shift_token_line(&mut self, amount: usize) {
+    pub(crate) fn for_each_token(&mut self, callback: &mut dyn FnMut(&mut crate::nodes::Token)) {
+        match self {
+            InterpolationSegment::String(segment) => segment.for_each_token(callback),
+            InterpolationSegment::Value(segment) => segment.for_each_token(callback),
+        }
+    }
+
+    pub(crate) fn clear_tokens(&mut self) {
         match self {
-            InterpolationSegment::String(segment) => segment.shift_token_line(amount),
-            InterpolationSegment::Value(segment) => segment.shift_token_line(amount),
+            InterpolationSegment::String(segment) => segment.clear_tokens(),
+            InterpolationSegment::Value(segment) => segment.clear_tokens(),
         }
     }
 
@@ -222,6 +237,13 @@ impl InterpolatedStringExpression {
 
     super::impl_token_fns!(iter = [tokens, segments]);
 
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        for segment in self.segments.iter_mut() {
+            segment.clear_tokens();
+        }
+    }
+
     pub fn iter_segments(&self) -> impl Iterator<Item = &InterpolationSegment> {
         self.segments.iter()
     }
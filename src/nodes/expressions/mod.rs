@@ -28,7 +28,7 @@ pub use table::*;
 pub use type_cast::*;
 pub use unary::*;
 
-use crate::nodes::{FunctionCall, Identifier, Token, Variable};
+use crate::nodes::{FunctionCall, Identifier, SourcePosition, Token, Variable};
 
 use super::impl_token_fns;
 
@@ -74,6 +74,41 @@ impl Expression {
     pub fn in_parentheses(self) -> Self {
         Self::Parenthese(ParentheseExpression::new(self).into())
     }
+
+    /// Returns the position of the first token of the expression. Expressions created
+    /// without tokens (for example, by rules) return `None`.
+    pub fn start_position(&self) -> Option<SourcePosition> {
+        match self {
+            Self::Binary(binary) => binary.left().start_position(),
+            Self::Call(call) => call.get_prefix().start_position(),
+            Self::False(token)
+            | Self::Nil(token)
+            | Self::True(token)
+            | Self::VariableArguments(token) => token.as_ref().and_then(Token::start_position),
+            Self::Field(field) => field.get_prefix().start_position(),
+            Self::Function(function) => function
+                .get_tokens()
+                .and_then(|tokens| tokens.function.start_position()),
+            Self::Identifier(identifier) => identifier.get_token().and_then(Token::start_position),
+            Self::If(if_expression) => if_expression
+                .get_tokens()
+                .and_then(|tokens| tokens.r#if.start_position()),
+            Self::Index(index) => index.get_prefix().start_position(),
+            Self::Number(number) => number.get_token().and_then(Token::start_position),
+            Self::Parenthese(parenthese) => parenthese
+                .get_tokens()
+                .and_then(|tokens| tokens.left_parenthese.start_position()),
+            Self::String(string) => string.get_token().and_then(Token::start_position),
+            Self::InterpolatedString(string) => string
+                .get_tokens()
+                .and_then(|tokens| tokens.opening_tick.start_position()),
+            Self::Table(table) => table
+                .get_tokens()
+                .and_then(|tokens| tokens.opening_brace.start_position()),
+            Self::Unary(unary) => unary.get_token().and_then(Token::start_position),
+            Self::TypeCast(type_cast) => type_cast.get_expression().start_position(),
+        }
+    }
 }
 
 impl From<bool> for Expression {
@@ -111,9 +146,7 @@ impl From<f64> for Expression {
                     let exponent = value.log10().floor();
                     let new_value = value / 10_f64.powf(exponent);
 
-                    DecimalNumber::new(new_value)
-                        .with_exponent(exponent as i64, true)
-                        .into()
+                    decimal_with_exponent(value, new_value, exponent as i64).into()
                 } else if value > 999.0 && (value / 100.0).fract() == 0.0 {
                     let mut exponent = value.log10().floor();
                     let mut power = 10_f64.powf(exponent);
@@ -123,9 +156,7 @@ impl From<f64> for Expression {
                         power /= 10.0;
                     }
 
-                    DecimalNumber::new(value / power)
-                        .with_exponent(exponent as i64, true)
-                        .into()
+                    decimal_with_exponent(value, value / power, exponent as i64).into()
                 } else {
                     DecimalNumber::new(value).into()
                 }
@@ -134,6 +165,27 @@ impl From<f64> for Expression {
     }
 }
 
+/// Creates a number with an exponent that is read back as exactly the given value. When
+/// the mantissa was rounded, the shortest scientific notation of the value is used, and
+/// the value is written without an exponent as a last resort.
+fn decimal_with_exponent(value: f64, mantissa: f64, exponent: i64) -> DecimalNumber {
+    let number = DecimalNumber::new(mantissa).with_exponent(exponent, true);
+    if number.compute_value() == value {
+        return number;
+    }
+
+    let scientific_notation = format!("{:e}", value);
+    scientific_notation
+        .split_once('e')
+        .and_then(|(mantissa, exponent)| {
+            let mantissa = mantissa.parse::<f64>().ok()?;
+            let exponent = exponent.parse::<i64>().ok()?;
+            Some(DecimalNumber::new(mantissa).with_exponent(exponent, true))
+        })
+        .filter(|number| number.compute_value() == value)
+        .unwrap_or_else(|| DecimalNumber::new(value))
+}
+
 impl From<f32> for Expression {
     fn from(value: f32) -> Self {
         (value as f64).into()
@@ -7,6 +7,7 @@ use crate::nodes::{Token, Trivia};
 pub struct DecimalNumber {
     float: f64,
     exponent: Option<(i64, bool)>,
+    has_decimal_point: bool,
     token: Option<Token>,
 }
 
@@ -17,6 +18,7 @@ impl DecimalNumber {
         Self {
             float: value,
             exponent: None,
+            has_decimal_point: false,
             token: None,
         }
     }
@@ -36,6 +38,18 @@ impl DecimalNumber {
         self.token.as_ref()
     }
 
+    /// Marks a whole number as written with a decimal point (like `1.0`), so that it is
+    /// generated back with it. Lua 5.3 reads such a number as a float instead of an integer.
+    pub fn with_decimal_point(mut self) -> Self {
+        self.has_decimal_point = true;
+        self
+    }
+
+    #[inline]
+    pub fn has_decimal_point(&self) -> bool {
+        self.has_decimal_point
+    }
+
     pub fn with_exponent(mut self, exponent: i64, is_uppercase: bool) -> Self {
         self.exponent.replace((exponent, is_uppercase));
         self
@@ -61,15 +75,24 @@ impl DecimalNumber {
         self.exponent.map(|(exponent, _)| exponent)
     }
 
+    /// Computes the value read from the number once it is generated: numbers with an
+    /// exponent are parsed from the same digits, so that the value does not depend on a
+    /// rounded multiplication.
     pub fn compute_value(&self) -> f64 {
         if let Some((exponent, _)) = self.exponent {
-            self.float * 10_f64.powf(exponent as f64)
+            format!("{}e{}", self.float, exponent)
+                .parse()
+                .unwrap_or_else(|_| self.float * 10_f64.powf(exponent as f64))
         } else {
             self.float
         }
     }
 
     super::impl_token_fns!(iter = [token]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -144,6 +167,10 @@ impl HexNumber {
     }
 
     super::impl_token_fns!(iter = [token]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -196,6 +223,10 @@ impl BinaryNumber {
     }
 
     super::impl_token_fns!(iter = [token]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -273,11 +304,19 @@ impl NumberExpression {
         }
     }
 
-    pub(crate) fn shift_token_line(&mut self, amount: usize) {
+    pub(crate) fn for_each_token(&mut self, callback: &mut dyn FnMut(&mut crate::nodes::Token)) {
         match self {
-            NumberExpression::Decimal(number) => number.shift_token_line(amount),
-            NumberExpression::Hex(number) => number.shift_token_line(amount),
-            NumberExpression::Binary(number) => number.shift_token_line(amount),
+            NumberExpression::Decimal(number) => number.for_each_token(callback),
+            NumberExpression::Hex(number) => number.for_each_token(callback),
+            NumberExpression::Binary(number) => number.for_each_token(callback),
+        }
+    }
+
+    pub(crate) fn clear_tokens(&mut self) {
+        match self {
+            NumberExpression::Decimal(number) => number.clear_tokens(),
+            NumberExpression::Hex(number) => number.clear_tokens(),
+            NumberExpression::Binary(number) => number.clear_tokens(),
         }
     }
 
@@ -335,6 +374,17 @@ fn filter_underscore(number: &str) -> String {
     number.chars().filter(|c| c != &'_').collect()
 }
 
+/// Keeps track of the decimal point of whole numbers, since the float value alone can not
+/// tell `1.0` apart from `1`.
+fn mark_whole_float(number: DecimalNumber) -> DecimalNumber {
+    let value = number.get_raw_float();
+    if value.is_finite() && value.fract() == 0.0 {
+        number.with_decimal_point()
+    } else {
+        number
+    }
+}
+
 impl FromStr for NumberExpression {
     type Err = NumberParsingError;
 
@@ -412,13 +462,28 @@ impl FromStr for NumberExpression {
                         .and_then(|string| string.parse().ok())
                         .ok_or(Self::Err::InvalidDecimalNumber)?;
 
-                    DecimalNumber::new(number).with_exponent(exponent, exponent_is_uppercase)
+                    let number =
+                        DecimalNumber::new(number).with_exponent(exponent, exponent_is_uppercase);
+
+                    // when the digits before the exponent can not be stored exactly, the
+                    // number is kept without its exponent so that it is generated with
+                    // the same value
+                    match filter_underscore(value).parse::<f64>() {
+                        Ok(exact_value) if exact_value != number.compute_value() => {
+                            mark_whole_float(DecimalNumber::new(exact_value))
+                        }
+                        _ => number,
+                    }
                 } else {
                     let number = filter_underscore(value)
                         .parse::<f64>()
                         .map_err(|_| Self::Err::InvalidDecimalNumber)?;
 
-                    DecimalNumber::new(number)
+                    if value.contains('.') {
+                        mark_whole_float(DecimalNumber::new(number))
+                    } else {
+                        DecimalNumber::new(number)
+                    }
                 }
                 .into()
             }
@@ -526,7 +591,8 @@ mod test {
             parse_multiple_decimal("123.24") => DecimalNumber::new(123.24_f64),
             parse_multiple_decimal_with_underscore("123.245_6") => DecimalNumber::new(123.245_6_f64),
             parse_multiple_decimal_with_underscore_after_point("0._24") => DecimalNumber::new(0.24_f64),
-            parse_float_with_trailing_dot("123.") => DecimalNumber::new(123_f64),
+            parse_float_with_trailing_dot("123.") => DecimalNumber::new(123_f64).with_decimal_point(),
+            parse_whole_float("1.0") => DecimalNumber::new(1_f64).with_decimal_point(),
             parse_starting_with_dot(".123") => DecimalNumber::new(0.123_f64),
             parse_digit_with_exponent("1e10") => DecimalNumber::new(1_f64).with_exponent(10, false),
             parse_digit_with_exponent_and_underscore("1e_10") => DecimalNumber::new(1_f64).with_exponent(10, false),
@@ -610,5 +676,27 @@ mod test {
             binary_zero("0b0") => 0b0,
             binary_ten("0b1010") => 0b1010,
         );
+
+        macro_rules! test_exact_compute_value {
+            ($($name:ident($input:literal) => $value:expr),* $(,)?) => {
+                $(
+                    #[test]
+                    fn $name() {
+                        let number = NumberExpression::from_str($input)
+                            .expect(&format!("unable to parse `{}`", $input));
+                        assert_eq!(number.compute_value().to_bits(), ($value as f64).to_bits());
+                    }
+                )*
+            };
+        }
+
+        test_exact_compute_value!(
+            exact_number_with_negative_exponent("1.2345e-50") => 1.2345e-50,
+            exact_number_with_large_exponent("8.98846567431158e307") => 8.98846567431158e307,
+            exact_subnormal_with_exponent("4.9e-324") => 4.9e-324,
+            exact_seventeen_digits_with_exponent("9.0071992547409930e15") => 9007199254740993.0,
+            exact_many_digits_with_exponent("1.4514526454261344529e-7")
+                => 1.4514526454261344529e-7,
+        );
     }
 }
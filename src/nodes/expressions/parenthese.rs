@@ -60,4 +60,8 @@ impl ParentheseExpression {
     }
 
     super::impl_token_fns!(iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
@@ -1,5 +1,6 @@
 use crate::nodes::{
     Expression, FieldExpression, FunctionCall, Identifier, IndexExpression, ParentheseExpression,
+    SourcePosition, Token,
 };
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -15,6 +16,19 @@ impl Prefix {
     pub fn from_name<S: Into<Identifier>>(name: S) -> Self {
         Self::Identifier(name.into())
     }
+
+    /// Returns the position of the first token of the prefix, if it has tokens.
+    pub fn start_position(&self) -> Option<SourcePosition> {
+        match self {
+            Self::Call(call) => call.get_prefix().start_position(),
+            Self::Field(field) => field.get_prefix().start_position(),
+            Self::Identifier(identifier) => identifier.get_token().and_then(Token::start_position),
+            Self::Index(index) => index.get_prefix().start_position(),
+            Self::Parenthese(parenthese) => parenthese
+                .get_tokens()
+                .and_then(|tokens| tokens.left_parenthese.start_position()),
+        }
+    }
 }
 
 impl From<Expression> for Prefix {
@@ -68,6 +82,18 @@ impl From<Identifier> for Prefix {
     }
 }
 
+impl From<&str> for Prefix {
+    fn from(name: &str) -> Self {
+        Self::from_name(name)
+    }
+}
+
+impl From<String> for Prefix {
+    fn from(name: String) -> Self {
+        Self::from_name(name)
+    }
+}
+
 impl From<IndexExpression> for Prefix {
     fn from(index: IndexExpression) -> Self {
         Self::Index(index.into())
@@ -59,6 +59,8 @@ impl StringExpression {
         }
     }
 
+    /// Creates a string from its value at runtime. The generators choose how to quote and
+    /// escape the value.
     pub fn from_value<T: Into<String>>(value: T) -> Self {
         Self {
             value: value.into(),
@@ -66,6 +68,13 @@ impl StringExpression {
         }
     }
 
+    /// Creates a string from the bytes it contains at runtime. Bytes that are not part of
+    /// a valid UTF-8 sequence are stored as the character with the same code point, like
+    /// escaped bytes in parsed strings.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self::from_value(string_utils::decode_bytes(bytes.to_vec()))
+    }
+
     pub fn with_token(mut self, token: Token) -> Self {
         self.token = Some(token);
         self
@@ -81,6 +90,8 @@ impl StringExpression {
         self.token.as_ref()
     }
 
+    /// Returns the value of the string at runtime, with its escape sequences resolved and
+    /// without its quotes or brackets.
     #[inline]
     pub fn get_value(&self) -> &str {
         &self.value
@@ -132,6 +143,10 @@ impl StringExpression {
     }
 
     super::impl_token_fns!(iter = [token]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+    }
 }
 
 #[cfg(test)]
@@ -198,8 +213,29 @@ mod test {
         escaped_unicode_two_hex_digits("\\u{AB}") => "\u{AB}",
         escaped_unicode_three_digit("\\u{123}") => "\u{123}",
         escaped_unicode_last_value("\\u{10FFFF}") => "\u{10FFFF}",
+        escaped_utf8_bytes("\\195\\169") => "é",
+        escaped_utf8_hex_bytes("\\xF0\\x9F\\x98\\x80") => "😀",
+        escaped_utf8_bytes_next_to_characters("a\\240\\159\\152\\128b") => "a😀b",
+        escaped_invalid_utf8_byte("\\255") => "\u{FF}",
+        escaped_incomplete_utf8_bytes("\\240\\159a") => "\u{F0}\u{9F}a",
     );
 
+    #[test]
+    fn from_bytes_decodes_utf8_characters() {
+        assert_eq!(
+            StringExpression::from_bytes("a😀\0]]".as_bytes()).get_value(),
+            "a😀\0]]"
+        );
+    }
+
+    #[test]
+    fn from_bytes_keeps_invalid_utf8_bytes() {
+        assert_eq!(
+            StringExpression::from_bytes(&[b'a', 0xFF, 0xF0, 0x9F]).get_value(),
+            "a\u{FF}\u{F0}\u{9F}"
+        );
+    }
+
     macro_rules! test_quoted_failures {
         ($($name:ident => $input:literal),* $(,)?) => {
             mod single_quoted_failures {
@@ -48,7 +48,9 @@ pub(crate) fn read_escaped_string(
 ) -> Result<String, StringError> {
     let mut chars = chars.peekable();
 
-    let mut value = String::new();
+    // escape sequences can produce any byte, so the string is read as bytes and decoded
+    // once all the escaped bytes of a character are known
+    let mut value = Vec::new();
     if let Some(reserve_size) = reserve_size {
         value.reserve(reserve_size);
     }
@@ -57,19 +59,19 @@ pub(crate) fn read_escaped_string(
         if char == '\\' {
             if let Some((_, next_char)) = chars.next() {
                 match next_char {
-                    '\n' | '"' | '\'' | '\\' => value.push(next_char),
-                    'n' => value.push('\n'),
-                    't' => value.push('\t'),
-                    'a' => value.push('\u{7}'),
-                    'b' => value.push('\u{8}'),
-                    'v' => value.push('\u{B}'),
-                    'f' => value.push('\u{C}'),
-                    'r' => value.push('\r'),
+                    '\n' | '"' | '\'' | '\\' => push_char(&mut value, next_char),
+                    'n' => push_char(&mut value, '\n'),
+                    't' => push_char(&mut value, '\t'),
+                    'a' => push_char(&mut value, '\u{7}'),
+                    'b' => push_char(&mut value, '\u{8}'),
+                    'v' => push_char(&mut value, '\u{B}'),
+                    'f' => push_char(&mut value, '\u{C}'),
+                    'r' => push_char(&mut value, '\r'),
                     first_digit if first_digit.is_ascii_digit() => {
                         let number = read_number(&mut chars, Some(first_digit), 10, 3);
 
                         if number < 256 {
-                            value.push(number as u8 as char);
+                            value.push(number as u8);
                         } else {
                             return Err(StringError::malformed_escape_sequence(
                                 position,
@@ -86,7 +88,7 @@ pub(crate) fn read_escaped_string(
                                 + second_digit.to_digit(16).unwrap();
 
                             if number < 256 {
-                                value.push(number as u8 as char);
+                                value.push(number as u8);
                             } else {
                                 return Err(StringError::malformed_escape_sequence(
                                     position,
@@ -124,7 +126,10 @@ pub(crate) fn read_escaped_string(
                             ));
                         }
 
-                        value.push(char::from_u32(number).expect("unable to convert u32 to char"));
+                        push_char(
+                            &mut value,
+                            char::from_u32(number).expect("unable to convert u32 to char"),
+                        );
                     }
                     'z' => {
                         while chars
@@ -137,7 +142,7 @@ pub(crate) fn read_escaped_string(
                     }
                     _ => {
                         // an invalid escape does not error: it simply skips the backslash
-                        value.push(next_char);
+                        push_char(&mut value, next_char);
                     }
                 }
             } else {
@@ -147,13 +152,51 @@ pub(crate) fn read_escaped_string(
                 ));
             }
         } else {
-            value.push(char);
+            push_char(&mut value, char);
         }
     }
 
-    value.shrink_to_fit();
+    Ok(decode_bytes(value))
+}
+
+#[inline]
+fn push_char(bytes: &mut Vec<u8>, character: char) {
+    bytes.extend_from_slice(character.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+/// Decodes the bytes of a string. Bytes that are not part of a valid UTF-8 sequence are
+/// converted to the character with the same code point.
+pub(crate) fn decode_bytes(bytes: Vec<u8>) -> String {
+    let bytes = match String::from_utf8(bytes) {
+        Ok(mut value) => {
+            value.shrink_to_fit();
+            return value;
+        }
+        Err(err) => err.into_bytes(),
+    };
+
+    let mut value = String::with_capacity(bytes.len());
+    let mut rest = bytes.as_slice();
+
+    loop {
+        match std::str::from_utf8(rest) {
+            Ok(valid) => {
+                value.push_str(valid);
+                break;
+            }
+            Err(err) => {
+                let (valid, invalid) = rest.split_at(err.valid_up_to());
+                value.push_str(std::str::from_utf8(valid).expect("bytes should be valid UTF-8"));
+
+                let invalid_length = err.error_len().unwrap_or(invalid.len());
+                value.extend(invalid[..invalid_length].iter().map(|byte| *byte as char));
+
+                rest = &invalid[invalid_length..];
+            }
+        }
+    }
 
-    Ok(value)
+    value
 }
 
 fn read_number(
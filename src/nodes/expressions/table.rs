@@ -61,6 +61,11 @@ impl TableFieldEntry {
         target = [field]
         iter = [token]
     );
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+        self.field.clear_tokens();
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -126,6 +131,10 @@ impl TableIndexEntry {
     }
 
     super::impl_token_fns!(iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -180,10 +189,18 @@ impl TableEntry {
         }
     }
 
-    pub(crate) fn shift_token_line(&mut self, amount: usize) {
+    pub(crate) fn for_each_token(&mut self, callback: &mut dyn FnMut(&mut crate::nodes::Token)) {
+        match self {
+            TableEntry::Field(entry) => entry.for_each_token(callback),
+            TableEntry::Index(entry) => entry.for_each_token(callback),
+            TableEntry::Value(_) => {}
+        }
+    }
+
+    pub(crate) fn clear_tokens(&mut self) {
         match self {
-            TableEntry::Field(entry) => entry.shift_token_line(amount),
-            TableEntry::Index(entry) => entry.shift_token_line(amount),
+            TableEntry::Field(entry) => entry.clear_tokens(),
+            TableEntry::Index(entry) => entry.clear_tokens(),
             TableEntry::Value(_) => {}
         }
     }
@@ -312,6 +329,13 @@ impl TableExpression {
     }
 
     super::impl_token_fns!(iter = [tokens, entries]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        for entry in self.entries.iter_mut() {
+            entry.clear_tokens();
+        }
+    }
 }
 
 impl Default for TableExpression {
@@ -62,6 +62,10 @@ impl TypeCastExpression {
     }
 
     super::impl_token_fns!(iter = [token]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+    }
 }
 
 #[cfg(test)]
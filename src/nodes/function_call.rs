@@ -1,4 +1,4 @@
-use crate::nodes::{Arguments, Expression, Identifier, Prefix, Token};
+use crate::nodes::{Arguments, Expression, Identifier, Prefix, StringExpression, Token};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct FunctionCallTokens {
@@ -45,6 +45,11 @@ impl FunctionCall {
         }
     }
 
+    /// Creates a method call like `prefix:method()`.
+    pub fn from_method<T: Into<Prefix>, U: Into<Identifier>>(prefix: T, method: U) -> Self {
+        Self::from_prefix(prefix).with_method(method)
+    }
+
     pub fn with_tokens(mut self, tokens: FunctionCallTokens) -> Self {
         self.tokens = Some(tokens);
         self
@@ -70,6 +75,10 @@ impl FunctionCall {
         self
     }
 
+    pub fn with_string_argument<IntoString: Into<String>>(self, value: IntoString) -> Self {
+        self.with_argument(StringExpression::from_value(value))
+    }
+
     pub fn with_method<IntoString: Into<Identifier>>(mut self, method: IntoString) -> Self {
         self.method.replace(method.into());
         self
@@ -116,4 +125,14 @@ impl FunctionCall {
     }
 
     super::impl_token_fns!(iter = [tokens, method]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        if let Some(method) = &mut self.method {
+            method.clear_tokens();
+        }
+        if let Arguments::Tuple(tuple) = &mut self.arguments {
+            tuple.clear_tokens();
+        }
+    }
 }
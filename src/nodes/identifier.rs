@@ -65,6 +65,10 @@ impl Identifier {
     }
 
     super::impl_token_fns!(iter = [token]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+    }
 }
 
 impl<IntoString: Into<String>> From<IntoString> for Identifier {
@@ -0,0 +1,1011 @@
+use std::fmt::{self, Write};
+
+use crate::nodes::*;
+
+/// Size and complexity measurements of a block, a statement or an expression, computed in
+/// a single traversal of the node.
+///
+/// - the node count includes every block, statement, expression and type
+/// - the estimated length approximates the length of the code produced by the
+///   [`DenseLuaGenerator`](crate::generator::DenseLuaGenerator), without generating it
+/// - the maximum depth is the number of nested nodes (as counted by the node count) on the
+///   deepest path, where a single node has a depth of one
+///
+/// # Example
+/// ```
+/// # use darklua_core::nodes::{BinaryExpression, BinaryOperator, Expression, NodeMetrics};
+/// let expression = Expression::from(BinaryExpression::new(
+///     BinaryOperator::Plus,
+///     Expression::identifier("a"),
+///     Expression::from(1),
+/// ));
+///
+/// let metrics = NodeMetrics::of_expression(&expression);
+///
+/// assert_eq!(metrics.get_node_count(), 3);
+/// assert_eq!(metrics.get_estimated_length(), "a+1".len());
+/// assert_eq!(metrics.get_max_depth(), 2);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NodeMetrics {
+    node_count: usize,
+    estimated_length: usize,
+    max_depth: usize,
+}
+
+impl NodeMetrics {
+    pub fn of_block(block: &Block) -> Self {
+        let mut counter = MetricsCounter::default();
+        counter.block(block);
+        counter.into_metrics()
+    }
+
+    pub fn of_statement(statement: &Statement) -> Self {
+        let mut counter = MetricsCounter::default();
+        counter.statement(statement);
+        counter.into_metrics()
+    }
+
+    pub fn of_last_statement(statement: &LastStatement) -> Self {
+        let mut counter = MetricsCounter::default();
+        counter.last_statement(statement);
+        counter.into_metrics()
+    }
+
+    pub fn of_expression(expression: &Expression) -> Self {
+        let mut counter = MetricsCounter::default();
+        counter.expression(expression);
+        counter.into_metrics()
+    }
+
+    pub fn of_type(r#type: &Type) -> Self {
+        let mut counter = MetricsCounter::default();
+        counter.r#type(r#type);
+        counter.into_metrics()
+    }
+
+    /// Returns the number of blocks, statements, expressions and types.
+    #[inline]
+    pub fn get_node_count(&self) -> usize {
+        self.node_count
+    }
+
+    /// Returns an approximation of the length of the dense generated code. It does not
+    /// account for line wrapping, semicolons between statements or escaped quotes.
+    #[inline]
+    pub fn get_estimated_length(&self) -> usize {
+        self.estimated_length
+    }
+
+    #[inline]
+    pub fn get_max_depth(&self) -> usize {
+        self.max_depth
+    }
+}
+
+#[inline]
+fn is_word_character(character: char) -> bool {
+    character.is_ascii_alphanumeric() || character == '_'
+}
+
+/// Counts the characters written to it, so that numbers can be measured without
+/// allocating their string.
+#[derive(Default)]
+struct LengthCounter(usize);
+
+impl Write for LengthCounter {
+    fn write_str(&mut self, content: &str) -> fmt::Result {
+        self.0 += content.len();
+        Ok(())
+    }
+}
+
+fn formatted_length(arguments: fmt::Arguments) -> usize {
+    let mut counter = LengthCounter::default();
+    counter
+        .write_fmt(arguments)
+        .expect("counting characters should not fail");
+    counter.0
+}
+
+#[derive(Default)]
+struct MetricsCounter {
+    node_count: usize,
+    length: usize,
+    depth: usize,
+    max_depth: usize,
+    ends_with_word: bool,
+}
+
+impl MetricsCounter {
+    fn into_metrics(self) -> NodeMetrics {
+        NodeMetrics {
+            node_count: self.node_count,
+            estimated_length: self.length,
+            max_depth: self.max_depth,
+        }
+    }
+
+    fn enter(&mut self) {
+        self.node_count += 1;
+        self.depth += 1;
+        self.max_depth = self.max_depth.max(self.depth);
+    }
+
+    fn exit(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Adds the length of a token, with a space when it would merge with the previous
+    /// token (like two keywords or identifiers).
+    fn push(&mut self, token: &str) {
+        if let Some(first) = token.chars().next() {
+            self.push_length(
+                token.len(),
+                is_word_character(first),
+                token.ends_with(is_word_character),
+            );
+        }
+    }
+
+    fn push_length(&mut self, length: usize, starts_with_word: bool, ends_with_word: bool) {
+        if self.ends_with_word && starts_with_word {
+            self.length += 1;
+        }
+        self.length += length;
+        self.ends_with_word = ends_with_word;
+    }
+
+    fn push_string(&mut self, value: &str) {
+        let escapes = value
+            .chars()
+            .filter(|character| matches!(character, '\n' | '\r' | '\t' | '\0' | '\\'))
+            .count();
+        self.push_length(value.len() + escapes + 2, false, false);
+    }
+
+    fn push_list<'a, T: 'a>(
+        &mut self,
+        items: impl IntoIterator<Item = &'a T>,
+        mut push_item: impl FnMut(&mut Self, &'a T),
+    ) {
+        for (index, item) in items.into_iter().enumerate() {
+            if index != 0 {
+                self.push(",");
+            }
+            push_item(self, item);
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        self.enter();
+
+        for statement in block.iter_statements() {
+            self.statement(statement);
+        }
+
+        if let Some(last_statement) = block.get_last_statement() {
+            self.last_statement(last_statement);
+        }
+
+        self.exit();
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        self.enter();
+
+        match statement {
+            Statement::Assign(assign) => {
+                self.push_list(assign.get_variables(), Self::variable);
+                self.push("=");
+                self.push_list(assign.iter_values(), Self::expression);
+            }
+            Statement::Do(do_statement) => {
+                self.push("do");
+                self.block(do_statement.get_block());
+                self.push("end");
+            }
+            Statement::Call(call) => self.function_call(call),
+            Statement::CompoundAssign(assign) => {
+                self.variable(assign.get_variable());
+                self.push(assign.get_operator().to_str());
+                self.expression(assign.get_value());
+            }
+            Statement::Function(function) => {
+                self.attributes(function.iter_attributes());
+                self.push("function");
+                let name = function.get_name();
+                self.push(name.get_name().get_name());
+                for field in name.get_field_names() {
+                    self.push(".");
+                    self.push(field.get_name());
+                }
+                if let Some(method) = name.get_method() {
+                    self.push(":");
+                    self.push(method.get_name());
+                }
+                self.function_body(
+                    function.get_generic_parameters(),
+                    function.get_parameters(),
+                    function.is_variadic(),
+                    function.get_variadic_type(),
+                    function.get_return_type(),
+                    function.get_block(),
+                );
+            }
+            Statement::GenericFor(generic_for) => {
+                self.push("for");
+                self.push_list(generic_for.get_identifiers(), Self::typed_identifier);
+                self.push("in");
+                self.push_list(generic_for.get_expressions(), Self::expression);
+                self.push("do");
+                self.block(generic_for.get_block());
+                self.push("end");
+            }
+            Statement::Goto(goto) => {
+                self.push("goto");
+                self.push(goto.get_label().get_name());
+            }
+            Statement::If(if_statement) => {
+                for (index, branch) in if_statement.iter_branches().enumerate() {
+                    self.push(if index == 0 { "if" } else { "elseif" });
+                    self.expression(branch.get_condition());
+                    self.push("then");
+                    self.block(branch.get_block());
+                }
+                if let Some(else_block) = if_statement.get_else_block() {
+                    self.push("else");
+                    self.block(else_block);
+                }
+                self.push("end");
+            }
+            Statement::Label(label) => {
+                self.push("::");
+                self.push(label.get_name().get_name());
+                self.push("::");
+            }
+            Statement::LocalAssign(assign) => {
+                self.push("local");
+                self.push_list(assign.get_variables(), Self::typed_identifier);
+                if assign.has_values() {
+                    self.push("=");
+                    self.push_list(assign.iter_values(), Self::expression);
+                }
+            }
+            Statement::LocalFunction(function) => {
+                self.attributes(function.iter_attributes());
+                self.push("local");
+                self.push("function");
+                self.push(function.get_name());
+                self.function_body(
+                    function.get_generic_parameters(),
+                    function.get_parameters(),
+                    function.is_variadic(),
+                    function.get_variadic_type(),
+                    function.get_return_type(),
+                    function.get_block(),
+                );
+            }
+            Statement::ExportTypeFunction(function) => {
+                self.push("export");
+                self.push("type");
+                self.push("function");
+                self.push(function.get_name());
+                self.function_body(
+                    function.get_generic_parameters(),
+                    function.get_parameters(),
+                    function.is_variadic(),
+                    function.get_variadic_type(),
+                    function.get_return_type(),
+                    function.get_block(),
+                );
+            }
+            Statement::TypeFunction(function) => {
+                self.push("type");
+                self.push("function");
+                self.push(function.get_name());
+                self.function_body(
+                    function.get_generic_parameters(),
+                    function.get_parameters(),
+                    function.is_variadic(),
+                    function.get_variadic_type(),
+                    function.get_return_type(),
+                    function.get_block(),
+                );
+            }
+            Statement::NumericFor(numeric_for) => {
+                self.push("for");
+                self.typed_identifier(numeric_for.get_identifier());
+                self.push("=");
+                self.expression(numeric_for.get_start());
+                self.push(",");
+                self.expression(numeric_for.get_end());
+                if let Some(step) = numeric_for.get_step() {
+                    self.push(",");
+                    self.expression(step);
+                }
+                self.push("do");
+                self.block(numeric_for.get_block());
+                self.push("end");
+            }
+            Statement::Repeat(repeat) => {
+                self.push("repeat");
+                self.block(repeat.get_block());
+                self.push("until");
+                self.expression(repeat.get_condition());
+            }
+            Statement::While(while_statement) => {
+                self.push("while");
+                self.expression(while_statement.get_condition());
+                self.push("do");
+                self.block(while_statement.get_block());
+                self.push("end");
+            }
+            Statement::TypeDeclaration(type_declaration) => {
+                if type_declaration.is_exported() {
+                    self.push("export");
+                }
+                self.push("type");
+                self.push(type_declaration.get_name().get_name());
+
+                if let Some(parameters) = type_declaration
+                    .get_generic_parameters()
+                    .filter(|parameters| !parameters.is_empty())
+                {
+                    self.push("<");
+                    for (index, parameter) in parameters.iter().enumerate() {
+                        if index != 0 {
+                            self.push(",");
+                        }
+                        self.generic_parameter(parameter);
+                    }
+                    self.push(">");
+                }
+
+                self.push("=");
+                self.r#type(type_declaration.get_type());
+            }
+        }
+
+        self.exit();
+    }
+
+    fn last_statement(&mut self, statement: &LastStatement) {
+        self.enter();
+
+        match statement {
+            LastStatement::Break(_) => self.push("break"),
+            LastStatement::Continue(_) => self.push("continue"),
+            LastStatement::Return(statement) => {
+                self.push("return");
+                self.push_list(statement.iter_expressions(), Self::expression);
+            }
+        }
+
+        self.exit();
+    }
+
+    fn attributes<'a>(&mut self, attributes: impl Iterator<Item = &'a Attribute>) {
+        for attribute in attributes {
+            self.push("@");
+            self.push(attribute.get_name().get_name());
+        }
+    }
+
+    fn function_body(
+        &mut self,
+        generics: Option<&GenericParameters>,
+        parameters: &[TypedIdentifier],
+        is_variadic: bool,
+        variadic_type: Option<&FunctionVariadicType>,
+        return_type: Option<&FunctionReturnType>,
+        block: &Block,
+    ) {
+        if let Some(generics) = generics {
+            self.generic_parameters(generics);
+        }
+
+        self.push("(");
+        self.push_list(parameters, Self::typed_identifier);
+        if is_variadic {
+            if !parameters.is_empty() {
+                self.push(",");
+            }
+            self.push("...");
+            if let Some(variadic_type) = variadic_type {
+                self.push(":");
+                match variadic_type {
+                    FunctionVariadicType::Type(r#type) => self.r#type(r#type),
+                    FunctionVariadicType::GenericTypePack(generic_pack) => {
+                        self.generic_type_pack(generic_pack)
+                    }
+                }
+            }
+        }
+        self.push(")");
+
+        if let Some(return_type) = return_type {
+            self.push(":");
+            self.function_return_type(return_type);
+        }
+
+        self.block(block);
+        self.push("end");
+    }
+
+    fn typed_identifier(&mut self, identifier: &TypedIdentifier) {
+        self.push(identifier.get_name());
+
+        if let Some(r#type) = identifier.get_type() {
+            self.push(":");
+            self.r#type(r#type);
+        }
+    }
+
+    fn variable(&mut self, variable: &Variable) {
+        self.enter();
+
+        match variable {
+            Variable::Identifier(identifier) => self.push(identifier.get_name()),
+            Variable::Field(field) => self.field(field),
+            Variable::Index(index) => self.index(index),
+        }
+
+        self.exit();
+    }
+
+    fn expression(&mut self, expression: &Expression) {
+        self.enter();
+
+        match expression {
+            Expression::Binary(binary) => {
+                let operator = binary.operator();
+                let left = binary.left();
+                let right = binary.right();
+
+                self.expression_in_parentheses(left, operator.left_needs_parentheses(left));
+                self.push(operator.to_str());
+                self.expression_in_parentheses(right, operator.right_needs_parentheses(right));
+            }
+            Expression::Call(call) => self.function_call(call),
+            Expression::False(_) => self.push("false"),
+            Expression::Field(field) => self.field(field),
+            Expression::Function(function) => {
+                self.attributes(function.iter_attributes());
+                self.push("function");
+                self.function_body(
+                    function.get_generic_parameters(),
+                    function.get_parameters(),
+                    function.is_variadic(),
+                    function.get_variadic_type(),
+                    function.get_return_type(),
+                    function.get_block(),
+                );
+            }
+            Expression::Identifier(identifier) => self.push(identifier.get_name()),
+            Expression::If(if_expression) => {
+                self.push("if");
+                self.expression(if_expression.get_condition());
+                self.push("then");
+                self.expression(if_expression.get_result());
+                for branch in if_expression.iter_branches() {
+                    self.push("elseif");
+                    self.expression(branch.get_condition());
+                    self.push("then");
+                    self.expression(branch.get_result());
+                }
+                self.push("else");
+                self.expression(if_expression.get_else_result());
+            }
+            Expression::Index(index) => self.index(index),
+            Expression::Nil(_) => self.push("nil"),
+            Expression::Number(number) => self.number(number),
+            Expression::Parenthese(parenthese) => {
+                self.expression_in_parentheses(parenthese.inner_expression(), true)
+            }
+            Expression::String(string) => self.push_string(string.get_value()),
+            Expression::InterpolatedString(string) => {
+                self.push("`");
+                for segment in string.iter_segments() {
+                    match segment {
+                        InterpolationSegment::String(segment) => {
+                            self.length += segment.get_value().len();
+                        }
+                        InterpolationSegment::Value(value) => {
+                            self.push("{");
+                            self.expression(value.get_expression());
+                            self.push("}");
+                        }
+                    }
+                }
+                self.push("`");
+            }
+            Expression::Table(table) => self.table(table),
+            Expression::True(_) => self.push("true"),
+            Expression::Unary(unary) => {
+                self.push(unary.operator().to_str());
+
+                let inner_expression = unary.get_expression();
+                let needs_parentheses = matches!(
+                    inner_expression,
+                    Expression::Binary(binary) if !binary.operator().precedes_unary_expression()
+                );
+                self.expression_in_parentheses(inner_expression, needs_parentheses);
+            }
+            Expression::VariableArguments(_) => self.push("..."),
+            Expression::TypeCast(type_cast) => {
+                let inner_expression = type_cast.get_expression();
+                self.expression_in_parentheses(
+                    inner_expression,
+                    TypeCastExpression::needs_parentheses(inner_expression),
+                );
+                self.push("::");
+                self.r#type(type_cast.get_type());
+            }
+        }
+
+        self.exit();
+    }
+
+    fn expression_in_parentheses(&mut self, expression: &Expression, needs_parentheses: bool) {
+        if needs_parentheses {
+            self.push("(");
+            self.expression(expression);
+            self.push(")");
+        } else {
+            self.expression(expression);
+        }
+    }
+
+    fn prefix(&mut self, prefix: &Prefix) {
+        self.enter();
+
+        match prefix {
+            Prefix::Call(call) => self.function_call(call),
+            Prefix::Field(field) => self.field(field),
+            Prefix::Identifier(identifier) => self.push(identifier.get_name()),
+            Prefix::Index(index) => self.index(index),
+            Prefix::Parenthese(parenthese) => {
+                self.expression_in_parentheses(parenthese.inner_expression(), true)
+            }
+        }
+
+        self.exit();
+    }
+
+    fn function_call(&mut self, call: &FunctionCall) {
+        self.prefix(call.get_prefix());
+
+        if let Some(method) = call.get_method() {
+            self.push(":");
+            self.push(method.get_name());
+        }
+
+        match call.get_arguments() {
+            Arguments::Tuple(tuple) => {
+                self.push("(");
+                self.push_list(tuple.iter_values(), Self::expression);
+                self.push(")");
+            }
+            Arguments::String(string) => self.push_string(string.get_value()),
+            Arguments::Table(table) => self.table(table),
+        }
+    }
+
+    fn field(&mut self, field: &FieldExpression) {
+        self.prefix(field.get_prefix());
+        self.push(".");
+        self.push(field.get_field().get_name());
+    }
+
+    fn index(&mut self, index: &IndexExpression) {
+        self.prefix(index.get_prefix());
+        self.push("[");
+        self.expression(index.get_index());
+        self.push("]");
+    }
+
+    fn table(&mut self, table: &TableExpression) {
+        self.push("{");
+        self.push_list(table.get_entries(), |counter, entry| match entry {
+            TableEntry::Field(entry) => {
+                counter.push(entry.get_field().get_name());
+                counter.push("=");
+                counter.expression(entry.get_value());
+            }
+            TableEntry::Index(entry) => {
+                counter.push("[");
+                counter.expression(entry.get_key());
+                counter.push("]=");
+                counter.expression(entry.get_value());
+            }
+            TableEntry::Value(value) => counter.expression(value),
+        });
+        self.push("}");
+    }
+
+    fn number(&mut self, number: &NumberExpression) {
+        match number {
+            NumberExpression::Decimal(number) => {
+                let float = number.get_raw_float();
+
+                if float.is_nan() {
+                    self.push("(0/0)");
+                } else if float.is_infinite() {
+                    self.push(if float.is_sign_negative() {
+                        "(-1/0)"
+                    } else {
+                        "(1/0)"
+                    });
+                } else {
+                    let decimal_point_length = if number.has_decimal_point() && float.fract() == 0.0
+                    {
+                        2
+                    } else {
+                        0
+                    };
+                    let length = formatted_length(format_args!("{}", float))
+                        + decimal_point_length
+                        + number
+                            .get_exponent()
+                            .map(|exponent| formatted_length(format_args!("e{}", exponent)))
+                            .unwrap_or(0);
+                    self.push_length(length, true, true);
+                }
+            }
+            NumberExpression::Hex(number) => {
+                let length = formatted_length(format_args!("0x{:x}", number.get_raw_integer()))
+                    + number
+                        .get_exponent()
+                        .map(|exponent| formatted_length(format_args!("p{}", exponent)))
+                        .unwrap_or(0);
+                self.push_length(length, true, true);
+            }
+            NumberExpression::Binary(number) => {
+                let length = formatted_length(format_args!("0b{:b}", number.get_raw_value()));
+                self.push_length(length, true, true);
+            }
+        }
+    }
+
+    fn r#type(&mut self, r#type: &Type) {
+        self.enter();
+
+        match r#type {
+            Type::Name(type_name) => self.type_name(type_name),
+            Type::Field(type_field) => {
+                self.push(type_field.get_namespace().get_name());
+                self.push(".");
+                self.type_name(type_field.get_type_name());
+            }
+            Type::True(_) => self.push("true"),
+            Type::False(_) => self.push("false"),
+            Type::Nil(_) => self.push("nil"),
+            Type::String(string_type) => self.push_string(string_type.get_value()),
+            Type::Array(array_type) => {
+                self.push("{");
+                self.r#type(array_type.get_element_type());
+                self.push("}");
+            }
+            Type::Table(table_type) => {
+                self.push("{");
+                self.push_list(table_type.iter_entries(), |counter, entry| match entry {
+                    TableEntryType::Property(property) => {
+                        counter.push(property.get_identifier().get_name());
+                        counter.push(":");
+                        counter.r#type(property.get_type());
+                    }
+                    TableEntryType::Literal(property) => {
+                        counter.push("[");
+                        counter.push_string(property.get_string().get_value());
+                        counter.push("]:");
+                        counter.r#type(property.get_type());
+                    }
+                    TableEntryType::Indexer(indexer) => {
+                        let key_type = indexer.get_key_type();
+                        let needs_parentheses = matches!(
+                            key_type,
+                            Type::Optional(_) | Type::Intersection(_) | Type::Union(_)
+                        );
+                        counter.push("[");
+                        counter.type_in_parentheses(key_type, needs_parentheses);
+                        counter.push("]:");
+                        counter.r#type(indexer.get_value_type());
+                    }
+                });
+                self.push("}");
+            }
+            Type::TypeOf(expression_type) => {
+                self.push("typeof(");
+                self.expression(expression_type.get_expression());
+                self.push(")");
+            }
+            Type::Parenthese(parenthese_type) => {
+                self.type_in_parentheses(parenthese_type.get_inner_type(), true)
+            }
+            Type::Function(function_type) => {
+                if let Some(generics) = function_type.get_generic_parameters() {
+                    self.generic_parameters(generics);
+                }
+                self.push("(");
+                self.push_list(function_type.iter_arguments(), |counter, argument| {
+                    if let Some(name) = argument.get_name() {
+                        counter.push(name.get_name());
+                        counter.push(":");
+                    }
+                    counter.r#type(argument.get_type());
+                });
+                if let Some(variadic_type) = function_type.get_variadic_argument_type() {
+                    if function_type.argument_len() > 0 {
+                        self.push(",");
+                    }
+                    self.variadic_argument_type(variadic_type);
+                }
+                self.push(")->");
+                self.function_return_type(function_type.get_return_type());
+            }
+            Type::Optional(optional) => {
+                let inner_type = optional.get_inner_type();
+                self.type_in_parentheses(inner_type, OptionalType::needs_parentheses(inner_type));
+                self.push("?");
+            }
+            Type::Intersection(intersection) => {
+                let last_index = intersection.len().saturating_sub(1);
+                for (index, r#type) in intersection.iter_types().enumerate() {
+                    if index != 0 || intersection.has_leading_token() {
+                        self.push("&");
+                    }
+                    let needs_parentheses = if index == last_index {
+                        IntersectionType::last_needs_parentheses(r#type)
+                    } else {
+                        IntersectionType::intermediate_needs_parentheses(r#type)
+                    };
+                    self.type_in_parentheses(r#type, needs_parentheses);
+                }
+            }
+            Type::Union(union) => {
+                let last_index = union.len().saturating_sub(1);
+                for (index, r#type) in union.iter_types().enumerate() {
+                    if index != 0 || union.has_leading_token() {
+                        self.push("|");
+                    }
+                    let needs_parentheses = if index == last_index {
+                        UnionType::last_needs_parentheses(r#type)
+                    } else {
+                        UnionType::intermediate_needs_parentheses(r#type)
+                    };
+                    self.type_in_parentheses(r#type, needs_parentheses);
+                }
+            }
+        }
+
+        self.exit();
+    }
+
+    fn type_in_parentheses(&mut self, r#type: &Type, needs_parentheses: bool) {
+        if needs_parentheses {
+            self.push("(");
+            self.r#type(r#type);
+            self.push(")");
+        } else {
+            self.r#type(r#type);
+        }
+    }
+
+    fn type_name(&mut self, type_name: &TypeName) {
+        self.push(type_name.get_type_name().get_name());
+
+        if let Some(parameters) = type_name.get_type_parameters() {
+            self.push("<");
+            self.push_list(parameters.iter(), |counter, parameter| match parameter {
+                TypeParameter::Type(r#type) => counter.r#type(r#type),
+                TypeParameter::TypePack(type_pack) => counter.type_pack(type_pack),
+                TypeParameter::VariadicTypePack(variadic_type_pack) => {
+                    counter.variadic_type_pack(variadic_type_pack)
+                }
+                TypeParameter::GenericTypePack(generic_type_pack) => {
+                    counter.generic_type_pack(generic_type_pack)
+                }
+            });
+            self.push(">");
+        }
+    }
+
+    fn generic_parameters(&mut self, generics: &GenericParameters) {
+        if generics.is_empty() {
+            return;
+        }
+
+        self.push("<");
+        self.push_list(generics.iter_type_variable(), |counter, type_variable| {
+            counter.push(type_variable.get_name())
+        });
+        for (index, generic_pack) in generics.iter_generic_type_pack().enumerate() {
+            if index != 0 || generics.iter_type_variable().next().is_some() {
+                self.push(",");
+            }
+            self.generic_type_pack(generic_pack);
+        }
+        self.push(">");
+    }
+
+    fn generic_parameter(&mut self, parameter: GenericParameterRef<'_>) {
+        match parameter {
+            GenericParameterRef::TypeVariable(identifier) => self.push(identifier.get_name()),
+            GenericParameterRef::TypeVariableWithDefault(identifier_with_default) => {
+                self.push(identifier_with_default.get_type_variable().get_name());
+                self.push("=");
+                self.r#type(identifier_with_default.get_default_type());
+            }
+            GenericParameterRef::GenericTypePack(generic_type_pack) => {
+                self.generic_type_pack(generic_type_pack)
+            }
+            GenericParameterRef::GenericTypePackWithDefault(generic_pack_with_default) => {
+                self.generic_type_pack(generic_pack_with_default.get_generic_type_pack());
+                self.push("=");
+                match generic_pack_with_default.get_default_type() {
+                    GenericTypePackDefault::TypePack(type_pack) => self.type_pack(type_pack),
+                    GenericTypePackDefault::VariadicTypePack(variadic_type_pack) => {
+                        self.variadic_type_pack(variadic_type_pack)
+                    }
+                    GenericTypePackDefault::GenericTypePack(generic_type_pack) => {
+                        self.generic_type_pack(generic_type_pack)
+                    }
+                }
+            }
+        }
+    }
+
+    fn function_return_type(&mut self, return_type: &FunctionReturnType) {
+        match return_type {
+            FunctionReturnType::Type(r#type) => self.r#type(r#type),
+            FunctionReturnType::TypePack(type_pack) => self.type_pack(type_pack),
+            FunctionReturnType::VariadicTypePack(variadic_type_pack) => {
+                self.variadic_type_pack(variadic_type_pack)
+            }
+            FunctionReturnType::GenericTypePack(generic_type_pack) => {
+                self.generic_type_pack(generic_type_pack)
+            }
+        }
+    }
+
+    fn type_pack(&mut self, type_pack: &TypePack) {
+        self.push("(");
+        self.push_list(type_pack, Self::r#type);
+        if let Some(variadic_type) = type_pack.get_variadic_type() {
+            if !type_pack.is_empty() {
+                self.push(",");
+            }
+            self.variadic_argument_type(variadic_type);
+        }
+        self.push(")");
+    }
+
+    fn variadic_argument_type(&mut self, variadic_type: &VariadicArgumentType) {
+        match variadic_type {
+            VariadicArgumentType::GenericTypePack(generic_type_pack) => {
+                self.generic_type_pack(generic_type_pack)
+            }
+            VariadicArgumentType::VariadicTypePack(variadic_type_pack) => {
+                self.variadic_type_pack(variadic_type_pack)
+            }
+        }
+    }
+
+    fn variadic_type_pack(&mut self, variadic_type_pack: &VariadicTypePack) {
+        self.push("...");
+        self.r#type(variadic_type_pack.get_type());
+    }
+
+    fn generic_type_pack(&mut self, generic_type_pack: &GenericTypePack) {
+        self.push(generic_type_pack.get_name().get_name());
+        self.push("...");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generator::{DenseLuaGenerator, LuaGenerator};
+    use crate::Parser;
+
+    fn parse(code: &str) -> Block {
+        Parser::default()
+            .parse(code)
+            .unwrap_or_else(|_| panic!("could not parse `{}`", code))
+    }
+
+    macro_rules! test_block_metrics {
+        ($($name:ident($code:literal) => ($node_count:expr, $length:expr, $depth:expr)),* $(,)?) => {
+            $(
+                #[test]
+                fn $name() {
+                    let metrics = NodeMetrics::of_block(&parse($code));
+
+                    pretty_assertions::assert_eq!(
+                        (
+                            metrics.get_node_count(),
+                            metrics.get_estimated_length(),
+                            metrics.get_max_depth()
+                        ),
+                        ($node_count, $length, $depth)
+                    );
+                }
+            )*
+        };
+    }
+
+    test_block_metrics!(
+        empty_block("") => (1, 0, 1),
+        local_assign("local a = 1") => (3, "local a=1".len(), 3),
+        return_call("return f(a, b.c)") => (7, "return f(a,b.c)".len(), 5),
+        not_parenthese("return not (a and b)") => (7, "return not(a and b)".len(), 6),
+        table_type("local x: { [string]: number? } = {}") => (
+            7,
+            "local x:{[string]:number?}={}".len(),
+            5
+        ),
+        nested_blocks("do do do end end end") => (7, "do do do end end end".len(), 7),
+        string_with_escapes("call('a\\nb')") => (3, "call'a\\nb'".len(), 3),
+    );
+
+    macro_rules! test_estimated_length {
+        ($($name:ident($code:literal)),* $(,)?) => {
+            $(
+                #[test]
+                fn $name() {
+                    let block = parse($code);
+
+                    let mut generator = DenseLuaGenerator::default();
+                    generator.write_block(&block);
+                    let actual = generator.into_string().len();
+
+                    let estimated = NodeMetrics::of_block(&block).get_estimated_length();
+                    let tolerance = actual / 10 + 2;
+
+                    assert!(
+                        estimated.abs_diff(actual) <= tolerance,
+                        "estimated length {} is too far from the generated length {}",
+                        estimated,
+                        actual
+                    );
+                }
+            )*
+        };
+    }
+
+    test_estimated_length!(
+        estimate_function(
+            "local function fib(n: number): number
+                if n < 2 then
+                    return n
+                end
+                return fib(n - 1) + fib(n - 2)
+            end"
+        ),
+        estimate_loops(
+            "for i = 1, 10, 2 do print(i) end
+            for key, value in pairs(t) do t[key] = value * 2 end
+            while x > 0 do x -= 1 end
+            repeat local done = check() until done"
+        ),
+        estimate_tables(
+            "local config = {
+                name = 'darklua',
+                values = { 1, 2, 3.5, 0xFF, 1e10 },
+                [\"key\"] = true,
+                nested = { deep = { deeper = {} } },
+            }"
+        ),
+        estimate_methods_and_strings(
+            "local object = Class.new(`value {x + 1}`)
+            object:method('text', \"other\", [[long]])
+            object.field.other[index] = -#list"
+        ),
+        estimate_types(
+            "export type Map<K, V = string> = { [K]: V }
+            type Callback = (value: number, ...string) -> (boolean, ...any)
+            local value: (Map<string, number> | nil)? = nil :: any"
+        ),
+    );
+}
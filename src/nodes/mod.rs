@@ -1,11 +1,15 @@
 //! The collection of nodes used for the Lua abstract syntax tree.
 
 mod arguments;
+mod attribute;
 mod block;
+pub mod builder;
+mod deep_token_fns;
 mod expressions;
 mod function_body;
 mod function_call;
 mod identifier;
+mod metrics;
 mod statements;
 mod token;
 mod typed_identifier;
@@ -13,11 +17,13 @@ mod types;
 mod variable;
 
 pub use arguments::*;
+pub use attribute::*;
 pub use block::*;
 pub use expressions::*;
 pub use function_body::*;
 pub use function_call::*;
 pub use identifier::*;
+pub use metrics::*;
 pub use statements::*;
 pub use token::*;
 pub use typed_identifier::*;
@@ -78,18 +84,18 @@ macro_rules! impl_token_fns {
             )*)?
         }
 
-        pub(crate) fn shift_token_line(&mut self, amount: usize) {
+        pub(crate) fn for_each_token(&mut self, callback: &mut dyn FnMut(&mut crate::nodes::Token)) {
             $(
-                self.$field.shift_token_line(amount);
+                self.$field.for_each_token(callback);
             )*
             $($(
                 for token in self.$iter_field.iter_mut() {
-                    token.shift_token_line(amount);
+                    token.for_each_token(callback);
                 }
             )*)?
             $($(
                 for token in self.$iter_flatten_field.iter_mut().flatten() {
-                    token.shift_token_line(amount);
+                    token.for_each_token(callback);
                 }
             )*)?
         }
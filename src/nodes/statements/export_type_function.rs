@@ -1,6 +1,6 @@
 use crate::nodes::{
     Block, FunctionBodyTokens, FunctionReturnType, FunctionVariadicType, GenericParameters,
-    Identifier, Token, TypedIdentifier,
+    Identifier, Token, TypeFunctionStatement, TypeFunctionTokens, TypedIdentifier,
 };
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -250,6 +250,53 @@ impl ExportTypeFunctionStatement {
         target = [identifier]
         iter = [parameters, generic_parameters, tokens]
     );
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        self.identifier.clear_tokens();
+        for parameter in self.parameters.iter_mut() {
+            parameter.clear_tokens();
+        }
+        if let Some(generic_parameters) = &mut self.generic_parameters {
+            generic_parameters.clear_tokens();
+        }
+    }
+}
+
+impl From<ExportTypeFunctionStatement> for TypeFunctionStatement {
+    fn from(statement: ExportTypeFunctionStatement) -> Self {
+        let mut type_function = TypeFunctionStatement::new(
+            statement.identifier,
+            statement.block,
+            statement.parameters,
+            statement.is_variadic,
+        );
+
+        if let Some(variadic_type) = statement.variadic_type {
+            type_function.set_variadic_type(variadic_type);
+        }
+        if let Some(return_type) = statement.return_type {
+            type_function.set_return_type(return_type);
+        }
+        if let Some(generic_parameters) = statement.generic_parameters {
+            type_function.set_generic_parameters(generic_parameters);
+        }
+        if let Some(tokens) = statement.tokens {
+            let ExportTypeFunctionTokens {
+                export,
+                mut r#type,
+                function_body,
+            } = *tokens;
+            r#type.prepend_leading_trivia_of(export);
+
+            type_function.set_tokens(TypeFunctionTokens {
+                r#type,
+                function_body,
+            });
+        }
+
+        type_function
+    }
 }
 
 #[cfg(test)]
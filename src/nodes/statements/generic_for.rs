@@ -121,4 +121,11 @@ impl GenericForStatement {
     }
 
     super::impl_token_fns!(iter = [tokens, identifiers]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        for identifier in self.identifiers.iter_mut() {
+            identifier.clear_tokens();
+        }
+    }
 }
@@ -0,0 +1,57 @@
+use crate::nodes::{Identifier, Token};
+
+/// A Lua 5.2 `goto` statement that jumps to the label with the given name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GotoStatement {
+    label: Identifier,
+    token: Option<Token>,
+}
+
+impl GotoStatement {
+    pub fn new(label: impl Into<Identifier>) -> Self {
+        Self {
+            label: label.into(),
+            token: None,
+        }
+    }
+
+    #[inline]
+    pub fn get_label(&self) -> &Identifier {
+        &self.label
+    }
+
+    #[inline]
+    pub fn mutate_label(&mut self) -> &mut Identifier {
+        &mut self.label
+    }
+
+    pub fn with_token(mut self, token: Token) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    #[inline]
+    pub fn set_token(&mut self, token: Token) {
+        self.token = Some(token);
+    }
+
+    #[inline]
+    pub fn get_token(&self) -> Option<&Token> {
+        self.token.as_ref()
+    }
+
+    #[inline]
+    pub fn mutate_token(&mut self) -> Option<&mut Token> {
+        self.token.as_mut()
+    }
+
+    super::impl_token_fns!(
+        target = [label]
+        iter = [token]
+    );
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+        self.label.clear_tokens();
+    }
+}
@@ -77,6 +77,10 @@ impl IfBranch {
     }
 
     super::impl_token_fns!(iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -234,4 +238,11 @@ impl IfStatement {
     }
 
     super::impl_token_fns!(iter = [tokens, branches]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        for branch in self.branches.iter_mut() {
+            branch.clear_tokens();
+        }
+    }
 }
@@ -0,0 +1,67 @@
+use crate::nodes::{Identifier, Token};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LabelTokens {
+    pub left_colons: Token,
+    pub right_colons: Token,
+}
+
+impl LabelTokens {
+    super::impl_token_fns!(target = [left_colons, right_colons]);
+}
+
+/// A Lua 5.2 label statement (`::name::`), used as the destination of `goto` statements.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LabelStatement {
+    name: Identifier,
+    tokens: Option<LabelTokens>,
+}
+
+impl LabelStatement {
+    pub fn new(name: impl Into<Identifier>) -> Self {
+        Self {
+            name: name.into(),
+            tokens: None,
+        }
+    }
+
+    #[inline]
+    pub fn get_name(&self) -> &Identifier {
+        &self.name
+    }
+
+    #[inline]
+    pub fn mutate_name(&mut self) -> &mut Identifier {
+        &mut self.name
+    }
+
+    pub fn with_tokens(mut self, tokens: LabelTokens) -> Self {
+        self.tokens = Some(tokens);
+        self
+    }
+
+    #[inline]
+    pub fn set_tokens(&mut self, tokens: LabelTokens) {
+        self.tokens = Some(tokens);
+    }
+
+    #[inline]
+    pub fn get_tokens(&self) -> Option<&LabelTokens> {
+        self.tokens.as_ref()
+    }
+
+    #[inline]
+    pub fn mutate_tokens(&mut self) -> Option<&mut LabelTokens> {
+        self.tokens.as_mut()
+    }
+
+    super::impl_token_fns!(
+        target = [name]
+        iter = [tokens]
+    );
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        self.name.clear_tokens();
+    }
+}
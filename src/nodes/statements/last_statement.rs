@@ -1,4 +1,4 @@
-use crate::nodes::{Expression, Token};
+use crate::nodes::{Expression, SourcePosition, Token};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ReturnTokens {
@@ -94,6 +94,10 @@ impl ReturnStatement {
     }
 
     super::impl_token_fns!(iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -113,6 +117,18 @@ impl LastStatement {
     pub fn new_continue() -> Self {
         Self::Continue(None)
     }
+
+    /// Returns the position of the first token of the statement, if it has tokens.
+    pub fn start_position(&self) -> Option<SourcePosition> {
+        match self {
+            Self::Break(token) | Self::Continue(token) => {
+                token.as_ref().and_then(Token::start_position)
+            }
+            Self::Return(statement) => statement
+                .get_tokens()
+                .and_then(|tokens| tokens.r#return.start_position()),
+        }
+    }
 }
 
 impl From<ReturnStatement> for LastStatement {
@@ -220,6 +220,13 @@ impl LocalAssignStatement {
     }
 
     super::impl_token_fns!(iter = [variables, tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        for variable in self.variables.iter_mut() {
+            variable.clear_tokens();
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,6 @@
 use crate::nodes::{
-    Block, FunctionBodyTokens, FunctionReturnType, FunctionVariadicType, GenericParameters,
-    Identifier, Token, TypedIdentifier,
+    Attribute, Block, FunctionBodyTokens, FunctionReturnType, FunctionVariadicType,
+    GenericParameters, Identifier, Token, TypedIdentifier,
 };
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -36,6 +36,7 @@ pub struct LocalFunctionStatement {
     variadic_type: Option<FunctionVariadicType>,
     return_type: Option<FunctionReturnType>,
     generic_parameters: Option<GenericParameters>,
+    attributes: Vec<Attribute>,
     tokens: Option<Box<LocalFunctionTokens>>,
 }
 
@@ -54,6 +55,7 @@ impl LocalFunctionStatement {
             variadic_type: None,
             return_type: None,
             generic_parameters: None,
+            attributes: Vec::new(),
             tokens: None,
         }
     }
@@ -67,6 +69,7 @@ impl LocalFunctionStatement {
             variadic_type: None,
             return_type: None,
             generic_parameters: None,
+            attributes: Vec::new(),
             tokens: None,
         }
     }
@@ -166,6 +169,41 @@ impl LocalFunctionStatement {
         self.generic_parameters.as_ref()
     }
 
+    pub fn with_attribute(mut self, attribute: impl Into<Attribute>) -> Self {
+        self.attributes.push(attribute.into());
+        self
+    }
+
+    #[inline]
+    pub fn push_attribute(&mut self, attribute: impl Into<Attribute>) {
+        self.attributes.push(attribute.into());
+    }
+
+    #[inline]
+    pub fn iter_attributes(&self) -> impl Iterator<Item = &Attribute> {
+        self.attributes.iter()
+    }
+
+    #[inline]
+    pub fn iter_mut_attributes(&mut self) -> impl Iterator<Item = &mut Attribute> {
+        self.attributes.iter_mut()
+    }
+
+    #[inline]
+    pub fn mutate_attributes(&mut self) -> &mut Vec<Attribute> {
+        &mut self.attributes
+    }
+
+    #[inline]
+    pub fn has_attributes(&self) -> bool {
+        !self.attributes.is_empty()
+    }
+
+    #[inline]
+    pub fn clear_attributes(&mut self) {
+        self.attributes.clear();
+    }
+
     #[inline]
     pub fn mutate_parameters(&mut self) -> &mut Vec<TypedIdentifier> {
         &mut self.parameters
@@ -247,8 +285,22 @@ impl LocalFunctionStatement {
 
     super::impl_token_fns!(
         target = [identifier]
-        iter = [parameters, generic_parameters, tokens]
+        iter = [parameters, generic_parameters, attributes, tokens]
     );
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        self.identifier.clear_tokens();
+        for parameter in self.parameters.iter_mut() {
+            parameter.clear_tokens();
+        }
+        if let Some(generic_parameters) = &mut self.generic_parameters {
+            generic_parameters.clear_tokens();
+        }
+        for attribute in self.attributes.iter_mut() {
+            attribute.clear_tokens();
+        }
+    }
 }
 
 #[cfg(test)]
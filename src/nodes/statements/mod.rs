@@ -4,7 +4,9 @@ mod do_statement;
 mod export_type_function;
 mod function;
 mod generic_for;
+mod goto;
 mod if_statement;
+mod label;
 mod last_statement;
 mod local_assign;
 mod local_function;
@@ -20,7 +22,9 @@ pub use do_statement::*;
 pub use export_type_function::*;
 pub use function::*;
 pub use generic_for::*;
+pub use goto::*;
 pub use if_statement::*;
+pub use label::*;
 pub use last_statement::*;
 pub use local_assign::*;
 pub use local_function::*;
@@ -30,10 +34,17 @@ pub use type_declaration::*;
 pub use type_function::*;
 pub use while_statement::*;
 
-use crate::nodes::FunctionCall;
+use crate::nodes::{FunctionCall, SourcePosition};
 
 use super::impl_token_fns;
 
+/// A statement of a block.
+///
+/// When the code is parsed with its tokens, comments are stored in the trivia of the
+/// tokens. A comment written between two statements is owned by the first token of the
+/// following statement (see [`Statement::edit_first_token`]), except when it is on the
+/// same line as the last token of the previous statement: it is then a trailing comment
+/// of that token.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Statement {
     Assign(AssignStatement),
@@ -42,7 +53,9 @@ pub enum Statement {
     CompoundAssign(CompoundAssignStatement),
     Function(FunctionStatement),
     GenericFor(GenericForStatement),
+    Goto(GotoStatement),
     If(IfStatement),
+    Label(LabelStatement),
     LocalAssign(LocalAssignStatement),
     LocalFunction(LocalFunctionStatement),
     ExportTypeFunction(ExportTypeFunctionStatement),
@@ -53,6 +66,75 @@ pub enum Statement {
     TypeDeclaration(TypeDeclarationStatement),
 }
 
+impl Statement {
+    /// Returns the position of the first token of the statement. Statements created
+    /// without tokens (for example, by rules) return `None`.
+    pub fn start_position(&self) -> Option<SourcePosition> {
+        match self {
+            Self::Assign(assign) => assign
+                .get_variables()
+                .first()
+                .and_then(|variable| variable.start_position()),
+            Self::Do(do_statement) => do_statement
+                .get_tokens()
+                .and_then(|tokens| tokens.r#do.start_position()),
+            Self::Call(call) => call.get_prefix().start_position(),
+            Self::CompoundAssign(assign) => assign.get_variable().start_position(),
+            Self::Function(function) => match function.iter_attributes().next() {
+                Some(attribute) => attribute
+                    .get_token()
+                    .and_then(|token| token.start_position()),
+                None => function
+                    .get_tokens()
+                    .and_then(|tokens| tokens.function.start_position()),
+            },
+            Self::GenericFor(generic_for) => generic_for
+                .get_tokens()
+                .and_then(|tokens| tokens.r#for.start_position()),
+            Self::Goto(goto) => goto.get_token().and_then(|token| token.start_position()),
+            Self::If(if_statement) => if_statement
+                .get_tokens()
+                .and_then(|tokens| tokens.r#if.start_position()),
+            Self::Label(label) => label
+                .get_tokens()
+                .and_then(|tokens| tokens.left_colons.start_position()),
+            Self::LocalAssign(assign) => assign
+                .get_tokens()
+                .and_then(|tokens| tokens.local.start_position()),
+            Self::LocalFunction(function) => match function.iter_attributes().next() {
+                Some(attribute) => attribute
+                    .get_token()
+                    .and_then(|token| token.start_position()),
+                None => function
+                    .get_tokens()
+                    .and_then(|tokens| tokens.local.start_position()),
+            },
+            Self::ExportTypeFunction(function) => function
+                .get_tokens()
+                .and_then(|tokens| tokens.export.start_position()),
+            Self::TypeFunction(function) => function
+                .get_tokens()
+                .and_then(|tokens| tokens.r#type.start_position()),
+            Self::NumericFor(numeric_for) => numeric_for
+                .get_tokens()
+                .and_then(|tokens| tokens.r#for.start_position()),
+            Self::Repeat(repeat) => repeat
+                .get_tokens()
+                .and_then(|tokens| tokens.repeat.start_position()),
+            Self::While(while_statement) => while_statement
+                .get_tokens()
+                .and_then(|tokens| tokens.r#while.start_position()),
+            Self::TypeDeclaration(declaration) => declaration.get_tokens().and_then(|tokens| {
+                tokens
+                    .export
+                    .as_ref()
+                    .unwrap_or(&tokens.r#type)
+                    .start_position()
+            }),
+        }
+    }
+}
+
 impl From<AssignStatement> for Statement {
     fn from(assign: AssignStatement) -> Statement {
         Statement::Assign(assign)
@@ -89,12 +171,24 @@ impl From<GenericForStatement> for Statement {
     }
 }
 
+impl From<GotoStatement> for Statement {
+    fn from(goto: GotoStatement) -> Statement {
+        Statement::Goto(goto)
+    }
+}
+
 impl From<IfStatement> for Statement {
     fn from(if_statement: IfStatement) -> Statement {
         Statement::If(if_statement)
     }
 }
 
+impl From<LabelStatement> for Statement {
+    fn from(label: LabelStatement) -> Statement {
+        Statement::Label(label)
+    }
+}
+
 impl From<LocalAssignStatement> for Statement {
     fn from(assign: LocalAssignStatement) -> Statement {
         Statement::LocalAssign(assign)
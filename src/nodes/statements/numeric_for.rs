@@ -130,4 +130,9 @@ impl NumericForStatement {
     }
 
     super::impl_token_fns!(target = [identifier] iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        self.identifier.clear_tokens();
+    }
 }
@@ -69,6 +69,17 @@ impl TypeDeclarationStatement {
         self.exported = true;
     }
 
+    /// Removes the `export` keyword of the type declaration. The comments placed before
+    /// the keyword are kept before the declaration.
+    pub fn remove_export(&mut self) {
+        self.exported = false;
+        if let Some(tokens) = &mut self.tokens {
+            if let Some(export) = tokens.export.take() {
+                tokens.r#type.prepend_leading_trivia_of(export);
+            }
+        }
+    }
+
     #[inline]
     pub fn is_exported(&self) -> bool {
         self.exported
@@ -195,27 +206,52 @@ impl TypeDeclarationStatement {
         }
     }
 
-    pub(crate) fn shift_token_line(&mut self, amount: usize) {
-        self.name.shift_token_line(amount);
+    pub(crate) fn for_each_token(&mut self, callback: &mut dyn FnMut(&mut crate::nodes::Token)) {
+        self.name.for_each_token(callback);
         if let Some(tokens) = &mut self.tokens {
-            tokens.shift_token_line(amount);
+            tokens.for_each_token(callback);
         }
         if let Some(parameters) = self.generic_parameters.as_mut() {
-            parameters.shift_token_line(amount);
+            parameters.for_each_token(callback);
+
+            for parameter in parameters {
+                match parameter {
+                    GenericParameterMutRef::TypeVariable(variable) => {
+                        variable.for_each_token(callback);
+                    }
+                    GenericParameterMutRef::TypeVariableWithDefault(variable_with_default) => {
+                        variable_with_default.for_each_token(callback);
+                    }
+                    GenericParameterMutRef::GenericTypePack(_) => {}
+                    GenericParameterMutRef::GenericTypePackWithDefault(
+                        generic_pack_with_default,
+                    ) => {
+                        generic_pack_with_default.for_each_token(callback);
+                    }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.name.clear_tokens();
+        self.tokens = None;
+        if let Some(parameters) = self.generic_parameters.as_mut() {
+            parameters.clear_tokens();
 
             for parameter in parameters {
                 match parameter {
                     GenericParameterMutRef::TypeVariable(variable) => {
-                        variable.shift_token_line(amount);
+                        variable.clear_tokens();
                     }
                     GenericParameterMutRef::TypeVariableWithDefault(variable_with_default) => {
-                        variable_with_default.shift_token_line(amount);
+                        variable_with_default.clear_tokens();
                     }
                     GenericParameterMutRef::GenericTypePack(_) => {}
                     GenericParameterMutRef::GenericTypePackWithDefault(
                         generic_pack_with_default,
                     ) => {
-                        generic_pack_with_default.shift_token_line(amount);
+                        generic_pack_with_default.clear_tokens();
                     }
                 }
             }
@@ -249,6 +249,17 @@ impl TypeFunctionStatement {
         target = [identifier]
         iter = [parameters, generic_parameters, tokens]
     );
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        self.identifier.clear_tokens();
+        for parameter in self.parameters.iter_mut() {
+            parameter.clear_tokens();
+        }
+        if let Some(generic_parameters) = &mut self.generic_parameters {
+            generic_parameters.clear_tokens();
+        }
+    }
 }
 
 #[cfg(test)]
@@ -24,6 +24,128 @@ impl Position {
             line_number,
         }
     }
+
+    fn start_position(&self) -> Option<SourcePosition> {
+        match self {
+            Self::LineNumberReference {
+                start, line_number, ..
+            } => Some(SourcePosition {
+                line: *line_number,
+                offset: Some(*start),
+            }),
+            Self::LineNumber { line_number, .. } => Some(SourcePosition {
+                line: *line_number,
+                offset: None,
+            }),
+            Self::Any { .. } => None,
+        }
+    }
+
+    fn end_offset(&self) -> Option<usize> {
+        match self {
+            Self::LineNumberReference { end, .. } => Some(*end),
+            Self::LineNumber { .. } | Self::Any { .. } => None,
+        }
+    }
+}
+
+/// The location of a token in the code it was parsed from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourcePosition {
+    line: usize,
+    offset: Option<usize>,
+}
+
+impl SourcePosition {
+    /// The line number, starting at 1.
+    #[inline]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The byte offset in the original code. Only available when the token still
+    /// references the code it was parsed from.
+    #[inline]
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Computes the column (starting at 1, counted in characters) using the original code.
+    pub fn column(&self, code: &str) -> Option<usize> {
+        let offset = self.offset?;
+        let before = code.get(..offset)?;
+        let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+        Some(before[line_start..].chars().count() + 1)
+    }
+}
+
+/// The kind of a comment: `-- line` or `--[[ block ]]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommentKind {
+    Line,
+    Block,
+}
+
+/// A comment read from the trivia of a token.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comment<'a> {
+    content: &'a str,
+    kind: CommentKind,
+    position: Option<SourcePosition>,
+}
+
+impl<'a> Comment<'a> {
+    fn new(content: &'a str, position: Option<SourcePosition>) -> Self {
+        let kind = if block_comment_level(content).is_some() {
+            CommentKind::Block
+        } else {
+            CommentKind::Line
+        };
+        Self {
+            content,
+            kind,
+            position,
+        }
+    }
+
+    /// The complete comment, including the `--` prefix (and the brackets for block comments).
+    #[inline]
+    pub fn content(&self) -> &'a str {
+        self.content
+    }
+
+    /// The text of the comment, without the `--` prefix and the brackets.
+    pub fn text(&self) -> &'a str {
+        match block_comment_level(self.content) {
+            Some(level) => {
+                let start = 4 + level;
+                let end = self.content.len().saturating_sub(2 + level).max(start);
+                self.content.get(start..end).unwrap_or("")
+            }
+            None => self.content.strip_prefix("--").unwrap_or(self.content),
+        }
+    }
+
+    #[inline]
+    pub fn kind(&self) -> CommentKind {
+        self.kind
+    }
+
+    /// The position of the comment in the original code, if available.
+    #[inline]
+    pub fn position(&self) -> Option<SourcePosition> {
+        self.position
+    }
+}
+
+fn block_comment_level(content: &str) -> Option<usize> {
+    let rest = content.strip_prefix("--[")?;
+    let level = rest.chars().take_while(|c| *c == '=').count();
+    if rest[level..].starts_with('[') {
+        Some(level)
+    } else {
+        None
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -90,6 +212,24 @@ impl Trivia {
             Position::Any { .. } => None,
         }
     }
+
+    /// Returns the position where the trivia starts in the code it was parsed from.
+    pub fn start_position(&self) -> Option<SourcePosition> {
+        self.position.start_position()
+    }
+
+    /// Returns the byte offset where the trivia ends in the code it was parsed from.
+    pub(crate) fn end_offset(&self) -> Option<usize> {
+        self.position.end_offset()
+    }
+
+    /// Reads the trivia as a comment. Returns `None` if the trivia is a whitespace.
+    pub fn as_comment<'a: 'b, 'b>(&'a self, code: &'b str) -> Option<Comment<'b>> {
+        match self.kind {
+            TriviaKind::Comment => Some(Comment::new(self.read(code), self.start_position())),
+            TriviaKind::Whitespace => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -159,6 +299,19 @@ impl Token {
         self.trailing_trivia.push(trivia);
     }
 
+    /// Moves the leading trivia of the given token before the leading trivia of this token.
+    pub(crate) fn prepend_leading_trivia_of(&mut self, token: Token) {
+        self.prepend_leading_trivia(token.leading_trivia);
+    }
+
+    pub(crate) fn prepend_leading_trivia(&mut self, trivia: Vec<Trivia>) {
+        self.leading_trivia.splice(0..0, trivia);
+    }
+
+    pub(crate) fn take_leading_trivia(&mut self) -> Vec<Trivia> {
+        std::mem::take(&mut self.leading_trivia)
+    }
+
     #[inline]
     pub fn iter_leading_trivia(&self) -> impl Iterator<Item = &Trivia> {
         self.leading_trivia.iter()
@@ -186,6 +339,106 @@ impl Token {
         }
     }
 
+    /// Returns the position where the token starts in the code it was parsed from. Tokens
+    /// created without any position return `None`.
+    pub fn start_position(&self) -> Option<SourcePosition> {
+        self.position.start_position()
+    }
+
+    /// Returns the byte offset where the token ends in the code it was parsed from, when
+    /// the token still references that code.
+    pub(crate) fn end_offset(&self) -> Option<usize> {
+        self.position.end_offset()
+    }
+
+    /// Iterates on the comments placed before the token. When parsing, the comments
+    /// that are on the lines preceding a token are attached to that token.
+    pub fn iter_leading_comments<'a>(
+        &'a self,
+        code: &'a str,
+    ) -> impl Iterator<Item = Comment<'a>> + 'a {
+        self.leading_trivia
+            .iter()
+            .filter_map(move |trivia| trivia.as_comment(code))
+    }
+
+    /// Iterates on the comments placed after the token. When parsing, only the
+    /// comments that are on the same line as the token are attached after it.
+    pub fn iter_trailing_comments<'a>(
+        &'a self,
+        code: &'a str,
+    ) -> impl Iterator<Item = Comment<'a>> + 'a {
+        self.trailing_trivia
+            .iter()
+            .filter_map(move |trivia| trivia.as_comment(code))
+    }
+
+    /// Removes the leading comment at the given index (only comments are counted), with
+    /// the whitespace that follows it. The removed comment is returned.
+    pub fn remove_leading_comment(&mut self, index: usize) -> Option<Trivia> {
+        let position = find_comment(&self.leading_trivia, index)?;
+        let comment = self.leading_trivia.remove(position);
+
+        if self
+            .leading_trivia
+            .get(position)
+            .filter(|trivia| trivia.kind == TriviaKind::Whitespace)
+            .is_some()
+        {
+            self.leading_trivia.remove(position);
+        }
+
+        Some(comment)
+    }
+
+    /// Removes the trailing comment at the given index (only comments are counted), with
+    /// the whitespace that separates it from the token. The removed comment is returned.
+    pub fn remove_trailing_comment(&mut self, index: usize) -> Option<Trivia> {
+        let mut position = find_comment(&self.trailing_trivia, index)?;
+
+        if position > 0 && self.trailing_trivia[position - 1].kind == TriviaKind::Whitespace {
+            self.trailing_trivia.remove(position - 1);
+            position -= 1;
+        }
+
+        Some(self.trailing_trivia.remove(position))
+    }
+
+    /// Adds a comment before the token. The content must be a complete comment, such
+    /// as `--comment` or `--[[ comment ]]`. A line break is inserted after line comments.
+    ///
+    /// Comments are stored in the trivia of tokens, which is only written by the
+    /// [`TokenBasedLuaGenerator`](crate::generator::TokenBasedLuaGenerator) (used by the
+    /// `retain_lines` generator). The dense and readable generators do not write any
+    /// comment, so the comments added to a token are lost with them.
+    pub fn push_leading_comment<IntoCowStr: Into<Cow<'static, str>>>(
+        &mut self,
+        content: IntoCowStr,
+    ) {
+        let content = content.into();
+        let is_line_comment = block_comment_level(&content).is_none();
+        self.leading_trivia
+            .push(TriviaKind::Comment.with_content(content));
+        if is_line_comment {
+            self.leading_trivia
+                .push(TriviaKind::Whitespace.with_content("\n"));
+        }
+    }
+
+    /// Adds a comment after the token, separated by a space. The content must be a
+    /// complete comment, such as `--comment` or `--[[ comment ]]`. Like leading comments,
+    /// it is only written by the
+    /// [`TokenBasedLuaGenerator`](crate::generator::TokenBasedLuaGenerator).
+    pub fn push_trailing_comment<IntoCowStr: Into<Cow<'static, str>>>(
+        &mut self,
+        content: IntoCowStr,
+    ) {
+        self.trailing_trivia
+            .push(TriviaKind::Whitespace.with_content(" "));
+        self.trailing_trivia
+            .push(TriviaKind::Comment.with_content(content));
+    }
+
     pub fn replace_with_content<IntoCowStr: Into<Cow<'static, str>>>(
         &mut self,
         content: IntoCowStr,
@@ -264,12 +517,31 @@ impl Token {
     }
 
     pub(crate) fn shift_token_line(&mut self, amount: usize) {
+        self.map_line_number(|line_number| line_number + amount);
+    }
+
+    /// Replaces the line number of the token (when it has one) with the result of the
+    /// given function.
+    pub(crate) fn map_line_number(&mut self, map: impl FnOnce(usize) -> usize) {
         match &mut self.position {
             Position::LineNumberReference { line_number, .. }
-            | Position::LineNumber { line_number, .. } => *line_number += amount,
+            | Position::LineNumber { line_number, .. } => *line_number = map(*line_number),
             Position::Any { .. } => {}
         }
     }
+
+    pub(crate) fn for_each_token(&mut self, callback: &mut dyn FnMut(&mut Token)) {
+        callback(self);
+    }
+}
+
+fn find_comment(trivia: &[Trivia], index: usize) -> Option<usize> {
+    trivia
+        .iter()
+        .enumerate()
+        .filter(|(_, trivia)| trivia.kind == TriviaKind::Comment)
+        .nth(index)
+        .map(|(position, _)| position)
 }
 
 #[cfg(test)]
@@ -290,4 +562,272 @@ mod test {
 
         assert_eq!("true", token.read(""));
     }
+
+    #[test]
+    fn start_position_of_line_number_reference_token() {
+        let code = "local a\nreturn true";
+        let token = Token::new_with_line(15, 19, 2);
+
+        let position = token.start_position().unwrap();
+
+        assert_eq!(position.line(), 2);
+        assert_eq!(position.offset(), Some(15));
+        assert_eq!(position.column(code), Some(8));
+    }
+
+    #[test]
+    fn start_position_of_line_number_token() {
+        let token = Token::from_position(Position::line_number("true", 4));
+
+        let position = token.start_position().unwrap();
+
+        assert_eq!(position.line(), 4);
+        assert_eq!(position.offset(), None);
+        assert_eq!(position.column("return true"), None);
+    }
+
+    #[test]
+    fn start_position_of_any_position_token() {
+        assert_eq!(Token::from_content("true").start_position(), None);
+    }
+
+    mod comments {
+        use super::*;
+        use crate::generator::{
+            DenseLuaGenerator, LuaGenerator, ReadableLuaGenerator, TokenBasedLuaGenerator,
+        };
+        use crate::nodes::{Arguments, Block, Expression, Statement};
+        use crate::Parser;
+
+        const CODE: &str = "do\nend -- after end\nprint(a, --[[ between ]] b)\n";
+
+        fn parse() -> Block {
+            Parser::default()
+                .preserve_tokens()
+                .parse(CODE)
+                .expect("code should parse")
+        }
+
+        fn generate(block: &Block) -> String {
+            let mut generator = TokenBasedLuaGenerator::new(CODE);
+            generator.write_block(block);
+            generator.into_string()
+        }
+
+        fn end_token(block: &mut Block) -> &mut Token {
+            match block.first_mut_statement() {
+                Some(Statement::Do(do_statement)) => &mut do_statement.mutate_tokens().unwrap().end,
+                _ => panic!("first statement should be a do statement"),
+            }
+        }
+
+        fn print_argument(block: &mut Block, index: usize) -> &mut Token {
+            match block.iter_mut_statements().nth(1) {
+                Some(Statement::Call(call)) => match call.mutate_arguments() {
+                    Arguments::Tuple(tuple) => match tuple.iter_mut_values().nth(index) {
+                        Some(Expression::Identifier(identifier)) => {
+                            identifier.mutate_token().unwrap()
+                        }
+                        _ => panic!("argument should be an identifier"),
+                    },
+                    _ => panic!("arguments should be a tuple"),
+                },
+                _ => panic!("second statement should be a call"),
+            }
+        }
+
+        #[test]
+        fn enumerate_comment_after_end() {
+            let mut block = parse();
+
+            let comments: Vec<_> = end_token(&mut block).iter_trailing_comments(CODE).collect();
+
+            assert_eq!(comments.len(), 1);
+            let comment = &comments[0];
+            assert_eq!(comment.content(), "-- after end");
+            assert_eq!(comment.text(), " after end");
+            assert_eq!(comment.kind(), CommentKind::Line);
+            let position = comment.position().unwrap();
+            assert_eq!(position.line(), 2);
+            assert_eq!(position.column(CODE), Some(5));
+        }
+
+        #[test]
+        fn enumerate_comment_between_arguments() {
+            let block = parse();
+
+            let tokens = match block.iter_statements().nth(1) {
+                Some(Statement::Call(call)) => match call.get_arguments() {
+                    Arguments::Tuple(tuple) => tuple.get_tokens().unwrap().clone(),
+                    _ => panic!("arguments should be a tuple"),
+                },
+                _ => panic!("second statement should be a call"),
+            };
+
+            let comments: Vec<_> = tokens.commas[0].iter_trailing_comments(CODE).collect();
+
+            assert_eq!(comments.len(), 1);
+            assert_eq!(comments[0].text(), " between ");
+            assert_eq!(comments[0].kind(), CommentKind::Block);
+            assert_eq!(comments[0].position().unwrap().line(), 3);
+        }
+
+        #[test]
+        fn remove_comment_after_end() {
+            let mut block = parse();
+
+            let removed = end_token(&mut block).remove_trailing_comment(0);
+
+            assert!(removed.is_some());
+            assert_eq!(
+                end_token(&mut block).iter_trailing_comments(CODE).count(),
+                0
+            );
+            assert_eq!(generate(&block), CODE.replace(" -- after end", ""));
+        }
+
+        #[test]
+        fn remove_missing_comment() {
+            let mut block = parse();
+
+            assert_eq!(end_token(&mut block).remove_trailing_comment(1), None);
+            assert_eq!(end_token(&mut block).remove_leading_comment(0), None);
+        }
+
+        #[test]
+        fn push_leading_comment_to_first_token() {
+            let mut block = parse();
+
+            match block.first_mut_statement() {
+                Some(Statement::Do(do_statement)) => do_statement
+                    .mutate_tokens()
+                    .unwrap()
+                    .r#do
+                    .push_leading_comment("--first"),
+                _ => panic!("first statement should be a do statement"),
+            }
+
+            assert_eq!(generate(&block), format!("--first\n{}", CODE));
+        }
+
+        #[test]
+        fn push_trailing_comment_to_argument() {
+            let mut block = parse();
+
+            print_argument(&mut block, 0).push_trailing_comment("--[[ new ]]");
+
+            assert_eq!(
+                generate(&block),
+                CODE.replace("print(a,", "print(a --[[ new ]],")
+            );
+        }
+
+        #[test]
+        fn remove_leading_comment_with_its_line_break() {
+            let code = "-- header\nprint(a)\n";
+            let mut block = Parser::default()
+                .preserve_tokens()
+                .parse(code)
+                .expect("code should parse");
+
+            let removed = block
+                .first_mut_statement()
+                .unwrap()
+                .edit_first_token(|token| {
+                    assert!(token.remove_leading_comment(0).is_some());
+                });
+
+            assert!(removed);
+            let mut generator = TokenBasedLuaGenerator::new(code);
+            generator.write_block(&block);
+            assert_eq!(generator.into_string(), "print(a)\n");
+        }
+
+        #[test]
+        fn comment_between_statements_is_owned_by_next_statement() {
+            let code = "local a = 1\n-- about b\nlocal b = 2\n";
+            let mut block = Parser::default()
+                .preserve_tokens()
+                .parse(code)
+                .expect("code should parse");
+
+            let mut comments = Vec::new();
+            block
+                .iter_mut_statements()
+                .nth(1)
+                .unwrap()
+                .edit_first_token(|token| {
+                    comments.extend(
+                        token
+                            .iter_leading_comments(code)
+                            .map(|comment| comment.content().to_owned()),
+                    );
+                });
+
+            assert_eq!(comments, vec!["-- about b".to_owned()]);
+        }
+
+        #[test]
+        fn push_leading_comment_to_statement() {
+            let mut block = parse();
+
+            assert!(block
+                .iter_mut_statements()
+                .nth(1)
+                .unwrap()
+                .push_leading_comment("--call"));
+
+            assert_eq!(
+                generate(&block),
+                CODE.replace("\nprint(", "\n--call\nprint(")
+            );
+        }
+
+        #[test]
+        fn push_leading_comment_to_statement_without_tokens() {
+            let mut statement: Statement = crate::nodes::DoStatement::default().into();
+
+            assert!(!statement.push_leading_comment("--comment"));
+        }
+
+        #[test]
+        fn dense_generator_does_not_write_comments() {
+            let mut block = parse();
+            print_argument(&mut block, 0).push_trailing_comment("--[[ new ]]");
+
+            let mut generator = DenseLuaGenerator::new(80);
+            generator.write_block(&block);
+            let output = generator.into_string();
+
+            assert!(!output.contains("--"), "unexpected comment in: {}", output);
+        }
+
+        #[test]
+        fn readable_generator_does_not_write_comments() {
+            let mut block = parse();
+            print_argument(&mut block, 0).push_trailing_comment("--[[ new ]]");
+
+            let mut generator = ReadableLuaGenerator::new(80);
+            generator.write_block(&block);
+            let output = generator.into_string();
+
+            assert!(!output.contains("--"), "unexpected comment in: {}", output);
+        }
+
+        #[test]
+        fn read_block_comment_text_with_equal_signs() {
+            let trivia = TriviaKind::Comment.with_content("--[==[ content ]==]");
+            let comment = trivia.as_comment("").unwrap();
+
+            assert_eq!(comment.kind(), CommentKind::Block);
+            assert_eq!(comment.text(), " content ");
+        }
+
+        #[test]
+        fn whitespace_is_not_a_comment() {
+            let trivia = TriviaKind::Whitespace.with_content(" ");
+
+            assert_eq!(trivia.as_comment(""), None);
+        }
+    }
 }
@@ -65,6 +65,11 @@ impl TypedIdentifier {
         target = [name]
         iter = [token]
     );
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+        self.name.clear_tokens();
+    }
 }
 
 impl<IntoIdentifier: Into<Identifier>> From<IntoIdentifier> for TypedIdentifier {
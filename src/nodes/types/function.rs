@@ -69,6 +69,13 @@ impl FunctionArgumentType {
     }
 
     super::impl_token_fns!(iter = [name, token]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+        if let Some(name) = &mut self.name {
+            name.clear_tokens();
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -245,6 +252,16 @@ impl FunctionType {
     }
 
     super::impl_token_fns!(iter = [tokens, generic_parameters, arguments]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        if let Some(generic_parameters) = &mut self.generic_parameters {
+            generic_parameters.clear_tokens();
+        }
+        for argument in self.arguments.iter_mut() {
+            argument.clear_tokens();
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
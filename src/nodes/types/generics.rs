@@ -48,6 +48,11 @@ impl GenericTypePack {
         target = [name]
         iter = [token]
     );
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+        self.name.clear_tokens();
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -139,6 +144,16 @@ impl GenericParameters {
     }
 
     super::impl_token_fns!(iter = [type_variables, generic_type_packs, tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        for type_variable in self.type_variables.iter_mut() {
+            type_variable.clear_tokens();
+        }
+        for generic_type_pack in self.generic_type_packs.iter_mut() {
+            generic_type_pack.clear_tokens();
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -236,6 +251,10 @@ impl GenericTypePackWithDefault {
     }
 
     super::impl_token_fns!(iter = [token]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -294,6 +313,11 @@ impl TypeVariableWithDefault {
         target = [variable]
         iter = [token]
     );
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+        self.variable.clear_tokens();
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -460,6 +484,10 @@ impl GenericParametersWithDefaults {
     }
 
     super::impl_token_fns!(iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
 
 pub enum GenericParameter {
@@ -109,6 +109,10 @@ impl IntersectionType {
     }
 
     super::impl_token_fns!(iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
 
 impl From<Vec<Type>> for IntersectionType {
@@ -63,4 +63,8 @@ impl StringType {
     }
 
     super::impl_token_fns!(target = [value]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.value.clear_tokens();
+    }
 }
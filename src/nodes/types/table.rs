@@ -54,6 +54,10 @@ impl TableIndexerType {
     }
 
     super::impl_token_fns!(iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -119,6 +123,11 @@ impl TablePropertyType {
     }
 
     super::impl_token_fns!(target = [property] iter = [token]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+        self.property.clear_tokens();
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -173,6 +182,10 @@ impl TableLiteralPropertyType {
     }
 
     super::impl_token_fns!(target = [string] iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -207,11 +220,19 @@ impl TableEntryType {
         }
     }
 
-    pub(crate) fn shift_token_line(&mut self, amount: usize) {
+    pub(crate) fn for_each_token(&mut self, callback: &mut dyn FnMut(&mut crate::nodes::Token)) {
         match self {
-            TableEntryType::Property(property) => property.shift_token_line(amount),
-            TableEntryType::Literal(literal) => literal.shift_token_line(amount),
-            TableEntryType::Indexer(indexer) => indexer.shift_token_line(amount),
+            TableEntryType::Property(property) => property.for_each_token(callback),
+            TableEntryType::Literal(literal) => literal.for_each_token(callback),
+            TableEntryType::Indexer(indexer) => indexer.for_each_token(callback),
+        }
+    }
+
+    pub(crate) fn clear_tokens(&mut self) {
+        match self {
+            TableEntryType::Property(property) => property.clear_tokens(),
+            TableEntryType::Literal(literal) => literal.clear_tokens(),
+            TableEntryType::Indexer(indexer) => indexer.clear_tokens(),
         }
     }
 
@@ -357,6 +378,13 @@ impl TableType {
     }
 
     super::impl_token_fns!(iter = [entries, tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+        for entry in self.entries.iter_mut() {
+            entry.clear_tokens();
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
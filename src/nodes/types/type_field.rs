@@ -50,4 +50,9 @@ impl TypeField {
     }
 
     super::impl_token_fns!(target = [namespace] iter = [token]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.token = None;
+        self.namespace.clear_tokens();
+    }
 }
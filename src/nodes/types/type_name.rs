@@ -65,6 +65,13 @@ impl TypeName {
     }
 
     super::impl_token_fns!(target = [type_name] iter = [type_parameters]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.type_name.clear_tokens();
+        if let Some(type_parameters) = &mut self.type_parameters {
+            type_parameters.clear_tokens();
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -126,6 +133,10 @@ impl TypeParameters {
     }
 
     super::impl_token_fns!(iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
 
 impl FromIterator<TypeParameter> for TypeParameters {
@@ -86,6 +86,10 @@ impl TypePack {
     }
 
     super::impl_token_fns!(iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
 
 impl FromIterator<Type> for TypePack {
@@ -104,6 +104,10 @@ impl UnionType {
     }
 
     super::impl_token_fns!(iter = [tokens]);
+
+    pub(crate) fn clear_tokens(&mut self) {
+        self.tokens = None;
+    }
 }
 
 impl From<Vec<Type>> for UnionType {
@@ -1,4 +1,4 @@
-use crate::nodes::{FieldExpression, Identifier, IndexExpression};
+use crate::nodes::{FieldExpression, Identifier, IndexExpression, SourcePosition, Token};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Variable {
@@ -11,6 +11,15 @@ impl Variable {
     pub fn new<S: Into<String>>(name: S) -> Self {
         Self::Identifier(Identifier::new(name))
     }
+
+    /// Returns the position of the first token of the variable, if it has tokens.
+    pub fn start_position(&self) -> Option<SourcePosition> {
+        match self {
+            Self::Identifier(identifier) => identifier.get_token().and_then(Token::start_position),
+            Self::Field(field) => field.get_prefix().start_position(),
+            Self::Index(index) => index.get_prefix().start_position(),
+        }
+    }
 }
 
 impl From<Identifier> for Variable {
@@ -11,6 +11,7 @@ use crate::{
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Parser {
     hold_token_data: bool,
+    recover_errors: bool,
 }
 
 impl Parser {
@@ -21,16 +22,24 @@ impl Parser {
             "full-moon parsing done in {}",
             full_moon_parse_timer.duration_label()
         );
-        parse_result.map_err(ParserError::parsing).and_then(|ast| {
-            log::trace!("start converting full-moon AST");
-            let conversion_timer = Timer::now();
-            let block = self.convert_ast(ast).map_err(ParserError::converting);
-            log::trace!(
-                " ⨽ completed AST conversion in {}",
-                conversion_timer.duration_label()
-            );
-            block
-        })
+        parse_result
+            .map_err(|errors| {
+                if self.recover_errors {
+                    ParserError::recovered(recover_syntax_errors(code, &errors))
+                } else {
+                    ParserError::parsing(errors, code)
+                }
+            })
+            .and_then(|ast| {
+                log::trace!("start converting full-moon AST");
+                let conversion_timer = Timer::now();
+                let block = self.convert_ast(ast).map_err(ParserError::converting);
+                log::trace!(
+                    " ⨽ completed AST conversion in {}",
+                    conversion_timer.duration_label()
+                );
+                block
+            })
     }
 
     pub fn preserve_tokens(mut self) -> Self {
@@ -38,6 +47,17 @@ impl Parser {
         self
     }
 
+    /// When the code can not be parsed, keeps looking for other syntax errors after the
+    /// first one so that they can all be reported at once (see
+    /// [`ParserError::iter_syntax_errors`]). After each error, the parser skips code until
+    /// the next line that starts with a statement keyword (like `local` or `function`) or
+    /// a keyword that closes a block (like `end`), then parses the code again. At most 20
+    /// syntax errors are reported.
+    pub fn recover_errors(mut self) -> Self {
+        self.recover_errors = true;
+        self
+    }
+
     pub(crate) fn is_preserving_tokens(&self) -> bool {
         self.hold_token_data
     }
@@ -48,9 +68,229 @@ impl Parser {
     }
 }
 
+/// Renders the given line of the code with a caret (`^`) under the given column, to show
+/// where an error is located. Lines and columns start at 1. Returns `None` if the line does
+/// not exist in the code.
+///
+/// ```
+/// # use darklua_core::render_code_frame;
+/// let code = "local a = 1\nlocal b = = 2\n";
+///
+/// assert_eq!(
+///     render_code_frame(code, 2, 11).unwrap(),
+///     "2 | local b = = 2\n  |           ^"
+/// );
+/// ```
+pub fn render_code_frame(code: &str, line: usize, column: usize) -> Option<String> {
+    let content = code.lines().nth(line.checked_sub(1)?)?;
+    let line_number = line.to_string();
+
+    // keep tabs in the caret line so that the caret stays aligned with the code
+    let padding: String = content
+        .chars()
+        .take(column.saturating_sub(1))
+        .map(|character| if character == '\t' { '\t' } else { ' ' })
+        .collect();
+
+    Some(format!(
+        "{} | {}\n{} | {}^",
+        line_number,
+        content,
+        " ".repeat(line_number.len()),
+        padding
+    ))
+}
+
+/// Keywords that begin a statement, where parsing can start again after a syntax error.
+const STATEMENT_KEYWORDS: [&str; 12] = [
+    "break", "continue", "do", "export", "for", "function", "goto", "if", "local", "repeat",
+    "return", "while",
+];
+
+/// Keywords that close a block, which are kept after a syntax error so that the blocks
+/// they close are still complete.
+const CLOSING_KEYWORDS: [&str; 4] = ["else", "elseif", "end", "until"];
+
+fn starts_with_keyword(line: &str, keywords: &[&str]) -> bool {
+    let line = line.trim_start();
+    keywords.iter().any(|keyword| {
+        line.strip_prefix(keyword).map_or(false, |rest| {
+            !rest.starts_with(|character: char| character.is_alphanumeric() || character == '_')
+        })
+    })
+}
+
+/// Returns the byte offset of the start of each line.
+fn line_starts(code: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(code.match_indices('\n').map(|(index, _)| index + 1))
+        .collect()
+}
+
+/// Finds the code to skip after a syntax error starting at the given byte offset. When
+/// the error is on the first token of a line, the statement that is not complete is on
+/// the previous lines, so the previous line is skipped. Otherwise, the code is skipped from
+/// the start of the line of the error until the next line that starts with a keyword.
+fn find_skipped_range(code: &str, error_start: usize) -> std::ops::Range<usize> {
+    let starts = line_starts(code);
+    let line_index = starts
+        .iter()
+        .rposition(|start| *start <= error_start)
+        .unwrap_or_default();
+    let line_start = starts[line_index];
+    let line_end = |index: usize| starts.get(index + 1).copied().unwrap_or(code.len());
+
+    let is_first_token = code[line_start..error_start].trim().is_empty();
+
+    if is_first_token {
+        let previous_line = (0..line_index)
+            .rev()
+            .find(|index| !code[starts[*index]..line_end(*index)].trim().is_empty());
+
+        if let Some(previous_line) = previous_line {
+            return starts[previous_line]..line_start;
+        }
+    }
+
+    let end = (line_index + 1..starts.len())
+        .map(|index| starts[index])
+        .find(|start| {
+            let line = &code[*start..];
+            starts_with_keyword(line, &STATEMENT_KEYWORDS)
+                || starts_with_keyword(line, &CLOSING_KEYWORDS)
+        })
+        .unwrap_or(code.len());
+
+    line_start..end
+}
+
+/// The maximum number of syntax errors found by [`Parser::recover_errors`]. Each error
+/// after the first one parses the whole code again, so without a limit, a large file full
+/// of errors would take a time quadratic in its size.
+const MAX_RECOVERED_SYNTAX_ERRORS: usize = 20;
+
+/// Parses the code again after each syntax error, skipping the code around the error, to
+/// find the errors that do not depend on each other. It stops after
+/// [`MAX_RECOVERED_SYNTAX_ERRORS`] errors.
+fn recover_syntax_errors(code: &str, first_errors: &[full_moon::Error]) -> Vec<SyntaxError> {
+    let mut syntax_errors: Vec<SyntaxError> = first_errors
+        .first()
+        .map(|error| SyntaxError::new(error, code))
+        .into_iter()
+        .collect();
+
+    let mut recovered_code = code.to_owned();
+    let mut error_start = match first_errors.first() {
+        Some(error) => error.range().0.bytes(),
+        None => return syntax_errors,
+    };
+
+    // each attempt finds one more error or stops
+    while syntax_errors.len() < MAX_RECOVERED_SYNTAX_ERRORS {
+        let skipped = find_skipped_range(&recovered_code, error_start);
+
+        if skipped.is_empty() {
+            break;
+        }
+
+        // replace the skipped code with spaces so that the positions of the following
+        // errors do not change
+        let blank: String = recovered_code[skipped.clone()]
+            .bytes()
+            .map(|byte| if byte == b'\n' { '\n' } else { ' ' })
+            .collect();
+        recovered_code.replace_range(skipped.clone(), &blank);
+
+        let error = match full_moon::parse(&recovered_code) {
+            Ok(_) => break,
+            Err(errors) => match errors.into_iter().next() {
+                Some(error) => error,
+                None => break,
+            },
+        };
+
+        let next_start = error.range().0.bytes();
+
+        // an error before the skipped code or at the same place as the previous error
+        // means that skipping code did not help
+        if next_start < skipped.start || next_start == error_start {
+            break;
+        }
+
+        syntax_errors.push(SyntaxError::new(&error, code));
+        error_start = next_start;
+    }
+
+    syntax_errors
+}
+
+/// A syntax error found while parsing code, with its location.
+#[derive(Clone, Debug)]
+pub struct SyntaxError {
+    message: String,
+    line: usize,
+    column: usize,
+    token: String,
+    code_frame: Option<String>,
+}
+
+impl SyntaxError {
+    fn new(error: &full_moon::Error, code: &str) -> Self {
+        let (start, end) = error.range();
+        let line = start.line();
+        let column = start.character();
+
+        Self {
+            message: error.to_string(),
+            line,
+            column,
+            token: code
+                .get(start.bytes()..end.bytes())
+                .unwrap_or_default()
+                .to_owned(),
+            code_frame: render_code_frame(code, line, column),
+        }
+    }
+
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    #[inline]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column of the error, starting at 1.
+    #[inline]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The code of the token where the error starts.
+    #[inline]
+    pub fn token_text(&self) -> &str {
+        &self.token
+    }
+
+    /// The line of code of the error, with a caret under the error (see
+    /// [`render_code_frame`]).
+    #[inline]
+    pub fn code_frame(&self) -> Option<&str> {
+        self.code_frame.as_deref()
+    }
+}
+
 #[derive(Clone, Debug)]
 enum ParserErrorKind {
-    Parsing(Vec<full_moon::Error>),
+    Parsing {
+        errors: Vec<full_moon::Error>,
+        location: Option<SyntaxError>,
+    },
+    Recovered {
+        syntax_errors: Vec<SyntaxError>,
+    },
     Converting(ConvertError),
 }
 
@@ -60,9 +300,16 @@ pub struct ParserError {
 }
 
 impl ParserError {
-    fn parsing(err: Vec<full_moon::Error>) -> Self {
+    fn parsing(errors: Vec<full_moon::Error>, code: &str) -> Self {
+        let location = errors.first().map(|error| SyntaxError::new(error, code));
+        Self {
+            kind: ParserErrorKind::Parsing { errors, location }.into(),
+        }
+    }
+
+    fn recovered(syntax_errors: Vec<SyntaxError>) -> Self {
         Self {
-            kind: ParserErrorKind::Parsing(err).into(),
+            kind: ParserErrorKind::Recovered { syntax_errors }.into(),
         }
     }
 
@@ -71,14 +318,72 @@ impl ParserError {
             kind: ParserErrorKind::Converting(err).into(),
         }
     }
+
+    fn location(&self) -> Option<&SyntaxError> {
+        self.iter_syntax_errors().next()
+    }
+
+    /// Iterates over the syntax errors that were found. Without
+    /// [`Parser::recover_errors`], only the first syntax error is available.
+    pub fn iter_syntax_errors(&self) -> impl Iterator<Item = &SyntaxError> {
+        let syntax_errors: &[SyntaxError] = match &*self.kind {
+            ParserErrorKind::Parsing { location, .. } => location
+                .as_ref()
+                .map(std::slice::from_ref)
+                .unwrap_or_default(),
+            ParserErrorKind::Recovered { syntax_errors } => syntax_errors,
+            ParserErrorKind::Converting(_) => &[],
+        };
+        syntax_errors.iter()
+    }
+
+    /// The line where the first syntax error starts.
+    pub fn line(&self) -> Option<usize> {
+        self.location().map(SyntaxError::line)
+    }
+
+    /// The column (starting at 1) where the first syntax error starts.
+    pub fn column(&self) -> Option<usize> {
+        self.location().map(SyntaxError::column)
+    }
+
+    /// The code of the token where the first syntax error starts.
+    pub fn token_text(&self) -> Option<&str> {
+        self.location().map(SyntaxError::token_text)
+    }
+
+    /// The line of code where the first syntax error starts, with a caret under the
+    /// error (see [`render_code_frame`]).
+    pub fn code_frame(&self) -> Option<&str> {
+        self.location().and_then(SyntaxError::code_frame)
+    }
 }
 
 impl fmt::Display for ParserError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &*self.kind {
-            ParserErrorKind::Parsing(errors) => {
-                for err in errors {
+            ParserErrorKind::Parsing { errors, location } => {
+                for (i, err) in errors.iter().enumerate() {
                     writeln!(f, "{}", err)?;
+
+                    if i == 0 {
+                        if let Some(code_frame) = location
+                            .as_ref()
+                            .and_then(|location| location.code_frame.as_ref())
+                        {
+                            writeln!(f, "{}", code_frame)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            ParserErrorKind::Recovered { syntax_errors } => {
+                for syntax_error in syntax_errors {
+                    writeln!(f, "{}", syntax_error.message)?;
+
+                    if let Some(code_frame) = &syntax_error.code_frame {
+                        writeln!(f, "{}", code_frame)?;
+                    }
                 }
                 Ok(())
             }
@@ -87,6 +392,8 @@ impl fmt::Display for ParserError {
     }
 }
 
+impl std::error::Error for ParserError {}
+
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
@@ -95,6 +402,126 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn render_code_frame_on_first_line() {
+        pretty_assertions::assert_eq!(
+            render_code_frame("returnone", 1, 10).unwrap(),
+            "1 | returnone\n  |          ^"
+        );
+    }
+
+    #[test]
+    fn render_code_frame_keeps_tabs() {
+        pretty_assertions::assert_eq!(
+            render_code_frame("do\n\tlocal = 1\nend", 2, 8).unwrap(),
+            "2 | \tlocal = 1\n  | \t      ^"
+        );
+    }
+
+    #[test]
+    fn render_code_frame_with_two_digits_line() {
+        let code = "\n".repeat(11) + "print(";
+
+        pretty_assertions::assert_eq!(
+            render_code_frame(&code, 12, 7).unwrap(),
+            "12 | print(\n   |       ^"
+        );
+    }
+
+    #[test]
+    fn render_code_frame_on_missing_line() {
+        assert_eq!(render_code_frame("return", 3, 1), None);
+        assert_eq!(render_code_frame("return", 0, 1), None);
+    }
+
+    #[test]
+    fn parser_error_has_location() {
+        let code = "local a = 1\nlocal b = = 2\n";
+        let error = Parser::default().parse(code).unwrap_err();
+
+        assert_eq!(error.line(), Some(2));
+        assert_eq!(error.column(), Some(11));
+        assert_eq!(error.token_text(), Some("="));
+        pretty_assertions::assert_eq!(
+            error.code_frame(),
+            Some("2 | local b = = 2\n  |           ^")
+        );
+        assert!(error
+            .to_string()
+            .contains("2 | local b = = 2\n  |           ^\n"));
+    }
+
+    const CODE_WITH_THREE_ERRORS: &str = "local a = = 1
+print('ok')
+local function f()
+    return 1 +
+end
+local b = { 1, 2
+local c = 3
+";
+
+    fn syntax_error_lines(error: &ParserError) -> Vec<usize> {
+        error.iter_syntax_errors().map(SyntaxError::line).collect()
+    }
+
+    #[test]
+    fn parser_error_without_recovery_has_first_error() {
+        let error = Parser::default().parse(CODE_WITH_THREE_ERRORS).unwrap_err();
+
+        assert_eq!(syntax_error_lines(&error), vec![1]);
+    }
+
+    #[test]
+    fn recover_errors_finds_independent_errors() {
+        let error = Parser::default()
+            .recover_errors()
+            .parse(CODE_WITH_THREE_ERRORS)
+            .unwrap_err();
+
+        assert_eq!(syntax_error_lines(&error), vec![1, 5, 7]);
+        assert_eq!(error.line(), Some(1));
+        assert_eq!(error.column(), Some(11));
+
+        let message = error.to_string();
+        assert!(message.contains("1 | local a = = 1\n"));
+        assert!(message.contains("5 | end\n"));
+        assert!(message.contains("7 | local c = 3\n"));
+    }
+
+    #[test]
+    fn recover_errors_in_nested_blocks() {
+        let error = Parser::default()
+            .recover_errors()
+            .parse("if a then\n    local b = = 1\n    print(b)\nelse\n    call(\nend\n")
+            .unwrap_err();
+
+        assert_eq!(syntax_error_lines(&error), vec![2, 6]);
+    }
+
+    #[test]
+    fn recover_errors_stops_after_maximum_errors() {
+        let code = "local a = = 1\nprint(a)\n".repeat(5_000);
+
+        let error = Parser::default().recover_errors().parse(&code).unwrap_err();
+
+        assert_eq!(
+            syntax_error_lines(&error),
+            (0..MAX_RECOVERED_SYNTAX_ERRORS)
+                .map(|index| index * 2 + 1)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn recover_errors_does_not_change_valid_code() {
+        let code = "local a = 1\nreturn a";
+
+        pretty_assertions::assert_eq!(
+            Parser::default().recover_errors().parse(code).unwrap(),
+            Parser::default().parse(code).unwrap()
+        );
+    }
+
     macro_rules! test_parse {
         ($($name:ident($input:literal) => $value:expr),* $(,)?) => {
             $(
@@ -196,6 +623,14 @@ mod test {
             LastStatement::new_continue(),
             true,
         ),
+        goto_label("goto done") => GotoStatement::new("done"),
+        label("::done::") => LabelStatement::new("done"),
+        goto_label_in_loop("while true do goto continue ::continue:: end") => WhileStatement::new(
+            Block::default()
+                .with_statement(GotoStatement::new("continue"))
+                .with_statement(LabelStatement::new("continue")),
+            true,
+        ),
         local_assignment_with_no_values("local var") => LocalAssignStatement::from_variable("var"),
         multiple_local_assignment_with_no_values("local foo, bar") => LocalAssignStatement::from_variable("foo")
             .with_variable("bar"),
@@ -2053,6 +2488,13 @@ mod test {
                 repeat: spaced_token(0, 6),
                 until: spaced_token(7, 12),
             }),
+            goto_label("goto done") => GotoStatement::new(create_identifier("done", 5, 0))
+                .with_token(spaced_token(0, 4)),
+            label("::done::") => LabelStatement::new(create_identifier("done", 2, 0))
+                .with_tokens(LabelTokens {
+                    left_colons: token_at_first_line(0, 2),
+                    right_colons: token_at_first_line(6, 8),
+                }),
             empty_while("while true do end") => WhileStatement::new(
                 default_block(),
                 create_true(6, 1),
@@ -0,0 +1,907 @@
+use std::collections::HashMap;
+use std::mem;
+
+use crate::nodes::*;
+use crate::process::{NodeProcessor, NodeVisitor, Scope, ScopeVisitor};
+
+/// Identifies a binding inside a [`BindingTable`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BindingId(usize);
+
+/// Identifies a lexical scope inside a [`BindingTable`]. The first scope opened during the
+/// analysis (usually the analyzed block) is the root scope.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScopeId(usize);
+
+impl ScopeId {
+    /// The identifier of the first scope opened during the analysis.
+    pub const ROOT: ScopeId = ScopeId(0);
+}
+
+/// How a binding was declared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BindingKind {
+    /// A variable from a local assignment (`local a = ...`).
+    Local,
+    /// The name of a local function (`local function a() end`).
+    LocalFunction,
+    /// A function parameter, including the implicit `self` parameter of methods.
+    Parameter,
+    /// A variable of a numeric or generic for loop.
+    LoopVariable,
+}
+
+/// A place where a binding is read or written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BindingReference {
+    index: usize,
+    position: Option<SourcePosition>,
+    from_inner_function: bool,
+}
+
+impl BindingReference {
+    /// The order in which the reference was found during the analysis. Declarations and
+    /// references share the same counter, so indexes can be compared with
+    /// [`Binding::get_declaration_index`] and [`Binding::get_scope_end`].
+    #[inline]
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    /// The position of the identifier, if it has a token.
+    #[inline]
+    pub fn get_position(&self) -> Option<SourcePosition> {
+        self.position
+    }
+
+    /// Returns true if the reference is inside a function nested in the scope where the
+    /// binding is declared.
+    #[inline]
+    pub fn is_from_inner_function(&self) -> bool {
+        self.from_inner_function
+    }
+}
+
+/// A variable declaration with all the places where it is used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Binding {
+    name: String,
+    kind: BindingKind,
+    scope: ScopeId,
+    declaration_index: usize,
+    scope_end: Option<usize>,
+    function_depth: usize,
+    definition_end: Option<usize>,
+    reads: Vec<BindingReference>,
+    writes: Vec<BindingReference>,
+}
+
+impl Binding {
+    #[inline]
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    pub fn get_kind(&self) -> BindingKind {
+        self.kind
+    }
+
+    /// The scope where the binding is declared.
+    #[inline]
+    pub fn get_scope(&self) -> ScopeId {
+        self.scope
+    }
+
+    /// The index at which the binding becomes visible.
+    #[inline]
+    pub fn get_declaration_index(&self) -> usize {
+        self.declaration_index
+    }
+
+    /// The index at which the scope of the binding was closed. Returns `None` if the
+    /// scope is still open.
+    #[inline]
+    pub fn get_scope_end(&self) -> Option<usize> {
+        self.scope_end
+    }
+
+    pub fn iter_reads(&self) -> impl Iterator<Item = &BindingReference> {
+        self.reads.iter()
+    }
+
+    pub fn iter_writes(&self) -> impl Iterator<Item = &BindingReference> {
+        self.writes.iter()
+    }
+
+    #[inline]
+    pub fn is_read(&self) -> bool {
+        !self.reads.is_empty()
+    }
+
+    #[inline]
+    pub fn is_written(&self) -> bool {
+        !self.writes.is_empty()
+    }
+
+    /// Returns true if the value of the binding can change after its declaration: when it
+    /// is written (including from inner functions) or when it is a loop variable, which
+    /// receives a new value on each iteration.
+    pub fn is_reassigned(&self) -> bool {
+        self.is_written() || self.kind == BindingKind::LoopVariable
+    }
+
+    /// Returns true if the binding is read or written from a function nested in the
+    /// scope where it is declared.
+    pub fn is_captured(&self) -> bool {
+        self.reads
+            .iter()
+            .chain(self.writes.iter())
+            .any(BindingReference::is_from_inner_function)
+    }
+
+    /// Returns true if the binding is read or written. References made by a local function
+    /// to itself (recursive calls) are ignored.
+    pub fn is_referenced(&self) -> bool {
+        self.reads
+            .iter()
+            .chain(self.writes.iter())
+            .any(|reference| !self.is_in_definition(reference))
+    }
+
+    fn is_in_definition(&self, reference: &BindingReference) -> bool {
+        self.definition_end
+            .map(|end| reference.index > self.declaration_index && reference.index < end)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScopeKind {
+    Block,
+    Function,
+    Loop,
+}
+
+#[derive(Clone, Debug)]
+struct PendingScope {
+    kind: ScopeKind,
+    depth: usize,
+    local_function: Option<BindingId>,
+}
+
+#[derive(Clone, Debug)]
+struct OpenScope {
+    id: ScopeId,
+    kind: ScopeKind,
+    function_depth: usize,
+    names: HashMap<String, BindingId>,
+    bindings: Vec<BindingId>,
+    local_function: Option<BindingId>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ReferenceKind {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+/// Finds every local variable, function parameter and loop variable of a block, with the
+/// places where they are read or written and whether they are captured by an inner function.
+///
+/// The table can be built at once with [`BindingTable::from_block`], or it can be filled
+/// while a processor visits a block using the [`ScopeVisitor`]. In that case, the processor
+/// must forward the [`Scope`] methods to the table (which can be done by implementing
+/// `DerefMut<Target = BindingTable>`) and call the table from these `NodeProcessor` methods:
+/// `process_variable`, `process_variable_expression`, `process_type_field`,
+/// `process_compound_assign_statement`, `process_function_statement`,
+/// `process_function_expression`, `process_local_function_statement`,
+/// `process_type_function_statement`, `process_export_type_function_statement`,
+/// `process_generic_for_statement` and `process_numeric_for_statement`.
+///
+/// ```
+/// # use darklua_core::process::{BindingKind, BindingTable};
+/// # use darklua_core::Parser;
+/// let mut block = Parser::default()
+///     .parse("local count = 0 local function increment() count += 1 end return count")
+///     .unwrap();
+///
+/// let bindings = BindingTable::from_block(&mut block);
+/// let count = bindings.iter_bindings().next().unwrap();
+///
+/// assert_eq!(count.get_name(), "count");
+/// assert_eq!(count.get_kind(), BindingKind::Local);
+/// assert_eq!(count.iter_reads().count(), 2);
+/// assert_eq!(count.iter_writes().count(), 1);
+/// assert!(count.is_captured());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BindingTable {
+    bindings: Vec<Binding>,
+    stack: Vec<OpenScope>,
+    pending_scopes: Vec<PendingScope>,
+    pending_reference: Option<ReferenceKind>,
+    compound_assignment: bool,
+    next_scope: usize,
+    index: usize,
+}
+
+impl BindingTable {
+    /// Analyzes all the bindings of a block.
+    pub fn from_block(block: &mut Block) -> Self {
+        let mut table = Self::default();
+        ScopeVisitor::visit_block(block, &mut table);
+        table
+    }
+
+    /// Analyzes a block and an extra expression that can access the block locals, like the
+    /// condition of a repeat statement. This method takes the same arguments as
+    /// [`NodeProcessor::process_scope`].
+    pub fn from_scope(block: &mut Block, extra: Option<&mut Expression>) -> Self {
+        let mut table = Self::default();
+        table.push();
+
+        for statement in block.iter_mut_statements() {
+            ScopeVisitor::visit_statement(statement, &mut table);
+        }
+
+        if let Some(last_statement) = block.mutate_last_statement() {
+            ScopeVisitor::visit_last_statement(last_statement, &mut table);
+        }
+
+        if let Some(expression) = extra {
+            ScopeVisitor::visit_expression(expression, &mut table);
+        }
+
+        table.pop();
+        table
+    }
+
+    /// Analyzes all the bindings of a statement, including the ones it declares in the
+    /// current scope.
+    pub fn from_statement(statement: &mut Statement) -> Self {
+        let mut table = Self::default();
+        table.push();
+        ScopeVisitor::visit_statement(statement, &mut table);
+        table.pop();
+        table
+    }
+
+    #[inline]
+    pub fn get_binding(&self, id: BindingId) -> Option<&Binding> {
+        self.bindings.get(id.0)
+    }
+
+    /// Iterates on all the bindings in the order of their declaration.
+    pub fn iter_bindings(&self) -> impl Iterator<Item = &Binding> {
+        self.bindings.iter()
+    }
+
+    /// Iterates on the bindings declared directly in the root scope, in the order of their
+    /// declaration.
+    pub fn iter_root_bindings(&self) -> impl Iterator<Item = &Binding> {
+        self.bindings
+            .iter()
+            .filter(|binding| binding.scope == ScopeId::ROOT)
+    }
+
+    /// Finds the binding that an identifier with the given name refers to at the current
+    /// point of the analysis. Returns `None` for global variables.
+    pub fn resolve(&self, name: &str) -> Option<&Binding> {
+        self.resolve_id(name).and_then(|id| self.get_binding(id))
+    }
+
+    fn resolve_id(&self, name: &str) -> Option<BindingId> {
+        self.stack
+            .iter()
+            .rev()
+            .find_map(|scope| scope.names.get(name).copied())
+    }
+
+    fn next_index(&mut self) -> usize {
+        let index = self.index;
+        self.index += 1;
+        index
+    }
+
+    fn current_scope(&mut self) -> &mut OpenScope {
+        if self.stack.is_empty() {
+            self.push();
+        }
+        self.stack.last_mut().expect("a scope should be open")
+    }
+
+    fn current_function_depth(&self) -> usize {
+        self.stack
+            .last()
+            .map(|scope| scope.function_depth)
+            .unwrap_or_default()
+    }
+
+    fn declare(&mut self, name: &str, kind: BindingKind) -> BindingId {
+        let id = BindingId(self.bindings.len());
+        let declaration_index = self.next_index();
+        let scope = self.current_scope();
+
+        scope.names.insert(name.to_owned(), id);
+        scope.bindings.push(id);
+        let (scope_id, function_depth) = (scope.id, scope.function_depth);
+
+        self.bindings.push(Binding {
+            name: name.to_owned(),
+            kind,
+            scope: scope_id,
+            declaration_index,
+            scope_end: None,
+            function_depth,
+            definition_end: None,
+            reads: Vec::new(),
+            writes: Vec::new(),
+        });
+
+        id
+    }
+
+    fn declare_parameter(&mut self, name: &str) {
+        let kind = match self.current_scope().kind {
+            ScopeKind::Function => BindingKind::Parameter,
+            ScopeKind::Loop => BindingKind::LoopVariable,
+            ScopeKind::Block => BindingKind::Local,
+        };
+        self.declare(name, kind);
+    }
+
+    fn reference(&mut self, identifier: &Identifier, kind: ReferenceKind) {
+        let index = self.next_index();
+        let function_depth = self.current_function_depth();
+
+        if let Some(id) = self.resolve_id(identifier.get_name()) {
+            let binding = &mut self.bindings[id.0];
+            let reference = BindingReference {
+                index,
+                position: identifier.get_token().and_then(Token::start_position),
+                from_inner_function: function_depth > binding.function_depth,
+            };
+
+            if kind != ReferenceKind::Write {
+                binding.reads.push(reference);
+            }
+            if kind != ReferenceKind::Read {
+                binding.writes.push(reference);
+            }
+        }
+    }
+
+    fn expect_scope(&mut self, kind: ScopeKind) {
+        self.pending_scopes.push(PendingScope {
+            kind,
+            depth: self.stack.len(),
+            local_function: None,
+        });
+    }
+}
+
+impl Scope for BindingTable {
+    fn push(&mut self) {
+        let depth = self.stack.len();
+        let pending = if self
+            .pending_scopes
+            .last()
+            .filter(|pending| pending.depth == depth)
+            .is_some()
+        {
+            self.pending_scopes.pop()
+        } else {
+            None
+        };
+
+        let (kind, local_function) = pending
+            .map(|pending| (pending.kind, pending.local_function))
+            .unwrap_or((ScopeKind::Block, None));
+
+        let function_depth =
+            self.current_function_depth() + if kind == ScopeKind::Function { 1 } else { 0 };
+
+        let id = ScopeId(self.next_scope);
+        self.next_scope += 1;
+
+        self.stack.push(OpenScope {
+            id,
+            kind,
+            function_depth,
+            names: HashMap::new(),
+            bindings: Vec::new(),
+            local_function,
+        });
+    }
+
+    fn pop(&mut self) {
+        if let Some(scope) = self.stack.pop() {
+            let end = self.index;
+
+            for id in scope.bindings {
+                self.bindings[id.0].scope_end = Some(end);
+            }
+
+            if let Some(id) = scope.local_function {
+                self.bindings[id.0].definition_end = Some(end);
+            }
+        }
+    }
+
+    fn insert(&mut self, identifier: &mut String) {
+        self.declare_parameter(identifier);
+    }
+
+    fn insert_self(&mut self) {
+        self.declare_parameter("self");
+    }
+
+    fn insert_local(&mut self, identifier: &mut String, _value: Option<&mut Expression>) {
+        self.declare(identifier, BindingKind::Local);
+    }
+
+    fn insert_local_function(&mut self, function: &mut LocalFunctionStatement) {
+        let id = self.declare(function.get_name(), BindingKind::LocalFunction);
+        let depth = self.stack.len();
+
+        if let Some(pending) = self
+            .pending_scopes
+            .last_mut()
+            .filter(|pending| pending.kind == ScopeKind::Function && pending.depth == depth)
+        {
+            pending.local_function = Some(id);
+        }
+    }
+}
+
+impl NodeProcessor for BindingTable {
+    fn process_variable(&mut self, variable: &mut Variable) {
+        let compound_assignment = mem::take(&mut self.compound_assignment);
+
+        if let Variable::Identifier(_) = variable {
+            self.pending_reference = Some(if compound_assignment {
+                ReferenceKind::ReadWrite
+            } else {
+                ReferenceKind::Write
+            });
+        }
+    }
+
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        let kind = self.pending_reference.take().unwrap_or(ReferenceKind::Read);
+        self.reference(identifier, kind);
+    }
+
+    fn process_type_field(&mut self, type_field: &mut TypeField) {
+        self.reference(type_field.get_namespace(), ReferenceKind::Read);
+    }
+
+    fn process_compound_assign_statement(&mut self, _: &mut CompoundAssignStatement) {
+        self.compound_assignment = true;
+    }
+
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        let name = function.get_name();
+        if name.get_field_names().is_empty() && !name.has_method() {
+            self.pending_reference = Some(ReferenceKind::Write);
+        }
+        self.expect_scope(ScopeKind::Function);
+    }
+
+    fn process_function_expression(&mut self, _: &mut FunctionExpression) {
+        self.expect_scope(ScopeKind::Function);
+    }
+
+    fn process_local_function_statement(&mut self, _: &mut LocalFunctionStatement) {
+        self.expect_scope(ScopeKind::Function);
+    }
+
+    fn process_type_function_statement(&mut self, _: &mut TypeFunctionStatement) {
+        self.expect_scope(ScopeKind::Function);
+    }
+
+    fn process_export_type_function_statement(&mut self, _: &mut ExportTypeFunctionStatement) {
+        self.expect_scope(ScopeKind::Function);
+    }
+
+    fn process_generic_for_statement(&mut self, _: &mut GenericForStatement) {
+        self.expect_scope(ScopeKind::Loop);
+    }
+
+    fn process_numeric_for_statement(&mut self, _: &mut NumericForStatement) {
+        self.expect_scope(ScopeKind::Loop);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Parser;
+
+    fn analyze(code: &str) -> BindingTable {
+        let mut block = Parser::default()
+            .parse(code)
+            .unwrap_or_else(|error| panic!("could not parse `{}`: {}", code, error));
+        BindingTable::from_block(&mut block)
+    }
+
+    fn bindings_named<'a>(table: &'a BindingTable, name: &'a str) -> Vec<&'a Binding> {
+        table
+            .iter_bindings()
+            .filter(|binding| binding.get_name() == name)
+            .collect()
+    }
+
+    fn get_binding<'a>(table: &'a BindingTable, name: &str) -> &'a Binding {
+        let bindings = bindings_named(table, name);
+        assert_eq!(bindings.len(), 1, "expected one binding named `{}`", name);
+        bindings[0]
+    }
+
+    #[test]
+    fn global_variables_have_no_binding() {
+        let table = analyze("print(value) value = 1");
+
+        assert_eq!(table.iter_bindings().count(), 0);
+    }
+
+    #[test]
+    fn local_read_and_write() {
+        let table = analyze("local a = 1 print(a) a = 2 a += 3");
+
+        let a = get_binding(&table, "a");
+        assert_eq!(a.get_kind(), BindingKind::Local);
+        assert_eq!(a.get_scope(), ScopeId::ROOT);
+        assert_eq!(a.iter_reads().count(), 2);
+        assert_eq!(a.iter_writes().count(), 2);
+        assert!(!a.is_captured());
+    }
+
+    #[test]
+    fn field_assignment_reads_the_table() {
+        let table = analyze("local t = {} t.field = true t[1] = false");
+
+        let t = get_binding(&table, "t");
+        assert_eq!(t.iter_reads().count(), 2);
+        assert!(!t.is_written());
+    }
+
+    #[test]
+    fn local_value_does_not_see_the_new_local() {
+        let table = analyze("local a = 1 local a = a + 1 return a");
+
+        let bindings = bindings_named(&table, "a");
+        assert_eq!(bindings.len(), 2);
+        assert_eq!(bindings[0].iter_reads().count(), 1);
+        assert_eq!(bindings[1].iter_reads().count(), 1);
+        assert!(
+            bindings[0].iter_reads().next().unwrap().get_index()
+                < bindings[1].get_declaration_index()
+        );
+    }
+
+    #[test]
+    fn shadowing_in_nested_block() {
+        let table = analyze("local a = 1 do local a = 2 print(a) a = 3 end print(a)");
+
+        let bindings = bindings_named(&table, "a");
+        assert_eq!(bindings.len(), 2);
+
+        let (outer, inner) = (bindings[0], bindings[1]);
+        assert_eq!(outer.iter_reads().count(), 1);
+        assert!(!outer.is_written());
+        assert_eq!(inner.iter_reads().count(), 1);
+        assert_eq!(inner.iter_writes().count(), 1);
+        assert_ne!(outer.get_scope(), inner.get_scope());
+
+        let inner_scope_end = inner.get_scope_end().expect("inner scope should be closed");
+        let outer_read = outer.iter_reads().next().unwrap();
+        assert!(outer_read.get_index() > inner_scope_end);
+    }
+
+    #[test]
+    fn shadowing_parameter() {
+        let table = analyze("local value = 1 local function f(value) return value end");
+
+        let bindings = bindings_named(&table, "value");
+        assert_eq!(bindings.len(), 2);
+        assert_eq!(bindings[0].get_kind(), BindingKind::Local);
+        assert!(!bindings[0].is_read());
+        assert_eq!(bindings[1].get_kind(), BindingKind::Parameter);
+        assert!(bindings[1].is_read());
+    }
+
+    #[test]
+    fn repeat_condition_sees_body_locals() {
+        let table = analyze("local done = false repeat local done = check() until done");
+
+        let bindings = bindings_named(&table, "done");
+        assert_eq!(bindings.len(), 2);
+        assert!(!bindings[0].is_read());
+        assert_eq!(bindings[1].iter_reads().count(), 1);
+    }
+
+    #[test]
+    fn while_condition_does_not_see_body_locals() {
+        let table = analyze("local done = false while not done do local done = true end");
+
+        let bindings = bindings_named(&table, "done");
+        assert_eq!(bindings[0].iter_reads().count(), 1);
+        assert!(!bindings[1].is_read());
+    }
+
+    #[test]
+    fn closure_capture() {
+        let table = analyze(
+            "local count = 0 local other = 1 local function increment() count += 1 end print(other)",
+        );
+
+        let count = get_binding(&table, "count");
+        assert!(count.is_captured());
+        assert!(count
+            .iter_reads()
+            .all(BindingReference::is_from_inner_function));
+
+        assert!(!get_binding(&table, "other").is_captured());
+        assert!(!get_binding(&table, "increment").is_captured());
+    }
+
+    #[test]
+    fn closure_capture_in_function_expression() {
+        let table = analyze("local a = 1 return function() return a end");
+
+        assert!(get_binding(&table, "a").is_captured());
+    }
+
+    #[test]
+    fn locals_in_loops_are_not_captured() {
+        let table = analyze("local a = 1 for i = 1, 10 do print(a, i) end");
+
+        assert!(!get_binding(&table, "a").is_captured());
+        assert!(!get_binding(&table, "i").is_captured());
+    }
+
+    #[test]
+    fn parameter_captured_by_inner_function() {
+        let table = analyze("local function f(a) return function() return a end end");
+
+        let a = get_binding(&table, "a");
+        assert_eq!(a.get_kind(), BindingKind::Parameter);
+        assert!(a.is_captured());
+    }
+
+    #[test]
+    fn loop_variables() {
+        let table = analyze("for i = 1, 10 do print(i) end for key, value in pairs(t) do end");
+
+        assert_eq!(
+            get_binding(&table, "i").get_kind(),
+            BindingKind::LoopVariable
+        );
+        assert_eq!(
+            get_binding(&table, "key").get_kind(),
+            BindingKind::LoopVariable
+        );
+        assert_eq!(
+            get_binding(&table, "value").get_kind(),
+            BindingKind::LoopVariable
+        );
+    }
+
+    #[test]
+    fn loop_inside_function_arguments() {
+        let table = analyze("for _, v in call(function(a) end) do end");
+
+        assert_eq!(get_binding(&table, "a").get_kind(), BindingKind::Parameter);
+        assert_eq!(
+            get_binding(&table, "v").get_kind(),
+            BindingKind::LoopVariable
+        );
+    }
+
+    #[test]
+    fn method_self_parameter() {
+        let table = analyze("local t = {} function t:method() return self end");
+
+        let self_binding = get_binding(&table, "self");
+        assert_eq!(self_binding.get_kind(), BindingKind::Parameter);
+        assert!(self_binding.is_read());
+        assert_eq!(get_binding(&table, "t").iter_reads().count(), 1);
+    }
+
+    #[test]
+    fn function_statement_writes_local() {
+        let table = analyze("local f function f() end");
+
+        let f = get_binding(&table, "f");
+        assert_eq!(f.iter_writes().count(), 1);
+        assert!(!f.is_read());
+    }
+
+    #[test]
+    fn local_without_writes_is_not_reassigned() {
+        let table = analyze("local a = 1 print(a)");
+
+        assert!(!get_binding(&table, "a").is_reassigned());
+    }
+
+    #[test]
+    fn compound_assignment_reassigns_local() {
+        let table = analyze("local a = 1 a ..= 'b'");
+
+        let a = get_binding(&table, "a");
+        assert!(a.is_reassigned());
+        assert_eq!(a.iter_writes().count(), 1);
+    }
+
+    #[test]
+    fn multiple_assignment_reassigns_each_local() {
+        let table = analyze("local a, b, c = 1, 2, 3 a, c = c, a");
+
+        assert!(get_binding(&table, "a").is_reassigned());
+        assert!(!get_binding(&table, "b").is_reassigned());
+        assert!(get_binding(&table, "c").is_reassigned());
+    }
+
+    #[test]
+    fn closure_writing_upvalue_reassigns_local() {
+        let table = analyze("local a = 1 local function set() a = 2 end set() return a");
+
+        let a = get_binding(&table, "a");
+        assert!(a.is_reassigned());
+        assert!(a
+            .iter_writes()
+            .all(BindingReference::is_from_inner_function));
+    }
+
+    #[test]
+    fn shadowed_redeclaration_does_not_reassign_outer_local() {
+        let table = analyze("local a = 1 do local a = 2 a = 3 end local a = a return a");
+
+        let bindings = bindings_named(&table, "a");
+        assert_eq!(bindings.len(), 3);
+        assert!(!bindings[0].is_reassigned());
+        assert!(bindings[1].is_reassigned());
+        assert!(!bindings[2].is_reassigned());
+    }
+
+    #[test]
+    fn loop_variables_are_reassigned() {
+        let table = analyze("for i = 1, 10 do end for _, v in t do end");
+
+        assert!(get_binding(&table, "i").is_reassigned());
+        assert!(!get_binding(&table, "i").is_written());
+        assert!(get_binding(&table, "v").is_reassigned());
+    }
+
+    #[test]
+    fn repeat_condition_reads_body_local() {
+        let table = analyze("repeat local done = true until done");
+
+        let done = get_binding(&table, "done");
+        assert!(done.is_read());
+        assert!(!done.is_reassigned());
+    }
+
+    #[test]
+    fn bindings_from_statement() {
+        let mut statement = Parser::default()
+            .parse("for i = 1, 3 do i = i + 1 end")
+            .unwrap()
+            .take_statements()
+            .pop()
+            .unwrap();
+        let table = BindingTable::from_statement(&mut statement);
+
+        let i = get_binding(&table, "i");
+        assert_eq!(i.get_kind(), BindingKind::LoopVariable);
+        assert_eq!(i.iter_writes().count(), 1);
+    }
+
+    #[test]
+    fn recursive_local_function_is_not_referenced() {
+        let table = analyze("local function f(n) return f(n - 1) end");
+
+        let f = get_binding(&table, "f");
+        assert_eq!(f.get_kind(), BindingKind::LocalFunction);
+        assert!(f.is_read());
+        assert!(!f.is_referenced());
+    }
+
+    #[test]
+    fn local_function_referenced_after_definition() {
+        let table = analyze("local function f() return f() end f()");
+
+        assert!(get_binding(&table, "f").is_referenced());
+    }
+
+    #[test]
+    fn type_field_reads_local() {
+        let table = analyze("local Module = require('module') type T = Module.Type");
+
+        assert!(get_binding(&table, "Module").is_read());
+    }
+
+    #[test]
+    fn reference_positions_with_tokens() {
+        let mut block = Parser::default()
+            .preserve_tokens()
+            .parse("local a = 1\nprint(a)")
+            .unwrap();
+        let table = BindingTable::from_block(&mut block);
+
+        let read = get_binding(&table, "a").iter_reads().next().unwrap();
+        assert_eq!(read.get_position().map(|position| position.line()), Some(2));
+    }
+
+    #[test]
+    fn root_bindings_from_scope() {
+        let mut block = Parser::default()
+            .parse("local a = 1 do local b = a end local function c() local d end")
+            .unwrap();
+        let table = BindingTable::from_scope(&mut block, None);
+
+        let names: Vec<_> = table.iter_root_bindings().map(Binding::get_name).collect();
+        assert_eq!(names, vec!["a", "c"]);
+    }
+
+    mod incremental {
+        use super::*;
+        use std::ops::{Deref, DerefMut};
+
+        #[derive(Default)]
+        struct CaptureChecker {
+            bindings: BindingTable,
+            captured_reads: Vec<String>,
+        }
+
+        impl Deref for CaptureChecker {
+            type Target = BindingTable;
+
+            fn deref(&self) -> &Self::Target {
+                &self.bindings
+            }
+        }
+
+        impl DerefMut for CaptureChecker {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.bindings
+            }
+        }
+
+        impl NodeProcessor for CaptureChecker {
+            fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+                self.bindings.process_variable_expression(identifier);
+
+                if let Some(binding) = self.bindings.resolve(identifier.get_name()) {
+                    if binding
+                        .iter_reads()
+                        .last()
+                        .filter(|reference| reference.is_from_inner_function())
+                        .is_some()
+                    {
+                        self.captured_reads.push(identifier.get_name().to_owned());
+                    }
+                }
+            }
+
+            fn process_function_expression(&mut self, function: &mut FunctionExpression) {
+                self.bindings.process_function_expression(function);
+            }
+        }
+
+        #[test]
+        fn resolve_during_traversal() {
+            let mut block = Parser::default()
+                .parse("local a, b = 1, 2 local f = function() return a end print(b)")
+                .unwrap();
+            let mut checker = CaptureChecker::default();
+
+            ScopeVisitor::visit_block(&mut block, &mut checker);
+
+            assert_eq!(checker.captured_reads, vec!["a".to_owned()]);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Tracks the budget given to the rule processing a file on the current thread. Visitors
+//! consume a step before each statement and stop visiting once the budget is exhausted, so
+//! that a rule running on a pathological file returns early instead of hanging.
+
+use std::{cell::RefCell, time::Duration};
+
+use crate::utils::Timer;
+
+// reading the clock is more expensive than counting, so the timeout is only verified
+// once every few steps
+const STEPS_PER_TIME_CHECK: u64 = 64;
+
+thread_local! {
+    static CURRENT_BUDGET: RefCell<Option<BudgetTracker>> = RefCell::new(None);
+}
+
+#[derive(Debug)]
+struct BudgetTracker {
+    timer: Timer,
+    time_left: Option<Duration>,
+    steps_left: Option<u64>,
+    steps: u64,
+    exhausted: bool,
+}
+
+impl BudgetTracker {
+    fn consume_step(&mut self) -> bool {
+        if self.exhausted {
+            return true;
+        }
+
+        self.steps += 1;
+
+        if let Some(steps_left) = self.steps_left {
+            if self.steps > steps_left {
+                self.exhausted = true;
+            }
+        }
+
+        if let Some(time_left) = self.time_left {
+            if self.steps % STEPS_PER_TIME_CHECK == 0 && self.timer.duration() > time_left {
+                self.exhausted = true;
+            }
+        }
+
+        self.exhausted
+    }
+}
+
+/// The steps consumed while a budget was tracked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BudgetUsage {
+    steps: u64,
+    exhausted: bool,
+}
+
+impl BudgetUsage {
+    pub(crate) fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub(crate) fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+}
+
+/// Runs the function while tracking the given budget for the visitors used on the current
+/// thread. The previous budget is restored afterwards, so budgets can be nested.
+pub(crate) fn track_budget<T>(
+    time_left: Option<Duration>,
+    steps_left: Option<u64>,
+    function: impl FnOnce() -> T,
+) -> (T, BudgetUsage) {
+    let previous = CURRENT_BUDGET.with(|budget| {
+        budget.borrow_mut().replace(BudgetTracker {
+            timer: Timer::now(),
+            time_left,
+            steps_left,
+            steps: 0,
+            exhausted: false,
+        })
+    });
+
+    let result = function();
+
+    let tracker =
+        CURRENT_BUDGET.with(|budget| std::mem::replace(&mut *budget.borrow_mut(), previous));
+
+    let usage = tracker
+        .map(|tracker| BudgetUsage {
+            steps: tracker.steps,
+            exhausted: tracker.exhausted,
+        })
+        .unwrap_or(BudgetUsage {
+            steps: 0,
+            exhausted: false,
+        });
+
+    (result, usage)
+}
+
+/// Consumes a step of the current budget and returns `true` when it is exhausted. Without
+/// a tracked budget, this always returns `false`.
+#[inline]
+pub(crate) fn consume_budget_step() -> bool {
+    CURRENT_BUDGET.with(|budget| {
+        budget
+            .borrow_mut()
+            .as_mut()
+            .map(BudgetTracker::consume_step)
+            .unwrap_or(false)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn without_budget_steps_are_never_exhausted() {
+        assert!(!(0..1000).any(|_| consume_budget_step()));
+    }
+
+    #[test]
+    fn step_budget_is_exhausted_after_the_last_step() {
+        let (consumed, usage) = track_budget(None, Some(3), || {
+            (0..5).map(|_| consume_budget_step()).collect::<Vec<_>>()
+        });
+
+        pretty_assertions::assert_eq!(consumed, vec![false, false, false, true, true]);
+        assert_eq!(usage.steps(), 4);
+        assert!(usage.is_exhausted());
+    }
+
+    #[test]
+    fn previous_budget_is_restored() {
+        let (_, usage) = track_budget(None, Some(10), || {
+            consume_budget_step();
+            let (_, inner_usage) = track_budget(None, None, consume_budget_step);
+            assert_eq!(inner_usage.steps(), 1);
+            consume_budget_step();
+        });
+
+        assert_eq!(usage.steps(), 2);
+        assert!(!usage.is_exhausted());
+        assert!(!consume_budget_step());
+    }
+
+    #[test]
+    fn time_budget_is_exhausted_after_timeout() {
+        let (exhausted, usage) = track_budget(Some(Duration::from_millis(1)), None, || {
+            std::thread::sleep(Duration::from_millis(5));
+            (0..STEPS_PER_TIME_CHECK).any(|_| consume_budget_step())
+        });
+
+        assert!(exhausted);
+        assert!(usage.is_exhausted());
+    }
+}
@@ -0,0 +1,19 @@
+/// The Lua version targeted when evaluating expressions. Operators do not always produce the
+/// same results from one version to another, so the [`Evaluator`](crate::process::Evaluator)
+/// only computes values that are identical in the selected dialect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LuaDialect {
+    /// Lua 5.1: all numbers are floats and the floor division operator (`//`) does not exist.
+    Lua51,
+    /// Lua 5.3: numbers are either integers or floats. Integer floor division and modulo by
+    /// zero are runtime errors.
+    Lua53,
+    /// Luau: all numbers are floats and division by zero follows IEEE 754 rules.
+    Luau,
+}
+
+impl Default for LuaDialect {
+    fn default() -> Self {
+        Self::Luau
+    }
+}
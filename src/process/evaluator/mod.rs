@@ -1,15 +1,46 @@
+mod lua_dialect;
 mod lua_value;
 
+pub use lua_dialect::*;
 pub use lua_value::*;
 
 use crate::nodes::*;
 
 /// A struct to convert an Expression node into a LuaValue object.
+///
+/// The evaluator is conservative: when it cannot figure out the value of an expression
+/// (like a variable or a function call), it returns [`LuaValue::Unknown`]. In the same way,
+/// an expression is considered to have side effects unless the evaluator can prove it
+/// does not.
+///
+/// ```
+/// # use darklua_core::nodes::{BinaryExpression, BinaryOperator, Expression};
+/// # use darklua_core::process::{Evaluator, LuaValue};
+/// let evaluator = Evaluator::default();
+///
+/// let expression = BinaryExpression::new(BinaryOperator::Plus, 1.0, 2.0).into();
+///
+/// assert_eq!(evaluator.evaluate(&expression), LuaValue::Number(3.0));
+/// assert!(!evaluator.has_side_effects(&expression));
+/// ```
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Evaluator {
     pure_metamethods: bool,
+    dialect: LuaDialect,
+    global_tostring: bool,
+    global_tonumber: bool,
 }
 
+/// Integers above this value can't be represented exactly with a float.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// The number of significant digits used by Lua 5.1 and Lua 5.3 to convert numbers to
+/// strings (the `%.14g` format).
+const LUA_NUMBER_PRECISION: usize = 14;
+
+const TOSTRING_FUNCTION: &str = "tostring";
+const TONUMBER_FUNCTION: &str = "tonumber";
+
 impl Evaluator {
     /// When evaluating expressions related to tables, this value tells the evaluator if
     /// metamethods can have side effects. For example, indexing a normal table in Lua does not
@@ -20,12 +51,81 @@ impl Evaluator {
         self
     }
 
+    /// Sets the Lua dialect used to evaluate operators. By default, the evaluator follows
+    /// Luau semantics.
+    ///
+    /// ```
+    /// # use darklua_core::nodes::{BinaryExpression, BinaryOperator};
+    /// # use darklua_core::process::{Evaluator, LuaDialect, LuaValue};
+    /// let floor_division = BinaryExpression::new(BinaryOperator::DoubleSlash, 7.0, 2.0).into();
+    ///
+    /// assert_eq!(Evaluator::default().evaluate(&floor_division), LuaValue::Number(3.0));
+    ///
+    /// // the `//` operator does not exist in Lua 5.1
+    /// let evaluator = Evaluator::default().with_dialect(LuaDialect::Lua51);
+    /// assert_eq!(evaluator.evaluate(&floor_division), LuaValue::Unknown);
+    /// ```
+    pub fn with_dialect(mut self, dialect: LuaDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    #[inline]
+    pub fn get_dialect(&self) -> LuaDialect {
+        self.dialect
+    }
+
+    /// Assumes that the `tostring` identifier refers to the function of the standard
+    /// library, so that calls with an argument that can be evaluated are computed. This
+    /// should only be used when `tostring` is not shadowed by a local variable and is never
+    /// assigned (like `tostring = custom` or `_G.tostring = custom`).
+    ///
+    /// ```
+    /// # use darklua_core::nodes::FunctionCall;
+    /// # use darklua_core::process::{Evaluator, LuaValue};
+    /// let call = FunctionCall::from_name("tostring").with_argument(true).into();
+    ///
+    /// assert_eq!(Evaluator::default().evaluate(&call), LuaValue::Unknown);
+    ///
+    /// let evaluator = Evaluator::default().assume_global_tostring();
+    /// assert_eq!(evaluator.evaluate(&call), LuaValue::from("true"));
+    /// ```
+    pub fn assume_global_tostring(mut self) -> Self {
+        self.global_tostring = true;
+        self
+    }
+
+    /// Assumes that the `tonumber` identifier refers to the function of the standard
+    /// library, so that calls with arguments that can be evaluated are computed. When the
+    /// string can't be converted, the call evaluates to `nil`. This should only be used when
+    /// `tonumber` is not shadowed by a local variable and is never assigned.
+    pub fn assume_global_tonumber(mut self) -> Self {
+        self.global_tonumber = true;
+        self
+    }
+
+    /// Computes the value of the given expression. Expressions that can't be statically
+    /// known (identifiers, function calls, field or index accesses, variadic arguments)
+    /// evaluate to [`LuaValue::Unknown`].
+    ///
+    /// ```
+    /// # use darklua_core::nodes::{Expression, FieldExpression, Identifier};
+    /// # use darklua_core::process::{Evaluator, LuaValue};
+    /// let evaluator = Evaluator::default();
+    ///
+    /// assert_eq!(evaluator.evaluate(&Expression::from(true)), LuaValue::True);
+    /// assert_eq!(evaluator.evaluate(&Expression::nil()), LuaValue::Nil);
+    ///
+    /// // indexing a table may call an `__index` metamethod
+    /// let field = FieldExpression::new(Identifier::new("object"), "value").into();
+    /// assert_eq!(evaluator.evaluate(&field), LuaValue::Unknown);
+    /// ```
     pub fn evaluate(&self, expression: &Expression) -> LuaValue {
         match expression {
             Expression::False(_) => LuaValue::False,
             Expression::Function(_) => LuaValue::Function,
             Expression::Nil(_) => LuaValue::Nil,
-            Expression::Number(number) => LuaValue::from(number.compute_value()),
+            Expression::Number(number) => self.evaluate_number(number),
             Expression::String(string) => LuaValue::from(string.get_value()),
             Expression::Table(_) => LuaValue::Table,
             Expression::True(_) => LuaValue::True,
@@ -58,10 +158,13 @@ impl Evaluator {
                                 LuaValue::String(string) => {
                                     result.push_str(&string);
                                 }
-                                LuaValue::Function
-                                | LuaValue::Number(_)
-                                | LuaValue::Table
-                                | LuaValue::Unknown => return LuaValue::Unknown,
+                                LuaValue::Number(number) => match format_luau_number(number) {
+                                    Some(formatted) => result.push_str(&formatted),
+                                    None => return LuaValue::Unknown,
+                                },
+                                LuaValue::Function | LuaValue::Table | LuaValue::Unknown => {
+                                    return LuaValue::Unknown
+                                }
                             }
                         }
                     }
@@ -69,14 +172,47 @@ impl Evaluator {
                 LuaValue::String(result)
             }
             Expression::TypeCast(type_cast) => self.evaluate(type_cast.get_expression()),
-            Expression::Call(_)
-            | Expression::Field(_)
+            Expression::Call(call) => self.evaluate_call(call),
+            Expression::Field(_)
             | Expression::Identifier(_)
             | Expression::Index(_)
             | Expression::VariableArguments(_) => LuaValue::Unknown,
         }
     }
 
+    /// Returns `true` if the expression evaluates to a known value that can be written back
+    /// as a literal (`nil`, a boolean, a number or a string) and evaluating it does not
+    /// have any side effects.
+    ///
+    /// ```
+    /// # use darklua_core::nodes::{BinaryExpression, BinaryOperator, Expression, TableExpression};
+    /// # use darklua_core::process::Evaluator;
+    /// let evaluator = Evaluator::default();
+    ///
+    /// let concat = BinaryExpression::new(
+    ///     BinaryOperator::Concat,
+    ///     Expression::from(1.0),
+    ///     Expression::from(2.0),
+    /// );
+    /// assert!(evaluator.is_constant(&concat.into()));
+    ///
+    /// // tables are not constants: each constructor creates a new table
+    /// assert!(!evaluator.is_constant(&TableExpression::default().into()));
+    /// assert!(!evaluator.is_constant(&Expression::identifier("var")));
+    /// ```
+    pub fn is_constant(&self, expression: &Expression) -> bool {
+        match self.evaluate(expression) {
+            LuaValue::False
+            | LuaValue::True
+            | LuaValue::Nil
+            | LuaValue::Number(_)
+            | LuaValue::String(_) => !self.has_side_effects(expression),
+            LuaValue::Function | LuaValue::Table | LuaValue::Unknown => false,
+        }
+    }
+
+    /// Returns `true` if the expression can produce more than one value (like a function
+    /// call or `...`), which matters when the expression is the last one of a list.
     #[allow(clippy::only_used_in_recursion)]
     pub fn can_return_multiple_values(&self, expression: &Expression) -> bool {
         match expression {
@@ -105,6 +241,29 @@ impl Evaluator {
         }
     }
 
+    /// Returns `true` if evaluating the expression may have side effects.
+    ///
+    /// Function calls are assumed to have side effects, except the calls to `tostring` and
+    /// `tonumber` that can be computed (see
+    /// [`assume_global_tostring`](Evaluator::assume_global_tostring) and
+    /// [`assume_global_tonumber`](Evaluator::assume_global_tonumber)). Unless the evaluator was
+    /// created with [`assume_pure_metamethods`](Evaluator::assume_pure_metamethods), any
+    /// operation that could trigger a metamethod on a value that is not known (indexing,
+    /// arithmetic, comparisons, length or unary minus) is also considered to have side
+    /// effects.
+    ///
+    /// ```
+    /// # use darklua_core::nodes::{Expression, FunctionCall, IndexExpression, Identifier};
+    /// # use darklua_core::process::Evaluator;
+    /// let evaluator = Evaluator::default();
+    ///
+    /// assert!(evaluator.has_side_effects(&FunctionCall::from_name("print").into()));
+    /// assert!(!evaluator.has_side_effects(&Expression::identifier("var")));
+    ///
+    /// let index = IndexExpression::new(Identifier::new("object"), Expression::from(1.0)).into();
+    /// assert!(evaluator.has_side_effects(&index));
+    /// assert!(!evaluator.assume_pure_metamethods().has_side_effects(&index));
+    /// ```
     pub fn has_side_effects(&self, expression: &Expression) -> bool {
         match expression {
             Expression::False(_)
@@ -229,8 +388,8 @@ impl Evaluator {
     }
 
     #[inline]
-    fn call_has_side_effects(&self, _call: &FunctionCall) -> bool {
-        true
+    fn call_has_side_effects(&self, call: &FunctionCall) -> bool {
+        self.evaluate_call(call) == LuaValue::Unknown
     }
 
     #[inline]
@@ -306,15 +465,13 @@ impl Evaluator {
                     _ => LuaValue::Unknown,
                 }
             }
-            BinaryOperator::Plus => self.evaluate_math(expression, |a, b| a + b),
-            BinaryOperator::Minus => self.evaluate_math(expression, |a, b| a - b),
-            BinaryOperator::Asterisk => self.evaluate_math(expression, |a, b| a * b),
-            BinaryOperator::Slash => self.evaluate_math(expression, |a, b| a / b),
-            BinaryOperator::DoubleSlash => self.evaluate_math(expression, |a, b| (a / b).floor()),
-            BinaryOperator::Caret => self.evaluate_math(expression, |a, b| a.powf(b)),
-            BinaryOperator::Percent => {
-                self.evaluate_math(expression, |a, b| a - b * (a / b).floor())
-            }
+            BinaryOperator::Plus
+            | BinaryOperator::Minus
+            | BinaryOperator::Asterisk
+            | BinaryOperator::Slash
+            | BinaryOperator::DoubleSlash
+            | BinaryOperator::Caret
+            | BinaryOperator::Percent => self.evaluate_math(expression),
             BinaryOperator::Concat => {
                 match (
                     self.evaluate(expression.left()).string_coercion(),
@@ -350,17 +507,21 @@ impl Evaluator {
         }
     }
 
-    fn evaluate_math<F>(&self, expression: &BinaryExpression, operation: F) -> LuaValue
-    where
-        F: Fn(f64, f64) -> f64,
-    {
-        let left = self.evaluate(expression.left()).number_coercion();
+    fn evaluate_math(&self, expression: &BinaryExpression) -> LuaValue {
+        let left = self.evaluate_math_operand(expression.left());
 
         if let LuaValue::Number(left) = left {
-            let right = self.evaluate(expression.right()).number_coercion();
+            let right = self.evaluate_math_operand(expression.right());
 
             if let LuaValue::Number(right) = right {
-                LuaValue::Number(operation(left, right))
+                match self.dialect {
+                    LuaDialect::Lua51 | LuaDialect::Luau => {
+                        self.compute_float_math(expression.operator(), left, right)
+                    }
+                    LuaDialect::Lua53 => {
+                        self.compute_lua53_math(expression.operator(), left, right)
+                    }
+                }
             } else {
                 LuaValue::Unknown
             }
@@ -369,6 +530,108 @@ impl Evaluator {
         }
     }
 
+    fn evaluate_number(&self, number: &NumberExpression) -> LuaValue {
+        let value = number.compute_value();
+        match self.dialect {
+            LuaDialect::Lua51 | LuaDialect::Luau => LuaValue::from(value),
+            // in Lua 5.3, a whole number written as a float (like `1.0` or `1e3`) is not an
+            // integer, but its value alone would be treated as one
+            LuaDialect::Lua53 => {
+                if is_float_literal(number) {
+                    float_result(value)
+                } else {
+                    LuaValue::from(value)
+                }
+            }
+        }
+    }
+
+    fn evaluate_math_operand(&self, expression: &Expression) -> LuaValue {
+        let value = self.evaluate(expression);
+        match self.dialect {
+            LuaDialect::Lua51 | LuaDialect::Luau => value.number_coercion(),
+            // in Lua 5.3, strings are converted to integers or floats depending on how they
+            // are written, which is not tracked by the evaluator
+            LuaDialect::Lua53 => match value {
+                LuaValue::String(_) => LuaValue::Unknown,
+                _ => value,
+            },
+        }
+    }
+
+    fn compute_float_math(&self, operator: BinaryOperator, left: f64, right: f64) -> LuaValue {
+        let result = match operator {
+            BinaryOperator::Plus => left + right,
+            BinaryOperator::Minus => left - right,
+            BinaryOperator::Asterisk => left * right,
+            BinaryOperator::Slash => left / right,
+            BinaryOperator::DoubleSlash => {
+                if self.dialect == LuaDialect::Lua51 {
+                    return LuaValue::Unknown;
+                }
+                (left / right).floor()
+            }
+            BinaryOperator::Caret => left.powf(right),
+            BinaryOperator::Percent => left - right * (left / right).floor(),
+            _ => return LuaValue::Unknown,
+        };
+        LuaValue::Number(result)
+    }
+
+    /// Lua 5.3 has integers and floats. A number value is considered an integer when it
+    /// does not have a fractional part, which matches how darklua writes numbers back. Whole
+    /// numbers written as floats evaluate to [`LuaValue::Unknown`], so they never reach this
+    /// function. The result is only returned when writing it back produces a number of the
+    /// same subtype.
+    fn compute_lua53_math(&self, operator: BinaryOperator, left: f64, right: f64) -> LuaValue {
+        let integers = is_integer(left) && is_integer(right);
+
+        match operator {
+            BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Asterisk => {
+                let result = match operator {
+                    BinaryOperator::Plus => left + right,
+                    BinaryOperator::Minus => left - right,
+                    _ => left * right,
+                };
+                if integers {
+                    integer_result(result)
+                } else {
+                    float_result(result)
+                }
+            }
+            BinaryOperator::Slash => float_result(left / right),
+            BinaryOperator::Caret => float_result(left.powf(right)),
+            BinaryOperator::DoubleSlash => {
+                if integers {
+                    if right == 0.0 {
+                        // integer division by zero is an error
+                        LuaValue::Unknown
+                    } else {
+                        integer_result((left / right).floor())
+                    }
+                } else {
+                    float_result((left / right).floor())
+                }
+            }
+            BinaryOperator::Percent => {
+                if integers {
+                    if right == 0.0 {
+                        LuaValue::Unknown
+                    } else {
+                        integer_result(left - right * (left / right).floor())
+                    }
+                } else {
+                    let mut result = left % right;
+                    if result * right < 0.0 {
+                        result += right;
+                    }
+                    float_result(result)
+                }
+            }
+            _ => LuaValue::Unknown,
+        }
+    }
+
     fn evaluate_relational<F>(&self, expression: &BinaryExpression, operation: F) -> LuaValue
     where
         F: Fn(f64, f64) -> bool,
@@ -431,6 +694,228 @@ impl Evaluator {
         }
     }
 
+    fn evaluate_call(&self, call: &FunctionCall) -> LuaValue {
+        if call.get_method().is_some() {
+            return LuaValue::Unknown;
+        }
+
+        let name = match call.get_prefix() {
+            Prefix::Identifier(identifier) => identifier.get_name().as_str(),
+            _ => return LuaValue::Unknown,
+        };
+
+        let is_known_function = match name {
+            TOSTRING_FUNCTION => self.global_tostring,
+            TONUMBER_FUNCTION => self.global_tonumber,
+            _ => false,
+        };
+
+        if !is_known_function {
+            return LuaValue::Unknown;
+        }
+
+        let arguments: Vec<LuaValue> = match call.get_arguments() {
+            Arguments::String(string) => vec![LuaValue::from(string.get_value())],
+            Arguments::Tuple(tuple) => {
+                if tuple
+                    .iter_values()
+                    .any(|value| self.has_side_effects(value))
+                {
+                    return LuaValue::Unknown;
+                }
+                tuple
+                    .iter_values()
+                    .map(|value| self.evaluate(value))
+                    .collect()
+            }
+            Arguments::Table(_) => return LuaValue::Unknown,
+        };
+
+        match (name, arguments.as_slice()) {
+            (TOSTRING_FUNCTION, [value]) => self.evaluate_tostring(value),
+            (TONUMBER_FUNCTION, [value]) => self.evaluate_tonumber(value),
+            (TONUMBER_FUNCTION, [value, base]) => self.evaluate_tonumber_with_base(value, base),
+            _ => LuaValue::Unknown,
+        }
+    }
+
+    fn evaluate_tostring(&self, value: &LuaValue) -> LuaValue {
+        match value {
+            LuaValue::Nil => LuaValue::from("nil"),
+            LuaValue::True => LuaValue::from("true"),
+            LuaValue::False => LuaValue::from("false"),
+            LuaValue::String(string) => LuaValue::from(string.as_str()),
+            LuaValue::Number(number) => self
+                .format_number(*number)
+                .map(LuaValue::String)
+                .unwrap_or(LuaValue::Unknown),
+            LuaValue::Function | LuaValue::Table | LuaValue::Unknown => LuaValue::Unknown,
+        }
+    }
+
+    /// Formats a number the same way `tostring` does in the dialect, or returns `None` when
+    /// the result is not certain.
+    fn format_number(&self, value: f64) -> Option<String> {
+        match self.dialect {
+            LuaDialect::Luau => format_luau_number(value),
+            LuaDialect::Lua51 => format_lua_number(value),
+            LuaDialect::Lua53 => {
+                if value.is_finite() && value.fract() == 0.0 {
+                    // numbers without a fractional part are integers (see `compute_lua53_math`)
+                    if value.is_sign_negative() && value == 0.0 {
+                        // `-0` can be the integer `0` or the float `-0.0`
+                        None
+                    } else if is_integer(value) && writes_integer_literal(value) {
+                        Some(format!("{}", value))
+                    } else {
+                        None
+                    }
+                } else {
+                    // floats that look like integers are written with a `.0` suffix
+                    format_lua_number(value).map(|mut formatted| {
+                        if formatted.chars().all(|c| c == '-' || c.is_ascii_digit()) {
+                            formatted.push_str(".0");
+                        }
+                        formatted
+                    })
+                }
+            }
+        }
+    }
+
+    fn evaluate_tonumber(&self, value: &LuaValue) -> LuaValue {
+        match value {
+            LuaValue::Number(number) => LuaValue::Number(*number),
+            LuaValue::String(string) => self.convert_to_number(string),
+            LuaValue::Nil | LuaValue::True | LuaValue::False => LuaValue::Nil,
+            LuaValue::Function | LuaValue::Table | LuaValue::Unknown => LuaValue::Unknown,
+        }
+    }
+
+    /// Converts a string to a number like `tonumber` does. Strings that are not accepted the
+    /// same way by every dialect (like signed hexadecimal numbers, `inf` or `nan`) evaluate
+    /// to an unknown value.
+    fn convert_to_number(&self, string: &str) -> LuaValue {
+        let trimmed = string.trim_matches(is_lua_whitespace);
+        let (negative, unsigned) = match trimmed.as_bytes().first() {
+            Some(b'-') => (true, &trimmed[1..]),
+            Some(b'+') => (false, &trimmed[1..]),
+            _ => (false, trimmed),
+        };
+
+        if let Some(digits) = unsigned
+            .strip_prefix("0x")
+            .or_else(|| unsigned.strip_prefix("0X"))
+        {
+            if unsigned.len() != trimmed.len()
+                || digits.is_empty()
+                || !digits.chars().all(|c| c.is_ascii_hexdigit())
+            {
+                return LuaValue::Unknown;
+            }
+
+            return match u64::from_str_radix(digits, 16) {
+                Ok(value) if value as f64 <= MAX_SAFE_INTEGER => self.integer_value(value as f64),
+                _ => LuaValue::Unknown,
+            };
+        }
+
+        if is_decimal_number(unsigned) {
+            return match unsigned.parse::<f64>() {
+                Ok(value) => {
+                    let value = if negative { -value } else { value };
+
+                    match self.dialect {
+                        LuaDialect::Lua51 | LuaDialect::Luau => LuaValue::Number(value),
+                        LuaDialect::Lua53 => {
+                            if unsigned.contains(|c: char| matches!(c, '.' | 'e' | 'E')) {
+                                float_result(value)
+                            } else {
+                                integer_result(value)
+                            }
+                        }
+                    }
+                }
+                Err(_) => LuaValue::Unknown,
+            };
+        }
+
+        let has_invalid_character = trimmed
+            .chars()
+            .any(|c| !c.is_ascii_alphanumeric() && !matches!(c, '.' | '+' | '-' | '_'));
+        // `inf` and `nan` are accepted by Lua 5.1
+        let starts_with_letter = unsigned
+            .starts_with(|c: char| c.is_ascii_alphabetic() && !matches!(c, 'i' | 'I' | 'n' | 'N'));
+
+        if trimmed.is_empty() || has_invalid_character || starts_with_letter {
+            LuaValue::Nil
+        } else {
+            LuaValue::Unknown
+        }
+    }
+
+    fn evaluate_tonumber_with_base(&self, value: &LuaValue, base: &LuaValue) -> LuaValue {
+        let base = match base {
+            LuaValue::Number(base) if base.fract() == 0.0 && (2.0..=36.0).contains(base) => {
+                *base as u32
+            }
+            _ => return LuaValue::Unknown,
+        };
+
+        let string = match value {
+            LuaValue::String(string) => string,
+            _ => return LuaValue::Unknown,
+        };
+
+        // Lua 5.1 and Luau read decimal numbers the same way with or without a base of 10
+        if base == 10 && self.dialect != LuaDialect::Lua53 {
+            return self.convert_to_number(string);
+        }
+
+        let digits = string.trim_matches(is_lua_whitespace);
+
+        // the `0x` prefix is only accepted by Lua 5.1 and Luau
+        if base == 16 && (digits.starts_with("0x") || digits.starts_with("0X")) {
+            return LuaValue::Unknown;
+        }
+
+        let mut result: u64 = 0;
+
+        for c in digits.chars() {
+            if matches!(c, '-' | '+') {
+                return LuaValue::Unknown;
+            }
+
+            let digit = match c.to_digit(base) {
+                Some(digit) => digit,
+                None => return LuaValue::Nil,
+            };
+
+            result = match result
+                .checked_mul(u64::from(base))
+                .and_then(|result| result.checked_add(u64::from(digit)))
+            {
+                Some(result) => result,
+                None => return LuaValue::Unknown,
+            };
+        }
+
+        if digits.is_empty() {
+            LuaValue::Nil
+        } else if result as f64 <= MAX_SAFE_INTEGER {
+            self.integer_value(result as f64)
+        } else {
+            LuaValue::Unknown
+        }
+    }
+
+    fn integer_value(&self, value: f64) -> LuaValue {
+        match self.dialect {
+            LuaDialect::Lua51 | LuaDialect::Luau => LuaValue::Number(value),
+            LuaDialect::Lua53 => integer_result(value),
+        }
+    }
+
     fn evaluate_if(&self, expression: &IfExpression) -> LuaValue {
         let condition = self.evaluate(expression.get_condition());
 
@@ -457,6 +942,143 @@ impl Evaluator {
     }
 }
 
+#[inline]
+fn is_integer(value: f64) -> bool {
+    value.is_finite() && value.fract() == 0.0 && value.abs() <= MAX_SAFE_INTEGER
+}
+
+/// Returns true if Lua 5.3 reads the number literal as a float.
+fn is_float_literal(number: &NumberExpression) -> bool {
+    match number {
+        NumberExpression::Decimal(number) => {
+            number.has_decimal_point() || number.get_exponent().is_some()
+        }
+        NumberExpression::Hex(number) => number.get_exponent().is_some(),
+        NumberExpression::Binary(_) => false,
+    }
+}
+
+/// Returns the value only if it is written back as an integer literal.
+fn integer_result(value: f64) -> LuaValue {
+    if is_integer(value) && writes_integer_literal(value) {
+        LuaValue::Number(value)
+    } else {
+        LuaValue::Unknown
+    }
+}
+
+/// Returns the value only if it can't be confused with an integer once written back.
+fn float_result(value: f64) -> LuaValue {
+    if value.is_finite() && value.fract() == 0.0 {
+        LuaValue::Unknown
+    } else {
+        LuaValue::Number(value)
+    }
+}
+
+/// Formats a number the same way Luau's `tostring` does. Luau prints the shortest
+/// representation that round-trips, but switches to scientific notation for very large or
+/// very small numbers: only the range where the output is certain to match is handled.
+fn format_luau_number(value: f64) -> Option<String> {
+    if !value.is_finite() {
+        return None;
+    }
+
+    let magnitude = value.abs();
+    if value.fract() == 0.0 {
+        if magnitude <= MAX_SAFE_INTEGER {
+            Some(format!("{}", value))
+        } else {
+            None
+        }
+    } else if (1e-4..1e15).contains(&magnitude) {
+        Some(format!("{}", value))
+    } else {
+        None
+    }
+}
+
+/// Formats a number like the `%.14g` format used by Lua 5.1 and Lua 5.3 to convert
+/// numbers to strings.
+fn format_lua_number(value: f64) -> Option<String> {
+    if !value.is_finite() {
+        return None;
+    }
+
+    // a value that is halfway between two numbers with 14 digits may not be rounded the
+    // same way as the C library does
+    let precise = format!("{:.*e}", LUA_NUMBER_PRECISION + 5, value);
+    if precise.split_once('e')?.0.ends_with("500000") {
+        return None;
+    }
+
+    let scientific = format!("{:.*e}", LUA_NUMBER_PRECISION - 1, value);
+    let (mantissa, exponent) = scientific.split_once('e')?;
+    let exponent: i32 = exponent.parse().ok()?;
+
+    if exponent < -4 || exponent >= LUA_NUMBER_PRECISION as i32 {
+        Some(format!(
+            "{}e{}{:02}",
+            trim_fraction_zeros(mantissa),
+            if exponent < 0 { '-' } else { '+' },
+            exponent.abs()
+        ))
+    } else {
+        let decimals = (LUA_NUMBER_PRECISION as i32 - 1 - exponent) as usize;
+        Some(trim_fraction_zeros(&format!("{:.*}", decimals, value)).to_owned())
+    }
+}
+
+fn trim_fraction_zeros(number: &str) -> &str {
+    if number.contains('.') {
+        number.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        number
+    }
+}
+
+#[inline]
+fn is_lua_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r' | '\x0b' | '\x0c')
+}
+
+/// Returns `true` for decimal numbers with an optional fractional part and exponent (like
+/// `12`, `.5`, `1.` or `1e-3`), without sign.
+fn is_decimal_number(string: &str) -> bool {
+    let (mantissa, exponent) = match string.find(|c: char| matches!(c, 'e' | 'E')) {
+        Some(index) => (&string[..index], Some(&string[index + 1..])),
+        None => (string, None),
+    };
+
+    let mut parts = mantissa.splitn(2, '.');
+    let integer = parts.next().unwrap_or_default();
+    let fraction = parts.next().unwrap_or_default();
+
+    let is_digits = |digits: &str| digits.chars().all(|c| c.is_ascii_digit());
+
+    let valid_mantissa =
+        is_digits(integer) && is_digits(fraction) && !(integer.is_empty() && fraction.is_empty());
+
+    let valid_exponent = match exponent {
+        Some(exponent) => {
+            let digits = exponent
+                .strip_prefix(|c: char| matches!(c, '-' | '+'))
+                .unwrap_or(exponent);
+            !digits.is_empty() && is_digits(digits)
+        }
+        None => true,
+    };
+
+    valid_mantissa && valid_exponent
+}
+
+fn writes_integer_literal(value: f64) -> bool {
+    match Expression::from(value.abs()) {
+        Expression::Number(NumberExpression::Decimal(number)) => number.get_exponent().is_none(),
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -503,6 +1125,37 @@ mod test {
                 .with_segment(Expression::from(true))
                 .with_segment("?")
         ) => LuaValue::String("variable = true?".to_owned()),
+        interpolated_string_expression_with_number_segment(
+            InterpolatedStringExpression::empty()
+                .with_segment("value: ")
+                .with_segment(BinaryExpression::new(BinaryOperator::Plus, 1.0, 2.0))
+        ) => LuaValue::String("value: 3".to_owned()),
+        interpolated_string_expression_with_decimal_number_segment(
+            InterpolatedStringExpression::empty().with_segment(Expression::from(0.5))
+        ) => LuaValue::String("0.5".to_owned()),
+        interpolated_string_expression_with_negative_zero_segment(
+            InterpolatedStringExpression::empty().with_segment(Expression::from(-0.0))
+        ) => LuaValue::String("-0".to_owned()),
+        interpolated_string_expression_with_concat_segment(
+            InterpolatedStringExpression::empty()
+                .with_segment("<")
+                .with_segment(BinaryExpression::new(
+                    BinaryOperator::Concat,
+                    StringExpression::from_value("a"),
+                    StringExpression::from_value("b"),
+                ))
+                .with_segment(">")
+        ) => LuaValue::String("<ab>".to_owned()),
+        interpolated_string_expression_with_infinity_segment(
+            InterpolatedStringExpression::empty()
+                .with_segment(BinaryExpression::new(BinaryOperator::Slash, 1.0, 0.0))
+        ) => LuaValue::Unknown,
+        interpolated_string_expression_with_huge_number_segment(
+            InterpolatedStringExpression::empty().with_segment(Expression::from(1e20))
+        ) => LuaValue::Unknown,
+        interpolated_string_expression_with_table_segment(
+            InterpolatedStringExpression::empty().with_segment(TableExpression::default())
+        ) => LuaValue::Unknown,
         interpolated_string_expression_with_mixed_segments_unknown(
             InterpolatedStringExpression::empty()
                 .with_segment("variable = ")
@@ -517,6 +1170,14 @@ mod test {
         string_wrapped_in_parens(ParentheseExpression::new(StringExpression::from_value("foo")))
             => LuaValue::from("foo"),
         table_expression(TableExpression::default()) => LuaValue::Table,
+        identifier_expression(Expression::identifier("var")) => LuaValue::Unknown,
+        call_expression(FunctionCall::from_name("foo")) => LuaValue::Unknown,
+        field_expression_on_unknown_table(FieldExpression::new(Identifier::new("var"), "field"))
+            => LuaValue::Unknown,
+        index_expression_on_unknown_table(
+            IndexExpression::new(Identifier::new("var"), Expression::from(1.0))
+        ) => LuaValue::Unknown,
+        variable_arguments_expression(Expression::variable_arguments()) => LuaValue::Unknown,
         if_expression_always_true(IfExpression::new(true, 1.0, 0.0)) => LuaValue::from(1.0),
         if_expression_always_false(IfExpression::new(false, 1.0, 0.0)) => LuaValue::from(0.0),
         if_expression_unknown_condition(IfExpression::new(Expression::identifier("test"), 1.0, 0.0))
@@ -1025,6 +1686,134 @@ mod test {
         );
     }
 
+    mod dialects {
+        use super::*;
+
+        macro_rules! evaluate_with_dialect {
+            ($($name:ident ($left:expr, $operator:ident, $right:expr) => {
+                $( $dialect:ident => $expect:expr ),* $(,)?
+            }),* $(,)?) => {
+                $(
+                    mod $name {
+                        use super::*;
+
+                        $(
+                            #[test]
+                            #[allow(non_snake_case)]
+                            fn $dialect() {
+                                let binary = BinaryExpression::new(
+                                    BinaryOperator::$operator,
+                                    $left,
+                                    $right,
+                                );
+
+                                let result = Evaluator::default()
+                                    .with_dialect(LuaDialect::$dialect)
+                                    .evaluate(&binary.into());
+
+                                match (&$expect, &result) {
+                                    (LuaValue::Number(expect), LuaValue::Number(result))
+                                        if expect.is_nan() =>
+                                    {
+                                        assert!(result.is_nan(), "{} should be NaN", result);
+                                    }
+                                    _ => assert_eq!($expect, result),
+                                }
+                            }
+                        )*
+                    }
+                )*
+            };
+        }
+
+        evaluate_with_dialect!(
+            minus_seven_floor_division_by_two(-7.0, DoubleSlash, 2.0) => {
+                Lua51 => LuaValue::Unknown,
+                Lua53 => LuaValue::Number(-4.0),
+                Luau => LuaValue::Number(-4.0),
+            },
+            seven_mod_minus_two(7.0, Percent, -2.0) => {
+                Lua51 => LuaValue::Number(-1.0),
+                Lua53 => LuaValue::Number(-1.0),
+                Luau => LuaValue::Number(-1.0),
+            },
+            seven_point_five_mod_minus_two(7.5, Percent, -2.0) => {
+                Lua51 => LuaValue::Number(-0.5),
+                Lua53 => LuaValue::Number(-0.5),
+                Luau => LuaValue::Number(-0.5),
+            },
+            one_divided_by_zero(1.0, Slash, 0.0) => {
+                Lua51 => LuaValue::Number(f64::INFINITY),
+                Lua53 => LuaValue::Number(f64::INFINITY),
+                Luau => LuaValue::Number(f64::INFINITY),
+            },
+            minus_one_divided_by_zero(-1.0, Slash, 0.0) => {
+                Lua51 => LuaValue::Number(f64::NEG_INFINITY),
+                Lua53 => LuaValue::Number(f64::NEG_INFINITY),
+                Luau => LuaValue::Number(f64::NEG_INFINITY),
+            },
+            one_floor_division_by_zero(1.0, DoubleSlash, 0.0) => {
+                Lua51 => LuaValue::Unknown,
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Number(f64::INFINITY),
+            },
+            one_point_five_floor_division_by_zero(1.5, DoubleSlash, 0.0) => {
+                Lua51 => LuaValue::Unknown,
+                Lua53 => LuaValue::Number(f64::INFINITY),
+                Luau => LuaValue::Number(f64::INFINITY),
+            },
+            one_mod_zero(1.0, Percent, 0.0) => {
+                Lua51 => LuaValue::Number(f64::NAN),
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Number(f64::NAN),
+            },
+            six_divided_by_two(6.0, Slash, 2.0) => {
+                Lua51 => LuaValue::Number(3.0),
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Number(3.0),
+            },
+            seven_divided_by_two(7.0, Slash, 2.0) => {
+                Lua51 => LuaValue::Number(3.5),
+                Lua53 => LuaValue::Number(3.5),
+                Luau => LuaValue::Number(3.5),
+            },
+            two_pow_three(2.0, Caret, 3.0) => {
+                Lua51 => LuaValue::Number(8.0),
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Number(8.0),
+            },
+            written_with_exponent(1000.0, Asterisk, 10.0) => {
+                Lua51 => LuaValue::Number(10000.0),
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Number(10000.0),
+            },
+            string_plus_number(StringExpression::from_value("10"), Plus, 1.0) => {
+                Lua51 => LuaValue::Number(11.0),
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Number(11.0),
+            },
+            float_one_plus_two(DecimalNumber::new(1.0).with_decimal_point(), Plus, 2.0) => {
+                Lua51 => LuaValue::Number(3.0),
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Number(3.0),
+            },
+            float_three_floor_division_by_two(
+                DecimalNumber::new(3.0).with_decimal_point(),
+                DoubleSlash,
+                2.0
+            ) => {
+                Lua51 => LuaValue::Unknown,
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Number(1.0),
+            },
+            two_pow_two(2.0, Caret, 2.0) => {
+                Lua51 => LuaValue::Number(4.0),
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Number(4.0),
+            },
+        );
+    }
+
     mod unary_expressions {
         use super::*;
         use UnaryOperator::*;
@@ -1106,6 +1895,15 @@ mod test {
         unary_minus_on_variable => UnaryExpression::new(UnaryOperator::Minus, Identifier::new("var")),
         length_on_variable => UnaryExpression::new(UnaryOperator::Length, Identifier::new("var")),
         field_index => FieldExpression::new(Identifier::new("var"), "field"),
+        index_unknown_table => IndexExpression::new(Identifier::new("var"), Expression::from(1.0)),
+        index_unknown_table_with_string => IndexExpression::new(
+            Identifier::new("var"),
+            StringExpression::from_value("key"),
+        ),
+        index_with_call_key => IndexExpression::new(
+            Identifier::new("var"),
+            FunctionCall::from_name("key"),
+        ),
         table_value_with_call_in_entry => TableExpression::default()
             .append_array_value(FunctionCall::from_name("call")),
 
@@ -1168,6 +1966,311 @@ mod test {
             length_on_variable => UnaryExpression::new(UnaryOperator::Length, Identifier::new("var")),
             not_on_variable => UnaryExpression::new(UnaryOperator::Not, Identifier::new("var")),
             field_index => FieldExpression::new(Identifier::new("var"), "field"),
+            index_unknown_table => IndexExpression::new(Identifier::new("var"), Expression::from(1.0)),
+        );
+
+        #[test]
+        fn index_with_call_key_has_side_effects() {
+            let evaluator = Evaluator::default().assume_pure_metamethods();
+            let index =
+                IndexExpression::new(Identifier::new("var"), FunctionCall::from_name("key"));
+            assert!(evaluator.has_side_effects(&index.into()));
+        }
+    }
+
+    mod global_functions {
+        use super::*;
+
+        fn tostring(value: impl Into<Expression>) -> FunctionCall {
+            FunctionCall::from_name("tostring").with_argument(value)
+        }
+
+        fn tonumber(value: &str) -> FunctionCall {
+            FunctionCall::from_name("tonumber").with_argument(StringExpression::from_value(value))
+        }
+
+        fn tonumber_with_base(value: &str, base: f64) -> FunctionCall {
+            tonumber(value).with_argument(base)
+        }
+
+        fn string(value: &str) -> Expression {
+            StringExpression::from_value(value).into()
+        }
+
+        macro_rules! evaluate_calls {
+            ($($name:ident ($call:expr) => {
+                $( $dialect:ident => $expect:expr ),* $(,)?
+            }),* $(,)?) => {
+                $(
+                    mod $name {
+                        use super::*;
+
+                        $(
+                            #[test]
+                            #[allow(non_snake_case)]
+                            fn $dialect() {
+                                let result = Evaluator::default()
+                                    .with_dialect(LuaDialect::$dialect)
+                                    .assume_global_tostring()
+                                    .assume_global_tonumber()
+                                    .evaluate(&$call.into());
+
+                                assert_eq!($expect, result);
+                            }
+                        )*
+                    }
+                )*
+            };
+        }
+
+        evaluate_calls!(
+            tostring_integer(tostring(42.0)) => {
+                Lua51 => LuaValue::from("42"),
+                Lua53 => LuaValue::from("42"),
+                Luau => LuaValue::from("42"),
+            },
+            tostring_true(tostring(true)) => {
+                Lua51 => LuaValue::from("true"),
+                Lua53 => LuaValue::from("true"),
+                Luau => LuaValue::from("true"),
+            },
+            tostring_nil(tostring(Expression::nil())) => {
+                Lua51 => LuaValue::from("nil"),
+                Lua53 => LuaValue::from("nil"),
+                Luau => LuaValue::from("nil"),
+            },
+            tostring_string(tostring(string("abc"))) => {
+                Lua51 => LuaValue::from("abc"),
+                Lua53 => LuaValue::from("abc"),
+                Luau => LuaValue::from("abc"),
+            },
+            tostring_simple_float(tostring(0.5)) => {
+                Lua51 => LuaValue::from("0.5"),
+                Lua53 => LuaValue::from("0.5"),
+                Luau => LuaValue::from("0.5"),
+            },
+            tostring_one_third(tostring(BinaryExpression::new(BinaryOperator::Slash, 1.0, 3.0))) => {
+                Lua51 => LuaValue::from("0.33333333333333"),
+                Lua53 => LuaValue::from("0.33333333333333"),
+                Luau => LuaValue::from("0.3333333333333333"),
+            },
+            tostring_float_rounded_to_integer(tostring(1.000000000000001)) => {
+                Lua51 => LuaValue::from("1"),
+                Lua53 => LuaValue::from("1.0"),
+                Luau => LuaValue::from("1.000000000000001"),
+            },
+            tostring_small_float(tostring(0.00001)) => {
+                Lua51 => LuaValue::from("1e-05"),
+                Lua53 => LuaValue::from("1e-05"),
+                Luau => LuaValue::Unknown,
+            },
+            tostring_large_number(tostring(1e100)) => {
+                Lua51 => LuaValue::from("1e+100"),
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Unknown,
+            },
+            tostring_large_integer(tostring(123_456_789_012_346.0)) => {
+                Lua51 => LuaValue::from("1.2345678901235e+14"),
+                Lua53 => LuaValue::from("123456789012346"),
+                Luau => LuaValue::from("123456789012346"),
+            },
+            tostring_halfway_between_fourteen_digits(tostring(123_456_789_012_345.0)) => {
+                Lua51 => LuaValue::Unknown,
+                Lua53 => LuaValue::from("123456789012345"),
+                Luau => LuaValue::from("123456789012345"),
+            },
+            tostring_infinity(tostring(BinaryExpression::new(BinaryOperator::Slash, 1.0, 0.0))) => {
+                Lua51 => LuaValue::Unknown,
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Unknown,
+            },
+            tostring_without_argument(FunctionCall::from_name("tostring")) => {
+                Lua51 => LuaValue::Unknown,
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Unknown,
+            },
+            tostring_unknown_value(tostring(Expression::identifier("value"))) => {
+                Lua51 => LuaValue::Unknown,
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Unknown,
+            },
+            tostring_table(tostring(TableExpression::default())) => {
+                Lua51 => LuaValue::Unknown,
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Unknown,
+            },
+            tonumber_integer(tonumber("12")) => {
+                Lua51 => LuaValue::Number(12.0),
+                Lua53 => LuaValue::Number(12.0),
+                Luau => LuaValue::Number(12.0),
+            },
+            tonumber_with_spaces(tonumber("  -12\n")) => {
+                Lua51 => LuaValue::Number(-12.0),
+                Lua53 => LuaValue::Number(-12.0),
+                Luau => LuaValue::Number(-12.0),
+            },
+            tonumber_float(tonumber("1.5")) => {
+                Lua51 => LuaValue::Number(1.5),
+                Lua53 => LuaValue::Number(1.5),
+                Luau => LuaValue::Number(1.5),
+            },
+            tonumber_float_without_fraction(tonumber("10.0")) => {
+                Lua51 => LuaValue::Number(10.0),
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Number(10.0),
+            },
+            tonumber_exponent(tonumber("2.5e-1")) => {
+                Lua51 => LuaValue::Number(0.25),
+                Lua53 => LuaValue::Number(0.25),
+                Luau => LuaValue::Number(0.25),
+            },
+            tonumber_hex(tonumber("0x10")) => {
+                Lua51 => LuaValue::Number(16.0),
+                Lua53 => LuaValue::Number(16.0),
+                Luau => LuaValue::Number(16.0),
+            },
+            tonumber_uppercase_hex(tonumber("0XfF")) => {
+                Lua51 => LuaValue::Number(255.0),
+                Lua53 => LuaValue::Number(255.0),
+                Luau => LuaValue::Number(255.0),
+            },
+            tonumber_negative_hex(tonumber("-0x10")) => {
+                Lua51 => LuaValue::Unknown,
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Unknown,
+            },
+            tonumber_word(tonumber("hello")) => {
+                Lua51 => LuaValue::Nil,
+                Lua53 => LuaValue::Nil,
+                Luau => LuaValue::Nil,
+            },
+            tonumber_empty_string(tonumber("")) => {
+                Lua51 => LuaValue::Nil,
+                Lua53 => LuaValue::Nil,
+                Luau => LuaValue::Nil,
+            },
+            tonumber_with_space_between_digits(tonumber("1 2")) => {
+                Lua51 => LuaValue::Nil,
+                Lua53 => LuaValue::Nil,
+                Luau => LuaValue::Nil,
+            },
+            tonumber_infinity(tonumber("inf")) => {
+                Lua51 => LuaValue::Unknown,
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Unknown,
+            },
+            tonumber_number(FunctionCall::from_name("tonumber").with_argument(7.0)) => {
+                Lua51 => LuaValue::Number(7.0),
+                Lua53 => LuaValue::Number(7.0),
+                Luau => LuaValue::Number(7.0),
+            },
+            tonumber_boolean(FunctionCall::from_name("tonumber").with_argument(true)) => {
+                Lua51 => LuaValue::Nil,
+                Lua53 => LuaValue::Nil,
+                Luau => LuaValue::Nil,
+            },
+            tonumber_octal(tonumber_with_base("12", 8.0)) => {
+                Lua51 => LuaValue::Number(10.0),
+                Lua53 => LuaValue::Number(10.0),
+                Luau => LuaValue::Number(10.0),
+            },
+            tonumber_hex_base(tonumber_with_base("ff", 16.0)) => {
+                Lua51 => LuaValue::Number(255.0),
+                Lua53 => LuaValue::Number(255.0),
+                Luau => LuaValue::Number(255.0),
+            },
+            tonumber_base_36(tonumber_with_base("Z", 36.0)) => {
+                Lua51 => LuaValue::Number(35.0),
+                Lua53 => LuaValue::Number(35.0),
+                Luau => LuaValue::Number(35.0),
+            },
+            tonumber_invalid_digit(tonumber_with_base("8", 8.0)) => {
+                Lua51 => LuaValue::Nil,
+                Lua53 => LuaValue::Nil,
+                Luau => LuaValue::Nil,
+            },
+            tonumber_float_with_base_10(tonumber_with_base("1.5", 10.0)) => {
+                Lua51 => LuaValue::Number(1.5),
+                Lua53 => LuaValue::Nil,
+                Luau => LuaValue::Number(1.5),
+            },
+            tonumber_hex_prefix_with_base(tonumber_with_base("0x10", 16.0)) => {
+                Lua51 => LuaValue::Unknown,
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Unknown,
+            },
+            tonumber_invalid_base(tonumber_with_base("1", 37.0)) => {
+                Lua51 => LuaValue::Unknown,
+                Lua53 => LuaValue::Unknown,
+                Luau => LuaValue::Unknown,
+            },
+            tostring_of_tonumber(tostring(tonumber("0x10"))) => {
+                Lua51 => LuaValue::from("16"),
+                Lua53 => LuaValue::from("16"),
+                Luau => LuaValue::from("16"),
+            },
+        );
+
+        #[test]
+        fn tostring_is_unknown_without_assuming_global_function() {
+            assert_eq!(
+                Evaluator::default().evaluate(&tostring(42.0).into()),
+                LuaValue::Unknown
+            );
+        }
+
+        #[test]
+        fn tonumber_is_unknown_without_assuming_global_function() {
+            let evaluator = Evaluator::default().assume_global_tostring();
+
+            assert_eq!(evaluator.evaluate(&tonumber("1").into()), LuaValue::Unknown);
+        }
+
+        #[test]
+        fn computed_call_does_not_have_side_effects() {
+            let evaluator = Evaluator::default().assume_global_tostring();
+
+            assert!(!evaluator.has_side_effects(&tostring(42.0).into()));
+        }
+
+        #[test]
+        fn call_with_side_effects_in_argument_has_side_effects() {
+            let evaluator = Evaluator::default().assume_global_tostring();
+
+            assert!(evaluator.has_side_effects(&tostring(FunctionCall::from_name("f")).into()));
+        }
+    }
+
+    mod is_constant {
+        use super::*;
+
+        macro_rules! is_constant {
+            ($($name:ident ($expression:expr) => $value:expr),* $(,)?) => {
+                $(
+                    #[test]
+                    fn $name() {
+                        assert_eq!(Evaluator::default().is_constant(&$expression.into()), $value);
+                    }
+                )*
+            };
+        }
+
+        is_constant!(
+            true_value(Expression::from(true)) => true,
+            nil_value(Expression::nil()) => true,
+            number_value(Expression::from(1.0)) => true,
+            string_value(StringExpression::from_value("foo")) => true,
+            addition(BinaryExpression::new(BinaryOperator::Plus, 1.0, 2.0)) => true,
+            table_value(TableExpression::default()) => false,
+            function_value(FunctionExpression::default()) => false,
+            identifier(Expression::identifier("var")) => false,
+            call(FunctionCall::from_name("foo")) => false,
+            index_unknown_table(IndexExpression::new(Identifier::new("var"), Expression::from(1.0))) => false,
+            false_and_call(BinaryExpression::new(
+                BinaryOperator::And,
+                Expression::from(false),
+                FunctionCall::from_name("foo"),
+            )) => true,
         );
     }
 }
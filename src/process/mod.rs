@@ -1,5 +1,7 @@
 //! Defines how rules can process and mutate Lua nodes.
 
+mod binding_table;
+mod budget;
 mod evaluator;
 mod expression_serializer;
 #[cfg(test)]
@@ -11,12 +13,13 @@ mod scope_visitor;
 pub(crate) mod utils;
 mod visitors;
 
+pub use binding_table::*;
+pub(crate) use budget::{consume_budget_step, track_budget};
 pub use evaluator::*;
 pub(crate) use expression_serializer::*;
 #[cfg(test)]
 pub use node_counter::NodeCounter;
 pub use node_processor::{NodePostProcessor, NodeProcessor};
 pub use post_visitor::{DefaultPostVisitor, NodePostVisitor};
-pub(crate) use scope_visitor::IdentifierTracker;
-pub use scope_visitor::{Scope, ScopeVisitor};
+pub use scope_visitor::{IdentifierTracker, Scope, ScopeVisitor};
 pub use visitors::{DefaultVisitor, NodeVisitor};
@@ -2,7 +2,23 @@ use crate::nodes::*;
 
 /// Used by the NodeVisitor trait, a NodeProcessor object is passed to each node to
 /// perform mutations.
+///
+/// Type annotations are visited like any other node: every type reached from a statement
+/// or an expression is passed to [`process_type`](NodeProcessor::process_type), before
+/// the method specific to its kind (like [`process_type_name`](NodeProcessor::process_type_name)).
 pub trait NodeProcessor {
+    /// Called by visitors right after `process_statement` or `process_expression`. When it
+    /// returns `true`, the visitor does not descend into the children of that node.
+    fn skip_children(&mut self) -> bool {
+        false
+    }
+
+    /// Visitors check this method before visiting each statement and expression. Once it
+    /// returns `true`, the traversal ends without visiting the remaining nodes.
+    fn is_stopped(&self) -> bool {
+        false
+    }
+
     fn process_block(&mut self, _: &mut Block) {}
     fn process_scope(&mut self, _block: &mut Block, _extra: Option<&mut Expression>) {}
     fn process_statement(&mut self, _: &mut Statement) {}
@@ -14,7 +30,9 @@ pub trait NodeProcessor {
     fn process_do_statement(&mut self, _: &mut DoStatement) {}
     fn process_function_statement(&mut self, _: &mut FunctionStatement) {}
     fn process_generic_for_statement(&mut self, _: &mut GenericForStatement) {}
+    fn process_goto_statement(&mut self, _: &mut GotoStatement) {}
     fn process_if_statement(&mut self, _: &mut IfStatement) {}
+    fn process_label_statement(&mut self, _: &mut LabelStatement) {}
     fn process_last_statement(&mut self, _: &mut LastStatement) {}
     fn process_local_assign_statement(&mut self, _: &mut LocalAssignStatement) {}
     fn process_local_function_statement(&mut self, _: &mut LocalFunctionStatement) {}
@@ -75,7 +93,9 @@ pub trait NodePostProcessor {
     fn process_after_do_statement(&mut self, _: &mut DoStatement) {}
     fn process_after_function_statement(&mut self, _: &mut FunctionStatement) {}
     fn process_after_generic_for_statement(&mut self, _: &mut GenericForStatement) {}
+    fn process_after_goto_statement(&mut self, _: &mut GotoStatement) {}
     fn process_after_if_statement(&mut self, _: &mut IfStatement) {}
+    fn process_after_label_statement(&mut self, _: &mut LabelStatement) {}
     fn process_after_last_statement(&mut self, _: &mut LastStatement) {}
     fn process_after_local_assign_statement(&mut self, _: &mut LocalAssignStatement) {}
     fn process_after_local_function_statement(&mut self, _: &mut LocalFunctionStatement) {}
@@ -2,7 +2,10 @@ use std::marker::PhantomData;
 
 use crate::nodes::*;
 
-use super::node_processor::{NodePostProcessor, NodeProcessor};
+use super::{
+    budget::consume_budget_step,
+    node_processor::{NodePostProcessor, NodeProcessor},
+};
 
 /// Similar to the NodeVisitor, except that visits the AST using a NodePostVisitor, which
 /// makes it possible to run transforms when leaving a node.
@@ -21,8 +24,21 @@ pub trait NodePostVisitor<T: NodeProcessor + NodePostProcessor> {
     }
 
     fn visit_statement(statement: &mut Statement, processor: &mut T) {
+        if processor.is_stopped() || consume_budget_step() {
+            return;
+        }
+
         processor.process_statement(statement);
 
+        if processor.is_stopped() {
+            return;
+        }
+
+        if processor.skip_children() {
+            processor.process_after_statement(statement);
+            return;
+        }
+
         match statement {
             Statement::Assign(statement) => Self::visit_assign_statement(statement, processor),
             Statement::Do(statement) => Self::visit_do_statement(statement, processor),
@@ -32,7 +48,9 @@ pub trait NodePostVisitor<T: NodeProcessor + NodePostProcessor> {
             }
             Statement::Function(statement) => Self::visit_function_statement(statement, processor),
             Statement::GenericFor(statement) => Self::visit_generic_for(statement, processor),
+            Statement::Goto(statement) => Self::visit_goto_statement(statement, processor),
             Statement::If(statement) => Self::visit_if_statement(statement, processor),
+            Statement::Label(statement) => Self::visit_label_statement(statement, processor),
             Statement::LocalAssign(statement) => Self::visit_local_assign(statement, processor),
             Statement::TypeFunction(statement) => Self::visit_type_function(statement, processor),
             Statement::ExportTypeFunction(statement) => {
@@ -50,6 +68,10 @@ pub trait NodePostVisitor<T: NodeProcessor + NodePostProcessor> {
     }
 
     fn visit_last_statement(last_statement: &mut LastStatement, processor: &mut T) {
+        if processor.is_stopped() {
+            return;
+        }
+
         processor.process_last_statement(last_statement);
 
         if let LastStatement::Return(expressions) = last_statement {
@@ -61,8 +83,21 @@ pub trait NodePostVisitor<T: NodeProcessor + NodePostProcessor> {
     }
 
     fn visit_expression(expression: &mut Expression, processor: &mut T) {
+        if processor.is_stopped() {
+            return;
+        }
+
         processor.process_expression(expression);
 
+        if processor.is_stopped() {
+            return;
+        }
+
+        if processor.skip_children() {
+            processor.process_after_expression(expression);
+            return;
+        }
+
         match expression {
             Expression::Binary(expression) => {
                 Self::visit_binary_expression(expression, processor);
@@ -197,6 +232,16 @@ pub trait NodePostVisitor<T: NodeProcessor + NodePostProcessor> {
         processor.process_after_do_statement(statement);
     }
 
+    fn visit_goto_statement(statement: &mut GotoStatement, processor: &mut T) {
+        processor.process_goto_statement(statement);
+        processor.process_after_goto_statement(statement);
+    }
+
+    fn visit_label_statement(statement: &mut LabelStatement, processor: &mut T) {
+        processor.process_label_statement(statement);
+        processor.process_after_label_statement(statement);
+    }
+
     fn visit_compound_assign(statement: &mut CompoundAssignStatement, processor: &mut T) {
         processor.process_compound_assign_statement(statement);
         Self::visit_variable(statement.mutate_variable(), processor);
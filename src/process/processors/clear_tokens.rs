@@ -0,0 +1,235 @@
+use crate::nodes::*;
+use crate::process::NodeProcessor;
+
+/// A processor that removes the tokens of every node, as if they were created without
+/// parsing any code.
+#[derive(Debug, Default)]
+pub(crate) struct ClearTokensProcessor {}
+
+impl NodeProcessor for ClearTokensProcessor {
+    fn process_block(&mut self, block: &mut Block) {
+        block.clear_tokens();
+    }
+
+    fn process_function_call(&mut self, call: &mut FunctionCall) {
+        call.clear_tokens();
+    }
+
+    fn process_assign_statement(&mut self, assign: &mut AssignStatement) {
+        assign.clear_tokens();
+    }
+
+    fn process_compound_assign_statement(&mut self, assign: &mut CompoundAssignStatement) {
+        assign.clear_tokens();
+    }
+
+    fn process_do_statement(&mut self, statement: &mut DoStatement) {
+        statement.clear_tokens();
+    }
+
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        function.clear_tokens();
+    }
+
+    fn process_generic_for_statement(&mut self, generic_for: &mut GenericForStatement) {
+        generic_for.clear_tokens();
+    }
+
+    fn process_goto_statement(&mut self, goto: &mut GotoStatement) {
+        goto.clear_tokens();
+    }
+
+    fn process_if_statement(&mut self, if_statement: &mut IfStatement) {
+        if_statement.clear_tokens();
+    }
+
+    fn process_label_statement(&mut self, label: &mut LabelStatement) {
+        label.clear_tokens();
+    }
+
+    fn process_last_statement(&mut self, statement: &mut LastStatement) {
+        match statement {
+            LastStatement::Break(token) | LastStatement::Continue(token) => {
+                *token = None;
+            }
+            LastStatement::Return(statement) => statement.clear_tokens(),
+        }
+    }
+
+    fn process_local_assign_statement(&mut self, assign: &mut LocalAssignStatement) {
+        assign.clear_tokens();
+    }
+
+    fn process_local_function_statement(&mut self, function: &mut LocalFunctionStatement) {
+        function.clear_tokens();
+    }
+
+    fn process_type_function_statement(&mut self, function: &mut TypeFunctionStatement) {
+        function.clear_tokens();
+    }
+
+    fn process_export_type_function_statement(
+        &mut self,
+        function: &mut ExportTypeFunctionStatement,
+    ) {
+        function.clear_tokens();
+    }
+
+    fn process_numeric_for_statement(&mut self, numeric_for: &mut NumericForStatement) {
+        numeric_for.clear_tokens();
+    }
+
+    fn process_repeat_statement(&mut self, repeat: &mut RepeatStatement) {
+        repeat.clear_tokens();
+    }
+
+    fn process_while_statement(&mut self, statement: &mut WhileStatement) {
+        statement.clear_tokens();
+    }
+
+    fn process_type_declaration(&mut self, type_declaration: &mut TypeDeclarationStatement) {
+        type_declaration.clear_tokens();
+    }
+
+    fn process_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::False(token)
+            | Expression::Nil(token)
+            | Expression::True(token)
+            | Expression::VariableArguments(token) => {
+                *token = None;
+            }
+            Expression::Binary(_)
+            | Expression::Call(_)
+            | Expression::Field(_)
+            | Expression::Function(_)
+            | Expression::Identifier(_)
+            | Expression::If(_)
+            | Expression::Index(_)
+            | Expression::Number(_)
+            | Expression::Parenthese(_)
+            | Expression::String(_)
+            | Expression::InterpolatedString(_)
+            | Expression::Table(_)
+            | Expression::Unary(_)
+            | Expression::TypeCast(_) => {}
+        }
+    }
+
+    fn process_binary_expression(&mut self, binary: &mut BinaryExpression) {
+        binary.clear_tokens();
+    }
+
+    fn process_field_expression(&mut self, field: &mut FieldExpression) {
+        field.clear_tokens();
+    }
+
+    fn process_function_expression(&mut self, function: &mut FunctionExpression) {
+        function.clear_tokens();
+    }
+
+    fn process_if_expression(&mut self, if_expression: &mut IfExpression) {
+        if_expression.clear_tokens();
+    }
+
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        identifier.clear_tokens();
+    }
+
+    fn process_index_expression(&mut self, index: &mut IndexExpression) {
+        index.clear_tokens();
+    }
+
+    fn process_number_expression(&mut self, number: &mut NumberExpression) {
+        number.clear_tokens();
+    }
+
+    fn process_parenthese_expression(&mut self, expression: &mut ParentheseExpression) {
+        expression.clear_tokens();
+    }
+
+    fn process_string_expression(&mut self, string: &mut StringExpression) {
+        string.clear_tokens();
+    }
+
+    fn process_interpolated_string_expression(
+        &mut self,
+        string: &mut InterpolatedStringExpression,
+    ) {
+        string.clear_tokens();
+    }
+
+    fn process_table_expression(&mut self, table: &mut TableExpression) {
+        table.clear_tokens();
+    }
+
+    fn process_unary_expression(&mut self, unary: &mut UnaryExpression) {
+        unary.clear_tokens();
+    }
+
+    fn process_type_cast_expression(&mut self, type_cast: &mut TypeCastExpression) {
+        type_cast.clear_tokens();
+    }
+
+    fn process_type(&mut self, r#type: &mut Type) {
+        if let Type::True(token) | Type::False(token) | Type::Nil(token) = r#type {
+            *token = None;
+        }
+    }
+
+    fn process_type_name(&mut self, type_name: &mut TypeName) {
+        type_name.clear_tokens();
+    }
+
+    fn process_type_field(&mut self, type_field: &mut TypeField) {
+        type_field.clear_tokens();
+    }
+
+    fn process_string_type(&mut self, string_type: &mut StringType) {
+        string_type.clear_tokens();
+    }
+
+    fn process_array_type(&mut self, array: &mut ArrayType) {
+        array.clear_tokens();
+    }
+
+    fn process_table_type(&mut self, table: &mut TableType) {
+        table.clear_tokens();
+    }
+
+    fn process_expression_type(&mut self, expression_type: &mut ExpressionType) {
+        expression_type.clear_tokens();
+    }
+
+    fn process_parenthese_type(&mut self, parenthese_type: &mut ParentheseType) {
+        parenthese_type.clear_tokens();
+    }
+
+    fn process_function_type(&mut self, function_type: &mut FunctionType) {
+        function_type.clear_tokens();
+    }
+
+    fn process_optional_type(&mut self, optional: &mut OptionalType) {
+        optional.clear_tokens();
+    }
+
+    fn process_intersection_type(&mut self, intersection: &mut IntersectionType) {
+        intersection.clear_tokens();
+    }
+
+    fn process_union_type(&mut self, union: &mut UnionType) {
+        union.clear_tokens();
+    }
+
+    fn process_type_pack(&mut self, type_pack: &mut TypePack) {
+        type_pack.clear_tokens();
+    }
+
+    fn process_generic_type_pack(&mut self, generic_type_pack: &mut GenericTypePack) {
+        generic_type_pack.clear_tokens();
+    }
+
+    fn process_variadic_type_pack(&mut self, variadic_type_pack: &mut VariadicTypePack) {
+        variadic_type_pack.clear_tokens();
+    }
+}
@@ -0,0 +1,260 @@
+use crate::nodes::*;
+use crate::process::NodeProcessor;
+
+/// A processor that calls a function with every token of the visited nodes.
+pub(crate) struct ForEachTokenProcessor<F> {
+    callback: F,
+}
+
+impl<F: FnMut(&mut Token)> ForEachTokenProcessor<F> {
+    pub(crate) fn new(callback: F) -> Self {
+        Self { callback }
+    }
+}
+
+/// Creates a processor that adds a number of lines to every token position.
+pub(crate) fn shift_token_line_processor(
+    shift_amount: usize,
+) -> ForEachTokenProcessor<impl FnMut(&mut Token)> {
+    ForEachTokenProcessor::new(move |token: &mut Token| token.shift_token_line(shift_amount))
+}
+
+impl<F: FnMut(&mut Token)> NodeProcessor for ForEachTokenProcessor<F> {
+    fn process_block(&mut self, block: &mut Block) {
+        block.for_each_token(&mut self.callback);
+    }
+
+    fn process_function_call(&mut self, call: &mut FunctionCall) {
+        call.for_each_token(&mut self.callback);
+        call.mutate_arguments().for_each_token(&mut self.callback);
+    }
+
+    fn process_assign_statement(&mut self, assign: &mut AssignStatement) {
+        assign.for_each_token(&mut self.callback);
+    }
+
+    fn process_compound_assign_statement(&mut self, assign: &mut CompoundAssignStatement) {
+        assign.for_each_token(&mut self.callback);
+    }
+
+    fn process_do_statement(&mut self, statement: &mut DoStatement) {
+        statement.for_each_token(&mut self.callback);
+    }
+
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        function.for_each_token(&mut self.callback);
+    }
+
+    fn process_generic_for_statement(&mut self, generic_for: &mut GenericForStatement) {
+        generic_for.for_each_token(&mut self.callback);
+    }
+
+    fn process_goto_statement(&mut self, goto: &mut GotoStatement) {
+        goto.for_each_token(&mut self.callback);
+    }
+
+    fn process_if_statement(&mut self, if_statement: &mut IfStatement) {
+        if_statement.for_each_token(&mut self.callback);
+    }
+
+    fn process_label_statement(&mut self, label: &mut LabelStatement) {
+        label.for_each_token(&mut self.callback);
+    }
+
+    fn process_last_statement(&mut self, statement: &mut LastStatement) {
+        match statement {
+            LastStatement::Break(token) | LastStatement::Continue(token) => {
+                if let Some(token) = token {
+                    token.for_each_token(&mut self.callback);
+                }
+            }
+            LastStatement::Return(statement) => statement.for_each_token(&mut self.callback),
+        }
+    }
+
+    fn process_local_assign_statement(&mut self, assign: &mut LocalAssignStatement) {
+        assign.for_each_token(&mut self.callback);
+    }
+
+    fn process_local_function_statement(&mut self, function: &mut LocalFunctionStatement) {
+        function.for_each_token(&mut self.callback);
+    }
+
+    fn process_type_function_statement(&mut self, function: &mut TypeFunctionStatement) {
+        function.for_each_token(&mut self.callback);
+    }
+
+    fn process_export_type_function_statement(
+        &mut self,
+        function: &mut ExportTypeFunctionStatement,
+    ) {
+        function.for_each_token(&mut self.callback);
+    }
+
+    fn process_numeric_for_statement(&mut self, numeric_for: &mut NumericForStatement) {
+        numeric_for.for_each_token(&mut self.callback);
+    }
+
+    fn process_repeat_statement(&mut self, repeat: &mut RepeatStatement) {
+        repeat.for_each_token(&mut self.callback);
+    }
+
+    fn process_while_statement(&mut self, statement: &mut WhileStatement) {
+        statement.for_each_token(&mut self.callback);
+    }
+
+    fn process_type_declaration(&mut self, type_declaration: &mut TypeDeclarationStatement) {
+        type_declaration.for_each_token(&mut self.callback);
+    }
+
+    fn process_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::False(token)
+            | Expression::Nil(token)
+            | Expression::True(token)
+            | Expression::VariableArguments(token) => {
+                if let Some(token) = token {
+                    token.for_each_token(&mut self.callback)
+                }
+            }
+            Expression::Binary(_)
+            | Expression::Call(_)
+            | Expression::Field(_)
+            | Expression::Function(_)
+            | Expression::Identifier(_)
+            | Expression::If(_)
+            | Expression::Index(_)
+            | Expression::Number(_)
+            | Expression::Parenthese(_)
+            | Expression::String(_)
+            | Expression::InterpolatedString(_)
+            | Expression::Table(_)
+            | Expression::Unary(_)
+            | Expression::TypeCast(_) => {}
+        }
+    }
+
+    fn process_binary_expression(&mut self, binary: &mut BinaryExpression) {
+        binary.for_each_token(&mut self.callback);
+    }
+
+    fn process_field_expression(&mut self, field: &mut FieldExpression) {
+        field.for_each_token(&mut self.callback);
+    }
+
+    fn process_function_expression(&mut self, function: &mut FunctionExpression) {
+        function.for_each_token(&mut self.callback);
+    }
+
+    fn process_if_expression(&mut self, if_expression: &mut IfExpression) {
+        if_expression.for_each_token(&mut self.callback);
+    }
+
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        identifier.for_each_token(&mut self.callback);
+    }
+
+    fn process_index_expression(&mut self, index: &mut IndexExpression) {
+        index.for_each_token(&mut self.callback);
+    }
+
+    fn process_number_expression(&mut self, number: &mut NumberExpression) {
+        number.for_each_token(&mut self.callback);
+    }
+
+    fn process_parenthese_expression(&mut self, expression: &mut ParentheseExpression) {
+        expression.for_each_token(&mut self.callback);
+    }
+
+    fn process_string_expression(&mut self, string: &mut StringExpression) {
+        string.for_each_token(&mut self.callback);
+    }
+
+    fn process_interpolated_string_expression(
+        &mut self,
+        string: &mut InterpolatedStringExpression,
+    ) {
+        string.for_each_token(&mut self.callback);
+    }
+
+    fn process_table_expression(&mut self, table: &mut TableExpression) {
+        table.for_each_token(&mut self.callback);
+    }
+
+    fn process_unary_expression(&mut self, unary: &mut UnaryExpression) {
+        unary.for_each_token(&mut self.callback);
+    }
+
+    fn process_type_cast_expression(&mut self, type_cast: &mut TypeCastExpression) {
+        type_cast.for_each_token(&mut self.callback);
+    }
+
+    fn process_prefix_expression(&mut self, _: &mut Prefix) {}
+
+    fn process_type(&mut self, r#type: &mut Type) {
+        match r#type {
+            Type::True(token) | Type::False(token) | Type::Nil(token) => {
+                if let Some(token) = token {
+                    token.for_each_token(&mut self.callback);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn process_type_name(&mut self, type_name: &mut TypeName) {
+        type_name.for_each_token(&mut self.callback);
+    }
+
+    fn process_type_field(&mut self, type_field: &mut TypeField) {
+        type_field.for_each_token(&mut self.callback);
+    }
+
+    fn process_string_type(&mut self, string_type: &mut StringType) {
+        string_type.for_each_token(&mut self.callback);
+    }
+
+    fn process_array_type(&mut self, array: &mut ArrayType) {
+        array.for_each_token(&mut self.callback);
+    }
+
+    fn process_table_type(&mut self, table: &mut TableType) {
+        table.for_each_token(&mut self.callback);
+    }
+
+    fn process_expression_type(&mut self, expression_type: &mut ExpressionType) {
+        expression_type.for_each_token(&mut self.callback);
+    }
+
+    fn process_parenthese_type(&mut self, parenthese_type: &mut ParentheseType) {
+        parenthese_type.for_each_token(&mut self.callback);
+    }
+
+    fn process_function_type(&mut self, function_type: &mut FunctionType) {
+        function_type.for_each_token(&mut self.callback);
+    }
+
+    fn process_optional_type(&mut self, optional: &mut OptionalType) {
+        optional.for_each_token(&mut self.callback);
+    }
+
+    fn process_intersection_type(&mut self, intersection: &mut IntersectionType) {
+        intersection.for_each_token(&mut self.callback);
+    }
+
+    fn process_union_type(&mut self, union: &mut UnionType) {
+        union.for_each_token(&mut self.callback);
+    }
+
+    fn process_type_pack(&mut self, type_pack: &mut TypePack) {
+        type_pack.for_each_token(&mut self.callback);
+    }
+
+    fn process_generic_type_pack(&mut self, generic_type_pack: &mut GenericTypePack) {
+        generic_type_pack.for_each_token(&mut self.callback);
+    }
+
+    fn process_variadic_type_pack(&mut self, variadic_type_pack: &mut VariadicTypePack) {
+        variadic_type_pack.for_each_token(&mut self.callback);
+    }
+}
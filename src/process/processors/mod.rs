@@ -1,7 +1,9 @@
 //! A collection of utility processors that can be used when creating rules.
 
+mod clear_tokens;
 mod find_identifier;
-mod find_usage;
+mod for_each_token;
 
+pub(crate) use clear_tokens::*;
 pub use find_identifier::*;
-pub(crate) use find_usage::*;
+pub(crate) use for_each_token::*;
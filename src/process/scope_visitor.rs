@@ -27,6 +27,12 @@ pub trait Scope {
 }
 
 /// A visitor that can be used only with a NodeProcessor that also implements the Scope trait.
+///
+/// The visitor pushes a scope for each block. Function parameters (including the implicit
+/// `self` parameter of methods) and the identifiers of numeric and generic for loops are
+/// inserted in an extra scope pushed around the function or loop block, after the loop
+/// expressions are visited. The condition of a repeat statement is visited before the scope
+/// of its block is popped, so it can see the locals of the block.
 pub struct ScopeVisitor;
 
 impl ScopeVisitor {
@@ -228,9 +234,18 @@ impl<T: NodeProcessor + Scope> NodeVisitor<T> for ScopeVisitor {
     }
 }
 
+/// Keeps track of the local identifiers that are visible while a block is visited with the
+/// [`ScopeVisitor`]. Processors can own a tracker and implement `DerefMut` into it to get a
+/// [`Scope`] implementation, or implement [`Scope`] themselves and forward each call to the
+/// tracker to be notified when identifiers are inserted or removed.
+///
+/// Global names that should never be shadowed (for example, globals that will be injected
+/// in the code) can be registered with [`IdentifierTracker::with_globals`], so that the
+/// generated identifiers and [`IdentifierTracker::is_identifier_free`] avoid them.
 #[derive(Debug, Clone, Default)]
-pub(crate) struct IdentifierTracker {
+pub struct IdentifierTracker {
     identifiers: Vec<HashSet<String>>,
+    globals: HashSet<String>,
 }
 
 impl IdentifierTracker {
@@ -245,22 +260,87 @@ impl IdentifierTracker {
     }
 
     pub fn new() -> IdentifierTracker {
-        Self {
-            identifiers: Vec::new(),
-        }
+        Self::default()
+    }
+
+    /// Registers global names that must not be used for new identifiers.
+    pub fn with_globals<I: IntoIterator<Item = S>, S: Into<String>>(mut self, globals: I) -> Self {
+        self.globals.extend(globals.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn insert_global(&mut self, global: impl Into<String>) {
+        self.globals.insert(global.into());
+    }
+
+    #[inline]
+    pub fn is_global(&self, identifier: &str) -> bool {
+        self.globals.contains(identifier)
+    }
+
+    pub fn iter_globals(&self) -> impl Iterator<Item = &str> {
+        self.globals.iter().map(String::as_str)
     }
 
+    /// Returns true if a local variable with the given name is visible in the current scope.
     pub fn is_identifier_used(&self, identifier: &str) -> bool {
         self.identifiers.iter().any(|set| set.contains(identifier))
     }
 
+    /// Returns true if the given name is a valid identifier that can be declared in the
+    /// current scope without shadowing a visible local variable or a registered global.
+    pub fn is_identifier_free(&self, identifier: &str) -> bool {
+        is_valid_identifier(identifier)
+            && !self.is_identifier_used(identifier)
+            && !self.is_global(identifier)
+    }
+
+    /// The number of scopes currently open. When visiting a block with the [`ScopeVisitor`],
+    /// the statements of the block are at depth 1.
+    #[inline]
+    pub fn scope_depth(&self) -> usize {
+        self.identifiers.len()
+    }
+
+    /// Returns the depth of the innermost scope where the given identifier is declared, or
+    /// `None` if it is not a visible local variable.
+    pub fn get_identifier_depth(&self, identifier: &str) -> Option<usize> {
+        self.identifiers
+            .iter()
+            .rposition(|set| set.contains(identifier))
+            .map(|index| index + 1)
+    }
+
+    /// Iterates (in no particular order) on all the local identifiers visible in the
+    /// current scope.
+    pub fn iter_identifiers(&self) -> impl Iterator<Item = &str> {
+        self.identifiers
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, set)| {
+                set.iter().filter(move |identifier| {
+                    !self.identifiers[index + 1..]
+                        .iter()
+                        .any(|set| set.contains(identifier.as_str()))
+                })
+            })
+            .map(String::as_str)
+    }
+
+    /// Iterates (in no particular order) on the identifiers declared in the innermost scope.
+    /// These are the identifiers removed when the scope is popped.
+    pub fn iter_current_scope(&self) -> impl Iterator<Item = &str> {
+        self.identifiers
+            .last()
+            .into_iter()
+            .flat_map(|set| set.iter().map(String::as_str))
+    }
+
     pub fn generate_identifier(&mut self) -> String {
         let mut permutator = identifier_permutator();
 
         let identifier = permutator
-            .find(|identifier| {
-                is_valid_identifier(identifier) && !self.is_identifier_used(identifier)
-            })
+            .find(|identifier| self.is_identifier_free(identifier))
             .expect("the permutator should always ultimately return a valid identifier");
         self.insert_identifier(&identifier);
         identifier
@@ -274,7 +354,7 @@ impl IdentifierTracker {
         let initial_length = identifier.len();
         let mut permutator = Permutator::new("012345689".chars());
 
-        while self.is_identifier_used(&identifier) {
+        while self.is_identifier_used(&identifier) || self.is_global(&identifier) {
             identifier.truncate(initial_length);
             let next_suffix = permutator.next().unwrap_or_else(|| "_".to_owned());
             identifier.push_str(&next_suffix);
@@ -0,0 +1,43 @@
+use std::collections::HashSet;
+
+use crate::nodes::{Block, Expression, Identifier, LocalFunctionStatement};
+use crate::process::{NodeProcessor, NodeVisitor, Scope, ScopeVisitor};
+
+/// Collects every identifier name that appears in a block, to find names for generated
+/// variables that cannot collide with existing variables.
+#[derive(Debug, Default)]
+struct IdentifierCollector {
+    identifiers: HashSet<String>,
+}
+
+impl Scope for IdentifierCollector {
+    fn push(&mut self) {}
+
+    fn pop(&mut self) {}
+
+    fn insert(&mut self, identifier: &mut String) {
+        self.identifiers.insert(identifier.clone());
+    }
+
+    fn insert_self(&mut self) {}
+
+    fn insert_local(&mut self, identifier: &mut String, _value: Option<&mut Expression>) {
+        self.identifiers.insert(identifier.clone());
+    }
+
+    fn insert_local_function(&mut self, function: &mut LocalFunctionStatement) {
+        self.identifiers.insert(function.get_name().to_owned());
+    }
+}
+
+impl NodeProcessor for IdentifierCollector {
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        self.identifiers.insert(identifier.get_name().to_owned());
+    }
+}
+
+pub(crate) fn collect_identifiers(block: &mut Block) -> HashSet<String> {
+    let mut collector = IdentifierCollector::default();
+    ScopeVisitor::visit_block(block, &mut collector);
+    collector.identifiers
+}
@@ -1,5 +1,7 @@
+mod identifier_collector;
 mod permutator;
 
+pub(crate) use identifier_collector::collect_identifiers;
 pub(crate) use permutator::Permutator;
 
 pub(crate) type CharPermutator = Permutator<std::str::Chars<'static>>;
@@ -14,9 +16,9 @@ pub(crate) fn generate_identifier(permutator: &mut CharPermutator) -> String {
         .expect("the permutator should always ultimately return a valid identifier")
 }
 
-pub(crate) const KEYWORDS: [&str; 21] = [
-    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "if", "in", "local",
-    "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+pub(crate) const KEYWORDS: [&str; 22] = [
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in",
+    "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
 ];
 
 macro_rules! matches_any_keyword {
@@ -30,6 +32,7 @@ macro_rules! matches_any_keyword {
             | "false"
             | "for"
             | "function"
+            | "goto"
             | "if"
             | "in"
             | "local"
@@ -1,9 +1,53 @@
 use crate::nodes::*;
-use crate::process::NodeProcessor;
+use crate::process::{consume_budget_step, NodeProcessor};
 
 use std::marker::PhantomData;
 
 /// A trait that defines method that iterates on nodes and process them using a NodeProcessor.
+///
+/// The default implementation of each method visits all the children of a node. A processor
+/// can control the traversal with [`NodeProcessor::skip_children`] and
+/// [`NodeProcessor::is_stopped`].
+///
+/// # Example
+/// Count calls to functions by name, without looking inside function expressions:
+/// ```
+/// # use std::collections::HashMap;
+/// # use darklua_core::nodes::{Expression, FunctionCall, Prefix};
+/// # use darklua_core::process::{DefaultVisitor, NodeProcessor, NodeVisitor};
+/// # use darklua_core::Parser;
+/// #[derive(Default)]
+/// struct CallCounter {
+///     calls: HashMap<String, usize>,
+///     skip: bool,
+/// }
+///
+/// impl NodeProcessor for CallCounter {
+///     fn process_function_call(&mut self, call: &mut FunctionCall) {
+///         if let Prefix::Identifier(identifier) = call.get_prefix() {
+///             *self.calls.entry(identifier.get_name().to_owned()).or_default() += 1;
+///         }
+///     }
+///
+///     fn process_expression(&mut self, expression: &mut Expression) {
+///         self.skip = matches!(expression, Expression::Function(_));
+///     }
+///
+///     fn skip_children(&mut self) -> bool {
+///         std::mem::take(&mut self.skip)
+///     }
+/// }
+///
+/// let mut block = Parser::default()
+///     .parse("print('a') print(1) local f = function() print('b') end warn()")
+///     .unwrap();
+///
+/// let mut counter = CallCounter::default();
+/// DefaultVisitor::visit_block(&mut block, &mut counter);
+///
+/// assert_eq!(counter.calls.get("print"), Some(&2));
+/// assert_eq!(counter.calls.get("warn"), Some(&1));
+/// ```
 pub trait NodeVisitor<T: NodeProcessor> {
     fn visit_block(block: &mut Block, processor: &mut T) {
         processor.process_block(block);
@@ -18,8 +62,16 @@ pub trait NodeVisitor<T: NodeProcessor> {
     }
 
     fn visit_statement(statement: &mut Statement, processor: &mut T) {
+        if processor.is_stopped() || consume_budget_step() {
+            return;
+        }
+
         processor.process_statement(statement);
 
+        if processor.is_stopped() || processor.skip_children() {
+            return;
+        }
+
         match statement {
             Statement::Assign(statement) => Self::visit_assign_statement(statement, processor),
             Statement::Do(statement) => Self::visit_do_statement(statement, processor),
@@ -29,7 +81,9 @@ pub trait NodeVisitor<T: NodeProcessor> {
             }
             Statement::Function(statement) => Self::visit_function_statement(statement, processor),
             Statement::GenericFor(statement) => Self::visit_generic_for(statement, processor),
+            Statement::Goto(statement) => Self::visit_goto_statement(statement, processor),
             Statement::If(statement) => Self::visit_if_statement(statement, processor),
+            Statement::Label(statement) => Self::visit_label_statement(statement, processor),
             Statement::LocalAssign(statement) => Self::visit_local_assign(statement, processor),
             Statement::LocalFunction(statement) => Self::visit_local_function(statement, processor),
             Statement::TypeFunction(statement) => Self::visit_type_function(statement, processor),
@@ -46,6 +100,10 @@ pub trait NodeVisitor<T: NodeProcessor> {
     }
 
     fn visit_last_statement(last_statement: &mut LastStatement, processor: &mut T) {
+        if processor.is_stopped() {
+            return;
+        }
+
         processor.process_last_statement(last_statement);
 
         if let LastStatement::Return(expressions) = last_statement {
@@ -56,8 +114,16 @@ pub trait NodeVisitor<T: NodeProcessor> {
     }
 
     fn visit_expression(expression: &mut Expression, processor: &mut T) {
+        if processor.is_stopped() {
+            return;
+        }
+
         processor.process_expression(expression);
 
+        if processor.is_stopped() || processor.skip_children() {
+            return;
+        }
+
         match expression {
             Expression::Binary(expression) => {
                 Self::visit_binary_expression(expression, processor);
@@ -181,6 +247,14 @@ pub trait NodeVisitor<T: NodeProcessor> {
         Self::visit_block(statement.mutate_block(), processor);
     }
 
+    fn visit_goto_statement(statement: &mut GotoStatement, processor: &mut T) {
+        processor.process_goto_statement(statement);
+    }
+
+    fn visit_label_statement(statement: &mut LabelStatement, processor: &mut T) {
+        processor.process_label_statement(statement);
+    }
+
     fn visit_compound_assign(statement: &mut CompoundAssignStatement, processor: &mut T) {
         processor.process_compound_assign_statement(statement);
         Self::visit_variable(statement.mutate_variable(), processor);
@@ -828,4 +902,136 @@ mod test {
         assert_eq!(counter.interpolated_string_count, 1);
         assert_eq!(counter.expression_count, 2);
     }
+
+    #[derive(Default)]
+    struct StatementLimit {
+        limit: usize,
+        statements: usize,
+        expressions: usize,
+    }
+
+    impl NodeProcessor for StatementLimit {
+        fn process_statement(&mut self, _: &mut Statement) {
+            self.statements += 1;
+        }
+
+        fn process_expression(&mut self, _: &mut Expression) {
+            self.expressions += 1;
+        }
+
+        fn is_stopped(&self) -> bool {
+            self.statements >= self.limit
+        }
+    }
+
+    #[test]
+    fn stop_visit_after_statement_limit() {
+        let mut processor = StatementLimit {
+            limit: 2,
+            ..Default::default()
+        };
+        let mut block = Block::default()
+            .with_statement(LocalAssignStatement::from_variable("a").with_value(true))
+            .with_statement(LocalAssignStatement::from_variable("b").with_value(true))
+            .with_statement(LocalAssignStatement::from_variable("c").with_value(true));
+
+        DefaultVisitor::visit_block(&mut block, &mut processor);
+
+        assert_eq!(processor.statements, 2);
+        assert_eq!(processor.expressions, 1);
+    }
+
+    #[derive(Default)]
+    struct SkipDoStatements {
+        statements: usize,
+        skip: bool,
+    }
+
+    impl NodeProcessor for SkipDoStatements {
+        fn process_statement(&mut self, statement: &mut Statement) {
+            self.statements += 1;
+            self.skip = matches!(statement, Statement::Do(_));
+        }
+
+        fn skip_children(&mut self) -> bool {
+            std::mem::take(&mut self.skip)
+        }
+    }
+
+    #[test]
+    fn skip_children_of_do_statement() {
+        let mut processor = SkipDoStatements::default();
+        let mut block = Block::default()
+            .with_statement(DoStatement::new(
+                Block::default().with_statement(DoStatement::default()),
+            ))
+            .with_statement(DoStatement::default());
+
+        DefaultVisitor::visit_block(&mut block, &mut processor);
+
+        assert_eq!(processor.statements, 2);
+    }
+
+    struct RenameType {
+        from: &'static str,
+        to: &'static str,
+    }
+
+    impl NodeProcessor for RenameType {
+        fn process_type_declaration(&mut self, type_declaration: &mut TypeDeclarationStatement) {
+            let name = type_declaration.mutate_name();
+            if name.get_name() == self.from {
+                name.set_name(self.to);
+            }
+        }
+
+        fn process_type(&mut self, r#type: &mut Type) {
+            // only rename references to the local type, not `Module.Old`
+            if let Type::Name(type_name) = r#type {
+                let name = type_name.mutate_type_name();
+                if name.get_name() == self.from {
+                    name.set_name(self.to);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rename_type_references() {
+        let parse = |code: &str| crate::Parser::default().parse(code).unwrap();
+        let mut block = parse(
+            r#"
+            type Old = { value: number }
+            export type Pair<T = Old> = { first: Old, [string]: Map<string, Array<Old>> }
+            local value: Old? = nil
+            local function f(a: Old | typeof(b), ...: Old & Other): (Old) -> Module.Old
+                return a :: Array<{ Old }>
+            end
+            local g: <T>(T, "Old") -> ...Old = nil
+            "#,
+        );
+
+        DefaultVisitor::visit_block(
+            &mut block,
+            &mut RenameType {
+                from: "Old",
+                to: "New",
+            },
+        );
+
+        pretty_assertions::assert_eq!(
+            block,
+            parse(
+                r#"
+                type New = { value: number }
+                export type Pair<T = New> = { first: New, [string]: Map<string, Array<New>> }
+                local value: New? = nil
+                local function f(a: New | typeof(b), ...: New & Other): (New) -> Module.Old
+                    return a :: Array<{ New }>
+                end
+                local g: <T>(T, "Old") -> ...New = nil
+                "#
+            )
+        );
+    }
 }
@@ -1,12 +1,12 @@
-use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::sync::OnceLock;
 
 use crate::nodes::{
     Block, BlockTokens, DoTokens, ExportTypeFunctionTokens, FunctionBodyTokens, GenericForTokens,
-    Identifier, IfStatementTokens, LastStatement, LocalAssignTokens, LocalFunctionTokens,
-    NumericForTokens, ParentheseExpression, ParentheseTokens, Prefix, RepeatTokens, ReturnTokens,
-    Statement, Token, TriviaKind, TypeDeclarationTokens, TypeFunctionTokens, Variable, WhileTokens,
+    Identifier, IfStatementTokens, LabelTokens, LastStatement, LocalAssignTokens,
+    LocalFunctionTokens, NumericForTokens, ParentheseExpression, ParentheseTokens, Prefix,
+    RepeatTokens, ReturnTokens, Statement, Token, Trivia, TriviaKind, TypeDeclarationTokens,
+    TypeFunctionTokens, Variable, WhileTokens,
 };
 use crate::rules::{
     verify_property_collisions, verify_required_any_properties, Context, Rule, RuleConfiguration,
@@ -17,7 +17,8 @@ use super::{FlawlessRule, ShiftTokenLine};
 
 pub const APPEND_TEXT_COMMENT_RULE_NAME: &str = "append_text_comment";
 
-/// A rule to append a comment at the beginning or the end of each file.
+/// A rule to append a comment at the beginning or the end of each file, or before each
+/// function defined at the top level of each file.
 #[derive(Debug, Default)]
 pub struct AppendTextComment {
     text_value: OnceLock<Result<String, String>>,
@@ -25,6 +26,15 @@ pub struct AppendTextComment {
     location: AppendLocation,
 }
 
+// the cached text value is ignored because it is read from the text content
+impl PartialEq for AppendTextComment {
+    fn eq(&self, other: &Self) -> bool {
+        self.text_content == other.text_content && self.location == other.location
+    }
+}
+
+impl Eq for AppendTextComment {}
+
 impl AppendTextComment {
     pub fn new(value: impl Into<String>) -> Self {
         Self {
@@ -47,17 +57,23 @@ impl AppendTextComment {
         self
     }
 
-    fn text(&self, project_path: &Path) -> Result<String, String> {
+    pub fn before_functions(mut self) -> Self {
+        self.location = AppendLocation::BeforeFunctions;
+        self
+    }
+
+    fn text(&self, context: &Context) -> Result<String, String> {
         self.text_value
             .get_or_init(|| {
                 match &self.text_content {
                     TextContent::None => Err("".to_owned()),
                     TextContent::Value(value) => Ok(value.clone()),
-                    TextContent::FilePath(file_path) => {
-                        fs::read_to_string(project_path.join(file_path)).map_err(|err| {
+                    TextContent::FilePath(file_path) => context
+                        .resources()
+                        .get(context.project_location().join(file_path))
+                        .map_err(|err| {
                             format!("unable to read file `{}`: {}", file_path.display(), err)
-                        })
-                    }
+                        }),
                 }
                 .map(|content| {
                     if content.is_empty() {
@@ -88,298 +104,419 @@ impl AppendTextComment {
     }
 }
 
+impl AppendTextComment {
+    fn append_before_statement(
+        &self,
+        statement: &mut Statement,
+        text: String,
+        code: &str,
+    ) -> RuleProcessResult {
+        match statement {
+            Statement::Assign(assign_statement) => {
+                let variable = assign_statement
+                    .iter_mut_variables()
+                    .next()
+                    .ok_or("an assign statement must have at least one variable")?;
+                self.location
+                    .append_comment(variable_get_first_token(variable), text, code);
+            }
+            Statement::Do(do_statement) => {
+                if let Some(tokens) = do_statement.mutate_tokens() {
+                    self.location.append_comment(&mut tokens.r#do, text, code);
+                } else {
+                    let mut token = Token::from_content("do");
+                    self.location.append_comment(&mut token, text, code);
+
+                    do_statement.set_tokens(DoTokens {
+                        r#do: token,
+                        end: Token::from_content("end"),
+                    });
+                }
+            }
+            Statement::Call(call) => {
+                self.location.append_comment(
+                    prefix_get_first_token(call.mutate_prefix()),
+                    text,
+                    code,
+                );
+            }
+            Statement::CompoundAssign(compound_assign) => {
+                self.location.append_comment(
+                    variable_get_first_token(compound_assign.mutate_variable()),
+                    text,
+                    code,
+                );
+            }
+            Statement::Function(function) => {
+                if let Some(tokens) = function.mutate_tokens() {
+                    self.location
+                        .append_comment(&mut tokens.function, text, code);
+                } else {
+                    let mut token = Token::from_content("function");
+                    self.location.append_comment(&mut token, text, code);
+
+                    function.set_tokens(FunctionBodyTokens {
+                        function: token,
+                        opening_parenthese: Token::from_content("("),
+                        closing_parenthese: Token::from_content(")"),
+                        end: Token::from_content("end"),
+                        parameter_commas: Vec::new(),
+                        variable_arguments: None,
+                        variable_arguments_colon: None,
+                        return_type_colon: None,
+                    });
+                }
+            }
+            Statement::GenericFor(generic_for) => {
+                if let Some(tokens) = generic_for.mutate_tokens() {
+                    self.location.append_comment(&mut tokens.r#for, text, code);
+                } else {
+                    let mut token = Token::from_content("for");
+                    self.location.append_comment(&mut token, text, code);
+
+                    generic_for.set_tokens(GenericForTokens {
+                        r#for: token,
+                        r#in: Token::from_content("in"),
+                        r#do: Token::from_content("do"),
+                        end: Token::from_content("end"),
+                        identifier_commas: Vec::new(),
+                        value_commas: Vec::new(),
+                    });
+                }
+            }
+            Statement::If(if_statement) => {
+                if let Some(tokens) = if_statement.mutate_tokens() {
+                    self.location.append_comment(&mut tokens.r#if, text, code);
+                } else {
+                    let mut token = Token::from_content("if");
+                    self.location.append_comment(&mut token, text, code);
+
+                    if_statement.set_tokens(IfStatementTokens {
+                        r#if: token,
+                        then: Token::from_content("then"),
+                        end: Token::from_content("end"),
+                        r#else: None,
+                    });
+                }
+            }
+            Statement::LocalAssign(local_assign) => {
+                if let Some(tokens) = local_assign.mutate_tokens() {
+                    self.location.append_comment(&mut tokens.local, text, code);
+                } else {
+                    let mut token = Token::from_content("local");
+                    self.location.append_comment(&mut token, text, code);
+
+                    local_assign.set_tokens(LocalAssignTokens {
+                        local: token,
+                        equal: None,
+                        variable_commas: Vec::new(),
+                        value_commas: Vec::new(),
+                    });
+                }
+            }
+            Statement::LocalFunction(local_function) => {
+                if let Some(tokens) = local_function.mutate_tokens() {
+                    self.location.append_comment(&mut tokens.local, text, code);
+                } else {
+                    let mut token = Token::from_content("local");
+                    self.location.append_comment(&mut token, text, code);
+
+                    local_function.set_tokens(LocalFunctionTokens {
+                        local: token,
+                        function_body: FunctionBodyTokens {
+                            function: Token::from_content("function"),
+                            opening_parenthese: Token::from_content("("),
+                            closing_parenthese: Token::from_content(")"),
+                            end: Token::from_content("end"),
+                            parameter_commas: Vec::new(),
+                            variable_arguments: None,
+                            variable_arguments_colon: None,
+                            return_type_colon: None,
+                        },
+                    });
+                }
+            }
+            Statement::TypeFunction(type_function) => {
+                if let Some(tokens) = type_function.mutate_tokens() {
+                    self.location.append_comment(&mut tokens.r#type, text, code);
+                } else {
+                    let mut token = Token::from_content("type");
+                    self.location.append_comment(&mut token, text, code);
+
+                    type_function.set_tokens(TypeFunctionTokens {
+                        r#type: token,
+                        function_body: FunctionBodyTokens {
+                            function: Token::from_content("function"),
+                            opening_parenthese: Token::from_content("("),
+                            closing_parenthese: Token::from_content(")"),
+                            end: Token::from_content("end"),
+                            parameter_commas: Vec::new(),
+                            variable_arguments: None,
+                            variable_arguments_colon: None,
+                            return_type_colon: None,
+                        },
+                    });
+                }
+            }
+            Statement::ExportTypeFunction(export_type_function) => {
+                if let Some(tokens) = export_type_function.mutate_tokens() {
+                    self.location
+                        .append_comment(&mut tokens.r#export, text, code);
+                } else {
+                    let mut token_a = Token::from_content("export");
+                    self.location.append_comment(&mut token_a, text, code);
+                    let token_b = Token::from_content("type");
+
+                    export_type_function.set_tokens(ExportTypeFunctionTokens {
+                        export: token_a,
+                        r#type: token_b,
+                        function_body: FunctionBodyTokens {
+                            function: Token::from_content("function"),
+                            opening_parenthese: Token::from_content("("),
+                            closing_parenthese: Token::from_content(")"),
+                            end: Token::from_content("end"),
+                            parameter_commas: Vec::new(),
+                            variable_arguments: None,
+                            variable_arguments_colon: None,
+                            return_type_colon: None,
+                        },
+                    });
+                }
+            }
+            Statement::NumericFor(numeric_for) => {
+                if let Some(tokens) = numeric_for.mutate_tokens() {
+                    self.location.append_comment(&mut tokens.r#for, text, code);
+                } else {
+                    let mut token = Token::from_content("for");
+                    self.location.append_comment(&mut token, text, code);
+
+                    numeric_for.set_tokens(NumericForTokens {
+                        r#for: token,
+                        equal: Token::from_content("="),
+                        r#do: Token::from_content("do"),
+                        end: Token::from_content("end"),
+                        end_comma: Token::from_content(","),
+                        step_comma: None,
+                    });
+                }
+            }
+            Statement::Goto(goto) => {
+                if let Some(token) = goto.mutate_token() {
+                    self.location.append_comment(token, text, code);
+                } else {
+                    let mut token = Token::from_content("goto");
+                    self.location.append_comment(&mut token, text, code);
+
+                    goto.set_token(token);
+                }
+            }
+            Statement::Label(label) => {
+                if let Some(tokens) = label.mutate_tokens() {
+                    self.location
+                        .append_comment(&mut tokens.left_colons, text, code);
+                } else {
+                    let mut token = Token::from_content("::");
+                    self.location.append_comment(&mut token, text, code);
+
+                    label.set_tokens(LabelTokens {
+                        left_colons: token,
+                        right_colons: Token::from_content("::"),
+                    });
+                }
+            }
+            Statement::Repeat(repeat) => {
+                if let Some(tokens) = repeat.mutate_tokens() {
+                    self.location.append_comment(&mut tokens.repeat, text, code);
+                } else {
+                    let mut token = Token::from_content("repeat");
+                    self.location.append_comment(&mut token, text, code);
+
+                    repeat.set_tokens(RepeatTokens {
+                        repeat: token,
+                        until: Token::from_content("until"),
+                    });
+                }
+            }
+            Statement::While(while_statement) => {
+                if let Some(tokens) = while_statement.mutate_tokens() {
+                    self.location
+                        .append_comment(&mut tokens.r#while, text, code);
+                } else {
+                    let mut token = Token::from_content("while");
+                    self.location.append_comment(&mut token, text, code);
+
+                    while_statement.set_tokens(WhileTokens {
+                        r#while: token,
+                        r#do: Token::from_content("do"),
+                        end: Token::from_content("end"),
+                    });
+                }
+            }
+            Statement::TypeDeclaration(type_declaration) => {
+                let is_exported = type_declaration.is_exported();
+                if let Some(tokens) = type_declaration.mutate_tokens() {
+                    if is_exported {
+                        self.location.append_comment(
+                            tokens
+                                .export
+                                .get_or_insert_with(|| Token::from_content("export")),
+                            text,
+                            code,
+                        );
+                    } else {
+                        self.location.append_comment(&mut tokens.r#type, text, code);
+                    }
+                } else if is_exported {
+                    let mut token = Token::from_content("export");
+                    self.location.append_comment(&mut token, text, code);
+
+                    type_declaration.set_tokens(TypeDeclarationTokens {
+                        r#type: Token::from_content("type"),
+                        equal: Token::from_content("="),
+                        export: Some(token),
+                    });
+                } else {
+                    let mut token = Token::from_content("type");
+                    self.location.append_comment(&mut token, text, code);
+
+                    type_declaration.set_tokens(TypeDeclarationTokens {
+                        r#type: token,
+                        equal: Token::from_content("="),
+                        export: None,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn append_before_last_statement(
+        &self,
+        statement: &mut LastStatement,
+        text: String,
+        code: &str,
+    ) {
+        match statement {
+            LastStatement::Break(token) => {
+                self.location.append_comment(
+                    token.get_or_insert_with(|| Token::from_content("break")),
+                    text,
+                    code,
+                );
+            }
+            LastStatement::Continue(token) => {
+                self.location.append_comment(
+                    token.get_or_insert_with(|| Token::from_content("continue")),
+                    text,
+                    code,
+                );
+            }
+            LastStatement::Return(return_statement) => {
+                if let Some(tokens) = return_statement.mutate_tokens() {
+                    self.location
+                        .append_comment(&mut tokens.r#return, text, code);
+                } else {
+                    let mut token = Token::from_content("return");
+                    self.location.append_comment(&mut token, text, code);
+
+                    return_statement.set_tokens(ReturnTokens {
+                        r#return: token,
+                        commas: Vec::new(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Appends the comment before each function defined in the top-level block. The
+    /// lines of the tokens that follow a comment are shifted by the number of lines of
+    /// the comment.
+    fn append_before_functions(
+        &self,
+        block: &mut Block,
+        text: String,
+        shift_lines: usize,
+        code: &str,
+    ) -> RuleProcessResult {
+        let mut shift = 0;
+        let mut statement_shifts = Vec::with_capacity(block.statements_len());
+
+        for statement in block.iter_mut_statements() {
+            let is_function = matches!(
+                statement,
+                Statement::Function(_) | Statement::LocalFunction(_)
+            );
+
+            if is_function {
+                shift += shift_lines;
+            }
+
+            statement.shift_token_lines(shift);
+
+            if is_function {
+                self.append_before_statement(statement, text.clone(), code)?;
+            }
+
+            statement_shifts.push(shift);
+        }
+
+        if shift == 0 {
+            return Ok(());
+        }
+
+        if let Some(last_statement) = block.mutate_last_statement() {
+            last_statement.shift_token_lines(shift);
+        }
+
+        if let Some(tokens) = block.mutate_tokens() {
+            for (semicolon, statement_shift) in tokens.semicolons.iter_mut().zip(statement_shifts) {
+                if let Some(semicolon) = semicolon {
+                    semicolon.shift_token_line(statement_shift);
+                }
+            }
+            if let Some(semicolon) = &mut tokens.last_semicolon {
+                semicolon.shift_token_line(shift);
+            }
+            if let Some(final_token) = &mut tokens.final_token {
+                final_token.shift_token_line(shift);
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl Rule for AppendTextComment {
     fn process(&self, block: &mut Block, context: &Context) -> RuleProcessResult {
-        let text = self.text(context.project_location())?;
+        let text = self.text(context)?;
 
         if text.is_empty() {
             return Ok(());
         }
 
         let shift_lines = text.lines().count();
-        ShiftTokenLine::new(shift_lines).flawless_process(block, context);
+        let code = context.original_code();
 
         match self.location {
             AppendLocation::Start => {
+                ShiftTokenLine::new(shift_lines).flawless_process(block, context);
+
                 if let Some(statement) = block.first_mut_statement() {
-                    match statement {
-                        Statement::Assign(assign_statement) => {
-                            let variable = assign_statement
-                                .iter_mut_variables()
-                                .next()
-                                .ok_or("an assign statement must have at least one variable")?;
-                            self.location
-                                .append_comment(variable_get_first_token(variable), text);
-                        }
-                        Statement::Do(do_statement) => {
-                            if let Some(tokens) = do_statement.mutate_tokens() {
-                                self.location.append_comment(&mut tokens.r#do, text);
-                            } else {
-                                let mut token = Token::from_content("do");
-                                self.location.append_comment(&mut token, text);
-
-                                do_statement.set_tokens(DoTokens {
-                                    r#do: token,
-                                    end: Token::from_content("end"),
-                                });
-                            }
-                        }
-                        Statement::Call(call) => {
-                            self.location
-                                .append_comment(prefix_get_first_token(call.mutate_prefix()), text);
-                        }
-                        Statement::CompoundAssign(compound_assign) => {
-                            self.location.append_comment(
-                                variable_get_first_token(compound_assign.mutate_variable()),
-                                text,
-                            );
-                        }
-                        Statement::Function(function) => {
-                            if let Some(tokens) = function.mutate_tokens() {
-                                self.location.append_comment(&mut tokens.function, text);
-                            } else {
-                                let mut token = Token::from_content("function");
-                                self.location.append_comment(&mut token, text);
-
-                                function.set_tokens(FunctionBodyTokens {
-                                    function: token,
-                                    opening_parenthese: Token::from_content("("),
-                                    closing_parenthese: Token::from_content(")"),
-                                    end: Token::from_content("end"),
-                                    parameter_commas: Vec::new(),
-                                    variable_arguments: None,
-                                    variable_arguments_colon: None,
-                                    return_type_colon: None,
-                                });
-                            }
-                        }
-                        Statement::GenericFor(generic_for) => {
-                            if let Some(tokens) = generic_for.mutate_tokens() {
-                                self.location.append_comment(&mut tokens.r#for, text);
-                            } else {
-                                let mut token = Token::from_content("for");
-                                self.location.append_comment(&mut token, text);
-
-                                generic_for.set_tokens(GenericForTokens {
-                                    r#for: token,
-                                    r#in: Token::from_content("in"),
-                                    r#do: Token::from_content("do"),
-                                    end: Token::from_content("end"),
-                                    identifier_commas: Vec::new(),
-                                    value_commas: Vec::new(),
-                                });
-                            }
-                        }
-                        Statement::If(if_statement) => {
-                            if let Some(tokens) = if_statement.mutate_tokens() {
-                                self.location.append_comment(&mut tokens.r#if, text);
-                            } else {
-                                let mut token = Token::from_content("if");
-                                self.location.append_comment(&mut token, text);
-
-                                if_statement.set_tokens(IfStatementTokens {
-                                    r#if: token,
-                                    then: Token::from_content("then"),
-                                    end: Token::from_content("end"),
-                                    r#else: None,
-                                });
-                            }
-                        }
-                        Statement::LocalAssign(local_assign) => {
-                            if let Some(tokens) = local_assign.mutate_tokens() {
-                                self.location.append_comment(&mut tokens.local, text);
-                            } else {
-                                let mut token = Token::from_content("local");
-                                self.location.append_comment(&mut token, text);
-
-                                local_assign.set_tokens(LocalAssignTokens {
-                                    local: token,
-                                    equal: None,
-                                    variable_commas: Vec::new(),
-                                    value_commas: Vec::new(),
-                                });
-                            }
-                        }
-                        Statement::LocalFunction(local_function) => {
-                            if let Some(tokens) = local_function.mutate_tokens() {
-                                self.location.append_comment(&mut tokens.local, text);
-                            } else {
-                                let mut token = Token::from_content("local");
-                                self.location.append_comment(&mut token, text);
-
-                                local_function.set_tokens(LocalFunctionTokens {
-                                    local: token,
-                                    function_body: FunctionBodyTokens {
-                                        function: Token::from_content("function"),
-                                        opening_parenthese: Token::from_content("("),
-                                        closing_parenthese: Token::from_content(")"),
-                                        end: Token::from_content("end"),
-                                        parameter_commas: Vec::new(),
-                                        variable_arguments: None,
-                                        variable_arguments_colon: None,
-                                        return_type_colon: None,
-                                    },
-                                });
-                            }
-                        }
-                        Statement::TypeFunction(type_function) => {
-                            if let Some(tokens) = type_function.mutate_tokens() {
-                                self.location.append_comment(&mut tokens.r#type, text);
-                            } else {
-                                let mut token = Token::from_content("type");
-                                self.location.append_comment(&mut token, text);
-
-                                type_function.set_tokens(TypeFunctionTokens {
-                                    r#type: token,
-                                    function_body: FunctionBodyTokens {
-                                        function: Token::from_content("function"),
-                                        opening_parenthese: Token::from_content("("),
-                                        closing_parenthese: Token::from_content(")"),
-                                        end: Token::from_content("end"),
-                                        parameter_commas: Vec::new(),
-                                        variable_arguments: None,
-                                        variable_arguments_colon: None,
-                                        return_type_colon: None,
-                                    },
-                                });
-                            }
-                        }
-                        Statement::ExportTypeFunction(export_type_function) => {
-                            if let Some(tokens) = export_type_function.mutate_tokens() {
-                                self.location.append_comment(&mut tokens.r#export, text);
-                            } else {
-                                let mut token_a = Token::from_content("export");
-                                self.location.append_comment(&mut token_a, text);
-                                let token_b = Token::from_content("type");
-
-                                export_type_function.set_tokens(ExportTypeFunctionTokens {
-                                    export: token_a,
-                                    r#type: token_b,
-                                    function_body: FunctionBodyTokens {
-                                        function: Token::from_content("function"),
-                                        opening_parenthese: Token::from_content("("),
-                                        closing_parenthese: Token::from_content(")"),
-                                        end: Token::from_content("end"),
-                                        parameter_commas: Vec::new(),
-                                        variable_arguments: None,
-                                        variable_arguments_colon: None,
-                                        return_type_colon: None,
-                                    },
-                                });
-                            }
-                        }
-                        Statement::NumericFor(numeric_for) => {
-                            if let Some(tokens) = numeric_for.mutate_tokens() {
-                                self.location.append_comment(&mut tokens.r#for, text);
-                            } else {
-                                let mut token = Token::from_content("for");
-                                self.location.append_comment(&mut token, text);
-
-                                numeric_for.set_tokens(NumericForTokens {
-                                    r#for: token,
-                                    equal: Token::from_content("="),
-                                    r#do: Token::from_content("do"),
-                                    end: Token::from_content("end"),
-                                    end_comma: Token::from_content(","),
-                                    step_comma: None,
-                                });
-                            }
-                        }
-                        Statement::Repeat(repeat) => {
-                            if let Some(tokens) = repeat.mutate_tokens() {
-                                self.location.append_comment(&mut tokens.repeat, text);
-                            } else {
-                                let mut token = Token::from_content("repeat");
-                                self.location.append_comment(&mut token, text);
-
-                                repeat.set_tokens(RepeatTokens {
-                                    repeat: token,
-                                    until: Token::from_content("until"),
-                                });
-                            }
-                        }
-                        Statement::While(while_statement) => {
-                            if let Some(tokens) = while_statement.mutate_tokens() {
-                                self.location.append_comment(&mut tokens.r#while, text);
-                            } else {
-                                let mut token = Token::from_content("while");
-                                self.location.append_comment(&mut token, text);
-
-                                while_statement.set_tokens(WhileTokens {
-                                    r#while: token,
-                                    r#do: Token::from_content("do"),
-                                    end: Token::from_content("end"),
-                                });
-                            }
-                        }
-                        Statement::TypeDeclaration(type_declaration) => {
-                            let is_exported = type_declaration.is_exported();
-                            if let Some(tokens) = type_declaration.mutate_tokens() {
-                                if is_exported {
-                                    self.location.append_comment(
-                                        tokens
-                                            .export
-                                            .get_or_insert_with(|| Token::from_content("export")),
-                                        text,
-                                    );
-                                } else {
-                                    self.location.append_comment(&mut tokens.r#type, text);
-                                }
-                            } else if is_exported {
-                                let mut token = Token::from_content("export");
-                                self.location.append_comment(&mut token, text);
-
-                                type_declaration.set_tokens(TypeDeclarationTokens {
-                                    r#type: Token::from_content("type"),
-                                    equal: Token::from_content("="),
-                                    export: Some(token),
-                                });
-                            } else {
-                                let mut token = Token::from_content("type");
-                                self.location.append_comment(&mut token, text);
-
-                                type_declaration.set_tokens(TypeDeclarationTokens {
-                                    r#type: token,
-                                    equal: Token::from_content("="),
-                                    export: None,
-                                });
-                            }
-                        }
-                    }
+                    self.append_before_statement(statement, text, code)?;
                 } else if let Some(statement) = block.mutate_last_statement() {
-                    match statement {
-                        LastStatement::Break(token) => {
-                            self.location.append_comment(
-                                token.get_or_insert_with(|| Token::from_content("break")),
-                                text,
-                            );
-                        }
-                        LastStatement::Continue(token) => {
-                            self.location.append_comment(
-                                token.get_or_insert_with(|| Token::from_content("continue")),
-                                text,
-                            );
-                        }
-                        LastStatement::Return(return_statement) => {
-                            if let Some(tokens) = return_statement.mutate_tokens() {
-                                self.location.append_comment(&mut tokens.r#return, text);
-                            } else {
-                                let mut token = Token::from_content("return");
-                                self.location.append_comment(&mut token, text);
-
-                                return_statement.set_tokens(ReturnTokens {
-                                    r#return: token,
-                                    commas: Vec::new(),
-                                });
-                            }
-                        }
-                    }
+                    self.append_before_last_statement(statement, text, code);
                 } else {
-                    self.location.write_to_block(block, text);
+                    self.location.write_to_block(block, text, code);
                 }
             }
             AppendLocation::End => {
-                self.location.write_to_block(block, text);
+                ShiftTokenLine::new(shift_lines).flawless_process(block, context);
+
+                self.location.write_to_block(block, text, code);
+            }
+            AppendLocation::BeforeFunctions => {
+                self.append_before_functions(block, text, shift_lines, code)?;
             }
         }
 
@@ -387,6 +524,28 @@ impl Rule for AppendTextComment {
     }
 }
 
+/// Inserts a comment before the statement at the given index of the block. When there
+/// is no statement at that index, the comment is inserted before the last statement or
+/// at the end of the block.
+pub(crate) fn insert_comment_before_statement(
+    block: &mut Block,
+    index: usize,
+    comment: String,
+    code: &str,
+) -> RuleProcessResult {
+    let rule = AppendTextComment::new("");
+
+    if let Some(statement) = block.iter_mut_statements().nth(index) {
+        rule.append_before_statement(statement, comment, code)?;
+    } else if let Some(statement) = block.mutate_last_statement() {
+        rule.append_before_last_statement(statement, comment, code);
+    } else {
+        rule.location.write_to_block(block, comment, code);
+    }
+
+    Ok(())
+}
+
 fn variable_get_first_token(variable: &mut Variable) -> &mut Token {
     match variable {
         Variable::Identifier(identifier) => identifier_get_first_token(identifier),
@@ -456,13 +615,14 @@ impl RuleConfiguration for AppendTextComment {
                     self.location = match value.expect_string(&key)?.as_str() {
                         "start" => AppendLocation::Start,
                         "end" => AppendLocation::End,
+                        "before_functions" => AppendLocation::BeforeFunctions,
                         unexpected => {
                             return Err(RuleConfigurationError::UnexpectedValue {
                                 property: "location".to_owned(),
                                 message: format!(
-                                    "invalid value `{}` (must be `start` or `end`)",
-                                    unexpected
-                                ),
+                                "invalid value `{}` (must be `start`, `end` or `before_functions`)",
+                                unexpected
+                            ),
                             })
                         }
                     };
@@ -486,8 +646,37 @@ impl RuleConfiguration for AppendTextComment {
             AppendLocation::End => {
                 properties.insert("location".to_owned(), "end".into());
             }
+            AppendLocation::BeforeFunctions => {
+                properties.insert("location".to_owned(), "before_functions".into());
+            }
+        }
+
+        match &self.text_content {
+            TextContent::None => {}
+            TextContent::Value(value) => {
+                properties.insert("text".to_owned(), value.into());
+            }
+            TextContent::FilePath(file_path) => {
+                properties.insert(
+                    "file".to_owned(),
+                    file_path.to_string_lossy().to_string().into(),
+                );
+            }
         }
 
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        let location = match self.location {
+            AppendLocation::Start => "start",
+            AppendLocation::End => "end",
+            AppendLocation::BeforeFunctions => "before_functions",
+        };
+        properties.insert("location".to_owned(), location.into());
+
         match &self.text_content {
             TextContent::None => {}
             TextContent::Value(value) => {
@@ -522,18 +711,19 @@ impl Default for TextContent {
 enum AppendLocation {
     Start,
     End,
+    BeforeFunctions,
 }
 
 impl AppendLocation {
-    fn write_to_block(&self, block: &mut Block, comment: String) {
+    fn write_to_block(&self, block: &mut Block, comment: String, code: &str) {
         if let Some(tokens) = block.mutate_tokens() {
             let final_token = tokens
                 .final_token
                 .get_or_insert_with(|| Token::from_content(""));
-            self.append_comment(final_token, comment);
+            self.append_comment(final_token, comment, code);
         } else {
             let mut token = Token::from_content("");
-            self.append_comment(&mut token, comment);
+            self.append_comment(&mut token, comment, code);
 
             block.set_tokens(BlockTokens {
                 semicolons: Vec::new(),
@@ -543,18 +733,35 @@ impl AppendLocation {
         }
     }
 
-    fn append_comment(&self, token: &mut Token, comment: String) {
+    fn append_comment(&self, token: &mut Token, comment: String, code: &str) {
+        // the comment is always placed after the existing trivia, so that shebangs,
+        // directives like `--!strict` and comments appended by previous rules stay first
         match self {
-            AppendLocation::Start => {
+            AppendLocation::Start | AppendLocation::BeforeFunctions => {
+                if ends_with_comment(token.iter_leading_trivia(), code) {
+                    token.push_leading_trivia(TriviaKind::Whitespace.with_content("\n"));
+                }
                 token.push_leading_trivia(TriviaKind::Comment.with_content(comment));
             }
             AppendLocation::End => {
+                if ends_with_comment(token.iter_trailing_trivia(), code) {
+                    token.push_trailing_trivia(TriviaKind::Whitespace.with_content("\n"));
+                }
                 token.push_trailing_trivia(TriviaKind::Comment.with_content(comment));
             }
         }
     }
 }
 
+/// Returns `true` if the last trivia is a comment that is not followed by a line break,
+/// in which case a new comment cannot be written directly after it.
+fn ends_with_comment<'a>(trivia: impl Iterator<Item = &'a Trivia>, code: &str) -> bool {
+    matches!(
+        trivia.last(),
+        Some(trivia) if trivia.kind() == TriviaKind::Comment && !trivia.read(code).ends_with('\n')
+    )
+}
+
 impl Default for AppendLocation {
     fn default() -> Self {
         Self::Start
@@ -582,6 +789,13 @@ mod test {
         assert_json_snapshot!("append_text_comment_with_text_at_end", rule);
     }
 
+    #[test]
+    fn serialize_rule_with_text_before_functions() {
+        let rule: Box<dyn Rule> = Box::new(AppendTextComment::new("content").before_functions());
+
+        assert_json_snapshot!("append_text_comment_with_text_before_functions", rule);
+    }
+
     #[test]
     fn configure_with_extra_field_error() {
         let result = json5::from_str::<Box<dyn Rule>>(
@@ -19,6 +19,7 @@ pub(crate) struct BundleOptions {
     parser: Parser,
     modules_identifier: String,
     excludes: Option<wax::Any<'static>>,
+    remove_type_exports: bool,
 }
 
 impl BundleOptions {
@@ -50,6 +51,7 @@ impl BundleOptions {
                     .expect("exclude globs errors should be filtered and only emit a warning");
                 Some(any_pattern)
             },
+            remove_type_exports: false,
         }
     }
 
@@ -61,6 +63,10 @@ impl BundleOptions {
         &self.modules_identifier
     }
 
+    fn remove_type_exports(&self) -> bool {
+        self.remove_type_exports
+    }
+
     fn is_excluded(&self, require: &Path) -> bool {
         self.excludes
             .as_ref()
@@ -92,6 +98,12 @@ impl Bundler {
         self.options.modules_identifier = modules_identifier.into();
         self
     }
+
+    /// Removes the `export` keyword from the type declarations of the inlined modules.
+    pub(crate) fn with_type_exports_removed(mut self, remove_type_exports: bool) -> Self {
+        self.options.remove_type_exports = remove_type_exports;
+        self
+    }
 }
 
 impl Rule for Bundler {
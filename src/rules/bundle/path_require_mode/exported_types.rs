@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Tracks the types exported by the bundled modules, to find types with the same name
+/// once their `export` keyword is removed.
+#[derive(Debug, Default)]
+pub(crate) struct ExportedTypes {
+    modules: HashMap<String, PathBuf>,
+}
+
+impl ExportedTypes {
+    /// Registers a type exported by the given module. If a different module already
+    /// exported a type with the same name, the path of that module is returned.
+    pub(crate) fn insert(&mut self, name: String, module: &Path) -> Option<&Path> {
+        let previous_module = self
+            .modules
+            .entry(name)
+            .or_insert_with(|| module.to_path_buf());
+
+        if previous_module == module {
+            None
+        } else {
+            Some(previous_module.as_path())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn insert_new_type() {
+        let mut exported_types = ExportedTypes::default();
+
+        assert_eq!(
+            exported_types.insert("A".to_owned(), Path::new("a.lua")),
+            None
+        );
+    }
+
+    #[test]
+    fn insert_same_type_from_same_module() {
+        let mut exported_types = ExportedTypes::default();
+        exported_types.insert("A".to_owned(), Path::new("a.lua"));
+
+        assert_eq!(
+            exported_types.insert("A".to_owned(), Path::new("a.lua")),
+            None
+        );
+    }
+
+    #[test]
+    fn insert_same_type_from_other_module() {
+        let mut exported_types = ExportedTypes::default();
+        exported_types.insert("A".to_owned(), Path::new("a.lua"));
+
+        assert_eq!(
+            exported_types.insert("A".to_owned(), Path::new("b.lua")),
+            Some(Path::new("a.lua"))
+        );
+    }
+
+    #[test]
+    fn insert_other_type_from_other_module() {
+        let mut exported_types = ExportedTypes::default();
+        exported_types.insert("A".to_owned(), Path::new("a.lua"));
+
+        assert_eq!(
+            exported_types.insert("B".to_owned(), Path::new("b.lua")),
+            None
+        );
+    }
+}
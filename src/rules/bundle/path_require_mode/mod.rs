@@ -1,5 +1,7 @@
+mod exported_types;
 mod module_definitions;
 
+use exported_types::ExportedTypes;
 use module_definitions::BuildModuleDefinitions;
 
 use std::collections::{HashMap, HashSet};
@@ -9,22 +11,27 @@ use std::{iter, mem};
 
 use serde::Serialize;
 
-use crate::frontend::DarkluaResult;
+use crate::frontend::{
+    BundleManifestBuilder, DarkluaError, DarkluaResult, DiagnosticKind, ModuleEdge, ModuleNode,
+    RequireLocation,
+};
 use crate::nodes::{
-    Block, DoStatement, Expression, FunctionCall, LocalAssignStatement, Prefix, Statement,
-    StringExpression,
+    Block, DoStatement, Expression, FunctionCall, LocalAssignStatement, Prefix, SourcePosition,
+    Statement, StringExpression,
 };
 use crate::process::{
     to_expression, DefaultVisitor, IdentifierTracker, NodeProcessor, NodeVisitor, ScopeVisitor,
 };
 use crate::rules::require::{
-    is_require_call, match_path_require_call, PathRequireMode, RequirePathLocator,
+    is_require_call, match_path_require_call, match_require_literal, PathRequireMode,
+    RequirePathLocator,
 };
 use crate::rules::{
-    Context, ContextBuilder, FlawlessRule, ReplaceReferencedTokens, RuleProcessResult,
+    remove_type_exports, Context, ContextBuilder, FlawlessRule, ReplaceReferencedTokens,
+    RuleProcessResult,
 };
-use crate::utils::Timer;
-use crate::{DarkluaError, Resources};
+use crate::utils::{self, Timer};
+use crate::{DarkluaError, ResourceError, Resources};
 
 use super::BundleOptions;
 
@@ -40,11 +47,19 @@ struct RequirePathProcessor<'a, 'b, 'resources, 'code> {
     path_locator: RequirePathLocator<'b, 'code, 'resources>,
     module_definitions: BuildModuleDefinitions,
     source: PathBuf,
+    // modules are identified by their canonical path, so that a module required with
+    // different paths (like through a source alias and a relative path) is bundled once
     module_cache: HashMap<PathBuf, Expression>,
-    require_stack: Vec<PathBuf>,
+    // the canonical path and the path used to require each module being loaded
+    require_stack: Vec<(PathBuf, PathBuf)>,
     skip_module_paths: HashSet<PathBuf>,
     resources: &'resources Resources,
-    errors: Vec<String>,
+    errors: Vec<(String, Option<SourcePosition>, DiagnosticKind)>,
+    exported_types: ExportedTypes,
+    module_edges: Option<Vec<ModuleEdge>>,
+    // the code of the current source, only kept to locate require calls in the module graph
+    source_code: Option<String>,
+    manifest: Option<BundleManifestBuilder>,
 }
 
 impl<'a, 'b, 'code, 'resources> RequirePathProcessor<'a, 'b, 'code, 'resources> {
@@ -73,15 +88,82 @@ impl<'a, 'b, 'code, 'resources> RequirePathProcessor<'a, 'b, 'code, 'resources>
             skip_module_paths: Default::default(),
             resources: context.resources(),
             errors: Vec::new(),
+            exported_types: ExportedTypes::default(),
+            module_edges: context.is_collecting_module_graph().then(Vec::new),
+            source_code: context
+                .is_collecting_module_graph()
+                .then(|| context.original_code().to_owned()),
+            manifest: context.is_collecting_bundle_manifest().then(|| {
+                BundleManifestBuilder::new(context.project_location(), context.current_path())
+            }),
         }
     }
 
-    fn apply(self, block: &mut Block, context: &Context) -> RuleProcessResult {
+    fn apply(mut self, block: &mut Block, context: &Context) -> RuleProcessResult {
         self.module_definitions.apply(block, context);
+
+        for checked_path in self.path_locator.take_checked_paths() {
+            context.add_file_dependency(checked_path);
+        }
+
+        context.add_rule_note("bundled_modules", self.module_cache.len());
+
+        for module_edge in self.module_edges.take().into_iter().flatten() {
+            context.add_module_edge(module_edge);
+        }
+
+        if let Some(manifest) = self.manifest.take() {
+            context.set_bundle_manifest(manifest.build());
+        }
+
         match self.errors.len() {
             0 => Ok(()),
-            1 => Err(self.errors.first().unwrap().to_string()),
-            _ => Err(format!("- {}", self.errors.join("\n- "))),
+            1 => {
+                let (error, position, kind) = self.errors.pop().unwrap();
+                Err(context.error_of_kind_at(kind, position, error))
+            }
+            _ => Err(format!(
+                "- {}",
+                self.errors
+                    .iter()
+                    .map(|(error, position, _)| match position {
+                        Some(_) =>
+                            format!("{} (at `{}`)", error, context.format_position(*position)),
+                        None => error.to_owned(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n- ")
+            )
+            .into()),
+        }
+    }
+
+    /// Errors are located at the require call only when it comes from the current file,
+    /// because the positions of the tokens of required modules refer to other files.
+    fn push_error(&mut self, error: DarkluaError, kind: DiagnosticKind, call: &FunctionCall) {
+        let position = if self.require_stack.is_empty() {
+            call.get_prefix().start_position()
+        } else {
+            None
+        };
+        self.errors.push((error.to_string(), position, kind));
+    }
+
+    fn add_module_edge(&mut self, call: &FunctionCall, target: ModuleNode) {
+        if let Some(module_edges) = self.module_edges.as_mut() {
+            let location = call
+                .get_prefix()
+                .start_position()
+                .zip(self.source_code.as_deref())
+                .map(|(position, code)| RequireLocation::new(position, code));
+
+            module_edges.push(ModuleEdge::new(&self.source, target, location));
+        }
+    }
+
+    fn add_unresolved_module_edge(&mut self, call: &FunctionCall) {
+        if let Some(literal) = match_require_literal(call) {
+            self.add_module_edge(call, ModuleNode::unresolved(literal));
         }
     }
 
@@ -102,6 +184,10 @@ impl<'a, 'b, 'code, 'resources> RequirePathProcessor<'a, 'b, 'code, 'resources>
                 literal_require_path.display(),
                 self.source.display()
             );
+            if let Some(manifest) = self.manifest.as_mut() {
+                manifest.add_external_module(&self.source, &literal_require_path.to_string_lossy());
+            }
+            self.add_unresolved_module_edge(call);
             return None;
         }
 
@@ -111,18 +197,27 @@ impl<'a, 'b, 'code, 'resources> RequirePathProcessor<'a, 'b, 'code, 'resources>
         {
             Ok(path) => path,
             Err(err) => {
-                self.errors.push(err.to_string());
+                self.add_unresolved_module_edge(call);
+                self.push_error(err, DiagnosticKind::RequireResolution, call);
                 return None;
             }
         };
 
+        self.add_module_edge(call, ModuleNode::file(&require_path));
+
         log::debug!(
             "found require call to path `{}` (normalized `{}`)",
             literal_require_path.display(),
             require_path.display()
         );
 
-        if self.skip_module_paths.contains(&require_path) {
+        let module_key = self.get_module_key(&require_path);
+
+        if let Some(manifest) = self.manifest.as_mut() {
+            manifest.add_dependency(&self.source, &module_key, &require_path);
+        }
+
+        if self.skip_module_paths.contains(&module_key) {
             log::trace!(
                 "skip `{}` because it previously errored",
                 require_path.display()
@@ -130,36 +225,49 @@ impl<'a, 'b, 'code, 'resources> RequirePathProcessor<'a, 'b, 'code, 'resources>
             return None;
         }
 
-        match self.inline_require(&require_path, call) {
+        match self.inline_require(&require_path, module_key.clone(), call) {
             Ok(expression) => Some(expression),
             Err(error) => {
-                self.errors.push(error.to_string());
-                self.skip_module_paths.insert(require_path);
+                let kind = error.to_diagnostic().kind();
+                self.push_error(error, kind, call);
+                self.skip_module_paths.insert(module_key);
                 None
             }
         }
     }
 
+    fn get_module_key(&self, require_path: &Path) -> PathBuf {
+        self.resources
+            .canonicalize(require_path)
+            .unwrap_or_else(|err| {
+                log::trace!(
+                    "unable to canonicalize `{}`: {}",
+                    require_path.display(),
+                    err
+                );
+                utils::normalize_path(require_path)
+            })
+    }
+
     fn inline_require(
         &mut self,
         require_path: &Path,
+        module_key: PathBuf,
         call: &FunctionCall,
     ) -> DarkluaResult<Expression> {
-        if let Some(expression) = self.module_cache.get(require_path) {
+        if let Some(expression) = self.module_cache.get(&module_key) {
             Ok(expression.clone())
         } else {
             if let Some(i) = self
                 .require_stack
                 .iter()
-                .enumerate()
-                .find(|(_, path)| **path == require_path)
-                .map(|(i, _)| i)
+                .position(|(key, _)| *key == module_key)
             {
                 let require_stack_paths: Vec<_> = self
                     .require_stack
                     .iter()
                     .skip(i)
-                    .map(|path| path.display().to_string())
+                    .map(|(_, path)| path.display().to_string())
                     .chain(iter::once(require_path.display().to_string()))
                     .collect();
 
@@ -169,7 +277,8 @@ impl<'a, 'b, 'code, 'resources> RequirePathProcessor<'a, 'b, 'code, 'resources>
                 )));
             }
 
-            self.require_stack.push(require_path.to_path_buf());
+            self.require_stack
+                .push((module_key.clone(), require_path.to_path_buf()));
             let required_resource = self.require_resource(require_path);
             self.require_stack.pop();
 
@@ -179,8 +288,13 @@ impl<'a, 'b, 'code, 'resources> RequirePathProcessor<'a, 'b, 'code, 'resources>
                 call,
             )?;
 
-            self.module_cache
-                .insert(require_path.to_path_buf(), module_value.clone());
+            if let Some(manifest) = self.manifest.as_mut() {
+                if let Some(module_name) = self.module_definitions.last_module_name() {
+                    manifest.add_inlined_module(&module_key, require_path, module_name);
+                }
+            }
+
+            self.module_cache.insert(module_key, module_value.clone());
 
             Ok(module_value)
         }
@@ -189,7 +303,17 @@ impl<'a, 'b, 'code, 'resources> RequirePathProcessor<'a, 'b, 'code, 'resources>
     fn require_resource(&mut self, path: impl AsRef<Path>) -> DarkluaResult<RequiredResource> {
         let path = path.as_ref();
         log::trace!("look for resource `{}`", path.display());
-        let content = self.resources.get(path).map_err(DarkluaError::from)?;
+        let content = self.resources.get(path).map_err(|err| match err {
+            ResourceError::InvalidUtf8(_) => DarkluaError::invalid_resource_path(
+                path.display().to_string(),
+                "the file does not contain valid UTF-8",
+            ),
+            err => DarkluaError::from(err),
+        })?;
+
+        if let Some(manifest) = self.manifest.as_mut() {
+            manifest.add_module_size(path, content.len());
+        }
 
         match path.extension() {
             Some(extension) => match extension.to_string_lossy().as_ref() {
@@ -227,6 +351,10 @@ impl<'a, 'b, 'code, 'resources> RequirePathProcessor<'a, 'b, 'code, 'resources>
                     }
 
                     let current_source = mem::replace(&mut self.source, path.to_path_buf());
+                    let current_code = self
+                        .source_code
+                        .as_mut()
+                        .map(|code| mem::replace(code, content.clone()));
 
                     let apply_processor_timer = Timer::now();
                     DefaultVisitor::visit_block(&mut block, self);
@@ -238,6 +366,13 @@ impl<'a, 'b, 'code, 'resources> RequirePathProcessor<'a, 'b, 'code, 'resources>
                     );
 
                     self.source = current_source;
+                    if current_code.is_some() {
+                        self.source_code = current_code;
+                    }
+
+                    if self.options.remove_type_exports() {
+                        self.remove_type_exports(&mut block, path);
+                    }
 
                     Ok(RequiredResource::Block(block))
                 }
@@ -259,6 +394,19 @@ impl<'a, 'b, 'code, 'resources> RequirePathProcessor<'a, 'b, 'code, 'resources>
             None => unreachable!("extension should be defined"),
         }
     }
+
+    fn remove_type_exports(&mut self, block: &mut Block, path: &Path) {
+        for name in remove_type_exports(block) {
+            if let Some(other_path) = self.exported_types.insert(name.clone(), path) {
+                log::warn!(
+                    "type `{}` exported by `{}` has the same name as the type exported by `{}`",
+                    name,
+                    path.display(),
+                    other_path.display()
+                );
+            }
+        }
+    }
 }
 
 impl Deref for RequirePathProcessor<'_, '_, '_, '_> {
@@ -343,7 +491,7 @@ pub(crate) fn process_block(
     context: &Context,
     options: &BundleOptions,
     path_require_mode: &PathRequireMode,
-) -> Result<(), String> {
+) -> RuleProcessResult {
     if options.parser().is_preserving_tokens() {
         log::trace!(
             "replacing token references of {}",
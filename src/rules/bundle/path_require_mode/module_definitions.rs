@@ -124,6 +124,13 @@ impl BuildModuleDefinitions {
         Ok(new_require_call)
     }
 
+    /// The name of the module built last, used to load it from the modules table.
+    pub(crate) fn last_module_name(&self) -> Option<&str> {
+        self.module_definitions
+            .last()
+            .map(|(module_name, _, _)| module_name.as_str())
+    }
+
     fn generate_module_name(&mut self) -> String {
         loop {
             let name = generate_identifier(&mut self.module_name_permutator);
@@ -300,6 +307,8 @@ fn last_statement_token(statement: &Statement) -> Option<&Token> {
         Statement::ExportTypeFunction(export_type_function) => {
             export_type_function.get_tokens().map(|tokens| &tokens.end)
         }
+        Statement::Goto(goto) => goto.get_label().get_token(),
+        Statement::Label(label) => label.get_tokens().map(|tokens| &tokens.right_colons),
         Statement::NumericFor(numeric_for) => numeric_for.get_tokens().map(|tokens| &tokens.end),
         Statement::Repeat(repeat) => last_expression_token(repeat.get_condition()),
         Statement::While(while_statement) => while_statement.get_tokens().map(|tokens| &tokens.end),
@@ -0,0 +1,660 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::iter;
+
+use crate::nodes::{
+    AssignStatement, BinaryOperator, Block, CompoundAssignStatement, Expression, FieldExpression,
+    FunctionCall, FunctionExpression, FunctionStatement, Identifier, InterpolationSegment,
+    LabelStatement, LastStatement, LocalAssignStatement, LocalFunctionStatement, Prefix, Statement,
+    TableEntry, Variable,
+};
+use crate::process::utils::collect_identifiers;
+use crate::process::{
+    DefaultPostVisitor, DefaultVisitor, NodePostProcessor, NodePostVisitor, NodeProcessor,
+    NodeVisitor,
+};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+use super::lua_limits::{count_active_locals, MAX_LOCALS};
+use super::verify_no_rule_properties;
+
+const CACHED_FIELD_PREFIX: &str = "__DARKLUA_CACHED";
+const MINIMUM_USES: usize = 3;
+const MINIMUM_FIELDS: usize = 2;
+
+/// Finds what a statement can modify. A statement that calls a function, assigns a field
+/// or an index, defines a function with a name or defines a label is a barrier: it may
+/// mutate any table (or jump back over a cached read), so it ends every sequence of reads.
+#[derive(Debug, Default)]
+struct StatementEffects {
+    is_barrier: bool,
+    assigned_identifiers: HashSet<String>,
+}
+
+impl StatementEffects {
+    fn from_statement(statement: &mut Statement) -> Self {
+        let mut effects = Self::default();
+
+        match statement {
+            Statement::LocalAssign(assign) => {
+                for variable in assign.iter_variables() {
+                    effects
+                        .assigned_identifiers
+                        .insert(variable.get_name().to_owned());
+                }
+            }
+            Statement::LocalFunction(function) => {
+                effects
+                    .assigned_identifiers
+                    .insert(function.get_name().to_owned());
+            }
+            _ => {}
+        }
+
+        DefaultVisitor::visit_statement(statement, &mut effects);
+        effects
+    }
+
+    fn from_last_statement(statement: &mut LastStatement) -> Self {
+        let mut effects = Self::default();
+        DefaultVisitor::visit_last_statement(statement, &mut effects);
+        effects
+    }
+
+    fn assign_variable(&mut self, variable: &Variable) {
+        match variable {
+            Variable::Identifier(identifier) => {
+                self.assigned_identifiers
+                    .insert(identifier.get_name().to_owned());
+            }
+            Variable::Field(_) | Variable::Index(_) => {
+                self.is_barrier = true;
+            }
+        }
+    }
+}
+
+impl NodeProcessor for StatementEffects {
+    fn is_stopped(&self) -> bool {
+        self.is_barrier
+    }
+
+    fn process_function_call(&mut self, _: &mut FunctionCall) {
+        self.is_barrier = true;
+    }
+
+    fn process_assign_statement(&mut self, assign: &mut AssignStatement) {
+        for variable in assign.get_variables() {
+            self.assign_variable(variable);
+        }
+    }
+
+    fn process_compound_assign_statement(&mut self, assign: &mut CompoundAssignStatement) {
+        self.assign_variable(assign.get_variable());
+    }
+
+    fn process_function_statement(&mut self, _: &mut FunctionStatement) {
+        self.is_barrier = true;
+    }
+
+    fn process_label_statement(&mut self, _: &mut LabelStatement) {
+        self.is_barrier = true;
+    }
+}
+
+#[derive(Debug)]
+struct ChainUse {
+    root: String,
+    path: String,
+    conditional: bool,
+}
+
+/// Walks the expressions that a statement evaluates itself (without entering nested blocks
+/// or functions) to find the field chains it reads, or to replace one of them with an
+/// identifier.
+#[derive(Debug, Default)]
+struct ChainWalker<'a> {
+    uses: Vec<ChainUse>,
+    replacement: Option<(&'a str, &'a str)>,
+}
+
+impl<'a> ChainWalker<'a> {
+    fn replacing(path: &'a str, identifier: &'a str) -> Self {
+        Self {
+            uses: Vec::new(),
+            replacement: Some((path, identifier)),
+        }
+    }
+
+    fn walk_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::Assign(assign) => {
+                for value in assign.iter_mut_values() {
+                    self.walk_expression(value, false);
+                }
+            }
+            Statement::CompoundAssign(assign) => {
+                self.walk_expression(assign.mutate_value(), false);
+            }
+            Statement::LocalAssign(assign) => {
+                for value in assign.iter_mut_values() {
+                    self.walk_expression(value, false);
+                }
+            }
+            Statement::If(if_statement) => {
+                // only the first condition is always evaluated
+                for (index, branch) in if_statement.mutate_branches().iter_mut().enumerate() {
+                    self.walk_expression(branch.mutate_condition(), index != 0);
+                }
+            }
+            Statement::While(while_statement) => {
+                self.walk_expression(while_statement.mutate_condition(), false);
+            }
+            Statement::NumericFor(numeric_for) => {
+                self.walk_expression(numeric_for.mutate_start(), false);
+                self.walk_expression(numeric_for.mutate_end(), false);
+                if let Some(step) = numeric_for.mutate_step() {
+                    self.walk_expression(step, false);
+                }
+            }
+            Statement::GenericFor(generic_for) => {
+                for expression in generic_for.iter_mut_expressions() {
+                    self.walk_expression(expression, false);
+                }
+            }
+            // the condition of a repeat statement is evaluated in the scope of its block,
+            // where the root of a chain may be shadowed
+            Statement::Repeat(_)
+            | Statement::Call(_)
+            | Statement::Do(_)
+            | Statement::Function(_)
+            | Statement::Goto(_)
+            | Statement::Label(_)
+            | Statement::LocalFunction(_)
+            | Statement::TypeFunction(_)
+            | Statement::ExportTypeFunction(_)
+            | Statement::TypeDeclaration(_) => {}
+        }
+    }
+
+    fn walk_last_statement(&mut self, statement: &mut LastStatement) {
+        if let LastStatement::Return(statement) = statement {
+            for expression in statement.iter_mut_expressions() {
+                self.walk_expression(expression, false);
+            }
+        }
+    }
+
+    fn walk_expression(&mut self, expression: &mut Expression, conditional: bool) {
+        let replacement = match expression {
+            Expression::Field(field) => self.walk_field(field, conditional),
+            Expression::Binary(binary) => {
+                let short_circuits =
+                    matches!(binary.operator(), BinaryOperator::And | BinaryOperator::Or);
+                self.walk_expression(binary.mutate_left(), conditional);
+                self.walk_expression(binary.mutate_right(), conditional || short_circuits);
+                None
+            }
+            Expression::If(if_expression) => {
+                self.walk_expression(if_expression.mutate_condition(), conditional);
+                self.walk_expression(if_expression.mutate_result(), true);
+                for branch in if_expression.iter_mut_branches() {
+                    self.walk_expression(branch.mutate_condition(), true);
+                    self.walk_expression(branch.mutate_result(), true);
+                }
+                self.walk_expression(if_expression.mutate_else_result(), true);
+                None
+            }
+            Expression::Index(index) => {
+                self.walk_prefix(index.mutate_prefix(), conditional);
+                self.walk_expression(index.mutate_index(), conditional);
+                None
+            }
+            Expression::Parenthese(parenthese) => {
+                self.walk_expression(parenthese.mutate_inner_expression(), conditional);
+                None
+            }
+            Expression::Table(table) => {
+                for entry in table.iter_mut_entries() {
+                    match entry {
+                        TableEntry::Field(entry) => {
+                            self.walk_expression(entry.mutate_value(), conditional);
+                        }
+                        TableEntry::Index(entry) => {
+                            self.walk_expression(entry.mutate_key(), conditional);
+                            self.walk_expression(entry.mutate_value(), conditional);
+                        }
+                        TableEntry::Value(value) => {
+                            self.walk_expression(value, conditional);
+                        }
+                    }
+                }
+                None
+            }
+            Expression::InterpolatedString(string) => {
+                for segment in string.iter_mut_segments() {
+                    if let InterpolationSegment::Value(segment) = segment {
+                        self.walk_expression(segment.mutate_expression(), conditional);
+                    }
+                }
+                None
+            }
+            Expression::Unary(unary) => {
+                self.walk_expression(unary.mutate_expression(), conditional);
+                None
+            }
+            Expression::TypeCast(type_cast) => {
+                self.walk_expression(type_cast.mutate_expression(), conditional);
+                None
+            }
+            Expression::Call(_)
+            | Expression::False(_)
+            | Expression::Function(_)
+            | Expression::Identifier(_)
+            | Expression::Nil(_)
+            | Expression::Number(_)
+            | Expression::String(_)
+            | Expression::True(_)
+            | Expression::VariableArguments(_) => None,
+        };
+
+        if let Some(identifier) = replacement {
+            *expression = Identifier::new(identifier).into();
+        }
+    }
+
+    fn walk_prefix(&mut self, prefix: &mut Prefix, conditional: bool) {
+        let replacement = match prefix {
+            Prefix::Field(field) => self.walk_field(field, conditional),
+            Prefix::Index(index) => {
+                self.walk_prefix(index.mutate_prefix(), conditional);
+                self.walk_expression(index.mutate_index(), conditional);
+                None
+            }
+            Prefix::Parenthese(parenthese) => {
+                self.walk_expression(parenthese.mutate_inner_expression(), conditional);
+                None
+            }
+            Prefix::Call(_) | Prefix::Identifier(_) => None,
+        };
+
+        if let Some(identifier) = replacement {
+            *prefix = Prefix::from_name(identifier);
+        }
+    }
+
+    /// Returns the identifier that replaces the field, or walks the prefix of the field.
+    fn walk_field(&mut self, field: &mut FieldExpression, conditional: bool) -> Option<&'a str> {
+        if let Some((root, path)) = get_chain(field) {
+            match self.replacement {
+                Some((replaced_path, identifier)) => {
+                    if path == replaced_path {
+                        return Some(identifier);
+                    }
+                }
+                None => self.uses.push(ChainUse {
+                    root,
+                    path,
+                    conditional,
+                }),
+            }
+        }
+
+        self.walk_prefix(field.mutate_prefix(), conditional);
+        None
+    }
+}
+
+/// Returns the root identifier and the dotted path of a field chain like `a.b.c`, if
+/// the chain has enough fields to be cached.
+fn get_chain(field: &FieldExpression) -> Option<(String, String)> {
+    let mut names = vec![field.get_field().get_name().as_str()];
+    let mut prefix = field.get_prefix();
+
+    loop {
+        match prefix {
+            Prefix::Field(field) => {
+                names.push(field.get_field().get_name().as_str());
+                prefix = field.get_prefix();
+            }
+            Prefix::Identifier(identifier) if names.len() >= MINIMUM_FIELDS => {
+                let root = identifier.get_name().to_owned();
+                let path = iter::once(root.as_str())
+                    .chain(names.into_iter().rev())
+                    .collect::<Vec<_>>()
+                    .join(".");
+                break Some((root, path));
+            }
+            _ => break None,
+        }
+    }
+}
+
+fn build_chain(path: &str) -> Prefix {
+    let mut names = path.split('.');
+    let mut prefix = Prefix::from_name(names.next().unwrap_or(path));
+
+    for name in names {
+        prefix = FieldExpression::new(prefix, name).into();
+    }
+
+    prefix
+}
+
+#[derive(Debug)]
+struct ChainSequence {
+    root: String,
+    first_statement: usize,
+    last_statement: usize,
+    uses: usize,
+}
+
+#[derive(Debug)]
+struct CachedChain {
+    path: String,
+    fields: usize,
+    first_statement: usize,
+    last_statement: usize,
+}
+
+impl CachedChain {
+    /// The longest chains are cached first, then the ones that appear first.
+    fn is_better_than(&self, other: &Self) -> bool {
+        (Reverse(self.fields), self.first_statement, &self.path)
+            < (Reverse(other.fields), other.first_statement, &other.path)
+    }
+}
+
+/// Tracks the sequences of statements where each chain is read, and keeps the best chain
+/// read enough times in a single sequence.
+#[derive(Debug, Default)]
+struct ChainTracker {
+    sequences: HashMap<String, ChainSequence>,
+    best: Option<CachedChain>,
+}
+
+impl ChainTracker {
+    fn process(&mut self, index: usize, effects: StatementEffects, uses: Vec<ChainUse>) {
+        if effects.is_barrier {
+            self.close_all();
+            return;
+        }
+
+        // sequences end before a statement that assigns the root of the chain
+        let assigned_paths: Vec<_> = self
+            .sequences
+            .iter()
+            .filter(|(_, sequence)| effects.assigned_identifiers.contains(&sequence.root))
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in assigned_paths {
+            self.close(path);
+        }
+
+        let mut statement_uses: HashMap<String, (String, usize, bool)> = HashMap::new();
+        for chain_use in uses {
+            if effects.assigned_identifiers.contains(&chain_use.root) {
+                continue;
+            }
+            let entry = statement_uses
+                .entry(chain_use.path)
+                .or_insert((chain_use.root, 0, false));
+            entry.1 += 1;
+            entry.2 |= !chain_use.conditional;
+        }
+
+        for (path, (root, count, has_unconditional_use)) in statement_uses {
+            if let Some(sequence) = self.sequences.get_mut(&path) {
+                sequence.uses += count;
+                sequence.last_statement = index;
+            } else if has_unconditional_use {
+                // the cached value is read before the statement, so the statement must
+                // read it whatever the conditions
+                self.sequences.insert(
+                    path,
+                    ChainSequence {
+                        root,
+                        first_statement: index,
+                        last_statement: index,
+                        uses: count,
+                    },
+                );
+            }
+        }
+    }
+
+    fn close(&mut self, path: String) {
+        if let Some(sequence) = self.sequences.remove(&path) {
+            if sequence.uses < MINIMUM_USES {
+                return;
+            }
+            let chain = CachedChain {
+                fields: path.matches('.').count(),
+                path,
+                first_statement: sequence.first_statement,
+                last_statement: sequence.last_statement,
+            };
+            let is_better = match &self.best {
+                Some(best) => chain.is_better_than(best),
+                None => true,
+            };
+            if is_better {
+                self.best = Some(chain);
+            }
+        }
+    }
+
+    fn close_all(&mut self) {
+        let paths: Vec<_> = self.sequences.keys().cloned().collect();
+        for path in paths {
+            self.close(path);
+        }
+    }
+}
+
+fn find_cacheable_chain(block: &mut Block) -> Option<CachedChain> {
+    let mut tracker = ChainTracker::default();
+    let statements_len = block.statements_len();
+
+    for (index, statement) in block.iter_mut_statements().enumerate() {
+        let effects = StatementEffects::from_statement(statement);
+        let mut walker = ChainWalker::default();
+        if !effects.is_barrier {
+            walker.walk_statement(statement);
+        }
+        tracker.process(index, effects, walker.uses);
+    }
+
+    if let Some(statement) = block.mutate_last_statement() {
+        let effects = StatementEffects::from_last_statement(statement);
+        let mut walker = ChainWalker::default();
+        if !effects.is_barrier {
+            walker.walk_last_statement(statement);
+        }
+        tracker.process(statements_len, effects, walker.uses);
+    }
+
+    tracker.close_all();
+    tracker.best
+}
+
+struct CacheFieldAccessProcessor {
+    used_identifiers: HashSet<String>,
+    next_index: usize,
+    /// For each function being visited, the maximum number of locals active at the same
+    /// time, counting every cached chain as if it was active at that point.
+    function_locals: Vec<usize>,
+}
+
+impl CacheFieldAccessProcessor {
+    fn new(block: &Block) -> Self {
+        Self {
+            used_identifiers: collect_identifiers(block),
+            next_index: 1,
+            function_locals: vec![count_active_locals(block)],
+        }
+    }
+
+    fn enter_function(&mut self, parameters: usize, block: &Block) {
+        self.function_locals
+            .push(parameters + count_active_locals(block));
+    }
+
+    fn leave_function(&mut self) {
+        self.function_locals.pop();
+    }
+
+    fn declare_local(&mut self) -> bool {
+        match self.function_locals.last_mut() {
+            Some(locals) if *locals < MAX_LOCALS => {
+                *locals += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn generate_identifier(&mut self) -> String {
+        loop {
+            let identifier = format!("{}{}", CACHED_FIELD_PREFIX, self.next_index);
+            self.next_index += 1;
+            if !self.used_identifiers.contains(&identifier) {
+                break identifier;
+            }
+        }
+    }
+
+    fn cache_chain(&mut self, block: &mut Block, chain: CachedChain) {
+        let identifier = self.generate_identifier();
+        let mut walker = ChainWalker::replacing(&chain.path, &identifier);
+        let statements_len = block.statements_len();
+
+        for statement in block
+            .iter_mut_statements()
+            .skip(chain.first_statement)
+            .take(chain.last_statement + 1 - chain.first_statement)
+        {
+            walker.walk_statement(statement);
+        }
+
+        if chain.last_statement == statements_len {
+            if let Some(statement) = block.mutate_last_statement() {
+                walker.walk_last_statement(statement);
+            }
+        }
+
+        block.insert_statement(
+            chain.first_statement,
+            LocalAssignStatement::from_variable(identifier.as_str())
+                .with_value(build_chain(&chain.path)),
+        );
+    }
+}
+
+impl NodeProcessor for CacheFieldAccessProcessor {
+    fn process_block(&mut self, block: &mut Block) {
+        while let Some(chain) = find_cacheable_chain(block) {
+            if !self.declare_local() {
+                break;
+            }
+            self.cache_chain(block, chain);
+        }
+    }
+
+    fn process_function_expression(&mut self, function: &mut FunctionExpression) {
+        self.enter_function(function.parameters_count(), function.get_block());
+    }
+
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        let parameters =
+            function.parameters_count() + usize::from(function.get_name().has_method());
+        self.enter_function(parameters, function.get_block());
+    }
+
+    fn process_local_function_statement(&mut self, function: &mut LocalFunctionStatement) {
+        self.enter_function(function.parameters_count(), function.get_block());
+    }
+}
+
+impl NodePostProcessor for CacheFieldAccessProcessor {
+    fn process_after_function_expression(&mut self, _: &mut FunctionExpression) {
+        self.leave_function();
+    }
+
+    fn process_after_function_statement(&mut self, _: &mut FunctionStatement) {
+        self.leave_function();
+    }
+
+    fn process_after_local_function_statement(&mut self, _: &mut LocalFunctionStatement) {
+        self.leave_function();
+    }
+}
+
+pub const CACHE_FIELD_ACCESS_RULE_NAME: &str = "cache_field_access";
+
+/// A rule that stores field chains read several times in a block into local variables.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CacheFieldAccess {}
+
+impl FlawlessRule for CacheFieldAccess {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut processor = CacheFieldAccessProcessor::new(block);
+        DefaultPostVisitor::visit_block(block, &mut processor);
+    }
+}
+
+impl RuleConfiguration for CacheFieldAccess {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        verify_no_rule_properties(&properties)?;
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        CACHE_FIELD_ACCESS_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        RuleProperties::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> CacheFieldAccess {
+        CacheFieldAccess::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_cache_field_access", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'cache_field_access',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn build_chain_from_path() {
+        assert_eq!(
+            build_chain("self.config"),
+            FieldExpression::new(Prefix::from_name("self"), "config").into()
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::{HashMap, HashSet};
+use std::ops;
+
+use crate::nodes::{
+    Arguments, AssignStatement, Block, CompoundAssignStatement, Expression, FunctionCall,
+    Identifier, LocalAssignStatement, LocalFunctionStatement, Prefix, Statement, StringExpression,
+    TypedIdentifier, Variable,
+};
+use crate::process::utils::{collect_identifiers, is_valid_identifier};
+use crate::process::{DefaultVisitor, IdentifierTracker, NodeProcessor, NodeVisitor, ScopeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+use super::verify_no_rule_properties;
+
+const DATAMODEL_IDENTIFIER: &str = "game";
+const GET_SERVICE_METHOD: &str = "GetService";
+
+/// Returns the service name of a `game:GetService("Name")` call, when the service name
+/// is a string literal that can be used as a variable name.
+fn get_service_name(call: &FunctionCall) -> Option<&str> {
+    if call.get_method()?.get_name() != GET_SERVICE_METHOD {
+        return None;
+    }
+
+    match call.get_prefix() {
+        Prefix::Identifier(identifier) if identifier.get_name() == DATAMODEL_IDENTIFIER => {}
+        _ => return None,
+    }
+
+    let name = match call.get_arguments() {
+        Arguments::String(string) => string.get_value(),
+        Arguments::Tuple(tuple) => {
+            let mut values = tuple.iter_values();
+            match (values.next(), values.next()) {
+                (Some(Expression::String(string)), None) => string.get_value(),
+                _ => return None,
+            }
+        }
+        Arguments::Table(_) => return None,
+    };
+
+    if is_valid_identifier(name) {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+fn create_service_call(service: &str) -> FunctionCall {
+    FunctionCall::from_name(DATAMODEL_IDENTIFIER)
+        .with_method(GET_SERVICE_METHOD)
+        .with_argument(StringExpression::from_value(service))
+}
+
+/// Collects the names of the variables assigned anywhere in the block, because a local
+/// holding a service can only be reused if it always contains that service.
+#[derive(Debug, Default)]
+struct AssignedVariables {
+    names: HashSet<String>,
+}
+
+impl NodeProcessor for AssignedVariables {
+    fn process_assign_statement(&mut self, assign: &mut AssignStatement) {
+        for variable in assign.iter_variables() {
+            if let Variable::Identifier(identifier) = variable {
+                self.names.insert(identifier.get_name().to_owned());
+            }
+        }
+    }
+
+    fn process_compound_assign_statement(&mut self, assign: &mut CompoundAssignStatement) {
+        if let Variable::Identifier(identifier) = assign.get_variable() {
+            self.names.insert(identifier.get_name().to_owned());
+        }
+    }
+}
+
+/// Replaces the `game:GetService("Name")` calls with a local variable. The locals already
+/// declared at the root of the file are reused, and new locals are named after the
+/// service without colliding with the existing identifiers.
+#[derive(Debug)]
+struct ServiceReplacer {
+    identifier_tracker: IdentifierTracker,
+    assigned_variables: HashSet<String>,
+    used_identifiers: HashSet<String>,
+    // the locals declared at the root of the file for each service
+    existing_locals: HashMap<String, String>,
+    // the new locals for each service, in the order where they are first needed
+    new_locals: Vec<(String, String)>,
+    new_local_indexes: HashMap<String, usize>,
+    // the value of a local that is reused must not be replaced
+    skip_next_call: bool,
+}
+
+impl ServiceReplacer {
+    fn new(block: &mut Block) -> Self {
+        let mut assigned_variables = AssignedVariables::default();
+        DefaultVisitor::visit_block(block, &mut assigned_variables);
+
+        Self {
+            identifier_tracker: IdentifierTracker::new(),
+            assigned_variables: assigned_variables.names,
+            used_identifiers: collect_identifiers(block),
+            existing_locals: HashMap::new(),
+            new_locals: Vec::new(),
+            new_local_indexes: HashMap::new(),
+            skip_next_call: false,
+        }
+    }
+
+    fn refers_to_datamodel(&self) -> bool {
+        !self.is_identifier_used(DATAMODEL_IDENTIFIER)
+    }
+
+    fn get_new_local(&mut self, service: &str) -> String {
+        if let Some(index) = self.new_local_indexes.get(service) {
+            return self.new_locals[*index].1.clone();
+        }
+
+        let mut identifier = service.to_owned();
+        let mut suffix = 2;
+        while self.used_identifiers.contains(&identifier) {
+            identifier = format!("{}{}", service, suffix);
+            suffix += 1;
+        }
+        self.used_identifiers.insert(identifier.clone());
+
+        self.new_local_indexes
+            .insert(service.to_owned(), self.new_locals.len());
+        self.new_locals
+            .push((service.to_owned(), identifier.clone()));
+        identifier
+    }
+
+    fn get_replacement(&mut self, call: &FunctionCall) -> Option<Identifier> {
+        if self.skip_next_call {
+            self.skip_next_call = false;
+            return None;
+        }
+
+        if !self.refers_to_datamodel() {
+            return None;
+        }
+
+        let service = get_service_name(call)?.to_owned();
+
+        let existing_local = self
+            .existing_locals
+            .get(&service)
+            .filter(|name| self.get_identifier_depth(name) == Some(1))
+            .cloned();
+
+        let identifier = match existing_local {
+            Some(name) => name,
+            None => self.get_new_local(&service),
+        };
+
+        Some(Identifier::new(identifier))
+    }
+
+    fn forget_existing_local(&mut self, name: &str) {
+        self.existing_locals.retain(|_, local| local != name);
+    }
+
+    fn into_declarations(self) -> Vec<Statement> {
+        self.new_locals
+            .into_iter()
+            .map(|(service, identifier)| {
+                LocalAssignStatement::new(
+                    vec![TypedIdentifier::new(identifier)],
+                    vec![create_service_call(&service).into()],
+                )
+                .into()
+            })
+            .collect()
+    }
+}
+
+impl ops::Deref for ServiceReplacer {
+    type Target = IdentifierTracker;
+
+    fn deref(&self) -> &Self::Target {
+        &self.identifier_tracker
+    }
+}
+
+impl ops::DerefMut for ServiceReplacer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.identifier_tracker
+    }
+}
+
+impl NodeProcessor for ServiceReplacer {
+    fn process_local_assign_statement(&mut self, assign: &mut LocalAssignStatement) {
+        if self.scope_depth() != 1 {
+            return;
+        }
+
+        for variable in assign.iter_variables() {
+            self.forget_existing_local(variable.get_name());
+        }
+
+        if assign.variables_len() != 1 || assign.values_len() != 1 || !self.refers_to_datamodel() {
+            return;
+        }
+
+        let name = match assign.iter_variables().next() {
+            Some(variable) => variable.get_name(),
+            None => return,
+        };
+
+        if self.assigned_variables.contains(name) {
+            return;
+        }
+
+        let service = match assign.iter_values().next() {
+            Some(Expression::Call(call)) => get_service_name(call),
+            _ => None,
+        };
+
+        if let Some(service) = service {
+            if !self.existing_locals.contains_key(service) {
+                self.existing_locals
+                    .insert(service.to_owned(), name.to_owned());
+                self.skip_next_call = true;
+            }
+        }
+    }
+
+    fn process_local_function_statement(&mut self, function: &mut LocalFunctionStatement) {
+        if self.scope_depth() == 1 {
+            self.forget_existing_local(function.get_name());
+        }
+    }
+
+    fn process_expression(&mut self, expression: &mut Expression) {
+        let identifier = match expression {
+            Expression::Call(call) => self.get_replacement(call),
+            _ => None,
+        };
+
+        if let Some(identifier) = identifier {
+            *expression = identifier.into();
+        }
+    }
+
+    fn process_prefix_expression(&mut self, prefix: &mut Prefix) {
+        let identifier = match prefix {
+            Prefix::Call(call) => self.get_replacement(call),
+            _ => None,
+        };
+
+        if let Some(identifier) = identifier {
+            *prefix = identifier.into();
+        }
+    }
+}
+
+pub const CACHE_SERVICES_RULE_NAME: &str = "cache_services";
+
+/// A rule that stores the Roblox services obtained with `game:GetService("Name")` in
+/// local variables declared at the top of the file.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CacheServices {}
+
+impl FlawlessRule for CacheServices {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut replacer = ServiceReplacer::new(block);
+        ScopeVisitor::visit_block(block, &mut replacer);
+
+        let mut declarations = replacer.into_declarations();
+
+        // the shebang and the directive comments (like `--!strict`) stay at the top
+        if let Some(first) = declarations.first_mut() {
+            first.prepend_leading_header(block.take_leading_header());
+        }
+
+        for (index, declaration) in declarations.into_iter().enumerate() {
+            block.insert_statement(index, declaration);
+        }
+    }
+}
+
+impl RuleConfiguration for CacheServices {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        verify_no_rule_properties(&properties)?;
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        CACHE_SERVICES_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        RuleProperties::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> CacheServices {
+        CacheServices::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_cache_services", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'cache_services',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+}
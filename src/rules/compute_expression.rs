@@ -1,62 +1,233 @@
-use crate::nodes::{BinaryOperator, Block, Expression};
-use crate::process::{DefaultVisitor, Evaluator, NodeProcessor, NodeVisitor};
+use crate::generator::{DenseLuaGenerator, LuaGenerator};
+use crate::nodes::{
+    AssignStatement, BinaryOperator, Block, CompoundAssignStatement, Expression, FunctionStatement,
+    Prefix, Variable,
+};
+use std::ops;
+
+use crate::process::{
+    DefaultVisitor, Evaluator, IdentifierTracker, LuaDialect, LuaValue, NodeProcessor, NodeVisitor,
+    ScopeVisitor,
+};
 use crate::rules::{
     Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
 };
 
-use super::verify_no_rule_properties;
+const MAXIMUM_EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+fn generate_expression(expression: &Expression) -> String {
+    let mut generator = DenseLuaGenerator::default();
+    generator.write_expression(expression);
+    generator.into_string()
+}
+
+const TOSTRING: &str = "tostring";
+const TONUMBER: &str = "tonumber";
+
+fn is_global_table(prefix: &Prefix) -> bool {
+    matches!(prefix, Prefix::Identifier(identifier) if identifier.get_name() == "_G")
+}
+
+/// Finds if the code assigns a new value to the global `tostring` or `tonumber`, either
+/// directly or through `_G`.
+#[derive(Debug, Clone, Default)]
+struct ReassignedGlobals {
+    tostring: bool,
+    tonumber: bool,
+}
+
+impl ReassignedGlobals {
+    fn write(&mut self, name: &str) {
+        match name {
+            TOSTRING => self.tostring = true,
+            TONUMBER => self.tonumber = true,
+            _ => {}
+        }
+    }
+
+    fn write_variable(&mut self, variable: &Variable) {
+        match variable {
+            Variable::Identifier(identifier) => self.write(identifier.get_name()),
+            Variable::Field(field) => {
+                if is_global_table(field.get_prefix()) {
+                    self.write(field.get_field().get_name());
+                }
+            }
+            Variable::Index(index) => {
+                if is_global_table(index.get_prefix()) {
+                    match index.get_index() {
+                        Expression::String(string) => self.write(string.get_value()),
+                        _ => {
+                            self.tostring = true;
+                            self.tonumber = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl NodeProcessor for ReassignedGlobals {
+    fn process_assign_statement(&mut self, assign: &mut AssignStatement) {
+        for variable in assign.iter_variables() {
+            self.write_variable(variable);
+        }
+    }
+
+    fn process_compound_assign_statement(&mut self, assign: &mut CompoundAssignStatement) {
+        self.write_variable(assign.get_variable());
+    }
+
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        let name = function.get_name();
+        let root = name.get_name().get_name();
+
+        match (name.get_field_names().as_slice(), name.get_method()) {
+            ([], None) => self.write(root),
+            ([field], None) if root == "_G" => self.write(field.get_name()),
+            _ => {}
+        }
+    }
+}
 
 #[derive(Debug, Clone, Default)]
 struct Computer {
+    identifier_tracker: IdentifierTracker,
+    dialect: LuaDialect,
     evaluator: Evaluator,
+    max_literal_length: Option<usize>,
+    reassigned_globals: ReassignedGlobals,
+}
+
+impl ops::Deref for Computer {
+    type Target = IdentifierTracker;
+
+    fn deref(&self) -> &Self::Target {
+        &self.identifier_tracker
+    }
+}
+
+impl ops::DerefMut for Computer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.identifier_tracker
+    }
 }
 
 impl Computer {
+    fn new(
+        dialect: LuaDialect,
+        max_literal_length: Option<usize>,
+        reassigned_globals: ReassignedGlobals,
+    ) -> Self {
+        Self {
+            identifier_tracker: IdentifierTracker::new(),
+            dialect,
+            evaluator: Evaluator::default().with_dialect(dialect),
+            max_literal_length,
+            reassigned_globals,
+        }
+    }
+
+    /// Calls to `tostring` and `tonumber` are only computed when they are not shadowed by
+    /// a local variable in the current scope, and when the code never assigns them.
+    fn update_evaluator(&mut self) {
+        let mut evaluator = Evaluator::default().with_dialect(self.dialect);
+
+        if !self.reassigned_globals.tostring && !self.is_identifier_used(TOSTRING) {
+            evaluator = evaluator.assume_global_tostring();
+        }
+        if !self.reassigned_globals.tonumber && !self.is_identifier_used(TONUMBER) {
+            evaluator = evaluator.assume_global_tonumber();
+        }
+
+        self.evaluator = evaluator;
+    }
+
+    fn compute(&self, expression: &Expression) -> Option<Expression> {
+        let value = self.evaluator.evaluate(expression);
+        let number = match &value {
+            LuaValue::Number(number) => Some(*number),
+            _ => None,
+        };
+
+        let computed = value.to_expression()?;
+
+        match number {
+            Some(number) if !self.accept_number(number, &computed, expression) => None,
+            _ => Some(computed),
+        }
+    }
+
+    /// Verifies that the generated number is read back as the exact same value, so that
+    /// computing the expression does not change the behavior of the code.
+    fn accept_number(&self, value: f64, computed: &Expression, original: &Expression) -> bool {
+        if !value.is_finite() || (value.fract() == 0.0 && value.abs() <= MAXIMUM_EXACT_INTEGER) {
+            return true;
+        }
+
+        let literal = generate_expression(computed);
+
+        let is_exact = literal
+            .trim_start_matches('-')
+            .parse::<f64>()
+            .map(|parsed| parsed == value.abs())
+            .unwrap_or(false);
+
+        if !is_exact {
+            return false;
+        }
+
+        match self.max_literal_length {
+            Some(max_length) if literal.len() > max_length => {
+                literal.len() <= generate_expression(original).len()
+            }
+            _ => true,
+        }
+    }
+
     fn replace_with(&mut self, expression: &Expression) -> Option<Expression> {
         match expression {
             Expression::Unary(_) => {
                 if !self.evaluator.has_side_effects(expression) {
-                    self.evaluator.evaluate(expression).to_expression()
+                    self.compute(expression)
                 } else {
                     None
                 }
             }
             Expression::Binary(binary) => {
                 if !self.evaluator.has_side_effects(expression) {
-                    self.evaluator
-                        .evaluate(expression)
-                        .to_expression()
-                        .or_else(|| {
-                            match binary.operator() {
-                                BinaryOperator::And => {
-                                    self.evaluator.evaluate(binary.left()).is_truthy().map(
-                                        |is_truthy| {
-                                            if is_truthy {
-                                                binary.right().clone()
-                                            } else {
-                                                binary.left().clone()
-                                            }
-                                        },
-                                    )
-                                }
-                                BinaryOperator::Or => {
-                                    self.evaluator.evaluate(binary.left()).is_truthy().map(
-                                        |is_truthy| {
-                                            if is_truthy {
-                                                binary.left().clone()
-                                            } else {
-                                                binary.right().clone()
-                                            }
-                                        },
-                                    )
-                                }
-                                _ => None,
+                    self.compute(expression).or_else(|| {
+                        match binary.operator() {
+                            BinaryOperator::And => {
+                                self.evaluator.evaluate(binary.left()).is_truthy().map(
+                                    |is_truthy| {
+                                        if is_truthy {
+                                            binary.right().clone()
+                                        } else {
+                                            binary.left().clone()
+                                        }
+                                    },
+                                )
                             }
-                            .map(|mut expression| {
-                                self.process_expression(&mut expression);
-                                expression
-                            })
+                            BinaryOperator::Or => {
+                                self.evaluator.evaluate(binary.left()).is_truthy().map(
+                                    |is_truthy| {
+                                        if is_truthy {
+                                            binary.left().clone()
+                                        } else {
+                                            binary.right().clone()
+                                        }
+                                    },
+                                )
+                            }
+                            _ => None,
+                        }
+                        .map(|mut expression| {
+                            self.process_expression(&mut expression);
+                            expression
                         })
+                    })
                 } else {
                     match binary.operator() {
                         BinaryOperator::And => {
@@ -93,9 +264,9 @@ impl Computer {
                     }
                 }
             }
-            Expression::If(_) => {
+            Expression::Call(_) | Expression::If(_) | Expression::InterpolatedString(_) => {
                 if !self.evaluator.has_side_effects(expression) {
-                    self.evaluator.evaluate(expression).to_expression()
+                    self.compute(expression)
                 } else {
                     None
                 }
@@ -107,6 +278,8 @@ impl Computer {
 
 impl NodeProcessor for Computer {
     fn process_expression(&mut self, expression: &mut Expression) {
+        self.update_evaluator();
+
         if let Some(replace_with) = self.replace_with(expression) {
             *expression = replace_with;
         }
@@ -117,18 +290,63 @@ pub const COMPUTE_EXPRESSIONS_RULE_NAME: &str = "compute_expression";
 
 /// A rule that compute expressions that do not have any side-effects.
 #[derive(Debug, Default, PartialEq, Eq)]
-pub struct ComputeExpression {}
+pub struct ComputeExpression {
+    dialect: LuaDialect,
+    max_literal_length: Option<usize>,
+}
+
+impl ComputeExpression {
+    /// Only compute values that are identical in the given Lua dialect.
+    pub fn with_dialect(mut self, dialect: LuaDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Do not compute numbers that generate a literal longer than the given length,
+    /// unless the literal is not longer than the original expression.
+    pub fn with_max_literal_length(mut self, max_literal_length: usize) -> Self {
+        self.max_literal_length = Some(max_literal_length);
+        self
+    }
+}
 
 impl FlawlessRule for ComputeExpression {
     fn flawless_process(&self, block: &mut Block, _: &Context) {
-        let mut processor = Computer::default();
-        DefaultVisitor::visit_block(block, &mut processor);
+        let mut reassigned_globals = ReassignedGlobals::default();
+        DefaultVisitor::visit_block(block, &mut reassigned_globals);
+
+        let mut processor =
+            Computer::new(self.dialect, self.max_literal_length, reassigned_globals);
+        ScopeVisitor::visit_block(block, &mut processor);
     }
 }
 
 impl RuleConfiguration for ComputeExpression {
     fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
-        verify_no_rule_properties(&properties)?;
+        for (key, value) in properties {
+            match key.as_str() {
+                "dialect" => {
+                    self.dialect = match value.expect_string(&key)?.as_str() {
+                        "lua51" => LuaDialect::Lua51,
+                        "lua53" => LuaDialect::Lua53,
+                        "luau" => LuaDialect::Luau,
+                        unexpected => {
+                            return Err(RuleConfigurationError::UnexpectedValue {
+                                property: "dialect".to_owned(),
+                                message: format!(
+                                    "invalid value `{}` (must be `lua51`, `lua53` or `luau`)",
+                                    unexpected
+                                ),
+                            })
+                        }
+                    };
+                }
+                "max_literal_length" => {
+                    self.max_literal_length = Some(value.expect_usize(&key)?);
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
 
         Ok(())
     }
@@ -138,7 +356,40 @@ impl RuleConfiguration for ComputeExpression {
     }
 
     fn serialize_to_properties(&self) -> RuleProperties {
-        RuleProperties::new()
+        let mut properties = RuleProperties::new();
+
+        match self.dialect {
+            LuaDialect::Luau => {}
+            LuaDialect::Lua51 => {
+                properties.insert("dialect".to_owned(), "lua51".into());
+            }
+            LuaDialect::Lua53 => {
+                properties.insert("dialect".to_owned(), "lua53".into());
+            }
+        }
+
+        if let Some(max_literal_length) = self.max_literal_length {
+            properties.insert("max_literal_length".to_owned(), max_literal_length.into());
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        let dialect = match self.dialect {
+            LuaDialect::Luau => "luau",
+            LuaDialect::Lua51 => "lua51",
+            LuaDialect::Lua53 => "lua53",
+        };
+        properties.insert("dialect".to_owned(), dialect.into());
+
+        if let Some(max_literal_length) = self.max_literal_length {
+            properties.insert("max_literal_length".to_owned(), max_literal_length.into());
+        }
+
+        properties
     }
 }
 
@@ -159,4 +410,49 @@ mod test {
 
         assert_json_snapshot!("default_compute_expression", rule);
     }
+
+    #[test]
+    fn serialize_rule_with_max_literal_length() {
+        let rule: Box<dyn Rule> = Box::new(new_rule().with_max_literal_length(8));
+
+        assert_json_snapshot!("compute_expression_with_max_literal_length", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'compute_expression',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn configure_with_invalid_dialect_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'compute_expression',
+            dialect: 'lua54',
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "unexpected value for field 'dialect': invalid value `lua54` (must be `lua51`, `lua53` or `luau`)"
+        );
+    }
+
+    #[test]
+    fn serialize_rule_with_dialect_round_trip() {
+        let rule: Box<dyn Rule> = Box::new(new_rule().with_dialect(LuaDialect::Lua53));
+
+        let serialized = json5::to_string(&rule).unwrap();
+        let deserialized: Box<dyn Rule> = json5::from_str(&serialized).unwrap();
+
+        pretty_assertions::assert_eq!(
+            deserialized.serialize_to_properties(),
+            rule.serialize_to_properties()
+        );
+    }
 }
@@ -25,6 +25,9 @@ pub enum RuleConfigurationError {
     /// When a property is associated with something else than an expected list of strings. The
     /// string is the property name.
     StringListExpected(String),
+    /// When a property is associated with something else than an expected map of strings. The
+    /// string is the property name.
+    StringMapExpected(String),
     /// When a property is associated with something else than an expected require mode. The
     /// string is the property name.
     RequireModeExpected(String),
@@ -57,6 +60,30 @@ fn enumerate_properties(properties: &[String]) -> String {
         .collect()
 }
 
+impl RuleConfigurationError {
+    /// The property that caused the error, when the error is caused by a single property.
+    pub fn property(&self) -> Option<&str> {
+        use RuleConfigurationError::*;
+
+        match self {
+            UnexpectedProperty(property)
+            | BooleanExpected(property)
+            | StringExpected(property)
+            | UsizeExpected(property)
+            | FloatExpected(property)
+            | StringListExpected(property)
+            | StringMapExpected(property)
+            | RequireModeExpected(property)
+            | UnexpectedValueType(property)
+            | UnexpectedValue { property, .. } => Some(property),
+            MissingProperty(_)
+            | MissingAnyProperty(_)
+            | PropertyCollision(_)
+            | InternalUsageOnly(_) => None,
+        }
+    }
+}
+
 impl fmt::Display for RuleConfigurationError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use RuleConfigurationError::*;
@@ -80,6 +107,9 @@ impl fmt::Display for RuleConfigurationError {
             StringListExpected(property) => {
                 write!(f, "list of string expected for field '{}'", property)
             }
+            StringMapExpected(property) => {
+                write!(f, "map of strings expected for field '{}'", property)
+            }
             RequireModeExpected(property) => {
                 write!(f, "require mode value expected for field `{}`", property)
             }
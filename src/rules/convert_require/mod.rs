@@ -5,10 +5,10 @@ mod rojo_sourcemap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::frontend::DarkluaResult;
+use crate::frontend::{DarkluaResult, ModuleEdge, ModuleNode, RequireLocation};
 use crate::nodes::{Arguments, Block, FunctionCall};
 use crate::process::{DefaultVisitor, IdentifierTracker, NodeProcessor, NodeVisitor};
-use crate::rules::require::{is_require_call, PathRequireMode};
+use crate::rules::require::{is_require_call, match_require_literal, PathRequireMode};
 use crate::rules::{Context, RuleConfiguration, RuleConfigurationError, RuleProperties};
 
 use instance_path::InstancePath;
@@ -90,6 +90,7 @@ struct RequireConverter<'a> {
     current: RequireMode,
     target: RequireMode,
     context: &'a Context<'a, 'a, 'a>,
+    converted_requires: usize,
 }
 
 impl Deref for RequireConverter<'_> {
@@ -113,11 +114,18 @@ impl<'a> RequireConverter<'a> {
             current,
             target,
             context,
+            converted_requires: 0,
         }
     }
 
     fn try_require_conversion(&mut self, call: &mut FunctionCall) -> DarkluaResult<()> {
-        if let Some(require_path) = self.current.find_require(call, self.context)? {
+        let found_require = self.current.find_require(call, self.context);
+
+        if self.context.is_collecting_module_graph() {
+            self.add_module_edge(call, &found_require);
+        }
+
+        if let Some(require_path) = found_require? {
             log::trace!("found require path `{}`", require_path.display());
 
             if let Some(new_arguments) =
@@ -125,19 +133,49 @@ impl<'a> RequireConverter<'a> {
                     .generate_require(&require_path, &self.current, self.context)?
             {
                 call.set_arguments(new_arguments);
+                self.converted_requires += 1;
             }
         }
         Ok(())
     }
 }
 
+impl RequireConverter<'_> {
+    fn add_module_edge(&self, call: &FunctionCall, found_require: &DarkluaResult<Option<PathBuf>>) {
+        let target = match found_require {
+            Ok(Some(require_path)) => ModuleNode::file(require_path),
+            Ok(None) => return,
+            Err(_) => match match_require_literal(call) {
+                Some(literal) => ModuleNode::unresolved(literal),
+                None => return,
+            },
+        };
+
+        let location = call
+            .get_prefix()
+            .start_position()
+            .map(|position| RequireLocation::new(position, self.context.original_code()));
+
+        self.context.add_module_edge(ModuleEdge::new(
+            self.context.current_path(),
+            target,
+            location,
+        ));
+    }
+}
+
 impl NodeProcessor for RequireConverter<'_> {
     fn process_function_call(&mut self, call: &mut FunctionCall) {
         if is_require_call(call, self) {
             match self.try_require_conversion(call) {
                 Ok(()) => {}
                 Err(err) => {
-                    log::warn!("{}", err);
+                    log::warn!(
+                        "{} (at `{}`)",
+                        err,
+                        self.context
+                            .format_position(call.get_prefix().start_position())
+                    );
                 }
             }
         }
@@ -176,6 +214,7 @@ impl Rule for ConvertRequire {
 
         let mut processor = RequireConverter::new(current_mode, target_mode, context);
         DefaultVisitor::visit_block(block, &mut processor);
+        context.add_rule_note("converted_requires", processor.converted_requires);
         Ok(())
     }
 }
@@ -204,7 +243,12 @@ impl RuleConfiguration for ConvertRequire {
     }
 
     fn serialize_to_properties(&self) -> RuleProperties {
-        RuleProperties::new()
+        let mut properties = RuleProperties::new();
+
+        properties.insert("current".to_owned(), (&self.current).into());
+        properties.insert("target".to_owned(), (&self.target).into());
+
+        properties
     }
 }
 
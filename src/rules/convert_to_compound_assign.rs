@@ -0,0 +1,174 @@
+use crate::nodes::{
+    AssignStatement, BinaryOperator, Block, CompoundAssignStatement, CompoundOperator, Expression,
+    Prefix, Statement, Variable,
+};
+use crate::process::{DefaultVisitor, NodeProcessor, NodeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+use super::verify_no_rule_properties;
+
+fn get_compound_operator(operator: BinaryOperator) -> Option<CompoundOperator> {
+    match operator {
+        BinaryOperator::Plus => Some(CompoundOperator::Plus),
+        BinaryOperator::Minus => Some(CompoundOperator::Minus),
+        BinaryOperator::Asterisk => Some(CompoundOperator::Asterisk),
+        BinaryOperator::Slash => Some(CompoundOperator::Slash),
+        BinaryOperator::DoubleSlash => Some(CompoundOperator::DoubleSlash),
+        BinaryOperator::Percent => Some(CompoundOperator::Percent),
+        BinaryOperator::Caret => Some(CompoundOperator::Caret),
+        BinaryOperator::Concat => Some(CompoundOperator::Concat),
+        BinaryOperator::And
+        | BinaryOperator::Or
+        | BinaryOperator::Equal
+        | BinaryOperator::NotEqual
+        | BinaryOperator::LowerThan
+        | BinaryOperator::LowerOrEqualThan
+        | BinaryOperator::GreaterThan
+        | BinaryOperator::GreaterOrEqualThan => None,
+    }
+}
+
+/// Returns `true` if both prefixes are the same variable or the same path made of fields
+/// and indexes that can be evaluated without side effects.
+fn is_same_prefix(prefix: &Prefix, other: &Prefix) -> bool {
+    match (prefix, other) {
+        (Prefix::Identifier(identifier), Prefix::Identifier(other)) => {
+            identifier.get_name() == other.get_name()
+        }
+        (Prefix::Field(field), Prefix::Field(other)) => {
+            field.get_field().get_name() == other.get_field().get_name()
+                && is_same_prefix(field.get_prefix(), other.get_prefix())
+        }
+        (Prefix::Index(index), Prefix::Index(other)) => {
+            is_same_index(index.get_index(), other.get_index())
+                && is_same_prefix(index.get_prefix(), other.get_prefix())
+        }
+        _ => false,
+    }
+}
+
+/// Returns `true` if both indexes are the same variable or the same literal string or number.
+fn is_same_index(index: &Expression, other: &Expression) -> bool {
+    match (index, other) {
+        (Expression::Identifier(identifier), Expression::Identifier(other)) => {
+            identifier.get_name() == other.get_name()
+        }
+        (Expression::String(string), Expression::String(other)) => {
+            string.get_value() == other.get_value()
+        }
+        (Expression::Number(number), Expression::Number(other)) => {
+            number.compute_value() == other.compute_value()
+        }
+        _ => false,
+    }
+}
+
+fn is_same_variable(variable: &Variable, expression: &Expression) -> bool {
+    match (variable, expression) {
+        (Variable::Identifier(identifier), Expression::Identifier(other)) => {
+            identifier.get_name() == other.get_name()
+        }
+        (Variable::Field(field), Expression::Field(other)) => {
+            field.get_field().get_name() == other.get_field().get_name()
+                && is_same_prefix(field.get_prefix(), other.get_prefix())
+        }
+        (Variable::Index(index), Expression::Index(other)) => {
+            is_same_index(index.get_index(), other.get_index())
+                && is_same_prefix(index.get_prefix(), other.get_prefix())
+        }
+        _ => false,
+    }
+}
+
+fn convert_assignment(assign: &AssignStatement) -> Option<CompoundAssignStatement> {
+    if assign.variables_len() != 1 || assign.values_len() != 1 {
+        return None;
+    }
+
+    let variable = assign.get_variables().first()?;
+
+    match assign.last_value()? {
+        Expression::Binary(binary) if is_same_variable(variable, binary.left()) => {
+            get_compound_operator(binary.operator()).map(|operator| {
+                CompoundAssignStatement::new(operator, variable.clone(), binary.right().clone())
+            })
+        }
+        _ => None,
+    }
+}
+
+struct Processor;
+
+impl NodeProcessor for Processor {
+    fn process_statement(&mut self, statement: &mut Statement) {
+        let compound_assign = match statement {
+            Statement::Assign(assign) => convert_assignment(assign),
+            _ => None,
+        };
+
+        if let Some(compound_assign) = compound_assign {
+            *statement = compound_assign.into();
+        }
+    }
+}
+
+pub const CONVERT_TO_COMPOUND_ASSIGNMENT_RULE_NAME: &str = "convert_to_compound_assignment";
+
+/// A rule that converts assignments like `x = x + 1` into compound assignments (`x += 1`).
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ConvertToCompoundAssignment {}
+
+impl FlawlessRule for ConvertToCompoundAssignment {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut processor = Processor;
+        DefaultVisitor::visit_block(block, &mut processor);
+    }
+}
+
+impl RuleConfiguration for ConvertToCompoundAssignment {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        verify_no_rule_properties(&properties)?;
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        CONVERT_TO_COMPOUND_ASSIGNMENT_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        RuleProperties::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> ConvertToCompoundAssignment {
+        ConvertToCompoundAssignment::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_convert_to_compound_assignment", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'convert_to_compound_assignment',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+}
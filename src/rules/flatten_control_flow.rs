@@ -0,0 +1,423 @@
+use std::collections::HashSet;
+use std::mem;
+
+use crate::nodes::{
+    AssignStatement, BinaryExpression, BinaryOperator, Block, Expression, FunctionExpression,
+    FunctionStatement, Identifier, IfBranch, IfStatement, LastStatement, LocalAssignStatement,
+    LocalFunctionStatement, Statement, TypedIdentifier, Variable, WhileStatement,
+};
+use crate::process::{processors::FindVariables, DefaultVisitor, NodeProcessor, NodeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+use crate::utils::SeededRandom;
+
+const STATE_VARIABLE: &str = "__DARKLUA_FLATTEN_STATE";
+const EXIT_STATE: usize = 0;
+const MAXIMUM_RANDOM_STATE: usize = 1 << 24;
+
+/// Returns `true` if the block is already a dispatcher created by this rule.
+fn is_flattened(block: &Block) -> bool {
+    block.iter_statements().any(|statement| match statement {
+        Statement::LocalAssign(assign) => assign
+            .iter_variables()
+            .any(|variable| variable.get_name() == STATE_VARIABLE),
+        _ => false,
+    })
+}
+
+fn get_declared_names(statement: &Statement) -> Vec<String> {
+    match statement {
+        Statement::LocalAssign(assign) => assign
+            .iter_variables()
+            .map(|variable| variable.get_name().to_owned())
+            .collect(),
+        Statement::LocalFunction(function) => vec![function.get_name().to_owned()],
+        _ => Vec::new(),
+    }
+}
+
+/// Returns `true` if the local variables declared by the statements can be declared at the
+/// start of the function without changing which variable each identifier refers to. It
+/// is not the case when a name is declared twice, or when a name is used before (or in
+/// the values of) its declaration.
+fn can_hoist_declarations(statements: &mut [Statement]) -> bool {
+    let mut declared = HashSet::new();
+
+    for index in 0..statements.len() {
+        let names = get_declared_names(&statements[index]);
+
+        if names.is_empty() {
+            continue;
+        }
+
+        for name in names.iter() {
+            if !declared.insert(name.clone()) {
+                return false;
+            }
+        }
+
+        let mut find_usage: FindVariables = names.iter().map(String::as_str).collect();
+        let (previous_statements, next_statements) = statements.split_at_mut(index);
+
+        for statement in previous_statements {
+            DefaultVisitor::visit_statement(statement, &mut find_usage);
+        }
+
+        if let Statement::LocalAssign(assign) = &mut next_statements[0] {
+            for value in assign.iter_mut_values() {
+                DefaultVisitor::visit_expression(value, &mut find_usage);
+            }
+        }
+
+        if find_usage.has_found_usage() {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn into_function_expression(mut function: LocalFunctionStatement) -> FunctionExpression {
+    let mut expression = FunctionExpression::default();
+    expression.set_variadic(function.is_variadic());
+    if let Some(variadic_type) = function.get_variadic_type() {
+        expression.set_variadic_type(variadic_type.clone());
+    }
+    if let Some(return_type) = function.get_return_type() {
+        expression.set_return_type(return_type.clone());
+    }
+    if let Some(generic_parameters) = function.get_generic_parameters() {
+        expression.set_generic_parameters(generic_parameters.clone());
+    }
+    mem::swap(expression.mutate_block(), function.mutate_block());
+    mem::swap(expression.mutate_parameters(), function.mutate_parameters());
+    mem::swap(expression.mutate_attributes(), function.mutate_attributes());
+    expression
+}
+
+/// Converts local declarations into assignments and pushes the declared variables into
+/// `hoisted`. Returns `None` if the statement only declares variables without values.
+fn hoist_declarations(
+    statement: Statement,
+    hoisted: &mut Vec<TypedIdentifier>,
+) -> Option<Statement> {
+    match statement {
+        Statement::LocalAssign(assign) => {
+            let (variables, values) = assign.into_assignments();
+            let assigned = variables
+                .iter()
+                .map(|variable| Variable::from(Identifier::new(variable.get_name())))
+                .collect();
+
+            hoisted.extend(variables);
+
+            if values.is_empty() {
+                None
+            } else {
+                Some(AssignStatement::new(assigned, values).into())
+            }
+        }
+        Statement::LocalFunction(function) => {
+            let name = function.get_name().to_owned();
+            hoisted.push(TypedIdentifier::new(name.clone()));
+
+            Some(
+                AssignStatement::from_variable(
+                    Identifier::new(name),
+                    into_function_expression(function),
+                )
+                .into(),
+            )
+        }
+        statement => Some(statement),
+    }
+}
+
+fn set_state(state: usize) -> Statement {
+    AssignStatement::from_variable(Identifier::new(STATE_VARIABLE), state).into()
+}
+
+struct FlattenProcessor {
+    minimum_statements: usize,
+    random: Option<SeededRandom>,
+}
+
+impl FlattenProcessor {
+    fn can_flatten(&self, block: &Block) -> bool {
+        let last_statement_len = match block.get_last_statement() {
+            None => 0,
+            Some(LastStatement::Return(_)) => 1,
+            Some(_) => return false,
+        };
+
+        block.statements_len() + last_statement_len >= self.minimum_statements
+            && !is_flattened(block)
+            && block.iter_statements().all(|statement| {
+                !matches!(
+                    statement,
+                    Statement::Goto(_)
+                        | Statement::Label(_)
+                        | Statement::TypeDeclaration(_)
+                        | Statement::TypeFunction(_)
+                        | Statement::ExportTypeFunction(_)
+                )
+            })
+    }
+
+    fn create_states(&mut self, count: usize) -> Vec<usize> {
+        match &mut self.random {
+            Some(random) => {
+                let mut used = HashSet::new();
+                (0..count)
+                    .map(|_| loop {
+                        let state = random.next_below(MAXIMUM_RANDOM_STATE) + 1;
+                        if used.insert(state) {
+                            break state;
+                        }
+                    })
+                    .collect()
+            }
+            None => (1..=count).collect(),
+        }
+    }
+
+    fn flatten(&mut self, block: &mut Block) {
+        if !self.can_flatten(block) {
+            return;
+        }
+
+        let mut statements = block.take_statements();
+
+        if !can_hoist_declarations(&mut statements) {
+            block.set_statements(statements);
+            return;
+        }
+
+        let mut hoisted = Vec::new();
+        let mut segments: Vec<Block> = statements
+            .into_iter()
+            .filter_map(|statement| hoist_declarations(statement, &mut hoisted))
+            .map(|statement| Block::default().with_statement(statement))
+            .collect();
+
+        if let Some(last_statement) = block.take_last_statement() {
+            segments.push(Block::default().with_last_statement(last_statement));
+        }
+
+        if segments.is_empty() {
+            if !hoisted.is_empty() {
+                block.push_statement(LocalAssignStatement::new(hoisted, Vec::new()));
+            }
+            return;
+        }
+
+        let states = self.create_states(segments.len());
+
+        let mut branches: Vec<IfBranch> = segments
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut segment)| {
+                if segment.get_last_statement().is_none() {
+                    segment.push_statement(set_state(
+                        states.get(index + 1).copied().unwrap_or(EXIT_STATE),
+                    ));
+                }
+
+                IfBranch::new(
+                    BinaryExpression::new(
+                        BinaryOperator::Equal,
+                        Identifier::new(STATE_VARIABLE),
+                        states[index],
+                    ),
+                    segment,
+                )
+            })
+            .collect();
+
+        if let Some(random) = &mut self.random {
+            random.shuffle(&mut branches);
+        }
+
+        let dispatcher = WhileStatement::new(
+            Block::default().with_statement(IfStatement::new(branches, None)),
+            BinaryExpression::new(
+                BinaryOperator::NotEqual,
+                Identifier::new(STATE_VARIABLE),
+                EXIT_STATE,
+            ),
+        );
+
+        if !hoisted.is_empty() {
+            block.push_statement(LocalAssignStatement::new(hoisted, Vec::new()));
+        }
+        block.push_statement(
+            LocalAssignStatement::from_variable(STATE_VARIABLE)
+                .with_value(Expression::from(states[0])),
+        );
+        block.push_statement(dispatcher);
+    }
+}
+
+impl NodeProcessor for FlattenProcessor {
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        self.flatten(function.mutate_block());
+    }
+
+    fn process_local_function_statement(&mut self, function: &mut LocalFunctionStatement) {
+        self.flatten(function.mutate_block());
+    }
+
+    fn process_function_expression(&mut self, function: &mut FunctionExpression) {
+        self.flatten(function.mutate_block());
+    }
+}
+
+pub const FLATTEN_CONTROL_FLOW_RULE_NAME: &str = "flatten_control_flow";
+
+const DEFAULT_MINIMUM_STATEMENTS: usize = 3;
+
+/// A rule that rewrites the statements of function bodies into a loop that runs each
+/// statement according to the value of a state variable.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FlattenControlFlow {
+    minimum_statements: usize,
+    seed: Option<usize>,
+}
+
+impl Default for FlattenControlFlow {
+    fn default() -> Self {
+        Self {
+            minimum_statements: DEFAULT_MINIMUM_STATEMENTS,
+            seed: None,
+        }
+    }
+}
+
+impl FlattenControlFlow {
+    pub fn with_minimum_statements(mut self, minimum_statements: usize) -> Self {
+        self.minimum_statements = minimum_statements;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: usize) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+impl FlawlessRule for FlattenControlFlow {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut processor = FlattenProcessor {
+            minimum_statements: self.minimum_statements,
+            random: self.seed.map(|seed| SeededRandom::new(seed as u64)),
+        };
+        DefaultVisitor::visit_block(block, &mut processor);
+    }
+}
+
+impl RuleConfiguration for FlattenControlFlow {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                "minimum_statements" => {
+                    self.minimum_statements = value.expect_usize(&key)?;
+                }
+                "seed" => {
+                    self.seed = Some(value.expect_usize(&key)?);
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        FLATTEN_CONTROL_FLOW_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        if self.minimum_statements != DEFAULT_MINIMUM_STATEMENTS {
+            properties.insert(
+                "minimum_statements".to_owned(),
+                self.minimum_statements.into(),
+            );
+        }
+        if let Some(seed) = self.seed {
+            properties.insert("seed".to_owned(), seed.into());
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert(
+            "minimum_statements".to_owned(),
+            self.minimum_statements.into(),
+        );
+        if let Some(seed) = self.seed {
+            properties.insert("seed".to_owned(), seed.into());
+        }
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> FlattenControlFlow {
+        FlattenControlFlow::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_flatten_control_flow", rule);
+    }
+
+    #[test]
+    fn serialize_rule_with_seed_and_minimum_statements() {
+        let rule: Box<dyn Rule> = Box::new(new_rule().with_seed(42).with_minimum_statements(5));
+
+        assert_json_snapshot!(
+            "flatten_control_flow_with_seed_and_minimum_statements",
+            rule
+        );
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'flatten_control_flow',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn configure_with_invalid_seed_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'flatten_control_flow',
+            seed: "abc",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "unsigned integer expected for field 'seed'"
+        );
+    }
+}
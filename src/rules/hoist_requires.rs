@@ -0,0 +1,256 @@
+use std::collections::HashSet;
+
+use crate::nodes::{
+    Arguments, AssignStatement, Block, CompoundAssignStatement, Expression, FunctionCall,
+    FunctionStatement, Identifier, LocalAssignStatement, LocalFunctionStatement, Prefix, Statement,
+    Variable,
+};
+use crate::process::{DefaultVisitor, Evaluator, LuaValue, NodeProcessor, NodeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+use super::verify_no_rule_properties;
+
+const REQUIRE_FUNCTION: &str = "require";
+
+/// The names used by the statements that a require statement would move above.
+#[derive(Debug, Default)]
+struct NameUsage {
+    referenced: HashSet<String>,
+    assigned: HashSet<String>,
+}
+
+impl NameUsage {
+    fn add_statement(&mut self, statement: &mut Statement) {
+        DefaultVisitor::visit_statement(statement, self);
+    }
+
+    fn is_used(&self, name: &str) -> bool {
+        self.referenced.contains(name) || self.assigned.contains(name)
+    }
+}
+
+impl NodeProcessor for NameUsage {
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        self.referenced.insert(identifier.get_name().to_owned());
+    }
+
+    fn process_assign_statement(&mut self, assign: &mut AssignStatement) {
+        for variable in assign.iter_variables() {
+            if let Variable::Identifier(identifier) = variable {
+                self.assigned.insert(identifier.get_name().to_owned());
+            }
+        }
+    }
+
+    fn process_compound_assign_statement(&mut self, assign: &mut CompoundAssignStatement) {
+        if let Variable::Identifier(identifier) = assign.get_variable() {
+            self.assigned.insert(identifier.get_name().to_owned());
+        }
+    }
+
+    fn process_local_assign_statement(&mut self, assign: &mut LocalAssignStatement) {
+        for variable in assign.iter_variables() {
+            self.assigned.insert(variable.get_name().to_owned());
+        }
+    }
+
+    fn process_local_function_statement(&mut self, function: &mut LocalFunctionStatement) {
+        self.assigned.insert(function.get_name().to_owned());
+    }
+
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        let name = function.get_name();
+        if name.get_field_names().is_empty() && !name.has_method() {
+            self.assigned.insert(name.get_name().get_name().to_owned());
+        }
+    }
+}
+
+fn is_constant_string(expression: &Expression) -> bool {
+    let evaluator = Evaluator::default();
+
+    matches!(evaluator.evaluate(expression), LuaValue::String(_))
+        && !evaluator.has_side_effects(expression)
+}
+
+fn is_require_call(call: &FunctionCall) -> bool {
+    if call.get_method().is_some() {
+        return false;
+    }
+
+    let is_require = matches!(
+        call.get_prefix(),
+        Prefix::Identifier(identifier) if identifier.get_name() == REQUIRE_FUNCTION
+    );
+
+    is_require
+        && match call.get_arguments() {
+            Arguments::String(_) => true,
+            Arguments::Tuple(tuple) => {
+                let mut values = tuple.iter_values();
+                matches!(
+                    (values.next(), values.next()),
+                    (Some(value), None) if is_constant_string(value)
+                )
+            }
+            Arguments::Table(_) => false,
+        }
+}
+
+/// Returns the name of the variable declared by a `local name = require(...)` statement,
+/// when its argument is a constant string.
+fn get_required_variable(statement: &Statement) -> Option<&str> {
+    let assign = match statement {
+        Statement::LocalAssign(assign) => assign,
+        _ => return None,
+    };
+
+    if assign.variables_len() != 1 || assign.values_len() != 1 {
+        return None;
+    }
+
+    let variable = assign.iter_variables().next()?;
+
+    if variable.has_type() {
+        return None;
+    }
+
+    match assign.iter_values().next()? {
+        Expression::Call(call) if is_require_call(call) => Some(variable.get_name().as_str()),
+        _ => None,
+    }
+}
+
+fn hoist_requires(block: &mut Block) {
+    let mut hoisted = Vec::new();
+    let mut others: Vec<Statement> = Vec::new();
+    let mut usage = NameUsage::default();
+    let mut first_is_require = false;
+    let mut has_moved_statements = false;
+
+    for mut statement in block.take_statements() {
+        let can_hoist = get_required_variable(&statement).map_or(false, |name| {
+            // a require is not moved above code that uses `require`, so that modules
+            // are still loaded in the same order
+            others.is_empty() || (!usage.is_used(name) && !usage.is_used(REQUIRE_FUNCTION))
+        });
+
+        if can_hoist {
+            if others.is_empty() {
+                first_is_require = true;
+            } else {
+                has_moved_statements = true;
+            }
+            hoisted.push(statement);
+        } else {
+            usage.add_statement(&mut statement);
+            others.push(statement);
+        }
+    }
+
+    if has_moved_statements && !first_is_require {
+        if let (Some(new_first), Some(original_first)) = (hoisted.first_mut(), others.first_mut()) {
+            // the comments written before the first statement of the file (like directive
+            // comments) stay at the top
+            let header = original_first.take_leading_header();
+            new_first.prepend_leading_header(header);
+        }
+    }
+
+    hoisted.extend(others);
+    block.set_statements(hoisted);
+}
+
+pub const HOIST_REQUIRES_RULE_NAME: &str = "hoist_requires";
+
+/// A rule that moves the `local name = require(...)` statements of a file to its top.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct HoistRequires {}
+
+impl FlawlessRule for HoistRequires {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        hoist_requires(block);
+    }
+}
+
+impl RuleConfiguration for HoistRequires {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        verify_no_rule_properties(&properties)?;
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        HOIST_REQUIRES_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        RuleProperties::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        generator::{LuaGenerator, TokenBasedLuaGenerator},
+        rules::{ContextBuilder, Rule},
+        Parser, Resources,
+    };
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> HoistRequires {
+        HoistRequires::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_hoist_requires", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'hoist_requires',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn keeps_file_header_comments_at_the_top() {
+        let code = "--!strict\n-- the module header\nlocal value = 1\nlocal Util = require('./util')\nreturn Util.get(value)\n";
+
+        let mut block = Parser::default()
+            .preserve_tokens()
+            .parse(code)
+            .expect("unable to parse code");
+
+        new_rule().flawless_process(
+            &mut block,
+            &ContextBuilder::new(".", &Resources::from_memory(), code).build(),
+        );
+
+        let mut generator = TokenBasedLuaGenerator::new(code);
+        generator.write_block(&block);
+        let output = generator.into_string();
+
+        assert!(
+            output.starts_with("--!strict\n-- the module header\n"),
+            "unexpected output: {}",
+            output
+        );
+        assert!(
+            output.find("require").unwrap() < output.find("local value").unwrap(),
+            "require was not hoisted: {}",
+            output
+        );
+    }
+}
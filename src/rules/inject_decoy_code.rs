@@ -0,0 +1,590 @@
+use std::collections::HashSet;
+
+use crate::nodes::{
+    AssignStatement, BinaryExpression, BinaryOperator, Block, ExportTypeFunctionStatement,
+    Expression, FunctionExpression, FunctionStatement, Identifier, IfStatement, LastStatement,
+    LocalAssignStatement, LocalFunctionStatement, Statement, TableEntry, TableExpression,
+    TypeFunctionStatement, TypedIdentifier, UnaryExpression, UnaryOperator,
+};
+use crate::process::{
+    utils::collect_identifiers, DefaultPostVisitor, DefaultVisitor, NodePostProcessor,
+    NodePostVisitor, NodeProcessor, NodeVisitor,
+};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+use crate::utils::SeededRandom;
+
+use super::lua_limits::MAX_LOCALS;
+
+const DECOY_PREFIX: &str = "__DARKLUA_DECOY";
+const MAXIMUM_DECOYS_PER_BLOCK: usize = 20;
+const MAXIMUM_OPERAND: usize = 1000;
+const MAXIMUM_MODULUS: usize = 100;
+
+/// Returns `true` if the variable name was generated by the `inject_decoy_code` rule. Rules
+/// that remove unused code use this to keep the decoys.
+pub(crate) fn is_decoy_name(name: &str) -> bool {
+    name.starts_with(DECOY_PREFIX)
+}
+
+#[derive(Debug, Default)]
+struct StatementCounter {
+    count: usize,
+}
+
+impl NodeProcessor for StatementCounter {
+    fn process_statement(&mut self, _: &mut Statement) {
+        self.count += 1;
+    }
+
+    fn process_last_statement(&mut self, _: &mut LastStatement) {
+        self.count += 1;
+    }
+}
+
+/// Counts the locals declared in a function block, without the locals of nested
+/// functions. The locals of sibling blocks are counted as if they were all in scope at the
+/// same time.
+fn count_function_locals(block: &Block) -> usize {
+    block
+        .iter_statements()
+        .map(|statement| match statement {
+            Statement::LocalAssign(assign) => assign.variables_len(),
+            Statement::LocalFunction(_) => 1,
+            Statement::Do(do_statement) => count_function_locals(do_statement.get_block()),
+            Statement::If(if_statement) => {
+                if_statement
+                    .iter_branches()
+                    .map(|branch| count_function_locals(branch.get_block()))
+                    .sum::<usize>()
+                    + if_statement
+                        .get_else_block()
+                        .map_or(0, count_function_locals)
+            }
+            Statement::While(while_statement) => count_function_locals(while_statement.get_block()),
+            Statement::Repeat(repeat) => count_function_locals(repeat.get_block()),
+            // loops also declare hidden locals to store their state
+            Statement::NumericFor(numeric_for) => {
+                4 + count_function_locals(numeric_for.get_block())
+            }
+            Statement::GenericFor(generic_for) => {
+                generic_for.get_identifiers().len()
+                    + 3
+                    + count_function_locals(generic_for.get_block())
+            }
+            _ => 0,
+        })
+        .sum()
+}
+
+/// The function where decoys are currently inserted.
+#[derive(Debug)]
+struct FunctionFrame {
+    is_variadic: bool,
+    parameters: Vec<String>,
+    /// The number of decoys that can be declared before reaching the limit of locals.
+    available_locals: usize,
+}
+
+impl FunctionFrame {
+    fn new<'a>(
+        is_variadic: bool,
+        parameters: impl Iterator<Item = &'a TypedIdentifier>,
+        block: &Block,
+    ) -> Self {
+        let parameters: Vec<_> = parameters
+            .map(|parameter| parameter.get_name().to_owned())
+            .collect();
+        let used_locals = parameters.len() + count_function_locals(block);
+
+        Self {
+            is_variadic,
+            available_locals: MAX_LOCALS.saturating_sub(used_locals),
+            parameters,
+        }
+    }
+}
+
+struct DecoyInjector {
+    random: SeededRandom,
+    density: usize,
+    budget: usize,
+    identifiers: HashSet<String>,
+    decoy_count: usize,
+    functions: Vec<FunctionFrame>,
+}
+
+impl DecoyInjector {
+    fn generate_name(&mut self) -> String {
+        loop {
+            self.decoy_count += 1;
+            let name = format!("{}{}", DECOY_PREFIX, self.decoy_count);
+
+            if !self.identifiers.contains(&name) {
+                break name;
+            }
+        }
+    }
+
+    fn next_operand(&mut self) -> usize {
+        self.random.next_below(MAXIMUM_OPERAND) + 1
+    }
+
+    fn next_modulus(&mut self) -> usize {
+        self.random.next_below(MAXIMUM_MODULUS - 1) + 2
+    }
+
+    /// Returns an operand that is only known when the code runs, so that the decoy cannot
+    /// be computed ahead of time: the number of values in `...`, or a number chosen by
+    /// comparing a local variable to `nil` (which never calls a metamethod). Like the
+    /// number literals, these operands are non-negative integers.
+    fn next_runtime_operand(&mut self, locals: &[String]) -> Expression {
+        let is_variadic = self
+            .functions
+            .last()
+            .map_or(false, |function| function.is_variadic);
+
+        if is_variadic && (locals.is_empty() || self.random.next_below(2) == 0) {
+            let values =
+                TableExpression::new(vec![TableEntry::Value(Expression::variable_arguments())]);
+            return UnaryExpression::new(UnaryOperator::Length, values).into();
+        }
+
+        if locals.is_empty() {
+            return self.next_operand().into();
+        }
+
+        let local = &locals[self.random.next_below(locals.len())];
+
+        BinaryExpression::new(
+            BinaryOperator::Or,
+            BinaryExpression::new(
+                BinaryOperator::And,
+                BinaryExpression::new(
+                    BinaryOperator::Equal,
+                    Identifier::new(local.as_str()),
+                    Expression::nil(),
+                ),
+                self.next_operand(),
+            ),
+            self.next_operand(),
+        )
+        .into()
+    }
+
+    /// Returns the number of decoys to insert before a statement. Each statement
+    /// receives `density / 100` decoys, plus one more with a probability of
+    /// `density % 100` percent.
+    fn decoys_before_statement(&mut self) -> usize {
+        let extra = self.random.next_below(100) < self.density % 100;
+        self.density / 100 + usize::from(extra)
+    }
+
+    /// Returns the decoy statements and the number of statements they count for in the
+    /// budget.
+    /// The operand `r` is only known at runtime (see `next_runtime_operand`), using the
+    /// given locals that are in scope.
+    fn create_decoy(&mut self, locals: &[String]) -> (Vec<Statement>, usize) {
+        let name = self.generate_name();
+
+        match self.random.next_below(3) {
+            0 => {
+                // local D = (r * a + b) % m
+                let value = BinaryExpression::new(
+                    BinaryOperator::Percent,
+                    BinaryExpression::new(
+                        BinaryOperator::Plus,
+                        BinaryExpression::new(
+                            BinaryOperator::Asterisk,
+                            self.next_runtime_operand(locals),
+                            self.next_operand(),
+                        ),
+                        self.next_operand(),
+                    ),
+                    self.next_modulus(),
+                );
+
+                (
+                    vec![LocalAssignStatement::from_variable(name)
+                        .with_value(value)
+                        .into()],
+                    1,
+                )
+            }
+            1 => {
+                // local D = r * a % m
+                // if <D compared to a value outside of 0 .. m - 1> then D = D + b end
+                let modulus = self.next_modulus();
+                let value = BinaryExpression::new(
+                    BinaryOperator::Percent,
+                    BinaryExpression::new(
+                        BinaryOperator::Asterisk,
+                        self.next_runtime_operand(locals),
+                        self.next_operand(),
+                    ),
+                    modulus,
+                );
+                let condition = match self.random.next_below(3) {
+                    0 => BinaryExpression::new(
+                        BinaryOperator::GreaterOrEqualThan,
+                        Identifier::new(&name),
+                        modulus,
+                    ),
+                    1 => {
+                        BinaryExpression::new(BinaryOperator::LowerThan, Identifier::new(&name), 0)
+                    }
+                    _ => BinaryExpression::new(
+                        BinaryOperator::Equal,
+                        Identifier::new(&name),
+                        modulus + self.next_operand(),
+                    ),
+                };
+                let update = AssignStatement::from_variable(
+                    Identifier::new(&name),
+                    BinaryExpression::new(
+                        BinaryOperator::Plus,
+                        Identifier::new(&name),
+                        self.next_operand(),
+                    ),
+                );
+
+                (
+                    vec![
+                        LocalAssignStatement::from_variable(name)
+                            .with_value(value)
+                            .into(),
+                        IfStatement::create(condition, Block::default().with_statement(update))
+                            .into(),
+                    ],
+                    3,
+                )
+            }
+            _ => (
+                // local function D() end
+                vec![LocalFunctionStatement::from_name(name, Block::default()).into()],
+                1,
+            ),
+        }
+    }
+
+    fn push_decoys(
+        &mut self,
+        statements: &mut Vec<Statement>,
+        block_decoys: &mut usize,
+        locals: &[String],
+    ) {
+        for _ in 0..self.decoys_before_statement() {
+            let available_locals = self
+                .functions
+                .last()
+                .map_or(0, |function| function.available_locals);
+
+            if *block_decoys >= MAXIMUM_DECOYS_PER_BLOCK || available_locals == 0 {
+                return;
+            }
+
+            let (decoy, cost) = self.create_decoy(locals);
+
+            if cost > self.budget {
+                return;
+            }
+
+            self.budget -= cost;
+            *block_decoys += 1;
+            if let Some(function) = self.functions.last_mut() {
+                // each decoy declares one local
+                function.available_locals -= 1;
+            }
+            statements.extend(decoy);
+        }
+    }
+}
+
+impl NodeProcessor for DecoyInjector {
+    fn process_function_expression(&mut self, function: &mut FunctionExpression) {
+        self.functions.push(FunctionFrame::new(
+            function.is_variadic(),
+            function.iter_parameters(),
+            function.get_block(),
+        ));
+    }
+
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        let mut frame = FunctionFrame::new(
+            function.is_variadic(),
+            function.iter_parameters(),
+            function.get_block(),
+        );
+        if function.get_name().has_method() {
+            frame.parameters.push("self".to_owned());
+            frame.available_locals = frame.available_locals.saturating_sub(1);
+        }
+        self.functions.push(frame);
+    }
+
+    fn process_local_function_statement(&mut self, function: &mut LocalFunctionStatement) {
+        self.functions.push(FunctionFrame::new(
+            function.is_variadic(),
+            function.iter_parameters(),
+            function.get_block(),
+        ));
+    }
+
+    fn process_type_function_statement(&mut self, function: &mut TypeFunctionStatement) {
+        self.functions.push(FunctionFrame::new(
+            function.is_variadic(),
+            function.iter_parameters(),
+            function.get_block(),
+        ));
+    }
+
+    fn process_export_type_function_statement(
+        &mut self,
+        function: &mut ExportTypeFunctionStatement,
+    ) {
+        self.functions.push(FunctionFrame::new(
+            function.is_variadic(),
+            function.iter_parameters(),
+            function.get_block(),
+        ));
+    }
+}
+
+impl NodePostProcessor for DecoyInjector {
+    fn process_after_block(&mut self, block: &mut Block) {
+        // a `goto` statement cannot jump into the scope of a local variable, so decoys
+        // are never inserted in blocks that define labels
+        if self.budget == 0
+            || block
+                .iter_statements()
+                .any(|statement| matches!(statement, Statement::Label(_)))
+        {
+            return;
+        }
+
+        let mut block_decoys = 0;
+        let mut statements = Vec::new();
+        // the locals in scope before each statement of the block
+        let mut locals = self
+            .functions
+            .last()
+            .map(|function| function.parameters.clone())
+            .unwrap_or_default();
+
+        for statement in block.take_statements() {
+            self.push_decoys(&mut statements, &mut block_decoys, &locals);
+
+            match &statement {
+                Statement::LocalAssign(assign) => locals.extend(
+                    assign
+                        .iter_variables()
+                        .map(|variable| variable.get_name().to_owned()),
+                ),
+                Statement::LocalFunction(function) => locals.push(function.get_name().to_owned()),
+                _ => {}
+            }
+
+            statements.push(statement);
+        }
+
+        if block.get_last_statement().is_some() {
+            self.push_decoys(&mut statements, &mut block_decoys, &locals);
+        }
+
+        block.set_statements(statements);
+    }
+
+    fn process_after_function_expression(&mut self, _: &mut FunctionExpression) {
+        self.functions.pop();
+    }
+
+    fn process_after_function_statement(&mut self, _: &mut FunctionStatement) {
+        self.functions.pop();
+    }
+
+    fn process_after_local_function_statement(&mut self, _: &mut LocalFunctionStatement) {
+        self.functions.pop();
+    }
+
+    fn process_after_type_function_statement(&mut self, _: &mut TypeFunctionStatement) {
+        self.functions.pop();
+    }
+
+    fn process_after_export_type_function_statement(
+        &mut self,
+        _: &mut ExportTypeFunctionStatement,
+    ) {
+        self.functions.pop();
+    }
+}
+
+pub const INJECT_DECOY_CODE_RULE_NAME: &str = "inject_decoy_code";
+
+const DEFAULT_DENSITY: usize = 10;
+const DEFAULT_MAXIMUM_GROWTH: usize = 50;
+
+/// A rule that inserts statements that have no effect at random positions.
+#[derive(Debug, PartialEq, Eq)]
+pub struct InjectDecoyCode {
+    seed: usize,
+    density: usize,
+    maximum_growth: usize,
+}
+
+impl Default for InjectDecoyCode {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            density: DEFAULT_DENSITY,
+            maximum_growth: DEFAULT_MAXIMUM_GROWTH,
+        }
+    }
+}
+
+impl InjectDecoyCode {
+    pub fn with_seed(mut self, seed: usize) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Set the number of decoys inserted for every 100 statements.
+    pub fn with_density(mut self, density: usize) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Set the maximum number of inserted statements, as a percentage of the number of
+    /// statements in the original code.
+    pub fn with_maximum_growth(mut self, maximum_growth: usize) -> Self {
+        self.maximum_growth = maximum_growth;
+        self
+    }
+}
+
+impl FlawlessRule for InjectDecoyCode {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut counter = StatementCounter::default();
+        DefaultVisitor::visit_block(block, &mut counter);
+
+        let budget = counter.count * self.maximum_growth / 100;
+
+        if budget == 0 || self.density == 0 {
+            return;
+        }
+
+        let mut injector = DecoyInjector {
+            random: SeededRandom::new(self.seed as u64),
+            density: self.density,
+            budget,
+            identifiers: collect_identifiers(block),
+            decoy_count: 0,
+            // the chunk is a variadic function
+            functions: vec![FunctionFrame::new(true, std::iter::empty(), block)],
+        };
+        DefaultPostVisitor::visit_block(block, &mut injector);
+    }
+}
+
+impl RuleConfiguration for InjectDecoyCode {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                "seed" => {
+                    self.seed = value.expect_usize(&key)?;
+                }
+                "density" => {
+                    self.density = value.expect_usize(&key)?;
+                }
+                "maximum_growth" => {
+                    self.maximum_growth = value.expect_usize(&key)?;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        INJECT_DECOY_CODE_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        if self.seed != 0 {
+            properties.insert("seed".to_owned(), self.seed.into());
+        }
+        if self.density != DEFAULT_DENSITY {
+            properties.insert("density".to_owned(), self.density.into());
+        }
+        if self.maximum_growth != DEFAULT_MAXIMUM_GROWTH {
+            properties.insert("maximum_growth".to_owned(), self.maximum_growth.into());
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert("seed".to_owned(), self.seed.into());
+        properties.insert("density".to_owned(), self.density.into());
+        properties.insert("maximum_growth".to_owned(), self.maximum_growth.into());
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> InjectDecoyCode {
+        InjectDecoyCode::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_inject_decoy_code", rule);
+    }
+
+    #[test]
+    fn serialize_rule_with_properties() {
+        let rule: Box<dyn Rule> = Box::new(
+            new_rule()
+                .with_seed(7)
+                .with_density(25)
+                .with_maximum_growth(100),
+        );
+
+        assert_json_snapshot!("inject_decoy_code_with_properties", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'inject_decoy_code',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn is_decoy_name_with_generated_name() {
+        assert!(is_decoy_name("__DARKLUA_DECOY12"));
+    }
+
+    #[test]
+    fn is_decoy_name_with_other_name() {
+        assert!(!is_decoy_name("decoy"));
+    }
+}
@@ -1,9 +1,10 @@
 use crate::nodes::{
-    Block, DecimalNumber, Expression, ParentheseExpression, Prefix, StringExpression, UnaryOperator,
+    Block, DecimalNumber, Expression, Identifier, LocalAssignStatement, ParentheseExpression,
+    Prefix, Statement, StringExpression, UnaryOperator,
 };
 use crate::process::{IdentifierTracker, NodeProcessor, NodeVisitor, ScopeVisitor};
 use crate::rules::{
-    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+    Context, Rule, RuleConfiguration, RuleConfigurationError, RuleProcessResult, RuleProperties,
     RulePropertyValue,
 };
 
@@ -81,6 +82,93 @@ impl NodeProcessor for ValueInjection {
     }
 }
 
+/// Finds if a global variable is used, including from nested functions.
+struct GlobalUsage<'a> {
+    identifier: &'a str,
+    identifier_tracker: IdentifierTracker,
+    found: bool,
+}
+
+impl<'a> GlobalUsage<'a> {
+    fn new(identifier: &'a str) -> Self {
+        Self {
+            identifier,
+            identifier_tracker: IdentifierTracker::default(),
+            found: false,
+        }
+    }
+}
+
+impl ops::Deref for GlobalUsage<'_> {
+    type Target = IdentifierTracker;
+
+    fn deref(&self) -> &Self::Target {
+        &self.identifier_tracker
+    }
+}
+
+impl ops::DerefMut for GlobalUsage<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.identifier_tracker
+    }
+}
+
+impl NodeProcessor for GlobalUsage<'_> {
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        if identifier.get_name() == self.identifier && !self.is_identifier_used(self.identifier) {
+            self.found = true;
+        }
+    }
+}
+
+/// Returns the index of the `do` statement where the bundle rule defines the inlined
+/// modules, if the block was produced by the bundle rule.
+fn find_bundled_modules(block: &Block) -> Option<usize> {
+    let modules_identifier = match block.first_statement() {
+        Some(Statement::LocalAssign(local_assign))
+            if local_assign.variables_len() == 1 && local_assign.values_len() == 0 =>
+        {
+            local_assign.iter_variables().next()?.get_name()
+        }
+        _ => return None,
+    };
+
+    block
+        .iter_statements()
+        .position(|statement| match statement {
+            Statement::Do(do_statement) => {
+                let modules = do_statement.get_block();
+                modules.statements_len() != 0
+                    && modules.get_last_statement().is_none()
+                    && modules.iter_statements().all(|statement| match statement {
+                        Statement::Function(function) => {
+                            let name = function.get_name();
+                            name.get_name().get_name() == modules_identifier
+                                && name.get_field_names().len() == 1
+                                && name.get_method().is_none()
+                        }
+                        _ => false,
+                    })
+            }
+            _ => false,
+        })
+}
+
+/// Defines where the local variable is declared in code produced by the bundle rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BundleScope {
+    /// Declare the local variable once, at the start of the bundle.
+    Top,
+    /// Declare the local variable at the start of each inlined module.
+    Module,
+}
+
+impl Default for BundleScope {
+    fn default() -> Self {
+        Self::Top
+    }
+}
+
 pub const INJECT_GLOBAL_VALUE_RULE_NAME: &str = "inject_global_value";
 
 /// A rule to replace global variables with values.
@@ -88,6 +176,8 @@ pub const INJECT_GLOBAL_VALUE_RULE_NAME: &str = "inject_global_value";
 pub struct InjectGlobalValue {
     identifier: String,
     value: Expression,
+    as_local: bool,
+    bundle_scope: BundleScope,
 }
 
 impl InjectGlobalValue {
@@ -95,6 +185,8 @@ impl InjectGlobalValue {
         Self {
             identifier: identifier.into(),
             value: Expression::nil(),
+            as_local: false,
+            bundle_scope: BundleScope::default(),
         }
     }
 
@@ -102,6 +194,8 @@ impl InjectGlobalValue {
         Self {
             identifier: identifier.into(),
             value: Expression::from(value),
+            as_local: false,
+            bundle_scope: BundleScope::default(),
         }
     }
 
@@ -109,6 +203,8 @@ impl InjectGlobalValue {
         Self {
             identifier: identifier.into(),
             value: StringExpression::from_value(value).into(),
+            as_local: false,
+            bundle_scope: BundleScope::default(),
         }
     }
 
@@ -116,7 +212,72 @@ impl InjectGlobalValue {
         Self {
             identifier: identifier.into(),
             value: Expression::from(value),
+            as_local: false,
+            bundle_scope: BundleScope::default(),
+        }
+    }
+
+    /// Declare a local variable with the value at the start of the file, instead of
+    /// replacing each reference to the global variable.
+    pub fn as_local(mut self) -> Self {
+        self.as_local = true;
+        self
+    }
+
+    /// When declaring a local variable in code produced by the bundle rule, declare it
+    /// at the start of each inlined module instead of once at the start of the bundle.
+    pub fn in_each_bundled_module(mut self) -> Self {
+        self.bundle_scope = BundleScope::Module;
+        self
+    }
+
+    /// Declares the local variable at `start` in the block, if the global variable is
+    /// used in the statements that follow.
+    fn declare_local(
+        &self,
+        block: &mut Block,
+        start: usize,
+        context: &Context,
+    ) -> RuleProcessResult {
+        let mut usage = GlobalUsage::new(&self.identifier);
+
+        for statement in block.iter_mut_statements().skip(start) {
+            let is_declared = match statement {
+                Statement::LocalAssign(local_assign) => local_assign
+                    .iter_variables()
+                    .any(|variable| variable.get_name() == &self.identifier),
+                Statement::LocalFunction(function) => function.get_name() == self.identifier,
+                _ => false,
+            };
+
+            if is_declared {
+                return Err(context.error_at(
+                    statement.start_position(),
+                    format!(
+                        "unable to inject `{}` as a local variable because a local variable with the same name is already declared",
+                        self.identifier
+                    ),
+                ));
+            }
+
+            // the block does not declare the identifier, so each statement can be
+            // visited separately
+            ScopeVisitor::visit_statement(statement, &mut usage);
+        }
+
+        if let Some(last_statement) = block.mutate_last_statement() {
+            ScopeVisitor::visit_last_statement(last_statement, &mut usage);
+        }
+
+        if usage.found {
+            block.insert_statement(
+                start,
+                LocalAssignStatement::from_variable(self.identifier.clone())
+                    .with_value(self.value.clone()),
+            );
         }
+
+        Ok(())
     }
 }
 
@@ -125,14 +286,37 @@ impl Default for InjectGlobalValue {
         Self {
             identifier: "".to_owned(),
             value: Expression::nil(),
+            as_local: false,
+            bundle_scope: BundleScope::default(),
         }
     }
 }
 
-impl FlawlessRule for InjectGlobalValue {
-    fn flawless_process(&self, block: &mut Block, _: &Context) {
-        let mut processor = ValueInjection::new(&self.identifier, self.value.clone());
-        ScopeVisitor::visit_block(block, &mut processor);
+impl Rule for InjectGlobalValue {
+    fn process(&self, block: &mut Block, context: &Context) -> RuleProcessResult {
+        context.add_rule_note("injected_globals", vec![self.identifier.clone()]);
+
+        if !self.as_local {
+            let mut processor = ValueInjection::new(&self.identifier, self.value.clone());
+            ScopeVisitor::visit_block(block, &mut processor);
+            return Ok(());
+        }
+
+        let start = match (self.bundle_scope, find_bundled_modules(block)) {
+            (BundleScope::Module, Some(index)) => {
+                if let Some(Statement::Do(do_statement)) = block.iter_mut_statements().nth(index) {
+                    for statement in do_statement.mutate_block().iter_mut_statements() {
+                        if let Statement::Function(function) = statement {
+                            self.declare_local(function.mutate_block(), 0, context)?;
+                        }
+                    }
+                }
+                index + 1
+            }
+            _ => 0,
+        };
+
+        self.declare_local(block, start, context)
     }
 }
 
@@ -162,6 +346,24 @@ impl RuleConfiguration for InjectGlobalValue {
                     }
                     _ => return Err(RuleConfigurationError::UnexpectedValueType(key)),
                 },
+                "as_local" => {
+                    self.as_local = value.expect_bool(&key)?;
+                }
+                "bundle_scope" => {
+                    self.bundle_scope = match value.expect_string(&key)?.as_str() {
+                        "top" => BundleScope::Top,
+                        "module" => BundleScope::Module,
+                        unexpected => {
+                            return Err(RuleConfigurationError::UnexpectedValue {
+                                property: key,
+                                message: format!(
+                                    "invalid value `{}` (must be `top` or `module`)",
+                                    unexpected
+                                ),
+                            })
+                        }
+                    };
+                }
                 "env" => {
                     let variable_name = value.expect_string(&key)?;
                     if let Some(os_value) = env::var_os(&variable_name) {
@@ -233,6 +435,27 @@ impl RuleConfiguration for InjectGlobalValue {
         };
         rules.insert("value".to_owned(), property_value);
 
+        if self.as_local {
+            rules.insert("as_local".to_owned(), true.into());
+        }
+        if self.bundle_scope == BundleScope::Module {
+            rules.insert("bundle_scope".to_owned(), "module".into());
+        }
+
+        rules
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut rules = self.serialize_to_properties();
+
+        rules.insert("as_local".to_owned(), self.as_local.into());
+
+        let bundle_scope = match self.bundle_scope {
+            BundleScope::Top => "top",
+            BundleScope::Module => "module",
+        };
+        rules.insert("bundle_scope".to_owned(), bundle_scope.into());
+
         rules
     }
 }
@@ -323,4 +546,36 @@ mod test {
 
         assert_json_snapshot!("inject_float_value_as_var", rule);
     }
+
+    #[test]
+    fn serialize_inject_string_as_local_var() {
+        let rule: Box<dyn Rule> = Box::new(InjectGlobalValue::string("VAR", "hello").as_local());
+
+        assert_json_snapshot!("inject_hello_value_as_local_var", rule);
+    }
+
+    #[test]
+    fn serialize_inject_string_as_local_var_in_each_module() {
+        let rule: Box<dyn Rule> = Box::new(
+            InjectGlobalValue::string("VAR", "hello")
+                .as_local()
+                .in_each_bundled_module(),
+        );
+
+        assert_json_snapshot!("inject_hello_value_as_local_var_in_each_module", rule);
+    }
+
+    #[test]
+    fn configure_with_invalid_bundle_scope_should_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'inject_global_value',
+            identifier: 'foo',
+            as_local: true,
+            bundle_scope: 'file',
+        }"#,
+        );
+
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,474 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::nodes::{
+    Arguments, AssignStatement, Block, CompoundAssignStatement, Expression, FieldExpression,
+    FunctionCall, FunctionStatement, LastStatement, LocalFunctionStatement, ParentheseExpression,
+    Prefix, Statement, UnaryOperator, Variable,
+};
+use crate::process::{DefaultVisitor, Evaluator, NodeProcessor, NodeVisitor, Scope, ScopeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+use super::verify_no_rule_properties;
+
+fn is_literal(expression: &Expression) -> bool {
+    match expression {
+        Expression::False(_)
+        | Expression::Nil(_)
+        | Expression::Number(_)
+        | Expression::String(_)
+        | Expression::True(_) => true,
+        Expression::Unary(unary) => {
+            unary.operator() == UnaryOperator::Minus
+                && matches!(unary.get_expression(), Expression::Number(_))
+        }
+        _ => false,
+    }
+}
+
+/// Returns the literal returned by a function block written like `return <literal>`.
+fn get_returned_literal(block: &Block) -> Option<&Expression> {
+    if block.statements_len() != 0 {
+        return None;
+    }
+
+    match block.get_last_statement() {
+        Some(LastStatement::Return(statement)) if statement.len() == 1 => statement
+            .iter_expressions()
+            .next()
+            .filter(|expression| is_literal(expression)),
+        _ => None,
+    }
+}
+
+fn get_path(prefix: &Prefix) -> Option<String> {
+    match prefix {
+        Prefix::Identifier(identifier) => Some(identifier.get_name().to_owned()),
+        Prefix::Field(field) => get_field_path(field),
+        _ => None,
+    }
+}
+
+fn get_field_path(field: &FieldExpression) -> Option<String> {
+    let mut path = get_path(field.get_prefix())?;
+    path.push('.');
+    path.push_str(field.get_field().get_name());
+    Some(path)
+}
+
+fn get_root(prefix: &Prefix) -> Option<&str> {
+    match prefix {
+        Prefix::Identifier(identifier) => Some(identifier.get_name()),
+        Prefix::Field(field) => get_root(field.get_prefix()),
+        Prefix::Index(index) => get_root(index.get_prefix()),
+        Prefix::Call(_) | Prefix::Parenthese(_) => None,
+    }
+}
+
+/// Returns `true` if writing to `written_path` can replace the value at `path`.
+fn overwrites(written_path: &str, path: &str) -> bool {
+    path == written_path
+        || (path.starts_with(written_path) && path[written_path.len()..].starts_with('.'))
+}
+
+#[derive(Debug)]
+struct TrivialFunction {
+    path: String,
+    root: String,
+    statement_index: usize,
+    value: Expression,
+    is_table_function: bool,
+}
+
+/// Finds the functions defined in the top-level block of a file that only return a
+/// literal. The functions are either local functions or functions defined on a table
+/// assigned to a local variable.
+fn find_trivial_functions(block: &Block) -> Vec<TrivialFunction> {
+    let mut functions = Vec::new();
+    let mut locals = HashSet::new();
+
+    for (index, statement) in block.iter_statements().enumerate() {
+        match statement {
+            Statement::LocalAssign(assign) => {
+                for variable in assign.iter_variables() {
+                    locals.insert(variable.get_name().to_owned());
+                }
+            }
+            Statement::LocalFunction(function) => {
+                let name = function.get_name().to_owned();
+                if let Some(value) = get_returned_literal(function.get_block()) {
+                    functions.push(TrivialFunction {
+                        path: name.clone(),
+                        root: name.clone(),
+                        statement_index: index,
+                        value: value.clone(),
+                        is_table_function: false,
+                    });
+                }
+                locals.insert(name);
+            }
+            Statement::Function(function) => {
+                let name = function.get_name();
+                let root = name.get_name().get_name();
+
+                if name.get_method().is_some()
+                    || name.get_field_names().is_empty()
+                    || !locals.contains(root)
+                {
+                    continue;
+                }
+
+                if let Some(value) = get_returned_literal(function.get_block()) {
+                    let path =
+                        name.get_field_names()
+                            .iter()
+                            .fold(root.to_owned(), |mut path, field| {
+                                path.push('.');
+                                path.push_str(field.get_name());
+                                path
+                            });
+                    functions.push(TrivialFunction {
+                        path,
+                        root: root.to_owned(),
+                        statement_index: index,
+                        value: value.clone(),
+                        is_table_function: true,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    functions
+}
+
+/// Collects every declaration, every write and every value read from a path in a file, to
+/// find which trivial functions can be reassigned or shadowed.
+#[derive(Debug, Default)]
+struct WriteCollector {
+    declarations: HashMap<String, usize>,
+    written_paths: HashMap<String, usize>,
+    dynamically_written_roots: HashSet<String>,
+    escaped_paths: HashSet<String>,
+    has_unknown_writes: bool,
+}
+
+impl WriteCollector {
+    fn declare(&mut self, name: &str) {
+        *self.declarations.entry(name.to_owned()).or_default() += 1;
+    }
+
+    fn write_path(&mut self, path: String) {
+        *self.written_paths.entry(path).or_default() += 1;
+    }
+
+    fn write_variable(&mut self, variable: &Variable) {
+        match variable {
+            Variable::Identifier(identifier) => {
+                self.write_path(identifier.get_name().to_owned());
+            }
+            Variable::Field(field) => match get_field_path(field) {
+                Some(path) => self.write_path(path),
+                None => self.write_unknown_root(field.get_prefix()),
+            },
+            Variable::Index(index) => self.write_unknown_root(index.get_prefix()),
+        }
+    }
+
+    fn write_unknown_root(&mut self, prefix: &Prefix) {
+        match get_root(prefix) {
+            Some(root) => {
+                self.dynamically_written_roots.insert(root.to_owned());
+            }
+            None => {
+                self.has_unknown_writes = true;
+            }
+        }
+    }
+
+    /// Records a table read as a value (stored in a variable, passed to a function or
+    /// returned), since it can then be modified without writing to its path.
+    fn escape_path(&mut self, path: String) {
+        self.escaped_paths.insert(path);
+    }
+
+    fn escape_prefix(&mut self, prefix: &Prefix) {
+        match get_path(prefix) {
+            Some(path) => self.escape_path(path),
+            None => self.escape_root(prefix),
+        }
+    }
+
+    fn escape_root(&mut self, prefix: &Prefix) {
+        if let Some(root) = get_root(prefix) {
+            self.escape_path(root.to_owned());
+        }
+    }
+
+    fn has_escaped_table(&self, function: &TrivialFunction) -> bool {
+        self.escaped_paths
+            .iter()
+            .any(|path| path != &function.path && overwrites(path, &function.path))
+    }
+
+    fn can_inline(&self, function: &TrivialFunction) -> bool {
+        if self.declarations.get(&function.root).copied().unwrap_or(0) != 1 {
+            return false;
+        }
+
+        let writes: usize = self
+            .written_paths
+            .iter()
+            .filter(|(path, _)| overwrites(path, &function.path))
+            .map(|(_, count)| count)
+            .sum();
+
+        if function.is_table_function {
+            // the definition of the function is the only write allowed
+            writes == 1
+                && !self.has_unknown_writes
+                && !self.dynamically_written_roots.contains(&function.root)
+                && !self.has_escaped_table(function)
+        } else {
+            writes == 0
+        }
+    }
+}
+
+impl Scope for WriteCollector {
+    fn push(&mut self) {}
+
+    fn pop(&mut self) {}
+
+    fn insert(&mut self, identifier: &mut String) {
+        self.declare(identifier);
+    }
+
+    fn insert_self(&mut self) {
+        self.declare("self");
+    }
+
+    fn insert_local(&mut self, identifier: &mut String, _value: Option<&mut Expression>) {
+        self.declare(identifier);
+    }
+
+    fn insert_local_function(&mut self, function: &mut LocalFunctionStatement) {
+        self.declare(function.get_name());
+    }
+}
+
+impl NodeProcessor for WriteCollector {
+    fn process_assign_statement(&mut self, assign: &mut AssignStatement) {
+        for variable in assign.iter_variables() {
+            self.write_variable(variable);
+        }
+    }
+
+    fn process_compound_assign_statement(&mut self, assign: &mut CompoundAssignStatement) {
+        self.write_variable(assign.get_variable());
+    }
+
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        let name = function.get_name();
+        let mut path = name.get_name().get_name().to_owned();
+
+        for field in name.get_field_names().iter().chain(name.get_method()) {
+            path.push('.');
+            path.push_str(field.get_name());
+        }
+
+        self.write_path(path);
+    }
+
+    fn process_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Identifier(identifier) => {
+                self.escape_path(identifier.get_name().to_owned());
+            }
+            Expression::Field(field) => match get_field_path(field) {
+                Some(path) => self.escape_path(path),
+                None => self.escape_root(field.get_prefix()),
+            },
+            Expression::Index(index) => self.escape_root(index.get_prefix()),
+            _ => {}
+        }
+    }
+
+    fn process_function_call(&mut self, call: &mut FunctionCall) {
+        // a method call passes its prefix as the `self` parameter
+        if call.get_method().is_some() {
+            self.escape_prefix(call.get_prefix());
+        }
+    }
+}
+
+/// Replaces the calls to trivial functions that appear after their definition.
+struct CallReplacer {
+    functions: HashMap<String, (usize, Expression)>,
+    statement_index: usize,
+    evaluator: Evaluator,
+}
+
+impl CallReplacer {
+    fn arguments_have_side_effects(&self, arguments: &Arguments) -> bool {
+        match arguments {
+            Arguments::Tuple(tuple) => tuple
+                .iter_values()
+                .any(|value| self.evaluator.has_side_effects(value)),
+            Arguments::String(_) => false,
+            Arguments::Table(table) => self
+                .evaluator
+                .has_side_effects(&Expression::from(table.clone())),
+        }
+    }
+
+    fn get_call_value(&self, call: &FunctionCall) -> Option<Expression> {
+        if call.get_method().is_some() {
+            return None;
+        }
+
+        let path = get_path(call.get_prefix())?;
+        let (definition_index, value) = self.functions.get(&path)?;
+
+        if *definition_index >= self.statement_index
+            || self.arguments_have_side_effects(call.get_arguments())
+        {
+            return None;
+        }
+
+        Some(value.clone())
+    }
+}
+
+impl NodeProcessor for CallReplacer {
+    fn process_expression(&mut self, expression: &mut Expression) {
+        let value = match expression {
+            Expression::Call(call) => self.get_call_value(call),
+            _ => None,
+        };
+
+        if let Some(value) = value {
+            *expression = value;
+        }
+    }
+
+    fn process_prefix_expression(&mut self, prefix: &mut Prefix) {
+        let value = match prefix {
+            Prefix::Call(call) => self.get_call_value(call),
+            _ => None,
+        };
+
+        if let Some(value) = value {
+            *prefix = ParentheseExpression::new(value).into();
+        }
+    }
+}
+
+pub const INLINE_TRIVIAL_FUNCTIONS_RULE_NAME: &str = "inline_trivial_functions";
+
+/// A rule that replaces calls to functions that only return a literal value with that value.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct InlineTrivialFunctions {}
+
+impl FlawlessRule for InlineTrivialFunctions {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let functions = find_trivial_functions(block);
+
+        if functions.is_empty() {
+            return;
+        }
+
+        let mut collector = WriteCollector::default();
+        ScopeVisitor::visit_block(block, &mut collector);
+
+        let functions: HashMap<_, _> = functions
+            .into_iter()
+            .filter(|function| collector.can_inline(function))
+            .map(|function| (function.path, (function.statement_index, function.value)))
+            .collect();
+
+        if functions.is_empty() {
+            return;
+        }
+
+        let mut replacer = CallReplacer {
+            functions,
+            statement_index: 0,
+            evaluator: Evaluator::default(),
+        };
+
+        for (index, statement) in block.iter_mut_statements().enumerate() {
+            replacer.statement_index = index;
+            DefaultVisitor::visit_statement(statement, &mut replacer);
+        }
+
+        replacer.statement_index = block.statements_len();
+        if let Some(last_statement) = block.mutate_last_statement() {
+            DefaultVisitor::visit_last_statement(last_statement, &mut replacer);
+        }
+    }
+}
+
+impl RuleConfiguration for InlineTrivialFunctions {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        verify_no_rule_properties(&properties)?;
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        INLINE_TRIVIAL_FUNCTIONS_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        RuleProperties::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> InlineTrivialFunctions {
+        InlineTrivialFunctions::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_inline_trivial_functions", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'inline_trivial_functions',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn overwrites_same_path() {
+        assert!(overwrites("Config.get", "Config.get"));
+    }
+
+    #[test]
+    fn overwrites_parent_path() {
+        assert!(overwrites("Config", "Config.get"));
+    }
+
+    #[test]
+    fn does_not_overwrite_path_with_same_start() {
+        assert!(!overwrites("Config.getter", "Config.get"));
+        assert!(!overwrites("Config.g", "Config.get"));
+    }
+}
@@ -0,0 +1,87 @@
+//! Limits of the Lua 5.1 compiler that rules must respect when they declare new locals.
+
+use crate::nodes::{Block, Statement};
+
+/// The maximum number of locals that can be active at the same time in a function.
+pub(crate) const MAX_LOCALS: usize = 200;
+/// The maximum number of upvalues that a function can use.
+pub(crate) const MAX_UPVALUES: usize = 60;
+
+/// Returns the maximum number of locals that are active at the same time in the block,
+/// including the hidden locals that loops declare to store their state. The locals of
+/// nested functions are not counted, because each function has its own limit.
+pub(crate) fn count_active_locals(block: &Block) -> usize {
+    let mut active = 0;
+    let mut maximum = 0;
+
+    for statement in block.iter_statements() {
+        let nested = match statement {
+            Statement::LocalAssign(assign) => {
+                active += assign.variables_len();
+                0
+            }
+            Statement::LocalFunction(_) => {
+                active += 1;
+                0
+            }
+            Statement::Do(do_statement) => count_active_locals(do_statement.get_block()),
+            Statement::If(if_statement) => if_statement
+                .iter_branches()
+                .map(|branch| count_active_locals(branch.get_block()))
+                .chain(if_statement.get_else_block().map(count_active_locals))
+                .max()
+                .unwrap_or(0),
+            Statement::While(while_statement) => count_active_locals(while_statement.get_block()),
+            Statement::Repeat(repeat) => count_active_locals(repeat.get_block()),
+            Statement::NumericFor(numeric_for) => 4 + count_active_locals(numeric_for.get_block()),
+            Statement::GenericFor(generic_for) => {
+                3 + generic_for.get_identifiers().len()
+                    + count_active_locals(generic_for.get_block())
+            }
+            _ => 0,
+        };
+        maximum = maximum.max(active + nested);
+    }
+
+    maximum
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::Parser;
+
+    fn count(code: &str) -> usize {
+        count_active_locals(&Parser::default().parse(code).expect("code should parse"))
+    }
+
+    #[test]
+    fn counts_locals_of_the_block() {
+        assert_eq!(count("local a, b = 1, 2 local function f() end"), 3);
+    }
+
+    #[test]
+    fn counts_locals_of_nested_blocks() {
+        assert_eq!(count("local a do local b, c end"), 3);
+    }
+
+    #[test]
+    fn sibling_blocks_are_not_active_at_the_same_time() {
+        assert_eq!(
+            count("do local a, b end do local c end if x then local d else local e, f end"),
+            2
+        );
+    }
+
+    #[test]
+    fn counts_hidden_locals_of_loops() {
+        assert_eq!(count("for i = 1, 2 do local a end"), 5);
+        assert_eq!(count("for k, v in pairs(t) do end"), 5);
+    }
+
+    #[test]
+    fn ignores_locals_of_nested_functions() {
+        assert_eq!(count("local function f() local a, b, c end"), 1);
+    }
+}
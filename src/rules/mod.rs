@@ -2,76 +2,124 @@
 
 mod append_text_comment;
 pub mod bundle;
+mod cache_field_access;
+mod cache_services;
 mod call_parens;
 mod compute_expression;
 mod configuration_error;
 mod convert_index_to_field;
 mod convert_require;
+mod convert_to_compound_assign;
 mod empty_do;
 mod filter_early_return;
+mod flatten_control_flow;
 mod group_local;
+mod hoist_requires;
+mod inject_decoy_code;
 mod inject_value;
+mod inline_trivial_functions;
+mod lua_limits;
 mod method_def;
 mod no_local_function;
+mod normalize_unpack;
+mod pool_strings;
 mod remove_assertions;
+mod remove_attributes;
 mod remove_call_match;
 mod remove_comments;
 mod remove_compound_assign;
 mod remove_continue;
 mod remove_debug_profiling;
 mod remove_floor_division;
+mod remove_goto;
 mod remove_if_expression;
 mod remove_interpolated_string;
 mod remove_nil_declarations;
 mod remove_spaces;
+mod remove_test_blocks;
+mod remove_trailing_nil_arguments;
+mod remove_type_assertions;
+mod remove_type_export;
 mod remove_types;
 mod remove_unused_variable;
 mod rename_variables;
+mod replace_calls;
 mod replace_referenced_tokens;
 pub(crate) mod require;
 mod rule_property;
 mod shift_token_line;
+mod simplify_nil_defaults;
+mod simplify_stdlib_idioms;
+mod simplify_string_format;
+mod split_large_constructs;
+mod unroll_loops;
 mod unused_if_branch;
 mod unused_while;
+mod wrap_in_pcall;
 
 pub use append_text_comment::*;
+pub use cache_field_access::*;
+pub use cache_services::*;
 pub use call_parens::*;
 pub use compute_expression::*;
 pub use configuration_error::RuleConfigurationError;
 pub use convert_index_to_field::*;
 pub use convert_require::*;
+pub use convert_to_compound_assign::*;
 pub use empty_do::*;
 pub use filter_early_return::*;
+pub use flatten_control_flow::*;
 pub use group_local::*;
+pub use hoist_requires::*;
+pub use inject_decoy_code::*;
 pub use inject_value::*;
+pub use inline_trivial_functions::*;
 pub use method_def::*;
 pub use no_local_function::*;
+pub use normalize_unpack::*;
+pub use pool_strings::*;
 pub use remove_assertions::*;
+pub use remove_attributes::*;
 pub use remove_comments::*;
 pub use remove_compound_assign::*;
 pub use remove_continue::*;
 pub use remove_debug_profiling::*;
 pub use remove_floor_division::*;
+pub use remove_goto::*;
 pub use remove_if_expression::*;
 pub use remove_interpolated_string::*;
 pub use remove_nil_declarations::*;
 pub use remove_spaces::*;
+pub use remove_test_blocks::*;
+pub use remove_trailing_nil_arguments::*;
+pub use remove_type_assertions::*;
+pub use remove_type_export::*;
 pub use remove_types::*;
 pub use remove_unused_variable::*;
 pub use rename_variables::*;
+pub use replace_calls::*;
 pub(crate) use replace_referenced_tokens::*;
+pub use require::PathRequireMode;
 pub use rule_property::*;
 pub(crate) use shift_token_line::*;
+pub use simplify_nil_defaults::*;
+pub use simplify_stdlib_idioms::*;
+pub use simplify_string_format::*;
+pub use split_large_constructs::*;
+pub use unroll_loops::*;
 pub use unused_if_branch::*;
 pub use unused_while::*;
+pub use wrap_in_pcall::*;
 
-use crate::nodes::Block;
+use crate::frontend::{BundleManifest, DiagnosticKind, ModuleEdge, RuleNoteValue};
+use crate::nodes::{Block, SourcePosition};
+use crate::utils::{deserialize_indexed_list, with_error_path, LuauConfigurationCache};
 use crate::Resources;
 
 use serde::de::{self, MapAccess, Visitor};
 use serde::ser::SerializeMap;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
@@ -83,6 +131,10 @@ pub struct ContextBuilder<'a, 'resources, 'code> {
     original_code: &'code str,
     blocks: HashMap<PathBuf, &'a Block>,
     project_location: Option<PathBuf>,
+    collect_module_graph: bool,
+    collect_rule_notes: bool,
+    collect_bundle_manifest: bool,
+    luau_configuration_cache: Option<LuauConfigurationCache>,
 }
 
 impl<'a, 'resources, 'code> ContextBuilder<'a, 'resources, 'code> {
@@ -97,6 +149,10 @@ impl<'a, 'resources, 'code> ContextBuilder<'a, 'resources, 'code> {
             original_code,
             blocks: Default::default(),
             project_location: None,
+            collect_module_graph: false,
+            collect_rule_notes: false,
+            collect_bundle_manifest: false,
+            luau_configuration_cache: None,
         }
     }
 
@@ -105,6 +161,30 @@ impl<'a, 'resources, 'code> ContextBuilder<'a, 'resources, 'code> {
         self
     }
 
+    /// Records the require calls found by the rules (see [`Context::add_module_edge`]).
+    pub(crate) fn collect_module_graph(mut self) -> Self {
+        self.collect_module_graph = true;
+        self
+    }
+
+    /// Records the notes attached by the rules (see [`Context::add_rule_note`]).
+    pub(crate) fn collect_rule_notes(mut self) -> Self {
+        self.collect_rule_notes = true;
+        self
+    }
+
+    /// Records the modules inlined by the bundler (see [`Context::set_bundle_manifest`]).
+    pub(crate) fn collect_bundle_manifest(mut self) -> Self {
+        self.collect_bundle_manifest = true;
+        self
+    }
+
+    /// Reuses the `.luaurc` configurations found while processing other files.
+    pub(crate) fn with_luau_configuration_cache(mut self, cache: LuauConfigurationCache) -> Self {
+        self.luau_configuration_cache = Some(cache);
+        self
+    }
+
     pub fn build(self) -> Context<'a, 'resources, 'code> {
         Context {
             path: self.path,
@@ -113,6 +193,23 @@ impl<'a, 'resources, 'code> ContextBuilder<'a, 'resources, 'code> {
             blocks: self.blocks,
             project_location: self.project_location,
             dependencies: Default::default(),
+            error_details: Default::default(),
+            module_edges: if self.collect_module_graph {
+                Some(Default::default())
+            } else {
+                None
+            },
+            rule_notes: if self.collect_rule_notes {
+                Some(Default::default())
+            } else {
+                None
+            },
+            bundle_manifest: if self.collect_bundle_manifest {
+                Some(Default::default())
+            } else {
+                None
+            },
+            luau_configuration_cache: self.luau_configuration_cache,
         }
     }
 
@@ -130,6 +227,17 @@ pub struct Context<'a, 'resources, 'code> {
     blocks: HashMap<PathBuf, &'a Block>,
     project_location: Option<PathBuf>,
     dependencies: std::cell::RefCell<Vec<PathBuf>>,
+    error_details: std::cell::RefCell<Vec<Option<RuleErrorDetails>>>,
+    module_edges: Option<std::cell::RefCell<Vec<ModuleEdge>>>,
+    rule_notes: Option<std::cell::RefCell<BTreeMap<String, RuleNoteValue>>>,
+    bundle_manifest: Option<std::cell::RefCell<Option<BundleManifest>>>,
+    luau_configuration_cache: Option<LuauConfigurationCache>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct RuleErrorDetails {
+    pub(crate) position: Option<SourcePosition>,
+    pub(crate) kind: Option<DiagnosticKind>,
 }
 
 impl Context<'_, '_, '_> {
@@ -154,6 +262,140 @@ impl Context<'_, '_, '_> {
         self.dependencies.into_inner().into_iter()
     }
 
+    pub(crate) fn luau_configuration_cache(&self) -> Option<&LuauConfigurationCache> {
+        self.luau_configuration_cache.as_ref()
+    }
+
+    pub(crate) fn is_collecting_module_graph(&self) -> bool {
+        self.module_edges.is_some()
+    }
+
+    /// Records a require call found by a rule, when the module graph is collected.
+    pub(crate) fn add_module_edge(&self, module_edge: ModuleEdge) {
+        if let Some(module_edges) = self.module_edges.as_ref() {
+            if let Ok(mut module_edges) = module_edges.try_borrow_mut() {
+                module_edges.push(module_edge);
+            } else {
+                log::warn!("unable to submit module edge (internal error)");
+            }
+        }
+    }
+
+    pub(crate) fn take_module_edges(&self) -> Vec<ModuleEdge> {
+        self.module_edges
+            .as_ref()
+            .and_then(|module_edges| module_edges.try_borrow_mut().ok())
+            .map(|mut module_edges| std::mem::take(&mut *module_edges))
+            .unwrap_or_default()
+    }
+
+    /// Attaches a short note describing what the rule did to the file, when the applied
+    /// rules are collected. A note with the same name replaces the previous one.
+    pub(crate) fn add_rule_note(&self, name: &str, value: impl Into<RuleNoteValue>) {
+        if let Some(rule_notes) = self.rule_notes.as_ref() {
+            if let Ok(mut rule_notes) = rule_notes.try_borrow_mut() {
+                rule_notes.insert(name.to_owned(), value.into());
+            } else {
+                log::warn!("unable to submit rule note (internal error)");
+            }
+        }
+    }
+
+    pub(crate) fn take_rule_notes(&self) -> BTreeMap<String, RuleNoteValue> {
+        self.rule_notes
+            .as_ref()
+            .and_then(|rule_notes| rule_notes.try_borrow_mut().ok())
+            .map(|mut rule_notes| std::mem::take(&mut *rule_notes))
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn is_collecting_bundle_manifest(&self) -> bool {
+        self.bundle_manifest.is_some()
+    }
+
+    /// Records the modules inlined by the bundler, when the bundle manifest is collected.
+    pub(crate) fn set_bundle_manifest(&self, manifest: BundleManifest) {
+        if let Some(bundle_manifest) = self.bundle_manifest.as_ref() {
+            if let Ok(mut bundle_manifest) = bundle_manifest.try_borrow_mut() {
+                bundle_manifest.replace(manifest);
+            } else {
+                log::warn!("unable to submit bundle manifest (internal error)");
+            }
+        }
+    }
+
+    pub(crate) fn take_bundle_manifest(&self) -> Option<BundleManifest> {
+        self.bundle_manifest
+            .as_ref()
+            .and_then(|bundle_manifest| bundle_manifest.try_borrow_mut().ok())
+            .and_then(|mut bundle_manifest| bundle_manifest.take())
+    }
+
+    /// Creates an error for a rule that failed because of a node located at the given
+    /// position (usually obtained with the `start_position` method of a node). When the
+    /// rule returns this error, darklua reports the line and the column of the node, with
+    /// the line of code where it is.
+    ///
+    /// Nodes that do not have tokens do not have a position: the error is then reported
+    /// like any other rule error.
+    pub fn error_at(
+        &self,
+        position: impl Into<Option<SourcePosition>>,
+        message: impl Into<String>,
+    ) -> RuleError {
+        self.record_error(position.into(), message.into(), None)
+    }
+
+    /// Like [`Context::error_at`], but also gives the kind of diagnostic reported for the
+    /// error (see [`DarkluaError::to_diagnostic`](crate::DarkluaError::to_diagnostic)).
+    pub(crate) fn error_of_kind_at(
+        &self,
+        kind: DiagnosticKind,
+        position: Option<SourcePosition>,
+        message: impl Into<String>,
+    ) -> RuleError {
+        self.record_error(position, message.into(), Some(kind))
+    }
+
+    fn record_error(
+        &self,
+        position: Option<SourcePosition>,
+        message: String,
+        kind: Option<DiagnosticKind>,
+    ) -> RuleError {
+        let mut error = RuleError::new(message);
+
+        if position.is_some() || kind.is_some() {
+            if let Ok(mut error_details) = self.error_details.try_borrow_mut() {
+                error.details_key = Some(error_details.len());
+                error_details.push(Some(RuleErrorDetails { position, kind }));
+            } else {
+                log::warn!("unable to submit error details (internal error)");
+            }
+        }
+
+        error
+    }
+
+    /// Returns the details given to [`Context::error_at`] when creating the given error.
+    pub(crate) fn take_error_details(&self, error: &RuleError) -> Option<RuleErrorDetails> {
+        let key = error.details_key?;
+        let mut error_details = self.error_details.try_borrow_mut().ok()?;
+        error_details.get_mut(key)?.take()
+    }
+
+    /// Formats the current path with the line and column of the given position, like
+    /// `src/main.lua:3:15`.
+    pub(crate) fn format_position(&self, position: Option<SourcePosition>) -> String {
+        match position {
+            Some(position) => match position.column(self.original_code) {
+                Some(column) => format!("{}:{}:{}", self.path.display(), position.line(), column),
+                None => format!("{}:{}", self.path.display(), position.line()),
+            },
+            None => self.path.display().to_string(),
+        }
+    }
+
     fn resources(&self) -> &Resources {
         self.resources
     }
@@ -176,11 +418,58 @@ impl Context<'_, '_, '_> {
     }
 }
 
-pub type RuleProcessResult = Result<(), String>;
+/// The error of a rule that could not process a block. It is created from a message, or
+/// with [`Context::error_at`] to locate the error in the processed file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleError {
+    message: String,
+    /// The index of the details recorded by the context that created the error.
+    details_key: Option<usize>,
+}
+
+impl RuleError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            details_key: None,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<String> for RuleError {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<&str> for RuleError {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<RuleError> for String {
+    fn from(error: RuleError) -> Self {
+        error.message
+    }
+}
+
+pub type RuleProcessResult = Result<(), RuleError>;
 
 /// Defines an interface that will be used to mutate blocks and how to serialize and deserialize
-/// the rule configuration.
-pub trait Rule: RuleConfiguration + fmt::Debug {
+/// the rule configuration. Rules must be `Send` and `Sync` because files can be processed in
+/// parallel (see [`Options::parallel`](crate::Options::parallel)).
+pub trait Rule: RuleConfiguration + fmt::Debug + Send + Sync {
     /// This method should mutate the given block to apply the rule
     fn process(&self, block: &mut Block, context: &Context) -> RuleProcessResult;
 
@@ -200,6 +489,12 @@ pub trait RuleConfiguration {
     /// For implementing the serialize trait on the Rule trait, this method should return all
     /// properties that differs from their default value.
     fn serialize_to_properties(&self) -> RuleProperties;
+    /// Returns all properties of the rule, including the ones that have their default value.
+    /// It is used to serialize rules verbosely and to compare them, so rules that skip their
+    /// default values in `serialize_to_properties` must override it.
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        self.serialize_to_properties()
+    }
     /// Returns `true` if the rule has at least one property.
     fn has_properties(&self) -> bool {
         !self.serialize_to_properties().is_empty()
@@ -210,13 +505,19 @@ pub trait FlawlessRule {
     fn flawless_process(&self, block: &mut Block, context: &Context);
 }
 
-impl<T: FlawlessRule + RuleConfiguration + fmt::Debug> Rule for T {
+impl<T: FlawlessRule + RuleConfiguration + fmt::Debug + Send + Sync> Rule for T {
     fn process(&self, block: &mut Block, context: &Context) -> RuleProcessResult {
         self.flawless_process(block, context);
         Ok(())
     }
 }
 
+impl<T: Rule + 'static> From<T> for Box<dyn Rule> {
+    fn from(rule: T) -> Self {
+        Box::new(rule)
+    }
+}
+
 /// A function to get the default rule stack for darklua. All the rules here must preserve all the
 /// functionalities of the original code after being applied. They must guarantee that the
 /// processed block will work as much as the original one.
@@ -241,141 +542,292 @@ pub fn get_default_rules() -> Vec<Box<dyn Rule>> {
 pub fn get_all_rule_names() -> Vec<&'static str> {
     vec![
         APPEND_TEXT_COMMENT_RULE_NAME,
+        CACHE_FIELD_ACCESS_RULE_NAME,
+        CACHE_SERVICES_RULE_NAME,
         COMPUTE_EXPRESSIONS_RULE_NAME,
         CONVERT_INDEX_TO_FIELD_RULE_NAME,
         CONVERT_LOCAL_FUNCTION_TO_ASSIGN_RULE_NAME,
         CONVERT_REQUIRE_RULE_NAME,
+        CONVERT_TO_COMPOUND_ASSIGNMENT_RULE_NAME,
         FILTER_AFTER_EARLY_RETURN_RULE_NAME,
+        FLATTEN_CONTROL_FLOW_RULE_NAME,
         GROUP_LOCAL_ASSIGNMENT_RULE_NAME,
+        HOIST_REQUIRES_RULE_NAME,
+        INJECT_DECOY_CODE_RULE_NAME,
         INJECT_GLOBAL_VALUE_RULE_NAME,
+        INLINE_TRIVIAL_FUNCTIONS_RULE_NAME,
+        NORMALIZE_UNPACK_RULE_NAME,
+        POOL_STRINGS_RULE_NAME,
         REMOVE_ASSERTIONS_RULE_NAME,
         REMOVE_COMMENTS_RULE_NAME,
         REMOVE_COMPOUND_ASSIGNMENT_RULE_NAME,
         REMOVE_DEBUG_PROFILING_RULE_NAME,
         REMOVE_EMPTY_DO_RULE_NAME,
+        REMOVE_FLOOR_DIVISION_RULE_NAME,
         REMOVE_FUNCTION_CALL_PARENS_RULE_NAME,
+        REMOVE_GOTO_RULE_NAME,
         REMOVE_INTERPOLATED_STRING_RULE_NAME,
         REMOVE_METHOD_DEFINITION_RULE_NAME,
         REMOVE_NIL_DECLARATION_RULE_NAME,
         REMOVE_SPACES_RULE_NAME,
+        REMOVE_TEST_BLOCKS_RULE_NAME,
+        REMOVE_TRAILING_NIL_ARGUMENTS_RULE_NAME,
+        REMOVE_TYPE_ASSERTIONS_RULE_NAME,
+        REMOVE_TYPE_EXPORT_RULE_NAME,
         REMOVE_TYPES_RULE_NAME,
         REMOVE_UNUSED_IF_BRANCH_RULE_NAME,
         REMOVE_UNUSED_VARIABLE_RULE_NAME,
         REMOVE_UNUSED_WHILE_RULE_NAME,
         RENAME_VARIABLES_RULE_NAME,
+        REPLACE_CALLS_RULE_NAME,
+        SIMPLIFY_NIL_DEFAULTS_RULE_NAME,
+        SIMPLIFY_STDLIB_IDIOMS_RULE_NAME,
+        SIMPLIFY_STRING_FORMAT_RULE_NAME,
+        SPLIT_LARGE_CONSTRUCTS_RULE_NAME,
+        UNROLL_LOOPS_RULE_NAME,
+        WRAP_IN_PCALL_RULE_NAME,
         REMOVE_IF_EXPRESSION_RULE_NAME,
         REMOVE_CONTINUE_RULE_NAME,
+        REMOVE_ATTRIBUTES_RULE_NAME,
     ]
 }
 
+/// Finds the rule name that is the closest to the given name, when the name looks like a
+/// typo of that rule name.
+fn find_closest_rule_name(name: &str) -> Option<&'static str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    get_all_rule_names()
+        .into_iter()
+        .map(|rule_name| (edit_distance(name, rule_name), rule_name))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, rule_name)| rule_name)
+}
+
+/// Computes the Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current_row = Vec::with_capacity(b.len() + 1);
+        current_row.push(i + 1);
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution_cost = if a_char == *b_char { 0 } else { 1 };
+            current_row.push(
+                (previous_row[j] + substitution_cost)
+                    .min(previous_row[j + 1] + 1)
+                    .min(current_row[j] + 1),
+            );
+        }
+
+        previous_row = current_row;
+    }
+
+    previous_row[b.len()]
+}
+
 impl FromStr for Box<dyn Rule> {
     type Err = String;
 
     fn from_str(string: &str) -> Result<Self, Self::Err> {
         let rule: Box<dyn Rule> = match string {
             APPEND_TEXT_COMMENT_RULE_NAME => Box::<AppendTextComment>::default(),
+            CACHE_FIELD_ACCESS_RULE_NAME => Box::<CacheFieldAccess>::default(),
+            CACHE_SERVICES_RULE_NAME => Box::<CacheServices>::default(),
             COMPUTE_EXPRESSIONS_RULE_NAME => Box::<ComputeExpression>::default(),
             CONVERT_INDEX_TO_FIELD_RULE_NAME => Box::<ConvertIndexToField>::default(),
             CONVERT_LOCAL_FUNCTION_TO_ASSIGN_RULE_NAME => {
                 Box::<ConvertLocalFunctionToAssign>::default()
             }
             CONVERT_REQUIRE_RULE_NAME => Box::<ConvertRequire>::default(),
+            CONVERT_TO_COMPOUND_ASSIGNMENT_RULE_NAME => {
+                Box::<ConvertToCompoundAssignment>::default()
+            }
             FILTER_AFTER_EARLY_RETURN_RULE_NAME => Box::<FilterAfterEarlyReturn>::default(),
+            FLATTEN_CONTROL_FLOW_RULE_NAME => Box::<FlattenControlFlow>::default(),
             GROUP_LOCAL_ASSIGNMENT_RULE_NAME => Box::<GroupLocalAssignment>::default(),
+            HOIST_REQUIRES_RULE_NAME => Box::<HoistRequires>::default(),
+            INJECT_DECOY_CODE_RULE_NAME => Box::<InjectDecoyCode>::default(),
             INJECT_GLOBAL_VALUE_RULE_NAME => Box::<InjectGlobalValue>::default(),
+            INLINE_TRIVIAL_FUNCTIONS_RULE_NAME => Box::<InlineTrivialFunctions>::default(),
+            NORMALIZE_UNPACK_RULE_NAME => Box::<NormalizeUnpack>::default(),
+            POOL_STRINGS_RULE_NAME => Box::<PoolStrings>::default(),
             REMOVE_ASSERTIONS_RULE_NAME => Box::<RemoveAssertions>::default(),
+            REMOVE_ATTRIBUTES_RULE_NAME => Box::<RemoveAttributes>::default(),
             REMOVE_COMMENTS_RULE_NAME => Box::<RemoveComments>::default(),
             REMOVE_COMPOUND_ASSIGNMENT_RULE_NAME => Box::<RemoveCompoundAssignment>::default(),
             REMOVE_DEBUG_PROFILING_RULE_NAME => Box::<RemoveDebugProfiling>::default(),
             REMOVE_EMPTY_DO_RULE_NAME => Box::<RemoveEmptyDo>::default(),
             REMOVE_FLOOR_DIVISION_RULE_NAME => Box::<RemoveFloorDivision>::default(),
             REMOVE_FUNCTION_CALL_PARENS_RULE_NAME => Box::<RemoveFunctionCallParens>::default(),
+            REMOVE_GOTO_RULE_NAME => Box::<RemoveGoto>::default(),
             REMOVE_INTERPOLATED_STRING_RULE_NAME => Box::<RemoveInterpolatedString>::default(),
             REMOVE_METHOD_DEFINITION_RULE_NAME => Box::<RemoveMethodDefinition>::default(),
             REMOVE_NIL_DECLARATION_RULE_NAME => Box::<RemoveNilDeclaration>::default(),
             REMOVE_SPACES_RULE_NAME => Box::<RemoveSpaces>::default(),
+            REMOVE_TEST_BLOCKS_RULE_NAME => Box::<RemoveTestBlocks>::default(),
+            REMOVE_TRAILING_NIL_ARGUMENTS_RULE_NAME => Box::<RemoveTrailingNilArguments>::default(),
+            REMOVE_TYPE_ASSERTIONS_RULE_NAME => Box::<RemoveTypeAssertions>::default(),
+            REMOVE_TYPE_EXPORT_RULE_NAME => Box::<RemoveTypeExport>::default(),
             REMOVE_TYPES_RULE_NAME => Box::<RemoveTypes>::default(),
             REMOVE_UNUSED_IF_BRANCH_RULE_NAME => Box::<RemoveUnusedIfBranch>::default(),
             REMOVE_UNUSED_VARIABLE_RULE_NAME => Box::<RemoveUnusedVariable>::default(),
             REMOVE_UNUSED_WHILE_RULE_NAME => Box::<RemoveUnusedWhile>::default(),
             RENAME_VARIABLES_RULE_NAME => Box::<RenameVariables>::default(),
+            REPLACE_CALLS_RULE_NAME => Box::<ReplaceCalls>::default(),
+            SIMPLIFY_NIL_DEFAULTS_RULE_NAME => Box::<SimplifyNilDefaults>::default(),
+            SIMPLIFY_STDLIB_IDIOMS_RULE_NAME => Box::<SimplifyStdlibIdioms>::default(),
+            SIMPLIFY_STRING_FORMAT_RULE_NAME => Box::<SimplifyStringFormat>::default(),
+            SPLIT_LARGE_CONSTRUCTS_RULE_NAME => Box::<SplitLargeConstructs>::default(),
+            UNROLL_LOOPS_RULE_NAME => Box::<UnrollLoops>::default(),
+            WRAP_IN_PCALL_RULE_NAME => Box::<WrapInPcall>::default(),
             REMOVE_IF_EXPRESSION_RULE_NAME => Box::<RemoveIfExpression>::default(),
             REMOVE_CONTINUE_RULE_NAME => Box::<RemoveContinue>::default(),
-            _ => return Err(format!("invalid rule name: {}", string)),
+            _ => {
+                return Err(match find_closest_rule_name(string) {
+                    Some(closest_name) => format!(
+                        "invalid rule name `{}`, did you mean `{}`?",
+                        string, closest_name
+                    ),
+                    None => format!("invalid rule name `{}`", string),
+                })
+            }
         };
 
         Ok(rule)
     }
 }
 
+fn serialize_rule<S: Serializer>(
+    rule_name: &str,
+    properties: RuleProperties,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let property_count = properties.len();
+
+    if property_count == 0 {
+        serializer.serialize_str(rule_name)
+    } else {
+        let mut map = serializer.serialize_map(Some(property_count + 1))?;
+
+        map.serialize_entry("rule", rule_name)?;
+
+        let mut ordered: Vec<(String, RulePropertyValue)> = properties.into_iter().collect();
+
+        ordered.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (key, value) in ordered {
+            map.serialize_entry(&key, &value)?;
+        }
+
+        map.end()
+    }
+}
+
 impl Serialize for dyn Rule {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let properties = self.serialize_to_properties();
-        let property_count = properties.len();
-        let rule_name = self.get_name();
+        serialize_rule(self.get_name(), self.serialize_to_properties(), serializer)
+    }
+}
 
-        if property_count == 0 {
-            serializer.serialize_str(rule_name)
-        } else {
-            let mut map = serializer.serialize_map(Some(property_count + 1))?;
+impl dyn Rule {
+    /// Serializes the rule with all its properties, including the ones that have their
+    /// default value. Unlike the [`Serialize`] implementation that only writes the properties
+    /// that differ from their default value, the output does not change if a default value
+    /// changes.
+    pub fn serialize_verbose<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_rule(
+            self.get_name(),
+            self.serialize_to_verbose_properties(),
+            serializer,
+        )
+    }
+}
 
-            map.serialize_entry("rule", rule_name)?;
+/// Two rules are equal when they have the same name and the same properties.
+impl PartialEq for dyn Rule {
+    fn eq(&self, other: &Self) -> bool {
+        self.get_name() == other.get_name()
+            && self.serialize_to_verbose_properties() == other.serialize_to_verbose_properties()
+    }
+}
 
-            let mut ordered: Vec<(String, RulePropertyValue)> = properties.into_iter().collect();
+/// Deserializes a rule from its name or from an object with its name and its properties.
+/// When `with_error_context` is `true`, errors contain the rule name and the path of the
+/// property that caused the error.
+struct StringOrStruct {
+    with_error_context: bool,
+}
 
-            ordered.sort_by(|a, b| a.0.cmp(&b.0));
+impl StringOrStruct {
+    fn configure_error(&self, err: RuleConfigurationError, rule_name: &str) -> String {
+        if !self.with_error_context {
+            return err.to_string();
+        }
 
-            for (key, value) in ordered {
-                map.serialize_entry(&key, &value)?;
-            }
+        let message = format!("{} in rule `{}`", err, rule_name);
 
-            map.end()
+        match err.property() {
+            Some(property) => with_error_path(message, property),
+            None => message,
         }
     }
-}
 
-impl<'de> Deserialize<'de> for Box<dyn Rule> {
-    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Box<dyn Rule>, D::Error> {
-        struct StringOrStruct;
+    fn property_error(&self, err: impl fmt::Display, rule_name: &str, property: &str) -> String {
+        if self.with_error_context {
+            with_error_path(format!("{} in rule `{}`", err, rule_name), property)
+        } else {
+            err.to_string()
+        }
+    }
+}
 
-        impl<'de> Visitor<'de> for StringOrStruct {
-            type Value = Box<dyn Rule>;
+impl<'de> Visitor<'de> for StringOrStruct {
+    type Value = Box<dyn Rule>;
 
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("rule name or rule object")
-            }
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("rule name or rule object")
+    }
 
-            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
-            where
-                E: de::Error,
-            {
-                let mut rule: Self::Value = FromStr::from_str(value).map_err(de::Error::custom)?;
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let mut rule: Self::Value = FromStr::from_str(value).map_err(de::Error::custom)?;
 
-                rule.configure(RuleProperties::new())
-                    .map_err(de::Error::custom)?;
+        rule.configure(RuleProperties::new())
+            .map_err(|err| de::Error::custom(self.configure_error(err, value)))?;
 
-                Ok(rule)
-            }
+        Ok(rule)
+    }
 
-            fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
-            where
-                M: MapAccess<'de>,
-            {
-                let mut rule_name = None;
-                let mut properties = HashMap::new();
-
-                while let Some(key) = map.next_key::<String>()? {
-                    match key.as_str() {
-                        "rule" => {
-                            if rule_name.is_none() {
-                                rule_name.replace(map.next_value::<String>()?);
-                            } else {
-                                return Err(de::Error::duplicate_field("rule"));
-                            }
-                        }
-                        property => {
-                            let value = map.next_value::<RulePropertyValue>()?;
+    fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
+    where
+        M: MapAccess<'de>,
+    {
+        let mut rule_name: Option<(String, String)> = None;
+        let mut properties = HashMap::new();
+        let mut property_errors = Vec::new();
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "rule" | "name" => {
+                    if rule_name.is_none() {
+                        rule_name.replace((map.next_value::<String>()?, key));
+                    } else {
+                        return Err(de::Error::duplicate_field("rule"));
+                    }
+                }
+                property => {
+                    // the value is gathered before being converted, so that its error can
+                    // be reported with the rule name that may come after the property
+                    let value = map.next_value::<serde_json::Value>()?;
 
+                    match RulePropertyValue::from_json_value(value) {
+                        Ok(value) => {
                             if properties.insert(property.to_owned(), value).is_some() {
                                 return Err(de::Error::custom(format!(
                                     "duplicate field {} in rule object",
@@ -383,26 +835,66 @@ impl<'de> Deserialize<'de> for Box<dyn Rule> {
                                 )));
                             }
                         }
+                        Err(err) => property_errors.push((property.to_owned(), err)),
                     }
                 }
+            }
+        }
 
-                if let Some(rule_name) = rule_name {
-                    let mut rule: Self::Value =
-                        FromStr::from_str(&rule_name).map_err(de::Error::custom)?;
-
-                    rule.configure(properties).map_err(de::Error::custom)?;
-
-                    Ok(rule)
+        if let Some((rule_name, rule_name_key)) = rule_name {
+            let mut rule: Self::Value = FromStr::from_str(&rule_name).map_err(|err| {
+                if self.with_error_context {
+                    de::Error::custom(with_error_path(err, &rule_name_key))
                 } else {
-                    Err(de::Error::missing_field("rule"))
+                    de::Error::custom(err)
                 }
+            })?;
+
+            if let Some((property, err)) = property_errors.into_iter().next() {
+                return Err(de::Error::custom(
+                    self.property_error(err, &rule_name, &property),
+                ));
             }
+
+            rule.configure(properties)
+                .map_err(|err| de::Error::custom(self.configure_error(err, &rule_name)))?;
+
+            Ok(rule)
+        } else {
+            Err(de::Error::missing_field("rule"))
         }
+    }
+}
+
+impl<'de> Deserialize<'de> for Box<dyn Rule> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Box<dyn Rule>, D::Error> {
+        deserializer.deserialize_any(StringOrStruct {
+            with_error_context: false,
+        })
+    }
+}
+
+struct RuleInList(Box<dyn Rule>);
 
-        deserializer.deserialize_any(StringOrStruct)
+impl<'de> Deserialize<'de> for RuleInList {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_any(StringOrStruct {
+                with_error_context: true,
+            })
+            .map(Self)
     }
 }
 
+/// Deserializes a list of rules. Errors contain the rule name and the path of the value
+/// that caused the error, like `rules[2].current`.
+pub(crate) fn deserialize_rules<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Box<dyn Rule>>, D::Error> {
+    deserialize_indexed_list(deserializer, "rules")
+        .map(|rules: Vec<RuleInList>| rules.into_iter().map(|RuleInList(rule)| rule).collect())
+}
+
 fn verify_no_rule_properties(properties: &RuleProperties) -> Result<(), RuleConfigurationError> {
     if let Some((key, _value)) = properties.iter().next() {
         return Err(RuleConfigurationError::UnexpectedProperty(key.to_owned()));
@@ -512,4 +1004,126 @@ mod test {
             assert!(json5::to_string(&rule).is_ok());
         }
     }
+
+    #[test]
+    fn verbose_serialization_round_trip_for_all_rules() {
+        let configured_rules = [
+            r#"{ rule: 'append_text_comment', text: 'hello', location: 'end' }"#,
+            "'cache_field_access'",
+            "'cache_services'",
+            "{ rule: 'compute_expression', dialect: 'lua51', max_literal_length: 20 }",
+            "'convert_index_to_field'",
+            "{ rule: 'convert_local_function_to_assign', recursive_functions: 'forward_declare' }",
+            "{ rule: 'convert_require', current: 'path', target: { name: 'roblox', rojo_sourcemap: 'sourcemap.json', indexing_style: 'wait_for_child' } }",
+            "'convert_to_compound_assignment'",
+            "'filter_after_early_return'",
+            "{ rule: 'flatten_control_flow', minimum_statements: 5, seed: 1 }",
+            "'group_local_assignment'",
+            "'hoist_requires'",
+            "{ rule: 'inject_decoy_code', seed: 4, density: 30, maximum_growth: 20 }",
+            "{ rule: 'inject_global_value', identifier: 'DEV', value: 1.5, as_local: true, bundle_scope: 'module' }",
+            "'inline_trivial_functions'",
+            "{ rule: 'normalize_unpack', target: 'lua52' }",
+            "{ rule: 'pool_strings', minimum_occurrences: 5, minimum_length: 30 }",
+            "{ rule: 'remove_assertions', preserve_arguments_side_effects: false }",
+            "{ rule: 'remove_comments', except: ['^!'], keep_first_comment: true }",
+            "'remove_compound_assignment'",
+            "{ rule: 'remove_debug_profiling', preserve_arguments_side_effects: false }",
+            "'remove_empty_do'",
+            "'remove_floor_division'",
+            "'remove_function_call_parens'",
+            "{ rule: 'remove_goto', strategy: 'repeat' }",
+            "{ rule: 'remove_interpolated_string', strategy: 'tostring' }",
+            "'remove_method_definition'",
+            "'remove_nil_declaration'",
+            "'remove_spaces'",
+            "{ rule: 'remove_test_blocks', functions: ['describe', 'it'] }",
+            "{ rule: 'remove_trailing_nil_arguments', keep_single_nil: true }",
+            "'remove_type_assertions'",
+            "'remove_type_export'",
+            "'remove_types'",
+            "{ rule: 'remove_unused_if_branch', preserve_comments: true }",
+            "'remove_unused_variable'",
+            "'remove_unused_while'",
+            "{ rule: 'rename_variables', globals: ['$default', 'custom'], include_functions: true }",
+            "{ rule: 'replace_calls', mapping: { 'table.getn': 'rawlen' }, replace_references: true, warn_only: true }",
+            "{ rule: 'simplify_nil_defaults', style: 'or', assume_no_false: true }",
+            "{ rule: 'simplify_stdlib_idioms', string_len: false, table_getn: true, math_pow: false, string_format: true }",
+            "{ rule: 'simplify_string_format', assume_strings: true }",
+            "{ rule: 'split_large_constructs', max_entries: 200, max_chunk_statements: 100 }",
+            "{ rule: 'unroll_loops', max_iterations: 3, max_body_statements: 2 }",
+            "{ rule: 'wrap_in_pcall', handler: 'Logger.error' }",
+            "'remove_if_expression'",
+            "'remove_continue'",
+            "{ rule: 'remove_attributes', only: ['native'] }",
+        ];
+
+        let rules: Vec<Box<dyn Rule>> = configured_rules
+            .iter()
+            .map(|content| {
+                json5::from_str(content)
+                    .unwrap_or_else(|err| panic!("unable to deserialize `{}`: {}", content, err))
+            })
+            .collect();
+
+        let covered_names: Vec<_> = rules.iter().map(|rule| rule.get_name()).collect();
+        pretty_assertions::assert_eq!(covered_names, get_all_rule_names());
+
+        for rule in rules {
+            let verbose = rule
+                .serialize_verbose(serde_json::value::Serializer)
+                .unwrap_or_else(|err| panic!("unable to serialize `{}`: {}", rule.get_name(), err));
+
+            let deserialized: Box<dyn Rule> = serde_json::from_value(verbose.clone())
+                .unwrap_or_else(|err| {
+                    panic!(
+                        "unable to deserialize `{}`: {}\n{}",
+                        rule.get_name(),
+                        err,
+                        verbose
+                    )
+                });
+
+            pretty_assertions::assert_eq!(&deserialized, &rule);
+            pretty_assertions::assert_eq!(
+                deserialized
+                    .serialize_verbose(serde_json::value::Serializer)
+                    .unwrap(),
+                verbose
+            );
+        }
+    }
+
+    #[test]
+    fn serialize_verbose_default_rule_includes_default_properties() {
+        let rule: Box<dyn Rule> = Box::<UnrollLoops>::default();
+
+        pretty_assertions::assert_eq!(
+            rule.serialize_verbose(serde_json::value::Serializer)
+                .unwrap(),
+            serde_json::json!({
+                "rule": "unroll_loops",
+                "max_body_statements": 4,
+                "max_iterations": 8,
+            })
+        );
+    }
+
+    #[test]
+    fn rules_with_different_properties_are_not_equal() {
+        let rule: Box<dyn Rule> = Box::<UnrollLoops>::default();
+        let other: Box<dyn Rule> =
+            json5::from_str("{ rule: 'unroll_loops', max_iterations: 3 }").unwrap();
+
+        assert_ne!(&rule, &other);
+    }
+
+    #[test]
+    fn default_rule_is_equal_to_rule_with_default_properties() {
+        let rule: Box<dyn Rule> = Box::<UnrollLoops>::default();
+        let other: Box<dyn Rule> =
+            json5::from_str("{ rule: 'unroll_loops', max_iterations: 8 }").unwrap();
+
+        assert_eq!(&rule, &other);
+    }
 }
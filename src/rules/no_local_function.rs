@@ -1,20 +1,86 @@
 use crate::nodes::{
-    Block, FunctionExpression, LocalAssignStatement, LocalFunctionStatement, Statement,
+    AssignStatement, Block, FunctionExpression, Identifier, LocalAssignStatement,
+    LocalFunctionStatement, Statement,
+};
+use crate::process::{
+    DefaultVisitor, IdentifierTracker, NodeProcessor, NodeVisitor, Scope, ScopeVisitor,
 };
-use crate::process::{processors::FindVariables, DefaultVisitor, NodeProcessor, NodeVisitor};
 use crate::rules::{
-    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+    Context, FlawlessRule, Rule, RuleConfiguration, RuleConfigurationError, RuleProperties,
 };
 
 use serde::ser::{Serialize, Serializer};
-use std::mem;
+use std::{mem, ops};
+
+/// Finds if a function refers to itself, ignoring the variables that shadow its name.
+struct RecursiveUsage<'a> {
+    name: &'a str,
+    identifier_tracker: IdentifierTracker,
+    found: bool,
+}
+
+impl<'a> RecursiveUsage<'a> {
+    fn find(local_function: &mut LocalFunctionStatement, name: &'a str) -> bool {
+        let mut usage = Self {
+            name,
+            identifier_tracker: IdentifierTracker::default(),
+            found: false,
+        };
+
+        usage.push();
+        for parameter in local_function.iter_parameters() {
+            usage.insert(&mut parameter.get_name().to_owned());
+        }
+        ScopeVisitor::visit_block(local_function.mutate_block(), &mut usage);
+        usage.pop();
+
+        usage.found
+    }
+}
 
-use super::verify_no_rule_properties;
+impl ops::Deref for RecursiveUsage<'_> {
+    type Target = IdentifierTracker;
 
-struct Processor;
+    fn deref(&self) -> &Self::Target {
+        &self.identifier_tracker
+    }
+}
+
+impl ops::DerefMut for RecursiveUsage<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.identifier_tracker
+    }
+}
+
+impl NodeProcessor for RecursiveUsage<'_> {
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        if identifier.get_name() == self.name && !self.is_identifier_used(self.name) {
+            self.found = true;
+        }
+    }
+}
+
+/// Defines how local functions that refer to themselves are converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RecursiveFunctions {
+    /// Keep recursive functions as local function statements.
+    Skip,
+    /// Declare the local variable before assigning the function to it.
+    ForwardDeclare,
+}
+
+impl Default for RecursiveFunctions {
+    fn default() -> Self {
+        Self::Skip
+    }
+}
+
+struct Processor {
+    recursive_functions: RecursiveFunctions,
+}
 
 impl Processor {
-    fn convert(&self, local_function: &mut LocalFunctionStatement) -> Statement {
+    fn convert(&self, local_function: &mut LocalFunctionStatement) -> FunctionExpression {
         let mut function_expression = FunctionExpression::default();
         function_expression.set_variadic(local_function.is_variadic());
         mem::swap(
@@ -25,31 +91,54 @@ impl Processor {
             function_expression.mutate_parameters(),
             local_function.mutate_parameters(),
         );
+        mem::swap(
+            function_expression.mutate_attributes(),
+            local_function.mutate_attributes(),
+        );
 
-        LocalAssignStatement::from_variable(local_function.get_name())
-            .with_value(function_expression)
-            .into()
+        function_expression
     }
 }
 
 impl NodeProcessor for Processor {
-    fn process_statement(&mut self, statement: &mut Statement) {
-        if let Statement::LocalFunction(local_function) = statement {
-            let name = local_function.get_name().to_owned();
-
-            if local_function.has_parameter(&name) {
-                let mut assign = self.convert(local_function);
-                mem::swap(statement, &mut assign)
-            } else {
-                let mut find_usage = FindVariables::new(&name);
-                DefaultVisitor::visit_block(local_function.mutate_block(), &mut find_usage);
-
-                if !find_usage.has_found_usage() {
-                    let mut assign = self.convert(local_function);
-                    mem::swap(statement, &mut assign)
+    fn process_block(&mut self, block: &mut Block) {
+        let has_local_functions = block
+            .iter_statements()
+            .any(|statement| matches!(statement, Statement::LocalFunction(_)));
+
+        if !has_local_functions {
+            return;
+        }
+
+        let mut statements = Vec::new();
+
+        for mut statement in block.take_statements() {
+            if let Statement::LocalFunction(local_function) = &mut statement {
+                let name = local_function.get_name().to_owned();
+
+                if !RecursiveUsage::find(local_function, &name) {
+                    statements.push(
+                        LocalAssignStatement::from_variable(name)
+                            .with_value(self.convert(local_function))
+                            .into(),
+                    );
+                    continue;
+                }
+
+                if self.recursive_functions == RecursiveFunctions::ForwardDeclare {
+                    let function = self.convert(local_function);
+                    statements.push(LocalAssignStatement::from_variable(name.clone()).into());
+                    statements.push(
+                        AssignStatement::from_variable(Identifier::new(name), function).into(),
+                    );
+                    continue;
                 }
             }
-        };
+
+            statements.push(statement);
+        }
+
+        block.set_statements(statements);
     }
 }
 
@@ -57,18 +146,50 @@ pub const CONVERT_LOCAL_FUNCTION_TO_ASSIGN_RULE_NAME: &str = "convert_local_func
 
 /// Convert local function statements into local assignements when the function is not recursive.
 #[derive(Debug, Default, PartialEq, Eq)]
-pub struct ConvertLocalFunctionToAssign {}
+pub struct ConvertLocalFunctionToAssign {
+    recursive_functions: RecursiveFunctions,
+}
+
+impl ConvertLocalFunctionToAssign {
+    /// Convert recursive functions by declaring the local variable first, and then
+    /// assigning the function to it (`local f; f = function() ... end`).
+    pub fn forward_declare_recursive_functions(mut self) -> Self {
+        self.recursive_functions = RecursiveFunctions::ForwardDeclare;
+        self
+    }
+}
 
 impl FlawlessRule for ConvertLocalFunctionToAssign {
     fn flawless_process(&self, block: &mut Block, _: &Context) {
-        let mut processor = Processor;
+        let mut processor = Processor {
+            recursive_functions: self.recursive_functions,
+        };
         DefaultVisitor::visit_block(block, &mut processor);
     }
 }
 
 impl RuleConfiguration for ConvertLocalFunctionToAssign {
     fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
-        verify_no_rule_properties(&properties)?;
+        for (key, value) in properties {
+            match key.as_str() {
+                "recursive_functions" => {
+                    self.recursive_functions = match value.expect_string(&key)?.as_str() {
+                        "skip" => RecursiveFunctions::Skip,
+                        "forward_declare" => RecursiveFunctions::ForwardDeclare,
+                        unexpected => {
+                            return Err(RuleConfigurationError::UnexpectedValue {
+                                property: "recursive_functions".to_owned(),
+                                message: format!(
+                                    "invalid value `{}` (must be `skip` or `forward_declare`)",
+                                    unexpected
+                                ),
+                            })
+                        }
+                    };
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
 
         Ok(())
     }
@@ -78,13 +199,31 @@ impl RuleConfiguration for ConvertLocalFunctionToAssign {
     }
 
     fn serialize_to_properties(&self) -> RuleProperties {
-        RuleProperties::new()
+        let mut properties = RuleProperties::new();
+
+        if self.recursive_functions == RecursiveFunctions::ForwardDeclare {
+            properties.insert("recursive_functions".to_owned(), "forward_declare".into());
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        let recursive_functions = match self.recursive_functions {
+            RecursiveFunctions::Skip => "skip",
+            RecursiveFunctions::ForwardDeclare => "forward_declare",
+        };
+        properties.insert("recursive_functions".to_owned(), recursive_functions.into());
+
+        properties
     }
 }
 
 impl Serialize for ConvertLocalFunctionToAssign {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        serializer.serialize_str(CONVERT_LOCAL_FUNCTION_TO_ASSIGN_RULE_NAME)
+        (self as &dyn Rule).serialize(serializer)
     }
 }
 
@@ -92,7 +231,8 @@ impl Serialize for ConvertLocalFunctionToAssign {
 mod test {
     use super::*;
 
-    use crate::rules::Rule;
+    use crate::rules::{ContextBuilder, Rule};
+    use crate::{Parser, Resources};
 
     use insta::assert_json_snapshot;
 
@@ -105,6 +245,28 @@ mod test {
         assert_json_snapshot!("default_convert_local_function_to_assign", new_rule());
     }
 
+    #[test]
+    fn serialize_rule_forward_declaring_recursive_functions() {
+        assert_json_snapshot!(
+            "convert_local_function_to_assign_forward_declaring_recursive_functions",
+            new_rule().forward_declare_recursive_functions()
+        );
+    }
+
+    #[test]
+    fn configure_with_invalid_recursive_functions_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'convert_local_function_to_assign',
+            recursive_functions: 'inline',
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "unexpected value for field 'recursive_functions': invalid value `inline` (must be `skip` or `forward_declare`)"
+        );
+    }
+
     #[test]
     fn configure_with_extra_field_error() {
         let result = json5::from_str::<Box<dyn Rule>>(
@@ -115,4 +277,22 @@ mod test {
         );
         pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
     }
+
+    #[test]
+    fn keeps_function_attributes() {
+        let code = "@native local function foo() end";
+        let mut block = Parser::default().parse(code).unwrap();
+
+        new_rule().flawless_process(
+            &mut block,
+            &ContextBuilder::new(".", &Resources::from_memory(), code).build(),
+        );
+
+        let expected_function = FunctionExpression::default().with_attribute("native");
+        let expected: Block = LocalAssignStatement::from_variable("foo")
+            .with_value(expected_function)
+            .into();
+
+        pretty_assertions::assert_eq!(block, expected);
+    }
 }
@@ -0,0 +1,303 @@
+use std::ops;
+
+use crate::nodes::{
+    BinaryExpression, BinaryOperator, Block, Expression, FieldExpression, Identifier,
+    LocalAssignStatement, Prefix,
+};
+use crate::process::{IdentifierTracker, NodeProcessor, NodeVisitor, ScopeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+const UNPACK_IDENTIFIER: &str = "unpack";
+const TABLE_LIBRARY: &str = "table";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UnpackTarget {
+    /// Lua 5.1 and older Luau versions, where `unpack` is a global
+    Lua51,
+    /// Lua 5.2 and newer, where `unpack` is only available from the table library
+    Lua52,
+    /// Use a local `unpack` variable defined with whichever function is available
+    Compat,
+}
+
+impl Default for UnpackTarget {
+    fn default() -> Self {
+        Self::Compat
+    }
+}
+
+enum UnpackReference {
+    Global,
+    TableField,
+}
+
+struct NormalizeUnpackProcessor {
+    identifier_tracker: IdentifierTracker,
+    target: UnpackTarget,
+    uses_global_unpack: bool,
+}
+
+impl ops::Deref for NormalizeUnpackProcessor {
+    type Target = IdentifierTracker;
+
+    fn deref(&self) -> &Self::Target {
+        &self.identifier_tracker
+    }
+}
+
+impl ops::DerefMut for NormalizeUnpackProcessor {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.identifier_tracker
+    }
+}
+
+impl NormalizeUnpackProcessor {
+    fn new(target: UnpackTarget) -> Self {
+        Self {
+            identifier_tracker: IdentifierTracker::new(),
+            target,
+            uses_global_unpack: false,
+        }
+    }
+
+    fn is_global_table_field(&self, field: &FieldExpression) -> bool {
+        field.get_field().get_name() == UNPACK_IDENTIFIER
+            && matches!(
+                field.get_prefix(),
+                Prefix::Identifier(prefix) if prefix.get_name() == TABLE_LIBRARY
+            )
+            && !self.is_identifier_used(TABLE_LIBRARY)
+    }
+
+    fn is_global_unpack(&self, identifier: &Identifier) -> bool {
+        identifier.get_name() == UNPACK_IDENTIFIER && !self.is_identifier_used(UNPACK_IDENTIFIER)
+    }
+
+    /// Returns the reference that should replace the current one, if any.
+    fn replace(&mut self, reference: UnpackReference) -> Option<UnpackReference> {
+        match (self.target, reference) {
+            (UnpackTarget::Lua52, UnpackReference::Global) => {
+                if self.is_identifier_used(TABLE_LIBRARY) {
+                    None
+                } else {
+                    Some(UnpackReference::TableField)
+                }
+            }
+            (UnpackTarget::Lua51, UnpackReference::TableField)
+            | (UnpackTarget::Compat, UnpackReference::TableField) => {
+                if self.is_identifier_used(UNPACK_IDENTIFIER) {
+                    None
+                } else {
+                    self.uses_global_unpack = true;
+                    Some(UnpackReference::Global)
+                }
+            }
+            (_, UnpackReference::Global) => {
+                self.uses_global_unpack = true;
+                None
+            }
+            (UnpackTarget::Lua52, UnpackReference::TableField) => None,
+        }
+    }
+
+    fn find_reference_in_prefix(&self, prefix: &Prefix) -> Option<UnpackReference> {
+        match prefix {
+            Prefix::Identifier(identifier) if self.is_global_unpack(identifier) => {
+                Some(UnpackReference::Global)
+            }
+            Prefix::Field(field) if self.is_global_table_field(field) => {
+                Some(UnpackReference::TableField)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn table_unpack_field() -> FieldExpression {
+    FieldExpression::new(Prefix::from_name(TABLE_LIBRARY), UNPACK_IDENTIFIER)
+}
+
+impl NodeProcessor for NormalizeUnpackProcessor {
+    fn process_expression(&mut self, expression: &mut Expression) {
+        let reference = match expression {
+            Expression::Identifier(identifier) if self.is_global_unpack(identifier) => {
+                UnpackReference::Global
+            }
+            Expression::Field(field) if self.is_global_table_field(field) => {
+                UnpackReference::TableField
+            }
+            _ => return,
+        };
+
+        match self.replace(reference) {
+            Some(UnpackReference::Global) => {
+                *expression = Identifier::new(UNPACK_IDENTIFIER).into();
+            }
+            Some(UnpackReference::TableField) => {
+                *expression = table_unpack_field().into();
+            }
+            None => {}
+        }
+    }
+
+    fn process_prefix_expression(&mut self, prefix: &mut Prefix) {
+        let reference = match self.find_reference_in_prefix(prefix) {
+            Some(reference) => reference,
+            None => return,
+        };
+
+        match self.replace(reference) {
+            Some(UnpackReference::Global) => {
+                *prefix = Prefix::from_name(UNPACK_IDENTIFIER);
+            }
+            Some(UnpackReference::TableField) => {
+                *prefix = table_unpack_field().into();
+            }
+            None => {}
+        }
+    }
+}
+
+pub const NORMALIZE_UNPACK_RULE_NAME: &str = "normalize_unpack";
+
+/// A rule that converts references to `unpack` and `table.unpack` for a given Lua version.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct NormalizeUnpack {
+    target: UnpackTarget,
+}
+
+impl FlawlessRule for NormalizeUnpack {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut processor = NormalizeUnpackProcessor::new(self.target);
+        ScopeVisitor::visit_block(block, &mut processor);
+
+        if self.target == UnpackTarget::Compat && processor.uses_global_unpack {
+            block.insert_statement(
+                0,
+                LocalAssignStatement::from_variable(UNPACK_IDENTIFIER).with_value(
+                    BinaryExpression::new(
+                        BinaryOperator::Or,
+                        table_unpack_field(),
+                        Identifier::new(UNPACK_IDENTIFIER),
+                    ),
+                ),
+            );
+        }
+    }
+}
+
+impl RuleConfiguration for NormalizeUnpack {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                "target" => {
+                    self.target = match value.expect_string(&key)?.as_str() {
+                        "lua51" => UnpackTarget::Lua51,
+                        "lua52" => UnpackTarget::Lua52,
+                        "compat" => UnpackTarget::Compat,
+                        unexpected => {
+                            return Err(RuleConfigurationError::UnexpectedValue {
+                                property: "target".to_owned(),
+                                message: format!(
+                                    "invalid value `{}` (must be `lua51`, `lua52` or `compat`)",
+                                    unexpected
+                                ),
+                            })
+                        }
+                    };
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        NORMALIZE_UNPACK_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        match self.target {
+            UnpackTarget::Compat => {}
+            UnpackTarget::Lua51 => {
+                properties.insert("target".to_owned(), "lua51".into());
+            }
+            UnpackTarget::Lua52 => {
+                properties.insert("target".to_owned(), "lua52".into());
+            }
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        let target = match self.target {
+            UnpackTarget::Compat => "compat",
+            UnpackTarget::Lua51 => "lua51",
+            UnpackTarget::Lua52 => "lua52",
+        };
+        properties.insert("target".to_owned(), target.into());
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> NormalizeUnpack {
+        NormalizeUnpack::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_normalize_unpack", rule);
+    }
+
+    #[test]
+    fn serialize_rule_with_lua52_target() {
+        let rule: Box<dyn Rule> = Box::new(NormalizeUnpack {
+            target: UnpackTarget::Lua52,
+        });
+
+        assert_json_snapshot!("normalize_unpack_lua52_target", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'normalize_unpack',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn configure_with_invalid_target_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'normalize_unpack',
+            target: 'lua54',
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "unexpected value for field 'target': invalid value `lua54` (must be `lua51`, `lua52` or `compat`)"
+        );
+    }
+}
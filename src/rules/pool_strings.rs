@@ -0,0 +1,511 @@
+use std::collections::{HashMap, HashSet};
+use std::mem;
+
+use crate::nodes::{
+    Arguments, Block, Expression, FunctionCall, FunctionExpression, FunctionStatement, Identifier,
+    LocalAssignStatement, LocalFunctionStatement, Prefix, Statement, StringExpression,
+    TupleArguments, TypedIdentifier,
+};
+use crate::process::utils::collect_identifiers;
+use crate::process::{
+    DefaultPostVisitor, IdentifierTracker, NodePostProcessor, NodePostVisitor, NodeProcessor,
+    NodeVisitor, Scope, ScopeVisitor,
+};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+use super::lua_limits::{count_active_locals, MAX_LOCALS, MAX_UPVALUES};
+
+const POOLED_STRING_PREFIX: &str = "__DARKLUA_STR";
+const REQUIRE_FUNCTION_IDENTIFIER: &str = "require";
+const MAX_POOLED_STRINGS: usize = 50;
+
+fn is_require_call(call: &FunctionCall) -> bool {
+    call.get_method().is_none()
+        && matches!(
+            call.get_prefix(),
+            Prefix::Identifier(identifier) if identifier.get_name() == REQUIRE_FUNCTION_IDENTIFIER
+        )
+}
+
+/// Counts the string literals that can be replaced with an identifier, in the order
+/// where they first appear.
+#[derive(Debug, Default)]
+struct StringCounter {
+    strings: Vec<(String, usize)>,
+    indexes: HashMap<String, usize>,
+    require_depth: usize,
+}
+
+impl StringCounter {
+    fn count(&mut self, value: &str) {
+        if self.require_depth != 0 {
+            return;
+        }
+        if let Some(index) = self.indexes.get(value) {
+            self.strings[*index].1 += 1;
+        } else {
+            self.indexes.insert(value.to_owned(), self.strings.len());
+            self.strings.push((value.to_owned(), 1));
+        }
+    }
+}
+
+impl NodeProcessor for StringCounter {
+    fn process_function_call(&mut self, call: &mut FunctionCall) {
+        if is_require_call(call) {
+            self.require_depth += 1;
+        } else if let Arguments::String(string) = call.get_arguments() {
+            self.count(string.get_value());
+        }
+    }
+
+    fn process_expression(&mut self, expression: &mut Expression) {
+        if let Expression::String(string) = expression {
+            self.count(string.get_value());
+        }
+    }
+}
+
+impl NodePostProcessor for StringCounter {
+    fn process_after_function_call(&mut self, call: &mut FunctionCall) {
+        if is_require_call(call) {
+            self.require_depth -= 1;
+        }
+    }
+}
+
+/// The upvalues of a function and the strings that it uses, including the strings used by
+/// its nested functions. Each of these strings that gets pooled is one more upvalue of the
+/// function.
+#[derive(Debug, Default)]
+struct FunctionUpvalues {
+    upvalues: HashSet<String>,
+    strings: HashSet<String>,
+    pooled_strings: usize,
+}
+
+impl FunctionUpvalues {
+    fn can_use_pooled_string(&self) -> bool {
+        self.upvalues.len() + self.pooled_strings < MAX_UPVALUES
+    }
+}
+
+/// Collects the upvalues and the strings of each function.
+#[derive(Debug, Default)]
+struct UpvalueCounter {
+    identifier_tracker: IdentifierTracker,
+    /// The functions being visited, with the scope depth of their parameters.
+    function_stack: Vec<(usize, FunctionUpvalues)>,
+    functions: Vec<FunctionUpvalues>,
+    entering_function: bool,
+    skip_require: bool,
+}
+
+impl UpvalueCounter {
+    fn use_string(&mut self, value: &str) {
+        for (_, function) in self.function_stack.iter_mut() {
+            function.strings.insert(value.to_owned());
+        }
+    }
+}
+
+impl Scope for UpvalueCounter {
+    fn push(&mut self) {
+        self.identifier_tracker.push();
+        // the parameters of a function are inserted in the scope pushed before its block
+        if mem::take(&mut self.entering_function) {
+            self.function_stack.push((
+                self.identifier_tracker.scope_depth(),
+                FunctionUpvalues::default(),
+            ));
+        }
+    }
+
+    fn pop(&mut self) {
+        let depth = self.identifier_tracker.scope_depth();
+        let leaves_function = self
+            .function_stack
+            .last()
+            .map_or(false, |(function_depth, _)| *function_depth == depth);
+        if leaves_function {
+            if let Some((_, function)) = self.function_stack.pop() {
+                self.functions.push(function);
+            }
+        }
+        self.identifier_tracker.pop();
+    }
+
+    fn insert(&mut self, identifier: &mut String) {
+        self.identifier_tracker.insert(identifier);
+    }
+
+    fn insert_self(&mut self) {
+        self.identifier_tracker.insert_self();
+    }
+
+    fn insert_local(&mut self, identifier: &mut String, value: Option<&mut Expression>) {
+        self.identifier_tracker.insert_local(identifier, value);
+    }
+
+    fn insert_local_function(&mut self, function: &mut LocalFunctionStatement) {
+        self.identifier_tracker.insert_local_function(function);
+    }
+}
+
+impl NodeProcessor for UpvalueCounter {
+    fn skip_children(&mut self) -> bool {
+        mem::take(&mut self.skip_require)
+    }
+
+    fn process_statement(&mut self, statement: &mut Statement) {
+        if let Statement::Call(call) = statement {
+            self.skip_require = is_require_call(call);
+        }
+    }
+
+    fn process_function_expression(&mut self, _: &mut FunctionExpression) {
+        self.entering_function = true;
+    }
+
+    fn process_function_statement(&mut self, _: &mut FunctionStatement) {
+        self.entering_function = true;
+    }
+
+    fn process_local_function_statement(&mut self, _: &mut LocalFunctionStatement) {
+        self.entering_function = true;
+    }
+
+    fn process_function_call(&mut self, call: &mut FunctionCall) {
+        if is_require_call(call) {
+            return;
+        }
+        if let Arguments::String(string) = call.get_arguments() {
+            self.use_string(string.get_value());
+        }
+    }
+
+    fn process_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::String(string) => self.use_string(string.get_value()),
+            Expression::Call(call) => self.skip_require = is_require_call(call),
+            _ => {}
+        }
+    }
+
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        let name = identifier.get_name();
+        if let Some(depth) = self.identifier_tracker.get_identifier_depth(name) {
+            for (_, function) in self
+                .function_stack
+                .iter_mut()
+                .rev()
+                .take_while(|(function_depth, _)| *function_depth > depth)
+            {
+                function.upvalues.insert(name.to_owned());
+            }
+        }
+    }
+}
+
+/// Replaces the pooled strings with the identifier of their local variable.
+#[derive(Debug)]
+struct StringReplacer {
+    identifiers: HashMap<String, String>,
+    require_depth: usize,
+}
+
+impl StringReplacer {
+    fn get_identifier(&self, value: &str) -> Option<Identifier> {
+        if self.require_depth != 0 {
+            return None;
+        }
+        self.identifiers
+            .get(value)
+            .map(|identifier| Identifier::new(identifier.as_str()))
+    }
+}
+
+impl NodeProcessor for StringReplacer {
+    fn process_function_call(&mut self, call: &mut FunctionCall) {
+        if is_require_call(call) {
+            self.require_depth += 1;
+            return;
+        }
+
+        let identifier = match call.get_arguments() {
+            Arguments::String(string) => self.get_identifier(string.get_value()),
+            _ => None,
+        };
+
+        if let Some(identifier) = identifier {
+            call.set_arguments(TupleArguments::default().with_argument(identifier).into());
+        }
+    }
+
+    fn process_expression(&mut self, expression: &mut Expression) {
+        let identifier = match expression {
+            Expression::String(string) => self.get_identifier(string.get_value()),
+            _ => None,
+        };
+
+        if let Some(identifier) = identifier {
+            *expression = identifier.into();
+        }
+    }
+}
+
+impl NodePostProcessor for StringReplacer {
+    fn process_after_function_call(&mut self, call: &mut FunctionCall) {
+        if is_require_call(call) {
+            self.require_depth -= 1;
+        }
+    }
+}
+
+/// Returns the first generated identifier, starting from the given index, that is not
+/// already used in the code. The index is updated to the index of the returned identifier.
+fn find_free_identifier(index: &mut usize, used_identifiers: &HashSet<String>) -> String {
+    loop {
+        let identifier = format!("{}{}", POOLED_STRING_PREFIX, index);
+        if !used_identifiers.contains(&identifier) {
+            break identifier;
+        }
+        *index += 1;
+    }
+}
+
+/// Returns `true` if replacing each occurrence of a string with an identifier, and
+/// declaring that identifier, makes the code shorter.
+fn reduces_size(value: &str, occurrences: usize, identifier: &str) -> bool {
+    // the length of the string when written between quotes
+    let literal_length = value.len() + 2;
+    // the declaration is written like `local <identifier>=<literal>`, but the pooled
+    // strings are declared in the same statement, so only a comma is added for each one
+    let declaration_length = identifier.len() + literal_length + 2;
+
+    literal_length.saturating_sub(identifier.len()) * occurrences > declaration_length
+}
+
+pub const POOL_STRINGS_RULE_NAME: &str = "pool_strings";
+
+const DEFAULT_MINIMUM_OCCURRENCES: usize = 3;
+const DEFAULT_MINIMUM_LENGTH: usize = 16;
+
+/// A rule that moves string literals repeated in a file into local variables declared at
+/// the top of the file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PoolStrings {
+    minimum_occurrences: usize,
+    minimum_length: usize,
+}
+
+impl Default for PoolStrings {
+    fn default() -> Self {
+        Self {
+            minimum_occurrences: DEFAULT_MINIMUM_OCCURRENCES,
+            minimum_length: DEFAULT_MINIMUM_LENGTH,
+        }
+    }
+}
+
+impl PoolStrings {
+    pub fn with_minimum_occurrences(mut self, occurrences: usize) -> Self {
+        self.minimum_occurrences = occurrences;
+        self
+    }
+
+    pub fn with_minimum_length(mut self, length: usize) -> Self {
+        self.minimum_length = length;
+        self
+    }
+}
+
+impl FlawlessRule for PoolStrings {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut counter = StringCounter::default();
+        DefaultPostVisitor::visit_block(block, &mut counter);
+
+        let mut upvalue_counter = UpvalueCounter::default();
+        ScopeVisitor::visit_block(block, &mut upvalue_counter);
+        let mut functions = upvalue_counter.functions;
+
+        let used_identifiers = collect_identifiers(block);
+        // the pooled strings are declared at the top of the chunk, so they are in scope
+        // with every other local of the chunk
+        let max_pooled_strings =
+            MAX_POOLED_STRINGS.min(MAX_LOCALS.saturating_sub(count_active_locals(block)));
+
+        let mut next_index = 1;
+        let mut identifiers = HashMap::new();
+        let mut variables = Vec::new();
+        let mut values = Vec::new();
+
+        for (value, occurrences) in counter.strings {
+            if variables.len() >= max_pooled_strings {
+                break;
+            }
+            if occurrences < self.minimum_occurrences || value.len() < self.minimum_length {
+                continue;
+            }
+
+            let identifier = find_free_identifier(&mut next_index, &used_identifiers);
+
+            if !reduces_size(&value, occurrences, &identifier) {
+                continue;
+            }
+
+            // the functions that use the string get one more upvalue once it is pooled
+            let mut using_functions: Vec<_> = functions
+                .iter_mut()
+                .filter(|function| function.strings.contains(&value))
+                .collect();
+            if !using_functions
+                .iter()
+                .all(|function| function.can_use_pooled_string())
+            {
+                continue;
+            }
+            for function in using_functions.iter_mut() {
+                function.pooled_strings += 1;
+            }
+            next_index += 1;
+
+            variables.push(TypedIdentifier::new(identifier.as_str()));
+            values.push(StringExpression::from_value(value.clone()).into());
+            identifiers.insert(value, identifier);
+        }
+
+        if identifiers.is_empty() {
+            return;
+        }
+
+        let mut replacer = StringReplacer {
+            identifiers,
+            require_depth: 0,
+        };
+        DefaultPostVisitor::visit_block(block, &mut replacer);
+
+        // the shebang and the directive comments (like `--!strict`) stay at the top
+        let mut declaration: Statement = LocalAssignStatement::new(variables, values).into();
+        declaration.prepend_leading_header(block.take_leading_header());
+
+        block.insert_statement(0, declaration);
+    }
+}
+
+impl RuleConfiguration for PoolStrings {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                "minimum_occurrences" => {
+                    self.minimum_occurrences = value.expect_usize(&key)?;
+                }
+                "minimum_length" => {
+                    self.minimum_length = value.expect_usize(&key)?;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        POOL_STRINGS_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        if self.minimum_occurrences != DEFAULT_MINIMUM_OCCURRENCES {
+            properties.insert(
+                "minimum_occurrences".to_owned(),
+                self.minimum_occurrences.into(),
+            );
+        }
+        if self.minimum_length != DEFAULT_MINIMUM_LENGTH {
+            properties.insert("minimum_length".to_owned(), self.minimum_length.into());
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert(
+            "minimum_occurrences".to_owned(),
+            self.minimum_occurrences.into(),
+        );
+        properties.insert("minimum_length".to_owned(), self.minimum_length.into());
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> PoolStrings {
+        PoolStrings::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_pool_strings", rule);
+    }
+
+    #[test]
+    fn serialize_rule_with_thresholds() {
+        let rule: Box<dyn Rule> = Box::new(
+            new_rule()
+                .with_minimum_occurrences(5)
+                .with_minimum_length(30),
+        );
+
+        assert_json_snapshot!("pool_strings_with_thresholds", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'pool_strings',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn configure_with_invalid_minimum_length_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'pool_strings',
+            minimum_length: "long",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "unsigned integer expected for field 'minimum_length'"
+        );
+    }
+
+    #[test]
+    fn short_string_repeated_twice_does_not_reduce_size() {
+        assert!(!reduces_size("abc", 2, "__DARKLUA_STR1"));
+    }
+
+    #[test]
+    fn long_string_repeated_three_times_reduces_size() {
+        assert!(reduces_size(&"a".repeat(40), 3, "__DARKLUA_STR1"));
+    }
+}
@@ -8,6 +8,7 @@ use crate::rules::{
 };
 
 use super::remove_call_match::{CallMatch, RemoveFunctionCallProcessor};
+use super::verify_property_collisions;
 
 const ASSERT_FUNCTION_NAME: &str = "assert";
 
@@ -86,9 +87,14 @@ impl FlawlessRule for RemoveAssertions {
 
 impl RuleConfiguration for RemoveAssertions {
     fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        verify_property_collisions(
+            &properties,
+            &["preserve_arguments_side_effects", "preserve_side_effects"],
+        )?;
+
         for (key, value) in properties {
             match key.as_str() {
-                "preserve_arguments_side_effects" => {
+                "preserve_arguments_side_effects" | "preserve_side_effects" => {
                     self.preserve_args_side_effects = value.expect_bool(&key)?;
                 }
                 _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
@@ -111,6 +117,17 @@ impl RuleConfiguration for RemoveAssertions {
 
         properties
     }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert(
+            "preserve_arguments_side_effects".to_owned(),
+            self.preserve_args_side_effects.into(),
+        );
+
+        properties
+    }
 }
 
 #[cfg(test)]
@@ -150,4 +167,19 @@ mod test {
         );
         pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
     }
+
+    #[test]
+    fn configure_with_alias_collision_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'remove_assertions',
+            preserve_arguments_side_effects: true,
+            preserve_side_effects: false,
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "the fields `preserve_arguments_side_effects` and `preserve_side_effects` cannot be defined together"
+        );
+    }
 }
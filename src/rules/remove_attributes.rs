@@ -0,0 +1,131 @@
+use crate::nodes::{
+    Attribute, Block, FunctionExpression, FunctionStatement, LocalFunctionStatement,
+};
+use crate::process::{DefaultVisitor, NodeProcessor, NodeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+    RulePropertyValue,
+};
+
+struct Processor<'a> {
+    only: &'a [String],
+}
+
+impl<'a> Processor<'a> {
+    fn new(only: &'a [String]) -> Self {
+        Self { only }
+    }
+
+    fn filter(&self, attributes: &mut Vec<Attribute>) {
+        if self.only.is_empty() {
+            attributes.clear();
+        } else {
+            attributes.retain(|attribute| {
+                let name = attribute.get_name().get_name();
+                !self.only.iter().any(|removed| removed == name)
+            });
+        }
+    }
+}
+
+impl NodeProcessor for Processor<'_> {
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        self.filter(function.mutate_attributes());
+    }
+
+    fn process_local_function_statement(&mut self, function: &mut LocalFunctionStatement) {
+        self.filter(function.mutate_attributes());
+    }
+
+    fn process_function_expression(&mut self, function: &mut FunctionExpression) {
+        self.filter(function.mutate_attributes());
+    }
+}
+
+pub const REMOVE_ATTRIBUTES_RULE_NAME: &str = "remove_attributes";
+
+/// A rule that removes Luau attributes (like `@native`) from functions.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RemoveAttributes {
+    only: Vec<String>,
+}
+
+impl FlawlessRule for RemoveAttributes {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut processor = Processor::new(&self.only);
+        DefaultVisitor::visit_block(block, &mut processor);
+    }
+}
+
+impl RuleConfiguration for RemoveAttributes {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                "only" => {
+                    self.only = value.expect_string_list(&key)?;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        REMOVE_ATTRIBUTES_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        if !self.only.is_empty() {
+            properties.insert(
+                "only".to_owned(),
+                RulePropertyValue::StringList(self.only.clone()),
+            );
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert(
+            "only".to_owned(),
+            RulePropertyValue::StringList(self.only.clone()),
+        );
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> RemoveAttributes {
+        RemoveAttributes::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_remove_attributes", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'remove_attributes',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+}
@@ -1,11 +1,16 @@
 use regex::Regex;
 
+use std::{cell::RefCell, mem};
+
 use crate::nodes::*;
 use crate::process::{DefaultVisitor, NodeProcessor, NodeVisitor};
 use crate::rules::{
     Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+    RulePropertyValue,
 };
 
+use super::verify_property_collisions;
+
 #[derive(Debug, Default)]
 pub(crate) struct RemoveCommentProcessor {}
 
@@ -39,10 +44,18 @@ impl NodeProcessor for RemoveCommentProcessor {
         generic_for.clear_comments();
     }
 
+    fn process_goto_statement(&mut self, goto: &mut GotoStatement) {
+        goto.clear_comments();
+    }
+
     fn process_if_statement(&mut self, if_statement: &mut IfStatement) {
         if_statement.clear_comments();
     }
 
+    fn process_label_statement(&mut self, label: &mut LabelStatement) {
+        label.clear_comments();
+    }
+
     fn process_last_statement(&mut self, statement: &mut LastStatement) {
         match statement {
             LastStatement::Break(token) | LastStatement::Continue(token) => {
@@ -234,6 +247,8 @@ impl NodeProcessor for RemoveCommentProcessor {
 pub(crate) struct FilterCommentProcessor<'a> {
     original_code: &'a str,
     except: &'a Vec<Regex>,
+    keep_before: usize,
+    removed_comments: Option<RefCell<Vec<(usize, String)>>>,
 }
 
 impl<'a> FilterCommentProcessor<'a> {
@@ -241,15 +256,116 @@ impl<'a> FilterCommentProcessor<'a> {
         Self {
             original_code,
             except,
+            keep_before: 0,
+            removed_comments: None,
         }
     }
 
+    /// Records the text of each removed comment, so that it can be read with
+    /// `take_removed_comments`.
+    pub(crate) fn record_removed_comments(mut self) -> Self {
+        self.removed_comments = Some(RefCell::new(Vec::new()));
+        self
+    }
+
+    /// Returns the text of the removed comments, in the order they appear in the original
+    /// code. Comments without a position are placed last.
+    pub(crate) fn take_removed_comments(&mut self) -> Vec<String> {
+        let mut comments = self
+            .removed_comments
+            .as_mut()
+            .map(|comments| mem::take(comments.get_mut()))
+            .unwrap_or_default();
+
+        comments.sort_by_key(|(offset, _)| *offset);
+
+        comments.into_iter().map(|(_, comment)| comment).collect()
+    }
+
+    /// Keeps the comments that start before the given byte offset of the original code.
+    pub(crate) fn keep_comments_before(mut self, offset: usize) -> Self {
+        self.keep_before = offset;
+        self
+    }
+
     fn ignore_trivia(&self, trivia: &Trivia) -> bool {
+        if trivia
+            .start_position()
+            .and_then(|position| position.offset())
+            .filter(|offset| *offset < self.keep_before)
+            .is_some()
+        {
+            return true;
+        }
         let content = trivia.read(self.original_code);
-        self.except.iter().any(|pattern| pattern.is_match(content))
+        let keep = self.except.iter().any(|pattern| pattern.is_match(content));
+
+        if !keep {
+            if let Some(removed_comments) = &self.removed_comments {
+                if let Some(comment) = trivia.as_comment(self.original_code) {
+                    let offset = comment
+                        .position()
+                        .and_then(|position| position.offset())
+                        .unwrap_or(usize::MAX);
+                    removed_comments
+                        .borrow_mut()
+                        .push((offset, comment.text().trim().to_owned()));
+                }
+            }
+        }
+
+        keep
     }
 }
 
+/// Returns the byte offset where the comments at the start of the code end. The comments
+/// are read until the first empty line or the first line of code.
+fn leading_comments_end(code: &str) -> usize {
+    let mut end = 0;
+    let mut rest = code;
+
+    loop {
+        let trimmed = rest.trim_start();
+        let whitespace = &rest[..rest.len() - trimmed.len()];
+
+        if end != 0 && whitespace.matches('\n').count() > 1 {
+            return end;
+        }
+
+        let length = match comment_length(trimmed) {
+            Some(length) => length,
+            None => return end,
+        };
+
+        end = code.len() - trimmed.len() + length;
+        rest = &trimmed[length..];
+    }
+}
+
+/// Returns the length of the comment at the start of the code, if the code starts with
+/// a line comment or a block comment.
+fn comment_length(code: &str) -> Option<usize> {
+    let rest = code.strip_prefix("--")?;
+
+    if let Some(block) = rest.strip_prefix('[') {
+        let level = block.chars().take_while(|c| *c == '=').count();
+        if block[level..].starts_with('[') {
+            let closing = format!("]{}]", "=".repeat(level));
+            let length = block
+                .find(&closing)
+                .map(|index| "--[".len() + index + closing.len())
+                .unwrap_or(code.len());
+            return Some(length);
+        }
+    }
+
+    Some(
+        rest.find('\n')
+            .map(|index| "--".len() + index)
+            .unwrap_or(code.len()),
+    )
+}
+
 impl NodeProcessor for FilterCommentProcessor<'_> {
     fn process_block(&mut self, block: &mut Block) {
         block.filter_comments(|trivia| self.ignore_trivia(trivia));
@@ -281,10 +397,18 @@ impl NodeProcessor for FilterCommentProcessor<'_> {
         generic_for.filter_comments(|trivia| self.ignore_trivia(trivia));
     }
 
+    fn process_goto_statement(&mut self, goto: &mut GotoStatement) {
+        goto.filter_comments(|trivia| self.ignore_trivia(trivia));
+    }
+
     fn process_if_statement(&mut self, if_statement: &mut IfStatement) {
         if_statement.filter_comments(|trivia| self.ignore_trivia(trivia));
     }
 
+    fn process_label_statement(&mut self, label: &mut LabelStatement) {
+        label.filter_comments(|trivia| self.ignore_trivia(trivia));
+    }
+
     fn process_last_statement(&mut self, statement: &mut LastStatement) {
         match statement {
             LastStatement::Break(token) | LastStatement::Continue(token) => {
@@ -480,15 +604,34 @@ pub const REMOVE_COMMENTS_RULE_NAME: &str = "remove_comments";
 #[derive(Debug, Default)]
 pub struct RemoveComments {
     except: Vec<Regex>,
+    keep_first_comment: bool,
 }
 
+impl PartialEq for RemoveComments {
+    fn eq(&self, other: &Self) -> bool {
+        self.keep_first_comment == other.keep_first_comment
+            && self.except.len() == other.except.len()
+            && self
+                .except
+                .iter()
+                .zip(other.except.iter())
+                .all(|(pattern, other_pattern)| pattern.as_str() == other_pattern.as_str())
+    }
+}
+
+impl Eq for RemoveComments {}
+
 impl FlawlessRule for RemoveComments {
     fn flawless_process(&self, block: &mut Block, context: &Context) {
-        if self.except.is_empty() {
+        if self.except.is_empty() && !self.keep_first_comment {
             let mut processor = RemoveCommentProcessor::default();
             DefaultVisitor::visit_block(block, &mut processor);
         } else {
-            let mut processor = FilterCommentProcessor::new(context.original_code(), &self.except);
+            let original_code = context.original_code();
+            let mut processor = FilterCommentProcessor::new(original_code, &self.except);
+            if self.keep_first_comment {
+                processor = processor.keep_comments_before(leading_comments_end(original_code));
+            }
             DefaultVisitor::visit_block(block, &mut processor);
         }
     }
@@ -496,11 +639,16 @@ impl FlawlessRule for RemoveComments {
 
 impl RuleConfiguration for RemoveComments {
     fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        verify_property_collisions(&properties, &["except", "keep"])?;
+
         for (key, value) in properties {
             match key.as_str() {
-                "except" => {
+                "except" | "keep" => {
                     self.except = value.expect_regex_list(&key)?;
                 }
+                "keep_first_comment" => {
+                    self.keep_first_comment = value.expect_bool(&key)?;
+                }
                 _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
             }
         }
@@ -513,7 +661,44 @@ impl RuleConfiguration for RemoveComments {
     }
 
     fn serialize_to_properties(&self) -> RuleProperties {
-        RuleProperties::new()
+        let mut properties = RuleProperties::new();
+
+        if !self.except.is_empty() {
+            properties.insert(
+                "except".to_owned(),
+                RulePropertyValue::StringList(
+                    self.except
+                        .iter()
+                        .map(|pattern| pattern.as_str().to_owned())
+                        .collect(),
+                ),
+            );
+        }
+        if self.keep_first_comment {
+            properties.insert("keep_first_comment".to_owned(), true.into());
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert(
+            "except".to_owned(),
+            RulePropertyValue::StringList(
+                self.except
+                    .iter()
+                    .map(|pattern| pattern.as_str().to_owned())
+                    .collect(),
+            ),
+        );
+        properties.insert(
+            "keep_first_comment".to_owned(),
+            self.keep_first_comment.into(),
+        );
+
+        properties
     }
 }
 
@@ -565,6 +750,63 @@ mod test {
         );
     }
 
+    #[test]
+    fn serialize_rule_with_keep_first_comment() {
+        let rule: Box<dyn Rule> = Box::new(RemoveComments {
+            except: Vec::new(),
+            keep_first_comment: true,
+        });
+
+        assert_json_snapshot!("remove_comments_keep_first_comment", rule);
+    }
+
+    #[test]
+    fn configure_with_except_and_keep_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'remove_comments',
+            except: ["^--!"],
+            keep: ["^--!"],
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "the fields `except` and `keep` cannot be defined together"
+        );
+    }
+
+    #[test]
+    fn leading_comments_end_without_comments() {
+        assert_eq!(leading_comments_end("return nil -- comment"), 0);
+    }
+
+    #[test]
+    fn leading_comments_end_after_line_comments() {
+        let code = "-- line 1\n-- line 2\nreturn nil";
+        assert_eq!(&code[..leading_comments_end(code)], "-- line 1\n-- line 2");
+    }
+
+    #[test]
+    fn leading_comments_end_after_block_comment() {
+        let code = "--[==[\n  license ]]\n]==] return nil";
+        assert_eq!(
+            &code[..leading_comments_end(code)],
+            "--[==[\n  license ]]\n]==]"
+        );
+    }
+
+    #[test]
+    fn leading_comments_end_at_empty_line() {
+        let code = "\n-- license\n\n-- comment\nreturn nil";
+        assert_eq!(&code[..leading_comments_end(code)], "\n-- license");
+    }
+
+    #[test]
+    fn leading_comments_end_with_unclosed_block_comment() {
+        let code = "--[[ comment";
+        assert_eq!(leading_comments_end(code), code.len());
+    }
+
     #[test]
     fn remove_comments_in_code() {
         let code = include_str!("../../tests/test_cases/spaces_and_comments.lua");
@@ -81,6 +81,17 @@ impl RuleConfiguration for RemoveDebugProfiling {
 
         properties
     }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert(
+            "preserve_arguments_side_effects".to_owned(),
+            self.preserve_args_side_effects.into(),
+        );
+
+        properties
+    }
 }
 
 #[cfg(test)]
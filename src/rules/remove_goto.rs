@@ -0,0 +1,658 @@
+use std::mem;
+
+use crate::nodes::{
+    Block, DoStatement, ExportTypeFunctionStatement, Expression, FunctionExpression,
+    FunctionStatement, GenericForStatement, GotoStatement, IfStatement, LastStatement,
+    LocalFunctionStatement, NumericForStatement, RepeatStatement, SourcePosition, Statement,
+    TypeFunctionStatement, UnaryExpression, UnaryOperator, WhileStatement,
+};
+use crate::process::{
+    DefaultPostVisitor, DefaultVisitor, NodePostProcessor, NodePostVisitor, NodeProcessor,
+    NodeVisitor,
+};
+use crate::rules::{
+    Context, Rule, RuleConfiguration, RuleConfigurationError, RuleProcessResult, RuleProperties,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoopStrategy {
+    Continue,
+    Repeat,
+}
+
+impl Default for LoopStrategy {
+    fn default() -> Self {
+        Self::Continue
+    }
+}
+
+#[derive(Debug)]
+struct GotoError {
+    position: Option<SourcePosition>,
+    message: String,
+}
+
+impl GotoError {
+    fn new(position: Option<SourcePosition>, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+struct Processor {
+    strategy: LoopStrategy,
+    error: Option<GotoError>,
+}
+
+impl Processor {
+    fn new(strategy: LoopStrategy) -> Self {
+        Self {
+            strategy,
+            error: None,
+        }
+    }
+
+    fn resolve(&mut self, block: &mut Block, is_loop_body: bool) {
+        if self.error.is_none() {
+            if let Err(error) = self.resolve_labels(block, is_loop_body) {
+                self.error = Some(error);
+            }
+        }
+    }
+
+    fn resolve_labels(&self, block: &mut Block, is_loop_body: bool) -> Result<(), GotoError> {
+        while let Some(name) = first_label_name(block) {
+            let label_at_end = find_label(block, &name)
+                .map(|index| is_at_block_end(block, index))
+                .unwrap_or_default();
+
+            if is_loop_body && label_at_end && self.strategy == LoopStrategy::Continue {
+                replace_loop_gotos(block, &name, LoopStrategy::Continue, false)?;
+            } else {
+                skip_forward_gotos(block, &name)?;
+
+                if is_loop_body && label_at_end && find_goto(block, &name).is_some() {
+                    wrap_in_repeat(block, &name)?;
+                }
+            }
+
+            if let Some(goto) = find_goto(block, &name) {
+                let label_index = find_label(block, &name).unwrap_or_default();
+                let is_backward = block
+                    .iter_statements()
+                    .skip(label_index)
+                    .any(|statement| contains_goto(statement, &name));
+
+                let message = if is_backward {
+                    format!(
+                        "unable to remove `goto {}`: jumping backward is not supported",
+                        name
+                    )
+                } else {
+                    format!(
+                        "unable to remove `goto {}`: the label must be at the end of a loop or after the goto in the same block",
+                        name
+                    )
+                };
+                return Err(GotoError::new(goto_position(goto), message));
+            }
+
+            remove_label(block, &name);
+        }
+
+        Ok(())
+    }
+}
+
+impl NodeProcessor for Processor {
+    fn is_stopped(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+impl NodePostProcessor for Processor {
+    fn process_after_do_statement(&mut self, statement: &mut DoStatement) {
+        self.resolve(statement.mutate_block(), false);
+    }
+
+    fn process_after_if_statement(&mut self, statement: &mut IfStatement) {
+        for block in statement.mutate_all_blocks() {
+            self.resolve(block, false);
+        }
+    }
+
+    fn process_after_generic_for_statement(&mut self, statement: &mut GenericForStatement) {
+        self.resolve(statement.mutate_block(), true);
+    }
+
+    fn process_after_numeric_for_statement(&mut self, statement: &mut NumericForStatement) {
+        self.resolve(statement.mutate_block(), true);
+    }
+
+    fn process_after_repeat_statement(&mut self, statement: &mut RepeatStatement) {
+        self.resolve(statement.mutate_block(), true);
+    }
+
+    fn process_after_while_statement(&mut self, statement: &mut WhileStatement) {
+        self.resolve(statement.mutate_block(), true);
+    }
+
+    fn process_after_function_statement(&mut self, function: &mut FunctionStatement) {
+        self.resolve(function.mutate_block(), false);
+    }
+
+    fn process_after_local_function_statement(&mut self, function: &mut LocalFunctionStatement) {
+        self.resolve(function.mutate_block(), false);
+    }
+
+    fn process_after_type_function_statement(&mut self, function: &mut TypeFunctionStatement) {
+        self.resolve(function.mutate_block(), false);
+    }
+
+    fn process_after_export_type_function_statement(
+        &mut self,
+        function: &mut ExportTypeFunctionStatement,
+    ) {
+        self.resolve(function.mutate_block(), false);
+    }
+
+    fn process_after_function_expression(&mut self, function: &mut FunctionExpression) {
+        self.resolve(function.mutate_block(), false);
+    }
+}
+
+#[derive(Default)]
+struct RemainingGotoFinder {
+    position: Option<Option<SourcePosition>>,
+    label: String,
+}
+
+impl NodeProcessor for RemainingGotoFinder {
+    fn is_stopped(&self) -> bool {
+        self.position.is_some()
+    }
+
+    fn process_goto_statement(&mut self, goto: &mut GotoStatement) {
+        self.label = goto.get_label().get_name().to_owned();
+        self.position = Some(goto_position(goto));
+    }
+}
+
+fn goto_position(goto: &GotoStatement) -> Option<SourcePosition> {
+    goto.get_token()
+        .and_then(|token| token.start_position())
+        .or_else(|| goto.get_label().get_token()?.start_position())
+}
+
+fn label_name(statement: &Statement) -> Option<&str> {
+    match statement {
+        Statement::Label(label) => Some(label.get_name().get_name().as_str()),
+        _ => None,
+    }
+}
+
+fn first_label_name(block: &Block) -> Option<String> {
+    block
+        .iter_statements()
+        .find_map(label_name)
+        .map(str::to_owned)
+}
+
+fn find_label(block: &Block, name: &str) -> Option<usize> {
+    block
+        .iter_statements()
+        .position(|statement| label_name(statement) == Some(name))
+}
+
+fn remove_label(block: &mut Block, name: &str) {
+    let mut removed = false;
+    block.filter_statements(|statement| {
+        if !removed && label_name(statement) == Some(name) {
+            removed = true;
+            false
+        } else {
+            true
+        }
+    });
+}
+
+/// A label is at the end of a block when it is only followed by other labels.
+fn is_at_block_end(block: &Block, index: usize) -> bool {
+    block.get_last_statement().is_none()
+        && block
+            .iter_statements()
+            .skip(index + 1)
+            .all(|statement| matches!(statement, Statement::Label(_)))
+}
+
+fn is_goto_to(statement: &Statement, name: &str) -> bool {
+    matches!(statement, Statement::Goto(goto) if goto.get_label().get_name() == name)
+}
+
+fn ends_with_goto_to(block: &Block, name: &str) -> bool {
+    block.get_last_statement().is_none()
+        && matches!(
+            block.reverse_iter_statements().next(),
+            Some(statement) if is_goto_to(statement, name)
+        )
+}
+
+/// Returns the child blocks of a statement that are part of the same function, with
+/// a boolean telling if the block is the body of a loop.
+fn child_blocks(statement: &Statement) -> Vec<(&Block, bool)> {
+    match statement {
+        Statement::Do(do_statement) => vec![(do_statement.get_block(), false)],
+        Statement::If(if_statement) => if_statement
+            .iter_branches()
+            .map(|branch| (branch.get_block(), false))
+            .chain(if_statement.get_else_block().map(|block| (block, false)))
+            .collect(),
+        Statement::GenericFor(generic_for) => vec![(generic_for.get_block(), true)],
+        Statement::NumericFor(numeric_for) => vec![(numeric_for.get_block(), true)],
+        Statement::Repeat(repeat) => vec![(repeat.get_block(), true)],
+        Statement::While(while_statement) => vec![(while_statement.get_block(), true)],
+        _ => Vec::new(),
+    }
+}
+
+fn find_goto<'a>(block: &'a Block, name: &str) -> Option<&'a GotoStatement> {
+    block
+        .iter_statements()
+        .find_map(|statement| find_goto_in_statement(statement, name))
+}
+
+fn find_goto_in_statement<'a>(statement: &'a Statement, name: &str) -> Option<&'a GotoStatement> {
+    match statement {
+        Statement::Goto(goto) if goto.get_label().get_name() == name => Some(goto),
+        _ => child_blocks(statement)
+            .into_iter()
+            .find_map(|(block, _)| find_goto(block, name)),
+    }
+}
+
+fn contains_goto(statement: &Statement, name: &str) -> bool {
+    find_goto_in_statement(statement, name).is_some()
+}
+
+/// Finds a `break` statement that exits the loop of the given block.
+fn find_loop_break(block: &Block) -> Option<&LastStatement> {
+    block
+        .get_last_statement()
+        .filter(|statement| matches!(statement, LastStatement::Break(_)))
+        .or_else(|| {
+            block.iter_statements().find_map(|statement| {
+                child_blocks(statement)
+                    .into_iter()
+                    .filter(|(_, is_loop_body)| !is_loop_body)
+                    .find_map(|(block, _)| find_loop_break(block))
+            })
+        })
+}
+
+/// Replaces the gotos to the given label with `continue` statements, or with `break`
+/// statements when the loop body is wrapped into a `repeat ... until true` loop.
+fn replace_loop_gotos(
+    block: &mut Block,
+    name: &str,
+    strategy: LoopStrategy,
+    in_nested_loop: bool,
+) -> Result<(), GotoError> {
+    let statements_len = block.statements_len();
+    // a goto can only be followed by labels, which are at the end of the loop body
+    let trailing_labels_start = statements_len
+        - block
+            .reverse_iter_statements()
+            .take_while(|statement| matches!(statement, Statement::Label(_)))
+            .count();
+    let has_last_statement = block.get_last_statement().is_some();
+    let mut goto_found = None;
+
+    for (index, statement) in block.iter_mut_statements().enumerate() {
+        match statement {
+            Statement::Goto(goto) if goto.get_label().get_name() == name => {
+                if in_nested_loop {
+                    return Err(GotoError::new(
+                        goto_position(goto),
+                        format!(
+                            "unable to remove `goto {}`: jumping out of a nested loop is not supported",
+                            name
+                        ),
+                    ));
+                }
+                if has_last_statement || index + 1 < trailing_labels_start {
+                    return Err(GotoError::new(
+                        goto_position(goto),
+                        format!(
+                            "unable to remove `goto {}`: it must be the last statement of its block",
+                            name
+                        ),
+                    ));
+                }
+                goto_found = Some((index, goto.get_token().cloned()));
+            }
+            Statement::Do(do_statement) => {
+                replace_loop_gotos(do_statement.mutate_block(), name, strategy, in_nested_loop)?
+            }
+            Statement::If(if_statement) => {
+                for block in if_statement.mutate_all_blocks() {
+                    replace_loop_gotos(block, name, strategy, in_nested_loop)?;
+                }
+            }
+            Statement::GenericFor(generic_for) => {
+                replace_loop_gotos(generic_for.mutate_block(), name, strategy, true)?
+            }
+            Statement::NumericFor(numeric_for) => {
+                replace_loop_gotos(numeric_for.mutate_block(), name, strategy, true)?
+            }
+            Statement::Repeat(repeat) => {
+                replace_loop_gotos(repeat.mutate_block(), name, strategy, true)?
+            }
+            Statement::While(while_statement) => {
+                replace_loop_gotos(while_statement.mutate_block(), name, strategy, true)?
+            }
+            _ => {}
+        }
+    }
+
+    match goto_found {
+        Some((index, goto_token)) if index + 1 == statements_len => {
+            block.truncate(index);
+
+            let content = match strategy {
+                LoopStrategy::Continue => "continue",
+                LoopStrategy::Repeat => "break",
+            };
+            let token = goto_token.map(|mut token| {
+                token.replace_with_content(content);
+                token
+            });
+
+            block.set_last_statement(match strategy {
+                LoopStrategy::Continue => LastStatement::Continue(token),
+                LoopStrategy::Repeat => LastStatement::Break(token),
+            });
+        }
+        Some((index, _)) => {
+            // the goto jumps to a label that directly follows it
+            let mut statements = block.take_statements();
+            statements.remove(index);
+            block.set_statements(statements);
+        }
+        None => {}
+    }
+
+    Ok(())
+}
+
+/// Wraps the statements of a loop body that are before the given label into a
+/// `repeat ... until true` loop, so that the gotos to the label can be replaced
+/// with `break` statements.
+fn wrap_in_repeat(block: &mut Block, name: &str) -> Result<(), GotoError> {
+    if let Some(statement) = find_loop_break(block) {
+        return Err(GotoError::new(
+            statement.start_position(),
+            format!(
+                "unable to remove `goto {}`: the loop contains a break statement",
+                name
+            ),
+        ));
+    }
+
+    replace_loop_gotos(block, name, LoopStrategy::Repeat, false)?;
+
+    let label_index = find_label(block, name).unwrap_or_default();
+    let mut statements = block.take_statements();
+    let after_label = statements.split_off(label_index);
+
+    let mut new_statements = vec![RepeatStatement::new(Block::new(statements, None), true).into()];
+    new_statements.extend(after_label);
+    block.set_statements(new_statements);
+
+    Ok(())
+}
+
+/// A goto can be replaced with an if statement when it is in the block of its label
+/// and before the label, either alone or as the last statement of an if statement that
+/// has a single branch.
+fn is_forward_goto(statement: &Statement, name: &str) -> bool {
+    match statement {
+        Statement::Goto(_) => is_goto_to(statement, name),
+        Statement::If(if_statement) => {
+            if_statement.branch_count() == 1
+                && if_statement.get_else_block().is_none()
+                && if_statement
+                    .iter_branches()
+                    .all(|branch| ends_with_goto_to(branch.get_block(), name))
+        }
+        _ => false,
+    }
+}
+
+fn find_local_definition(statements: &[Statement]) -> Option<(&Statement, &str)> {
+    statements.iter().find_map(|statement| match statement {
+        Statement::LocalAssign(assign) => assign
+            .iter_variables()
+            .next()
+            .map(|variable| (statement, variable.get_identifier().get_name().as_str())),
+        Statement::LocalFunction(function) => Some((statement, function.get_name())),
+        _ => None,
+    })
+}
+
+fn negate(mut condition: Expression) -> Expression {
+    if let Expression::Unary(unary) = &mut condition {
+        if unary.operator() == UnaryOperator::Not {
+            return mem::replace(unary.mutate_expression(), Expression::nil());
+        }
+    }
+    UnaryExpression::new(UnaryOperator::Not, condition).into()
+}
+
+/// Replaces the gotos that jump forward to the given label by skipping the statements
+/// in between with if statements.
+fn skip_forward_gotos(block: &mut Block, name: &str) -> Result<(), GotoError> {
+    loop {
+        let label_index = match find_label(block, name) {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+        let goto_index = match block
+            .iter_statements()
+            .take(label_index)
+            .rposition(|statement| is_forward_goto(statement, name))
+        {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+        let label_at_end = is_at_block_end(block, label_index);
+
+        let mut statements = block.take_statements();
+        let after_label = statements.split_off(label_index);
+        let skipped = statements.split_off(goto_index + 1);
+        let goto_statement = statements.pop().expect("goto statement should exist");
+
+        let mut new_statements = statements;
+
+        if let Statement::If(mut if_statement) = goto_statement {
+            if !label_at_end {
+                if let Some((statement, local)) = find_local_definition(&skipped) {
+                    return Err(GotoError::new(
+                        statement.start_position(),
+                        format!(
+                            "unable to remove `goto {}`: it jumps into the scope of local `{}`",
+                            name, local
+                        ),
+                    ));
+                }
+            }
+
+            let branch = &mut if_statement.mutate_branches()[0];
+            let branch_block = branch.mutate_block();
+            branch_block.truncate(branch_block.statements_len().saturating_sub(1));
+
+            if skipped.is_empty() {
+                new_statements.push(if_statement.into());
+            } else if branch_block.is_empty() {
+                let condition = mem::replace(branch.mutate_condition(), Expression::nil());
+                let mut skip_statement =
+                    IfStatement::create(negate(condition), Block::new(skipped, None));
+                if let Some(tokens) = if_statement.get_tokens() {
+                    skip_statement.set_tokens(tokens.clone());
+                }
+                new_statements.push(skip_statement.into());
+            } else {
+                if_statement.set_else_block(Block::new(skipped, None));
+                new_statements.push(if_statement.into());
+            }
+        }
+        // a goto that is not in an if statement makes the skipped statements unreachable
+
+        new_statements.extend(after_label);
+        block.set_statements(new_statements);
+    }
+}
+
+pub const REMOVE_GOTO_RULE_NAME: &str = "remove_goto";
+
+/// A rule that removes goto statements and labels, by converting jumps to the end of a
+/// loop into `continue` statements (or `break` statements from a `repeat ... until true`
+/// loop) and forward jumps into if statements.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RemoveGoto {
+    strategy: LoopStrategy,
+}
+
+impl Rule for RemoveGoto {
+    fn process(&self, block: &mut Block, context: &Context) -> RuleProcessResult {
+        let mut processor = Processor::new(self.strategy);
+        DefaultPostVisitor::visit_block(block, &mut processor);
+        processor.resolve(block, false);
+
+        if let Some(error) = processor.error {
+            return Err(context.error_at(error.position, error.message));
+        }
+
+        let mut finder = RemainingGotoFinder::default();
+        DefaultVisitor::visit_block(block, &mut finder);
+
+        if let Some(position) = finder.position {
+            return Err(context.error_at(
+                position,
+                format!("no visible label `{}` for goto", finder.label),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl RuleConfiguration for RemoveGoto {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                "strategy" => {
+                    self.strategy = match value.expect_string(&key)?.as_str() {
+                        "continue" => LoopStrategy::Continue,
+                        "repeat" => LoopStrategy::Repeat,
+                        unexpected => {
+                            return Err(RuleConfigurationError::UnexpectedValue {
+                                property: "strategy".to_owned(),
+                                message: format!(
+                                    "invalid value `{}` (must be `continue` or `repeat`)",
+                                    unexpected
+                                ),
+                            })
+                        }
+                    };
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        REMOVE_GOTO_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        match self.strategy {
+            LoopStrategy::Continue => {}
+            LoopStrategy::Repeat => {
+                properties.insert("strategy".to_owned(), "repeat".into());
+            }
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        let strategy = match self.strategy {
+            LoopStrategy::Continue => "continue",
+            LoopStrategy::Repeat => "repeat",
+        };
+        properties.insert("strategy".to_owned(), strategy.into());
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> RemoveGoto {
+        RemoveGoto::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_remove_goto", rule);
+    }
+
+    #[test]
+    fn serialize_rule_with_repeat_strategy() {
+        let rule: Box<dyn Rule> = Box::new(RemoveGoto {
+            strategy: LoopStrategy::Repeat,
+        });
+
+        assert_json_snapshot!("remove_goto_with_repeat_strategy", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'remove_goto',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn configure_with_invalid_strategy_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'remove_goto',
+            strategy: 'jump',
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "unexpected value for field 'strategy': invalid value `jump` (must be `continue` or `repeat`)"
+        );
+    }
+}
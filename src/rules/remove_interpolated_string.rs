@@ -5,7 +5,9 @@ use crate::nodes::{
     InterpolationSegment, LocalAssignStatement, Prefix, StringExpression, TupleArguments,
     TypedIdentifier,
 };
-use crate::process::{IdentifierTracker, NodeProcessor, NodeVisitor, ScopeVisitor};
+use crate::process::{
+    Evaluator, IdentifierTracker, LuaValue, NodeProcessor, NodeVisitor, ScopeVisitor,
+};
 use crate::rules::{
     Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
 };
@@ -29,6 +31,7 @@ struct RemoveInterpolatedStringProcessor {
     define_tostring: bool,
     identifier_tracker: IdentifierTracker,
     strategy: ReplacementStrategy,
+    evaluator: Evaluator,
 }
 
 impl ops::Deref for RemoveInterpolatedStringProcessor {
@@ -62,6 +65,7 @@ impl RemoveInterpolatedStringProcessor {
             define_tostring: false,
             identifier_tracker: Default::default(),
             strategy,
+            evaluator: Evaluator::default(),
         }
     }
 
@@ -149,6 +153,19 @@ impl RemoveInterpolatedStringProcessor {
 
 impl NodeProcessor for RemoveInterpolatedStringProcessor {
     fn process_expression(&mut self, expression: &mut Expression) {
+        if !matches!(expression, Expression::InterpolatedString(_)) {
+            return;
+        }
+
+        // when every segment is constant, a string literal is smaller than the
+        // `string.format` call
+        if !self.evaluator.has_side_effects(expression) {
+            if let LuaValue::String(value) = self.evaluator.evaluate(expression) {
+                *expression = StringExpression::from_value(value).into();
+                return;
+            }
+        }
+
         if let Expression::InterpolatedString(string) = expression {
             *expression = self.replace_with(string);
         }
@@ -242,6 +259,18 @@ impl RuleConfiguration for RemoveInterpolatedString {
 
         properties
     }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        let strategy = match self.strategy {
+            ReplacementStrategy::StringSpecifier => "string",
+            ReplacementStrategy::ToStringSpecifier => "tostring",
+        };
+        properties.insert("strategy".to_owned(), strategy.into());
+
+        properties
+    }
 }
 
 #[cfg(test)]
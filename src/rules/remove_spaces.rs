@@ -1,11 +1,11 @@
+use std::collections::BTreeSet;
+
 use crate::nodes::*;
 use crate::process::{DefaultVisitor, NodeProcessor, NodeVisitor};
 use crate::rules::{
     Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
 };
 
-use super::verify_no_rule_properties;
-
 #[derive(Debug, Default)]
 pub(crate) struct RemoveWhitespacesProcessor {}
 
@@ -39,10 +39,18 @@ impl NodeProcessor for RemoveWhitespacesProcessor {
         generic_for.clear_whitespaces();
     }
 
+    fn process_goto_statement(&mut self, goto: &mut GotoStatement) {
+        goto.clear_whitespaces();
+    }
+
     fn process_if_statement(&mut self, if_statement: &mut IfStatement) {
         if_statement.clear_whitespaces();
     }
 
+    fn process_label_statement(&mut self, label: &mut LabelStatement) {
+        label.clear_whitespaces();
+    }
+
     fn process_last_statement(&mut self, statement: &mut LastStatement) {
         match statement {
             LastStatement::Break(token) | LastStatement::Continue(token) => {
@@ -232,20 +240,169 @@ impl NodeProcessor for RemoveWhitespacesProcessor {
 
 pub const REMOVE_SPACES_RULE_NAME: &str = "remove_spaces";
 
+/// Defines between which statements a blank line is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeepBlankLines {
+    /// Keep a blank line between the statements of the file.
+    TopLevel,
+    /// Keep a blank line between the statements of every block.
+    All,
+    /// Keep every line where it was.
+    None,
+}
+
+impl Default for KeepBlankLines {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl KeepBlankLines {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::TopLevel => "top_level",
+            Self::All => "all",
+            Self::None => "none",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct StatementBoundaries {
+    lines: Vec<usize>,
+}
+
+impl StatementBoundaries {
+    fn collect(&mut self, block: &Block) {
+        let start_lines = block
+            .iter_statements()
+            .map(Statement::start_position)
+            .chain(
+                block
+                    .get_last_statement()
+                    .map(LastStatement::start_position),
+            )
+            .skip(1)
+            .filter_map(|position| position.map(|position| position.line()));
+
+        self.lines.extend(start_lines);
+    }
+}
+
+impl NodeProcessor for StatementBoundaries {
+    fn process_block(&mut self, block: &mut Block) {
+        self.collect(block);
+    }
+}
+
+/// Finds the lines to remove so that blank lines only remain before the given statement
+/// boundaries, where a single blank line is kept.
+fn find_removed_lines(block: &mut Block, boundaries: &[usize], code: &str) -> Vec<usize> {
+    let mut occupied_lines = BTreeSet::new();
+
+    block.for_each_deep_token(|token| {
+        if let Some(line) = token.get_line_number() {
+            let extra_lines = token.read(code).matches('\n').count();
+            occupied_lines.extend(line..=line + extra_lines);
+        }
+    });
+
+    let mut removed_lines = Vec::new();
+    let mut previous_line = 0;
+
+    for line in occupied_lines {
+        let first_blank_line = if previous_line != 0 && boundaries.binary_search(&line).is_ok() {
+            previous_line + 2
+        } else {
+            previous_line + 1
+        };
+        removed_lines.extend(first_blank_line..line);
+        previous_line = line;
+    }
+
+    removed_lines
+}
+
 /// A rule that removes whitespaces associated with AST nodes.
 #[derive(Debug, Default, PartialEq, Eq)]
-pub struct RemoveSpaces {}
+pub struct RemoveSpaces {
+    keep_blank_between_statements: KeepBlankLines,
+}
+
+impl RemoveSpaces {
+    /// Keep a single blank line between the statements of the file when they were
+    /// separated by blank lines.
+    pub fn keep_blank_lines_between_top_level_statements(mut self) -> Self {
+        self.keep_blank_between_statements = KeepBlankLines::TopLevel;
+        self
+    }
+
+    /// Keep a single blank line between the statements of every block when they were
+    /// separated by blank lines.
+    pub fn keep_blank_lines_between_all_statements(mut self) -> Self {
+        self.keep_blank_between_statements = KeepBlankLines::All;
+        self
+    }
+
+    fn collapse_blank_lines(&self, block: &mut Block, code: &str) {
+        let mut boundaries = StatementBoundaries::default();
+
+        match self.keep_blank_between_statements {
+            KeepBlankLines::TopLevel => boundaries.collect(block),
+            KeepBlankLines::All => DefaultVisitor::visit_block(block, &mut boundaries),
+            KeepBlankLines::None => return,
+        }
+
+        let mut boundaries = boundaries.lines;
+        boundaries.sort_unstable();
+
+        let removed_lines = find_removed_lines(block, &boundaries, code);
+
+        if removed_lines.is_empty() {
+            return;
+        }
+
+        block.for_each_deep_token(|token| {
+            token.map_line_number(|line| {
+                line - removed_lines.partition_point(|removed| *removed < line)
+            })
+        });
+    }
+}
 
 impl FlawlessRule for RemoveSpaces {
-    fn flawless_process(&self, block: &mut Block, _: &Context) {
+    fn flawless_process(&self, block: &mut Block, context: &Context) {
         let mut processor = RemoveWhitespacesProcessor::default();
         DefaultVisitor::visit_block(block, &mut processor);
+
+        self.collapse_blank_lines(block, context.original_code());
     }
 }
 
 impl RuleConfiguration for RemoveSpaces {
     fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
-        verify_no_rule_properties(&properties)?;
+        for (key, value) in properties {
+            match key.as_str() {
+                "keep_blank_between_statements" => {
+                    self.keep_blank_between_statements = match value.expect_string(&key)?.as_str() {
+                        "top_level" => KeepBlankLines::TopLevel,
+                        "all" => KeepBlankLines::All,
+                        "none" => KeepBlankLines::None,
+                        unexpected => {
+                            return Err(RuleConfigurationError::UnexpectedValue {
+                                property: "keep_blank_between_statements".to_owned(),
+                                message: format!(
+                                    "invalid value `{}` (must be `top_level`, `all` or `none`)",
+                                    unexpected
+                                ),
+                            })
+                        }
+                    };
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
         Ok(())
     }
 
@@ -254,7 +411,27 @@ impl RuleConfiguration for RemoveSpaces {
     }
 
     fn serialize_to_properties(&self) -> RuleProperties {
-        RuleProperties::new()
+        let mut properties = RuleProperties::new();
+
+        if self.keep_blank_between_statements != KeepBlankLines::None {
+            properties.insert(
+                "keep_blank_between_statements".to_owned(),
+                self.keep_blank_between_statements.as_str().into(),
+            );
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert(
+            "keep_blank_between_statements".to_owned(),
+            self.keep_blank_between_statements.as_str().into(),
+        );
+
+        properties
     }
 }
 
@@ -312,4 +489,103 @@ mod test {
 
         insta::assert_snapshot!("remove_spaces_in_code", code_output);
     }
+
+    fn process_with_tokens(rule: &RemoveSpaces, code: &str) -> String {
+        let mut block = Parser::default()
+            .preserve_tokens()
+            .parse(code)
+            .expect("unable to parse code");
+
+        rule.flawless_process(
+            &mut block,
+            &ContextBuilder::new(".", &Resources::from_memory(), code).build(),
+        );
+
+        let mut generator = TokenBasedLuaGenerator::new(code);
+        generator.write_block(&block);
+        generator.into_string()
+    }
+
+    const FUNCTIONS_WITH_BLANK_LINES: &str = "local function a()\n\n    return 1\nend\n\n\n\nlocal function b()\n    local value = 2\n\n\n    return value\nend\n\nlocal function c()\nend\n\n\n\n\n\nreturn a, b, c\n";
+
+    #[test]
+    fn keep_one_blank_line_between_top_level_statements() {
+        pretty_assertions::assert_eq!(
+            process_with_tokens(
+                &new_rule().keep_blank_lines_between_top_level_statements(),
+                FUNCTIONS_WITH_BLANK_LINES
+            ),
+            "local function a()\nreturn 1\nend\n\nlocal function b()\nlocal value=2\nreturn value\nend\n\nlocal function c()\nend\n\nreturn a,b,c"
+        );
+    }
+
+    #[test]
+    fn keep_one_blank_line_between_all_statements() {
+        pretty_assertions::assert_eq!(
+            process_with_tokens(
+                &new_rule().keep_blank_lines_between_all_statements(),
+                FUNCTIONS_WITH_BLANK_LINES
+            ),
+            "local function a()\nreturn 1\nend\n\nlocal function b()\nlocal value=2\n\nreturn value\nend\n\nlocal function c()\nend\n\nreturn a,b,c"
+        );
+    }
+
+    #[test]
+    fn keep_statements_on_their_lines_by_default() {
+        pretty_assertions::assert_eq!(
+            process_with_tokens(&new_rule(), FUNCTIONS_WITH_BLANK_LINES),
+            "local function a()\n\nreturn 1\nend\n\n\n\nlocal function b()\nlocal value=2\n\n\nreturn value\nend\n\nlocal function c()\nend\n\n\n\n\n\nreturn a,b,c"
+        );
+    }
+
+    #[test]
+    fn keep_blank_lines_ignores_statements_on_consecutive_lines() {
+        let code = "\n\nlocal a = 1\nlocal b = 2\n\n\nlocal c = 3";
+
+        pretty_assertions::assert_eq!(
+            process_with_tokens(
+                &new_rule().keep_blank_lines_between_top_level_statements(),
+                code
+            ),
+            "local a=1\nlocal b=2\n\nlocal c=3"
+        );
+    }
+
+    #[test]
+    fn keep_blank_lines_preserves_multiline_strings() {
+        let code = "local a = [[\n\n]]\n\n\nlocal b = 2";
+
+        pretty_assertions::assert_eq!(
+            process_with_tokens(
+                &new_rule().keep_blank_lines_between_top_level_statements(),
+                code
+            ),
+            "local a=[[\n\n]]\n\nlocal b=2"
+        );
+    }
+
+    #[test]
+    fn serialize_rule_keeping_blank_lines_between_top_level_statements() {
+        let rule: Box<dyn Rule> =
+            Box::new(new_rule().keep_blank_lines_between_top_level_statements());
+
+        assert_json_snapshot!(
+            "remove_spaces_keeping_blank_lines_between_top_level_statements",
+            rule
+        );
+    }
+
+    #[test]
+    fn configure_with_invalid_keep_blank_between_statements_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'remove_spaces',
+            keep_blank_between_statements: 'functions',
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "unexpected value for field 'keep_blank_between_statements': invalid value `functions` (must be `top_level`, `all` or `none`)"
+        );
+    }
 }
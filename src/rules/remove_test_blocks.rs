@@ -0,0 +1,213 @@
+use std::collections::HashSet;
+use std::ops;
+
+use crate::nodes::{Arguments, Block, Expression, FunctionCall, Prefix, Statement};
+use crate::process::{IdentifierTracker, NodeProcessor, NodeVisitor, ScopeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+    RulePropertyValue,
+};
+
+const FUNCTIONS_PROPERTY: &str = "functions";
+
+const DEFAULT_TEST_FUNCTIONS: [&str; 5] = ["describe", "it", "test", "beforeEach", "afterAll"];
+
+struct TestBlockRemover<'a> {
+    identifier_tracker: IdentifierTracker,
+    functions: &'a [String],
+}
+
+impl ops::Deref for TestBlockRemover<'_> {
+    type Target = IdentifierTracker;
+
+    fn deref(&self) -> &Self::Target {
+        &self.identifier_tracker
+    }
+}
+
+impl ops::DerefMut for TestBlockRemover<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.identifier_tracker
+    }
+}
+
+impl TestBlockRemover<'_> {
+    /// Returns `true` if the call is a call to a test function, like
+    /// `describe("name", function() ... end)`. The locals declared previously in the
+    /// current block are given with `block_locals`, because the identifier tracker only
+    /// knows about the locals of the parent scopes when the block is processed.
+    fn is_test_block(&self, call: &FunctionCall, block_locals: &HashSet<String>) -> bool {
+        if call.get_method().is_some() {
+            return false;
+        }
+
+        let name = match call.get_prefix() {
+            Prefix::Identifier(identifier) => identifier.get_name(),
+            _ => return false,
+        };
+
+        if !self.functions.iter().any(|function| function == name)
+            || block_locals.contains(name)
+            || self.is_identifier_used(name)
+        {
+            return false;
+        }
+
+        match call.get_arguments() {
+            Arguments::Tuple(tuple) => matches!(tuple.last_value(), Some(Expression::Function(_))),
+            Arguments::String(_) | Arguments::Table(_) => false,
+        }
+    }
+}
+
+impl NodeProcessor for TestBlockRemover<'_> {
+    fn process_block(&mut self, block: &mut Block) {
+        let mut block_locals = HashSet::new();
+
+        block.filter_statements(|statement| match statement {
+            Statement::Call(call) => !self.is_test_block(call, &block_locals),
+            Statement::LocalAssign(assign) => {
+                block_locals.extend(
+                    assign
+                        .iter_variables()
+                        .map(|variable| variable.get_name().to_owned()),
+                );
+                true
+            }
+            Statement::LocalFunction(function) => {
+                block_locals.insert(function.get_name().to_owned());
+                true
+            }
+            _ => true,
+        });
+    }
+}
+
+pub const REMOVE_TEST_BLOCKS_RULE_NAME: &str = "remove_test_blocks";
+
+/// A rule that removes the statements that call test functions (like `describe` or `it`)
+/// with a function as their last argument.
+#[derive(Debug, PartialEq, Eq)]
+pub struct RemoveTestBlocks {
+    functions: Vec<String>,
+}
+
+impl Default for RemoveTestBlocks {
+    fn default() -> Self {
+        Self {
+            functions: DEFAULT_TEST_FUNCTIONS
+                .iter()
+                .map(|name| (*name).to_owned())
+                .collect(),
+        }
+    }
+}
+
+impl RemoveTestBlocks {
+    pub fn with_functions<I: IntoIterator<Item = S>, S: Into<String>>(
+        mut self,
+        functions: I,
+    ) -> Self {
+        self.functions = functions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn has_default_functions(&self) -> bool {
+        self.functions.len() == DEFAULT_TEST_FUNCTIONS.len()
+            && self
+                .functions
+                .iter()
+                .zip(DEFAULT_TEST_FUNCTIONS.iter())
+                .all(|(function, default)| function == default)
+    }
+}
+
+impl FlawlessRule for RemoveTestBlocks {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut processor = TestBlockRemover {
+            identifier_tracker: IdentifierTracker::new(),
+            functions: &self.functions,
+        };
+        ScopeVisitor::visit_block(block, &mut processor);
+    }
+}
+
+impl RuleConfiguration for RemoveTestBlocks {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                FUNCTIONS_PROPERTY => {
+                    self.functions = value.expect_string_list(&key)?;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        REMOVE_TEST_BLOCKS_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        if !self.has_default_functions() {
+            properties.insert(
+                FUNCTIONS_PROPERTY.to_owned(),
+                RulePropertyValue::StringList(self.functions.clone()),
+            );
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert(
+            FUNCTIONS_PROPERTY.to_owned(),
+            RulePropertyValue::StringList(self.functions.clone()),
+        );
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> RemoveTestBlocks {
+        RemoveTestBlocks::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_remove_test_blocks", rule);
+    }
+
+    #[test]
+    fn serialize_rule_with_functions() {
+        let rule: Box<dyn Rule> = Box::new(new_rule().with_functions(["spec", "suite"]));
+
+        assert_json_snapshot!("remove_test_blocks_with_functions", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'remove_test_blocks',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+}
@@ -0,0 +1,123 @@
+use crate::nodes::{Arguments, Block, Expression, FunctionCall};
+use crate::process::{DefaultVisitor, NodeProcessor, NodeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+struct Processor {
+    keep_single_nil: bool,
+}
+
+impl NodeProcessor for Processor {
+    fn process_function_call(&mut self, call: &mut FunctionCall) {
+        // string and table arguments (like `f"text"` or `f{}`) never end with `nil`
+        if let Arguments::Tuple(tuple) = call.mutate_arguments() {
+            let minimum_length = usize::from(self.keep_single_nil);
+
+            while tuple.len() > minimum_length
+                && matches!(tuple.last_value(), Some(Expression::Nil(_)))
+            {
+                tuple.pop_value();
+            }
+        }
+    }
+}
+
+pub const REMOVE_TRAILING_NIL_ARGUMENTS_RULE_NAME: &str = "remove_trailing_nil_arguments";
+
+/// A rule that removes the `nil` arguments at the end of function calls.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RemoveTrailingNilArguments {
+    keep_single_nil: bool,
+}
+
+impl RemoveTrailingNilArguments {
+    /// Keep one `nil` argument in calls where every argument is `nil`.
+    pub fn keep_single_nil(mut self) -> Self {
+        self.keep_single_nil = true;
+        self
+    }
+}
+
+impl FlawlessRule for RemoveTrailingNilArguments {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut processor = Processor {
+            keep_single_nil: self.keep_single_nil,
+        };
+        DefaultVisitor::visit_block(block, &mut processor);
+    }
+}
+
+impl RuleConfiguration for RemoveTrailingNilArguments {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                "keep_single_nil" => {
+                    self.keep_single_nil = value.expect_bool(&key)?;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        REMOVE_TRAILING_NIL_ARGUMENTS_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        if self.keep_single_nil {
+            properties.insert("keep_single_nil".to_owned(), true.into());
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert("keep_single_nil".to_owned(), self.keep_single_nil.into());
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> RemoveTrailingNilArguments {
+        RemoveTrailingNilArguments::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_remove_trailing_nil_arguments", rule);
+    }
+
+    #[test]
+    fn serialize_rule_keeping_single_nil() {
+        let rule: Box<dyn Rule> = Box::new(new_rule().keep_single_nil());
+
+        assert_json_snapshot!("remove_trailing_nil_arguments_keeping_single_nil", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'remove_trailing_nil_arguments',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+}
@@ -0,0 +1,109 @@
+use crate::nodes::{Block, Expression};
+use crate::process::{DefaultVisitor, Evaluator, NodeProcessor, NodeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+use super::verify_no_rule_properties;
+
+/// Replaces a type assertion (`value :: T`) with its value. When the value can produce
+/// multiple values (like a function call), it is wrapped in parentheses to keep the
+/// truncation to a single value that the assertion implied.
+pub(crate) fn remove_type_assertion(expression: &mut Expression, evaluator: &Evaluator) {
+    match expression {
+        Expression::TypeCast(_) => {
+            let value = strip_type_assertions(expression).clone();
+            if evaluator.can_return_multiple_values(&value) {
+                *expression = value.in_parentheses();
+            } else {
+                *expression = value;
+            }
+        }
+        Expression::Parenthese(parenthese) => {
+            // the parentheses already truncate the value, so they do not need to be doubled
+            if matches!(parenthese.inner_expression(), Expression::TypeCast(_)) {
+                let value = strip_type_assertions(parenthese.inner_expression()).clone();
+                *parenthese.mutate_inner_expression() = value;
+            }
+        }
+        _ => {}
+    }
+}
+
+fn strip_type_assertions(expression: &Expression) -> &Expression {
+    let mut current = expression;
+    while let Expression::TypeCast(type_cast) = current {
+        current = type_cast.get_expression();
+    }
+    current
+}
+
+#[derive(Default)]
+struct RemoveTypeAssertionsProcessor {
+    evaluator: Evaluator,
+}
+
+impl NodeProcessor for RemoveTypeAssertionsProcessor {
+    fn process_expression(&mut self, expression: &mut Expression) {
+        remove_type_assertion(expression, &self.evaluator);
+    }
+}
+
+pub const REMOVE_TYPE_ASSERTIONS_RULE_NAME: &str = "remove_type_assertions";
+
+/// A rule that removes Luau type assertions (`value :: T`) while keeping the other type
+/// annotations.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RemoveTypeAssertions {}
+
+impl FlawlessRule for RemoveTypeAssertions {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut processor = RemoveTypeAssertionsProcessor::default();
+        DefaultVisitor::visit_block(block, &mut processor);
+    }
+}
+
+impl RuleConfiguration for RemoveTypeAssertions {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        verify_no_rule_properties(&properties)?;
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        REMOVE_TYPE_ASSERTIONS_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        RuleProperties::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> RemoveTypeAssertions {
+        RemoveTypeAssertions::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_remove_type_assertions", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'remove_type_assertions',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+}
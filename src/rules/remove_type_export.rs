@@ -0,0 +1,101 @@
+use std::mem;
+
+use crate::nodes::{Block, DoStatement, Statement, TypeFunctionStatement};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+use super::verify_no_rule_properties;
+
+/// Removes the `export` keyword from the type declarations and type functions of the
+/// block and returns the names of the types that were exported.
+pub(crate) fn remove_type_exports(block: &mut Block) -> Vec<String> {
+    let mut names = Vec::new();
+
+    for statement in block.iter_mut_statements() {
+        match statement {
+            Statement::TypeDeclaration(type_declaration) if type_declaration.is_exported() => {
+                type_declaration.remove_export();
+                names.push(type_declaration.get_name().get_name().to_owned());
+            }
+            Statement::ExportTypeFunction(_) => {
+                if let Statement::ExportTypeFunction(export_type_function) =
+                    mem::replace(statement, DoStatement::default().into())
+                {
+                    names.push(export_type_function.get_name().to_owned());
+                    *statement = TypeFunctionStatement::from(export_type_function).into();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    names
+}
+
+pub const REMOVE_TYPE_EXPORT_RULE_NAME: &str = "remove_type_export";
+
+/// A rule that removes the `export` keyword from type declarations, which keeps the types
+/// local to the module.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RemoveTypeExport {}
+
+impl FlawlessRule for RemoveTypeExport {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        remove_type_exports(block);
+    }
+}
+
+impl RuleConfiguration for RemoveTypeExport {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        verify_no_rule_properties(&properties)?;
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        REMOVE_TYPE_EXPORT_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        RuleProperties::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> RemoveTypeExport {
+        RemoveTypeExport::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_remove_type_export", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'remove_type_export',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn returns_names_of_exported_types() {
+        let mut block = crate::Parser::default()
+            .parse("export type A = string type B = number export type function C(t) return t end")
+            .unwrap();
+
+        assert_eq!(remove_type_exports(&mut block), vec!["A", "C"]);
+    }
+}
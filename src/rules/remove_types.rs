@@ -4,11 +4,19 @@ use crate::rules::{
     Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
 };
 
-use super::verify_no_rule_properties;
-
+use super::{remove_type_assertion, verify_no_rule_properties};
+
+/// Removes the types from the nodes that own them.
+///
+/// The `process_type*` hooks of the traversal receive the `Type` itself and not the
+/// annotation that holds it, so they can rewrite a type but cannot remove it from its
+/// parent node. Each owner is cleared in its own hook instead, which runs before the
+/// visitor descends into the owner's types. Any type that still reaches `process_type`
+/// is therefore an annotation that this processor does not remove.
 #[derive(Default)]
 struct RemoveTypesProcessor {
     evaluator: Evaluator,
+    remaining_types: usize,
 }
 
 impl NodeProcessor for RemoveTypesProcessor {
@@ -49,20 +57,16 @@ impl NodeProcessor for RemoveTypesProcessor {
 
     fn process_expression(&mut self, expression: &mut Expression) {
         match expression {
-            Expression::TypeCast(type_cast) => {
-                let value = type_cast.get_expression();
-                if self.evaluator.can_return_multiple_values(value) {
-                    *expression = value.clone().in_parentheses();
-                } else {
-                    *expression = value.clone();
-                }
-            }
             Expression::Function(function) => {
                 function.clear_types();
             }
-            _ => {}
+            _ => remove_type_assertion(expression, &self.evaluator),
         }
     }
+
+    fn process_type(&mut self, _: &mut Type) {
+        self.remaining_types += 1;
+    }
 }
 
 pub const REMOVE_TYPES_RULE_NAME: &str = "remove_types";
@@ -75,6 +79,14 @@ impl FlawlessRule for RemoveTypes {
     fn flawless_process(&self, block: &mut Block, _: &Context) {
         let mut processor = RemoveTypesProcessor::default();
         DefaultVisitor::visit_block(block, &mut processor);
+
+        if processor.remaining_types != 0 {
+            log::warn!(
+                "{} failed to remove {} type annotation(s)",
+                REMOVE_TYPES_RULE_NAME,
+                processor.remaining_types
+            );
+        }
     }
 }
 
@@ -104,6 +116,34 @@ mod test {
         RemoveTypes::default()
     }
 
+    #[test]
+    fn removes_types_before_they_are_visited() {
+        let mut block = crate::Parser::default()
+            .parse(
+                r#"
+            type Old = { value: number }
+            export type Pair<T = Old> = { first: Old, [string]: Map<string, Array<Old>> }
+            type function f(t) return t end
+            export type function g(t) return t end
+            local value: Old? = nil
+            local function f(a: Old | typeof(b), ...: Old & Other): (Old) -> Module.Old
+                return a :: Array<{ Old }>
+            end
+            function m.f<T>(a: T, ...: T): ...T end
+            local g = function(a: string, ...: number): (string, ...number) end
+            for i: number = 1, 2 do end
+            for k: string, v: { Old } in pairs(t) do end
+            local s = `{value :: string}`
+            "#,
+            )
+            .unwrap();
+
+        let mut processor = RemoveTypesProcessor::default();
+        DefaultVisitor::visit_block(&mut block, &mut processor);
+
+        pretty_assertions::assert_eq!(processor.remaining_types, 0);
+    }
+
     #[test]
     fn serialize_default_rule() {
         let rule: Box<dyn Rule> = Box::new(new_rule());
@@ -1,11 +1,13 @@
 use crate::nodes::*;
-use crate::process::processors::FindUsage;
-use crate::process::{DefaultVisitor, Evaluator, NodeProcessor, NodeVisitor, ScopeVisitor};
+use crate::process::{
+    Binding, BindingTable, DefaultVisitor, Evaluator, NodeProcessor, NodeVisitor,
+};
 use crate::rules::{
     Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
 };
 use crate::utils::expressions_as_statement;
 
+use super::inject_decoy_code::is_decoy_name;
 use super::verify_no_rule_properties;
 
 #[derive(Default)]
@@ -22,80 +24,30 @@ impl RemoveUnusedVariableProcessor {
 
 impl NodeProcessor for RemoveUnusedVariableProcessor {
     fn process_scope(&mut self, block: &mut Block, extra: Option<&mut Expression>) {
-        let length = block.statements_len();
+        let bindings = BindingTable::from_scope(block, extra);
+        let mut root_bindings = bindings.iter_root_bindings();
 
-        let assignments = block
-            .reverse_iter_statements()
+        let usages = block
+            .iter_statements()
             .enumerate()
-            .filter_map(|(i, statement)| match statement {
-                Statement::LocalAssign(assignment) => {
-                    let identifiers = assignment
-                        .get_variables()
-                        .iter()
-                        .map(TypedIdentifier::get_identifier)
-                        .map(Identifier::get_name)
-                        .map(ToOwned::to_owned)
-                        .collect::<Vec<_>>();
-
-                    Some((length - i - 1, identifiers))
-                }
-                Statement::LocalFunction(function) => {
-                    Some((length - i - 1, vec![function.get_name().to_owned()]))
-                }
-                _ => None,
-            })
-            .collect::<Vec<_>>();
-
-        let usages_in_extra = if let Some(expression) = extra {
-            let mut found_identifiers = Vec::new();
-            for (_, identifiers) in assignments.iter() {
-                for identifier in identifiers {
-                    let mut find_usage = FindUsage::new(identifier);
-                    ScopeVisitor::visit_expression(expression, &mut find_usage);
-                    if find_usage.has_found_usage() {
-                        found_identifiers.push(identifier.to_owned());
-                    }
-                }
-            }
-            found_identifiers
-        } else {
-            Vec::new()
-        };
-
-        let usages = assignments
-            .into_iter()
-            .map(|(index, identifiers)| {
-                let usages = identifiers
-                    .into_iter()
-                    .map(|identifier| {
-                        let mut find_usage = FindUsage::new(&identifier);
-
-                        block
-                            .iter_mut_statements()
-                            .skip(index + 1)
-                            .any(|next_statement| {
-                                ScopeVisitor::visit_statement(next_statement, &mut find_usage);
-                                find_usage.has_found_usage()
-                            })
-                            || block
-                                .mutate_last_statement()
-                                .into_iter()
-                                .any(|last_statement| {
-                                    ScopeVisitor::visit_last_statement(
-                                        last_statement,
-                                        &mut find_usage,
-                                    );
-                                    find_usage.has_found_usage()
-                                })
-                            || usages_in_extra.contains(&identifier)
-                    })
+            .filter_map(|(index, statement)| {
+                let count = match statement {
+                    Statement::LocalAssign(assignment) => assignment.variables_len(),
+                    Statement::LocalFunction(_) => 1,
+                    _ => return None,
+                };
+
+                let usages = root_bindings
+                    .by_ref()
+                    .take(count)
+                    .map(|binding| binding.is_referenced() || is_decoy_name(binding.get_name()))
                     .collect::<Vec<_>>();
 
-                (index, usages)
+                Some((index, usages))
             })
             .collect::<Vec<_>>();
 
-        let mut usages_iter = usages.into_iter().rev();
+        let mut usages_iter = usages.into_iter();
 
         if let Some((mut find_next_index, mut usages)) = usages_iter.next() {
             let mut i = 0;
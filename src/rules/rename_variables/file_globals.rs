@@ -0,0 +1,154 @@
+use crate::nodes::{
+    Block, Expression, FieldExpression, FunctionStatement, Identifier, IndexExpression, Prefix,
+};
+use crate::process::{IdentifierTracker, NodeProcessor, NodeVisitor, ScopeVisitor};
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ops;
+
+// the name of the global table, which can read or write any global with a dynamic name
+const GLOBAL_TABLE: &str = "_G";
+
+// functions that give access to the global variables without naming them
+const GLOBAL_ENVIRONMENT_ACCESSORS: [&str; 5] =
+    ["getfenv", "setfenv", "load", "loadstring", "_ENV"];
+
+#[derive(Debug, Default)]
+struct GlobalUsage {
+    defined: bool,
+    used_before_definition: bool,
+    accessed_from_global_table: bool,
+}
+
+/// Finds the global variables that can be renamed because they are only used inside the
+/// file.
+///
+/// A global is renameable when it is defined with a function statement at the root of
+/// the file (`function Foo() end`, but not `function Foo.bar() end`) and the file never:
+/// - reads or writes it before that definition (even from a function defined earlier)
+/// - accesses it with a field or a string index of `_G` (`_G.Foo` or `_G["Foo"]`)
+///
+/// When the file uses `_G` in any other way (like `_G[name]` or `pairs(_G)`) or refers to
+/// `getfenv`, `setfenv`, `load`, `loadstring` or `_ENV`, the globals can be accessed
+/// without being named, so none of them are renamed.
+#[derive(Debug, Default)]
+pub(crate) struct FileGlobals {
+    identifier_tracker: IdentifierTracker,
+    usages: HashMap<String, GlobalUsage>,
+    global_names: HashSet<String>,
+    global_table_references: usize,
+    global_table_named_accesses: usize,
+    environment_accessed: bool,
+}
+
+impl FileGlobals {
+    pub(crate) fn find(block: &mut Block) -> Self {
+        let mut file_globals = Self::default();
+        ScopeVisitor::visit_block(block, &mut file_globals);
+        file_globals
+    }
+
+    fn can_access_any_global(&self) -> bool {
+        self.environment_accessed || self.global_table_references > self.global_table_named_accesses
+    }
+
+    /// The globals that can be renamed, sorted to generate the same names on each run.
+    pub(crate) fn renameable(&self) -> BTreeSet<String> {
+        if self.can_access_any_global() {
+            return BTreeSet::new();
+        }
+
+        self.usages
+            .iter()
+            .filter(|(_, usage)| {
+                usage.defined && !usage.used_before_definition && !usage.accessed_from_global_table
+            })
+            .map(|(name, _)| name.to_owned())
+            .collect()
+    }
+
+    /// Every global name used in the file.
+    pub(crate) fn iter_global_names(&self) -> impl Iterator<Item = &str> {
+        self.global_names.iter().map(String::as_str)
+    }
+
+    fn refers_to_global(&self, name: &str) -> bool {
+        !self.identifier_tracker.is_identifier_used(name)
+    }
+
+    fn access_from_global_table(&mut self, prefix: &Prefix, name: &str) {
+        if let Prefix::Identifier(identifier) = prefix {
+            if identifier.get_name() == GLOBAL_TABLE && self.refers_to_global(GLOBAL_TABLE) {
+                self.global_table_named_accesses += 1;
+                self.usages
+                    .entry(name.to_owned())
+                    .or_default()
+                    .accessed_from_global_table = true;
+            }
+        }
+    }
+}
+
+impl ops::Deref for FileGlobals {
+    type Target = IdentifierTracker;
+
+    fn deref(&self) -> &Self::Target {
+        &self.identifier_tracker
+    }
+}
+
+impl ops::DerefMut for FileGlobals {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.identifier_tracker
+    }
+}
+
+impl NodeProcessor for FileGlobals {
+    fn process_function_statement(&mut self, function: &mut FunctionStatement) {
+        let name = function.get_name();
+
+        if self.scope_depth() == 1
+            && name.get_field_names().is_empty()
+            && !name.has_method()
+            && self.refers_to_global(name.get_name().get_name())
+        {
+            self.usages
+                .entry(name.get_name().get_name().to_owned())
+                .or_default()
+                .defined = true;
+        }
+    }
+
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        let name = identifier.get_name();
+
+        if !self.refers_to_global(name) {
+            return;
+        }
+
+        if name == GLOBAL_TABLE {
+            self.global_table_references += 1;
+        } else if GLOBAL_ENVIRONMENT_ACCESSORS.contains(&name.as_str()) {
+            self.environment_accessed = true;
+        }
+
+        self.global_names.insert(name.to_owned());
+
+        let usage = self.usages.entry(name.to_owned()).or_default();
+        if !usage.defined {
+            usage.used_before_definition = true;
+        }
+    }
+
+    fn process_field_expression(&mut self, field: &mut FieldExpression) {
+        let name = field.get_field().get_name().to_owned();
+        self.access_from_global_table(field.get_prefix(), &name);
+    }
+
+    fn process_index_expression(&mut self, index: &mut IndexExpression) {
+        if let Expression::String(string) = index.get_index() {
+            let name = string.get_value().to_owned();
+            self.access_from_global_table(index.get_prefix(), &name);
+        }
+    }
+}
@@ -1,12 +1,13 @@
-mod function_names;
+mod file_globals;
 mod globals;
 mod rename_processor;
 
+use file_globals::FileGlobals;
 use rename_processor::RenameProcessor;
 
 use crate::nodes::Block;
 use crate::process::utils::is_valid_identifier;
-use crate::process::{DefaultVisitor, NodeVisitor, ScopeVisitor};
+use crate::process::{BindingKind, BindingTable, NodeVisitor, ScopeVisitor};
 use crate::rules::{
     Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
     RulePropertyValue,
@@ -22,6 +23,7 @@ pub const RENAME_VARIABLES_RULE_NAME: &str = "rename_variables";
 pub struct RenameVariables {
     globals: Vec<String>,
     include_functions: bool,
+    include_file_globals: bool,
 }
 
 impl RenameVariables {
@@ -29,6 +31,7 @@ impl RenameVariables {
         Self {
             globals: Vec::from_iter(iter),
             include_functions: false,
+            include_file_globals: false,
         }
     }
 
@@ -37,6 +40,14 @@ impl RenameVariables {
         self
     }
 
+    /// Also rename the global functions defined at the root of the processed file, when
+    /// they are not used before their definition or accessed through `_G`. Use this only
+    /// when no other code accesses these globals.
+    pub fn with_file_globals(mut self) -> Self {
+        self.include_file_globals = true;
+        self
+    }
+
     fn set_globals(&mut self, list: Vec<String>) -> Result<(), RuleConfigurationError> {
         for value in list {
             match value.as_str() {
@@ -98,15 +109,40 @@ impl FlawlessRule for RenameVariables {
         let avoid_identifiers = if self.include_functions {
             Vec::new()
         } else {
-            let mut collect_functions = function_names::CollectFunctionNames::default();
-            DefaultVisitor::visit_block(block, &mut collect_functions);
-            collect_functions.into()
+            BindingTable::from_block(block)
+                .iter_bindings()
+                .filter(|binding| binding.get_kind() == BindingKind::LocalFunction)
+                .map(|binding| binding.get_name().to_owned())
+                .collect()
+        };
+
+        let (file_globals, external_globals) = if self.include_file_globals {
+            let file_globals = FileGlobals::find(block);
+            let renameable: Vec<_> = file_globals
+                .renameable()
+                .into_iter()
+                .filter(|name| !self.globals.contains(name))
+                .collect();
+            let external_globals: Vec<_> = file_globals
+                .iter_global_names()
+                .filter(|name| !renameable.iter().any(|renamed| renamed == name))
+                .map(ToOwned::to_owned)
+                .collect();
+
+            (renameable, external_globals)
+        } else {
+            (Vec::new(), Vec::new())
         };
 
         let mut processor = RenameProcessor::new(
-            self.globals.clone().into_iter().chain(avoid_identifiers),
+            self.globals
+                .clone()
+                .into_iter()
+                .chain(avoid_identifiers)
+                .chain(external_globals),
             self.include_functions,
         );
+        processor.rename_globals(file_globals);
         ScopeVisitor::visit_block(block, &mut processor);
     }
 }
@@ -121,6 +157,9 @@ impl RuleConfiguration for RenameVariables {
                 "include_functions" => {
                     self.include_functions = value.expect_bool(&key)?;
                 }
+                "include_file_globals" => {
+                    self.include_file_globals = value.expect_bool(&key)?;
+                }
                 _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
             }
         }
@@ -150,6 +189,32 @@ impl RuleConfiguration for RenameVariables {
             );
         }
 
+        if self.include_file_globals {
+            properties.insert(
+                "include_file_globals".to_owned(),
+                RulePropertyValue::Boolean(self.include_file_globals),
+            );
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert(
+            "globals".to_owned(),
+            RulePropertyValue::StringList(self.normalize_globals()),
+        );
+        properties.insert(
+            "include_functions".to_owned(),
+            RulePropertyValue::Boolean(self.include_functions),
+        );
+        properties.insert(
+            "include_file_globals".to_owned(),
+            RulePropertyValue::Boolean(self.include_file_globals),
+        );
+
         properties
     }
 }
@@ -40,6 +40,15 @@ impl RenameProcessor {
         }
     }
 
+    /// Renames the given globals in the whole block. Their new names are never reused
+    /// for local variables.
+    pub fn rename_globals<I: IntoIterator<Item = String>>(&mut self, globals: I) {
+        for global in globals {
+            let obfuscated_name = self.generate_identifier();
+            self.add(global, obfuscated_name, false);
+        }
+    }
+
     pub fn get_obfuscated_name(&self, real: &str) -> Option<&String> {
         self.real_to_obfuscated
             .iter()
@@ -0,0 +1,349 @@
+use std::collections::BTreeMap;
+use std::{iter, ops};
+
+use crate::nodes::{Block, Expression, FieldExpression, Prefix, SourcePosition, Token};
+use crate::process::utils::is_valid_identifier;
+use crate::process::{IdentifierTracker, NodeProcessor, NodeVisitor, ScopeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+use super::verify_required_properties;
+
+struct ReplaceCallsProcessor<'a> {
+    identifier_tracker: IdentifierTracker,
+    mapping: &'a BTreeMap<String, String>,
+    replace_references: bool,
+    warn_only: bool,
+    context: &'a Context<'a, 'a, 'a>,
+}
+
+impl ops::Deref for ReplaceCallsProcessor<'_> {
+    type Target = IdentifierTracker;
+
+    fn deref(&self) -> &Self::Target {
+        &self.identifier_tracker
+    }
+}
+
+impl ops::DerefMut for ReplaceCallsProcessor<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.identifier_tracker
+    }
+}
+
+impl<'a> ReplaceCallsProcessor<'a> {
+    /// Returns the replacement of the given path, if the path is mapped and its root
+    /// identifier is a global variable.
+    fn find_replacement(
+        &self,
+        root: &str,
+        path: &str,
+        position: Option<SourcePosition>,
+    ) -> Option<&'a str> {
+        let replacement = self.mapping.get(path)?;
+
+        if self.is_identifier_used(root) {
+            return None;
+        }
+
+        let position = self.context.format_position(position);
+
+        if self.warn_only {
+            log::warn!(
+                "`{}` should be replaced with `{}` (at `{}`)",
+                path,
+                replacement,
+                position
+            );
+            return None;
+        }
+
+        let replacement_root = get_root(replacement);
+        if self.is_identifier_used(replacement_root) {
+            log::warn!(
+                "unable to replace `{}` with `{}` because `{}` is shadowed by a local variable (at `{}`)",
+                path,
+                replacement,
+                replacement_root,
+                position
+            );
+            return None;
+        }
+
+        Some(replacement.as_str())
+    }
+}
+
+impl NodeProcessor for ReplaceCallsProcessor<'_> {
+    fn process_expression(&mut self, expression: &mut Expression) {
+        if !self.replace_references {
+            return;
+        }
+
+        let replacement = match &*expression {
+            Expression::Identifier(identifier) => self.find_replacement(
+                identifier.get_name(),
+                identifier.get_name(),
+                identifier.get_token().and_then(Token::start_position),
+            ),
+            Expression::Field(field) => get_field_path(field).and_then(|(root, path)| {
+                self.find_replacement(root, &path, field.get_prefix().start_position())
+            }),
+            _ => None,
+        };
+
+        if let Some(replacement) = replacement {
+            *expression = build_prefix(replacement).into();
+        }
+    }
+
+    fn process_prefix_expression(&mut self, prefix: &mut Prefix) {
+        let replacement = get_path(prefix)
+            .and_then(|(root, path)| self.find_replacement(root, &path, prefix.start_position()));
+
+        if let Some(replacement) = replacement {
+            *prefix = build_prefix(replacement);
+        }
+    }
+}
+
+/// Returns the root identifier and the dotted path of a prefix made only of identifiers
+/// and field accesses.
+fn get_path(prefix: &Prefix) -> Option<(&str, String)> {
+    match prefix {
+        Prefix::Identifier(identifier) => Some((
+            identifier.get_name().as_str(),
+            identifier.get_name().to_owned(),
+        )),
+        Prefix::Field(field) => get_field_path(field),
+        _ => None,
+    }
+}
+
+fn get_field_path(field: &FieldExpression) -> Option<(&str, String)> {
+    let (root, mut path) = get_path(field.get_prefix())?;
+    path.push('.');
+    path.push_str(field.get_field().get_name());
+    Some((root, path))
+}
+
+fn get_root(path: &str) -> &str {
+    path.split('.').next().unwrap_or(path)
+}
+
+fn build_prefix(path: &str) -> Prefix {
+    let mut names = path.split('.');
+    let mut prefix = Prefix::from_name(names.next().unwrap_or(path));
+
+    for name in names {
+        prefix = FieldExpression::new(prefix, name).into();
+    }
+
+    prefix
+}
+
+fn is_valid_path(path: &str) -> bool {
+    path.split('.').all(is_valid_identifier)
+}
+
+pub const REPLACE_CALLS_RULE_NAME: &str = "replace_calls";
+
+/// A rule that replaces calls and accesses to global functions or values using a mapping
+/// of dotted paths.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ReplaceCalls {
+    mapping: BTreeMap<String, String>,
+    replace_references: bool,
+    warn_only: bool,
+}
+
+impl ReplaceCalls {
+    pub fn new(mapping: impl IntoIterator<Item = (String, String)>) -> Self {
+        Self {
+            mapping: mapping.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_references(mut self) -> Self {
+        self.replace_references = true;
+        self
+    }
+
+    pub fn with_warnings_only(mut self) -> Self {
+        self.warn_only = true;
+        self
+    }
+}
+
+impl FlawlessRule for ReplaceCalls {
+    fn flawless_process(&self, block: &mut Block, context: &Context) {
+        if self.mapping.is_empty() {
+            return;
+        }
+
+        let mut processor = ReplaceCallsProcessor {
+            identifier_tracker: IdentifierTracker::new(),
+            mapping: &self.mapping,
+            replace_references: self.replace_references,
+            warn_only: self.warn_only,
+            context,
+        };
+        ScopeVisitor::visit_block(block, &mut processor);
+    }
+}
+
+impl RuleConfiguration for ReplaceCalls {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        verify_required_properties(&properties, &["mapping"])?;
+
+        for (key, value) in properties {
+            match key.as_str() {
+                "mapping" => {
+                    let mapping = value.expect_string_map(&key)?;
+
+                    if let Some(invalid_path) = mapping
+                        .iter()
+                        .flat_map(|(path, replacement)| {
+                            iter::once(path).chain(iter::once(replacement))
+                        })
+                        .find(|path| !is_valid_path(path))
+                    {
+                        return Err(RuleConfigurationError::UnexpectedValue {
+                            property: key,
+                            message: format!(
+                                "invalid path `{}` (must be identifiers separated by `.`)",
+                                invalid_path
+                            ),
+                        });
+                    }
+
+                    self.mapping = mapping;
+                }
+                "replace_references" => {
+                    self.replace_references = value.expect_bool(&key)?;
+                }
+                "warn_only" => {
+                    self.warn_only = value.expect_bool(&key)?;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        REPLACE_CALLS_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert("mapping".to_owned(), self.mapping.clone().into());
+
+        if self.replace_references {
+            properties.insert("replace_references".to_owned(), true.into());
+        }
+        if self.warn_only {
+            properties.insert("warn_only".to_owned(), true.into());
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert("mapping".to_owned(), self.mapping.clone().into());
+        properties.insert(
+            "replace_references".to_owned(),
+            self.replace_references.into(),
+        );
+        properties.insert("warn_only".to_owned(), self.warn_only.into());
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> ReplaceCalls {
+        ReplaceCalls::new(vec![(
+            "utils.deepCopy".to_owned(),
+            "table.clone".to_owned(),
+        )])
+    }
+
+    #[test]
+    fn serialize_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("replace_calls", rule);
+    }
+
+    #[test]
+    fn serialize_rule_with_references_and_warnings() {
+        let rule: Box<dyn Rule> = Box::new(new_rule().with_references().with_warnings_only());
+
+        assert_json_snapshot!("replace_calls_with_references_and_warnings", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'replace_calls',
+            mapping: {},
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn configure_without_mapping_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'replace_calls',
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "missing required field 'mapping'"
+        );
+    }
+
+    #[test]
+    fn configure_with_invalid_path_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'replace_calls',
+            mapping: { 'utils.': 'table.clone' },
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "unexpected value for field 'mapping': invalid path `utils.` (must be identifiers separated by `.`)"
+        );
+    }
+
+    #[test]
+    fn build_prefix_from_identifier() {
+        assert_eq!(build_prefix("workspace"), Prefix::from_name("workspace"));
+    }
+
+    #[test]
+    fn build_prefix_from_dotted_path() {
+        assert_eq!(
+            build_prefix("table.clone"),
+            FieldExpression::new(Prefix::from_name("table"), "clone").into()
+        );
+    }
+}
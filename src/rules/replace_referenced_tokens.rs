@@ -47,10 +47,18 @@ impl NodeProcessor for Processor<'_> {
         generic_for.replace_referenced_tokens(self.code);
     }
 
+    fn process_goto_statement(&mut self, goto: &mut GotoStatement) {
+        goto.replace_referenced_tokens(self.code);
+    }
+
     fn process_if_statement(&mut self, if_statement: &mut IfStatement) {
         if_statement.replace_referenced_tokens(self.code);
     }
 
+    fn process_label_statement(&mut self, label: &mut LabelStatement) {
+        label.replace_referenced_tokens(self.code);
+    }
+
     fn process_last_statement(&mut self, statement: &mut LastStatement) {
         match statement {
             LastStatement::Break(token) | LastStatement::Continue(token) => {
@@ -1,7 +1,7 @@
 use std::path::{Path, PathBuf};
 
 use crate::{
-    nodes::{Arguments, Expression, FunctionCall, Prefix},
+    nodes::{FunctionCall, Prefix, StringExpression},
     process::IdentifierTracker,
     utils,
 };
@@ -22,19 +22,18 @@ pub(crate) fn is_require_call(call: &FunctionCall, identifier_tracker: &Identifi
     }
 }
 
-pub(crate) fn match_path_require_call(call: &FunctionCall) -> Option<PathBuf> {
-    match call.get_arguments() {
-        Arguments::String(string) => Some(string.get_value()),
-        Arguments::Tuple(tuple) if tuple.len() == 1 => {
-            let expression = tuple.iter_values().next().unwrap();
+/// Returns the string passed to a require call, like `"./module"` in `require("./module")`.
+pub(crate) fn match_require_literal(call: &FunctionCall) -> Option<&str> {
+    let mut values = call.get_arguments().iter_values();
 
-            match expression {
-                Expression::String(string) => Some(string.get_value()),
-                _ => None,
-            }
-        }
+    match (values.next(), values.next()) {
+        (Some(value), None) => value.as_string().map(StringExpression::get_value),
         _ => None,
     }
-    .map(Path::new)
-    .map(utils::normalize_path_with_current_dir)
+}
+
+pub(crate) fn match_path_require_call(call: &FunctionCall) -> Option<PathBuf> {
+    match_require_literal(call)
+        .map(Path::new)
+        .map(utils::normalize_path_with_current_dir)
 }
@@ -1,8 +1,12 @@
 mod match_require;
+mod package_path;
 mod path_iterator;
 mod path_locator;
 mod path_require_mode;
+mod remap_extension;
+mod rojo_project;
 
-pub(crate) use match_require::{is_require_call, match_path_require_call};
+pub(crate) use match_require::{is_require_call, match_path_require_call, match_require_literal};
 pub(crate) use path_locator::RequirePathLocator;
-pub(crate) use path_require_mode::PathRequireMode;
+pub use path_require_mode::PathRequireMode;
+pub(crate) use remap_extension::remap_require_extensions;
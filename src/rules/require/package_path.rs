@@ -0,0 +1,195 @@
+use std::path::{Path, PathBuf};
+
+use crate::{utils, DarkluaError, Resources};
+
+const TEMPLATE_SEPARATOR: char = ';';
+const MODULE_NAME_PLACEHOLDER: char = '?';
+const MODULE_NAME_SEPARATOR: char = '.';
+
+fn iter_templates(package_path: &str) -> impl Iterator<Item = &str> {
+    package_path
+        .split(TEMPLATE_SEPARATOR)
+        .map(str::trim)
+        .filter(|template| !template.is_empty())
+}
+
+fn substitute_module_name(template: &str, module_name: &str) -> PathBuf {
+    PathBuf::from(template.replace(
+        MODULE_NAME_PLACEHOLDER,
+        &module_name.replace(MODULE_NAME_SEPARATOR, "/"),
+    ))
+}
+
+/// Returns the module name that makes the template produce the given path (relative to
+/// the project root and separated with `/`). Templates with more than one placeholder
+/// and paths that contain dots outside of the template are not inverted.
+fn invert_template(template: &str, relative_path: &str) -> Option<String> {
+    let template = template.trim_start_matches("./");
+    let (prefix, suffix) = template.split_once(MODULE_NAME_PLACEHOLDER)?;
+
+    if suffix.contains(MODULE_NAME_PLACEHOLDER) {
+        return None;
+    }
+
+    let module_path = relative_path.strip_prefix(prefix)?.strip_suffix(suffix)?;
+
+    if module_path.contains(MODULE_NAME_SEPARATOR) || module_path.split('/').any(str::is_empty) {
+        None
+    } else {
+        Some(module_path.replace('/', "."))
+    }
+}
+
+fn get_relative_path(path: &Path, root: &Path) -> Result<String, DarkluaError> {
+    let path = utils::normalize_path(path);
+    let root = utils::normalize_path(root);
+
+    let relative_path = if root == Path::new(".") || root == Path::new("") {
+        path.as_path()
+    } else {
+        path.strip_prefix(&root).map_err(|_| {
+            DarkluaError::custom(format!(
+                "unable to make `{}` relative to the project root `{}`",
+                path.display(),
+                root.display()
+            ))
+        })?
+    };
+
+    relative_path
+        .components()
+        .map(|component| utils::convert_os_string(component.as_os_str()))
+        .collect::<Result<Vec<_>, _>>()
+        .map(|components| components.join("/"))
+}
+
+/// Finds the file required by a module name using a `package.path` style list of
+/// templates. The first template that leads to an existing file wins.
+pub(crate) fn find_package_path(
+    package_path: &str,
+    module_name: &str,
+    root: &Path,
+    is_file: impl Fn(&Path) -> Result<bool, DarkluaError>,
+) -> Result<PathBuf, DarkluaError> {
+    let potential_paths: Vec<_> = iter_templates(package_path)
+        .map(|template| {
+            utils::normalize_path_with_current_dir(
+                root.join(substitute_module_name(template, module_name)),
+            )
+        })
+        .collect();
+
+    for potential_path in potential_paths.iter() {
+        if is_file(potential_path)? {
+            return Ok(potential_path.clone());
+        }
+    }
+
+    Err(
+        DarkluaError::resource_not_found(module_name).context(format!(
+            "tried `{}`",
+            potential_paths
+                .iter()
+                .map(|potential_path| potential_path.display().to_string())
+                .collect::<Vec<_>>()
+                .join("`, `")
+        )),
+    )
+}
+
+/// Generates the module name that requires the given path using a `package.path` style
+/// list of templates. When multiple templates can produce the path, the shortest module
+/// name that still resolves to the path is used.
+pub(crate) fn generate_module_name(
+    package_path: &str,
+    path: &Path,
+    root: &Path,
+    resources: &Resources,
+) -> Result<String, DarkluaError> {
+    let relative_path = get_relative_path(path, root)?;
+
+    let mut module_names: Vec<_> = iter_templates(package_path)
+        .filter_map(|template| invert_template(template, &relative_path))
+        .collect();
+    module_names.sort_by_key(String::len);
+
+    let expected_path = utils::normalize_path(path);
+
+    for module_name in module_names {
+        let resolves_to_path = find_package_path(package_path, &module_name, root, |path| {
+            Ok(resources.is_file(path)?)
+        })
+        .map(|required_path| utils::normalize_path(required_path) == expected_path)
+        .unwrap_or(false);
+
+        if resolves_to_path {
+            return Ok(module_name);
+        }
+    }
+
+    Err(DarkluaError::custom(format!(
+        "unable to generate a module name for `{}` with the package path `{}`",
+        path.display(),
+        package_path
+    )))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn substitute_dotted_module_name() {
+        pretty_assertions::assert_eq!(
+            substitute_module_name("lib/?.lua", "foo.bar"),
+            PathBuf::from("lib/foo/bar.lua")
+        );
+    }
+
+    #[test]
+    fn substitute_module_name_in_init_template() {
+        pretty_assertions::assert_eq!(
+            substitute_module_name("?/init.lua", "foo"),
+            PathBuf::from("foo/init.lua")
+        );
+    }
+
+    #[test]
+    fn iter_templates_skips_empty_segments() {
+        pretty_assertions::assert_eq!(
+            iter_templates("?.lua;;lib/?.lua;").collect::<Vec<_>>(),
+            vec!["?.lua", "lib/?.lua"]
+        );
+    }
+
+    #[test]
+    fn invert_template_with_prefix() {
+        pretty_assertions::assert_eq!(
+            invert_template("lib/?.lua", "lib/foo/bar.lua"),
+            Some("foo.bar".to_owned())
+        );
+    }
+
+    #[test]
+    fn invert_template_with_current_dir() {
+        pretty_assertions::assert_eq!(
+            invert_template("./?.lua", "foo.lua"),
+            Some("foo".to_owned())
+        );
+    }
+
+    #[test]
+    fn invert_template_with_unmatched_prefix() {
+        pretty_assertions::assert_eq!(invert_template("lib/?.lua", "src/foo.lua"), None);
+    }
+
+    #[test]
+    fn invert_template_with_dot_in_file_name() {
+        pretty_assertions::assert_eq!(invert_template("?.lua", "foo.spec.lua"), None);
+    }
+
+    #[test]
+    fn invert_template_with_multiple_placeholders() {
+        pretty_assertions::assert_eq!(invert_template("?/?.lua", "foo/foo.lua"), None);
+    }
+}
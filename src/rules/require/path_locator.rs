@@ -1,13 +1,19 @@
-use std::path::{Path, PathBuf};
+use std::cell::RefCell;
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
 
-use super::{path_iterator, PathRequireMode};
+use super::rojo_project::{self, DEFAULT_PROJECT_FILE_NAME};
+use super::{package_path, path_iterator, PathRequireMode};
 use crate::{utils, DarkluaError, Resources};
 
+const MAX_PROJECT_FILE_DEPTH: usize = 8;
+
 #[derive(Debug)]
 pub(crate) struct RequirePathLocator<'a, 'b, 'resources> {
     path_require_mode: &'a PathRequireMode,
     extra_module_relative_location: &'b Path,
     resources: &'resources Resources,
+    checked_paths: RefCell<Vec<PathBuf>>,
 }
 
 impl<'a, 'b, 'c> RequirePathLocator<'a, 'b, 'c> {
@@ -20,21 +26,62 @@ impl<'a, 'b, 'c> RequirePathLocator<'a, 'b, 'c> {
             path_require_mode,
             extra_module_relative_location,
             resources,
+            checked_paths: Default::default(),
         }
     }
 
+    /// Returns the paths checked while resolving requires, including the ones that do not
+    /// exist. Adding or removing any of these files can change which file is required.
+    pub(crate) fn take_checked_paths(&self) -> Vec<PathBuf> {
+        self.checked_paths.take()
+    }
+
+    fn is_file(&self, path: &Path) -> Result<bool, DarkluaError> {
+        self.checked_paths.borrow_mut().push(path.to_path_buf());
+        Ok(self.resources.is_file(path)?)
+    }
+
     pub(crate) fn find_require_path(
         &self,
         path: impl Into<PathBuf>,
         source: &Path,
     ) -> Result<PathBuf, DarkluaError> {
-        let mut path: PathBuf = path.into();
+        let path: PathBuf = path.into();
+        let (require_path, source_location) = self.locate_require_path(path.clone(), source)?;
+
+        if self.path_require_mode.is_enforcing_project_boundary() {
+            self.verify_project_boundary(&path, &require_path, source_location.as_deref())?;
+        }
+
+        Ok(require_path)
+    }
+
+    /// Finds the path of the required file, with the location of the source used by the
+    /// require (if any).
+    fn locate_require_path(
+        &self,
+        mut path: PathBuf,
+        source: &Path,
+    ) -> Result<(PathBuf, Option<PathBuf>), DarkluaError> {
         log::trace!(
             "find require path for `{}` from `{}`",
             path.display(),
             source.display()
         );
 
+        if let Some(package_path) = self.path_require_mode.package_path() {
+            let module_name = utils::convert_os_string(path.as_os_str())?;
+            return package_path::find_package_path(
+                package_path,
+                module_name,
+                self.extra_module_relative_location,
+                |path| self.is_file(path),
+            )
+            .map(|require_path| (require_path, None));
+        }
+
+        let mut source_location = None;
+
         if is_require_relative(&path) {
             let mut new_path = source.to_path_buf();
             new_path.pop();
@@ -63,6 +110,31 @@ impl<'a, 'b, 'c> RequirePathLocator<'a, 'b, 'c> {
                             )
                         })?,
                 );
+                source_location = Some(extra_module_location.clone());
+
+                if let Some(alias_file) = self.find_alias_file(&extra_module_location)? {
+                    let remainder: PathBuf = components.collect();
+
+                    if remainder.components().next().is_some() {
+                        return Err(DarkluaError::invalid_resource_path(
+                            path.display().to_string(),
+                            format!(
+                                "`{}` points to the file `{}`, so it cannot be followed by `{}`",
+                                source_name,
+                                alias_file.display(),
+                                remainder.display()
+                            ),
+                        ));
+                    }
+
+                    log::trace!(
+                        "resolve `{}` to the file `{}`",
+                        source_name,
+                        alias_file.display()
+                    );
+                    return Ok((alias_file, source_location));
+                }
+
                 extra_module_location.extend(components);
                 path = extra_module_location;
             }
@@ -70,20 +142,128 @@ impl<'a, 'b, 'c> RequirePathLocator<'a, 'b, 'c> {
         // else: the path is absolute so darklua should attempt to require it directly
 
         let normalized_path = utils::normalize_path_with_current_dir(&path);
+        self.resolve_path(&normalized_path, 0)
+            .map(|require_path| (require_path, source_location))
+    }
+
+    /// Returns the file targeted by a source or an alias, when it points to a file instead
+    /// of a directory. The extension of the file can be omitted.
+    fn find_alias_file(&self, location: &Path) -> Result<Option<PathBuf>, DarkluaError> {
+        let location = utils::normalize_path_with_current_dir(location);
+
+        if self.resources.is_directory(&location)? {
+            return Ok(None);
+        }
+
+        if self.is_file(&location)? {
+            return Ok(Some(location));
+        }
+
+        if let Some(file_name) = location.file_name().and_then(OsStr::to_str) {
+            for extension in &["luau", "lua"] {
+                let potential_path =
+                    location.with_file_name(format!("{}.{}", file_name, extension));
+                if self.is_file(&potential_path)? {
+                    return Ok(Some(potential_path));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn verify_project_boundary(
+        &self,
+        literal_path: &Path,
+        require_path: &Path,
+        source_location: Option<&Path>,
+    ) -> Result<(), DarkluaError> {
+        let resolved_location = self.canonical_location(require_path);
+
+        let is_inside =
+            |location: &Path| is_descendant(&resolved_location, &self.canonical_location(location));
+
+        let allowed_by_source = !self.path_require_mode.is_strict_project_boundary()
+            && source_location.map(is_inside).unwrap_or(false);
+
+        if allowed_by_source || is_inside(self.extra_module_relative_location) {
+            Ok(())
+        } else {
+            Err(DarkluaError::invalid_resource_path(
+                literal_path.display().to_string(),
+                format!(
+                    "the resolved path `{}` is outside of the project",
+                    require_path.display()
+                ),
+            ))
+        }
+    }
+
+    /// Resolves the location like the resources do (following symbolic links on the
+    /// file system), so that paths written differently can be compared.
+    fn canonical_location(&self, location: &Path) -> PathBuf {
+        let location = if location == Path::new("") {
+            Path::new(".")
+        } else {
+            location
+        };
+        self.resources
+            .canonicalize(location)
+            .unwrap_or_else(|_| utils::normalize_path(location))
+    }
+
+    fn resolve_path(
+        &self,
+        normalized_path: &Path,
+        project_depth: usize,
+    ) -> Result<PathBuf, DarkluaError> {
         for potential_path in path_iterator::find_require_paths(
-            &normalized_path,
+            normalized_path,
             self.path_require_mode.module_folder_name(),
         ) {
-            if self.resources.is_file(&potential_path)? {
+            if self.is_file(&potential_path)? {
                 return Ok(utils::normalize_path_with_current_dir(potential_path));
             }
         }
 
+        // a folder with a project file is a nested Rojo project (like Wally packages), so
+        // the resolution continues into the `$path` of its root node
+        let project_path = normalized_path.join(DEFAULT_PROJECT_FILE_NAME);
+        if self.is_file(&project_path)? {
+            if project_depth >= MAX_PROJECT_FILE_DEPTH {
+                return Err(DarkluaError::custom(format!(
+                    "unable to resolve `{}`: too many nested Rojo project files (the limit is {})",
+                    normalized_path.display(),
+                    MAX_PROJECT_FILE_DEPTH
+                )));
+            }
+
+            let content = self.resources.get(&project_path)?;
+            let root_path = rojo_project::get_project_root_path(&content, &project_path)?;
+            log::trace!(
+                "resolve Rojo project file `{}` to `{}`",
+                project_path.display(),
+                root_path.display()
+            );
+
+            return self
+                .resolve_path(
+                    &utils::normalize_path_with_current_dir(root_path),
+                    project_depth + 1,
+                )
+                .map_err(|err| {
+                    err.context(format!(
+                        "while resolving Rojo project file `{}`",
+                        project_path.display()
+                    ))
+                });
+        }
+
         Err(
-            DarkluaError::resource_not_found(&normalized_path).context(format!(
+            DarkluaError::resource_not_found(normalized_path).context(format!(
                 "tried `{}`",
                 path_iterator::find_require_paths(
-                    &normalized_path,
+                    normalized_path,
                     self.path_require_mode.module_folder_name(),
                 )
                 .map(|potential_path| potential_path.display().to_string())
@@ -94,6 +274,16 @@ impl<'a, 'b, 'c> RequirePathLocator<'a, 'b, 'c> {
     }
 }
 
+fn is_descendant(path: &Path, location: &Path) -> bool {
+    if location == Path::new(".") {
+        // the current directory contains every relative path that does not start
+        // with `..`
+        !path.has_root() && !matches!(path.components().next(), Some(Component::ParentDir))
+    } else {
+        path.starts_with(location)
+    }
+}
+
 // the `is_relative` method from std::path::Path is not what darklua needs
 // to consider a require relative, which are paths that starts with `.` or `..`
 fn is_require_relative(path: &Path) -> bool {
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::frontend::DarkluaResult;
-use crate::nodes::FunctionCall;
+use crate::nodes::{Arguments, FunctionCall, StringExpression};
 use crate::rules::require::match_path_require_call;
 use crate::rules::Context;
 use crate::utils::find_luau_configuration;
@@ -11,6 +11,7 @@ use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 
+use super::package_path::generate_module_name;
 use super::RequirePathLocator;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -23,6 +24,12 @@ pub struct PathRequireMode {
     module_folder_name: String,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     sources: HashMap<String, PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    package_path: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    enforce_project_boundary: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    strict_project_boundary: bool,
     #[serde(skip)]
     luau_rc_aliases: Option<HashMap<String, PathBuf>>,
 }
@@ -32,6 +39,9 @@ impl Default for PathRequireMode {
         Self {
             module_folder_name: get_default_module_folder_name(),
             sources: Default::default(),
+            package_path: None,
+            enforce_project_boundary: false,
+            strict_project_boundary: false,
             luau_rc_aliases: Default::default(),
         }
     }
@@ -48,20 +58,58 @@ fn is_default_module_folder_name(value: &String) -> bool {
     value == DEFAULT_MODULE_FOLDER_NAME
 }
 
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
 impl PathRequireMode {
     pub fn new(module_folder_name: impl Into<String>) -> Self {
         Self {
             module_folder_name: module_folder_name.into(),
             sources: Default::default(),
+            package_path: None,
+            enforce_project_boundary: false,
+            strict_project_boundary: false,
             luau_rc_aliases: Default::default(),
         }
     }
 
+    /// Rejects the requires that resolve to a file outside of the project location. The
+    /// requires through a source (or an alias of a `.luaurc` file) may still resolve inside
+    /// the location of that source, because sources are configured by the project.
+    pub fn with_project_boundary_enforced(mut self) -> Self {
+        self.enforce_project_boundary = true;
+        self
+    }
+
+    /// Like [`with_project_boundary_enforced`](Self::with_project_boundary_enforced), but
+    /// also rejects the requires through sources that point outside of the project.
+    pub fn with_strict_project_boundary(mut self) -> Self {
+        self.strict_project_boundary = true;
+        self
+    }
+
+    /// Resolves requires with a `package.path` style list of templates separated with
+    /// `;` (like `?.lua;?/init.lua`). The dots of the module name are converted to path
+    /// separators and the result replaces the `?` of each template, relative to the
+    /// project root.
+    pub fn with_package_path(mut self, package_path: impl Into<String>) -> Self {
+        self.package_path = Some(package_path.into());
+        self
+    }
+
     pub(crate) fn initialize(&mut self, context: &Context) -> Result<(), DarkluaError> {
-        self.luau_rc_aliases =
-            find_luau_configuration(context.current_path(), context.resources())?
-                .map(|config| Some(config.aliases))
-                .unwrap_or_default();
+        let search = find_luau_configuration(
+            context.current_path(),
+            context.resources(),
+            context.luau_configuration_cache(),
+        )?;
+
+        for checked_path in search.checked_paths {
+            context.add_file_dependency(checked_path);
+        }
+
+        self.luau_rc_aliases = search.configuration.map(|config| config.aliases);
 
         Ok(())
     }
@@ -70,6 +118,18 @@ impl PathRequireMode {
         &self.module_folder_name
     }
 
+    pub(crate) fn package_path(&self) -> Option<&str> {
+        self.package_path.as_deref()
+    }
+
+    pub(crate) fn is_enforcing_project_boundary(&self) -> bool {
+        self.enforce_project_boundary || self.strict_project_boundary
+    }
+
+    pub(crate) fn is_strict_project_boundary(&self) -> bool {
+        self.strict_project_boundary
+    }
+
     pub(crate) fn get_source(&self, name: &str) -> Option<&Path> {
         self.luau_rc_aliases
             .as_ref()
@@ -84,11 +144,15 @@ impl PathRequireMode {
         context: &Context,
     ) -> DarkluaResult<Option<PathBuf>> {
         if let Some(literal_path) = match_path_require_call(call) {
-            let required_path =
-                RequirePathLocator::new(self, context.project_location(), context.resources())
-                    .find_require_path(literal_path, context.current_path())?;
+            let locator =
+                RequirePathLocator::new(self, context.project_location(), context.resources());
+            let required_path = locator.find_require_path(literal_path, context.current_path());
+
+            for checked_path in locator.take_checked_paths() {
+                context.add_file_dependency(checked_path);
+            }
 
-            Ok(Some(required_path))
+            Ok(Some(required_path?))
         } else {
             Ok(None)
         }
@@ -102,12 +166,25 @@ impl PathRequireMode {
 
     pub(crate) fn generate_require(
         &self,
-        _path: &Path,
+        path: &Path,
         _current_mode: &crate::rules::RequireMode,
-        _context: &Context<'_, '_, '_>,
+        context: &Context<'_, '_, '_>,
     ) -> Result<Option<crate::nodes::Arguments>, crate::DarkluaError> {
-        Err(DarkluaError::custom("unsupported target require mode")
-            .context("path require mode cannot be used"))
+        if let Some(package_path) = self.package_path() {
+            let module_name = generate_module_name(
+                package_path,
+                path,
+                context.project_location(),
+                context.resources(),
+            )?;
+
+            Ok(Some(
+                Arguments::default().with_argument(StringExpression::from_value(module_name)),
+            ))
+        } else {
+            Err(DarkluaError::custom("unsupported target require mode")
+                .context("path require mode cannot be used without a package path"))
+        }
     }
 }
 
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+
+use crate::nodes::{Arguments, Block, Expression, FunctionCall, StringExpression};
+use crate::process::{IdentifierTracker, NodeProcessor, NodeVisitor, ScopeVisitor};
+
+use super::is_require_call;
+
+struct RequireExtensionProcessor<'a> {
+    identifier_tracker: IdentifierTracker,
+    extensions: &'a BTreeMap<String, String>,
+}
+
+impl Deref for RequireExtensionProcessor<'_> {
+    type Target = IdentifierTracker;
+
+    fn deref(&self) -> &Self::Target {
+        &self.identifier_tracker
+    }
+}
+
+impl DerefMut for RequireExtensionProcessor<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.identifier_tracker
+    }
+}
+
+impl NodeProcessor for RequireExtensionProcessor<'_> {
+    fn process_function_call(&mut self, call: &mut FunctionCall) {
+        if !is_require_call(call, self) {
+            return;
+        }
+
+        let string = match call.mutate_arguments() {
+            Arguments::String(string) => string,
+            Arguments::Tuple(tuple) if tuple.len() == 1 => {
+                match tuple.iter_mut_values().next().unwrap() {
+                    Expression::String(string) => string,
+                    _ => return,
+                }
+            }
+            _ => return,
+        };
+
+        if let Some(new_path) = remap_path_extension(string.get_value(), self.extensions) {
+            log::trace!(
+                "remap require path `{}` to `{}`",
+                string.get_value(),
+                new_path
+            );
+            // the token is dropped because it contains the previous path
+            *string = StringExpression::from_value(new_path);
+        }
+    }
+}
+
+/// Replaces the extension of the paths given to require calls using the given mapping
+/// (from an input file extension to an output file extension), so that require calls
+/// point to the files written with a different extension.
+pub(crate) fn remap_require_extensions(block: &mut Block, extensions: &BTreeMap<String, String>) {
+    if extensions.is_empty() {
+        return;
+    }
+
+    let mut processor = RequireExtensionProcessor {
+        identifier_tracker: IdentifierTracker::new(),
+        extensions,
+    };
+    ScopeVisitor::visit_block(block, &mut processor);
+}
+
+fn remap_path_extension(path: &str, extensions: &BTreeMap<String, String>) -> Option<String> {
+    let extension = Path::new(path).extension()?.to_str()?;
+    let new_extension = extensions.get(extension)?;
+
+    if !path.ends_with(extension) {
+        return None;
+    }
+
+    let stem = &path[..path.len() - extension.len()];
+    Some(format!("{}{}", stem, new_extension))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn luau_to_lua() -> BTreeMap<String, String> {
+        vec![("luau".to_owned(), "lua".to_owned())]
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn remap_relative_path() {
+        assert_eq!(
+            remap_path_extension("./module.luau", &luau_to_lua()),
+            Some("./module.lua".to_owned())
+        );
+    }
+
+    #[test]
+    fn remap_module_folder_path() {
+        assert_eq!(
+            remap_path_extension("../folder/init.luau", &luau_to_lua()),
+            Some("../folder/init.lua".to_owned())
+        );
+    }
+
+    #[test]
+    fn keep_path_without_extension() {
+        assert_eq!(remap_path_extension("./folder", &luau_to_lua()), None);
+    }
+
+    #[test]
+    fn keep_path_with_other_extension() {
+        assert_eq!(remap_path_extension("./data.json", &luau_to_lua()), None);
+    }
+}
@@ -0,0 +1,87 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::DarkluaError;
+
+pub(crate) const DEFAULT_PROJECT_FILE_NAME: &str = "default.project.json";
+
+#[derive(Debug, Deserialize)]
+struct RojoProject {
+    tree: RojoProjectNode,
+}
+
+#[derive(Debug, Deserialize)]
+struct RojoProjectNode {
+    #[serde(rename = "$path")]
+    path: Option<PathBuf>,
+}
+
+/// Returns the location of the `$path` of the root node of a Rojo project file, relative
+/// to the folder that contains the project file.
+pub(crate) fn get_project_root_path(
+    content: &str,
+    project_path: &Path,
+) -> Result<PathBuf, DarkluaError> {
+    let project = serde_json::from_str::<RojoProject>(content).map_err(|err| {
+        DarkluaError::from(err).context(format!(
+            "unable to parse Rojo project file at `{}`",
+            project_path.display()
+        ))
+    })?;
+
+    let root_path = project.tree.path.ok_or_else(|| {
+        DarkluaError::custom(format!(
+            "the root of the Rojo project file at `{}` does not have a `$path`",
+            project_path.display()
+        ))
+    })?;
+
+    Ok(project_path
+        .parent()
+        .map(|parent| parent.join(&root_path))
+        .unwrap_or(root_path))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn get_root_path_relative_to_project_folder() {
+        pretty_assertions::assert_eq!(
+            get_project_root_path(
+                r#"{ "name": "foo", "tree": { "$path": "src" } }"#,
+                Path::new("Packages/foo/default.project.json"),
+            )
+            .unwrap(),
+            PathBuf::from("Packages/foo/src")
+        );
+    }
+
+    #[test]
+    fn get_root_path_ignores_children() {
+        pretty_assertions::assert_eq!(
+            get_project_root_path(
+                r#"{ "name": "foo", "tree": { "$path": "lib", "Other": { "$path": "other" } } }"#,
+                Path::new("default.project.json"),
+            )
+            .unwrap(),
+            PathBuf::from("lib")
+        );
+    }
+
+    #[test]
+    fn get_root_path_without_path_errors() {
+        let err = get_project_root_path(
+            r#"{ "name": "foo", "tree": { "$className": "Folder" } }"#,
+            Path::new("Packages/foo/default.project.json"),
+        )
+        .unwrap_err();
+
+        pretty_assertions::assert_eq!(
+            err.to_string(),
+            "the root of the Rojo project file at `Packages/foo/default.project.json` does not have a `$path`"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -18,10 +18,27 @@ pub enum RulePropertyValue {
     Float(f64),
     StringList(Vec<String>),
     RequireMode(RequireMode),
+    StringMap(BTreeMap<String, String>),
     None,
 }
 
 impl RulePropertyValue {
+    /// Converts a generic value into a property value. Objects that do not match any property
+    /// type are converted to a require mode, so the error tells which field of the require
+    /// mode is invalid instead of only telling that no property type was matched.
+    pub(crate) fn from_json_value(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        match Self::deserialize(&value) {
+            Ok(property_value) => Ok(property_value),
+            Err(err) => {
+                if value.is_object() {
+                    RequireMode::deserialize(value).map(Self::RequireMode)
+                } else {
+                    Err(err)
+                }
+            }
+        }
+    }
+
     pub(crate) fn expect_bool(self, key: &str) -> Result<bool, RuleConfigurationError> {
         if let Self::Boolean(value) = self {
             Ok(value)
@@ -38,6 +55,14 @@ impl RulePropertyValue {
         }
     }
 
+    pub(crate) fn expect_usize(self, key: &str) -> Result<usize, RuleConfigurationError> {
+        if let Self::Usize(value) = self {
+            Ok(value)
+        } else {
+            Err(RuleConfigurationError::UsizeExpected(key.to_owned()))
+        }
+    }
+
     pub(crate) fn expect_string_list(
         self,
         key: &str,
@@ -49,6 +74,17 @@ impl RulePropertyValue {
         }
     }
 
+    pub(crate) fn expect_string_map(
+        self,
+        key: &str,
+    ) -> Result<BTreeMap<String, String>, RuleConfigurationError> {
+        if let Self::StringMap(value) = self {
+            Ok(value)
+        } else {
+            Err(RuleConfigurationError::StringMapExpected(key.to_owned()))
+        }
+    }
+
     pub(crate) fn expect_regex_list(self, key: &str) -> Result<Vec<Regex>, RuleConfigurationError> {
         if let Self::StringList(value) = self {
             value
@@ -79,6 +115,14 @@ impl RulePropertyValue {
                         message: err,
                     })
             }
+            // an object with only string values is gathered as a map of strings, so it is
+            // converted to a require mode to report the invalid field
+            Self::StringMap(map) => serde_json::to_value(map)
+                .and_then(RequireMode::deserialize)
+                .map_err(|err| RuleConfigurationError::UnexpectedValue {
+                    property: key.to_owned(),
+                    message: err.to_string(),
+                }),
             _ => Err(RuleConfigurationError::RequireModeExpected(key.to_owned())),
         }
     }
@@ -120,6 +164,12 @@ impl From<f64> for RulePropertyValue {
     }
 }
 
+impl From<BTreeMap<String, String>> for RulePropertyValue {
+    fn from(value: BTreeMap<String, String>) -> Self {
+        Self::StringMap(value)
+    }
+}
+
 impl From<&RequireMode> for RulePropertyValue {
     fn from(value: &RequireMode) -> Self {
         match value {
@@ -202,6 +252,16 @@ mod test {
         assert_eq!(RulePropertyValue::from(1.0), RulePropertyValue::Float(1.0));
     }
 
+    #[test]
+    fn from_string_map() {
+        let map: BTreeMap<String, String> =
+            vec![("a".to_owned(), "b".to_owned())].into_iter().collect();
+        assert_eq!(
+            RulePropertyValue::from(map.clone()),
+            RulePropertyValue::StringMap(map)
+        );
+    }
+
     #[test]
     fn from_boolean_option_some() {
         let bool = Some(true);
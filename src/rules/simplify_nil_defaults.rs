@@ -0,0 +1,288 @@
+use crate::nodes::{
+    AssignStatement, BinaryExpression, BinaryOperator, Block, Expression, Identifier, IfExpression,
+    IfStatement, Statement, Variable,
+};
+use crate::process::{DefaultVisitor, NodeProcessor, NodeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DefaultStyle {
+    /// `x = if x == nil then default else x`, which only exists in Luau
+    IfExpression,
+    /// `x = x or default`, which also replaces `false` values
+    Or,
+}
+
+impl Default for DefaultStyle {
+    fn default() -> Self {
+        Self::IfExpression
+    }
+}
+
+/// Returns the identifier compared to `nil` with the given operator.
+fn get_nil_comparison(condition: &Expression, operator: BinaryOperator) -> Option<&Identifier> {
+    match condition {
+        Expression::Binary(binary) if binary.operator() == operator => {
+            match (binary.left(), binary.right()) {
+                (Expression::Identifier(identifier), Expression::Nil(_))
+                | (Expression::Nil(_), Expression::Identifier(identifier)) => Some(identifier),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the value of the block if it only assigns a value to the given identifier.
+fn get_default_assignment<'a>(block: &'a Block, identifier: &Identifier) -> Option<&'a Expression> {
+    if block.statements_len() != 1 || block.get_last_statement().is_some() {
+        return None;
+    }
+
+    match block.first_statement() {
+        Some(Statement::Assign(assign))
+            if assign.variables_len() == 1 && assign.values_len() == 1 =>
+        {
+            match assign.get_variables().first() {
+                Some(Variable::Identifier(variable))
+                    if variable.get_name() == identifier.get_name() =>
+                {
+                    assign.last_value()
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the identifier and its default value if the if statement is written like
+/// `if x == nil then x = default end` or `if x ~= nil then else x = default end`.
+fn match_nil_default(if_statement: &IfStatement) -> Option<(&Identifier, &Expression)> {
+    let branch = match if_statement.get_branches().as_slice() {
+        [branch] => branch,
+        _ => return None,
+    };
+
+    if let Some(identifier) = get_nil_comparison(branch.get_condition(), BinaryOperator::Equal) {
+        if matches!(if_statement.get_else_block(), Some(block) if !block.is_empty()) {
+            return None;
+        }
+
+        get_default_assignment(branch.get_block(), identifier).map(|value| (identifier, value))
+    } else if let Some(identifier) =
+        get_nil_comparison(branch.get_condition(), BinaryOperator::NotEqual)
+    {
+        if !branch.get_block().is_empty() {
+            return None;
+        }
+
+        if_statement
+            .get_else_block()
+            .and_then(|block| get_default_assignment(block, identifier))
+            .map(|value| (identifier, value))
+    } else {
+        None
+    }
+}
+
+struct NilDefaultProcessor {
+    style: DefaultStyle,
+}
+
+impl NilDefaultProcessor {
+    fn build_value(&self, identifier: Identifier, default: Expression) -> Expression {
+        match self.style {
+            DefaultStyle::IfExpression => IfExpression::new(
+                BinaryExpression::new(BinaryOperator::Equal, identifier.clone(), Expression::nil()),
+                default,
+                identifier,
+            )
+            .into(),
+            DefaultStyle::Or => {
+                BinaryExpression::new(BinaryOperator::Or, identifier, default).into()
+            }
+        }
+    }
+}
+
+impl NodeProcessor for NilDefaultProcessor {
+    fn process_statement(&mut self, statement: &mut Statement) {
+        let assignment = match statement {
+            Statement::If(if_statement) => match_nil_default(if_statement)
+                .map(|(identifier, default)| (identifier.clone(), default.clone())),
+            _ => None,
+        };
+
+        if let Some((identifier, default)) = assignment {
+            let value = self.build_value(identifier.clone(), default);
+            *statement = AssignStatement::from_variable(identifier, value).into();
+        }
+    }
+}
+
+pub const SIMPLIFY_NIL_DEFAULTS_RULE_NAME: &str = "simplify_nil_defaults";
+
+/// A rule that converts if statements that assign a default value to a `nil` variable into
+/// a single assignment.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SimplifyNilDefaults {
+    style: DefaultStyle,
+    assume_no_false: bool,
+}
+
+impl SimplifyNilDefaults {
+    /// Use the `x = x or default` form. This form also replaces variables that are `false`,
+    /// so it can only be used when variables are never `false`.
+    pub fn with_or_style_assuming_no_false(mut self) -> Self {
+        self.style = DefaultStyle::Or;
+        self.assume_no_false = true;
+        self
+    }
+}
+
+impl FlawlessRule for SimplifyNilDefaults {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut processor = NilDefaultProcessor { style: self.style };
+        DefaultVisitor::visit_block(block, &mut processor);
+    }
+}
+
+impl RuleConfiguration for SimplifyNilDefaults {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                "style" => {
+                    self.style = match value.expect_string(&key)?.as_str() {
+                        "if_expression" => DefaultStyle::IfExpression,
+                        "or" => DefaultStyle::Or,
+                        unexpected => {
+                            return Err(RuleConfigurationError::UnexpectedValue {
+                                property: "style".to_owned(),
+                                message: format!(
+                                    "invalid value `{}` (must be `if_expression` or `or`)",
+                                    unexpected
+                                ),
+                            })
+                        }
+                    };
+                }
+                "assume_no_false" => {
+                    self.assume_no_false = value.expect_bool(&key)?;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        if self.style == DefaultStyle::Or && !self.assume_no_false {
+            return Err(RuleConfigurationError::UnexpectedValue {
+                property: "style".to_owned(),
+                message: "the `or` style changes the behavior of variables that can be `false` \
+                    and requires `assume_no_false` to be `true`"
+                    .to_owned(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        SIMPLIFY_NIL_DEFAULTS_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        match self.style {
+            DefaultStyle::IfExpression => {}
+            DefaultStyle::Or => {
+                properties.insert("style".to_owned(), "or".into());
+            }
+        }
+        if self.assume_no_false {
+            properties.insert("assume_no_false".to_owned(), true.into());
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        let style = match self.style {
+            DefaultStyle::IfExpression => "if_expression",
+            DefaultStyle::Or => "or",
+        };
+        properties.insert("style".to_owned(), style.into());
+        properties.insert("assume_no_false".to_owned(), self.assume_no_false.into());
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> SimplifyNilDefaults {
+        SimplifyNilDefaults::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_simplify_nil_defaults", rule);
+    }
+
+    #[test]
+    fn serialize_rule_with_or_style() {
+        let rule: Box<dyn Rule> = Box::new(new_rule().with_or_style_assuming_no_false());
+
+        assert_json_snapshot!("simplify_nil_defaults_or_style", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'simplify_nil_defaults',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn configure_with_invalid_style_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'simplify_nil_defaults',
+            style: 'and',
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "unexpected value for field 'style': invalid value `and` (must be `if_expression` or `or`)"
+        );
+    }
+
+    #[test]
+    fn configure_or_style_without_assume_no_false_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'simplify_nil_defaults',
+            style: 'or',
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "unexpected value for field 'style': the `or` style changes the behavior of variables that can be `false` and requires `assume_no_false` to be `true`"
+        );
+    }
+}
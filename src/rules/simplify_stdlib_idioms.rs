@@ -0,0 +1,295 @@
+use std::convert::TryFrom;
+use std::ops;
+
+use crate::nodes::{
+    BinaryExpression, BinaryOperator, Block, Expression, FunctionCall, ParentheseExpression,
+    Prefix, UnaryExpression, UnaryOperator,
+};
+use crate::process::{IdentifierTracker, NodeProcessor, NodeVisitor, ScopeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+const STRING_LIBRARY: &str = "string";
+const TABLE_LIBRARY: &str = "table";
+const MATH_LIBRARY: &str = "math";
+const TOSTRING_IDENTIFIER: &str = "tostring";
+
+const STRING_LEN_PROPERTY: &str = "string_len";
+const TABLE_GETN_PROPERTY: &str = "table_getn";
+const MATH_POW_PROPERTY: &str = "math_pow";
+const STRING_FORMAT_PROPERTY: &str = "string_format";
+
+struct SimplifyStdlibIdiomsProcessor<'a> {
+    identifier_tracker: IdentifierTracker,
+    rule: &'a SimplifyStdlibIdioms,
+}
+
+impl ops::Deref for SimplifyStdlibIdiomsProcessor<'_> {
+    type Target = IdentifierTracker;
+
+    fn deref(&self) -> &Self::Target {
+        &self.identifier_tracker
+    }
+}
+
+impl ops::DerefMut for SimplifyStdlibIdiomsProcessor<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.identifier_tracker
+    }
+}
+
+impl SimplifyStdlibIdiomsProcessor<'_> {
+    /// Returns the name of the function called with `library.name(...)`, when `library`
+    /// refers to the global library.
+    fn get_library_function<'b>(&self, call: &'b FunctionCall, library: &str) -> Option<&'b str> {
+        if call.get_method().is_some() {
+            return None;
+        }
+
+        match call.get_prefix() {
+            Prefix::Field(field) => match field.get_prefix() {
+                Prefix::Identifier(identifier)
+                    if identifier.get_name() == library && !self.is_identifier_used(library) =>
+                {
+                    Some(field.get_field().get_name().as_str())
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn is_library_call(&self, call: &FunctionCall, library: &str, function: &str) -> bool {
+        self.get_library_function(call, library) == Some(function)
+    }
+
+    fn replace_call(&self, call: &FunctionCall) -> Option<Expression> {
+        let arguments = || call.get_arguments().clone().to_expressions();
+
+        if (self.rule.string_len && self.is_library_call(call, STRING_LIBRARY, "len"))
+            || (self.rule.table_getn && self.is_library_call(call, TABLE_LIBRARY, "getn"))
+        {
+            return match <[Expression; 1]>::try_from(arguments()) {
+                Ok([value]) => Some(UnaryExpression::new(UnaryOperator::Length, value).into()),
+                Err(_) => None,
+            };
+        }
+
+        if self.rule.math_pow && self.is_library_call(call, MATH_LIBRARY, "pow") {
+            return match <[Expression; 2]>::try_from(arguments()) {
+                Ok([base, exponent]) => {
+                    Some(BinaryExpression::new(BinaryOperator::Caret, base, exponent).into())
+                }
+                Err(_) => None,
+            };
+        }
+
+        if self.rule.string_format
+            && self.is_library_call(call, STRING_LIBRARY, "format")
+            && !self.is_identifier_used(TOSTRING_IDENTIFIER)
+        {
+            return match <[Expression; 2]>::try_from(arguments()) {
+                Ok([Expression::String(format), value]) if format.get_value() == "%s" => Some(
+                    FunctionCall::from_name(TOSTRING_IDENTIFIER)
+                        .with_argument(value)
+                        .into(),
+                ),
+                _ => None,
+            };
+        }
+
+        None
+    }
+}
+
+impl NodeProcessor for SimplifyStdlibIdiomsProcessor<'_> {
+    fn process_expression(&mut self, expression: &mut Expression) {
+        let replacement = match expression {
+            Expression::Call(call) => self.replace_call(call),
+            _ => None,
+        };
+
+        if let Some(replacement) = replacement {
+            *expression = replacement;
+        }
+    }
+
+    fn process_prefix_expression(&mut self, prefix: &mut Prefix) {
+        let replacement = match prefix {
+            Prefix::Call(call) => self.replace_call(call),
+            _ => None,
+        };
+
+        if let Some(replacement) = replacement {
+            *prefix = match replacement {
+                Expression::Call(call) => (*call).into(),
+                replacement => ParentheseExpression::new(replacement).into(),
+            };
+        }
+    }
+}
+
+pub const SIMPLIFY_STDLIB_IDIOMS_RULE_NAME: &str = "simplify_stdlib_idioms";
+
+/// A rule that replaces calls to some standard library functions with the equivalent
+/// operator: `string.len(s)` and `table.getn(t)` with `#`, `math.pow(a, b)` with `^` and
+/// `string.format("%s", value)` with `tostring(value)`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SimplifyStdlibIdioms {
+    string_len: bool,
+    table_getn: bool,
+    math_pow: bool,
+    string_format: bool,
+}
+
+impl Default for SimplifyStdlibIdioms {
+    fn default() -> Self {
+        Self {
+            string_len: true,
+            table_getn: true,
+            math_pow: true,
+            string_format: true,
+        }
+    }
+}
+
+impl SimplifyStdlibIdioms {
+    pub fn with_string_len(mut self, enabled: bool) -> Self {
+        self.string_len = enabled;
+        self
+    }
+
+    pub fn with_table_getn(mut self, enabled: bool) -> Self {
+        self.table_getn = enabled;
+        self
+    }
+
+    pub fn with_math_pow(mut self, enabled: bool) -> Self {
+        self.math_pow = enabled;
+        self
+    }
+
+    pub fn with_string_format(mut self, enabled: bool) -> Self {
+        self.string_format = enabled;
+        self
+    }
+
+    fn iter_idioms(&self) -> impl Iterator<Item = (&'static str, bool)> {
+        vec![
+            (STRING_LEN_PROPERTY, self.string_len),
+            (TABLE_GETN_PROPERTY, self.table_getn),
+            (MATH_POW_PROPERTY, self.math_pow),
+            (STRING_FORMAT_PROPERTY, self.string_format),
+        ]
+        .into_iter()
+    }
+}
+
+impl FlawlessRule for SimplifyStdlibIdioms {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut processor = SimplifyStdlibIdiomsProcessor {
+            identifier_tracker: IdentifierTracker::new(),
+            rule: self,
+        };
+        ScopeVisitor::visit_block(block, &mut processor);
+    }
+}
+
+impl RuleConfiguration for SimplifyStdlibIdioms {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                STRING_LEN_PROPERTY => {
+                    self.string_len = value.expect_bool(&key)?;
+                }
+                TABLE_GETN_PROPERTY => {
+                    self.table_getn = value.expect_bool(&key)?;
+                }
+                MATH_POW_PROPERTY => {
+                    self.math_pow = value.expect_bool(&key)?;
+                }
+                STRING_FORMAT_PROPERTY => {
+                    self.string_format = value.expect_bool(&key)?;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        SIMPLIFY_STDLIB_IDIOMS_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        for (name, enabled) in self.iter_idioms() {
+            if !enabled {
+                properties.insert(name.to_owned(), false.into());
+            }
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        for (name, enabled) in self.iter_idioms() {
+            properties.insert(name.to_owned(), enabled.into());
+        }
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> SimplifyStdlibIdioms {
+        SimplifyStdlibIdioms::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_simplify_stdlib_idioms", rule);
+    }
+
+    #[test]
+    fn serialize_rule_without_math_pow() {
+        let rule: Box<dyn Rule> = Box::new(new_rule().with_math_pow(false));
+
+        assert_json_snapshot!("simplify_stdlib_idioms_without_math_pow", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'simplify_stdlib_idioms',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn configure_with_invalid_idiom_value_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'simplify_stdlib_idioms',
+            string_len: "yes",
+        }"#,
+        );
+        assert!(result.is_err());
+    }
+}
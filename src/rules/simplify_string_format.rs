@@ -0,0 +1,317 @@
+use std::ops;
+
+use crate::nodes::{
+    BinaryExpression, BinaryOperator, Block, Expression, FunctionCall, ParentheseExpression,
+    Prefix, StringExpression,
+};
+use crate::process::{IdentifierTracker, NodeProcessor, NodeVisitor, ScopeVisitor};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+const STRING_LIBRARY: &str = "string";
+const FORMAT_FUNCTION: &str = "format";
+const TOSTRING_IDENTIFIER: &str = "tostring";
+
+#[derive(Debug, PartialEq, Eq)]
+enum FormatSegment {
+    Literal(String),
+    Argument,
+}
+
+/// Splits a format string into literal segments and `%s` specifiers. Returns `None` if
+/// the format string uses any other specifier.
+fn parse_format(format: &str) -> Option<Vec<FormatSegment>> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut chars = format.chars();
+
+    while let Some(current) = chars.next() {
+        if current != '%' {
+            literal.push(current);
+            continue;
+        }
+
+        match chars.next()? {
+            '%' => literal.push('%'),
+            's' => {
+                if !literal.is_empty() {
+                    segments.push(FormatSegment::Literal(std::mem::take(&mut literal)));
+                }
+                segments.push(FormatSegment::Argument);
+            }
+            _ => return None,
+        }
+    }
+
+    if !literal.is_empty() {
+        segments.push(FormatSegment::Literal(literal));
+    }
+
+    Some(segments)
+}
+
+struct SimplifyStringFormatProcessor {
+    identifier_tracker: IdentifierTracker,
+    assume_strings: bool,
+}
+
+impl ops::Deref for SimplifyStringFormatProcessor {
+    type Target = IdentifierTracker;
+
+    fn deref(&self) -> &Self::Target {
+        &self.identifier_tracker
+    }
+}
+
+impl ops::DerefMut for SimplifyStringFormatProcessor {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.identifier_tracker
+    }
+}
+
+impl SimplifyStringFormatProcessor {
+    fn is_string_format(&self, call: &FunctionCall) -> bool {
+        call.get_method().is_none()
+            && matches!(
+                call.get_prefix(),
+                Prefix::Field(field) if field.get_field().get_name() == FORMAT_FUNCTION
+                    && matches!(
+                        field.get_prefix(),
+                        Prefix::Identifier(identifier) if identifier.get_name() == STRING_LIBRARY
+                    )
+            )
+            && !self.is_identifier_used(STRING_LIBRARY)
+    }
+
+    fn convert_argument(&self, argument: &Expression) -> Expression {
+        if self.assume_strings || matches!(argument, Expression::String(_)) {
+            argument.clone()
+        } else {
+            FunctionCall::from_name(TOSTRING_IDENTIFIER)
+                .with_argument(argument.clone())
+                .into()
+        }
+    }
+
+    /// Returns the concatenation equivalent to the `string.format` call, if the call only
+    /// uses `%s` specifiers with the same number of arguments.
+    fn replace_call(&self, call: &FunctionCall) -> Option<Expression> {
+        if !self.is_string_format(call) {
+            return None;
+        }
+
+        let mut values = call.get_arguments().iter_values();
+        let format = values.next()?.as_string()?.get_value();
+        let arguments = values
+            .map(|value| value.as_expression())
+            .collect::<Option<Vec<&Expression>>>()?;
+
+        let segments = parse_format(format)?;
+
+        let specifiers = segments
+            .iter()
+            .filter(|segment| **segment == FormatSegment::Argument)
+            .count();
+        if specifiers != arguments.len() {
+            return None;
+        }
+
+        if !self.assume_strings && self.is_identifier_used(TOSTRING_IDENTIFIER) {
+            let needs_tostring = arguments
+                .iter()
+                .any(|argument| !matches!(argument, Expression::String(_)));
+            if needs_tostring {
+                return None;
+            }
+        }
+
+        let mut arguments = arguments.into_iter();
+        let mut operands: Vec<Expression> = segments
+            .into_iter()
+            .map(|segment| match segment {
+                FormatSegment::Literal(value) => StringExpression::from_value(value).into(),
+                FormatSegment::Argument => self.convert_argument(
+                    arguments
+                        .next()
+                        .expect("the number of arguments should match the specifiers"),
+                ),
+            })
+            .collect();
+
+        let last = match operands.pop() {
+            Some(last) => last,
+            None => return Some(StringExpression::from_value("").into()),
+        };
+
+        if operands.is_empty() {
+            // a single argument that may return multiple values must be truncated
+            return Some(match last {
+                Expression::Call(_) | Expression::VariableArguments(_) => last.in_parentheses(),
+                _ => last,
+            });
+        }
+
+        Some(
+            operands
+                .into_iter()
+                .rev()
+                .fold(last, |concatenation, operand| {
+                    BinaryExpression::new(BinaryOperator::Concat, operand, concatenation).into()
+                }),
+        )
+    }
+}
+
+impl NodeProcessor for SimplifyStringFormatProcessor {
+    fn process_expression(&mut self, expression: &mut Expression) {
+        let replacement = match expression {
+            Expression::Call(call) => self.replace_call(call),
+            _ => None,
+        };
+
+        if let Some(replacement) = replacement {
+            *expression = replacement;
+        }
+    }
+
+    fn process_prefix_expression(&mut self, prefix: &mut Prefix) {
+        let replacement = match prefix {
+            Prefix::Call(call) => self.replace_call(call),
+            _ => None,
+        };
+
+        if let Some(replacement) = replacement {
+            *prefix = match replacement {
+                Expression::Parenthese(parenthese) => (*parenthese).into(),
+                replacement => ParentheseExpression::new(replacement).into(),
+            };
+        }
+    }
+}
+
+pub const SIMPLIFY_STRING_FORMAT_RULE_NAME: &str = "simplify_string_format";
+
+/// A rule that converts `string.format` calls that only use `%s` specifiers into
+/// concatenations.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SimplifyStringFormat {
+    assume_strings: bool,
+}
+
+impl SimplifyStringFormat {
+    pub fn with_strings_assumed(mut self) -> Self {
+        self.assume_strings = true;
+        self
+    }
+}
+
+impl FlawlessRule for SimplifyStringFormat {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut processor = SimplifyStringFormatProcessor {
+            identifier_tracker: IdentifierTracker::new(),
+            assume_strings: self.assume_strings,
+        };
+        ScopeVisitor::visit_block(block, &mut processor);
+    }
+}
+
+impl RuleConfiguration for SimplifyStringFormat {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                "assume_strings" => {
+                    self.assume_strings = value.expect_bool(&key)?;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        SIMPLIFY_STRING_FORMAT_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        if self.assume_strings {
+            properties.insert("assume_strings".to_owned(), true.into());
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert("assume_strings".to_owned(), self.assume_strings.into());
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> SimplifyStringFormat {
+        SimplifyStringFormat::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_simplify_string_format", rule);
+    }
+
+    #[test]
+    fn serialize_rule_with_strings_assumed() {
+        let rule: Box<dyn Rule> = Box::new(new_rule().with_strings_assumed());
+
+        assert_json_snapshot!("simplify_string_format_with_strings_assumed", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'simplify_string_format',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn parse_format_with_escaped_percent() {
+        assert_eq!(
+            parse_format("%s%%"),
+            Some(vec![
+                FormatSegment::Argument,
+                FormatSegment::Literal("%".to_owned())
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_format_with_other_specifier() {
+        assert_eq!(parse_format("%d"), None);
+    }
+
+    #[test]
+    fn parse_format_with_width() {
+        assert_eq!(parse_format("%5s"), None);
+    }
+
+    #[test]
+    fn parse_format_with_trailing_percent() {
+        assert_eq!(parse_format("100%"), None);
+    }
+}
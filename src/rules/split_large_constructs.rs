@@ -0,0 +1,545 @@
+use std::collections::{HashMap, HashSet};
+use std::mem;
+
+use crate::nodes::{
+    AssignStatement, Block, DoStatement, Expression, FieldExpression, Identifier, IndexExpression,
+    LastStatement, LocalAssignStatement, Statement, TableEntry, TableExpression, Variable,
+};
+use crate::process::utils::collect_identifiers;
+use crate::process::{
+    DefaultPostVisitor, DefaultVisitor, NodePostProcessor, NodePostVisitor, NodeProcessor,
+    NodeVisitor,
+};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+const TABLE_VARIABLE_PREFIX: &str = "__DARKLUA_TABLE";
+
+/// Collects the identifiers referenced in some nodes. Shadowing is ignored, so the result
+/// may contain identifiers that refer to other variables with the same name.
+#[derive(Debug, Default)]
+struct ReferencedIdentifiers {
+    identifiers: HashSet<String>,
+}
+
+impl ReferencedIdentifiers {
+    fn of_statement(statement: &mut Statement) -> HashSet<String> {
+        let mut collector = Self::default();
+        DefaultVisitor::visit_statement(statement, &mut collector);
+        collector.identifiers
+    }
+
+    fn of_last_statement(statement: &mut LastStatement) -> HashSet<String> {
+        let mut collector = Self::default();
+        DefaultVisitor::visit_last_statement(statement, &mut collector);
+        collector.identifiers
+    }
+
+    fn of_entries(entries: &mut [TableEntry]) -> HashSet<String> {
+        let mut collector = Self::default();
+        for entry in entries {
+            match entry {
+                TableEntry::Field(entry) => {
+                    DefaultVisitor::visit_expression(entry.mutate_value(), &mut collector)
+                }
+                TableEntry::Index(entry) => {
+                    DefaultVisitor::visit_expression(entry.mutate_key(), &mut collector);
+                    DefaultVisitor::visit_expression(entry.mutate_value(), &mut collector);
+                }
+                TableEntry::Value(value) => DefaultVisitor::visit_expression(value, &mut collector),
+            }
+        }
+        collector.identifiers
+    }
+}
+
+impl NodeProcessor for ReferencedIdentifiers {
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        self.identifiers.insert(identifier.get_name().to_owned());
+    }
+}
+
+/// Returns `true` if the entries of the table can be assigned one by one with the same
+/// result as the constructor.
+fn can_split_entries(table: &TableExpression) -> bool {
+    let entries = table.get_entries();
+
+    // a call or `...` at the end of the constructor adds a number of values that is only
+    // known at runtime
+    if matches!(
+        entries.last(),
+        Some(TableEntry::Value(Expression::Call(_)))
+            | Some(TableEntry::Value(Expression::VariableArguments(_)))
+    ) {
+        return false;
+    }
+
+    let has_array_values = entries
+        .iter()
+        .any(|entry| matches!(entry, TableEntry::Value(_)));
+
+    // the array values of a constructor are assigned after the keys that come before
+    // them, so a key that may be an integer could get a different value once split
+    !has_array_values
+        || entries.iter().all(|entry| match entry {
+            TableEntry::Index(entry) => matches!(entry.get_key(), Expression::String(_)),
+            TableEntry::Field(_) | TableEntry::Value(_) => true,
+        })
+}
+
+fn assign_entry(table: &str, entry: TableEntry, array_index: &mut usize) -> Statement {
+    let (variable, value): (Variable, Expression) = match entry {
+        TableEntry::Field(mut entry) => (
+            FieldExpression::new(Identifier::new(table), entry.get_field().clone()).into(),
+            mem::replace(entry.mutate_value(), Expression::nil()),
+        ),
+        TableEntry::Index(mut entry) => (
+            IndexExpression::new(
+                Identifier::new(table),
+                mem::replace(entry.mutate_key(), Expression::nil()),
+            )
+            .into(),
+            mem::replace(entry.mutate_value(), Expression::nil()),
+        ),
+        TableEntry::Value(value) => {
+            *array_index += 1;
+            (
+                IndexExpression::new(Identifier::new(table), *array_index).into(),
+                value,
+            )
+        }
+    };
+
+    AssignStatement::from_variable(variable, value).into()
+}
+
+struct ConstructSplitter<'a> {
+    max_entries: usize,
+    table_variable: &'a str,
+}
+
+impl ConstructSplitter<'_> {
+    /// Keeps the first entries in the constructor and returns the assignments of the other
+    /// entries to the given variable, or `None` if the table must not be split.
+    fn split_table(&self, table: &mut TableExpression, variable: &str) -> Option<Vec<Statement>> {
+        if table.len() <= self.max_entries || !can_split_entries(table) {
+            return None;
+        }
+
+        let entries = table.mutate_entries();
+        let moved_entries = entries.split_off(self.max_entries);
+
+        // a call or `...` in the middle of the constructor only adds its first value, but
+        // it would add all of its values once it becomes the last entry
+        if let Some(TableEntry::Value(value)) = entries.last_mut() {
+            if matches!(
+                value,
+                Expression::Call(_) | Expression::VariableArguments(_)
+            ) {
+                let expression = mem::replace(value, Expression::nil());
+                *value = expression.in_parentheses();
+            }
+        }
+
+        let mut array_index = entries
+            .iter()
+            .filter(|entry| matches!(entry, TableEntry::Value(_)))
+            .count();
+
+        Some(
+            moved_entries
+                .into_iter()
+                .map(|entry| assign_entry(variable, entry, &mut array_index))
+                .collect(),
+        )
+    }
+
+    /// Returns `true` if the entries that would be moved out of the constructor refer to
+    /// the given identifier.
+    fn moved_entries_refer_to(&self, value: &mut Expression, identifier: &str) -> bool {
+        match value {
+            Expression::Table(table) if table.len() > self.max_entries => {
+                ReferencedIdentifiers::of_entries(&mut table.mutate_entries()[self.max_entries..])
+                    .contains(identifier)
+            }
+            _ => false,
+        }
+    }
+
+    /// Builds the table in the generated variable and replaces the value with that
+    /// variable.
+    fn split_into_table_variable(&self, value: &mut Expression, statements: &mut Vec<Statement>) {
+        let assignments = match value {
+            Expression::Table(table) => self.split_table(table, self.table_variable),
+            _ => None,
+        };
+
+        if let Some(assignments) = assignments {
+            let table = mem::replace(value, Expression::identifier(self.table_variable));
+            statements.push(
+                LocalAssignStatement::from_variable(self.table_variable)
+                    .with_value(table)
+                    .into(),
+            );
+            statements.extend(assignments);
+        }
+    }
+
+    /// Splits the table assigned by the statement. The statements that must run before it
+    /// are pushed to the given statements, and the assignments that must run after it are
+    /// returned.
+    fn split_statement(
+        &self,
+        statement: &mut Statement,
+        statements: &mut Vec<Statement>,
+    ) -> Option<Vec<Statement>> {
+        match statement {
+            Statement::LocalAssign(assign)
+                if assign.variables_len() == 1 && assign.values_len() == 1 =>
+            {
+                let variable = assign
+                    .iter_variables()
+                    .map(|variable| variable.get_name().to_owned())
+                    .next()?;
+                let value = assign.iter_mut_values().next()?;
+
+                // the variable is not defined yet in the constructor, so the entries that
+                // refer to it must not be assigned after the variable is declared
+                if self.moved_entries_refer_to(value, &variable) {
+                    self.split_into_table_variable(value, statements);
+                    None
+                } else {
+                    match value {
+                        Expression::Table(table) => self.split_table(table, &variable),
+                        _ => None,
+                    }
+                }
+            }
+            Statement::Assign(assign)
+                if assign.variables_len() == 1
+                    && assign.values_len() == 1
+                    && matches!(
+                        assign.iter_variables().next(),
+                        Some(Variable::Identifier(_))
+                    ) =>
+            {
+                let value = assign.iter_mut_values().next()?;
+                self.split_into_table_variable(value, statements);
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+impl NodeProcessor for ConstructSplitter<'_> {}
+
+impl NodePostProcessor for ConstructSplitter<'_> {
+    fn process_after_block(&mut self, block: &mut Block) {
+        let mut statements = Vec::with_capacity(block.statements_len());
+
+        for mut statement in block.take_statements() {
+            let assignments = self.split_statement(&mut statement, &mut statements);
+            statements.push(statement);
+            statements.extend(assignments.into_iter().flatten());
+        }
+
+        if let Some(LastStatement::Return(statement)) = block.mutate_last_statement() {
+            if statement.len() == 1 {
+                if let Some(value) = statement.iter_mut_expressions().next() {
+                    self.split_into_table_variable(value, &mut statements);
+                }
+            }
+        }
+
+        block.set_statements(statements);
+    }
+}
+
+/// Returns `true` if the statement must stay directly in the block, because the names
+/// it declares are only visible in the block where they are declared.
+fn must_stay_in_block(statement: &Statement) -> bool {
+    matches!(
+        statement,
+        Statement::Label(_)
+            | Statement::TypeDeclaration(_)
+            | Statement::TypeFunction(_)
+            | Statement::ExportTypeFunction(_)
+    )
+}
+
+fn declared_locals(statement: &Statement) -> Vec<String> {
+    match statement {
+        Statement::LocalAssign(assign) => assign
+            .iter_variables()
+            .map(|variable| variable.get_name().to_owned())
+            .collect(),
+        Statement::LocalFunction(function) => vec![function.get_name().to_owned()],
+        _ => Vec::new(),
+    }
+}
+
+/// Moves the statements of the block into `do` blocks of at most the given number of
+/// statements, so that the locals they declare go out of scope. Statements are only put
+/// in a `do` block when their locals are not used after it.
+fn split_chunk(block: &mut Block, max_statements: usize) {
+    if max_statements == 0 || block.statements_len() <= max_statements {
+        return;
+    }
+
+    let mut statements = block.take_statements();
+
+    // the index of the last statement that refers to each identifier
+    let mut last_uses: HashMap<String, usize> = HashMap::new();
+    for (index, statement) in statements.iter_mut().enumerate() {
+        for identifier in ReferencedIdentifiers::of_statement(statement) {
+            last_uses.insert(identifier, index);
+        }
+    }
+    if let Some(last_statement) = block.mutate_last_statement() {
+        for identifier in ReferencedIdentifiers::of_last_statement(last_statement) {
+            last_uses.insert(identifier, usize::MAX);
+        }
+    }
+
+    let declarations: Vec<Vec<String>> = statements.iter().map(declared_locals).collect();
+
+    // the start and end indexes of the statements moved into each `do` block
+    let mut groups = Vec::new();
+    let mut start = 0;
+
+    while start < statements.len() {
+        let mut end = None;
+        let mut last_use = 0;
+        let mut declares_locals = false;
+
+        for (index, (statement, names)) in statements
+            .iter()
+            .zip(declarations.iter())
+            .enumerate()
+            .skip(start)
+            .take(max_statements)
+        {
+            if must_stay_in_block(statement) {
+                break;
+            }
+
+            for name in names {
+                declares_locals = true;
+                last_use = last_use.max(last_uses.get(name).copied().unwrap_or(index));
+            }
+
+            if last_use <= index {
+                end = Some((index + 1, declares_locals));
+            }
+        }
+
+        match end {
+            Some((end, true)) if end - start > 1 => {
+                groups.push((start, end));
+                start = end;
+            }
+            Some((end, false)) => start = end,
+            _ => start += 1,
+        }
+    }
+
+    if groups.is_empty() {
+        block.set_statements(statements);
+        return;
+    }
+
+    let mut new_statements = Vec::new();
+    let mut statements = statements.into_iter().enumerate().peekable();
+
+    for (group_start, group_end) in groups {
+        while let Some((_, statement)) = statements.next_if(|(index, _)| *index < group_start) {
+            new_statements.push(statement);
+        }
+
+        let group: Vec<_> = statements
+            .by_ref()
+            .take(group_end - group_start)
+            .map(|(_, statement)| statement)
+            .collect();
+        new_statements.push(DoStatement::new(Block::new(group, None)).into());
+    }
+
+    new_statements.extend(statements.map(|(_, statement)| statement));
+
+    block.set_statements(new_statements);
+}
+
+pub const SPLIT_LARGE_CONSTRUCTS_RULE_NAME: &str = "split_large_constructs";
+
+const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+/// A rule that splits large table constructors into a smaller constructor followed by
+/// assignments, and optionally splits the statements of files into `do` blocks.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SplitLargeConstructs {
+    max_entries: usize,
+    max_chunk_statements: Option<usize>,
+}
+
+impl Default for SplitLargeConstructs {
+    fn default() -> Self {
+        Self {
+            max_entries: DEFAULT_MAX_ENTRIES,
+            max_chunk_statements: None,
+        }
+    }
+}
+
+impl SplitLargeConstructs {
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    pub fn with_max_chunk_statements(mut self, max_chunk_statements: usize) -> Self {
+        self.max_chunk_statements = Some(max_chunk_statements);
+        self
+    }
+}
+
+impl FlawlessRule for SplitLargeConstructs {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let used_identifiers = collect_identifiers(block);
+        let mut index = 0;
+        let table_variable = loop {
+            let identifier = if index == 0 {
+                TABLE_VARIABLE_PREFIX.to_owned()
+            } else {
+                format!("{}{}", TABLE_VARIABLE_PREFIX, index)
+            };
+            if !used_identifiers.contains(&identifier) {
+                break identifier;
+            }
+            index += 1;
+        };
+
+        let mut processor = ConstructSplitter {
+            max_entries: self.max_entries,
+            table_variable: &table_variable,
+        };
+        DefaultPostVisitor::visit_block(block, &mut processor);
+
+        if let Some(max_statements) = self.max_chunk_statements {
+            split_chunk(block, max_statements);
+        }
+    }
+}
+
+impl RuleConfiguration for SplitLargeConstructs {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                "max_entries" => {
+                    let max_entries = value.expect_usize(&key)?;
+                    if max_entries == 0 {
+                        return Err(RuleConfigurationError::UnexpectedValue {
+                            property: key,
+                            message: "must be greater than zero".to_owned(),
+                        });
+                    }
+                    self.max_entries = max_entries;
+                }
+                "max_chunk_statements" => {
+                    let max_statements = value.expect_usize(&key)?;
+                    if max_statements == 0 {
+                        return Err(RuleConfigurationError::UnexpectedValue {
+                            property: key,
+                            message: "must be greater than zero".to_owned(),
+                        });
+                    }
+                    self.max_chunk_statements = Some(max_statements);
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        SPLIT_LARGE_CONSTRUCTS_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        if self.max_entries != DEFAULT_MAX_ENTRIES {
+            properties.insert("max_entries".to_owned(), self.max_entries.into());
+        }
+        if let Some(max_statements) = self.max_chunk_statements {
+            properties.insert("max_chunk_statements".to_owned(), max_statements.into());
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert("max_entries".to_owned(), self.max_entries.into());
+        if let Some(max_statements) = self.max_chunk_statements {
+            properties.insert("max_chunk_statements".to_owned(), max_statements.into());
+        }
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> SplitLargeConstructs {
+        SplitLargeConstructs::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_split_large_constructs", rule);
+    }
+
+    #[test]
+    fn serialize_rule_with_thresholds() {
+        let rule: Box<dyn Rule> = Box::new(
+            new_rule()
+                .with_max_entries(100)
+                .with_max_chunk_statements(50),
+        );
+
+        assert_json_snapshot!("split_large_constructs_with_thresholds", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'split_large_constructs',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    #[test]
+    fn configure_with_zero_max_entries_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'split_large_constructs',
+            max_entries: 0,
+        }"#,
+        );
+        assert!(result.is_err());
+    }
+}
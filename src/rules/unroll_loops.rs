@@ -0,0 +1,384 @@
+use std::ops;
+
+use crate::nodes::{
+    Block, DecimalNumber, DoStatement, Expression, LastStatement, NumericForStatement,
+    ParentheseExpression, Prefix, Statement, UnaryExpression, UnaryOperator,
+};
+use crate::process::{
+    BindingTable, DefaultPostVisitor, IdentifierTracker, NodePostProcessor, NodePostVisitor,
+    NodeProcessor, NodeVisitor, ScopeVisitor,
+};
+use crate::rules::{
+    Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+const MAXIMUM_EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+fn get_number(expression: &Expression) -> Option<f64> {
+    match expression {
+        Expression::Number(number) => Some(number.compute_value()),
+        Expression::Unary(unary) if unary.operator() == UnaryOperator::Minus => {
+            match unary.get_expression() {
+                Expression::Number(number) => Some(-number.compute_value()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn is_exact_integer(value: f64) -> bool {
+    value.fract() == 0.0 && value.abs() <= MAXIMUM_EXACT_INTEGER
+}
+
+/// Creates an integer literal, without the exponent notation that would make it a float
+/// in Lua 5.3 and later.
+fn create_integer(value: f64) -> Expression {
+    if value < 0.0 {
+        UnaryExpression::new(UnaryOperator::Minus, DecimalNumber::new(-value)).into()
+    } else {
+        DecimalNumber::new(value).into()
+    }
+}
+
+/// Returns the values of the loop variable for each iteration, if the start, end and step
+/// of the loop are integer literals and if there are at most `max_iterations` iterations.
+fn get_iteration_values(
+    numeric_for: &NumericForStatement,
+    max_iterations: usize,
+) -> Option<Vec<f64>> {
+    let start = get_number(numeric_for.get_start())?;
+    let end = get_number(numeric_for.get_end())?;
+    let step = match numeric_for.get_step() {
+        Some(step) => get_number(step)?,
+        None => 1.0,
+    };
+
+    if step == 0.0
+        || ![start, end, step]
+            .iter()
+            .all(|value| is_exact_integer(*value))
+    {
+        return None;
+    }
+
+    let count = if step > 0.0 {
+        if start > end {
+            0.0
+        } else {
+            ((end - start) / step).floor() + 1.0
+        }
+    } else if start < end {
+        0.0
+    } else {
+        ((start - end) / -step).floor() + 1.0
+    };
+
+    if count > max_iterations as f64 {
+        return None;
+    }
+
+    Some(
+        (0..count as usize)
+            .map(|iteration| start + step * iteration as f64)
+            .collect(),
+    )
+}
+
+/// Returns `true` if the block contains a `break` or `continue` statement that exits the
+/// loop containing the block.
+fn has_loop_exit(block: &Block) -> bool {
+    matches!(
+        block.get_last_statement(),
+        Some(LastStatement::Break(_)) | Some(LastStatement::Continue(_))
+    ) || block.iter_statements().any(|statement| match statement {
+        Statement::Do(do_statement) => has_loop_exit(do_statement.get_block()),
+        Statement::If(if_statement) => {
+            if_statement
+                .iter_branches()
+                .any(|branch| has_loop_exit(branch.get_block()))
+                || matches!(if_statement.get_else_block(), Some(block) if has_loop_exit(block))
+        }
+        _ => false,
+    })
+}
+
+/// Replaces the loop variable with its value, except where it is shadowed by another
+/// variable.
+struct LoopVariableReplacer {
+    identifier_tracker: IdentifierTracker,
+    variable: String,
+    value: f64,
+}
+
+impl ops::Deref for LoopVariableReplacer {
+    type Target = IdentifierTracker;
+
+    fn deref(&self) -> &Self::Target {
+        &self.identifier_tracker
+    }
+}
+
+impl ops::DerefMut for LoopVariableReplacer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.identifier_tracker
+    }
+}
+
+impl LoopVariableReplacer {
+    fn is_loop_variable(&self, name: &str) -> bool {
+        name == self.variable && !self.is_identifier_used(&self.variable)
+    }
+}
+
+impl NodeProcessor for LoopVariableReplacer {
+    fn process_expression(&mut self, expression: &mut Expression) {
+        if let Expression::Identifier(identifier) = expression {
+            if self.is_loop_variable(identifier.get_name()) {
+                *expression = create_integer(self.value);
+            }
+        }
+    }
+
+    fn process_prefix_expression(&mut self, prefix: &mut Prefix) {
+        if let Prefix::Identifier(identifier) = prefix {
+            if self.is_loop_variable(identifier.get_name()) {
+                *prefix = ParentheseExpression::new(create_integer(self.value)).into();
+            }
+        }
+    }
+}
+
+struct UnrollProcessor {
+    max_iterations: usize,
+    max_body_statements: usize,
+}
+
+impl UnrollProcessor {
+    fn unroll(&self, statement: &mut Statement) -> Option<Vec<Statement>> {
+        let values = match statement {
+            Statement::NumericFor(numeric_for) => {
+                let values = get_iteration_values(numeric_for, self.max_iterations)?;
+
+                let block = numeric_for.get_block();
+                let body_statements =
+                    block.statements_len() + usize::from(block.get_last_statement().is_some());
+
+                if body_statements > self.max_body_statements || has_loop_exit(block) {
+                    return None;
+                }
+
+                values
+            }
+            _ => return None,
+        };
+
+        let bindings = BindingTable::from_statement(statement);
+        // the bounds of the loop are numbers, so the loop variable is the first binding
+        let loop_variable = bindings.iter_bindings().next()?;
+
+        if loop_variable.is_written() || loop_variable.is_captured() {
+            return None;
+        }
+
+        let numeric_for = match statement {
+            Statement::NumericFor(numeric_for) => numeric_for,
+            _ => return None,
+        };
+        let variable = numeric_for.get_identifier().get_name().to_owned();
+
+        let block = numeric_for.get_block();
+        // each copy of the body needs its own scope when it declares variables or labels,
+        // or when it ends with a `return` statement
+        let needs_scope = block.get_last_statement().is_some()
+            || block.iter_statements().any(|statement| {
+                matches!(
+                    statement,
+                    Statement::LocalAssign(_) | Statement::LocalFunction(_) | Statement::Label(_)
+                )
+            });
+
+        let mut statements = Vec::new();
+
+        for value in values {
+            let mut copy = block.clone();
+            let mut replacer = LoopVariableReplacer {
+                identifier_tracker: IdentifierTracker::new(),
+                variable: variable.clone(),
+                value,
+            };
+            ScopeVisitor::visit_block(&mut copy, &mut replacer);
+
+            if needs_scope {
+                statements.push(DoStatement::new(copy).into());
+            } else {
+                statements.extend(copy.take_statements());
+            }
+        }
+
+        Some(statements)
+    }
+}
+
+impl NodeProcessor for UnrollProcessor {}
+
+impl NodePostProcessor for UnrollProcessor {
+    // loops are unrolled after their body, so that nested loops are unrolled from the
+    // inside out
+    fn process_after_block(&mut self, block: &mut Block) {
+        let has_loops = block
+            .iter_statements()
+            .any(|statement| matches!(statement, Statement::NumericFor(_)));
+
+        if !has_loops {
+            return;
+        }
+
+        let mut statements = Vec::new();
+
+        for mut statement in block.take_statements() {
+            let unrolled = self.unroll(&mut statement);
+
+            match unrolled {
+                Some(unrolled) => statements.extend(unrolled),
+                None => statements.push(statement),
+            }
+        }
+
+        block.set_statements(statements);
+    }
+}
+
+pub const UNROLL_LOOPS_RULE_NAME: &str = "unroll_loops";
+
+const DEFAULT_MAX_ITERATIONS: usize = 8;
+const DEFAULT_MAX_BODY_STATEMENTS: usize = 4;
+
+/// A rule that unrolls numeric for loops with a small number of iterations.
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnrollLoops {
+    max_iterations: usize,
+    max_body_statements: usize,
+}
+
+impl Default for UnrollLoops {
+    fn default() -> Self {
+        Self {
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            max_body_statements: DEFAULT_MAX_BODY_STATEMENTS,
+        }
+    }
+}
+
+impl UnrollLoops {
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn with_max_body_statements(mut self, max_body_statements: usize) -> Self {
+        self.max_body_statements = max_body_statements;
+        self
+    }
+}
+
+impl FlawlessRule for UnrollLoops {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut processor = UnrollProcessor {
+            max_iterations: self.max_iterations,
+            max_body_statements: self.max_body_statements,
+        };
+        DefaultPostVisitor::visit_block(block, &mut processor);
+    }
+}
+
+impl RuleConfiguration for UnrollLoops {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        for (key, value) in properties {
+            match key.as_str() {
+                "max_iterations" => {
+                    self.max_iterations = value.expect_usize(&key)?;
+                }
+                "max_body_statements" => {
+                    self.max_body_statements = value.expect_usize(&key)?;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        UNROLL_LOOPS_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        if self.max_iterations != DEFAULT_MAX_ITERATIONS {
+            properties.insert("max_iterations".to_owned(), self.max_iterations.into());
+        }
+        if self.max_body_statements != DEFAULT_MAX_BODY_STATEMENTS {
+            properties.insert(
+                "max_body_statements".to_owned(),
+                self.max_body_statements.into(),
+            );
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert("max_iterations".to_owned(), self.max_iterations.into());
+        properties.insert(
+            "max_body_statements".to_owned(),
+            self.max_body_statements.into(),
+        );
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rules::Rule;
+
+    use insta::assert_json_snapshot;
+
+    fn new_rule() -> UnrollLoops {
+        UnrollLoops::default()
+    }
+
+    #[test]
+    fn serialize_default_rule() {
+        let rule: Box<dyn Rule> = Box::new(new_rule());
+
+        assert_json_snapshot!("default_unroll_loops", rule);
+    }
+
+    #[test]
+    fn serialize_rule_with_thresholds() {
+        let rule: Box<dyn Rule> = Box::new(
+            new_rule()
+                .with_max_iterations(4)
+                .with_max_body_statements(2),
+        );
+
+        assert_json_snapshot!("unroll_loops_with_thresholds", rule);
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'unroll_loops',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+}
@@ -1,10 +1,11 @@
+use crate::generator::{LuaGenerator, ReadableLuaGenerator};
 use crate::nodes::{Block, DoStatement, Expression, IfExpression, IfStatement, Statement};
 use crate::process::{DefaultVisitor, Evaluator, NodeProcessor, NodeVisitor};
 use crate::rules::{
     Context, FlawlessRule, RuleConfiguration, RuleConfigurationError, RuleProperties,
 };
 
-use super::verify_no_rule_properties;
+use super::{insert_comment_before_statement, FilterCommentProcessor};
 
 enum FilterResult {
     Keep,
@@ -12,13 +13,94 @@ enum FilterResult {
     Replace(Statement),
 }
 
-#[derive(Debug, Clone, Default)]
-struct IfFilter {
+/// The comments found in a branch removed from an if statement.
+struct RemovedBranch {
+    label: String,
+    comments: Vec<String>,
+}
+
+/// Formats the comments of the removed branches into a single block comment.
+fn format_removed_branches(removed_branches: &[RemovedBranch]) -> Option<String> {
+    let content = removed_branches
+        .iter()
+        .filter(|branch| !branch.comments.is_empty())
+        .map(|branch| {
+            format!(
+                "removed `{}` branch:\n{}",
+                branch.label,
+                branch.comments.join("\n")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if content.is_empty() {
+        return None;
+    }
+
+    let mut equal_count = 0;
+
+    let close_comment = loop {
+        let close_comment = format!("]{}]", "=".repeat(equal_count));
+        if !content.contains(&close_comment) {
+            break close_comment;
+        }
+        equal_count += 1;
+    };
+
+    Some(format!(
+        "--[{}[\n{}\n{}\n",
+        "=".repeat(equal_count),
+        content,
+        close_comment
+    ))
+}
+
+fn generate_condition(keyword: &str, condition: &Expression) -> String {
+    let mut generator = ReadableLuaGenerator::default();
+    generator.write_expression(condition);
+    format!("{} {}", keyword, generator.into_string())
+}
+
+#[derive(Debug, Clone)]
+struct IfFilter<'a> {
     evaluator: Evaluator,
+    preserve_comments_from: Option<&'a str>,
 }
 
-impl IfFilter {
-    fn simplify_if_statement(&self, if_statement: &mut IfStatement) -> FilterResult {
+impl<'a> IfFilter<'a> {
+    fn new(preserve_comments_from: Option<&'a str>) -> Self {
+        Self {
+            evaluator: Evaluator::default(),
+            preserve_comments_from,
+        }
+    }
+
+    /// Collects the comments inside a removed block, when comments are preserved.
+    fn collect_removed_block(
+        &self,
+        label: impl FnOnce() -> String,
+        mut block: Block,
+        removed_branches: &mut Vec<RemovedBranch>,
+    ) {
+        if let Some(code) = self.preserve_comments_from {
+            let except = Vec::new();
+            let mut processor =
+                FilterCommentProcessor::new(code, &except).record_removed_comments();
+            DefaultVisitor::visit_block(&mut block, &mut processor);
+
+            removed_branches.push(RemovedBranch {
+                label: label(),
+                comments: processor.take_removed_comments(),
+            });
+        }
+    }
+
+    fn simplify_if_statement(
+        &self,
+        if_statement: &mut IfStatement,
+        removed_branches: &mut Vec<RemovedBranch>,
+    ) -> FilterResult {
         if let Some(else_block) = if_statement.get_else_block() {
             if else_block.is_empty() {
                 if_statement.take_else_block();
@@ -27,36 +109,56 @@ impl IfFilter {
 
         let mut keep_next_branches = true;
         let mut replace_else_with = None;
+        let mut branch_index = 0;
 
         let is_empty = if_statement.retain_branches_mut(|branch| {
-            keep_next_branches && {
-                let branch_condition_value = self.evaluator.evaluate(branch.get_condition());
-                match branch_condition_value.is_truthy() {
-                    Some(true) => {
-                        keep_next_branches = false;
-
-                        if self.evaluator.has_side_effects(branch.get_condition()) {
-                            true
-                        } else {
-                            replace_else_with = Some(branch.take_block());
-                            false
-                        }
-                    }
-                    Some(false) => {
-                        if self.evaluator.has_side_effects(branch.get_condition()) {
-                            branch.take_block();
-                            true
-                        } else {
-                            false
-                        }
+            let keyword = if branch_index == 0 { "if" } else { "elseif" };
+            branch_index += 1;
+
+            if !keep_next_branches {
+                let block = branch.take_block();
+                self.collect_removed_block(
+                    || generate_condition(keyword, branch.get_condition()),
+                    block,
+                    removed_branches,
+                );
+                return false;
+            }
+
+            let branch_condition_value = self.evaluator.evaluate(branch.get_condition());
+            match branch_condition_value.is_truthy() {
+                Some(true) => {
+                    keep_next_branches = false;
+
+                    if self.evaluator.has_side_effects(branch.get_condition()) {
+                        true
+                    } else {
+                        replace_else_with = Some(branch.take_block());
+                        false
                     }
-                    None => true,
                 }
+                Some(false) => {
+                    let has_side_effects = self.evaluator.has_side_effects(branch.get_condition());
+
+                    let block = branch.take_block();
+                    self.collect_removed_block(
+                        || generate_condition(keyword, branch.get_condition()),
+                        block,
+                        removed_branches,
+                    );
+
+                    has_side_effects
+                }
+                None => true,
             }
         });
 
         if is_empty {
             if let Some(block_replacer) = replace_else_with {
+                if let Some(else_block) = if_statement.take_else_block() {
+                    self.collect_removed_block(|| "else".to_owned(), else_block, removed_branches);
+                }
+
                 if block_replacer.is_empty() {
                     FilterResult::Remove
                 } else {
@@ -73,10 +175,12 @@ impl IfFilter {
             }
         } else {
             if !keep_next_branches {
+                if let Some(else_block) = if_statement.take_else_block() {
+                    self.collect_removed_block(|| "else".to_owned(), else_block, removed_branches);
+                }
+
                 if let Some(block_replacer) = replace_else_with {
                     if_statement.set_else_block(block_replacer);
-                } else {
-                    if_statement.take_else_block();
                 }
             }
             FilterResult::Keep
@@ -172,22 +276,46 @@ impl IfFilter {
     }
 }
 
-impl NodeProcessor for IfFilter {
+impl NodeProcessor for IfFilter<'_> {
     fn process_block(&mut self, block: &mut Block) {
+        let mut index = 0;
+        let mut comments = Vec::new();
+
         block.filter_mut_statements(|statement| {
-            if let Statement::If(if_statement) = statement {
-                match self.simplify_if_statement(if_statement) {
+            let keep = if let Statement::If(if_statement) = statement {
+                let mut removed_branches = Vec::new();
+
+                let keep = match self.simplify_if_statement(if_statement, &mut removed_branches) {
                     FilterResult::Keep => true,
                     FilterResult::Remove => false,
                     FilterResult::Replace(new_statement) => {
                         *statement = new_statement;
                         true
                     }
+                };
+
+                if let Some(comment) = format_removed_branches(&removed_branches) {
+                    comments.push((index, comment));
                 }
+
+                keep
             } else {
                 true
+            };
+
+            if keep {
+                index += 1;
             }
+            keep
         });
+
+        if let Some(code) = self.preserve_comments_from {
+            for (index, comment) in comments {
+                // inserting a comment before a statement can only fail on assignments
+                // without any variable, which cannot be parsed
+                insert_comment_before_statement(block, index, comment, code).ok();
+            }
+        }
     }
 
     fn process_expression(&mut self, expression: &mut Expression) {
@@ -204,18 +332,36 @@ pub const REMOVE_UNUSED_IF_BRANCH_RULE_NAME: &str = "remove_unused_if_branch";
 /// A rule that removes unused if branches. It can also turn a if statement into a do block
 /// statement.
 #[derive(Debug, Default, PartialEq, Eq)]
-pub struct RemoveUnusedIfBranch {}
+pub struct RemoveUnusedIfBranch {
+    preserve_comments: bool,
+}
+
+impl RemoveUnusedIfBranch {
+    /// Keep the comments from the removed branches in a single block comment, placed
+    /// where the if statement was.
+    pub fn preserve_comments(mut self) -> Self {
+        self.preserve_comments = true;
+        self
+    }
+}
 
 impl FlawlessRule for RemoveUnusedIfBranch {
-    fn flawless_process(&self, block: &mut Block, _: &Context) {
-        let mut processor = IfFilter::default();
+    fn flawless_process(&self, block: &mut Block, context: &Context) {
+        let mut processor = IfFilter::new(self.preserve_comments.then(|| context.original_code()));
         DefaultVisitor::visit_block(block, &mut processor);
     }
 }
 
 impl RuleConfiguration for RemoveUnusedIfBranch {
     fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
-        verify_no_rule_properties(&properties)?;
+        for (key, value) in properties {
+            match key.as_str() {
+                "preserve_comments" => {
+                    self.preserve_comments = value.expect_bool(&key)?;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
 
         Ok(())
     }
@@ -225,7 +371,24 @@ impl RuleConfiguration for RemoveUnusedIfBranch {
     }
 
     fn serialize_to_properties(&self) -> RuleProperties {
-        RuleProperties::new()
+        let mut properties = RuleProperties::new();
+
+        if self.preserve_comments {
+            properties.insert("preserve_comments".to_owned(), true.into());
+        }
+
+        properties
+    }
+
+    fn serialize_to_verbose_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert(
+            "preserve_comments".to_owned(),
+            self.preserve_comments.into(),
+        );
+
+        properties
     }
 }
 
@@ -247,6 +410,13 @@ mod test {
         assert_json_snapshot!("default_remove_unused_if_branch", rule);
     }
 
+    #[test]
+    fn serialize_rule_preserving_comments() {
+        let rule: Box<dyn Rule> = Box::new(new_rule().preserve_comments());
+
+        assert_json_snapshot!("remove_unused_if_branch_preserving_comments", rule);
+    }
+
     #[test]
     fn configure_with_extra_field_error() {
         let result = json5::from_str::<Box<dyn Rule>>(
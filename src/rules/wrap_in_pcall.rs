@@ -0,0 +1,366 @@
+use crate::nodes::{
+    Block, BlockTokens, Expression, FieldExpression, FunctionCall, FunctionExpression,
+    FunctionStatement, Identifier, IfStatement, LastStatement, LocalAssignStatement,
+    LocalFunctionStatement, Prefix, ReturnStatement, Statement, TypeFunctionStatement,
+    TypedIdentifier, UnaryExpression, UnaryOperator,
+};
+use crate::process::utils::is_valid_identifier;
+use crate::process::{DefaultPostVisitor, NodePostProcessor, NodePostVisitor, NodeProcessor};
+use crate::rules::{
+    Context, Rule, RuleConfiguration, RuleConfigurationError, RuleProcessResult, RuleProperties,
+};
+
+use super::verify_required_properties;
+
+const HANDLER_PROPERTY: &str = "handler";
+
+const PCALL_IDENTIFIER: &str = "pcall";
+const OK_IDENTIFIER: &str = "__DARKLUA_PCALL_OK";
+const ERROR_IDENTIFIER: &str = "__DARKLUA_PCALL_ERROR";
+const RESULT_FUNCTION_IDENTIFIER: &str = "__DARKLUA_PCALL_RESULT";
+
+/// Finds how the code at the root of the chunk (outside of any function) uses the
+/// variable arguments and return statements.
+#[derive(Debug, Default)]
+struct ChunkUsage {
+    function_depth: usize,
+    uses_variable_arguments: bool,
+    has_return: bool,
+}
+
+impl ChunkUsage {
+    fn find(block: &mut Block) -> Self {
+        let mut usage = Self::default();
+        DefaultPostVisitor::visit_block(block, &mut usage);
+        usage
+    }
+}
+
+impl NodeProcessor for ChunkUsage {
+    fn process_function_expression(&mut self, _: &mut FunctionExpression) {
+        self.function_depth += 1;
+    }
+
+    fn process_function_statement(&mut self, _: &mut FunctionStatement) {
+        self.function_depth += 1;
+    }
+
+    fn process_local_function_statement(&mut self, _: &mut LocalFunctionStatement) {
+        self.function_depth += 1;
+    }
+
+    fn process_type_function_statement(&mut self, _: &mut TypeFunctionStatement) {
+        self.function_depth += 1;
+    }
+
+    fn process_expression(&mut self, expression: &mut Expression) {
+        if self.function_depth == 0 && matches!(expression, Expression::VariableArguments(_)) {
+            self.uses_variable_arguments = true;
+        }
+    }
+
+    fn process_last_statement(&mut self, statement: &mut LastStatement) {
+        if self.function_depth == 0 && matches!(statement, LastStatement::Return(_)) {
+            self.has_return = true;
+        }
+    }
+}
+
+impl NodePostProcessor for ChunkUsage {
+    fn process_after_function_expression(&mut self, _: &mut FunctionExpression) {
+        self.function_depth -= 1;
+    }
+
+    fn process_after_function_statement(&mut self, _: &mut FunctionStatement) {
+        self.function_depth -= 1;
+    }
+
+    fn process_after_local_function_statement(&mut self, _: &mut LocalFunctionStatement) {
+        self.function_depth -= 1;
+    }
+
+    fn process_after_type_function_statement(&mut self, _: &mut TypeFunctionStatement) {
+        self.function_depth -= 1;
+    }
+}
+
+/// Exported types must stay at the root of the chunk.
+fn must_stay_outside(statement: &Statement) -> bool {
+    match statement {
+        Statement::TypeDeclaration(declaration) => declaration.is_exported(),
+        Statement::ExportTypeFunction(_) => true,
+        _ => false,
+    }
+}
+
+pub const WRAP_IN_PCALL_RULE_NAME: &str = "wrap_in_pcall";
+
+/// A rule that runs the code of each file inside a protected call, and calls a handler
+/// function with the error when the code fails.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct WrapInPcall {
+    handler: String,
+}
+
+impl WrapInPcall {
+    /// Creates the rule with the function called with the error, which can be a global
+    /// (like `__reportError`) or a field of a global (like `Logger.error`).
+    pub fn new(handler: impl Into<String>) -> Self {
+        Self {
+            handler: handler.into(),
+        }
+    }
+
+    fn handler_call(&self, error: Expression) -> FunctionCall {
+        let mut names = self.handler.split('.');
+        let mut prefix = Prefix::from(Identifier::new(names.next().unwrap_or_default()));
+
+        for field in names {
+            prefix = FieldExpression::new(prefix, field).into();
+        }
+
+        FunctionCall::from_prefix(prefix).with_argument(error)
+    }
+
+    fn wrap(&self, block: &mut Block) {
+        let usage = ChunkUsage::find(block);
+        let header = block.take_leading_header();
+
+        let final_token = block
+            .mutate_tokens()
+            .and_then(|tokens| tokens.final_token.take());
+
+        let last_statement = block.take_last_statement();
+        let (mut outer_statements, inner_statements): (Vec<_>, Vec<_>) = block
+            .take_statements()
+            .into_iter()
+            .partition(must_stay_outside);
+
+        let mut function =
+            FunctionExpression::from_block(Block::new(inner_statements, last_statement));
+        let mut pcall = FunctionCall::from_name(PCALL_IDENTIFIER);
+
+        if usage.uses_variable_arguments {
+            function = function.variadic();
+            pcall = pcall
+                .with_argument(function)
+                .with_argument(Expression::variable_arguments());
+        } else {
+            pcall = pcall.with_argument(function);
+        }
+
+        let mut last_statement = None;
+
+        if usage.has_return {
+            let result_function = LocalFunctionStatement::from_name(
+                RESULT_FUNCTION_IDENTIFIER,
+                Block::default()
+                    .with_statement(IfStatement::create(
+                        Identifier::new(OK_IDENTIFIER),
+                        Block::default().with_last_statement(ReturnStatement::one(
+                            Expression::variable_arguments(),
+                        )),
+                    ))
+                    .with_statement(
+                        self.handler_call(Expression::variable_arguments().in_parentheses()),
+                    ),
+            )
+            .with_parameter(OK_IDENTIFIER)
+            .variadic();
+
+            outer_statements.push(result_function.into());
+
+            last_statement = Some(
+                ReturnStatement::one(
+                    FunctionCall::from_name(RESULT_FUNCTION_IDENTIFIER).with_argument(pcall),
+                )
+                .into(),
+            );
+        } else {
+            outer_statements.push(
+                LocalAssignStatement::new(
+                    vec![
+                        TypedIdentifier::new(OK_IDENTIFIER),
+                        TypedIdentifier::new(ERROR_IDENTIFIER),
+                    ],
+                    vec![pcall.into()],
+                )
+                .into(),
+            );
+            outer_statements.push(
+                IfStatement::create(
+                    UnaryExpression::new(UnaryOperator::Not, Identifier::new(OK_IDENTIFIER)),
+                    Block::default().with_statement(
+                        self.handler_call(Identifier::new(ERROR_IDENTIFIER).into()),
+                    ),
+                )
+                .into(),
+            );
+        }
+
+        if let Some(first) = outer_statements.first_mut() {
+            first.prepend_leading_header(header);
+        }
+
+        *block = Block::new(outer_statements, last_statement);
+
+        if final_token.is_some() {
+            block.set_tokens(BlockTokens {
+                semicolons: Vec::new(),
+                last_semicolon: None,
+                final_token,
+            });
+        }
+    }
+}
+
+impl Rule for WrapInPcall {
+    fn process(&self, block: &mut Block, _: &Context) -> RuleProcessResult {
+        if self.handler.is_empty() {
+            return Err(format!("`{}` must be defined", HANDLER_PROPERTY).into());
+        }
+
+        if !block.is_empty() {
+            self.wrap(block);
+        }
+
+        Ok(())
+    }
+}
+
+impl RuleConfiguration for WrapInPcall {
+    fn configure(&mut self, properties: RuleProperties) -> Result<(), RuleConfigurationError> {
+        verify_required_properties(&properties, &[HANDLER_PROPERTY])?;
+
+        for (key, value) in properties {
+            match key.as_str() {
+                HANDLER_PROPERTY => {
+                    let handler = value.expect_string(&key)?;
+
+                    if !handler.split('.').all(is_valid_identifier) {
+                        return Err(RuleConfigurationError::UnexpectedValue {
+                            property: key,
+                            message: format!(
+                                "invalid value `{}` (must be a global or fields of a global, like `Logger.error`)",
+                                handler
+                            ),
+                        });
+                    }
+
+                    self.handler = handler;
+                }
+                _ => return Err(RuleConfigurationError::UnexpectedProperty(key)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        WRAP_IN_PCALL_RULE_NAME
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        let mut properties = RuleProperties::new();
+
+        properties.insert(HANDLER_PROPERTY.to_owned(), self.handler.clone().into());
+
+        properties
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        generator::{LuaGenerator, TokenBasedLuaGenerator},
+        rules::{ContextBuilder, Rule},
+        Parser, Resources,
+    };
+
+    use insta::assert_json_snapshot;
+
+    #[test]
+    fn serialize_rule_with_handler() {
+        let rule: Box<dyn Rule> = Box::new(WrapInPcall::new("__reportError"));
+
+        assert_json_snapshot!("wrap_in_pcall_with_handler", rule);
+    }
+
+    #[test]
+    fn configure_without_handler_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'wrap_in_pcall',
+        }"#,
+        );
+        pretty_assertions::assert_eq!(
+            result.unwrap_err().to_string(),
+            "missing required field 'handler'"
+        );
+    }
+
+    #[test]
+    fn configure_with_invalid_handler_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'wrap_in_pcall',
+            handler: 'report error',
+        }"#,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn configure_with_extra_field_error() {
+        let result = json5::from_str::<Box<dyn Rule>>(
+            r#"{
+            rule: 'wrap_in_pcall',
+            handler: '__reportError',
+            prop: "something",
+        }"#,
+        );
+        pretty_assertions::assert_eq!(result.unwrap_err().to_string(), "unexpected field 'prop'");
+    }
+
+    fn process_with_tokens(code: &str) -> String {
+        let mut block = Parser::default()
+            .preserve_tokens()
+            .parse(code)
+            .expect("unable to parse code");
+
+        WrapInPcall::new("__reportError")
+            .process(
+                &mut block,
+                &ContextBuilder::new(".", &Resources::from_memory(), code).build(),
+            )
+            .expect("rule should succeed");
+
+        let mut generator = TokenBasedLuaGenerator::new(code);
+        generator.write_block(&block);
+        generator.into_string()
+    }
+
+    #[test]
+    fn keeps_directive_comments_outside_of_the_wrapper() {
+        let output =
+            process_with_tokens("--!strict\n-- the module header\nlocal value = 1\nreturn value\n");
+
+        assert!(
+            output.starts_with("--!strict\n-- the module header\nlocal function"),
+            "unexpected output: {}",
+            output
+        );
+    }
+
+    #[test]
+    fn keeps_shebang_outside_of_the_wrapper() {
+        let output = process_with_tokens("#!/usr/bin/env lune\nprint('hello')\n");
+
+        assert!(
+            output.starts_with("#!/usr/bin/env lune\nlocal "),
+            "unexpected output: {}",
+            output
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Utilities to test rules that need more than the code of the file they process.
+//!
+//! Rules like [`ConvertRequire`](crate::rules::ConvertRequire) read other files and
+//! resolve paths from the location of the current file. [`RuleTestContext`] holds
+//! in-memory files and the paths used to build the [`Context`] given to those rules.
+
+use std::path::{Path, PathBuf};
+
+use crate::{
+    nodes::Block,
+    rules::{Context, ContextBuilder, Rule},
+    Parser, Resources,
+};
+
+/// In-memory files with the path of the file being processed and the project location,
+/// used to apply a rule without accessing the file system.
+///
+/// ```
+/// # use darklua_core::{rules::ConvertRequire, test_utils::RuleTestContext};
+/// let context = RuleTestContext::new("src/main.lua")
+///     .with_file("src/value.lua", "return true");
+///
+/// let block = context
+///     .process(&ConvertRequire::default(), "local value = require('./value')")
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RuleTestContext {
+    resources: Resources,
+    current_path: PathBuf,
+    project_location: Option<PathBuf>,
+}
+
+impl RuleTestContext {
+    /// Creates a context for the rule applied to the file at the given path.
+    pub fn new(current_path: impl Into<PathBuf>) -> Self {
+        Self {
+            resources: Resources::from_memory(),
+            current_path: current_path.into(),
+            project_location: None,
+        }
+    }
+
+    /// Adds a file to the resources available to the rule.
+    pub fn with_file(self, path: impl AsRef<Path>, content: impl AsRef<str>) -> Self {
+        self.resources
+            .write(path, content.as_ref())
+            .expect("memory resources should accept writes");
+        self
+    }
+
+    /// Sets the location from where the project paths are resolved (like the sources of
+    /// the path require mode). When not set, the directory of the current file is used,
+    /// like it is done when processing a file without a configuration file.
+    pub fn with_project_location(mut self, path: impl Into<PathBuf>) -> Self {
+        self.project_location = Some(path.into());
+        self
+    }
+
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    pub fn current_path(&self) -> &Path {
+        &self.current_path
+    }
+
+    /// Builds the context given to a rule that processes the given code.
+    pub fn build_context<'a>(&'a self, code: &'a str) -> Context<'a, 'a, 'a> {
+        let mut builder = ContextBuilder::new(&self.current_path, &self.resources, code);
+
+        if let Some(project_location) = &self.project_location {
+            builder = builder.with_project_location(project_location);
+        }
+
+        builder.build()
+    }
+
+    /// Parses the code, writes it at the current path and applies the rule on it.
+    pub fn process(&self, rule: &dyn Rule, code: &str) -> Result<Block, String> {
+        let mut block = Parser::default()
+            .parse(code)
+            .map_err(|error| error.to_string())?;
+
+        self.resources
+            .write(&self.current_path, code)
+            .map_err(|error| error.to_string())?;
+
+        rule.process(&mut block, &self.build_context(code))?;
+
+        Ok(block)
+    }
+}
@@ -0,0 +1,270 @@
+use crate::nodes::{Block, Expression, Type};
+use crate::process::{DefaultPostVisitor, NodePostProcessor, NodePostVisitor, NodeProcessor};
+
+/// The default maximum nesting depth of the code that is processed. The parser, the rules
+/// and the generators are recursive, so each level of nesting uses some of the stack given
+/// by [`DEFAULT_STACK_SIZE`](super::DEFAULT_STACK_SIZE). This limit keeps the deepest code
+/// well within that stack, so that it produces an error instead of a stack overflow.
+pub(crate) const DEFAULT_DEPTH_LIMIT: usize = 30_000;
+
+/// Estimates how deeply the code is nested without parsing it, from its brackets and from
+/// the `do`, `function` and `repeat` keywords (closed by `end` and `until`). Strings and
+/// comments are skipped. Other statements like `if` are not counted, so the estimate can
+/// be lower than the real depth, but never higher.
+pub(crate) fn estimate_code_depth(code: &str) -> usize {
+    let bytes = code.as_bytes();
+    let mut index = 0;
+    let mut depth: usize = 0;
+    let mut max_depth = 0;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'-' if bytes.get(index + 1) == Some(&b'-') => {
+                index = match long_bracket_level(bytes, index + 2) {
+                    Some(level) => skip_long_bracket(code, index + 2, level),
+                    None => skip_line(bytes, index + 2),
+                };
+                continue;
+            }
+            b'[' => {
+                if let Some(level) = long_bracket_level(bytes, index) {
+                    index = skip_long_bracket(code, index, level);
+                    continue;
+                }
+                depth += 1;
+            }
+            b'(' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+            b'"' | b'\'' | b'`' => {
+                index = skip_string(bytes, index + 1, bytes[index]);
+                continue;
+            }
+            byte if byte.is_ascii_alphanumeric() || byte == b'_' => {
+                let start = index;
+                while index < bytes.len()
+                    && (bytes[index].is_ascii_alphanumeric() || bytes[index] == b'_')
+                {
+                    index += 1;
+                }
+                match &code[start..index] {
+                    "do" | "function" | "repeat" => depth += 1,
+                    "end" | "until" => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                max_depth = max_depth.max(depth);
+                continue;
+            }
+            _ => {}
+        }
+        max_depth = max_depth.max(depth);
+        index += 1;
+    }
+
+    max_depth
+}
+
+/// Returns the level of the long bracket (the number of `=`) that opens at `index`.
+fn long_bracket_level(bytes: &[u8], index: usize) -> Option<usize> {
+    if bytes.get(index) != Some(&b'[') {
+        return None;
+    }
+    let level = bytes[index + 1..]
+        .iter()
+        .take_while(|byte| **byte == b'=')
+        .count();
+    if bytes.get(index + 1 + level) == Some(&b'[') {
+        Some(level)
+    } else {
+        None
+    }
+}
+
+fn skip_long_bracket(code: &str, index: usize, level: usize) -> usize {
+    let closing = format!("]{}]", "=".repeat(level));
+    let content_start = index + level + 2;
+    code[content_start..]
+        .find(&closing)
+        .map(|offset| content_start + offset + closing.len())
+        .unwrap_or(code.len())
+}
+
+fn skip_line(bytes: &[u8], index: usize) -> usize {
+    bytes[index..]
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .map(|offset| index + offset + 1)
+        .unwrap_or(bytes.len())
+}
+
+fn skip_string(bytes: &[u8], mut index: usize, quote: u8) -> usize {
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => index += 2,
+            b'\n' if quote != b'`' => return index + 1,
+            byte if byte == quote => return index + 1,
+            _ => index += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Returns true when the blocks, expressions and types of the block are nested deeper than
+/// the limit. The traversal stops as soon as the limit is exceeded, so it never recurses
+/// much further than the limit.
+pub(crate) fn exceeds_depth_limit(block: &mut Block, limit: usize) -> bool {
+    let mut processor = DepthLimitProcessor::new(limit);
+    DefaultPostVisitor::visit_block(block, &mut processor);
+    processor.exceeded
+}
+
+struct DepthLimitProcessor {
+    depth: usize,
+    limit: usize,
+    exceeded: bool,
+}
+
+impl DepthLimitProcessor {
+    fn new(limit: usize) -> Self {
+        Self {
+            depth: 0,
+            limit,
+            exceeded: false,
+        }
+    }
+
+    fn enter(&mut self) {
+        self.depth += 1;
+        if self.depth > self.limit {
+            self.exceeded = true;
+        }
+    }
+
+    fn leave(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+}
+
+impl NodeProcessor for DepthLimitProcessor {
+    fn is_stopped(&self) -> bool {
+        self.exceeded
+    }
+
+    fn process_block(&mut self, _: &mut Block) {
+        self.enter();
+    }
+
+    fn process_expression(&mut self, _: &mut Expression) {
+        self.enter();
+    }
+
+    fn process_type(&mut self, _: &mut Type) {
+        self.enter();
+    }
+}
+
+impl NodePostProcessor for DepthLimitProcessor {
+    fn process_after_block(&mut self, _: &mut Block) {
+        self.leave();
+    }
+
+    fn process_after_expression(&mut self, _: &mut Expression) {
+        self.leave();
+    }
+
+    fn process_after_type(&mut self, _: &mut Type) {
+        self.leave();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::Parser;
+
+    #[test]
+    fn estimate_depth_of_empty_code() {
+        assert_eq!(estimate_code_depth(""), 0);
+    }
+
+    #[test]
+    fn estimate_depth_of_brackets() {
+        assert_eq!(estimate_code_depth("local a = ((t[{ 1 }]))"), 4);
+    }
+
+    #[test]
+    fn estimate_depth_of_blocks() {
+        assert_eq!(
+            estimate_code_depth("do local function f() repeat until x end end"),
+            3
+        );
+    }
+
+    #[test]
+    fn estimate_depth_skips_strings() {
+        assert_eq!(estimate_code_depth("local a = '((\\'(' .. \"do ((\""), 0);
+    }
+
+    #[test]
+    fn estimate_depth_skips_interpolated_strings() {
+        assert_eq!(estimate_code_depth("local a = `((`"), 0);
+    }
+
+    #[test]
+    fn estimate_depth_skips_long_strings() {
+        assert_eq!(estimate_code_depth("local a = [==[ (( ]] ]==]"), 0);
+    }
+
+    #[test]
+    fn estimate_depth_skips_comments() {
+        assert_eq!(estimate_code_depth("-- ((\n--[[ do ( ]] return"), 0);
+    }
+
+    #[test]
+    fn estimate_depth_skips_keywords_inside_identifiers() {
+        assert_eq!(estimate_code_depth("local done, functions = 1, 2"), 0);
+    }
+
+    #[test]
+    fn estimate_depth_does_not_count_if_statements() {
+        assert_eq!(estimate_code_depth("if a then if b then end end"), 0);
+    }
+
+    #[test]
+    fn estimate_depth_of_unbalanced_code() {
+        assert_eq!(estimate_code_depth(")) end ((("), 3);
+    }
+
+    fn parse(code: &str) -> Block {
+        Parser::default().parse(code).expect("code should parse")
+    }
+
+    #[test]
+    fn block_within_limit() {
+        let mut block = parse("return (((1)))");
+
+        assert!(!exceeds_depth_limit(&mut block, 5));
+    }
+
+    #[test]
+    fn nested_parentheses_exceed_limit() {
+        let mut block = parse("return (((1)))");
+
+        assert!(exceeds_depth_limit(&mut block, 4));
+    }
+
+    #[test]
+    fn nested_blocks_exceed_limit() {
+        let mut block = parse("do do do end end end");
+
+        assert!(exceeds_depth_limit(&mut block, 3));
+        assert!(!exceeds_depth_limit(&mut block, 4));
+    }
+
+    #[test]
+    fn binary_chain_exceeds_limit() {
+        let mut block = parse("return 1 + 2 + 3 + 4 + 5");
+
+        assert!(exceeds_depth_limit(&mut block, 4));
+    }
+}
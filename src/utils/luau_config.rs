@@ -1,92 +1,338 @@
 use std::{
-    cell::RefCell,
     collections::HashMap,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
 };
 
-use serde::Deserialize;
+use serde_json::Value;
 
 use crate::{DarkluaError, Resources};
 
 const LUAU_RC_FILE_NAME: &str = ".luaurc";
+const ALIASES_FIELD: &str = "aliases";
+const ALIAS_PREFIX: char = '@';
 
-#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub(crate) struct LuauConfiguration {
     pub(crate) aliases: HashMap<String, PathBuf>,
 }
 
+fn describe_json_value(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Parses the content of a `.luaurc` file. Like Luau, comments and trailing commas are
+/// allowed. The alias paths are resolved relative to the directory of the file.
+fn parse_luau_configuration(
+    config_path: &Path,
+    directory: &Path,
+    content: &str,
+) -> Result<LuauConfiguration, DarkluaError> {
+    let invalid_file =
+        |message: String| DarkluaError::invalid_configuration_file(config_path).context(message);
+
+    let value: Value = json5::from_str(content).map_err(|err| invalid_file(err.to_string()))?;
+
+    let object = match value {
+        Value::Object(object) => object,
+        other => {
+            return Err(invalid_file(format!(
+                "expected an object, but found {}",
+                describe_json_value(&other)
+            )))
+        }
+    };
+
+    let aliases = match object.get(ALIASES_FIELD) {
+        None => return Ok(LuauConfiguration::default()),
+        Some(Value::Object(aliases)) => aliases,
+        Some(other) => {
+            return Err(invalid_file(format!(
+                "expected `{}` to be an object, but found {}",
+                ALIASES_FIELD,
+                describe_json_value(other)
+            )))
+        }
+    };
+
+    let mut config = LuauConfiguration::default();
+
+    for (name, value) in aliases {
+        let alias_path = match value {
+            Value::String(alias_path) => alias_path,
+            other => {
+                return Err(invalid_file(format!(
+                    "expected the alias `{}.{}` to be a string path, but found {}",
+                    ALIASES_FIELD,
+                    name,
+                    describe_json_value(other)
+                )))
+            }
+        };
+
+        let alias_name = match name.strip_prefix(ALIAS_PREFIX) {
+            Some(stripped_name) => {
+                log::warn!(
+                    "alias `{}` in `{}` should not start with `{}` (it is added automatically)",
+                    name,
+                    config_path.display(),
+                    ALIAS_PREFIX,
+                );
+                stripped_name
+            }
+            None => name.as_str(),
+        };
+
+        if alias_name.is_empty() || alias_name.contains(['/', '\\']) {
+            return Err(invalid_file(format!(
+                "invalid alias name `{}.{}`: expected a non-empty name without `/`",
+                ALIASES_FIELD, name
+            )));
+        }
+
+        config.aliases.insert(
+            format!("{}{}", ALIAS_PREFIX, alias_name),
+            directory.join(alias_path),
+        );
+    }
+
+    Ok(config)
+}
+
+/// The result of searching the closest `.luaurc` file of a Luau file.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LuauConfigurationSearch {
+    pub(crate) configuration: Option<LuauConfiguration>,
+    /// The `.luaurc` paths that were checked, including the ones that do not exist. Creating
+    /// one of the missing files changes the configuration found.
+    pub(crate) checked_paths: Vec<PathBuf>,
+}
+
 fn find_luau_configuration_private(
     luau_file: &Path,
     resources: &Resources,
-) -> Result<Option<LuauConfiguration>, DarkluaError> {
+) -> Result<LuauConfigurationSearch, DarkluaError> {
     log::debug!(
         "find closest {} for '{}'",
         LUAU_RC_FILE_NAME,
         luau_file.display()
     );
 
-    for ancestor in luau_file.ancestors() {
+    let mut search = LuauConfigurationSearch::default();
+
+    for ancestor in luau_file.parent().into_iter().flat_map(Path::ancestors) {
         let config_path = ancestor.join(LUAU_RC_FILE_NAME);
+        search.checked_paths.push(config_path.clone());
 
         if resources.exists(&config_path)? {
-            let config = resources.get(&config_path)?;
-
-            return serde_json::from_str(&config)
-                .map(|mut config: LuauConfiguration| {
-                    log::debug!("found luau configuration at '{}'", config_path.display());
-
-                    config.aliases = config
-                        .aliases
-                        .into_iter()
-                        .map(|(mut key, value)| {
-                            key.insert(0, '@');
-                            (key, ancestor.join(value))
-                        })
-                        .collect();
-
-                    Some(config)
-                })
-                .map_err(Into::into);
+            let content = resources.get(&config_path)?;
+
+            let config = parse_luau_configuration(&config_path, ancestor, &content)?;
+            log::debug!("found luau configuration at '{}'", config_path.display());
+
+            search.configuration = Some(config);
+            break;
         }
     }
 
-    Ok(None)
+    Ok(search)
 }
 
-thread_local! {
-    static LUAU_RC_CACHE: RefCell<HashMap<Option<PathBuf>, Option<LuauConfiguration>>> =  RefCell::new(HashMap::new());
+/// Caches the closest `.luaurc` configuration of each directory during a process run. Clones
+/// share the same cache, so that the workers running on other threads can reuse it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LuauConfigurationCache {
+    configurations: Arc<Mutex<HashMap<Option<PathBuf>, LuauConfigurationSearch>>>,
+}
+
+impl LuauConfigurationCache {
+    fn get(&self, key: &Option<PathBuf>) -> Option<LuauConfigurationSearch> {
+        self.configurations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .cloned()
+    }
+
+    fn insert(&self, key: Option<PathBuf>, value: LuauConfigurationSearch) {
+        self.configurations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, value);
+    }
 }
 
 pub(crate) fn find_luau_configuration(
     luau_file: &Path,
     resources: &Resources,
-) -> Result<Option<LuauConfiguration>, DarkluaError> {
+    cache: Option<&LuauConfigurationCache>,
+) -> Result<LuauConfigurationSearch, DarkluaError> {
+    let cache = match cache {
+        Some(cache) => cache,
+        None => return find_luau_configuration_private(luau_file, resources),
+    };
+
     let key = luau_file.parent().map(Path::to_path_buf);
 
-    LUAU_RC_CACHE.with(|luau_rc_cache| {
-        {
-            let cache = luau_rc_cache.borrow();
+    if let Some(res) = cache.get(&key) {
+        return Ok(res);
+    }
 
-            let res = cache.get(&key);
-            if let Some(res) = res {
-                return Ok(res.clone());
-            }
-        }
+    let value = find_luau_configuration_private(luau_file, resources)?;
 
-        let mut cache = luau_rc_cache.borrow_mut();
+    cache.insert(key, value.clone());
 
-        let value = find_luau_configuration_private(luau_file, resources)?;
+    Ok(value)
+}
 
-        cache.insert(key, value.clone());
+#[cfg(test)]
+mod test {
+    use super::*;
 
-        Ok(value)
-    })
-}
+    fn parse(content: &str) -> Result<LuauConfiguration, DarkluaError> {
+        parse_luau_configuration(Path::new("project/.luaurc"), Path::new("project"), content)
+    }
+
+    #[test]
+    fn parse_aliases_with_comments_and_trailing_commas() {
+        let config = parse(
+            r#"{
+                // comments are allowed like in Luau
+                "languageMode": "strict",
+                "aliases": {
+                    "pkg": "Packages", /* block comments too */
+                },
+            }"#,
+        )
+        .unwrap();
+
+        pretty_assertions::assert_eq!(
+            config.aliases,
+            HashMap::from([("@pkg".to_owned(), PathBuf::from("project/Packages"))])
+        );
+    }
+
+    #[test]
+    fn parse_without_aliases() {
+        let config = parse(r#"{ "languageMode": "nonstrict" }"#).unwrap();
+
+        assert!(config.aliases.is_empty());
+    }
 
-pub fn clear_luau_configuration_cache() {
-    LUAU_RC_CACHE.with(|luau_rc_cache| {
-        let mut cache = luau_rc_cache.borrow_mut();
-        cache.clear();
-        log::debug!("luau configuration cache cleared");
-    })
+    #[test]
+    fn parse_alias_starting_with_prefix() {
+        let config = parse(r#"{ "aliases": { "@pkg": "Packages" } }"#).unwrap();
+
+        pretty_assertions::assert_eq!(
+            config.aliases,
+            HashMap::from([("@pkg".to_owned(), PathBuf::from("project/Packages"))])
+        );
+    }
+
+    #[test]
+    fn parse_alias_with_invalid_type_errors() {
+        let error = parse(r#"{ "aliases": { "pkg": 10 } }"#).unwrap_err();
+
+        pretty_assertions::assert_eq!(
+            error.to_string().replace('\\', "/"),
+            "invalid configuration file at `project/.luaurc` \
+            (expected the alias `aliases.pkg` to be a string path, but found a number)"
+        );
+    }
+
+    #[test]
+    fn parse_aliases_with_invalid_type_errors() {
+        let error = parse(r#"{ "aliases": ["Packages"] }"#).unwrap_err();
+
+        pretty_assertions::assert_eq!(
+            error.to_string().replace('\\', "/"),
+            "invalid configuration file at `project/.luaurc` \
+            (expected `aliases` to be an object, but found an array)"
+        );
+    }
+
+    #[test]
+    fn parse_alias_name_with_slash_errors() {
+        let error = parse(r#"{ "aliases": { "pkg/sub": "Packages" } }"#).unwrap_err();
+
+        pretty_assertions::assert_eq!(
+            error.to_string().replace('\\', "/"),
+            "invalid configuration file at `project/.luaurc` \
+            (invalid alias name `aliases.pkg/sub`: expected a non-empty name without `/`)"
+        );
+    }
+
+    #[test]
+    fn parse_invalid_json_errors_with_file_path() {
+        let error = parse("{ \"aliases\": { \"pkg\": } }").unwrap_err();
+
+        let message = error.to_string().replace('\\', "/");
+        assert!(
+            message.starts_with("invalid configuration file at `project/.luaurc` ("),
+            "unexpected error message: {}",
+            message
+        );
+    }
+
+    fn find_package_alias(resources: &Resources, cache: &LuauConfigurationCache) -> PathBuf {
+        find_luau_configuration(Path::new("src/main.lua"), resources, Some(cache))
+            .unwrap()
+            .configuration
+            .expect("configuration should be found")
+            .aliases
+            .remove("@pkg")
+            .expect("alias should be defined")
+    }
+
+    #[test]
+    fn caches_of_different_runs_are_independent() {
+        let first = Resources::from_memory();
+        first
+            .write(".luaurc", r#"{ "aliases": { "pkg": "a" } }"#)
+            .unwrap();
+        let second = Resources::from_memory();
+        second
+            .write(".luaurc", r#"{ "aliases": { "pkg": "b" } }"#)
+            .unwrap();
+
+        let first_cache = LuauConfigurationCache::default();
+        let second_cache = LuauConfigurationCache::default();
+
+        assert_eq!(find_package_alias(&first, &first_cache), PathBuf::from("a"));
+        assert_eq!(
+            find_package_alias(&second, &second_cache),
+            PathBuf::from("b")
+        );
+        assert_eq!(
+            find_package_alias(&first, &first_cache.clone()),
+            PathBuf::from("a")
+        );
+    }
+
+    #[test]
+    fn search_records_checked_paths_up_to_the_configuration() {
+        let resources = Resources::from_memory();
+        resources
+            .write("project/.luaurc", r#"{ "aliases": {} }"#)
+            .unwrap();
+
+        let search =
+            find_luau_configuration(Path::new("project/src/main.lua"), &resources, None).unwrap();
+
+        assert!(search.configuration.is_some());
+        pretty_assertions::assert_eq!(
+            search.checked_paths,
+            vec![
+                PathBuf::from("project/src/.luaurc"),
+                PathBuf::from("project/.luaurc"),
+            ]
+        );
+    }
 }
@@ -1,11 +1,21 @@
+mod depth_limit;
 mod expressions_as_statement;
 mod luau_config;
+mod random;
+mod serde_error_path;
 mod serde_string_or_struct;
+mod stack;
 mod timer;
 
+pub(crate) use depth_limit::{estimate_code_depth, exceeds_depth_limit, DEFAULT_DEPTH_LIMIT};
 pub(crate) use expressions_as_statement::{expressions_as_expression, expressions_as_statement};
-pub(crate) use luau_config::{clear_luau_configuration_cache, find_luau_configuration};
+pub(crate) use luau_config::{find_luau_configuration, LuauConfigurationCache};
+pub(crate) use random::SeededRandom;
+pub(crate) use serde_error_path::{
+    deserialize_at_path, deserialize_indexed_list, split_error_path, with_error_path,
+};
 pub(crate) use serde_string_or_struct::string_or_struct;
+pub(crate) use stack::{run_with_stack_size, spawn_scoped_with_stack_size, DEFAULT_STACK_SIZE};
 pub use timer::Timer;
 
 use std::{
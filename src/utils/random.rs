@@ -0,0 +1,74 @@
+/// A small deterministic pseudo-random number generator (SplitMix64). Rules that have
+/// random output use it so that the same seed always produces the same code.
+#[derive(Debug, Clone)]
+pub(crate) struct SeededRandom {
+    state: u64,
+}
+
+impl SeededRandom {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        value ^ (value >> 31)
+    }
+
+    /// Returns a number in the range `0..bound`.
+    pub(crate) fn next_below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "the bound must be greater than zero");
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    pub(crate) fn shuffle<T>(&mut self, values: &mut [T]) {
+        for index in (1..values.len()).rev() {
+            let other = self.next_below(index + 1);
+            values.swap(index, other);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn same_seed_produces_same_numbers() {
+        let mut random = SeededRandom::new(7);
+        let mut other = SeededRandom::new(7);
+
+        for _ in 0..10 {
+            assert_eq!(random.next_u64(), other.next_u64());
+        }
+    }
+
+    #[test]
+    fn different_seeds_produce_different_numbers() {
+        assert_ne!(
+            SeededRandom::new(1).next_u64(),
+            SeededRandom::new(2).next_u64()
+        );
+    }
+
+    #[test]
+    fn next_below_is_in_range() {
+        let mut random = SeededRandom::new(0);
+
+        for _ in 0..100 {
+            assert!(random.next_below(5) < 5);
+        }
+    }
+
+    #[test]
+    fn shuffle_keeps_values() {
+        let mut values: Vec<_> = (0..20).collect();
+        SeededRandom::new(3).shuffle(&mut values);
+        values.sort_unstable();
+
+        assert_eq!(values, (0..20).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,159 @@
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+const PATH_START: &str = " (at `";
+const PATH_END: &str = "`)";
+
+/// Adds the path of the value that failed to deserialize to an error message. When the
+/// message already has a path (from a nested value), the given path is placed in front of
+/// it, so an error bubbling up from a list of rules ends up at a path like
+/// `rules[2].current`.
+pub(crate) fn with_error_path(message: impl fmt::Display, path: &str) -> String {
+    let message = message.to_string();
+
+    match split_error_path(&message) {
+        (message, Some(inner_path)) => format!(
+            "{}{}{}{}",
+            message,
+            PATH_START,
+            join_path(path, inner_path),
+            PATH_END
+        ),
+        (message, None) => format!("{}{}{}{}", message, PATH_START, path, PATH_END),
+    }
+}
+
+/// Splits the path added by [`with_error_path`] from an error message.
+pub(crate) fn split_error_path(message: &str) -> (&str, Option<&str>) {
+    message
+        .strip_suffix(PATH_END)
+        .and_then(|rest| rest.rfind(PATH_START).map(|index| (rest, index)))
+        .map(|(rest, index)| (&rest[..index], Some(&rest[index + PATH_START.len()..])))
+        .filter(|(_, path)| path.map_or(false, |path| !path.contains('`')))
+        .unwrap_or((message, None))
+}
+
+fn join_path(parent: &str, child: &str) -> String {
+    if parent.is_empty() {
+        child.to_owned()
+    } else if child.is_empty() || child.starts_with('[') {
+        format!("{}{}", parent, child)
+    } else {
+        format!("{}.{}", parent, child)
+    }
+}
+
+/// Deserializes a value and adds the given path to its errors.
+pub(crate) fn deserialize_at_path<'de, T, D>(deserializer: D, path: &str) -> Result<T, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map_err(|err| de::Error::custom(with_error_path(err, path)))
+}
+
+/// Deserializes a list and adds the given path and the index of the element to the errors
+/// of its elements.
+pub(crate) fn deserialize_indexed_list<'de, T, D>(
+    deserializer: D,
+    path: &'static str,
+) -> Result<Vec<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    struct IndexedList<T> {
+        path: &'static str,
+        element: PhantomData<T>,
+    }
+
+    impl<'de, T> Visitor<'de> for IndexedList<T>
+    where
+        T: Deserialize<'de>,
+    {
+        type Value = Vec<T>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a list")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+
+            loop {
+                let index = elements.len();
+                match seq.next_element::<T>() {
+                    Ok(Some(element)) => elements.push(element),
+                    Ok(None) => break,
+                    Err(err) => {
+                        return Err(de::Error::custom(with_error_path(
+                            err,
+                            &format!("{}[{}]", self.path, index),
+                        )))
+                    }
+                }
+            }
+
+            Ok(elements)
+        }
+    }
+
+    deserializer
+        .deserialize_seq(IndexedList {
+            path,
+            element: PhantomData,
+        })
+        .map_err(|err| {
+            let message = err.to_string();
+            match split_error_path(&message) {
+                (_, Some(_)) => err,
+                (message, None) => de::Error::custom(with_error_path(message, path)),
+            }
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn add_path_to_message() {
+        pretty_assertions::assert_eq!(with_error_path("oops", "rules[0]"), "oops (at `rules[0]`)");
+    }
+
+    #[test]
+    fn add_path_in_front_of_nested_path() {
+        pretty_assertions::assert_eq!(
+            with_error_path(with_error_path("oops", "current"), "rules[2]"),
+            "oops (at `rules[2].current`)"
+        );
+    }
+
+    #[test]
+    fn add_path_in_front_of_nested_index() {
+        pretty_assertions::assert_eq!(
+            with_error_path(with_error_path("oops", "[1]"), "overrides"),
+            "oops (at `overrides[1]`)"
+        );
+    }
+
+    #[test]
+    fn split_message_without_path() {
+        pretty_assertions::assert_eq!(split_error_path("oops"), ("oops", None));
+    }
+
+    #[test]
+    fn split_message_with_path() {
+        pretty_assertions::assert_eq!(
+            split_error_path("oops (at `rules[2].current`)"),
+            ("oops", Some("rules[2].current"))
+        );
+    }
+}
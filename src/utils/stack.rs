@@ -0,0 +1,109 @@
+use std::{
+    panic,
+    sync::{Mutex, PoisonError},
+    thread,
+};
+
+/// The size of the stack of the threads that process files. The parser, the rules and the
+/// generators are recursive, so deeply nested code (like thousands of nested parentheses
+/// or `if` statements) needs a lot more stack than the default size of a thread. The memory
+/// is only reserved, it is not used until the stack grows.
+pub(crate) const DEFAULT_STACK_SIZE: usize = 256 * 1024 * 1024;
+
+fn thread_builder(stack_size: Option<usize>) -> thread::Builder {
+    let builder = thread::Builder::new();
+    match stack_size {
+        Some(size) => builder.stack_size(size),
+        None => builder,
+    }
+}
+
+/// Spawns a scoped thread with the given stack size. Panics if the thread can't be
+/// created, like [`thread::Scope::spawn`].
+pub(crate) fn spawn_scoped_with_stack_size<'scope, 'env, T, F>(
+    scope: &'scope thread::Scope<'scope, 'env>,
+    stack_size: Option<usize>,
+    function: F,
+) -> thread::ScopedJoinHandle<'scope, T>
+where
+    F: FnOnce() -> T + Send + 'scope,
+    T: Send + 'scope,
+{
+    thread_builder(stack_size)
+        .spawn_scoped(scope, function)
+        .expect("failed to spawn thread")
+}
+
+/// Runs the function on a new thread with the given stack size and waits for its result.
+/// When no stack size is given, or when the thread can't be created, the function runs on
+/// the current thread.
+pub(crate) fn run_with_stack_size<T, F>(stack_size: Option<usize>, function: F) -> T
+where
+    F: FnOnce() -> T + Send,
+    T: Send,
+{
+    if stack_size.is_none() {
+        return function();
+    }
+
+    let function = Mutex::new(Some(function));
+
+    let result = thread::scope(|scope| {
+        let function = &function;
+        thread_builder(stack_size)
+            .spawn_scoped(scope, move || {
+                let function = function
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .take();
+                function.map(|function| function())
+            })
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|payload| panic::resume_unwind(payload))
+            })
+    });
+
+    match result {
+        Ok(Some(value)) => value,
+        Ok(None) => unreachable!("the function should only be taken by the spawned thread"),
+        Err(err) => {
+            log::debug!("unable to spawn thread with a larger stack: {}", err);
+            let function = function
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner)
+                .expect("the function should not be taken when the thread is not spawned");
+            function()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn run_without_stack_size_returns_result() {
+        assert_eq!(run_with_stack_size(None, || 1 + 2), 3);
+    }
+
+    #[test]
+    fn run_with_stack_size_returns_result() {
+        let mut values = vec![1, 2];
+
+        let length = run_with_stack_size(Some(DEFAULT_STACK_SIZE), || {
+            values.push(3);
+            values.len()
+        });
+
+        assert_eq!(length, 3);
+        assert_eq!(values, vec![1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "oops")]
+    fn run_with_stack_size_propagates_panics() {
+        run_with_stack_size(Some(DEFAULT_STACK_SIZE), || panic!("oops"));
+    }
+}
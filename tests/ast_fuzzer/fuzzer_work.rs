@@ -15,6 +15,8 @@ pub enum AstFuzzerWork {
     },
     FuzzFunctionReturnType,
     FuzzTypedIdentifier,
+    EnterBlockContext(BlockContext),
+    ExitBlockContext,
     MakeBlock {
         has_last_statement: bool,
         statement_count: usize,
@@ -40,6 +42,7 @@ pub enum AstFuzzerWork {
         parameters: usize,
         has_return_type: bool,
         has_variadic_type: bool,
+        is_variadic: bool,
     },
     MakeTypedIdentifier,
     MakeReturnStatement {
@@ -69,11 +72,13 @@ pub enum AstFuzzerWork {
         parameters: usize,
         has_return_type: bool,
         has_variadic_type: bool,
+        is_variadic: bool,
     },
     MakeLocalFunctionStatement {
         parameters: usize,
         has_return_type: bool,
         has_variadic_type: bool,
+        is_variadic: bool,
     },
     MakeLocalAssignStatement {
         variables: usize,
@@ -135,6 +140,14 @@ pub enum AstFuzzerWork {
     },
 }
 
+#[derive(Copy, Clone)]
+pub struct BlockContext {
+    // break and continue statements are only valid inside a loop
+    pub in_loop: bool,
+    // the `...` expression is only valid inside a variadic function
+    pub is_variadic: bool,
+}
+
 #[derive(Clone)]
 pub enum VariableKind {
     Field,
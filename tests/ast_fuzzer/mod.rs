@@ -29,7 +29,10 @@ pub struct AstFuzzer {
     function_return_types: Vec<FunctionReturnType>,
     type_packs: Vec<TypePack>,
     type_parameters: Vec<TypeParameter>,
+    block_contexts: Vec<BlockContext>,
     work_stack: Vec<AstFuzzerWork>,
+    max_depth: Option<usize>,
+    luau_syntax: bool,
 }
 
 impl AstFuzzer {
@@ -51,10 +54,33 @@ impl AstFuzzer {
             function_return_types: Vec::new(),
             type_packs: Vec::new(),
             type_parameters: Vec::new(),
+            block_contexts: vec![BlockContext {
+                in_loop: false,
+                is_variadic: true,
+            }],
             random: RandomAst::default(),
+            max_depth: None,
+            luau_syntax: true,
         }
     }
 
+    /// Limits how deep expressions can be nested into each other.
+    #[allow(dead_code)]
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Only generate the syntax of Lua 5.1: no types, no compound assignments, no
+    /// continue statements, no if expressions, no interpolated strings, no binary
+    /// numbers and no floor division.
+    #[allow(dead_code)]
+    pub fn without_luau_syntax(mut self) -> Self {
+        self.budget = self.budget.with_types(0);
+        self.luau_syntax = false;
+        self
+    }
+
     pub fn fuzz_block(mut self) -> Block {
         self.work_stack.push(AstFuzzerWork::FuzzBlock);
 
@@ -102,10 +128,14 @@ impl AstFuzzer {
                     self.push_repeated_work(AstFuzzerWork::FuzzStatement, statement_count);
                 }
                 AstFuzzerWork::FuzzStatement => {
-                    match self
-                        .random
-                        .range(if self.budget.has_types() { 12 } else { 11 })
-                    {
+                    let bound = if self.budget.has_types() {
+                        12
+                    } else if self.luau_syntax {
+                        11
+                    } else {
+                        10
+                    };
+                    match self.random.range(bound) {
                         0 => {
                             let variables = self.random.assignment_variables();
                             let expressions = self
@@ -132,11 +162,12 @@ impl AstFuzzer {
                         }
                         3 => {
                             self.generate_function(
-                                |parameters, has_return_type, has_variadic_type| {
+                                |parameters, has_return_type, has_variadic_type, is_variadic| {
                                     AstFuzzerWork::MakeFunctionStatement {
                                         parameters,
                                         has_return_type,
                                         has_variadic_type,
+                                        is_variadic,
                                     }
                                 },
                             );
@@ -155,7 +186,7 @@ impl AstFuzzer {
 
                             self.push_repeated_work(AstFuzzerWork::FuzzTypedIdentifier, variables);
                             self.fuzz_multiple_expression(expressions);
-                            self.push_work(AstFuzzerWork::FuzzBlock);
+                            self.fuzz_loop_block();
                         }
                         5 => {
                             self.budget.take_expression();
@@ -195,11 +226,12 @@ impl AstFuzzer {
                         }
                         7 => {
                             self.generate_function(
-                                |parameters, has_return_type, has_variadic_type| {
+                                |parameters, has_return_type, has_variadic_type, is_variadic| {
                                     AstFuzzerWork::MakeLocalFunctionStatement {
                                         parameters,
                                         has_return_type,
                                         has_variadic_type,
+                                        is_variadic,
                                     }
                                 },
                             );
@@ -210,7 +242,7 @@ impl AstFuzzer {
                                 self.budget.take_expression() && self.random.numeric_for_step();
 
                             self.push_work(AstFuzzerWork::MakeNumericForStatement { has_step });
-                            self.push_work(AstFuzzerWork::FuzzBlock);
+                            self.fuzz_loop_block();
                             self.push_work(AstFuzzerWork::FuzzTypedIdentifier);
                             self.fuzz_expression();
                             self.fuzz_expression();
@@ -221,13 +253,13 @@ impl AstFuzzer {
                         }
                         9 => {
                             self.push_work(AstFuzzerWork::MakeRepeatStatement);
-                            self.push_work(AstFuzzerWork::FuzzBlock);
+                            self.fuzz_loop_block();
                             self.budget.take_expression();
                             self.fuzz_expression();
                         }
                         10 => {
                             self.push_work(AstFuzzerWork::MakeWhileStatement);
-                            self.push_work(AstFuzzerWork::FuzzBlock);
+                            self.fuzz_loop_block();
                             self.budget.take_expression();
                             self.fuzz_expression();
                         }
@@ -397,25 +429,37 @@ impl AstFuzzer {
 
                     self.statements.push(type_declaration.into());
                 }
-                AstFuzzerWork::FuzzLastStatement => match self.random.range(2) {
-                    0 => {
-                        self.last_statements.push(LastStatement::new_break());
-                    }
-                    1 => {
-                        self.last_statements.push(LastStatement::new_continue());
-                    }
-                    _ => {
-                        let expressions = self
-                            .budget
-                            .try_take_expressions(self.random.return_length());
-                        self.push_work(AstFuzzerWork::MakeReturnStatement { expressions });
-                        self.fuzz_multiple_expression(expressions);
+                AstFuzzerWork::FuzzLastStatement => {
+                    let in_loop = self.current_block_context().in_loop;
+
+                    match self.random.range(2) {
+                        0 if in_loop => {
+                            self.last_statements.push(LastStatement::new_break());
+                        }
+                        1 if in_loop && self.luau_syntax => {
+                            self.last_statements.push(LastStatement::new_continue());
+                        }
+                        _ => {
+                            let expressions = self
+                                .budget
+                                .try_take_expressions(self.random.return_length());
+                            self.push_work(AstFuzzerWork::MakeReturnStatement { expressions });
+                            self.fuzz_multiple_expression(expressions);
+                        }
                     }
-                },
+                }
+                AstFuzzerWork::EnterBlockContext(context) => {
+                    self.block_contexts.push(context);
+                }
+                AstFuzzerWork::ExitBlockContext => {
+                    self.block_contexts.pop();
+                }
                 AstFuzzerWork::FuzzExpression { depth } => {
-                    let start = if !self.random.nested_expression(depth) {
+                    let start = if !self.random.nested_expression(depth)
+                        || self.max_depth.map_or(false, |max_depth| depth >= max_depth)
+                    {
                         6
-                    } else if self.budget.can_have_expression(3) {
+                    } else if self.budget.can_have_expression(3) && self.luau_syntax {
                         0
                     } else if self.budget.can_have_expression(2) {
                         1
@@ -481,7 +525,11 @@ impl AstFuzzer {
                             self.expressions.push(Expression::nil());
                         }
                         9 => {
-                            self.expressions.push(Expression::variable_arguments());
+                            if self.current_block_context().is_variadic {
+                                self.expressions.push(Expression::variable_arguments());
+                            } else {
+                                self.expressions.push(Expression::nil());
+                            }
                         }
                         10 => {
                             self.push_work(AstFuzzerWork::MakeCallExpression);
@@ -491,11 +539,12 @@ impl AstFuzzer {
                         }
                         11 => {
                             self.generate_function(
-                                |parameters, has_return_type, has_variadic_type| {
+                                |parameters, has_return_type, has_variadic_type, is_variadic| {
                                     AstFuzzerWork::MakeFunctionExpression {
                                         parameters,
                                         has_return_type,
                                         has_variadic_type,
+                                        is_variadic,
                                     }
                                 },
                             );
@@ -504,7 +553,8 @@ impl AstFuzzer {
                             self.expressions.push(self.random.identifier().into());
                         }
                         13 => {
-                            let number = match self.random.range(2) {
+                            let bound = if self.luau_syntax { 2 } else { 1 };
+                            let number = match self.random.range(bound) {
                                 0 => DecimalNumber::new(self.random.decimal_number()).into(),
                                 1 => HexNumber::new(
                                     self.random.hexadecimal_number(),
@@ -1032,6 +1082,7 @@ impl AstFuzzer {
                     parameters,
                     has_return_type,
                     has_variadic_type,
+                    is_variadic,
                 } => {
                     let name = FunctionName::new(
                         self.random.identifier(),
@@ -1048,12 +1099,7 @@ impl AstFuzzer {
                     let block = self.pop_block();
                     let parameters = self.pop_typed_identifiers(parameters);
 
-                    let mut function = FunctionStatement::new(
-                        name,
-                        block,
-                        parameters,
-                        has_variadic_type || self.random.function_is_variadic(),
-                    );
+                    let mut function = FunctionStatement::new(name, block, parameters, is_variadic);
 
                     if let Some(generics) = self.generate_function_generics() {
                         function.set_generic_parameters(generics);
@@ -1079,6 +1125,7 @@ impl AstFuzzer {
                     parameters,
                     has_return_type,
                     has_variadic_type,
+                    is_variadic,
                 } => {
                     let block = self.pop_block();
                     let parameters = self.pop_typed_identifiers(parameters);
@@ -1087,7 +1134,7 @@ impl AstFuzzer {
                         self.random.identifier(),
                         block,
                         parameters,
-                        has_variadic_type || self.random.function_is_variadic(),
+                        is_variadic,
                     );
 
                     if let Some(generics) = self.generate_function_generics() {
@@ -1108,15 +1155,12 @@ impl AstFuzzer {
                     parameters,
                     has_return_type,
                     has_variadic_type,
+                    is_variadic,
                 } => {
                     let block = self.pop_block();
                     let parameters = self.pop_typed_identifiers(parameters);
 
-                    let mut function = FunctionExpression::new(
-                        block,
-                        parameters,
-                        has_variadic_type || self.random.function_is_variadic(),
-                    );
+                    let mut function = FunctionExpression::new(block, parameters, is_variadic);
 
                     if let Some(generics) = self.generate_function_generics() {
                         function.set_generic_parameters(generics);
@@ -1242,7 +1286,10 @@ impl AstFuzzer {
                     self.expressions.push(if_expression.into());
                 }
                 AstFuzzerWork::MakeBinaryExpression => {
-                    let operator = self.random.binary_operator();
+                    let mut operator = self.random.binary_operator();
+                    while !self.luau_syntax && operator == BinaryOperator::DoubleSlash {
+                        operator = self.random.binary_operator();
+                    }
                     let mut left = self.pop_expression();
                     let mut right = self.pop_expression();
 
@@ -1562,7 +1609,7 @@ impl AstFuzzer {
 
     fn generate_function_generics(&mut self) -> Option<GenericParameters> {
         let generic_types_count = self.random.function_generic_types();
-        if generic_types_count > 0 {
+        if generic_types_count > 0 && self.luau_syntax {
             let mut generics = if self.random.function_generic_type_is_generic_pack() {
                 GenericParameters::from_generic_type_pack(GenericTypePack::new(
                     self.random.identifier(),
@@ -1585,18 +1632,26 @@ impl AstFuzzer {
         }
     }
 
-    fn generate_function(&mut self, function_work: impl Fn(usize, bool, bool) -> AstFuzzerWork) {
+    fn generate_function(
+        &mut self,
+        function_work: impl Fn(usize, bool, bool, bool) -> AstFuzzerWork,
+    ) {
         let parameters = self.random.function_parameters();
         let has_return_type = self.random.function_return_type() && self.budget.take_type();
         let has_variadic_type = self.random.function_has_variadic_type() && self.budget.take_type();
+        let is_variadic = has_variadic_type || self.random.function_is_variadic();
 
         self.push_work(function_work(
             parameters,
             has_return_type,
             has_variadic_type,
+            is_variadic,
         ));
 
-        self.push_work(AstFuzzerWork::FuzzBlock);
+        self.fuzz_block_with_context(BlockContext {
+            in_loop: false,
+            is_variadic,
+        });
         self.push_repeated_work(AstFuzzerWork::FuzzTypedIdentifier, parameters);
 
         if has_return_type {
@@ -1608,6 +1663,28 @@ impl AstFuzzer {
         }
     }
 
+    fn current_block_context(&self) -> BlockContext {
+        *self.block_contexts.last().expect("expected block context")
+    }
+
+    // the work stack is processed depth-first, so every block, statement and
+    // expression generated for this block is done between entering and exiting
+    // the context
+    fn fuzz_block_with_context(&mut self, context: BlockContext) {
+        self.push_work(AstFuzzerWork::ExitBlockContext);
+        self.push_work(AstFuzzerWork::FuzzBlock);
+        self.push_work(AstFuzzerWork::EnterBlockContext(context));
+    }
+
+    fn fuzz_loop_block(&mut self) {
+        let is_variadic = self.current_block_context().is_variadic;
+
+        self.fuzz_block_with_context(BlockContext {
+            in_loop: true,
+            is_variadic,
+        });
+    }
+
     fn pop_block(&mut self) -> Block {
         self.blocks.pop().expect("expected block")
     }
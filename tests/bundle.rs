@@ -219,6 +219,47 @@ mod without_rules {
             ));
         }
 
+        #[test]
+        fn require_directory_with_rojo_project_file() {
+            process_main_require_value(memory_resources!(
+                "Packages/foo/default.project.json" => r#"{ "name": "foo", "tree": { "$path": "src" } }"#,
+                "Packages/foo/src/init.luau" => "return true",
+                "src/main.lua" => "local value = require('../Packages/foo')",
+                ".darklua.json" => DARKLUA_BUNDLE_ONLY_READABLE_CONFIG,
+            ));
+        }
+
+        #[test]
+        fn require_source_directory_with_rojo_project_file() {
+            process_main_require_value(memory_resources!(
+                "Packages/foo/default.project.json" => r#"{ "name": "foo", "tree": { "$path": "src" } }"#,
+                "Packages/foo/src/init.luau" => "return true",
+                "src/main.lua" => "local value = require('Packages/foo')",
+                ".darklua.json" => "{ \"rules\": [], \"generator\": \"readable\", \"bundle\": { \"require_mode\": { \"name\": \"path\", \"sources\": { \"Packages\": \"./Packages\" } } } }",
+            ));
+        }
+
+        #[test]
+        fn require_directory_with_rojo_project_file_pointing_to_file() {
+            process_main_require_value(memory_resources!(
+                "Packages/foo/default.project.json" => r#"{ "name": "foo", "tree": { "$path": "lib/value.lua" } }"#,
+                "Packages/foo/lib/value.lua" => "return true",
+                "src/main.lua" => "local value = require('../Packages/foo')",
+                ".darklua.json" => DARKLUA_BUNDLE_ONLY_READABLE_CONFIG,
+            ));
+        }
+
+        #[test]
+        fn require_directory_with_nested_rojo_project_files() {
+            process_main_require_value(memory_resources!(
+                "Packages/foo/default.project.json" => r#"{ "name": "foo", "tree": { "$path": "package" } }"#,
+                "Packages/foo/package/default.project.json" => r#"{ "name": "package", "tree": { "$path": "src" } }"#,
+                "Packages/foo/package/src/init.lua" => "return true",
+                "src/main.lua" => "local value = require('../Packages/foo')",
+                ".darklua.json" => DARKLUA_BUNDLE_ONLY_READABLE_CONFIG,
+            ));
+        }
+
         #[test]
         fn require_directory_with_custom_init_file() {
             process_main_require_value(memory_resources!(
@@ -227,6 +268,34 @@ mod without_rules {
                 ".darklua.json" => "{ \"rules\": [], \"generator\": \"readable\", \"bundle\": { \"require_mode\": { \"name\": \"path\", \"module_folder_name\": \"__init__.lua\" } } }",
             ));
         }
+
+        #[test]
+        fn require_source_pointing_to_file() {
+            process_main_require_value(memory_resources!(
+                "build/config.generated.luau" => "return true",
+                "src/main.lua" => "local value = require('@config')",
+                ".darklua.json" => "{ \"rules\": [], \"generator\": \"readable\", \"bundle\": { \"require_mode\": { \"name\": \"path\", \"sources\": { \"@config\": \"./build/config.generated.luau\" } } } }",
+            ));
+        }
+
+        #[test]
+        fn require_source_pointing_to_file_without_extension() {
+            process_main_require_value(memory_resources!(
+                "build/config.generated.luau" => "return true",
+                "src/main.lua" => "local value = require('@config')",
+                ".darklua.json" => "{ \"rules\": [], \"generator\": \"readable\", \"bundle\": { \"require_mode\": { \"name\": \"path\", \"sources\": { \"@config\": \"./build/config.generated\" } } } }",
+            ));
+        }
+
+        #[test]
+        fn require_luaurc_alias_pointing_to_file_without_extension() {
+            process_main_require_value(memory_resources!(
+                "build/config.lua" => "return true",
+                "src/main.lua" => "local value = require('@config')",
+                ".luaurc" => r#"{ "aliases": { "config": "build/config" } }"#,
+                ".darklua.json" => DARKLUA_BUNDLE_ONLY_READABLE_CONFIG,
+            ));
+        }
     }
 
     #[test]
@@ -286,6 +355,57 @@ mod without_rules {
         process_main(&resources, "require_lua_file_twice_with_different_paths");
     }
 
+    #[test]
+    fn require_module_through_source_alias_and_relative_path() {
+        let resources = memory_resources!(
+            "Packages/Promise/init.lua" => "print('load promise module') return {}",
+            "src/a.lua" => "local Promise = require('@pkg/Promise')\nreturn Promise",
+            "src/main.lua" => concat!(
+                "local Promise = require('../Packages/Promise')\n",
+                "local a = require('./a')\n",
+                "print(Promise == a)"
+            ),
+            ".darklua.json" => concat!(
+                "{ \"rules\": [], \"generator\": \"readable\", \"bundle\": { \"require_mode\": ",
+                "{ \"name\": \"path\", \"sources\": { \"@pkg\": \"./Packages\" } } } }"
+            ),
+        );
+
+        process(
+            &resources,
+            Options::new("src/main.lua").with_output("out.lua"),
+        )
+        .unwrap()
+        .result()
+        .unwrap();
+
+        let main = resources.get("out.lua").unwrap();
+
+        assert_eq!(
+            main.matches("load promise module").count(),
+            1,
+            "module is bundled more than once:\n{}",
+            main
+        );
+
+        let promise_module_id = main
+            .lines()
+            .find(|line| line.contains("local Promise = __DARKLUA_BUNDLE_MODULES.load("))
+            .map(|line| {
+                line.trim()
+                    .trim_start_matches("local Promise = ")
+                    .to_owned()
+            })
+            .expect("unable to find the bundled require call");
+
+        assert_eq!(
+            main.matches(&promise_module_id).count(),
+            2,
+            "both require calls should load the same module:\n{}",
+            main
+        );
+    }
+
     #[test]
     fn require_lua_file_with_field_expression() {
         let resources = memory_resources!(
@@ -416,6 +536,28 @@ data:
         process_main_with_errors(&resources, "require_unknown_module");
     }
 
+    #[test]
+    fn require_rojo_project_file_without_root_path() {
+        let resources = memory_resources!(
+            "Packages/foo/default.project.json" => r#"{ "name": "foo", "tree": { "$className": "Folder" } }"#,
+            "src/main.lua" => "local library = require('../Packages/foo')",
+            ".darklua.json" => DARKLUA_BUNDLE_ONLY_READABLE_CONFIG,
+        );
+
+        process_main_with_errors(&resources, "require_rojo_project_file_without_root_path");
+    }
+
+    #[test]
+    fn require_rojo_project_file_pointing_to_itself() {
+        let resources = memory_resources!(
+            "Packages/foo/default.project.json" => r#"{ "name": "foo", "tree": { "$path": "." } }"#,
+            "src/main.lua" => "local library = require('../Packages/foo')",
+            ".darklua.json" => DARKLUA_BUNDLE_ONLY_READABLE_CONFIG,
+        );
+
+        process_main_with_errors(&resources, "require_rojo_project_file_pointing_to_itself");
+    }
+
     #[test]
     fn require_unknown_relative_file() {
         let resources = memory_resources!(
@@ -426,6 +568,79 @@ data:
         process_main_with_errors(&resources, "require_unknown_relative_file");
     }
 
+    #[test]
+    fn require_unknown_relative_file_error_points_at_call() {
+        let resources = memory_resources!(
+            "src/main.lua" => "local a = 1\nlocal b = 2\nlocal value = require('./missing')\nreturn value",
+            ".darklua.json" => DARKLUA_BUNDLE_ONLY_RETAIN_LINES_CONFIG,
+        );
+
+        let errors = process(
+            &resources,
+            Options::new("src/main.lua").with_output("out.lua"),
+        )
+        .unwrap()
+        .result()
+        .unwrap_err();
+
+        pretty_assertions::assert_eq!(errors.len(), 1);
+        let error = errors.first().unwrap();
+
+        pretty_assertions::assert_eq!(error.line(), Some(3));
+        pretty_assertions::assert_eq!(error.column(), Some(15));
+        pretty_assertions::assert_eq!(
+            error.to_string().replace('\\', "/"),
+            concat!(
+                "error processing `src/main.lua:3:15` (bundler): unable to find `src/missing` ",
+                "(tried `src/missing`, `src/missing.luau`, `src/missing.lua`, `src/missing/init`, ",
+                "`src/missing/init.luau`, `src/missing/init.lua`)\n",
+                "3 | local value = require('./missing')\n",
+                "  |               ^",
+            )
+        );
+    }
+
+    #[test]
+    fn require_source_pointing_to_file_with_remaining_path_errors() {
+        let resources = memory_resources!(
+            "build/config.luau" => "return true",
+            "src/main.lua" => "local a = 1\nlocal value = require('@config/sub')",
+            ".darklua.json" => concat!(
+                "{ \"rules\": [], \"generator\": \"retain_lines\", \"bundle\": { \"require_mode\": ",
+                "{ \"name\": \"path\", \"sources\": { \"@config\": \"./build/config\" } } } }"
+            ),
+        );
+
+        let errors = process(
+            &resources,
+            Options::new("src/main.lua").with_output("out.lua"),
+        )
+        .unwrap()
+        .result()
+        .unwrap_err();
+
+        pretty_assertions::assert_eq!(errors.len(), 1);
+        let error = errors.first().unwrap();
+
+        pretty_assertions::assert_eq!(error.line(), Some(2));
+        pretty_assertions::assert_eq!(error.column(), Some(15));
+
+        let message = error.to_string().replace('\\', "/");
+        assert!(
+            message.starts_with(concat!(
+                "error processing `src/main.lua:2:15` (bundler): ",
+                "unable to require resource at `@config/sub`: `@config` points to the file ",
+            )),
+            "unexpected error message: {}",
+            message
+        );
+        assert!(
+            message.contains("build/config.luau`, so it cannot be followed by `sub`"),
+            "unexpected error message: {}",
+            message
+        );
+    }
+
     #[test]
     fn require_unknown_relative_file_with_extension() {
         let resources = memory_resources!(
@@ -615,3 +830,322 @@ data:
         }
     }
 }
+
+mod remove_type_exports {
+    use super::*;
+
+    const REMOVE_TYPE_EXPORTS_CONFIG: &str = "{ rules: [], generator: 'retain_lines', bundle: { require_mode: 'path', remove_type_exports: true } }";
+
+    fn bundle_main(config: &'static str) -> String {
+        let resources = memory_resources!(
+            "src/point.luau" => "export type Point = { x: number, y: number }\nexport type Id = string\nreturn {}",
+            "src/vector.luau" => "export type Point = { x: number }\ntype Length = number\nreturn {}",
+            "src/main.luau" => "local point = require('./point')\nlocal vector = require('./vector')\nexport type Main = number\nreturn nil",
+            ".darklua.json" => config,
+        );
+
+        process(
+            &resources,
+            Options::new("src/main.luau").with_output("out.luau"),
+        )
+        .unwrap()
+        .result()
+        .unwrap();
+
+        resources.get("out.luau").unwrap()
+    }
+
+    #[test]
+    fn remove_export_of_inlined_modules() {
+        let main = bundle_main(REMOVE_TYPE_EXPORTS_CONFIG);
+
+        assert!(!main.contains("export type Point"), "{}", main);
+        assert!(!main.contains("export type Id"), "{}", main);
+        assert_eq!(main.matches("type Point =").count(), 2, "{}", main);
+        assert!(main.contains("type Id = string"), "{}", main);
+        assert!(main.contains("type Length = number"), "{}", main);
+    }
+
+    #[test]
+    fn keep_export_of_bundled_file() {
+        let main = bundle_main(REMOVE_TYPE_EXPORTS_CONFIG);
+
+        assert!(main.contains("export type Main = number"), "{}", main);
+    }
+
+    #[test]
+    fn keep_export_of_inlined_modules_by_default() {
+        let main = bundle_main(DARKLUA_BUNDLE_ONLY_RETAIN_LINES_CONFIG);
+
+        assert_eq!(main.matches("export type Point =").count(), 2, "{}", main);
+    }
+}
+
+mod manifest {
+    use std::path::Path;
+
+    use darklua_core::{BundleManifest, BundledModuleStatus};
+
+    use super::*;
+
+    const MANIFEST_CONFIG: &str = "{ rules: [], generator: 'readable', bundle: { require_mode: 'path', excludes: ['@lune/**'], manifest_path: 'bundle-manifest.json' } }";
+
+    const MAIN_CODE: &str = "local a = require('./a')\nlocal b = require('./b')\nprint(a, b)";
+    const A_CODE: &str = "local c = require('./c')\nreturn { c = c }";
+    const B_CODE: &str =
+        "local fs = require('@lune/fs')\nlocal c = require('./c')\nreturn { c = c, fs = fs }";
+    const C_CODE: &str = "return 'c'";
+
+    fn bundle_with_manifest() -> (Resources, BundleManifest) {
+        let resources = memory_resources!(
+            "src/main.lua" => MAIN_CODE,
+            "src/a.lua" => A_CODE,
+            "src/b.lua" => B_CODE,
+            "src/c.lua" => C_CODE,
+            ".darklua.json" => MANIFEST_CONFIG,
+        );
+
+        let report = process(
+            &resources,
+            Options::new("src/main.lua").with_output("out.lua"),
+        )
+        .unwrap()
+        .report();
+
+        let file = report
+            .get("src/main.lua")
+            .expect("main file should be reported");
+        assert!(file.status().is_success(), "{:?}", file.status());
+
+        let manifest = file
+            .bundle_manifest()
+            .expect("bundle manifest should be collected")
+            .clone();
+
+        (resources, manifest)
+    }
+
+    fn dependencies(iterator: impl Iterator<Item = impl AsRef<Path>>) -> Vec<String> {
+        iterator
+            .map(|path| path.as_ref().display().to_string().replace('\\', "/"))
+            .collect()
+    }
+
+    #[test]
+    fn lists_dependencies_of_the_entry() {
+        let (_, manifest) = bundle_with_manifest();
+
+        assert_eq!(manifest.entry(), Path::new("src/main.lua"));
+        pretty_assertions::assert_eq!(
+            dependencies(manifest.iter_dependencies()),
+            vec!["src/a.lua", "src/b.lua"]
+        );
+    }
+
+    #[test]
+    fn lists_each_module_with_its_dependencies() {
+        let (_, manifest) = bundle_with_manifest();
+
+        let modules: Vec<_> = manifest
+            .iter_modules()
+            .map(|module| {
+                (
+                    module.path().display().to_string().replace('\\', "/"),
+                    dependencies(module.iter_dependencies()),
+                )
+            })
+            .collect();
+
+        pretty_assertions::assert_eq!(
+            modules,
+            vec![
+                ("@lune/fs".to_owned(), vec![]),
+                ("src/a.lua".to_owned(), vec!["src/c.lua".to_owned()]),
+                (
+                    "src/b.lua".to_owned(),
+                    vec!["@lune/fs".to_owned(), "src/c.lua".to_owned()]
+                ),
+                ("src/c.lua".to_owned(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn inlined_modules_have_an_id_and_sizes() {
+        let (resources, manifest) = bundle_with_manifest();
+        let bundle = resources.get("out.lua").unwrap();
+
+        for (path, code) in [
+            ("src/a.lua", A_CODE),
+            ("src/b.lua", B_CODE),
+            ("src/c.lua", C_CODE),
+        ] {
+            let module = manifest.get_module(path).expect("module should be listed");
+            let id = module.id().expect("inlined module should have an id");
+
+            assert!(
+                bundle.contains(&format!("__DARKLUA_BUNDLE_MODULES.load('{}')", id)),
+                "module `{}` is not loaded with id `{}`:\n{}",
+                path,
+                id,
+                bundle
+            );
+            assert_eq!(module.size_before_rules(), Some(code.len()));
+            assert!(module.size_after_rules().is_some());
+        }
+    }
+
+    #[test]
+    fn excluded_module_is_external() {
+        let (_, manifest) = bundle_with_manifest();
+
+        let module = manifest
+            .get_module("@lune/fs")
+            .expect("external module should be listed");
+
+        assert_eq!(module.status(), &BundledModuleStatus::External);
+        assert_eq!(module.size_before_rules(), None);
+    }
+
+    #[test]
+    fn manifest_is_written_next_to_the_bundle() {
+        let (resources, manifest) = bundle_with_manifest();
+
+        let content = resources
+            .get("bundle-manifest.json")
+            .expect("manifest file should be written");
+        let value: serde_json::Value = serde_json::from_str(&content).unwrap();
+
+        assert_eq!(value, serde_json::to_value(&manifest).unwrap());
+        assert_eq!(value["entry"], "src/main.lua");
+        assert_eq!(value["modules"][0]["status"], "external");
+        assert_eq!(value["modules"][1]["status"], "inlined");
+    }
+}
+
+mod project_boundary {
+    use super::*;
+
+    fn boundary_config(require_mode_fields: &str) -> String {
+        format!(
+            "{{ rules: [], generator: 'readable', bundle: {{ require_mode: {{ name: 'path', sources: {{ shared: '../shared' }}, {} }} }} }}",
+            require_mode_fields
+        )
+    }
+
+    fn bundle_project(resources: &Resources) -> Result<String, Vec<String>> {
+        process(
+            resources,
+            Options::new("project/src/main.lua")
+                .with_configuration_at("project/.darklua.json")
+                .with_output("project/out.lua"),
+        )
+        .unwrap()
+        .result()
+        .map(|_| resources.get("project/out.lua").unwrap())
+        .map_err(|errors| errors.into_iter().map(|err| err.to_string()).collect())
+    }
+
+    fn project_requiring(require: &str, config: &str) -> Resources {
+        memory_resources!(
+            "project/src/main.lua" => &format!("local value = require('{}')\nprint(value)", require),
+            "project/src/value.lua" => "return 'value'",
+            "secret.lua" => "return 'secret'",
+            "/etc/secret.lua" => "return 'absolute secret'",
+            "shared/util.lua" => "return 'util'",
+            "project/.darklua.json" => config,
+        )
+    }
+
+    fn assert_escape_error(require: &str, resolved: &str, config: &str) {
+        let resources = project_requiring(require, config);
+
+        let errors = bundle_project(&resources).unwrap_err();
+
+        assert_eq!(errors.len(), 1, "{:#?}", errors);
+        assert!(
+            errors[0].contains(&format!("`{}`", require)),
+            "error should name the require `{}`: {}",
+            require,
+            errors[0]
+        );
+        assert!(
+            errors[0].contains(&format!(
+                "the resolved path `{}` is outside of the project",
+                resolved
+            )),
+            "error should name the resolved path `{}`: {}",
+            resolved,
+            errors[0]
+        );
+    }
+
+    #[test]
+    fn require_inside_the_project_is_allowed() {
+        let resources = project_requiring(
+            "./value",
+            &boundary_config("enforce_project_boundary: true"),
+        );
+
+        let main = bundle_project(&resources).unwrap();
+
+        assert!(main.contains("return 'value'"), "{}", main);
+    }
+
+    #[test]
+    fn require_escaping_with_parent_directories_is_rejected() {
+        assert_escape_error(
+            "../../secret",
+            "secret.lua",
+            &boundary_config("enforce_project_boundary: true"),
+        );
+    }
+
+    #[test]
+    fn require_with_absolute_path_is_rejected() {
+        assert_escape_error(
+            "/etc/secret",
+            "/etc/secret.lua",
+            &boundary_config("enforce_project_boundary: true"),
+        );
+    }
+
+    #[test]
+    fn require_through_source_outside_the_project_is_allowed() {
+        let resources = project_requiring(
+            "shared/util",
+            &boundary_config("enforce_project_boundary: true"),
+        );
+
+        let main = bundle_project(&resources).unwrap();
+
+        assert!(main.contains("return 'util'"), "{}", main);
+    }
+
+    #[test]
+    fn require_escaping_the_source_location_is_rejected() {
+        assert_escape_error(
+            "shared/../secret",
+            "secret.lua",
+            &boundary_config("enforce_project_boundary: true"),
+        );
+    }
+
+    #[test]
+    fn require_through_source_outside_the_project_is_rejected_when_strict() {
+        assert_escape_error(
+            "shared/util",
+            "shared/util.lua",
+            &boundary_config("strict_project_boundary: true"),
+        );
+    }
+
+    #[test]
+    fn require_escaping_the_project_is_allowed_by_default() {
+        let resources = project_requiring("../../secret", &boundary_config(""));
+
+        let main = bundle_project(&resources).unwrap();
+
+        assert!(main.contains("return 'secret'"), "{}", main);
+    }
+}
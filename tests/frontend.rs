@@ -4,7 +4,7 @@ use darklua_core::{process, Options, Resources};
 
 use pretty_assertions::assert_eq;
 
-use utils::memory_resources;
+use utils::{memory_resources, process_outputs};
 
 const ANY_CODE: &str = "do end return true";
 const ANY_CODE_DEFAULT_PROCESS: &str = "return true";
@@ -64,6 +64,22 @@ fn apply_default_config_to_output_from_file_in_directory() {
     );
 }
 
+#[test]
+fn process_without_rules_keeps_code_identical() {
+    let code = include_str!("./test_cases/spaces_and_comments.lua");
+    let resources = memory_resources!(
+        "src/test.lua" => code,
+        ".darklua.json" => "{ rules: [], generator: 'retain_lines' }",
+    );
+
+    process(&resources, Options::new("src").with_output("output"))
+        .unwrap()
+        .result()
+        .unwrap();
+
+    assert_eq!(resources.get("output/test.lua").unwrap(), code);
+}
+
 #[test]
 fn apply_default_config_to_output_with_nested_content() {
     let init_lua = "return{}";
@@ -172,6 +188,37 @@ fn use_default_json5_config_in_place() {
     assert_eq!(resources.get("src/test.lua").unwrap(), "return 'Hello'");
 }
 
+#[test]
+fn use_default_toml_config_in_place() {
+    let resources = memory_resources!(
+        "src/test.lua" => "return _G.VALUE",
+        ".darklua.toml" => "[[rules]]\nname = 'inject_global_value'\nidentifier = 'VALUE'\nvalue = 'Hello'\n",
+    );
+
+    process(&resources, Options::new("src"))
+        .unwrap()
+        .result()
+        .unwrap();
+
+    assert_eq!(resources.get("src/test.lua").unwrap(), "return 'Hello'");
+}
+
+#[test]
+fn use_provided_toml_config_with_invalid_rule() {
+    let resources = memory_resources!(
+        "src/test.lua" => "return _G.VALUE",
+        "config.toml" => "[[rules]]\nname = 'remove_comments'\n\n[[rules]]\nname = 'oops'\n",
+    );
+
+    let error = process(
+        &resources,
+        Options::new("src").with_configuration_at("config.toml"),
+    )
+    .unwrap_err();
+
+    assert!(error.to_string().contains("rules[1]"), "{}", error);
+}
+
 mod errors {
     use std::path::{Path, PathBuf};
 
@@ -274,3 +321,3705 @@ mod errors {
         );
     }
 }
+
+mod overlay_resources {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    const BUNDLE_CONFIG: &str =
+        "{ \"rules\": [], \"generator\": \"readable\", \"bundle\": { \"require_mode\": \"path\" } }";
+
+    #[test]
+    fn process_overlay_file_requiring_base_module() {
+        let base = memory_resources!(
+            "src/main.lua" => "return nil",
+            "src/value.lua" => "return 42",
+            ".darklua.json" => BUNDLE_CONFIG,
+        );
+        let resources = Resources::overlay(
+            base.clone(),
+            vec![(
+                "src/main.lua",
+                "local value = require('./value.lua') return value",
+            )],
+        );
+
+        process(
+            &resources,
+            Options::new("src/main.lua").with_output("out.lua"),
+        )
+        .unwrap()
+        .result()
+        .unwrap();
+
+        let output = resources.get("out.lua").unwrap();
+        assert!(output.contains("42"), "unexpected output:\n{}", output);
+        assert!(
+            !output.contains("require"),
+            "unexpected output:\n{}",
+            output
+        );
+
+        assert_eq!(base.exists("out.lua"), Ok(false));
+        assert_eq!(base.get("src/main.lua").unwrap(), "return nil");
+        assert_eq!(
+            resources.overlay_writes(),
+            vec![(PathBuf::from("out.lua"), output)]
+                .into_iter()
+                .collect()
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn process_overlay_file_requiring_module_from_file_system() {
+        let directory = tempfile::tempdir().unwrap();
+        let root = directory.path();
+        std::fs::write(root.join("value.lua"), "return 42").unwrap();
+
+        let main = root.join("main.lua");
+        let output = root.join("out.lua");
+        let config = root.join("config.json");
+
+        let resources = Resources::overlay(
+            Resources::from_file_system(),
+            vec![
+                (
+                    main.clone(),
+                    "local value = require('./value.lua') return value",
+                ),
+                (config.clone(), BUNDLE_CONFIG),
+            ],
+        );
+
+        process(
+            &resources,
+            Options::new(&main)
+                .with_configuration_at(&config)
+                .with_output(&output),
+        )
+        .unwrap()
+        .result()
+        .unwrap();
+
+        let code = resources.get(&output).unwrap();
+        assert!(code.contains("42"), "unexpected output:\n{}", code);
+
+        assert!(!main.exists());
+        assert!(!output.exists());
+        assert_eq!(resources.overlay_writes().len(), 1);
+    }
+}
+
+mod custom_resource_backend {
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+
+    use darklua_core::{FileStatus, MemoryBackend, ResourceBackend, ResourceError};
+
+    use super::*;
+
+    const BUNDLE_CONFIG: &str =
+        "{ \"rules\": [], \"generator\": \"readable\", \"bundle\": { \"require_mode\": \"path\" } }";
+
+    type AccessLog = Arc<Mutex<Vec<(&'static str, PathBuf)>>>;
+
+    #[derive(Debug, Default)]
+    struct RecordingBackend {
+        files: MemoryBackend,
+        accesses: AccessLog,
+    }
+
+    impl RecordingBackend {
+        fn record(&self, operation: &'static str, location: &Path) {
+            self.accesses
+                .lock()
+                .unwrap()
+                .push((operation, location.to_path_buf()));
+        }
+    }
+
+    impl ResourceBackend for RecordingBackend {
+        fn exists(&self, location: &Path) -> Result<bool, ResourceError> {
+            self.record("exists", location);
+            self.files.exists(location)
+        }
+
+        fn is_directory(&self, location: &Path) -> Result<bool, ResourceError> {
+            self.record("is_directory", location);
+            self.files.is_directory(location)
+        }
+
+        fn is_file(&self, location: &Path) -> Result<bool, ResourceError> {
+            self.record("is_file", location);
+            self.files.is_file(location)
+        }
+
+        fn read(&self, location: &Path) -> Result<String, ResourceError> {
+            self.record("read", location);
+            self.files.read(location)
+        }
+
+        fn write(&self, location: &Path, content: &str) -> Result<(), ResourceError> {
+            self.record("write", location);
+            self.files.write(location, content)
+        }
+
+        fn remove(&self, location: &Path) -> Result<(), ResourceError> {
+            self.record("remove", location);
+            self.files.remove(location)
+        }
+
+        fn read_directory(&self, location: &Path) -> Result<Vec<PathBuf>, ResourceError> {
+            self.record("read_directory", location);
+            self.files.read_directory(location)
+        }
+    }
+
+    fn new_resources(files: &[(&str, &str)]) -> (Resources, AccessLog) {
+        let backend = RecordingBackend::default();
+        for (path, content) in files {
+            backend.files.write(Path::new(path), content).unwrap();
+        }
+        let accesses = Arc::clone(&backend.accesses);
+
+        (Resources::from_backend(backend), accesses)
+    }
+
+    fn was_accessed(accesses: &AccessLog, operation: &str, path: &str) -> bool {
+        accesses.lock().unwrap().iter().any(|(recorded, location)| {
+            *recorded == operation
+                && location.strip_prefix(".").unwrap_or(location) == Path::new(path)
+        })
+    }
+
+    #[test]
+    fn bundle_reads_every_file_through_the_backend() {
+        let (resources, accesses) = new_resources(&[
+            (
+                "src/main.lua",
+                "local value = require('./value') local data = require('./data.json') return value + data.count",
+            ),
+            ("src/value.lua", "return 42"),
+            ("src/data.json", "{ \"count\": 3 }"),
+            (".darklua.json", BUNDLE_CONFIG),
+        ]);
+
+        process(
+            &resources,
+            Options::new("src/main.lua").with_output("out.lua"),
+        )
+        .unwrap()
+        .result()
+        .unwrap();
+
+        for path in &[
+            ".darklua.json",
+            "src/main.lua",
+            "src/value.lua",
+            "src/data.json",
+        ] {
+            assert!(
+                was_accessed(&accesses, "read", path),
+                "`{}` was not read through the backend: {:?}",
+                path,
+                accesses.lock().unwrap()
+            );
+        }
+        assert!(was_accessed(&accesses, "write", "out.lua"));
+
+        let output = resources.get("out.lua").unwrap();
+        assert!(output.contains("42"), "unexpected output:\n{}", output);
+        assert!(output.contains("count"), "unexpected output:\n{}", output);
+        assert!(
+            !output.contains("require"),
+            "unexpected output:\n{}",
+            output
+        );
+    }
+
+    #[test]
+    fn process_reads_input_directory_through_the_backend() {
+        let (resources, accesses) = new_resources(&[("src/test.lua", ANY_CODE)]);
+
+        process(&resources, Options::new("src"))
+            .unwrap()
+            .result()
+            .unwrap();
+
+        assert!(was_accessed(&accesses, "read_directory", "src"));
+        assert_eq!(
+            resources.get("src/test.lua").unwrap(),
+            ANY_CODE_DEFAULT_PROCESS
+        );
+    }
+
+    fn count_writes(accesses: &AccessLog) -> usize {
+        accesses
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(operation, _)| *operation == "write")
+            .count()
+    }
+
+    const RETAIN_LINES_CONFIG: &str = "{ \"rules\": [], \"generator\": \"retain_lines\" }";
+
+    #[test]
+    fn second_process_does_not_write_unchanged_outputs() {
+        let (resources, accesses) = new_resources(&[
+            (
+                "src/main.lua",
+                "local value = require('./value')\nreturn value",
+            ),
+            ("src/value.lua", "return 42"),
+            (".darklua.json", RETAIN_LINES_CONFIG),
+        ]);
+        let options = || Options::new("src").with_output("out");
+
+        process(&resources, options()).unwrap().result().unwrap();
+        assert_eq!(count_writes(&accesses), 2);
+
+        accesses.lock().unwrap().clear();
+
+        let report = process(&resources, options()).unwrap().report();
+
+        assert_eq!(count_writes(&accesses), 0);
+        assert!(report
+            .iter_files()
+            .all(|file| matches!(file.status(), FileStatus::Unchanged)));
+        assert_eq!(resources.get("out/value.lua").unwrap(), "return 42");
+    }
+
+    #[test]
+    fn always_write_outputs_writes_unchanged_outputs() {
+        let (resources, accesses) = new_resources(&[
+            ("src/value.lua", "return 42"),
+            (".darklua.json", RETAIN_LINES_CONFIG),
+        ]);
+        let options = || Options::new("src").with_output("out");
+
+        process(&resources, options()).unwrap().result().unwrap();
+        accesses.lock().unwrap().clear();
+
+        let report = process(&resources, options().always_write_outputs())
+            .unwrap()
+            .report();
+
+        assert!(was_accessed(&accesses, "write", "out/value.lua"));
+        assert!(matches!(
+            report.get("src/value.lua").unwrap().status(),
+            FileStatus::Unchanged
+        ));
+    }
+
+    #[test]
+    fn missing_file_error_comes_from_the_backend() {
+        let (resources, _) = new_resources(&[(".darklua.json", BUNDLE_CONFIG)]);
+
+        assert_eq!(
+            resources.get("src/missing.lua"),
+            Err(ResourceError::not_found("src/missing.lua"))
+        );
+    }
+}
+
+mod process_code {
+    use darklua_core::{process_code, process_code_at, Configuration};
+
+    use super::*;
+
+    fn parse_configuration(configuration: &str) -> Configuration {
+        json5::from_str(configuration).expect("unable to parse configuration")
+    }
+
+    #[test]
+    fn process_code_with_default_configuration() {
+        let code = process_code(ANY_CODE, &Configuration::default()).unwrap();
+
+        assert_eq!(code, ANY_CODE_DEFAULT_PROCESS);
+    }
+
+    #[test]
+    fn process_code_with_multiple_rules() {
+        let configuration = parse_configuration(
+            "{ rules: ['remove_comments', 'compute_expression', 'rename_variables'], generator: 'dense' }",
+        );
+
+        let code = process_code(
+            "-- a comment\nlocal value = 1 + 2 --[[ another comment ]] return value",
+            &configuration,
+        )
+        .unwrap();
+
+        utils::assert_blocks_eq(
+            &utils::parse_input(&code),
+            &utils::parse_input("local a = 3 return a"),
+        );
+        assert!(!code.contains("comment"), "unexpected output: {}", code);
+    }
+
+    #[test]
+    fn process_code_with_syntax_error() {
+        let error = process_code("return +", &Configuration::empty()).unwrap_err();
+
+        assert!(
+            error.to_string().contains("file.lua"),
+            "unexpected error: {}",
+            error
+        );
+    }
+
+    #[test]
+    fn process_code_at_virtual_path_with_syntax_error() {
+        let error =
+            process_code_at("return +", "src/editor.lua", &Configuration::empty()).unwrap_err();
+
+        assert!(
+            error.to_string().contains("src/editor.lua"),
+            "unexpected error: {}",
+            error
+        );
+    }
+
+    #[test]
+    fn process_code_with_unresolved_require_in_bundle() {
+        let configuration = parse_configuration("{ rules: [], bundle: { require_mode: 'path' } }");
+
+        let error = process_code_at(
+            "local library = require('./library')",
+            "src/main.lua",
+            &configuration,
+        )
+        .unwrap_err();
+
+        assert!(
+            error.to_string().contains("unable to find `src/library`"),
+            "unexpected error: {}",
+            error
+        );
+    }
+}
+
+mod parallel {
+    use super::*;
+
+    const FILE_COUNT: usize = 300;
+
+    fn create_resources() -> Resources {
+        let resources = Resources::from_memory();
+
+        for i in 0..FILE_COUNT {
+            let code = if i % 37 == 0 {
+                format!("local value{} = ", i)
+            } else {
+                format!(
+                    "-- file {}\nlocal function compute(n)\n    local total = 0\n    for j = 1, n do total = total + j * {} end\n    return total\nend\n\nreturn {{ value = compute({}), name = 'module' .. {} }}",
+                    i,
+                    i % 7,
+                    i,
+                    i
+                )
+            };
+            resources
+                .write(format!("src/folder{}/module{}.lua", i % 10, i), &code)
+                .unwrap();
+        }
+
+        resources
+    }
+
+    fn process_files(options: Options) -> (Vec<(String, String)>, Vec<String>) {
+        let resources = create_resources();
+
+        let worker_tree = process(&resources, options.with_output("out")).unwrap();
+
+        let mut errors: Vec<_> = worker_tree
+            .collect_errors()
+            .into_iter()
+            .map(ToString::to_string)
+            .collect();
+        errors.sort();
+
+        let outputs = (0..FILE_COUNT)
+            .filter_map(|i| {
+                let path = format!("out/folder{}/module{}.lua", i % 10, i);
+                resources.get(&path).ok().map(|content| (path, content))
+            })
+            .collect();
+
+        (outputs, errors)
+    }
+
+    #[test]
+    fn parallel_results_are_identical_to_serial_results() {
+        let (serial_outputs, serial_errors) = process_files(Options::new("src"));
+        let (parallel_outputs, parallel_errors) = process_files(Options::new("src").parallel(4));
+
+        assert_eq!(serial_outputs.len(), FILE_COUNT - serial_errors.len());
+        assert_eq!(serial_errors.len(), 9);
+        assert_eq!(serial_outputs, parallel_outputs);
+        assert_eq!(serial_errors, parallel_errors);
+    }
+
+    #[test]
+    fn parallel_with_available_cores() {
+        let (serial_outputs, _) = process_files(Options::new("src"));
+        let (parallel_outputs, _) = process_files(Options::new("src").parallel(0));
+
+        assert_eq!(serial_outputs, parallel_outputs);
+    }
+
+    fn process_with_luaurc_alias(package: &str) -> String {
+        let resources = memory_resources!(
+            "a/value.lua" => "return 'a'",
+            "b/value.lua" => "return 'b'",
+            "src/main.lua" => "return require('@pkg/value')",
+            ".luaurc" => format!(r#"{{ "aliases": {{ "pkg": "{}" }} }}"#, package),
+            ".darklua.json" => "{ \"rules\": [], \"generator\": \"readable\", \"bundle\": { \"require_mode\": \"path\" } }",
+        );
+
+        process(
+            &resources,
+            Options::new("src/main.lua").with_output("out.lua"),
+        )
+        .unwrap()
+        .result()
+        .unwrap();
+
+        resources.get("out.lua").unwrap()
+    }
+
+    #[test]
+    fn concurrent_processes_use_their_own_luaurc_aliases() {
+        let threads: Vec<_> = vec!["a", "b"]
+            .into_iter()
+            .map(|package| {
+                std::thread::spawn(move || {
+                    for _ in 0..20 {
+                        let output = process_with_luaurc_alias(package);
+                        assert!(
+                            output.contains(&format!("return '{}'", package)),
+                            "unexpected output for alias to `{}`: {}",
+                            package,
+                            output
+                        );
+                    }
+                })
+            })
+            .collect();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn parallel_fail_fast_stops_with_errors() {
+        let resources = create_resources();
+
+        let errors = process(&resources, Options::new("src").parallel(4).fail_fast())
+            .unwrap()
+            .result()
+            .unwrap_err();
+
+        assert!(!errors.is_empty());
+    }
+}
+
+mod process_report {
+    use std::path::Path;
+
+    use darklua_core::{Configuration, FileStatus, GeneratorParameters};
+
+    use super::*;
+
+    const UNCHANGED_CODE: &str = "local value = 1\nreturn value\n";
+    const REWRITTEN_CODE: &str = "return true";
+
+    fn process_with_report() -> darklua_core::ProcessReport {
+        let resources = memory_resources!(
+            "src/unchanged.lua" => UNCHANGED_CODE,
+            "out/unchanged.lua" => UNCHANGED_CODE,
+            "src/rewritten.lua" => REWRITTEN_CODE,
+            "out/rewritten.lua" => "return false",
+            "src/invalid.lua" => "local value = ",
+        );
+
+        process(
+            &resources,
+            Options::new("src").with_output("out").with_configuration(
+                Configuration::empty().with_generator(GeneratorParameters::default_retain_lines()),
+            ),
+        )
+        .unwrap()
+        .report()
+    }
+
+    #[test]
+    fn report_contains_every_file() {
+        let report = process_with_report();
+
+        assert_eq!(
+            report
+                .iter_files()
+                .map(|file| file.source())
+                .collect::<Vec<_>>(),
+            vec![
+                Path::new("src/invalid.lua"),
+                Path::new("src/rewritten.lua"),
+                Path::new("src/unchanged.lua"),
+            ]
+        );
+        assert!(report.has_errors());
+    }
+
+    #[test]
+    fn report_unchanged_file() {
+        let report = process_with_report();
+
+        let file = report.get("src/unchanged.lua").unwrap();
+        assert!(matches!(file.status(), FileStatus::Unchanged));
+        assert_eq!(file.output(), Path::new("out/unchanged.lua"));
+    }
+
+    #[test]
+    fn report_rewritten_file() {
+        let report = process_with_report();
+
+        let file = report.get("src/rewritten.lua").unwrap();
+        assert!(matches!(file.status(), FileStatus::Written));
+        assert_eq!(
+            report.iter_written().collect::<Vec<_>>(),
+            vec![Path::new("out/rewritten.lua")]
+        );
+    }
+
+    #[test]
+    fn report_file_with_syntax_error() {
+        let report = process_with_report();
+
+        let file = report.get("src/invalid.lua").unwrap();
+        assert!(matches!(file.status(), FileStatus::Failed { .. }));
+        assert_eq!(report.iter_errors().count(), 1);
+    }
+
+    #[test]
+    fn serialize_report() {
+        let report = process_with_report();
+
+        let value = serde_json::to_value(&report).unwrap();
+        let files = value["files"].as_array().unwrap();
+
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0]["status"], "failed");
+        assert!(files[0]["error"]
+            .as_str()
+            .unwrap()
+            .contains("src/invalid.lua"));
+        assert_eq!(files[1]["status"], "written");
+        assert_eq!(files[1]["output"], "out/rewritten.lua");
+        assert_eq!(files[2]["status"], "unchanged");
+        assert_eq!(files[2]["artifacts"], serde_json::json!([]));
+    }
+}
+
+mod module_graph {
+    use darklua_core::{ModuleNode, ProcessReport};
+
+    use super::*;
+
+    type DescribedEdge = (String, String, Option<(usize, Option<usize>)>);
+
+    fn describe_edges(report: &ProcessReport) -> Vec<DescribedEdge> {
+        report
+            .module_graph()
+            .expect("module graph should be collected")
+            .iter_edges()
+            .map(|edge| {
+                (
+                    edge.source().display().to_string(),
+                    match edge.target() {
+                        ModuleNode::File { path } => path.display().to_string(),
+                        ModuleNode::Unresolved { require } => format!("unresolved `{}`", require),
+                    },
+                    edge.location()
+                        .map(|location| (location.line(), location.column())),
+                )
+            })
+            .collect()
+    }
+
+    fn edge(source: &str, target: &str, location: Option<(usize, Option<usize>)>) -> DescribedEdge {
+        (source.to_owned(), target.to_owned(), location)
+    }
+
+    const CONVERT_REQUIRE_CONFIG: &str = "{ rules: [{ rule: 'convert_require', current: 'path', target: 'roblox' }], generator: 'retain_lines' }";
+    const BUNDLE_CONFIG: &str =
+        "{ rules: [], generator: 'retain_lines', bundle: { require_mode: 'path' } }";
+
+    fn create_convert_require_resources() -> Resources {
+        memory_resources!(
+            "src/main.lua" => "local a = require('./a')\nlocal missing = require('./missing')\nreturn a",
+            "src/a.lua" => "local b = require('./b')\nreturn b",
+            "src/b.lua" => "return nil",
+            ".darklua.json" => CONVERT_REQUIRE_CONFIG,
+        )
+    }
+
+    #[test]
+    fn module_graph_is_not_collected_by_default() {
+        let resources = create_convert_require_resources();
+
+        let report = process(&resources, Options::new("src")).unwrap().report();
+
+        assert!(report.module_graph().is_none());
+    }
+
+    #[test]
+    fn collect_module_graph_from_convert_require() {
+        let resources = create_convert_require_resources();
+
+        let report = process(&resources, Options::new("src").collect_module_graph())
+            .unwrap()
+            .report();
+
+        assert_eq!(
+            describe_edges(&report),
+            vec![
+                edge("src/a.lua", "src/b.lua", Some((1, Some(11)))),
+                edge("src/main.lua", "src/a.lua", Some((1, Some(11)))),
+                edge(
+                    "src/main.lua",
+                    "unresolved `./missing`",
+                    Some((2, Some(17)))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn collect_module_graph_from_bundle() {
+        let resources = memory_resources!(
+            "src/main.lua" => "local a = require('./a')\nreturn a",
+            "src/a.lua" => "local b = require('./b')\nreturn b",
+            "src/b.lua" => "return nil",
+            ".darklua.json" => BUNDLE_CONFIG,
+        );
+
+        let report = process(
+            &resources,
+            Options::new("src/main.lua")
+                .with_output("out.lua")
+                .collect_module_graph(),
+        )
+        .unwrap()
+        .report();
+
+        assert_eq!(
+            describe_edges(&report),
+            vec![
+                edge("src/a.lua", "src/b.lua", Some((1, Some(11)))),
+                edge("src/main.lua", "src/a.lua", Some((1, Some(11)))),
+            ]
+        );
+    }
+
+    #[test]
+    fn collect_unresolved_require_from_bundle() {
+        let resources = memory_resources!(
+            "src/main.lua" => "local a = require('./a')\nreturn a",
+            "src/a.lua" => "local b = require('./b')\nreturn b",
+            ".darklua.json" => BUNDLE_CONFIG,
+        );
+
+        let report = process(
+            &resources,
+            Options::new("src/main.lua")
+                .with_output("out.lua")
+                .collect_module_graph(),
+        )
+        .unwrap()
+        .report();
+
+        assert!(report.has_errors());
+        assert_eq!(
+            describe_edges(&report),
+            vec![
+                edge("src/a.lua", "unresolved `./b`", Some((1, Some(11)))),
+                edge("src/main.lua", "src/a.lua", Some((1, Some(11)))),
+            ]
+        );
+    }
+
+    #[test]
+    fn serialize_module_graph() {
+        let resources = create_convert_require_resources();
+
+        let report = process(&resources, Options::new("src").collect_module_graph())
+            .unwrap()
+            .report();
+
+        let value = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(
+            value["module_graph"]["edges"][2],
+            serde_json::json!({
+                "source": "src/main.lua",
+                "target": { "kind": "unresolved", "require": "./missing" },
+                "location": { "line": 2, "column": 17 },
+            })
+        );
+    }
+}
+
+mod configuration_extends {
+    use super::*;
+
+    const BASE_CONFIG: &str = r#"{
+        generator: "dense",
+        rules: [
+            "remove_comments",
+            { rule: "inject_global_value", identifier: "DEV", value: false },
+        ],
+    }"#;
+
+    fn process_with_config(config: &str) -> String {
+        let resources = memory_resources!(
+            "src/test.lua" => "-- comment\nreturn _G.DEV",
+            "config/base.json5" => BASE_CONFIG,
+            ".darklua.json5" => config,
+        );
+
+        process(&resources, Options::new("src"))
+            .unwrap()
+            .result()
+            .unwrap();
+
+        resources.get("src/test.lua").unwrap()
+    }
+
+    #[test]
+    fn inherit_base_configuration() {
+        assert_eq!(
+            process_with_config("{ extends: './config/base.json5' }"),
+            "return false"
+        );
+    }
+
+    #[test]
+    fn override_inherited_rule_properties() {
+        assert_eq!(
+            process_with_config(
+                "{ extends: './config/base.json5', rules: [{ rule: 'inject_global_value', value: true }] }"
+            ),
+            "return true"
+        );
+    }
+
+    #[test]
+    fn disable_inherited_rule() {
+        assert_eq!(
+            process_with_config(
+                "{ extends: './config/base.json5', rules: [{ disable: 'inject_global_value' }] }"
+            ),
+            "return _G.DEV"
+        );
+    }
+
+    #[test]
+    fn append_rule_to_inherited_rules() {
+        assert_eq!(
+            process_with_config(
+                "{ extends: './config/base.json5', rules: [{ rule: 'inject_global_value', identifier: 'OTHER', value: 1 }] }"
+            ),
+            "return false"
+        );
+    }
+
+    #[test]
+    fn override_generator() {
+        assert_eq!(
+            process_with_config("{ extends: './config/base.json5', generator: 'retain_lines' }"),
+            "\nreturn false"
+        );
+    }
+
+    #[test]
+    fn multiple_levels_of_inheritance() {
+        let resources = memory_resources!(
+            "src/test.lua" => "-- comment\nreturn _G.DEV",
+            "config/base.json5" => BASE_CONFIG,
+            "config/release.toml" => "extends = './base.json5'\n\n[[rules]]\nname = 'inject_global_value'\nvalue = true\n",
+            ".darklua.json5" => "{ extends: './config/release.toml', generator: 'retain_lines', rules: [{ disable: 'remove_comments' }] }",
+        );
+
+        process(&resources, Options::new("src"))
+            .unwrap()
+            .result()
+            .unwrap();
+
+        assert_eq!(
+            resources.get("src/test.lua").unwrap(),
+            "-- comment\nreturn true"
+        );
+    }
+
+    #[test]
+    fn paths_of_base_in_other_directory_are_resolved_from_loaded_configuration() {
+        let resources = memory_resources!(
+            "src/test.lua" => "-- comment\nreturn 1",
+            "src/generated/value.lua" => "-- comment\nreturn 2",
+            "config/base.json5" => r#"{
+                rules: ["remove_comments"],
+                overrides: [{ include: "src/generated/**", rules: [] }],
+            }"#,
+            ".darklua.json5" => "{ extends: './config/base.json5', generator: 'retain_lines' }",
+        );
+
+        process(&resources, Options::new("src"))
+            .unwrap()
+            .result()
+            .unwrap();
+
+        assert_eq!(resources.get("src/test.lua").unwrap(), "\nreturn 1");
+        assert_eq!(
+            resources.get("src/generated/value.lua").unwrap(),
+            "-- comment\nreturn 2"
+        );
+    }
+
+    #[test]
+    fn cyclic_extends_error_lists_chain() {
+        let resources = memory_resources!(
+            "src/test.lua" => "return _G.DEV",
+            "a.json5" => "{ extends: './config/b.json5' }",
+            "config/b.json5" => "{ extends: './c.json5' }",
+            "config/c.json5" => "{ extends: '../a.json5' }",
+        );
+
+        let error = process(
+            &resources,
+            Options::new("src").with_configuration_at("a.json5"),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "cyclic configuration `extends` detected: `a.json5` -> `config/b.json5` -> `config/c.json5` -> `a.json5`"
+        );
+    }
+}
+
+mod located_rule_errors {
+    use darklua_core::{
+        nodes::Block,
+        nodes::SourcePosition,
+        rules::{
+            Context, Rule, RuleConfiguration, RuleConfigurationError, RuleError, RuleProcessResult,
+            RuleProperties,
+        },
+        Configuration, GeneratorParameters,
+    };
+
+    use super::*;
+
+    fn last_statement_position(block: &Block) -> Option<SourcePosition> {
+        block
+            .iter_statements()
+            .last()
+            .and_then(|statement| statement.start_position())
+    }
+
+    #[derive(Debug)]
+    struct RejectLastStatement;
+
+    impl Rule for RejectLastStatement {
+        fn process(&self, block: &mut Block, context: &Context) -> RuleProcessResult {
+            Err(context.error_at(last_statement_position(block), "statement is not allowed"))
+        }
+    }
+
+    impl RuleConfiguration for RejectLastStatement {
+        fn configure(&mut self, _: RuleProperties) -> Result<(), RuleConfigurationError> {
+            Ok(())
+        }
+
+        fn get_name(&self) -> &'static str {
+            "reject_last_statement"
+        }
+
+        fn serialize_to_properties(&self) -> RuleProperties {
+            RuleProperties::new()
+        }
+    }
+
+    /// Creates a located error, but fails with another error that has the same message.
+    #[derive(Debug)]
+    struct ReturnOtherError;
+
+    impl Rule for ReturnOtherError {
+        fn process(&self, block: &mut Block, context: &Context) -> RuleProcessResult {
+            let located_error =
+                context.error_at(last_statement_position(block), "statement is not allowed");
+            Err(RuleError::new(located_error.message()))
+        }
+    }
+
+    impl RuleConfiguration for ReturnOtherError {
+        fn configure(&mut self, _: RuleProperties) -> Result<(), RuleConfigurationError> {
+            Ok(())
+        }
+
+        fn get_name(&self) -> &'static str {
+            "return_other_error"
+        }
+
+        fn serialize_to_properties(&self) -> RuleProperties {
+            RuleProperties::new()
+        }
+    }
+
+    fn process_with_rule(rule: Box<dyn Rule>, generator: GeneratorParameters) -> String {
+        let resources = memory_resources!(
+            "src/test.lua" => "local a = 1\n\tprint(a)\n",
+        );
+
+        let errors = process(
+            &resources,
+            Options::new("src/test.lua").with_configuration(
+                Configuration::empty()
+                    .with_generator(generator)
+                    .with_rule(rule),
+            ),
+        )
+        .unwrap()
+        .result()
+        .unwrap_err();
+
+        errors.first().unwrap().to_string()
+    }
+
+    fn process_with_generator(generator: GeneratorParameters) -> String {
+        process_with_rule(Box::new(RejectLastStatement), generator)
+    }
+
+    #[test]
+    fn error_contains_location_and_code_frame() {
+        assert_eq!(
+            process_with_generator(GeneratorParameters::default_retain_lines()),
+            "error processing `src/test.lua:2:2` (reject_last_statement [#0]): statement is not allowed\n2 | \tprint(a)\n  | \t^"
+        );
+    }
+
+    #[test]
+    fn error_without_tokens_has_no_location() {
+        assert_eq!(
+            process_with_generator(GeneratorParameters::default_dense()),
+            "error processing `src/test.lua` (reject_last_statement [#0]): statement is not allowed"
+        );
+    }
+
+    #[test]
+    fn other_error_with_same_message_has_no_location() {
+        assert_eq!(
+            process_with_rule(
+                Box::new(ReturnOtherError),
+                GeneratorParameters::default_retain_lines()
+            ),
+            "error processing `src/test.lua` (return_other_error [#0]): statement is not allowed"
+        );
+    }
+}
+
+mod diagnostics {
+    use darklua_core::DiagnosticKind;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn serialize_parse_error() {
+        let resources = memory_resources!(
+            "src/test.lua" => "local value = ",
+            ".darklua.json" => "{ rules: [] }",
+        );
+
+        let diagnostics = process(&resources, Options::new("src/test.lua"))
+            .unwrap()
+            .diagnostics();
+
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = diagnostics.first().unwrap();
+        assert_eq!(diagnostic.kind(), DiagnosticKind::Parse);
+
+        let value = serde_json::to_value(diagnostic).unwrap();
+        assert_eq!(value["kind"], "parse");
+        assert_eq!(value["path"], "src/test.lua");
+        assert_eq!(value["span"]["line"], 1);
+        assert!(value["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("unable to parse `src/test.lua`"));
+        assert_eq!(value["context"], json!([]));
+        assert_eq!(value.get("rule"), None);
+    }
+
+    #[test]
+    fn recover_every_syntax_error_of_a_file() {
+        let resources = memory_resources!(
+            "src/test.lua" => "local a = = 1\nprint('ok')\nlocal function f()\n    return 1 +\nend\nlocal b = { 1, 2\nlocal c = 3",
+            "src/valid.lua" => "return 1",
+            ".darklua.json" => "{ rules: [] }",
+        );
+
+        let diagnostics = process(&resources, Options::new("src").recover_syntax_errors())
+            .unwrap()
+            .diagnostics();
+
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.kind() == DiagnosticKind::Parse));
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.span().map(|span| span.line()))
+                .collect::<Vec<_>>(),
+            vec![Some(1), Some(5), Some(7)]
+        );
+        assert_eq!(resources.get("src/valid.lua").unwrap(), "return 1");
+    }
+
+    #[test]
+    fn report_first_syntax_error_without_recovery() {
+        let resources = memory_resources!(
+            "src/test.lua" => "local a = = 1\nprint('ok')\nlocal b = { 1, 2\nlocal c = 3",
+            ".darklua.json" => "{ rules: [] }",
+        );
+
+        let diagnostics = process(&resources, Options::new("src/test.lua"))
+            .unwrap()
+            .diagnostics();
+
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn serialize_require_resolution_error() {
+        let resources = memory_resources!(
+            "src/main.lua" => "local a = 1\nlocal value = require('./missing')\nreturn value",
+            ".darklua.json" => "{ rules: [], generator: 'retain_lines', bundle: { require_mode: 'path' } }",
+        );
+
+        let diagnostics = process(
+            &resources,
+            Options::new("src/main.lua").with_output("out.lua"),
+        )
+        .unwrap()
+        .diagnostics();
+
+        assert_eq!(diagnostics.len(), 1);
+
+        let value = serde_json::to_value(diagnostics.first().unwrap()).unwrap();
+        assert_eq!(value["kind"], "require_resolution");
+        assert_eq!(value["path"], "src/main.lua");
+        assert_eq!(value["span"], json!({ "line": 2, "column": 15 }));
+        assert_eq!(value["rule"], "bundler");
+        assert!(value["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("unable to find `src/missing`"));
+    }
+}
+
+mod error_kinds {
+    use std::error::Error;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    use darklua_core::{
+        process_code, Configuration, DarkluaError, ErrorKind, MemoryBackend, ParserError,
+        ResourceBackend, ResourceError, WorkerTree,
+    };
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct UnreadableBackend {
+        files: MemoryBackend,
+    }
+
+    impl ResourceBackend for UnreadableBackend {
+        fn exists(&self, location: &Path) -> Result<bool, ResourceError> {
+            self.files.exists(location)
+        }
+
+        fn is_directory(&self, location: &Path) -> Result<bool, ResourceError> {
+            self.files.is_directory(location)
+        }
+
+        fn is_file(&self, location: &Path) -> Result<bool, ResourceError> {
+            self.files.is_file(location)
+        }
+
+        fn read(&self, location: &Path) -> Result<String, ResourceError> {
+            Err(ResourceError::io_error(
+                location,
+                io::Error::new(io::ErrorKind::PermissionDenied, "access denied"),
+            ))
+        }
+
+        fn write(&self, location: &Path, content: &str) -> Result<(), ResourceError> {
+            self.files.write(location, content)
+        }
+
+        fn remove(&self, location: &Path) -> Result<(), ResourceError> {
+            self.files.remove(location)
+        }
+
+        fn read_directory(&self, location: &Path) -> Result<Vec<PathBuf>, ResourceError> {
+            self.files.read_directory(location)
+        }
+    }
+
+    fn process_errors(resources: &Resources, options: Options) -> Vec<DarkluaError> {
+        process(resources, options)
+            .map_err(|err| vec![err])
+            .and_then(WorkerTree::result)
+            .unwrap_err()
+    }
+
+    fn find_source<T: Error + 'static>(error: &DarkluaError) -> Option<&T> {
+        let mut source = error.source();
+        while let Some(current) = source {
+            if let Some(found) = current.downcast_ref::<T>() {
+                return Some(found);
+            }
+            source = current.source();
+        }
+        None
+    }
+
+    #[test]
+    fn io_error_keeps_the_original_error_as_source() {
+        let backend = UnreadableBackend::default();
+        backend
+            .files
+            .write(Path::new("src/test.lua"), ANY_CODE)
+            .unwrap();
+        let resources = Resources::from_backend(backend);
+
+        let errors = process_errors(&resources, Options::new("src/test.lua"));
+
+        assert_eq!(errors.len(), 1);
+        let error = errors.first().unwrap();
+        assert_eq!(error.kind(), ErrorKind::Io);
+
+        let io_error = find_source::<io::Error>(error).expect("io error in the source chain");
+        assert_eq!(io_error.kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(io_error.to_string(), "access denied");
+    }
+
+    #[test]
+    fn resource_error_source_is_the_io_error() {
+        let error = ResourceError::io_error(
+            "src/test.lua",
+            io::Error::new(io::ErrorKind::PermissionDenied, "disk failure"),
+        );
+
+        let source = error.source().expect("io error source");
+        assert_eq!(
+            source.downcast_ref::<io::Error>().map(io::Error::kind),
+            Some(io::ErrorKind::PermissionDenied)
+        );
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error_without_source() {
+        let error = DarkluaError::from(ResourceError::not_found("src/missing.lua"));
+
+        assert_eq!(error.kind(), ErrorKind::Io);
+        assert!(error.source().is_none());
+    }
+
+    #[test]
+    fn parse_error_source_is_the_parser_error() {
+        let error = process_code("return +", &Configuration::empty()).unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::Parse);
+        assert!(find_source::<ParserError>(&error).is_some());
+    }
+
+    #[test]
+    fn invalid_configuration_source_is_the_deserialization_error() {
+        let resources = memory_resources!(
+            "src/test.lua" => ANY_CODE,
+            ".darklua.json" => "{ rules: [ }",
+        );
+
+        let errors = process_errors(&resources, Options::new("src"));
+
+        let error = errors.first().unwrap();
+        assert_eq!(error.kind(), ErrorKind::Configuration);
+        assert!(find_source::<json5::Error>(error).is_some());
+    }
+
+    #[test]
+    fn missing_configuration_file_is_a_configuration_error() {
+        let resources = memory_resources!(
+            "src/test.lua" => ANY_CODE,
+        );
+
+        let errors = process_errors(
+            &resources,
+            Options::new("src").with_configuration_at("missing/config.json"),
+        );
+
+        assert_eq!(errors.first().unwrap().kind(), ErrorKind::Configuration);
+    }
+
+    #[test]
+    fn multiple_configuration_files_is_a_configuration_error() {
+        let resources = memory_resources!(
+            "src/test.lua" => ANY_CODE,
+            ".darklua.json" => "{ rules: [] }",
+            ".darklua.json5" => "{ rules: [] }",
+        );
+
+        let errors = process_errors(&resources, Options::new("src"));
+
+        assert_eq!(errors.first().unwrap().kind(), ErrorKind::Configuration);
+    }
+
+    #[test]
+    fn unresolved_require_in_bundle_is_a_require_resolution_error() {
+        let resources = memory_resources!(
+            "src/main.lua" => "return require('./missing')",
+            ".darklua.json" => "{ rules: [], bundle: { require_mode: 'path' } }",
+        );
+
+        let errors = process_errors(
+            &resources,
+            Options::new("src/main.lua").with_output("out.lua"),
+        );
+
+        assert_eq!(errors.first().unwrap().kind(), ErrorKind::RequireResolution);
+    }
+
+    #[test]
+    fn context_chain_is_inspectable() {
+        let error = DarkluaError::custom("something failed")
+            .context("while doing a")
+            .context("while doing b");
+
+        assert_eq!(error.kind(), ErrorKind::Other);
+        assert_eq!(
+            error.contexts().collect::<Vec<_>>(),
+            vec!["while doing a", "while doing b"]
+        );
+        assert_eq!(error.to_string(), "something failed (while doing b)");
+    }
+}
+
+#[cfg(feature = "fs")]
+mod invalid_utf8_files {
+    use std::fs;
+
+    use darklua_core::{Configuration, ErrorKind, FileStatus, WorkerTree};
+
+    use super::*;
+
+    const INVALID_UTF8: &[u8] = &[0x72, 0x65, 0x74, 0xff, 0xfe, 0x6e];
+
+    #[test]
+    fn skip_invalid_utf8_file_found_in_directory() {
+        let directory = tempfile::tempdir().unwrap();
+        let root = directory.path();
+        fs::write(root.join("valid.lua"), ANY_CODE).unwrap();
+        fs::write(root.join("invalid.lua"), INVALID_UTF8).unwrap();
+
+        let resources = Resources::from_file_system();
+
+        let worker_tree = process(
+            &resources,
+            Options::new(root).with_configuration(Configuration::default()),
+        )
+        .unwrap();
+        let report = worker_tree.report();
+
+        worker_tree.result().unwrap();
+
+        assert_eq!(
+            fs::read_to_string(root.join("valid.lua")).unwrap(),
+            ANY_CODE_DEFAULT_PROCESS
+        );
+        assert_eq!(fs::read(root.join("invalid.lua")).unwrap(), INVALID_UTF8);
+
+        let skipped = report.get(root.join("invalid.lua")).unwrap();
+        match skipped.status() {
+            FileStatus::Skipped { reason } => {
+                assert_eq!(reason, "the file does not contain valid UTF-8")
+            }
+            status => panic!("unexpected status: {:?}", status),
+        }
+    }
+
+    #[test]
+    fn invalid_utf8_input_file_is_an_error() {
+        let directory = tempfile::tempdir().unwrap();
+        let input = directory.path().join("invalid.lua");
+        fs::write(&input, INVALID_UTF8).unwrap();
+
+        let resources = Resources::from_file_system();
+
+        let errors = process(
+            &resources,
+            Options::new(&input).with_configuration(Configuration::default()),
+        )
+        .map_err(|err| vec![err])
+        .and_then(WorkerTree::result)
+        .unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        let error = errors.first().unwrap();
+        assert_eq!(error.kind(), ErrorKind::Io);
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "unable to read `{}`: the file does not contain valid UTF-8",
+                input.display()
+            )
+        );
+    }
+
+    #[test]
+    fn require_invalid_utf8_file_in_bundle_is_a_located_error() {
+        let directory = tempfile::tempdir().unwrap();
+        let root = directory.path();
+        let main = root.join("main.lua");
+        let config = root.join("config.json");
+        fs::write(
+            &main,
+            "local a = 1\nlocal model = require('./model.rbxm')\nreturn model",
+        )
+        .unwrap();
+        fs::write(root.join("model.rbxm"), INVALID_UTF8).unwrap();
+        fs::write(
+            &config,
+            "{ rules: [], generator: 'retain_lines', bundle: { require_mode: 'path' } }",
+        )
+        .unwrap();
+
+        let resources = Resources::from_file_system();
+
+        let errors = process(
+            &resources,
+            Options::new(&main)
+                .with_configuration_at(&config)
+                .with_output(root.join("out.lua")),
+        )
+        .map_err(|err| vec![err])
+        .and_then(WorkerTree::result)
+        .unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        let error = errors.first().unwrap();
+        assert_eq!(error.kind(), ErrorKind::RequireResolution);
+        assert_eq!(error.line(), Some(2));
+        assert!(
+            error
+                .to_string()
+                .contains("the file does not contain valid UTF-8"),
+            "unexpected error: {}",
+            error
+        );
+    }
+}
+
+mod directory_walk {
+    use std::collections::BTreeMap;
+    use std::path::{Path, PathBuf};
+
+    use darklua_core::{Configuration, MemoryBackend, ResourceBackend, ResourceError};
+
+    use super::*;
+
+    /// A backend where some directories are links to other directories, like symbolic
+    /// links on a file system.
+    #[derive(Debug, Default)]
+    struct LinkedBackend {
+        files: MemoryBackend,
+        links: BTreeMap<PathBuf, PathBuf>,
+    }
+
+    impl LinkedBackend {
+        fn with_file(self, path: &str, content: &str) -> Self {
+            self.files.write(Path::new(path), content).unwrap();
+            self
+        }
+
+        fn with_link(mut self, path: &str, target: &str) -> Self {
+            self.links
+                .insert(PathBuf::from(path), PathBuf::from(target));
+            self
+        }
+
+        fn resolve(&self, location: &Path) -> PathBuf {
+            let mut resolved = location.to_path_buf();
+
+            for _ in 0..32 {
+                let link = self.links.iter().find_map(|(link, target)| {
+                    resolved
+                        .strip_prefix(link)
+                        .ok()
+                        .map(|rest| target.join(rest))
+                });
+                match link {
+                    Some(next) => resolved = next,
+                    None => break,
+                }
+            }
+
+            resolved
+        }
+    }
+
+    impl ResourceBackend for LinkedBackend {
+        fn exists(&self, location: &Path) -> Result<bool, ResourceError> {
+            Ok(self.is_file(location)? || self.is_directory(location)?)
+        }
+
+        fn is_directory(&self, location: &Path) -> Result<bool, ResourceError> {
+            let resolved = self.resolve(location);
+            Ok(self.files.is_directory(&resolved)?
+                || self
+                    .links
+                    .keys()
+                    .any(|link| link.starts_with(&resolved) && link != &resolved))
+        }
+
+        fn is_file(&self, location: &Path) -> Result<bool, ResourceError> {
+            self.files.is_file(&self.resolve(location))
+        }
+
+        fn read(&self, location: &Path) -> Result<String, ResourceError> {
+            self.files.read(&self.resolve(location))
+        }
+
+        fn write(&self, location: &Path, content: &str) -> Result<(), ResourceError> {
+            self.files.write(&self.resolve(location), content)
+        }
+
+        fn remove(&self, location: &Path) -> Result<(), ResourceError> {
+            self.files.remove(&self.resolve(location))
+        }
+
+        fn read_directory(&self, location: &Path) -> Result<Vec<PathBuf>, ResourceError> {
+            let resolved = self.resolve(location);
+
+            let mut entries: Vec<_> = self
+                .files
+                .read_directory(&resolved)?
+                .into_iter()
+                .chain(
+                    self.links
+                        .keys()
+                        .filter(|link| link.parent() == Some(resolved.as_path()))
+                        .cloned(),
+                )
+                .filter_map(|entry| {
+                    entry
+                        .strip_prefix(&resolved)
+                        .ok()
+                        .map(|name| location.join(name))
+                })
+                .collect();
+            entries.sort();
+            entries.dedup();
+
+            Ok(entries)
+        }
+
+        fn canonicalize(&self, location: &Path) -> Result<PathBuf, ResourceError> {
+            Ok(self.resolve(location))
+        }
+    }
+
+    #[test]
+    fn skip_link_to_parent_directory() {
+        let resources = Resources::from_backend(
+            LinkedBackend::default()
+                .with_file("src/main.lua", ANY_CODE)
+                .with_file("src/lib/value.lua", ANY_CODE)
+                .with_link("src/lib/loop", "src"),
+        );
+
+        assert_eq!(
+            resources.collect_work("src").collect::<Vec<_>>(),
+            vec![
+                PathBuf::from("src/lib/value.lua"),
+                PathBuf::from("src/main.lua"),
+            ]
+        );
+    }
+
+    #[test]
+    fn skip_self_referencing_link() {
+        let resources = Resources::from_backend(
+            LinkedBackend::default()
+                .with_file("src/main.lua", ANY_CODE)
+                .with_link("src/self", "src/self"),
+        );
+
+        assert_eq!(
+            resources.collect_work("src").collect::<Vec<_>>(),
+            vec![PathBuf::from("src/main.lua")]
+        );
+    }
+
+    #[test]
+    fn file_reached_through_two_links_is_collected_once() {
+        let resources = Resources::from_backend(
+            LinkedBackend::default()
+                .with_file("packages/value.lua", ANY_CODE)
+                .with_link("src/b", "packages")
+                .with_link("src/a", "packages"),
+        );
+
+        assert_eq!(
+            resources.collect_work("src").collect::<Vec<_>>(),
+            vec![PathBuf::from("src/a/value.lua")]
+        );
+    }
+
+    #[test]
+    fn file_reached_through_two_links_is_processed_once() {
+        let resources = Resources::from_backend(
+            LinkedBackend::default()
+                .with_file("packages/value.lua", ANY_CODE)
+                .with_file("src/main.lua", ANY_CODE)
+                .with_link("src/b", "packages")
+                .with_link("src/a", "packages"),
+        );
+
+        let report = process(
+            &resources,
+            Options::new("src")
+                .with_output("out")
+                .with_configuration(Configuration::default()),
+        )
+        .unwrap()
+        .report();
+
+        assert_eq!(
+            report
+                .iter_files()
+                .map(|file| file.source())
+                .collect::<Vec<_>>(),
+            vec![Path::new("src/a/value.lua"), Path::new("src/main.lua")]
+        );
+        assert_eq!(
+            resources.get("out/a/value.lua").unwrap(),
+            ANY_CODE_DEFAULT_PROCESS
+        );
+        assert_eq!(resources.exists("out/b/value.lua"), Ok(false));
+    }
+
+    #[test]
+    fn directories_deeper_than_the_limit_are_not_walked() {
+        let resources = memory_resources!(
+            "src/main.lua" => ANY_CODE,
+            "src/a/value.lua" => ANY_CODE,
+            "src/a/b/value.lua" => ANY_CODE,
+            "src/a/b/c/value.lua" => ANY_CODE,
+        );
+
+        let report = process(
+            &resources,
+            Options::new("src")
+                .with_max_directory_depth(2)
+                .with_configuration(Configuration::default()),
+        )
+        .unwrap()
+        .report();
+
+        assert_eq!(
+            report
+                .iter_files()
+                .map(|file| file.source())
+                .collect::<Vec<_>>(),
+            vec![
+                Path::new("src/a/b/value.lua"),
+                Path::new("src/a/value.lua"),
+                Path::new("src/main.lua"),
+            ]
+        );
+    }
+}
+
+mod file_filters {
+    use super::*;
+
+    fn create_resources() -> Resources {
+        memory_resources!(
+            "src/main.lua" => "local value = require('./generated/value') return value",
+            "src/main.spec.lua" => ANY_CODE,
+            "src/node_modules/lib/init.lua" => ANY_CODE,
+            "src/nested/node_modules/lib/init.lua" => ANY_CODE,
+            "src/generated/value.lua" => ANY_CODE,
+            "src/generated/keep.lua" => ANY_CODE,
+            ".darklua.json" => "{ rules: [], generator: 'retain_lines', bundle: { require_mode: 'path' } }",
+        )
+    }
+
+    #[test]
+    fn exclude_nested_directories_and_file_pattern() {
+        let resources = create_resources();
+
+        let outputs = process_outputs(
+            &resources,
+            Options::new("src").with_output("out").with_excludes([
+                "**/node_modules/**",
+                "*.spec.lua",
+                "generated/",
+            ]),
+        );
+
+        assert_eq!(outputs, vec!["out/main.lua"]);
+    }
+
+    #[test]
+    fn excluded_files_can_still_be_required() {
+        let resources = create_resources();
+
+        process_outputs(
+            &resources,
+            Options::new("src")
+                .with_output("out")
+                .with_excludes(["generated/", "node_modules/"]),
+        );
+
+        let main = resources.get("out/main.lua").unwrap();
+        assert!(main.contains("__DARKLUA_BUNDLE_MODULES"));
+        assert!(!main.contains("require("));
+    }
+
+    #[test]
+    fn include_again_excluded_file() {
+        let resources = create_resources();
+
+        let outputs = process_outputs(
+            &resources,
+            Options::new("src").with_output("out").with_excludes([
+                "node_modules/",
+                "generated/",
+                "!generated/keep.lua",
+                "*.spec.lua",
+            ]),
+        );
+
+        assert_eq!(outputs, vec!["out/generated/keep.lua", "out/main.lua"]);
+    }
+
+    #[test]
+    fn include_only_matching_files() {
+        let resources = create_resources();
+
+        let outputs = process_outputs(
+            &resources,
+            Options::new("src")
+                .with_output("out")
+                .with_includes(["generated/**"])
+                .with_excludes(["generated/value.lua"]),
+        );
+
+        assert_eq!(outputs, vec!["out/generated/keep.lua"]);
+    }
+
+    #[test]
+    fn invalid_pattern_is_an_error() {
+        let resources = create_resources();
+
+        let error = process(&resources, Options::new("src").with_excludes(["{a"])).unwrap_err();
+
+        assert!(error.to_string().starts_with("invalid file pattern `{a`"));
+    }
+}
+
+mod ignore_files {
+    use super::*;
+
+    fn create_resources(ignore_files: &[(&str, &str)]) -> Resources {
+        let resources = memory_resources!(
+            "src/main.lua" => "local value = require('./generated/value') return value",
+            "src/main.spec.lua" => ANY_CODE,
+            "src/Packages/_Index/lib/init.lua" => ANY_CODE,
+            "src/generated/value.lua" => ANY_CODE,
+            "src/generated/keep.lua" => ANY_CODE,
+            ".darklua.json" => "{ rules: [], generator: 'retain_lines', bundle: { require_mode: 'path' } }",
+        );
+        for (path, content) in ignore_files {
+            resources.write(path, content).unwrap();
+        }
+        resources
+    }
+
+    #[test]
+    fn negated_pattern_includes_again_file_in_ignored_directory() {
+        let resources = create_resources(&[(
+            ".darkluaignore",
+            "# dependencies\nPackages\n*.spec.lua\n\nsrc/generated/\n!src/generated/keep.lua\n",
+        )]);
+
+        let outputs = process_outputs(&resources, Options::new("src").with_output("out"));
+
+        assert_eq!(outputs, vec!["out/generated/keep.lua", "out/main.lua"]);
+    }
+
+    #[test]
+    fn ignored_files_can_still_be_required() {
+        let resources = create_resources(&[(".darkluaignore", "src/generated/")]);
+
+        process_outputs(&resources, Options::new("src").with_output("out"));
+
+        let main = resources.get("out/main.lua").unwrap();
+        assert!(main.contains("__DARKLUA_BUNDLE_MODULES"));
+        assert!(!main.contains("require("));
+    }
+
+    #[test]
+    fn gitignore_is_not_used_by_default() {
+        let resources =
+            create_resources(&[(".gitignore", "Packages/\nsrc/generated/\n*.spec.lua")]);
+
+        let outputs = process_outputs(&resources, Options::new("src").with_output("out"));
+
+        assert_eq!(outputs.len(), 5);
+    }
+
+    #[test]
+    fn respect_gitignore() {
+        let resources =
+            create_resources(&[(".gitignore", "Packages/\nsrc/generated/\n*.spec.lua")]);
+
+        let outputs = process_outputs(
+            &resources,
+            Options::new("src").with_output("out").respect_gitignore(),
+        );
+
+        assert_eq!(outputs, vec!["out/main.lua"]);
+    }
+
+    #[test]
+    fn darkluaignore_is_applied_after_gitignore() {
+        let resources = create_resources(&[
+            (".gitignore", "Packages/\nsrc/generated/\n*.spec.lua"),
+            (".darkluaignore", "!src/generated/keep.lua"),
+        ]);
+
+        let outputs = process_outputs(
+            &resources,
+            Options::new("src").with_output("out").respect_gitignore(),
+        );
+
+        assert_eq!(outputs, vec!["out/generated/keep.lua", "out/main.lua"]);
+    }
+
+    #[test]
+    fn explicit_includes_win_over_ignore_files() {
+        let resources = create_resources(&[(".darkluaignore", "src/generated/")]);
+
+        let outputs = process_outputs(
+            &resources,
+            Options::new("src")
+                .with_output("out")
+                .with_includes(["generated/**"])
+                .with_excludes(["generated/value.lua"]),
+        );
+
+        assert_eq!(outputs, vec!["out/generated/keep.lua"]);
+    }
+
+    #[test]
+    fn invalid_pattern_in_ignore_file_is_an_error() {
+        let resources = create_resources(&[(".darkluaignore", "{a")]);
+
+        let error = process(&resources, Options::new("src")).unwrap_err();
+
+        let message = error.to_string();
+        assert!(message.starts_with("invalid file pattern `{a`"));
+        assert!(message.ends_with("(in ignore file `.darkluaignore`)"));
+    }
+}
+
+mod output_extensions {
+    use super::*;
+
+    const RETAIN_LINES_CONFIG: &str = "{ rules: [], generator: 'retain_lines' }";
+
+    #[test]
+    fn rename_output_files() {
+        let resources = memory_resources!(
+            "src/main.luau" => "return nil",
+            "src/lib/init.luau" => "return nil",
+            "src/legacy.lua" => "return nil",
+            ".darklua.json" => RETAIN_LINES_CONFIG,
+        );
+
+        let outputs = process_outputs(
+            &resources,
+            Options::new("src")
+                .with_output("out")
+                .with_output_extension("luau", "lua"),
+        );
+
+        assert_eq!(
+            outputs,
+            vec!["out/legacy.lua", "out/lib/init.lua", "out/main.lua"]
+        );
+    }
+
+    #[test]
+    fn rename_output_of_single_file_in_directory() {
+        let resources = memory_resources!(
+            "src/main.luau" => "return nil",
+            ".darklua.json" => RETAIN_LINES_CONFIG,
+        );
+
+        let outputs = process_outputs(
+            &resources,
+            Options::new("src/main.luau")
+                .with_output("out")
+                .with_output_extension(".luau", ".lua"),
+        );
+
+        assert_eq!(outputs, vec!["out/main.lua"]);
+    }
+
+    #[test]
+    fn update_require_paths_with_extension() {
+        let resources = memory_resources!(
+            "src/main.luau" => concat!(
+                "local value = require('./value.luau')\n",
+                "local lib = require('./lib/init.luau')\n",
+                "local folder = require('./lib')\n",
+                "local data = require('./data.json')\n",
+                "return value, lib, folder, data",
+            ),
+            "src/value.luau" => "return 1",
+            "src/lib/init.luau" => "return 2",
+            ".darklua.json" => RETAIN_LINES_CONFIG,
+        );
+
+        process_outputs(
+            &resources,
+            Options::new("src")
+                .with_output("out")
+                .with_output_extension("luau", "lua"),
+        );
+
+        let main = resources.get("out/main.lua").unwrap();
+        assert!(main.contains("require('./value.lua')"));
+        assert!(main.contains("require('./lib/init.lua')"));
+        assert!(main.contains("require('./lib')"));
+        assert!(main.contains("require('./data.json')"));
+    }
+
+    #[test]
+    fn update_require_paths_excluded_from_bundle() {
+        let resources = memory_resources!(
+            "src/main.luau" => "local value = require('./value.luau') local lib = require('./lib.luau') return value, lib",
+            "src/value.luau" => "return 1",
+            "src/lib.luau" => "return 2",
+            ".darklua.json" => "{ rules: [], generator: 'retain_lines', bundle: { require_mode: 'path', excludes: ['**/lib.luau'] } }",
+        );
+
+        process_outputs(
+            &resources,
+            Options::new("src/main.luau")
+                .with_output("out")
+                .with_output_extension("luau", "lua"),
+        );
+
+        let main = resources.get("out/main.lua").unwrap();
+        assert!(main.contains("require('./lib.lua')"));
+        assert!(!main.contains("./value.lua"));
+    }
+}
+
+mod configuration_overrides {
+    use super::*;
+
+    const CODE: &str = "-- comment\nlocal value = 1 + 1\nreturn value";
+
+    fn process_with_config(config: &str) -> (String, String) {
+        let resources = memory_resources!(
+            "src/main.lua" => CODE,
+            "src/Packages/lib/init.lua" => CODE,
+            ".darklua.json" => config,
+        );
+
+        process(&resources, Options::new("src").with_output("out"))
+            .unwrap()
+            .result()
+            .unwrap();
+
+        (
+            resources.get("out/main.lua").unwrap(),
+            resources.get("out/Packages/lib/init.lua").unwrap(),
+        )
+    }
+
+    #[test]
+    fn override_replaces_rules() {
+        let (main, package) = process_with_config(
+            r#"{
+                generator: "retain_lines",
+                rules: ["remove_comments", "compute_expression"],
+                overrides: [{ include: "Packages/", rules: ["remove_comments"] }],
+            }"#,
+        );
+
+        assert!(!main.contains("comment"));
+        assert!(main.contains("local value = 2"));
+        assert!(!package.contains("comment"));
+        assert!(package.contains("local value = 1 + 1"));
+    }
+
+    #[test]
+    fn override_merges_rules() {
+        let (main, package) = process_with_config(
+            r#"{
+                generator: "retain_lines",
+                rules: ["remove_comments"],
+                overrides: [{ include: "**/Packages/**", rules: ["compute_expression"], merge: true }],
+            }"#,
+        );
+
+        assert!(!main.contains("comment"));
+        assert!(main.contains("local value = 1 + 1"));
+        assert!(!package.contains("comment"));
+        assert!(package.contains("local value = 2"));
+    }
+
+    #[test]
+    fn last_matching_override_is_used() {
+        let (main, package) = process_with_config(
+            r#"{
+                generator: "retain_lines",
+                rules: [],
+                overrides: [
+                    { include: "*.lua", rules: ["remove_comments"] },
+                    { include: "Packages/", rules: ["compute_expression"] },
+                ],
+            }"#,
+        );
+
+        assert!(!main.contains("comment"));
+        assert!(main.contains("local value = 1 + 1"));
+        assert_eq!(package, "-- comment\nlocal value = 2\nreturn value");
+    }
+}
+
+mod error_modes {
+    use std::path::{Path, PathBuf};
+
+    use darklua_core::{
+        nodes::Block,
+        rules::{
+            Context, Rule, RuleConfiguration, RuleConfigurationError, RuleProcessResult,
+            RuleProperties,
+        },
+        Configuration, ErrorMode, GeneratorParameters,
+    };
+
+    use super::*;
+
+    fn create_resources() -> Resources {
+        memory_resources!(
+            "src/a.lua" => "return 'a'",
+            "src/b.lua" => "return (",
+            "src/c.lua" => "return 'c'",
+            ".darklua.json" => "{ rules: [], generator: 'retain_lines' }",
+        )
+    }
+
+    #[test]
+    fn continue_mode_writes_successful_outputs() {
+        let resources = create_resources();
+
+        let worker_tree = process(
+            &resources,
+            Options::new("src")
+                .with_output("out")
+                .with_error_mode(ErrorMode::Continue),
+        )
+        .unwrap();
+
+        assert_eq!(resources.get("out/a.lua").unwrap(), "return 'a'");
+        assert_eq!(resources.get("out/c.lua").unwrap(), "return 'c'");
+        assert!(!resources.exists("out/b.lua").unwrap());
+
+        let report = worker_tree.report();
+        assert!(report.has_errors());
+        assert_eq!(report.iter_successes().count(), 2);
+        assert_eq!(report.iter_errors().count(), 1);
+    }
+
+    #[test]
+    fn continue_is_the_default_mode() {
+        assert_eq!(Options::new("src").error_mode(), ErrorMode::Continue);
+    }
+
+    #[test]
+    fn fail_fast_mode_stops_at_first_error() {
+        let resources = memory_resources!(
+            "src/a.lua" => "return (",
+            "src/b.lua" => "return (",
+            ".darklua.json" => "{ rules: [], generator: 'retain_lines' }",
+        );
+
+        let errors = process(
+            &resources,
+            Options::new("src")
+                .with_output("out")
+                .with_error_mode(ErrorMode::FailFast),
+        )
+        .unwrap()
+        .result()
+        .unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[derive(Debug)]
+    struct RequireContent(PathBuf);
+
+    impl Rule for RequireContent {
+        fn process(&self, _: &mut Block, _: &Context) -> RuleProcessResult {
+            Ok(())
+        }
+
+        fn require_content(&self, current_source: &Path, _: &Block) -> Vec<PathBuf> {
+            if current_source == Path::new("src/a.lua") {
+                vec![self.0.clone()]
+            } else {
+                Vec::new()
+            }
+        }
+    }
+
+    impl RuleConfiguration for RequireContent {
+        fn configure(&mut self, _: RuleProperties) -> Result<(), RuleConfigurationError> {
+            Ok(())
+        }
+
+        fn get_name(&self) -> &'static str {
+            "require_content"
+        }
+
+        fn serialize_to_properties(&self) -> RuleProperties {
+            RuleProperties::new()
+        }
+    }
+
+    #[test]
+    fn work_requiring_failed_work_fails() {
+        let resources = create_resources();
+
+        let worker_tree = process(
+            &resources,
+            Options::new("src").with_output("out").with_configuration(
+                Configuration::empty()
+                    .with_generator(GeneratorParameters::default_retain_lines())
+                    .with_rule(
+                        Box::new(RequireContent(PathBuf::from("src/b.lua"))) as Box<dyn Rule>
+                    ),
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(resources.get("out/c.lua").unwrap(), "return 'c'");
+
+        let mut errors: Vec<_> = worker_tree
+            .collect_errors()
+            .into_iter()
+            .map(ToString::to_string)
+            .collect();
+        errors.sort();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[1],
+            "unable to process `src/a.lua` because it requires `src/b.lua`, which failed"
+        );
+    }
+}
+
+mod file_budgets {
+    use std::{path::Path, thread, time::Duration};
+
+    use darklua_core::{
+        nodes::{Block, Statement},
+        process::{DefaultVisitor, NodeProcessor, NodeVisitor},
+        rules::{
+            Context, Rule, RuleConfiguration, RuleConfigurationError, RuleProcessResult,
+            RuleProperties,
+        },
+        Configuration, ErrorKind, ErrorMode, FileBudget, GeneratorParameters, WorkerTree,
+    };
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct SleepOnSlowFiles;
+
+    impl Rule for SleepOnSlowFiles {
+        fn process(&self, _: &mut Block, context: &Context) -> RuleProcessResult {
+            if context.current_path() == Path::new("src/slow.lua") {
+                thread::sleep(Duration::from_millis(50));
+            }
+            Ok(())
+        }
+    }
+
+    impl RuleConfiguration for SleepOnSlowFiles {
+        fn configure(&mut self, _: RuleProperties) -> Result<(), RuleConfigurationError> {
+            Ok(())
+        }
+
+        fn get_name(&self) -> &'static str {
+            "sleep_on_slow_files"
+        }
+
+        fn serialize_to_properties(&self) -> RuleProperties {
+            RuleProperties::new()
+        }
+    }
+
+    #[derive(Default)]
+    struct StatementCounter {
+        statements: usize,
+    }
+
+    impl NodeProcessor for StatementCounter {
+        fn process_statement(&mut self, _: &mut Statement) {
+            self.statements += 1;
+        }
+    }
+
+    /// Fails when the visitor did not visit every statement of the block.
+    #[derive(Debug)]
+    struct VisitStatements;
+
+    impl Rule for VisitStatements {
+        fn process(&self, block: &mut Block, _: &Context) -> RuleProcessResult {
+            let mut counter = StatementCounter::default();
+            DefaultVisitor::visit_block(block, &mut counter);
+
+            if counter.statements == block.iter_statements().count() {
+                Ok(())
+            } else {
+                Err(format!("visited {} statements", counter.statements).into())
+            }
+        }
+    }
+
+    impl RuleConfiguration for VisitStatements {
+        fn configure(&mut self, _: RuleProperties) -> Result<(), RuleConfigurationError> {
+            Ok(())
+        }
+
+        fn get_name(&self) -> &'static str {
+            "visit_statements"
+        }
+
+        fn serialize_to_properties(&self) -> RuleProperties {
+            RuleProperties::new()
+        }
+    }
+
+    fn create_resources() -> Resources {
+        memory_resources!(
+            "src/fast.lua" => "local a = 1\nreturn a",
+            "src/slow.lua" => "print()\n".repeat(100),
+        )
+    }
+
+    fn process_with_budget(
+        resources: &Resources,
+        rule: impl Rule + 'static,
+        budget: FileBudget,
+        error_mode: ErrorMode,
+    ) -> WorkerTree {
+        process(
+            resources,
+            Options::new("src")
+                .with_output("out")
+                .with_error_mode(error_mode)
+                .with_file_budget(budget)
+                .with_configuration(
+                    Configuration::empty()
+                        .with_generator(GeneratorParameters::default_retain_lines())
+                        .with_rule(rule),
+                ),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn timeout_abandons_slow_file() {
+        let resources = create_resources();
+
+        let worker_tree = process_with_budget(
+            &resources,
+            SleepOnSlowFiles,
+            FileBudget::new().with_timeout(Duration::from_millis(5)),
+            ErrorMode::Continue,
+        );
+
+        let errors = worker_tree.collect_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind(), ErrorKind::Timeout);
+        assert_eq!(
+            errors[0].to_string(),
+            "unable to process `src/slow.lua`: the file exceeded its budget (timeout of 5ms) while applying rule `sleep_on_slow_files`"
+        );
+
+        assert_eq!(
+            resources.get("out/fast.lua").unwrap(),
+            "local a = 1\nreturn a"
+        );
+        assert!(resources.get("out/slow.lua").is_err());
+    }
+
+    #[test]
+    fn step_budget_stops_visitors() {
+        let resources = create_resources();
+
+        let worker_tree = process_with_budget(
+            &resources,
+            VisitStatements,
+            FileBudget::new().with_steps(10),
+            ErrorMode::Continue,
+        );
+
+        let errors = worker_tree.collect_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind(), ErrorKind::Timeout);
+        assert_eq!(
+            errors[0].to_string(),
+            "unable to process `src/slow.lua`: the file exceeded its budget (more than 10 steps) while applying rule `visit_statements`"
+        );
+        assert!(resources.get("out/fast.lua").is_ok());
+    }
+
+    #[test]
+    fn step_budget_is_shared_by_the_rules_of_a_file() {
+        let resources = memory_resources!(
+            "src/main.lua" => "print()\n".repeat(6),
+        );
+
+        let worker_tree = process(
+            &resources,
+            Options::new("src")
+                .with_output("out")
+                .with_file_budget(FileBudget::new().with_steps(10))
+                .with_configuration(
+                    Configuration::empty()
+                        .with_rule(VisitStatements)
+                        .with_rule(VisitStatements),
+                ),
+        )
+        .unwrap();
+
+        let errors = worker_tree.collect_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind(), ErrorKind::Timeout);
+    }
+
+    #[test]
+    fn file_within_budget_is_processed() {
+        let resources = create_resources();
+
+        let worker_tree = process_with_budget(
+            &resources,
+            VisitStatements,
+            FileBudget::new()
+                .with_steps(1000)
+                .with_timeout(Duration::from_secs(60)),
+            ErrorMode::Continue,
+        );
+
+        assert!(worker_tree.collect_errors().is_empty());
+        assert!(resources.get("out/slow.lua").is_ok());
+    }
+
+    #[test]
+    fn fail_fast_stops_at_exceeded_budget() {
+        let resources = memory_resources!(
+            "src/a.lua" => "print()\n".repeat(20),
+            "src/b.lua" => "print()\n".repeat(20),
+        );
+
+        let worker_tree = process_with_budget(
+            &resources,
+            VisitStatements,
+            FileBudget::new().with_steps(10),
+            ErrorMode::FailFast,
+        );
+
+        assert_eq!(worker_tree.collect_errors().len(), 1);
+    }
+}
+
+mod rule_timings {
+    use super::*;
+
+    fn process_files(options: Options) -> darklua_core::ProcessReport {
+        let resources = memory_resources!(
+            "src/a.lua" => "-- comment\nreturn 1 + 1",
+            "src/b.lua" => "return 2 + 2",
+            ".darklua.json" => "{ rules: ['remove_comments', 'compute_expression'] }",
+        );
+
+        let worker_tree = process(&resources, options.with_output("out")).unwrap();
+        let report = worker_tree.report();
+        assert!(!report.has_errors());
+        report
+    }
+
+    #[test]
+    fn report_contains_total_of_each_rule() {
+        let report = process_files(Options::new("src").measure_rule_timings());
+
+        let timings: Vec<_> = report
+            .iter_rule_timings()
+            .map(|timing| (timing.rule(), timing.files()))
+            .collect();
+
+        assert_eq!(
+            timings,
+            vec![("remove_comments", 2), ("compute_expression", 2)]
+        );
+    }
+
+    #[test]
+    fn report_contains_durations_of_each_file() {
+        let report = process_files(Options::new("src").measure_rule_timings());
+
+        let file = report.get("src/a.lua").unwrap();
+
+        assert_eq!(
+            file.iter_rule_durations()
+                .map(|duration| duration.rule())
+                .collect::<Vec<_>>(),
+            vec!["remove_comments", "compute_expression"]
+        );
+    }
+
+    #[test]
+    fn serialize_rule_timings() {
+        let report = process_files(Options::new("src").measure_rule_timings());
+
+        let value = serde_json::to_value(&report).unwrap();
+        let timings = value["rule_timings"].as_array().unwrap();
+
+        assert_eq!(timings.len(), 2);
+        assert_eq!(timings[0]["rule"], "remove_comments");
+        assert_eq!(timings[0]["files"], 2);
+        assert!(timings[0]["duration_ms"].as_f64().unwrap() >= 0.0);
+        assert_eq!(
+            value["files"][0]["rule_durations"][1]["rule"],
+            "compute_expression"
+        );
+    }
+
+    #[test]
+    fn timings_are_not_measured_by_default() {
+        let report = process_files(Options::new("src"));
+
+        assert_eq!(report.iter_rule_timings().count(), 0);
+        assert_eq!(
+            serde_json::to_value(&report).unwrap().get("rule_timings"),
+            None
+        );
+    }
+}
+
+mod input_code {
+    use super::*;
+
+    const BUNDLE_CONFIG: &str =
+        "{ rules: [], generator: 'retain_lines', bundle: { require_mode: 'path' } }";
+
+    #[test]
+    fn bundle_virtual_entry_requiring_modules() {
+        let resources = memory_resources!(
+            "src/value.lua" => "return 'bundled value'",
+            ".darklua.json" => BUNDLE_CONFIG,
+        );
+
+        let worker_tree = process(
+            &resources,
+            Options::from_code("src/main.lua", "return require('./value')"),
+        )
+        .unwrap();
+
+        assert!(!worker_tree.report().has_errors());
+        let output_code = worker_tree.output_code().unwrap();
+        assert!(output_code.contains("'bundled value'"));
+        assert!(!resources.exists("src/main.lua").unwrap());
+    }
+
+    #[test]
+    fn output_is_not_written_to_resources() {
+        let resources = memory_resources!(
+            ".darklua.json" => "{ rules: ['remove_comments'], generator: 'retain_lines' }",
+        );
+
+        let worker_tree = process(
+            &resources,
+            Options::from_code("main.lua", "-- comment\nreturn nil").with_output("out.lua"),
+        )
+        .unwrap();
+
+        let output_code = worker_tree.output_code().unwrap();
+        assert!(output_code.contains("return nil"));
+        assert!(!output_code.contains("comment"));
+        assert!(!resources.exists("out.lua").unwrap());
+        assert!(!resources.exists("main.lua").unwrap());
+    }
+
+    #[test]
+    fn output_code_is_missing_when_processing_fails() {
+        let resources = memory_resources!(
+            ".darklua.json" => BUNDLE_CONFIG,
+        );
+
+        let worker_tree = process(
+            &resources,
+            Options::from_code("src/main.lua", "return require('./missing')"),
+        )
+        .unwrap();
+
+        assert!(worker_tree.report().has_errors());
+        assert_eq!(worker_tree.output_code(), None);
+    }
+
+    #[test]
+    fn output_code_is_missing_without_input_code() {
+        let resources = memory_resources!(
+            "src/main.lua" => "return nil",
+            ".darklua.json" => "{ rules: [] }",
+        );
+
+        let worker_tree = process(&resources, Options::new("src/main.lua")).unwrap();
+
+        assert_eq!(worker_tree.output_code(), None);
+    }
+}
+
+mod configuration_targets {
+    use darklua_core::{
+        nodes::{
+            BinaryExpression, BinaryOperator, CompoundAssignStatement, Expression, LastStatement,
+            Statement, Type,
+        },
+        process::{DefaultVisitor, NodeProcessor, NodeVisitor},
+        Parser,
+    };
+
+    use super::*;
+
+    const LUAU_CODE: &str = r#"
+type Point = { x: number, y: number }
+local function sum(points: { Point }): number
+    local total: number = 0
+    for _, point in ipairs(points) do
+        if point.x < 0 then
+            continue
+        end
+        total += point.x // 2
+    end
+    local label = if total > 10 then "big" else "small"
+    print(`total is {total} ({label})`)
+    return total :: number
+end
+return sum
+"#;
+
+    /// Finds the syntax that is not supported by Lua 5.1.
+    #[derive(Default)]
+    struct Lua51Checker {
+        unsupported: Vec<&'static str>,
+    }
+
+    impl NodeProcessor for Lua51Checker {
+        fn process_statement(&mut self, statement: &mut Statement) {
+            match statement {
+                Statement::TypeDeclaration(_)
+                | Statement::TypeFunction(_)
+                | Statement::ExportTypeFunction(_) => self.unsupported.push("type declaration"),
+                _ => {}
+            }
+        }
+
+        fn process_compound_assign_statement(&mut self, _: &mut CompoundAssignStatement) {
+            self.unsupported.push("compound assignment");
+        }
+
+        fn process_last_statement(&mut self, statement: &mut LastStatement) {
+            if let LastStatement::Continue(_) = statement {
+                self.unsupported.push("continue");
+            }
+        }
+
+        fn process_expression(&mut self, expression: &mut Expression) {
+            match expression {
+                Expression::If(_) => self.unsupported.push("if expression"),
+                Expression::InterpolatedString(_) => self.unsupported.push("interpolated string"),
+                Expression::TypeCast(_) => self.unsupported.push("type assertion"),
+                _ => {}
+            }
+        }
+
+        fn process_binary_expression(&mut self, binary: &mut BinaryExpression) {
+            if binary.operator() == BinaryOperator::DoubleSlash {
+                self.unsupported.push("floor division");
+            }
+        }
+
+        fn process_type(&mut self, _: &mut Type) {
+            self.unsupported.push("type");
+        }
+    }
+
+    fn process_with_config(code: &str, config: &str) -> String {
+        let resources = memory_resources!(
+            "src/main.luau" => code,
+            "config/base.json5" => "{ target: 'lua51', rules: [], generator: 'readable' }",
+            ".darklua.json5" => config,
+        );
+
+        process(&resources, Options::new("src"))
+            .unwrap()
+            .result()
+            .unwrap();
+
+        resources.get("src/main.luau").unwrap()
+    }
+
+    fn find_unsupported_lua51_syntax(code: &str) -> Vec<&'static str> {
+        let mut block = Parser::default().parse(code).unwrap();
+        let mut checker = Lua51Checker::default();
+        DefaultVisitor::visit_block(&mut block, &mut checker);
+        checker.unsupported
+    }
+
+    fn assert_same_code(output: &str, expected: &str) {
+        assert_eq!(
+            Parser::default().parse(output).unwrap(),
+            Parser::default().parse(expected).unwrap()
+        );
+    }
+
+    #[test]
+    fn fixture_uses_unsupported_syntax() {
+        assert!(!find_unsupported_lua51_syntax(LUAU_CODE).is_empty());
+    }
+
+    #[test]
+    fn lua51_target_outputs_lua51_code() {
+        let output = process_with_config(
+            LUAU_CODE,
+            "{ target: 'lua51', rules: [], generator: 'readable' }",
+        );
+
+        assert_eq!(find_unsupported_lua51_syntax(&output), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn lua53_target_keeps_floor_division() {
+        let output = process_with_config(
+            "return a // b",
+            "{ target: 'lua53', rules: [], generator: 'readable' }",
+        );
+
+        assert_same_code(&output, "return a // b");
+    }
+
+    #[test]
+    fn luau_target_keeps_luau_syntax() {
+        let output = process_with_config(
+            "local a: number = 1 a += 1",
+            "{ target: 'luau', rules: [], generator: 'readable' }",
+        );
+
+        assert_same_code(&output, "local a: number = 1 a += 1");
+    }
+
+    #[test]
+    fn disable_target_rule() {
+        let output = process_with_config(
+            "local a: number = 1",
+            "{ target: 'lua51', rules: [{ disable: 'remove_types' }], generator: 'readable' }",
+        );
+
+        assert_same_code(&output, "local a: number = 1");
+    }
+
+    #[test]
+    fn override_target_rule_properties() {
+        let code = "return `value: {value}`";
+        let default_output = process_with_config(
+            code,
+            "{ target: 'lua51', rules: [], generator: 'readable' }",
+        );
+        let output = process_with_config(
+            code,
+            "{ target: 'lua51', rules: [{ rule: 'remove_interpolated_string', strategy: 'tostring' }], generator: 'readable' }",
+        );
+
+        assert_ne!(output, default_output);
+        assert_eq!(find_unsupported_lua51_syntax(&output), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn target_rules_are_applied_before_configuration_rules() {
+        let resources = memory_resources!(
+            "src/main.lua" => "return nil",
+            ".darklua.json5" => "{ target: 'lua53', rules: ['remove_comments'] }",
+        );
+
+        let worker_tree = process(&resources, Options::new("src").measure_rule_timings()).unwrap();
+        let report = worker_tree.report();
+
+        assert_eq!(
+            report
+                .get("src/main.lua")
+                .unwrap()
+                .iter_rule_durations()
+                .map(|duration| duration.rule())
+                .collect::<Vec<_>>(),
+            vec![
+                "remove_types",
+                "remove_attributes",
+                "remove_interpolated_string",
+                "remove_if_expression",
+                "remove_compound_assignment",
+                "remove_continue",
+                "remove_comments",
+            ]
+        );
+    }
+
+    #[test]
+    fn inherit_target_from_extended_configuration() {
+        let output =
+            process_with_config("local a: number = 1", "{ extends: './config/base.json5' }");
+
+        assert_same_code(&output, "local a = 1");
+    }
+
+    #[test]
+    fn disable_target_rule_of_extended_configuration() {
+        let output = process_with_config(
+            "local a: number = 1",
+            "{ extends: './config/base.json5', rules: [{ disable: 'remove_types' }] }",
+        );
+
+        assert_same_code(&output, "local a: number = 1");
+    }
+
+    #[test]
+    fn invalid_target_is_an_error() {
+        let resources = memory_resources!(
+            "src/main.lua" => "return nil",
+            ".darklua.json5" => "{ target: 'lua52' }",
+        );
+
+        let errors = process(&resources, Options::new("src"))
+            .unwrap()
+            .result()
+            .unwrap_err();
+
+        assert!(errors[0].to_string().contains("invalid `target`"));
+    }
+}
+
+mod configuration_error_paths {
+    use super::*;
+
+    use darklua_core::WorkerTree;
+
+    fn configuration_error(config_path: &str, config: &str) -> String {
+        let resources = memory_resources!(
+            "src/test.lua" => ANY_CODE,
+        );
+        resources.write(config_path, config).unwrap();
+
+        let errors = process(&resources, Options::new("src"))
+            .map_err(|err| vec![err])
+            .and_then(WorkerTree::result)
+            .expect_err("configuration should be invalid");
+
+        errors.first().unwrap().to_string()
+    }
+
+    #[test]
+    fn typo_inside_third_rule() {
+        assert_eq!(
+            configuration_error(
+                ".darklua.json",
+                r#"{
+  rules: [
+    'remove_comments',
+    'remove_spaces',
+    {
+      rule: 'convert_require',
+      current: { name: 'path', modul_folder_name: 'init' },
+      target: 'roblox',
+    },
+  ],
+}"#
+            ),
+            "invalid configuration file at `.darklua.json` (unexpected value for field 'current': \
+            unknown field `modul_folder_name`, expected one of `module_folder_name`, `sources`, \
+            `package_path` in rule `convert_require` (at `rules[2].current`, line 7, column 7))"
+        );
+    }
+
+    #[test]
+    fn unknown_rule_name() {
+        assert_eq!(
+            configuration_error(
+                ".darklua.json5",
+                r#"{
+  rules: [
+    'remove_comments',
+    'remove_space',
+  ],
+}"#
+            ),
+            "invalid configuration file at `.darklua.json5` (invalid rule name `remove_space`, \
+            did you mean `remove_spaces`? (at `rules[1]`, line 4, column 5))"
+        );
+    }
+
+    #[test]
+    fn typo_inside_third_rule_of_toml_configuration() {
+        assert_eq!(
+            configuration_error(
+                ".darklua.toml",
+                r#"[[rules]]
+rule = "remove_comments"
+
+[[rules]]
+rule = "remove_spaces"
+
+[[rules]]
+rule = "convert_require"
+current = "path"
+target = "roblox"
+modul_folder_name = "init"
+"#
+            ),
+            "invalid configuration file at `.darklua.toml` (unexpected field 'modul_folder_name' \
+            in rule `convert_require` (at `rules[2].modul_folder_name`, line 11, column 1))"
+        );
+    }
+}
+
+mod configuration_layers {
+    use super::*;
+
+    use darklua_core::{
+        rules::{ComputeExpression, Rule},
+        ConfigurationLayer, GeneratorParameters,
+    };
+
+    const CODE: &str = "-- comment\nlocal value = 1 + 1\nreturn value";
+
+    fn process_with_layer(layer: ConfigurationLayer) -> String {
+        let resources = memory_resources!(
+            "src/main.lua" => CODE,
+            ".darklua.json" => r#"{
+                generator: "dense",
+                rules: ["remove_comments", "rename_variables"],
+            }"#,
+        );
+
+        process(
+            &resources,
+            Options::new("src")
+                .with_output("out")
+                .with_configuration_layer(layer),
+        )
+        .unwrap()
+        .result()
+        .unwrap();
+
+        resources.get("out/main.lua").unwrap()
+    }
+
+    #[test]
+    fn without_layer_uses_configuration_file() {
+        let output = process_with_layer(ConfigurationLayer::new());
+
+        assert!(!output.contains("comment"));
+        assert!(!output.contains("value"));
+        assert!(output.contains("1+1"));
+        assert!(!output.contains('\n'));
+    }
+
+    #[test]
+    fn layer_disables_overrides_rules_and_sets_generator() {
+        let output = process_with_layer(
+            ConfigurationLayer::new()
+                .with_rule_disabled("rename_variables")
+                .with_rule_overridden(Box::<ComputeExpression>::default() as Box<dyn Rule>)
+                .with_generator(GeneratorParameters::default_retain_lines()),
+        );
+
+        assert!(!output.contains("comment"));
+        assert!(output.ends_with("local value = 2\nreturn value"));
+    }
+}
+
+mod applied_rules {
+    use darklua_core::{ProcessReport, RuleNoteValue};
+
+    use super::*;
+
+    const CONFIG: &str = "{
+        rules: [
+            { rule: 'convert_require', current: 'path', target: 'roblox' },
+            'remove_types',
+            { rule: 'inject_global_value', identifier: 'DEV', value: true },
+        ],
+    }";
+
+    fn process_files(options: Options) -> ProcessReport {
+        let resources = memory_resources!(
+            "src/main.lua" => "local a = require('./a')\nlocal b = require('./b')\nreturn DEV and a or b",
+            "src/a.lua" => "return nil",
+            "src/b.lua" => "return nil",
+            ".darklua.json" => CONFIG,
+        );
+
+        let report = process(&resources, options.with_output("out"))
+            .unwrap()
+            .report();
+        assert!(!report.has_errors());
+        report
+    }
+
+    #[test]
+    fn report_contains_rules_that_modified_the_file() {
+        let report = process_files(Options::new("src").collect_applied_rules());
+
+        let file = report.get("src/main.lua").unwrap();
+
+        assert_eq!(
+            file.iter_applied_rules()
+                .map(|applied_rule| (applied_rule.rule(), applied_rule.modifications()))
+                .collect::<Vec<_>>(),
+            vec![("convert_require", 2), ("inject_global_value", 1)]
+        );
+    }
+
+    #[test]
+    fn report_contains_rule_notes() {
+        let report = process_files(Options::new("src").collect_applied_rules());
+
+        let applied_rules: Vec<_> = report
+            .get("src/main.lua")
+            .unwrap()
+            .iter_applied_rules()
+            .collect();
+
+        assert_eq!(
+            applied_rules[0].get_note("converted_requires"),
+            Some(&RuleNoteValue::Count(2))
+        );
+        assert_eq!(
+            applied_rules[1].get_note("injected_globals"),
+            Some(&RuleNoteValue::Names(vec!["DEV".to_owned()]))
+        );
+    }
+
+    #[test]
+    fn files_without_modifications_have_no_applied_rules() {
+        let report = process_files(Options::new("src").collect_applied_rules());
+
+        assert_eq!(
+            report
+                .get("src/a.lua")
+                .unwrap()
+                .iter_applied_rules()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn serialize_applied_rules() {
+        let report = process_files(Options::new("src").collect_applied_rules());
+
+        let value = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(
+            value["files"][2]["applied_rules"],
+            serde_json::json!([
+                {
+                    "rule": "convert_require",
+                    "modifications": 2,
+                    "notes": { "converted_requires": 2 },
+                },
+                {
+                    "rule": "inject_global_value",
+                    "modifications": 1,
+                    "notes": { "injected_globals": ["DEV"] },
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn applied_rules_are_not_collected_by_default() {
+        let report = process_files(Options::new("src"));
+
+        assert_eq!(
+            report
+                .get("src/main.lua")
+                .unwrap()
+                .iter_applied_rules()
+                .count(),
+            0
+        );
+    }
+}
+
+mod statement_separators {
+    use darklua_core::Parser;
+
+    use super::*;
+
+    fn process_with_retain_lines(code: &str) -> String {
+        let resources = memory_resources!(
+            "src/main.lua" => code,
+            ".darklua.json" => "{ rules: ['remove_unused_variable'], generator: 'retain_lines' }",
+        );
+
+        process(&resources, Options::new("src"))
+            .unwrap()
+            .result()
+            .unwrap();
+
+        resources.get("src/main.lua").unwrap()
+    }
+
+    fn assert_same_code(output: &str, expected: &str) {
+        assert_eq!(
+            Parser::default().parse(output).unwrap(),
+            Parser::default().parse(expected).unwrap(),
+            "`{}` is not read like `{}`",
+            output,
+            expected
+        );
+    }
+
+    #[test]
+    fn removed_statement_between_call_and_parenthese_call_on_one_line() {
+        let output = process_with_retain_lines("local a = f() local unused = 1; (g or h)(a)");
+
+        assert_same_code(&output, "local a = f(); (g or h)(a)");
+    }
+
+    #[test]
+    fn removed_statement_between_call_and_parenthese_call_on_multiple_lines() {
+        let output = process_with_retain_lines("local a = f()\nlocal unused = 1;\n(g or h)(a)\n");
+
+        assert_same_code(&output, "local a = f(); (g or h)(a)");
+        assert_eq!(output.lines().count(), 3);
+    }
+}
+
+mod escape_unicode {
+    use darklua_core::Parser;
+
+    use super::*;
+
+    const CODE: &str =
+        "local emoji = \"a😀\\255b\"\nlocal long = [[é]] -- café\nreturn emoji, long\n";
+
+    fn process_with_generator(generator: &str) -> String {
+        let resources = memory_resources!(
+            "src/main.lua" => CODE,
+            ".darklua.json" => generator,
+        );
+
+        process(&resources, Options::new("src"))
+            .unwrap()
+            .result()
+            .unwrap();
+
+        resources.get("src/main.lua").unwrap()
+    }
+
+    #[test]
+    fn retain_lines_escapes_strings_and_keeps_comments() {
+        assert_eq!(
+            process_with_generator(
+                "{ rules: [], generator: { name: 'retain_lines', escape_unicode: true } }"
+            ),
+            "local emoji = \"a\\240\\159\\152\\128\\255b\"\nlocal long = '\\195\\169' -- café\nreturn emoji, long\n"
+        );
+    }
+
+    #[test]
+    fn retain_lines_escaped_strings_have_the_same_value() {
+        let output = process_with_generator(
+            "{ rules: [], generator: { name: 'retain_lines', escape_unicode: true } }",
+        );
+
+        assert_eq!(
+            Parser::default().parse(&output).unwrap(),
+            Parser::default().parse(CODE).unwrap()
+        );
+    }
+
+    #[test]
+    fn retain_lines_keeps_unicode_by_default() {
+        assert_eq!(
+            process_with_generator("{ rules: [], generator: 'retain_lines' }"),
+            CODE
+        );
+    }
+}
+
+mod darklua_builder {
+    use super::*;
+
+    use darklua_core::{
+        rules::{ComputeExpression, PathRequireMode, RemoveComments, RemoveEmptyDo},
+        Configuration, ConfigurationLayer, Darklua, ErrorKind, GeneratorParameters,
+    };
+
+    const CODE: &str = "-- comment\ndo end\nlocal value = 1 + 1\nreturn value";
+
+    fn project() -> Resources {
+        memory_resources!(
+            "src/main.lua" => CODE,
+        )
+    }
+
+    #[test]
+    fn apply_rules_and_generator() {
+        let resources = project();
+
+        let report = Darklua::new()
+            .with_resources(resources.clone())
+            .with_input("src")
+            .with_output("dist")
+            .with_rule(RemoveEmptyDo::default())
+            .with_rule_named("remove_comments")
+            .unwrap()
+            .with_generator(GeneratorParameters::dense(80))
+            .run()
+            .unwrap();
+
+        assert!(!report.has_errors());
+        assert_eq!(report.len(), 1);
+
+        let output = resources.get("dist/main.lua").unwrap();
+        assert!(!output.contains("comment"));
+        assert!(!output.contains("do"));
+        assert!(output.contains("1+1"));
+        assert!(!output.contains('\n'));
+        assert_eq!(resources.get("src/main.lua").unwrap(), CODE);
+    }
+
+    #[test]
+    fn without_rules_only_generates_code() {
+        let resources = project();
+
+        Darklua::new()
+            .with_resources(resources.clone())
+            .with_input("src")
+            .with_output("dist")
+            .with_generator(GeneratorParameters::default_retain_lines())
+            .run()
+            .unwrap();
+
+        assert_eq!(resources.get("dist/main.lua").unwrap(), CODE);
+    }
+
+    #[test]
+    fn configuration_is_used_as_base() {
+        let resources = project();
+
+        Darklua::new()
+            .with_resources(resources.clone())
+            .with_input("src")
+            .with_output("dist")
+            .with_configuration(
+                Configuration::empty()
+                    .with_rule(RemoveComments::default())
+                    .with_rule(RemoveEmptyDo::default())
+                    .with_generator(GeneratorParameters::default_retain_lines()),
+            )
+            .with_rule_disabled("remove_comments")
+            .with_rule(ComputeExpression::default())
+            .run()
+            .unwrap();
+
+        let output = resources.get("dist/main.lua").unwrap();
+        assert!(output.starts_with("-- comment\n"));
+        assert!(!output.contains("do end"));
+        assert!(output.ends_with("local value = 2\nreturn value"));
+    }
+
+    #[test]
+    fn layers_are_applied_after_previous_changes() {
+        let resources = project();
+
+        Darklua::new()
+            .with_resources(resources.clone())
+            .with_input("src")
+            .with_output("dist")
+            .with_rule(RemoveComments::default())
+            .with_generator(GeneratorParameters::default_dense())
+            .with_layer(
+                ConfigurationLayer::new()
+                    .with_rule_disabled("remove_comments")
+                    .with_generator(GeneratorParameters::default_retain_lines()),
+            )
+            .with_rule(RemoveEmptyDo::default())
+            .run()
+            .unwrap();
+
+        let output = resources.get("dist/main.lua").unwrap();
+        assert!(output.starts_with("-- comment\n"));
+        assert!(!output.contains("do end"));
+        assert!(output.ends_with("local value = 1 + 1\nreturn value"));
+    }
+
+    #[test]
+    fn bundle_with_require_mode() {
+        let resources = memory_resources!(
+            "src/main.lua" => "local value = require('./value') return value",
+            "src/value.lua" => "return 1 + 1",
+        );
+
+        let report = Darklua::new()
+            .with_resources(resources.clone())
+            .with_input("src/main.lua")
+            .with_output("dist/main.lua")
+            .with_generator(GeneratorParameters::readable(80))
+            .with_require_mode(PathRequireMode::new("init"))
+            .run()
+            .unwrap();
+
+        assert!(!report.has_errors());
+
+        let output = resources.get("dist/main.lua").unwrap();
+        assert!(!output.contains("require"));
+        assert!(output.contains("1 + 1"));
+    }
+
+    #[test]
+    fn unknown_rule_name_is_a_configuration_error() {
+        let error = Darklua::new()
+            .with_rule_named("remove_comment")
+            .unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::Configuration);
+        assert_eq!(
+            error.to_string(),
+            "invalid options: invalid rule name `remove_comment`, did you mean `remove_comments`?"
+        );
+    }
+
+    #[test]
+    fn rule_name_that_requires_properties_is_a_configuration_error() {
+        let error = Darklua::new()
+            .with_rule_named("inject_global_value")
+            .unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::Configuration);
+        assert!(error.to_string().ends_with("in rule `inject_global_value`"));
+    }
+
+    #[test]
+    fn run_without_input_is_a_configuration_error() {
+        let error = Darklua::new()
+            .with_resources(project())
+            .with_rule(RemoveEmptyDo::default())
+            .run()
+            .unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::Configuration);
+        assert_eq!(
+            error.to_string(),
+            "invalid options: an input path is required"
+        );
+    }
+
+    #[test]
+    fn run_with_configuration_and_configuration_file_is_a_configuration_error() {
+        let error = Darklua::new()
+            .with_resources(project())
+            .with_input("src")
+            .with_configuration(Configuration::empty())
+            .with_configuration_at("darklua.json")
+            .run()
+            .unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::Configuration);
+    }
+}
+
+mod rule_directives {
+    use darklua_core::{process_code, Configuration};
+
+    use super::*;
+
+    fn process_main(code: &str, rules: &str) -> String {
+        let resources = memory_resources!(
+            "src/main.lua" => code,
+            ".darklua.json" => format!("{{ rules: {}, generator: 'dense' }}", rules),
+        );
+
+        process(&resources, Options::new("src"))
+            .unwrap()
+            .result()
+            .unwrap();
+
+        resources.get("src/main.lua").unwrap()
+    }
+
+    fn assert_same_code(output: &str, expected: &str) {
+        utils::assert_blocks_eq(&utils::parse_input(output), &utils::parse_input(expected));
+    }
+
+    #[test]
+    fn disable_next_line_keeps_statement_removed_by_rule() {
+        let output = process_main(
+            "-- darklua-disable-next-line remove_empty_do\ndo end\ndo end\nreturn 1\n",
+            "['remove_empty_do']",
+        );
+
+        assert_same_code(&output, "do end return 1");
+    }
+
+    #[test]
+    fn disable_next_line_with_multiple_rules() {
+        let output = process_main(
+            "-- darklua-disable-next-line remove_empty_do, remove_unused_variable\nlocal unused = 1\ndo end\n",
+            "['remove_empty_do', 'remove_unused_variable']",
+        );
+
+        assert_same_code(&output, "local unused = 1");
+    }
+
+    #[test]
+    fn disable_next_line_only_disables_the_given_rules() {
+        let output = process_main(
+            "-- darklua-disable-next-line remove_unused_variable\nlocal unused = 1 + 1\n",
+            "['remove_unused_variable', 'compute_expression']",
+        );
+
+        assert_same_code(&output, "local unused = 2");
+    }
+
+    #[test]
+    fn disable_next_line_with_all_rules() {
+        let output = process_main(
+            "-- darklua-disable-next-line all\nlocal unused = 1 + 1\ndo end\n",
+            "['remove_empty_do', 'remove_unused_variable', 'compute_expression']",
+        );
+
+        assert_same_code(&output, "local unused = 1 + 1");
+    }
+
+    #[test]
+    fn disable_next_line_inside_function() {
+        let output = process_main(
+            "local function f()\n  -- darklua-disable-next-line remove_empty_do\n  do end\n  do end\nend\nreturn f\n",
+            "['remove_empty_do']",
+        );
+
+        assert_same_code(&output, "local function f() do end end return f");
+    }
+
+    #[test]
+    fn disable_next_line_does_not_skip_blank_lines() {
+        let output = process_main(
+            "-- darklua-disable-next-line remove_empty_do\n\ndo end\nreturn\n",
+            "['remove_empty_do']",
+        );
+
+        assert_same_code(&output, "return");
+    }
+
+    #[test]
+    fn disabled_region_spanning_several_statements() {
+        let output = process_main(
+            "-- darklua-disable remove_empty_do\ndo end\nlocal a = 1\ndo end\n-- darklua-enable remove_empty_do\ndo end\nreturn a\n",
+            "['remove_empty_do']",
+        );
+
+        assert_same_code(&output, "do end local a = 1 do end return a");
+    }
+
+    #[test]
+    fn nested_disabled_regions_end_with_the_outer_region() {
+        let output = process_main(
+            "-- darklua-disable remove_empty_do\ndo end\n-- darklua-disable remove_empty_do\ndo end\n-- darklua-enable remove_empty_do\ndo end\n-- darklua-enable remove_empty_do\ndo end\nreturn\n",
+            "['remove_empty_do']",
+        );
+
+        assert_same_code(&output, "do end do end do end return");
+    }
+
+    #[test]
+    fn unclosed_disabled_region_continues_until_the_end_of_the_file() {
+        let output = process_main(
+            "do end\n-- darklua-disable remove_empty_do\ndo end\ndo end\n",
+            "['remove_empty_do']",
+        );
+
+        assert_same_code(&output, "do end do end");
+    }
+
+    #[test]
+    fn enable_without_disabled_region_is_ignored() {
+        let output = process_main(
+            "do end\n-- darklua-enable remove_empty_do\ndo end\nreturn\n",
+            "['remove_empty_do']",
+        );
+
+        assert_same_code(&output, "return");
+    }
+
+    #[test]
+    fn unknown_rule_name_is_ignored() {
+        let output = process_main(
+            "-- darklua-disable-next-line remove_empty_does\ndo end\nreturn\n",
+            "['remove_empty_do']",
+        );
+
+        assert_same_code(&output, "return");
+    }
+
+    #[test]
+    fn disabled_statement_keeps_the_variables_it_reads() {
+        let output = process_main(
+            "local value = 1\n-- darklua-disable-next-line all\nprint(value)\n",
+            "['remove_unused_variable']",
+        );
+
+        assert_same_code(&output, "local value = 1 print(value)");
+    }
+
+    #[test]
+    fn disabled_statement_follows_renamed_variables() {
+        let output = process_main(
+            "local value = 1\n-- darklua-disable-next-line rename_variables\nvalue = value + 1\nreturn value\n",
+            "['rename_variables']",
+        );
+
+        assert_same_code(&output, "local a = 1 a = a + 1 return a");
+    }
+
+    #[test]
+    fn variables_declared_by_disabled_statement_are_renamed() {
+        let output = process_main(
+            "local value = 1\n-- darklua-disable-next-line rename_variables\nlocal value = value + 1\nreturn value\n",
+            "['rename_variables']",
+        );
+
+        assert_same_code(&output, "local a = 1 local b = a + 1 return b");
+    }
+
+    #[test]
+    fn bundled_module_statements_are_not_disabled() {
+        let resources = memory_resources!(
+            "src/main.lua" => "local m = require('./mod')\n-- darklua-disable-next-line remove_empty_do\ndo end\nreturn m\n",
+            "src/mod.lua" => "do end\ndo end\ndo end\nreturn nil\n",
+            ".darklua.json" => "{ rules: ['remove_empty_do'], generator: 'retain_lines', bundle: { require_mode: 'path' } }",
+        );
+
+        process(
+            &resources,
+            Options::new("src/main.lua").with_output("out.lua"),
+        )
+        .unwrap()
+        .result()
+        .unwrap();
+
+        let output = resources.get("out.lua").unwrap();
+
+        assert_eq!(
+            output.matches("do end").count(),
+            1,
+            "unexpected output:\n{}",
+            output
+        );
+    }
+
+    #[test]
+    fn directives_are_kept_with_retain_lines_generator() {
+        let code = "-- darklua-disable-next-line remove_empty_do\ndo end\ndo end\nreturn\n";
+        let resources = memory_resources!(
+            "src/main.lua" => code,
+            ".darklua.json" => "{ rules: ['remove_empty_do'], generator: 'retain_lines' }",
+        );
+
+        process(&resources, Options::new("src"))
+            .unwrap()
+            .result()
+            .unwrap();
+
+        let output = resources.get("src/main.lua").unwrap();
+
+        assert!(
+            output.starts_with("-- darklua-disable-next-line remove_empty_do\ndo end\n"),
+            "unexpected output: {}",
+            output
+        );
+        assert_eq!(output.matches("do end").count(), 1);
+    }
+
+    #[test]
+    fn process_code_with_disable_next_line() {
+        let configuration: Configuration =
+            json5::from_str("{ rules: ['remove_empty_do'], generator: 'dense' }").unwrap();
+
+        let code = process_code(
+            "-- darklua-disable-next-line remove_empty_do\ndo end\ndo end",
+            &configuration,
+        )
+        .unwrap();
+
+        assert_same_code(&code, "do end");
+    }
+}
+
+mod deeply_nested_code {
+    use super::*;
+
+    const NESTING_DEPTH: usize = 20_000;
+
+    fn process_with_config(code: &str, configuration: &str) -> String {
+        let resources = memory_resources!(
+            "src/main.lua" => code,
+            ".darklua.json" => configuration,
+        );
+
+        process(&resources, Options::new("src"))
+            .unwrap()
+            .result()
+            .unwrap();
+
+        resources.get("src/main.lua").unwrap()
+    }
+
+    fn nested_parentheses() -> String {
+        format!(
+            "return {}1{}",
+            "(".repeat(NESTING_DEPTH),
+            ")".repeat(NESTING_DEPTH)
+        )
+    }
+
+    fn long_binary_chain() -> String {
+        format!("return {}1", "1 + ".repeat(NESTING_DEPTH))
+    }
+
+    fn nested_if_statements() -> String {
+        format!(
+            "{}return{}",
+            "if true then ".repeat(NESTING_DEPTH),
+            " end".repeat(NESTING_DEPTH)
+        )
+    }
+
+    macro_rules! test_generators {
+        ($($name:ident => $generator:literal),* $(,)?) => {
+            $(
+                mod $name {
+                    use super::*;
+
+                    fn config(rule: &str) -> String {
+                        format!("{{ rules: ['{}'], generator: '{}' }}", rule, $generator)
+                    }
+
+                    #[test]
+                    fn compute_nested_parentheses() {
+                        let output =
+                            process_with_config(&nested_parentheses(), &config("compute_expression"));
+
+                        assert_eq!(output.trim(), "return 1");
+                    }
+
+                    #[test]
+                    fn compute_long_binary_chain() {
+                        let output =
+                            process_with_config(&long_binary_chain(), &config("compute_expression"));
+
+                        assert_eq!(output.trim(), format!("return {}", NESTING_DEPTH + 1));
+                    }
+
+                    #[test]
+                    fn keep_long_binary_chain() {
+                        let output = process_with_config(&long_binary_chain(), &config("remove_empty_do"));
+
+                        assert_eq!(output.matches('+').count(), NESTING_DEPTH);
+                    }
+                }
+            )*
+        };
+    }
+
+    test_generators!(
+        dense => "dense",
+        readable => "readable",
+        retain_lines => "retain_lines",
+    );
+
+    // the readable generator is not tested with nested blocks, because indenting each
+    // level creates an output that grows with the square of the depth
+    #[test]
+    fn remove_nested_if_statements_with_dense_generator() {
+        let output = process_with_config(
+            &nested_if_statements(),
+            "{ rules: ['remove_unused_if_branch'], generator: 'dense' }",
+        );
+
+        assert_eq!(output.matches("do").count(), NESTING_DEPTH);
+    }
+
+    #[test]
+    fn remove_nested_if_statements_with_retain_lines_generator() {
+        let output = process_with_config(
+            &nested_if_statements(),
+            "{ rules: ['remove_unused_if_branch'], generator: 'retain_lines' }",
+        );
+
+        assert_eq!(output.matches("do").count(), NESTING_DEPTH);
+    }
+
+    #[test]
+    fn process_in_parallel() {
+        let resources = memory_resources!(
+            "src/parentheses.lua" => &nested_parentheses(),
+            "src/chain.lua" => &long_binary_chain(),
+            ".darklua.json" => "{ rules: ['compute_expression'], generator: 'dense' }",
+        );
+
+        process(&resources, Options::new("src").parallel(2))
+            .unwrap()
+            .result()
+            .unwrap();
+
+        assert_eq!(resources.get("src/parentheses.lua").unwrap(), "return 1");
+        assert_eq!(
+            resources.get("src/chain.lua").unwrap(),
+            format!("return {}", NESTING_DEPTH + 1)
+        );
+    }
+
+    #[test]
+    fn process_code_with_nested_parentheses() {
+        let configuration: darklua_core::Configuration =
+            json5::from_str("{ rules: ['compute_expression'], generator: 'dense' }").unwrap();
+
+        let code = darklua_core::process_code(&nested_parentheses(), &configuration).unwrap();
+
+        assert_eq!(code, "return 1");
+    }
+
+    fn process_with_depth_limit(
+        code: &str,
+        rule: &str,
+        depth_limit: usize,
+    ) -> (Resources, darklua_core::WorkerTree) {
+        let resources = memory_resources!(
+            "src/main.lua" => code,
+            ".darklua.json" => &format!("{{ rules: ['{}'], generator: 'dense' }}", rule),
+        );
+
+        let worker_tree = process(
+            &resources,
+            Options::new("src").with_depth_limit(depth_limit),
+        )
+        .unwrap();
+
+        (resources, worker_tree)
+    }
+
+    fn assert_depth_limit_exceeded(worker_tree: &darklua_core::WorkerTree, limit: usize) {
+        let errors = worker_tree.collect_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].kind(),
+            darklua_core::ErrorKind::DepthLimitExceeded
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            format!(
+                "unable to process `src/main.lua`: the code is nested deeper than the limit of {} levels",
+                limit
+            )
+        );
+    }
+
+    #[test]
+    fn nested_parentheses_exceed_depth_limit() {
+        let (_, worker_tree) =
+            process_with_depth_limit(&nested_parentheses(), "compute_expression", 1_000);
+
+        assert_depth_limit_exceeded(&worker_tree, 1_000);
+    }
+
+    #[test]
+    fn long_binary_chain_exceeds_depth_limit() {
+        let (_, worker_tree) =
+            process_with_depth_limit(&long_binary_chain(), "compute_expression", 1_000);
+
+        assert_depth_limit_exceeded(&worker_tree, 1_000);
+    }
+
+    #[test]
+    fn nested_if_statements_exceed_depth_limit() {
+        let (_, worker_tree) =
+            process_with_depth_limit(&nested_if_statements(), "remove_unused_if_branch", 1_000);
+
+        assert_depth_limit_exceeded(&worker_tree, 1_000);
+    }
+
+    #[test]
+    fn depth_limit_of_zero_disables_the_limit() {
+        let (resources, worker_tree) =
+            process_with_depth_limit(&nested_parentheses(), "compute_expression", 0);
+
+        worker_tree.result().unwrap();
+
+        assert_eq!(resources.get("src/main.lua").unwrap(), "return 1");
+    }
+
+    #[test]
+    fn process_code_exceeds_default_depth_limit() {
+        let configuration: darklua_core::Configuration =
+            json5::from_str("{ rules: [], generator: 'dense' }").unwrap();
+        let code = format!("return {}1{}", "(".repeat(30_001), ")".repeat(30_001));
+
+        let error = darklua_core::process_code(&code, &configuration).unwrap_err();
+
+        assert_eq!(error.kind(), darklua_core::ErrorKind::DepthLimitExceeded);
+    }
+}
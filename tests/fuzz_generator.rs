@@ -76,8 +76,8 @@ macro_rules! fuzz_test_expression {
 }
 
 macro_rules! fuzz_test_block {
-    ($budget:expr, $generator:expr) => {
-        let block = AstFuzzer::new($budget).fuzz_block();
+    (fuzzer = $fuzzer:expr, $generator:expr) => {
+        let block = $fuzzer.fuzz_block();
 
         let mut generator = $generator;
         generator.write_block(&block);
@@ -125,6 +125,9 @@ macro_rules! fuzz_test_block {
             generated_lua_code,
         );
     };
+    ($budget:expr, $generator:expr) => {
+        fuzz_test_block!(fuzzer = AstFuzzer::new($budget), $generator);
+    };
 }
 
 fn run_for_minimum_time<F: Fn()>(func: F) {
@@ -311,6 +314,18 @@ generate_fuzz_tests!(
                 }
             });
         }
+
+        #[test]
+        fn fuzz_lua51_blocks() {
+            for _ in 0..300 {
+                fuzz_test_block!(
+                    fuzzer = AstFuzzer::new(FuzzBudget::new(20, 40))
+                        .with_max_depth(4)
+                        .without_luau_syntax(),
+                    DenseLuaGenerator::new(80)
+                );
+            }
+        }
     },
 
     readable_generator(ReadableLuaGenerator::new(80)) => {
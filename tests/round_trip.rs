@@ -0,0 +1,111 @@
+// this test file verifies that code processed without any rule is written back exactly
+// as it was, using every Lua fixture of the test suite
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use darklua_core::{
+    generator::{LuaGenerator, TokenBasedLuaGenerator},
+    Parser,
+};
+
+fn collect_lua_files(directory: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(directory).expect("unable to read directory") {
+        let path = entry.expect("unable to read directory entry").path();
+
+        if path.is_dir() {
+            collect_lua_files(&path, files);
+        } else if matches!(
+            path.extension().and_then(|extension| extension.to_str()),
+            Some("lua") | Some("luau")
+        ) {
+            files.push(path);
+        }
+    }
+}
+
+fn regenerate(code: &str) -> String {
+    let block = Parser::default()
+        .preserve_tokens()
+        .parse(code)
+        .unwrap_or_else(|error| panic!("could not parse content: {}", error));
+
+    let mut generator = TokenBasedLuaGenerator::new(code);
+    generator.write_block(&block);
+    generator.into_string()
+}
+
+fn first_different_line(expected: &str, received: &str) -> usize {
+    expected
+        .lines()
+        .zip(received.lines())
+        .position(|(expected_line, received_line)| expected_line != received_line)
+        .unwrap_or_else(|| expected.lines().count().min(received.lines().count()))
+        + 1
+}
+
+#[test]
+fn regenerate_every_fixture_without_changes() {
+    let tests_directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut files = Vec::new();
+    collect_lua_files(&tests_directory, &mut files);
+    files.sort();
+
+    assert!(!files.is_empty(), "no Lua fixture found");
+
+    let failures: Vec<_> = files
+        .iter()
+        .filter_map(|path| {
+            let code = fs::read_to_string(path).expect("unable to read fixture");
+            let output = regenerate(&code);
+
+            if output == code {
+                None
+            } else {
+                Some(format!(
+                    "`{}` differs at line {}",
+                    path.display(),
+                    first_different_line(&code, &output)
+                ))
+            }
+        })
+        .collect();
+
+    assert!(
+        failures.is_empty(),
+        "generated code is different from the original code:\n{}",
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn regenerate_adjacent_tokens_without_changes() {
+    for code in [
+        "return a[b[c]]",
+        "return name1..suffix",
+        "return list[index[1]]..tail",
+        "local value = t[1]; return value;",
+    ] {
+        pretty_assertions::assert_eq!(regenerate(code), code);
+    }
+}
+
+// the parser also reads Lua 5.2 code, where `goto` is a keyword and labels are written with
+// `::`, so Luau code that uses these tokens differently must still be read the same way
+#[test]
+fn regenerate_luau_code_similar_to_lua52_without_changes() {
+    for code in [
+        "local goto = 1",
+        "goto()",
+        "goto = goto + 1",
+        "return goto.field, t.goto",
+        "local x = y :: T",
+        "local x = y::T",
+        "print(value :: any)",
+        "local x = (y :: T) :: U",
+    ] {
+        pretty_assertions::assert_eq!(regenerate(code), code);
+    }
+}
@@ -1,4 +1,8 @@
-use darklua_core::rules::Rule;
+use darklua_core::{
+    generator::{LuaGenerator, TokenBasedLuaGenerator},
+    rules::{AppendTextComment, ContextBuilder, Rule},
+    Parser, Resources,
+};
 
 test_rule_with_tokens!(
     append_text_comment_start,
@@ -38,6 +42,43 @@ test_rule_with_tokens!(
     append_native_direction("return {}") => "--!native\nreturn {}",
 );
 
+test_rule_with_tokens!(
+    append_text_comment_start_after_directives,
+    json5::from_str::<Box<dyn Rule>>(r#"{
+        rule: 'append_text_comment',
+        text: 'hello',
+    }"#).unwrap(),
+    after_strict_directive("--!strict\nlocal a = 1") => "--!strict\n--hello\nlocal a = 1",
+    after_multiple_directives("--!strict\n--!native\nreturn {}") => "--!strict\n--!native\n--hello\nreturn {}",
+    after_shebang("#!/usr/bin/env lua\nprint('hi')") => "#!/usr/bin/env lua\n--hello\nprint('hi')",
+    after_directive_without_statements("--!strict") => "--!strict\n--hello",
+);
+
+test_rule_with_tokens!(
+    append_text_comment_before_functions,
+    json5::from_str::<Box<dyn Rule>>(r#"{
+        rule: 'append_text_comment',
+        text: 'hello',
+        location: 'before_functions',
+    }"#).unwrap(),
+    function_statement("function foo() end") => "--hello\nfunction foo() end",
+    local_function_after_statement("local a = 1\nlocal function foo() end") => "local a = 1\n--hello\nlocal function foo() end",
+    multiple_functions("local function a() end\nprint(a)\nfunction b() end") => "--hello\nlocal function a() end\nprint(a)\n--hello\nfunction b() end",
+    function_before_return("local function a() end\nreturn a") => "--hello\nlocal function a() end\nreturn a",
+    nested_function("local function a()\n\tlocal function b() end\nend") => "--hello\nlocal function a()\n\tlocal function b() end\nend",
+    function_after_directive("--!strict\nlocal function a() end") => "--!strict\n--hello\nlocal function a() end",
+);
+
+test_rule_with_tokens!(
+    append_text_comment_multiline_before_functions,
+    json5::from_str::<Box<dyn Rule>>(r#"{
+        rule: 'append_text_comment',
+        text: '1\n2',
+        location: 'before_functions',
+    }"#).unwrap(),
+    two_functions("function a() end\nfunction b() end") => "--[[\n1\n2\n]]\nfunction a() end\n--[[\n1\n2\n]]\nfunction b() end",
+);
+
 test_rule_with_tokens!(
     append_text_comment_multiline,
     json5::from_str::<Box<dyn Rule>>(r#"{
@@ -72,6 +113,83 @@ test_rule_without_effects!(
     after_empty_ast(""),
 );
 
+test_rule_without_effects!(
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'append_text_comment',
+        text: 'hello',
+        location: 'before_functions',
+    }"#
+    )
+    .unwrap(),
+    without_functions("local a = 1"),
+    function_expression("local a = function() end"),
+    returned_function_expression("return function() end"),
+    function_in_do_block("do local function a() end end"),
+    empty_ast_before_functions(""),
+);
+
+fn append_comments(code: &str, rules: &[AppendTextComment]) -> String {
+    let mut block = Parser::default()
+        .preserve_tokens()
+        .parse(code)
+        .expect("unable to parse code");
+
+    let resources = Resources::from_memory();
+    let context = ContextBuilder::new("src/test.lua", &resources, code).build();
+
+    for rule in rules {
+        rule.process(&mut block, &context)
+            .expect("rule should succeed");
+    }
+
+    let mut generator = TokenBasedLuaGenerator::new(code);
+    generator.write_block(&block);
+    generator.into_string()
+}
+
+#[test]
+fn multiple_rules_at_start_follow_declaration_order() {
+    pretty_assertions::assert_eq!(
+        append_comments(
+            "--!strict\nlocal a = 1",
+            &[
+                AppendTextComment::new("first"),
+                AppendTextComment::new("second")
+            ]
+        ),
+        "--!strict\n--first\n--second\nlocal a = 1"
+    );
+}
+
+#[test]
+fn multiple_rules_before_functions_follow_declaration_order() {
+    pretty_assertions::assert_eq!(
+        append_comments(
+            "local a = 1\nlocal function f() end",
+            &[
+                AppendTextComment::new("first").before_functions(),
+                AppendTextComment::new("second").before_functions(),
+            ]
+        ),
+        "local a = 1\n--first\n--second\nlocal function f() end"
+    );
+}
+
+#[test]
+fn multiple_rules_with_different_locations() {
+    pretty_assertions::assert_eq!(
+        append_comments(
+            "local function f() end\n",
+            &[
+                AppendTextComment::new("banner").before_functions(),
+                AppendTextComment::new("header"),
+            ]
+        ),
+        "--banner\n--header\nlocal function f() end\n"
+    );
+}
+
 #[test]
 fn deserialize_from_object_notation() {
     json5::from_str::<Box<dyn Rule>>(
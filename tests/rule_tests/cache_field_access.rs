@@ -0,0 +1,141 @@
+use darklua_core::{
+    generator::{DenseLuaGenerator, LuaGenerator},
+    rules::{CacheFieldAccess, ContextBuilder, Rule},
+    Parser, Resources,
+};
+
+test_rule!(
+    cache_field_access,
+    CacheFieldAccess::default(),
+    chain_read_in_three_statements(
+        "local width = self.config.rendering.width local height = self.config.rendering.height local scale = self.config.rendering.scale"
+    ) => "local __DARKLUA_CACHED1 = self.config.rendering local width = __DARKLUA_CACHED1.width local height = __DARKLUA_CACHED1.height local scale = __DARKLUA_CACHED1.scale",
+    chain_read_in_return_statement(
+        "return self.a.b.x + self.a.b.y + self.a.b.z"
+    ) => "local __DARKLUA_CACHED1 = self.a.b return __DARKLUA_CACHED1.x + __DARKLUA_CACHED1.y + __DARKLUA_CACHED1.z",
+    longest_chain_is_cached(
+        "local a = self.config.rendering.size.x local b = self.config.rendering.size.y local c = self.config.rendering.size.z"
+    ) => "local __DARKLUA_CACHED1 = self.config.rendering.size local a = __DARKLUA_CACHED1.x local b = __DARKLUA_CACHED1.y local c = __DARKLUA_CACHED1.z",
+    chain_in_function_body(
+        "local function update(self) local w = self.config.rendering.width local h = self.config.rendering.height return w * h * self.config.rendering.scale end"
+    ) => "local function update(self) local __DARKLUA_CACHED1 = self.config.rendering local w = __DARKLUA_CACHED1.width local h = __DARKLUA_CACHED1.height return w * h * __DARKLUA_CACHED1.scale end",
+    chain_in_loop_body(
+        "for i = 1, 10 do local a = obj.data.values.x local b = obj.data.values.y sum = sum + a + b + obj.data.values.z end"
+    ) => "for i = 1, 10 do local __DARKLUA_CACHED1 = obj.data.values local a = __DARKLUA_CACHED1.x local b = __DARKLUA_CACHED1.y sum = sum + a + b + __DARKLUA_CACHED1.z end",
+    chain_in_if_condition(
+        "if self.state.flags.visible then end local a = self.state.flags.size local b = self.state.flags.color"
+    ) => "local __DARKLUA_CACHED1 = self.state.flags if __DARKLUA_CACHED1.visible then end local a = __DARKLUA_CACHED1.size local b = __DARKLUA_CACHED1.color",
+    conditional_read_after_first_read(
+        "local a = self.config.rendering.width local b = enabled and self.config.rendering.height local c = self.config.rendering.scale"
+    ) => "local __DARKLUA_CACHED1 = self.config.rendering local a = __DARKLUA_CACHED1.width local b = enabled and __DARKLUA_CACHED1.height local c = __DARKLUA_CACHED1.scale",
+    two_different_chains(
+        "local a = x.y.z.a local b = x.y.z.b local c = x.y.z.c local d = p.q.r.d local e = p.q.r.e local f = p.q.r.f"
+    ) => "local __DARKLUA_CACHED1 = x.y.z local a = __DARKLUA_CACHED1.a local b = __DARKLUA_CACHED1.b local c = __DARKLUA_CACHED1.c local __DARKLUA_CACHED2 = p.q.r local d = __DARKLUA_CACHED2.d local e = __DARKLUA_CACHED2.e local f = __DARKLUA_CACHED2.f",
+    avoid_existing_identifier(
+        "local __DARKLUA_CACHED1 = 1 return a.b.c.x, a.b.c.y, a.b.c.z"
+    ) => "local __DARKLUA_CACHED1 = 1 local __DARKLUA_CACHED2 = a.b.c return __DARKLUA_CACHED2.x, __DARKLUA_CACHED2.y, __DARKLUA_CACHED2.z",
+);
+
+test_rule_without_effects!(
+    CacheFieldAccess::default(),
+    chain_read_twice("local a = self.config.rendering.width local b = self.config.rendering.height"),
+    chain_with_one_field("return self.config.a, self.config.b, self.config.c"),
+    intervening_call_statement(
+        "local a = self.config.rendering.width print(a) local b = self.config.rendering.height local c = self.config.rendering.scale"
+    ),
+    intervening_call_in_expression(
+        "local a = self.config.rendering.width local b = self.config.rendering.height + compute() local c = self.config.rendering.scale"
+    ),
+    intervening_method_call(
+        "local a = self.config.rendering.width local b = self:getHeight() local c = self.config.rendering.height local d = self.config.rendering.scale"
+    ),
+    intervening_function_expression_with_call(
+        "local a = x.y.z.a local f = function() print(x) end local b = x.y.z.b local c = x.y.z.c"
+    ),
+    write_through_chain(
+        "local a = self.config.rendering.width self.config.rendering.height = 10 local b = self.config.rendering.height local c = self.config.rendering.scale"
+    ),
+    compound_write_through_chain(
+        "local a = x.y.z.a local b = x.y.z.b x.y.z.count += 1 local c = x.y.z.c local d = x.y.z.d"
+    ),
+    write_in_nested_block(
+        "local a = t.a.b.x if cond then t.a.b.y = 1 end local b = t.a.b.y local c = t.a.b.z"
+    ),
+    root_reassigned(
+        "local a = self.config.rendering.width self = other local b = self.config.rendering.height local c = self.config.rendering.scale"
+    ),
+    root_redeclared(
+        "local a = self.config.rendering.width local self = other local b = self.config.rendering.height local c = self.config.rendering.scale"
+    ),
+    root_reassigned_in_nested_block(
+        "local a = x.y.z.a while cond do x = nil end local b = x.y.z.b local c = x.y.z.c"
+    ),
+    conditional_first_read(
+        "local a = enabled and self.config.rendering.width local b = self.config.rendering.height local c = self.config.rendering.scale"
+    ),
+    conditional_first_read_in_elseif(
+        "if a then elseif self.c.r.x then end local b = self.c.r.y local c = self.c.r.z"
+    ),
+    reads_in_nested_blocks(
+        "if cond then return x.y.z.a end do local b = x.y.z.b end local c = x.y.z.c"
+    ),
+    label_between_reads(
+        "local a = x.y.z.a ::continue:: local b = x.y.z.b local c = x.y.z.c"
+    ),
+);
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'cache_field_access',
+    }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'cache_field_access'").unwrap();
+}
+
+fn cache_field_access_with_locals(locals: usize, function_parameters: &str) -> String {
+    let locals: Vec<_> = (0..locals).map(|i| format!("v{}", i)).collect();
+    let code = format!(
+        "local function f({}) local {} = nil \
+        local a = x.y.z.a local b = x.y.z.b local c = x.y.z.c \
+        local d = p.q.r.d local e = p.q.r.e local f = p.q.r.f end",
+        function_parameters,
+        locals.join(", ")
+    );
+
+    let resources = Resources::from_memory();
+    let mut block = Parser::default()
+        .parse(&code)
+        .expect("unable to parse code");
+
+    let context = ContextBuilder::new("test.lua", &resources, &code).build();
+    CacheFieldAccess::default()
+        .process(&mut block, &context)
+        .expect("rule should succeed");
+
+    let mut generator = DenseLuaGenerator::default();
+    generator.write_block(&block);
+    generator.into_string()
+}
+
+#[test]
+fn cached_chains_respect_locals_limit() {
+    // the function already has 192 locals and its parameter, and the reads declare 6 more
+    let output = cache_field_access_with_locals(192, "self");
+
+    assert!(output.contains("__DARKLUA_CACHED1"), "{}", output);
+    assert!(!output.contains("__DARKLUA_CACHED2"), "{}", output);
+}
+
+#[test]
+fn chains_are_not_cached_at_locals_limit() {
+    let output = cache_field_access_with_locals(194, "");
+
+    assert!(!output.contains("__DARKLUA_CACHED"), "{}", output);
+}
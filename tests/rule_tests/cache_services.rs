@@ -0,0 +1,91 @@
+use darklua_core::{
+    generator::{LuaGenerator, TokenBasedLuaGenerator},
+    rules::{CacheServices, ContextBuilder, Rule},
+    Parser, Resources,
+};
+
+test_rule!(
+    cache_services,
+    CacheServices::default(),
+    single_call("return game:GetService('Players')")
+        => "local Players = game:GetService('Players') return Players",
+    call_with_string_argument("return game:GetService 'Players'")
+        => "local Players = game:GetService('Players') return Players",
+    repeated_calls("local a = game:GetService('Players') print(game:GetService('Players'))")
+        => "local a = game:GetService('Players') print(a)",
+    calls_to_different_services(
+        "print(game:GetService('RunService'), game:GetService('Players'), game:GetService('RunService'))"
+    ) => "local RunService = game:GetService('RunService') local Players = game:GetService('Players') print(RunService, Players, RunService)",
+    call_used_as_prefix("game:GetService('RunService').Heartbeat:Connect(update)")
+        => "local RunService = game:GetService('RunService') RunService.Heartbeat:Connect(update)",
+    call_in_method_call_prefix("local remote = game:GetService('ReplicatedStorage'):WaitForChild('Remote')")
+        => "local ReplicatedStorage = game:GetService('ReplicatedStorage') local remote = ReplicatedStorage:WaitForChild('Remote')",
+    call_inside_function("local function getPlayers() return game:GetService('Players'):GetPlayers() end")
+        => "local Players = game:GetService('Players') local function getPlayers() return Players:GetPlayers() end",
+    reuse_existing_declaration(
+        "local ReplicatedStorage = game:GetService('ReplicatedStorage') local function get() return game:GetService('ReplicatedStorage').Remote end"
+    ) => "local ReplicatedStorage = game:GetService('ReplicatedStorage') local function get() return ReplicatedStorage.Remote end",
+    reuse_existing_declaration_with_another_name(
+        "local RS = game:GetService('ReplicatedStorage') print(game:GetService('ReplicatedStorage'))"
+    ) => "local RS = game:GetService('ReplicatedStorage') print(RS)",
+    duplicated_declaration_reuse_first(
+        "local A = game:GetService('Players') local B = game:GetService('Players')"
+    ) => "local A = game:GetService('Players') local B = A",
+    call_before_existing_declaration(
+        "print(game:GetService('Players')) local Players = game:GetService('Players')"
+    ) => "local Players2 = game:GetService('Players') print(Players2) local Players = game:GetService('Players')",
+    existing_declaration_shadowed_in_function(
+        "local Players = game:GetService('Players') local function run(Players) return game:GetService('Players') end"
+    ) => "local Players2 = game:GetService('Players') local Players = game:GetService('Players') local function run(Players) return Players2 end",
+    existing_declaration_redeclared(
+        "local Players = game:GetService('Players') local Players = nil print(game:GetService('Players'))"
+    ) => "local Players2 = game:GetService('Players') local Players = game:GetService('Players') local Players = nil print(Players2)",
+    avoid_collision_with_local("local Players = {} print(game:GetService('Players'))")
+        => "local Players2 = game:GetService('Players') local Players = {} print(Players2)",
+    avoid_collision_with_global("print(Players, game:GetService('Players'))")
+        => "local Players2 = game:GetService('Players') print(Players, Players2)",
+    avoid_collision_with_nested_local(
+        "local function run() local Players2, Players = 1, 2 return game:GetService('Players') end"
+    ) => "local Players3 = game:GetService('Players') local function run() local Players2, Players = 1, 2 return Players3 end",
+);
+
+test_rule_without_effects!(
+    CacheServices::default(),
+    existing_declaration_only("local Players = game:GetService('Players') return Players"),
+    call_statement("game:GetService('Players')"),
+    non_literal_argument("local name = 'Players' return game:GetService(name)"),
+    service_name_is_not_an_identifier("return game:GetService('Some Service')"),
+    service_name_is_a_keyword("return game:GetService('end')"),
+    multiple_arguments("return game:GetService('Players', 1)"),
+    shadowed_game("local game = getGame() return game:GetService('Players')"),
+    shadowed_game_parameter("local function get(game) return game:GetService('Players') end"),
+    different_method("return game:FindService('Players')"),
+    different_object("return workspace:GetService('Players')"),
+    function_call_without_method("return game.GetService(game, 'Players')"),
+);
+
+#[test]
+fn cached_services_are_declared_after_file_header() {
+    let code = "--!strict\n-- the module header\nreturn game:GetService('Players')\n";
+
+    let resources = Resources::from_memory();
+    let mut block = Parser::default()
+        .preserve_tokens()
+        .parse(code)
+        .expect("unable to parse code");
+
+    let context = ContextBuilder::new("test.lua", &resources, code).build();
+    CacheServices::default()
+        .process(&mut block, &context)
+        .expect("rule should succeed");
+
+    let mut generator = TokenBasedLuaGenerator::new(code);
+    generator.write_block(&block);
+    let output = generator.into_string();
+
+    assert!(
+        output.starts_with("--!strict\n-- the module header\nlocal Players"),
+        "unexpected output: {}",
+        output
+    );
+}
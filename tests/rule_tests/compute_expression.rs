@@ -37,11 +37,133 @@ test_rule!(
     preserve_negative_zero("return -0") => "return -0",
     addition_preserve_negative_zero("return -0 + -0") => "return -0",
     subtract_preserve_negative_zero("return -0 - 0") => "return -0",
+    interpolated_string_with_number("return `value: {1 + 2}`") => "return 'value: 3'",
+    interpolated_string_with_concat("return `{'a' .. 'b'}!`") => "return 'ab!'",
+    interpolated_string_with_boolean("return `{true}`") => "return 'true'",
+    tostring_integer("return tostring(42)") => "return '42'",
+    tostring_boolean("return tostring(true)") => "return 'true'",
+    tostring_nil("return tostring(nil)") => "return 'nil'",
+    tostring_string_call("return tostring 'abc'") => "return 'abc'",
+    tostring_concatenation("return 'id: ' .. tostring(1 + 2)") => "return 'id: 3'",
+    tonumber_integer("return tonumber('12')") => "return 12",
+    tonumber_hex_string("return tonumber('0x10')") => "return 16",
+    tonumber_with_base("return tonumber('12', 8)") => "return 10",
+    tonumber_hex_with_base("return tonumber('ff', 16)") => "return 255",
+    tonumber_failure("return tonumber('hello')") => "return nil",
+    tonumber_failure_with_base("return tonumber('9', 8)") => "return nil",
+    tonumber_failure_in_condition("return tonumber('abc') or 0") => "return 0",
+    tostring_of_tonumber("return tostring(tonumber('0x10'))") => "return '16'",
+    tostring_shadowed_in_another_scope("do local tostring = f end return tostring(1)")
+        => "do local tostring = f end return '1'",
+    tostring_with_reassigned_tonumber("tonumber = custom return tostring(1)")
+        => "tonumber = custom return '1'",
 );
 
 test_rule_without_effects!(
     ComputeExpression::default(),
     if_expression_unknown_condition("return if condition then func() else func2()"),
+    interpolated_string_with_variable("return `value: {value}`"),
+    interpolated_string_with_table("return `{ {} }`"),
+    division_without_exact_literal("return 1 / 1400"),
+    tostring_variable("return tostring(value)"),
+    tostring_table("return tostring({})"),
+    tostring_with_side_effects("return tostring(f())"),
+    tostring_large_number_with_exponent("return tostring(1e100)"),
+    shadowed_tostring("local tostring = f return tostring(42)"),
+    shadowed_tostring_parameter("local function f(tostring) return tostring(42) end"),
+    shadowed_tonumber("local tonumber = f return tonumber('0x10')"),
+    tonumber_signed_hex_string("return tonumber('-0x10')"),
+    tonumber_invalid_base("return tonumber('1', 40)"),
+    tostring_method("return value:tostring(1)"),
+    reassigned_global_tostring("tostring = custom return tostring(42)"),
+    reassigned_global_tostring_after_call("local value = tostring(42) tostring = custom"),
+    reassigned_tostring_field_of_global_table("_G.tostring = custom return tostring(42)"),
+    reassigned_tonumber_index_of_global_table("_G['tonumber'] = custom return tonumber('12')"),
+    global_table_written_with_unknown_key("_G[name] = custom return tonumber('12')"),
+    redefined_global_tostring("function tostring() end return tostring(42)"),
+    redefined_tonumber_field_of_global_table("function _G.tonumber() end return tonumber('12')"),
+);
+
+test_rule!(
+    compute_expression_exact_literals,
+    ComputeExpression::default(),
+    addition_with_exact_round_trip("return 0.1 + 0.2") => "return 0.30000000000000004",
+    small_division_with_exact_literal("return 1 / 30") => "return 3.333333333333333E-2",
+    large_integer("return 2 ^ 53") => "return 9007199254740992",
+);
+
+test_rule!(
+    compute_expression_with_max_literal_length,
+    json5::from_str::<Box<dyn Rule>>(r#"{ rule: 'compute_expression', max_literal_length: 8 }"#).unwrap(),
+    short_fraction("return 7 / 2") => "return 3.5",
+    integer_longer_than_expression("return 2 ^ 40") => "return 1099511627776",
+    literal_longer_than_limit_but_shorter_than_expression("return 0.25 + 0.0625 + 0.0078125")
+        => "return 0.3203125",
+);
+
+test_rule_without_effects!(
+    json5::from_str::<Box<dyn Rule>>(r#"{ rule: 'compute_expression', max_literal_length: 8 }"#)
+        .unwrap(),
+    addition_longer_than_limit("return 0.1 + 0.2"),
+    division_longer_than_limit("return 1 / 3"),
+);
+
+test_rule!(
+    compute_expression_luau,
+    json5::from_str::<Box<dyn Rule>>(r#"{ rule: 'compute_expression', dialect: 'luau' }"#).unwrap(),
+    negative_floor_division("return -7 // 2") => "return -4",
+    modulo_negative_divisor("return 7 % -2") => "return -1",
+    floor_division_by_zero("return 1 // 0") => "return 1/0",
+    modulo_by_zero("return 1 % 0") => "return 0/0",
+    divide_integers("return 6 / 2") => "return 3",
+);
+
+test_rule!(
+    compute_expression_lua51,
+    json5::from_str::<Box<dyn Rule>>(r#"{ rule: 'compute_expression', dialect: 'lua51' }"#).unwrap(),
+    modulo_negative_divisor("return 7 % -2") => "return -1",
+    divide_by_zero("return -1 / 0") => "return -1/0",
+    modulo_by_zero("return 1 % 0") => "return 0/0",
+    tostring_with_fourteen_digits("return tostring(1 / 3)") => "return '0.33333333333333'",
+    tostring_large_number_with_exponent("return tostring(1e100)") => "return '1e+100'",
+    tostring_small_number_with_exponent("return tostring(0.00001)") => "return '1e-05'",
+);
+
+test_rule_without_effects!(
+    json5::from_str::<Box<dyn Rule>>(r#"{ rule: 'compute_expression', dialect: 'lua51' }"#)
+        .unwrap(),
+    lua51_floor_division("return -7 // 2"),
+    tostring_halfway_between_fourteen_digits("return tostring(123456789012345)"),
+);
+
+test_rule!(
+    compute_expression_lua53,
+    json5::from_str::<Box<dyn Rule>>(r#"{ rule: 'compute_expression', dialect: 'lua53' }"#).unwrap(),
+    negative_floor_division("return -7 // 2") => "return -4",
+    modulo_negative_divisor("return 7 % -2") => "return -1",
+    float_floor_division_by_zero("return 1.5 // 0") => "return 1/0",
+    divide_with_fraction("return 7 / 2") => "return 3.5",
+    tostring_integer("return tostring(7)") => "return '7'",
+    tostring_float("return tostring(7 / 2)") => "return '3.5'",
+    tostring_float_rounded_to_integer("return tostring(1.000000000000001)") => "return '1.0'",
+    tonumber_integer_string("return tonumber('12')") => "return 12",
+    tonumber_float_string("return tonumber('1.5')") => "return 1.5",
+    tonumber_float_with_base_ten("return tonumber('1.5', 10)") => "return nil",
+);
+
+test_rule_without_effects!(
+    json5::from_str::<Box<dyn Rule>>(r#"{ rule: 'compute_expression', dialect: 'lua53' }"#)
+        .unwrap(),
+    integer_floor_division_by_zero("return 1 // 0"),
+    integer_modulo_by_zero("return 1 % 0"),
+    divide_integers_into_float("return 6 / 2"),
+    string_coercion("return '10' + 1"),
+    tonumber_float_string_without_fraction("return tonumber('10.0')"),
+    tostring_negative_zero("return tostring(-0)"),
+    float_plus_integer("return 1.0 + 2"),
+    float_floor_division("return 3.0 // 2"),
+    integer_power("return 2 ^ 2"),
+    tostring_whole_float("return tostring(1.0)"),
 );
 
 #[test]
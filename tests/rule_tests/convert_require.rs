@@ -138,6 +138,61 @@ test_rule!(
         => "local module = require(script.Parent['a module'])",
 );
 
+test_rule_with_context!(
+    convert_path_require_with_sources,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+            rule: 'convert_require',
+            current: { name: 'path', sources: { pkg: 'Packages', config: 'build/config' } },
+            target: 'roblox',
+        }"#
+    ).unwrap(),
+    current_path = "context/src/main.lua",
+    project_location = "context",
+    files = {
+        "context/src/value.lua" => "return nil",
+        "context/src/folder/init.lua" => "return nil",
+        "context/Packages/lib.lua" => "return nil",
+        "context/Packages/Promise/init.luau" => "return nil",
+        "context/build/config.luau" => "return nil",
+    },
+    sibling_module("local value = require('./value')")
+        => "local value = require(script.Parent:FindFirstChild('value'))",
+    sibling_init_folder("local folder = require('./folder')")
+        => "local folder = require(script.Parent:FindFirstChild('folder'))",
+    source_module("local lib = require('pkg/lib')")
+        => "local lib = require(script.Parent.Parent:FindFirstChild('Packages'):FindFirstChild('lib'))",
+    source_init_folder("local Promise = require('pkg/Promise')")
+        => "local Promise = require(script.Parent.Parent:FindFirstChild('Packages'):FindFirstChild('Promise'))",
+    source_file_without_extension("local config = require('config')")
+        => "local config = require(script.Parent.Parent:FindFirstChild('build'):FindFirstChild('config'))",
+    source_file_with_remaining_path("local config = require('config/sub')")
+        => "local config = require('config/sub')",
+    unresolved_sibling_module("local missing = require('./missing')")
+        => "local missing = require('./missing')",
+    unknown_source("local lib = require('unknown/lib')")
+        => "local lib = require('unknown/lib')",
+);
+
+test_rule_with_context!(
+    convert_path_require_from_init_module,
+    json5::from_str::<Box<dyn Rule>>(
+        "{ rule: 'convert_require', current: 'path', target: 'roblox' }"
+    ).unwrap(),
+    current_path = "context/src/init.lua",
+    files = {
+        "context/src/value.lua" => "return nil",
+        "context/src/folder/init.lua" => "return nil",
+        "context/format.lua" => "return nil",
+    },
+    child_module("local value = require('./value')")
+        => "local value = require(script:FindFirstChild('value'))",
+    child_init_folder("local folder = require('./folder')")
+        => "local folder = require(script:FindFirstChild('folder'))",
+    module_in_parent("local format = require('../format')")
+        => "local format = require(script.Parent:FindFirstChild('format'))",
+);
+
 fn process_file(resources: &Resources, file_name: &str) -> String {
     darklua_core::process(resources, Options::new(file_name))
         .unwrap()
@@ -222,6 +277,70 @@ mod luaurc {
         );
     }
 
+    #[test]
+    fn convert_alias_module_without_extension_from_init_module() {
+        let resources = memory_resources!(
+            "src/init.lua" => "local value = require('@value')",
+            "src/value.luau" => "return nil",
+            ".luaurc" => r#"{ "aliases": { "value": "src/value" } }"#,
+            ".darklua.json" => CONVERT_PATH_TO_ROBLOX_DEFAULT_CONFIG,
+        );
+        expect_file_process(
+            &resources,
+            "src/init.lua",
+            "local value = require(script:FindFirstChild('value'))",
+        );
+    }
+
+    #[test]
+    fn convert_alias_module_with_commented_luaurc() {
+        let resources = memory_resources!(
+            "src/init.lua" => "local value = require('@value')",
+            "src/value.lua" => "return nil",
+            ".luaurc" => concat!(
+                "{\n",
+                "  // the aliases used by the project\n",
+                "  \"aliases\": {\n",
+                "    \"value\": \"src/value.lua\",\n",
+                "  },\n",
+                "}\n",
+            ),
+            ".darklua.json" => CONVERT_PATH_TO_ROBLOX_DEFAULT_CONFIG,
+        );
+        expect_file_process(
+            &resources,
+            "src/init.lua",
+            "local value = require(script:FindFirstChild('value'))",
+        );
+    }
+
+    #[test]
+    fn invalid_alias_type_errors() {
+        let resources = memory_resources!(
+            "src/init.lua" => "local value = require('@value')",
+            "src/value.lua" => "return nil",
+            ".luaurc" => r#"{ "aliases": { "value": true } }"#,
+            ".darklua.json" => CONVERT_PATH_TO_ROBLOX_DEFAULT_CONFIG,
+        );
+
+        let errors = darklua_core::process(&resources, Options::new("src/init.lua"))
+            .unwrap()
+            .result()
+            .unwrap_err();
+
+        pretty_assertions::assert_eq!(errors.len(), 1);
+
+        let message = errors.first().unwrap().to_string().replace('\\', "/");
+        assert!(
+            message.contains(concat!(
+                "invalid configuration file at `.luaurc` ",
+                "(expected the alias `aliases.value` to be a string path, but found a boolean)"
+            )),
+            "unexpected error message: {}",
+            message
+        );
+    }
+
     #[test]
     fn convert_folder_alias_module_from_init_module() {
         let resources = memory_resources!(
@@ -253,6 +372,117 @@ mod luaurc {
     }
 }
 
+mod package_path {
+    use super::*;
+
+    const CONVERT_PACKAGE_PATH_TO_ROBLOX_CONFIG: &str = concat!(
+        "{ rules: [{ rule: 'convert_require', ",
+        "current: { name: 'path', package_path: '?.lua;lib/?.lua;?/init.lua' }, ",
+        "target: 'roblox' }], generator: \"retain_lines\" }"
+    );
+
+    const CONVERT_PATH_TO_PACKAGE_PATH_CONFIG: &str = concat!(
+        "{ rules: [{ rule: 'convert_require', ",
+        "current: 'path', ",
+        "target: { name: 'path', package_path: '?.lua;lib/?.lua;?/init.lua' } }], ",
+        "generator: \"retain_lines\" }"
+    );
+
+    const CONVERT_PACKAGE_PATH_TO_PACKAGE_PATH_CONFIG: &str = concat!(
+        "{ rules: [{ rule: 'convert_require', ",
+        "current: { name: 'path', package_path: '?.lua;lib/?.lua;?/init.lua' }, ",
+        "target: { name: 'path', package_path: '?.lua;lib/?.lua;?/init.lua' } }], ",
+        "generator: \"retain_lines\" }"
+    );
+
+    #[test]
+    fn convert_module_from_first_template() {
+        let resources = memory_resources!(
+            "main.lua" => "local value = require('value')",
+            "value.lua" => "return nil",
+            ".darklua.json" => CONVERT_PACKAGE_PATH_TO_ROBLOX_CONFIG,
+        );
+        expect_file_process(
+            &resources,
+            "main.lua",
+            "local value = require(script.Parent:FindFirstChild('value'))",
+        );
+    }
+
+    #[test]
+    fn convert_dotted_module_from_second_template() {
+        let resources = memory_resources!(
+            "main.lua" => "local value = require('utils.str')",
+            "lib/utils/str.lua" => "return nil",
+            ".darklua.json" => CONVERT_PACKAGE_PATH_TO_ROBLOX_CONFIG,
+        );
+        expect_file_process(
+            &resources,
+            "main.lua",
+            "local value = require(script.Parent:FindFirstChild('lib'):FindFirstChild('utils'):FindFirstChild('str'))",
+        );
+    }
+
+    #[test]
+    fn convert_module_from_nested_file_resolves_against_project_root() {
+        let resources = memory_resources!(
+            "lib/utils/init.lua" => "local value = require('utils.str')",
+            "lib/utils/str.lua" => "return nil",
+            ".darklua.json" => CONVERT_PACKAGE_PATH_TO_ROBLOX_CONFIG,
+        );
+        expect_file_process(
+            &resources,
+            "lib/utils/init.lua",
+            "local value = require(script:FindFirstChild('str'))",
+        );
+    }
+
+    #[test]
+    fn generate_dotted_module_name_from_relative_path() {
+        let resources = memory_resources!(
+            "main.lua" => "local value = require('./lib/utils/str.lua')",
+            "lib/utils/str.lua" => "return nil",
+            ".darklua.json" => CONVERT_PATH_TO_PACKAGE_PATH_CONFIG,
+        );
+        expect_file_process(&resources, "main.lua", "local value = require('utils.str')");
+    }
+
+    #[test]
+    fn generate_module_name_from_init_template() {
+        let resources = memory_resources!(
+            "main.lua" => "local value = require('./pkg/init.lua')",
+            "pkg/init.lua" => "return nil",
+            ".darklua.json" => CONVERT_PATH_TO_PACKAGE_PATH_CONFIG,
+        );
+        expect_file_process(&resources, "main.lua", "local value = require('pkg')");
+    }
+
+    #[test]
+    fn round_trip_module_from_second_template() {
+        let resources = memory_resources!(
+            "main.lua" => "local value = require('utils.str')",
+            "lib/utils/str.lua" => "return nil",
+            ".darklua.json" => CONVERT_PACKAGE_PATH_TO_PACKAGE_PATH_CONFIG,
+        );
+        expect_file_process(&resources, "main.lua", "local value = require('utils.str')");
+    }
+
+    #[test]
+    fn round_trip_module_shadowed_by_first_template() {
+        let resources = memory_resources!(
+            "main.lua" => "local value = require('lib.utils.str')",
+            "utils/str.lua" => "return nil",
+            "lib/utils/str.lua" => "return nil",
+            ".darklua.json" => CONVERT_PACKAGE_PATH_TO_PACKAGE_PATH_CONFIG,
+        );
+        expect_file_process(
+            &resources,
+            "main.lua",
+            "local value = require('lib.utils.str')",
+        );
+    }
+}
+
 mod sourcemap {
     use super::*;
 
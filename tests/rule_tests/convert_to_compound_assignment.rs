@@ -0,0 +1,55 @@
+use darklua_core::rules::{ConvertToCompoundAssignment, Rule};
+
+test_rule!(
+    convert_to_compound_assignment,
+    ConvertToCompoundAssignment::default(),
+    addition("x = x + 1") => "x += 1",
+    subtraction("x = x - 1") => "x -= 1",
+    multiplication("x = x * 2") => "x *= 2",
+    division("x = x / 2") => "x /= 2",
+    floor_division("x = x // 2") => "x //= 2",
+    modulo("x = x % 2") => "x %= 2",
+    exponentiation("x = x ^ 2") => "x ^= 2",
+    concatenation("x = x .. 'suffix'") => "x ..= 'suffix'",
+    field("t.a = t.a .. e") => "t.a ..= e",
+    nested_fields("self.stats.count = self.stats.count + 1") => "self.stats.count += 1",
+    index_with_identifier("t[k] = t[k] * e") => "t[k] *= e",
+    index_with_string("t['key'] = t['key'] + 1") => "t['key'] += 1",
+    index_with_number("t[1] = t[1] - 1") => "t[1] -= 1",
+    index_in_prefix("t[k].count = t[k].count + 1") => "t[k].count += 1",
+    right_side_with_lower_precedence("x = x + a * b") => "x += a * b",
+    right_associative_concatenation("x = x .. a .. b") => "x ..= a .. b",
+    nested_in_function("local function f() n = n + 1 end") => "local function f() n += 1 end",
+);
+
+test_rule_without_effects!(
+    ConvertToCompoundAssignment::default(),
+    different_variable("x = y + 1"),
+    different_field("t.a = t.b + 1"),
+    different_prefix("t.a = u.a + 1"),
+    different_index("t[i] = t[j] + 1"),
+    variable_on_the_right("x = 1 + x"),
+    left_associative_chain("x = x + a + b"),
+    index_with_call("t[f()] = t[f()] + 1"),
+    prefix_with_call("f().a = f().a + 1"),
+    index_with_expression("t[i + 1] = t[i + 1] + 1"),
+    parenthese_prefix("(t).a = (t).a + 1"),
+    comparison("x = x == 1"),
+    logical_operator("x = x or 1"),
+    multiple_assignment("x, y = x + 1, y + 1"),
+);
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'convert_to_compound_assignment',
+    }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'convert_to_compound_assignment'").unwrap();
+}
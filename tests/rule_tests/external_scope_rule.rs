@@ -0,0 +1,142 @@
+//! An example of a rule written outside of darklua that uses the scope tracking processor.
+
+use darklua_core::nodes::{Block, Expression, Identifier, LocalFunctionStatement};
+use darklua_core::process::{IdentifierTracker, NodeProcessor, NodeVisitor, Scope, ScopeVisitor};
+use darklua_core::rules::{
+    Context, FlawlessRule, Rule, RuleConfiguration, RuleConfigurationError, RuleProperties,
+};
+
+/// Renames the local variables declared at the top level of a file, and every reference
+/// to them that is not shadowed by another local variable.
+#[derive(Debug)]
+struct RenameTopLevelVariable {
+    name: &'static str,
+    new_name: &'static str,
+}
+
+impl RenameTopLevelVariable {
+    fn new(name: &'static str, new_name: &'static str) -> Self {
+        Self { name, new_name }
+    }
+}
+
+struct Processor<'a> {
+    rule: &'a RenameTopLevelVariable,
+    identifiers: IdentifierTracker,
+    declared: bool,
+}
+
+impl Processor<'_> {
+    fn rename_declaration(&mut self, identifier: &mut String) {
+        if identifier.as_str() == self.rule.name && self.identifiers.scope_depth() == 1 {
+            assert!(self.identifiers.is_identifier_free(self.rule.new_name));
+            *identifier = self.rule.new_name.to_owned();
+            self.declared = true;
+        }
+    }
+}
+
+impl Scope for Processor<'_> {
+    fn push(&mut self) {
+        self.identifiers.push();
+    }
+
+    fn pop(&mut self) {
+        self.identifiers.pop();
+    }
+
+    fn insert(&mut self, identifier: &mut String) {
+        self.identifiers.insert(identifier);
+    }
+
+    fn insert_self(&mut self) {
+        self.identifiers.insert_self();
+    }
+
+    fn insert_local(&mut self, identifier: &mut String, value: Option<&mut Expression>) {
+        self.rename_declaration(identifier);
+        self.identifiers.insert_local(identifier, value);
+    }
+
+    fn insert_local_function(&mut self, function: &mut LocalFunctionStatement) {
+        self.rename_declaration(function.mutate_identifier().mutate_name());
+        self.identifiers.insert_local_function(function);
+    }
+}
+
+impl NodeProcessor for Processor<'_> {
+    fn process_variable_expression(&mut self, identifier: &mut Identifier) {
+        if self.declared
+            && identifier.get_name() == self.rule.name
+            && !self.identifiers.is_identifier_used(self.rule.name)
+        {
+            identifier.set_name(self.rule.new_name);
+        }
+    }
+}
+
+impl FlawlessRule for RenameTopLevelVariable {
+    fn flawless_process(&self, block: &mut Block, _: &Context) {
+        let mut processor = Processor {
+            rule: self,
+            identifiers: IdentifierTracker::new(),
+            declared: false,
+        };
+        ScopeVisitor::visit_block(block, &mut processor);
+    }
+}
+
+impl RuleConfiguration for RenameTopLevelVariable {
+    fn configure(&mut self, _: RuleProperties) -> Result<(), RuleConfigurationError> {
+        Ok(())
+    }
+
+    fn get_name(&self) -> &'static str {
+        "rename_top_level_variable"
+    }
+
+    fn serialize_to_properties(&self) -> RuleProperties {
+        RuleProperties::new()
+    }
+}
+
+test_rule!(
+    rename_top_level_variable,
+    RenameTopLevelVariable::new("value", "renamed"),
+    local_assign("local value = 1 print(value)") => "local renamed = 1 print(renamed)",
+    local_function("local function value() end value()") => "local function renamed() end renamed()",
+    global_before_declaration("print(value) local value = 1 print(value)")
+        => "print(value) local renamed = 1 print(renamed)",
+    assignment("local value value = 1") => "local renamed renamed = 1",
+    nested_local_is_not_renamed("local value = 1 do local value = 2 print(value) end print(value)")
+        => "local renamed = 1 do local value = 2 print(value) end print(renamed)",
+    nested_local_value_sees_outer_variable("local value = 1 do local value = value + 1 end")
+        => "local renamed = 1 do local value = renamed + 1 end",
+    function_parameter("local value = 1 local function f(value) return value end return value")
+        => "local renamed = 1 local function f(value) return value end return renamed",
+    captured_variable("local value = 0 local function increment() value += 1 end")
+        => "local renamed = 0 local function increment() renamed += 1 end",
+    numeric_for_control_variable("local value = 1 for value = value, 10 do print(value) end")
+        => "local renamed = 1 for value = renamed, 10 do print(value) end",
+    generic_for_variable("local value = {} for _, value in ipairs(value) do print(value) end")
+        => "local renamed = {} for _, value in ipairs(renamed) do print(value) end",
+    repeat_condition_sees_body_local("local value = 1 repeat local value = 2 until value")
+        => "local renamed = 1 repeat local value = 2 until value",
+    repeat_condition_without_shadowing("local value = 1 repeat value -= 1 until value == 0")
+        => "local renamed = 1 repeat renamed -= 1 until renamed == 0",
+);
+
+test_rule!(
+    rename_top_level_self,
+    RenameTopLevelVariable::new("self", "module"),
+    method_shadows_self(
+        "local self = {} function self:method() return self end function self.call() return self end"
+    ) => "local module = {} function module:method() return self end function module.call() return module end",
+);
+
+test_rule_without_effects!(
+    RenameTopLevelVariable::new("value", "renamed"),
+    global_variable("print(value)"),
+    nested_declaration("do local value = 1 print(value) end"),
+    function_statement("function value() end"),
+);
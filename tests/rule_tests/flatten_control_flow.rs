@@ -0,0 +1,235 @@
+use darklua_core::{
+    generator::{LuaGenerator, ReadableLuaGenerator},
+    rules::{ContextBuilder, FlattenControlFlow, Rule},
+    Parser, Resources,
+};
+
+test_rule!(
+    flatten_control_flow,
+    FlattenControlFlow::default(),
+    local_function_with_return(
+        "local function f(a) local b = a + 1 print(b) return b end"
+    ) => r#"
+local function f(a)
+    local b
+    local __DARKLUA_FLATTEN_STATE = 1
+    while __DARKLUA_FLATTEN_STATE ~= 0 do
+        if __DARKLUA_FLATTEN_STATE == 1 then
+            b = a + 1
+            __DARKLUA_FLATTEN_STATE = 2
+        elseif __DARKLUA_FLATTEN_STATE == 2 then
+            print(b)
+            __DARKLUA_FLATTEN_STATE = 3
+        elseif __DARKLUA_FLATTEN_STATE == 3 then
+            return b
+        end
+    end
+end
+"#,
+    function_statement_without_return(
+        "function Module.run() start() update() stop() end"
+    ) => r#"
+function Module.run()
+    local __DARKLUA_FLATTEN_STATE = 1
+    while __DARKLUA_FLATTEN_STATE ~= 0 do
+        if __DARKLUA_FLATTEN_STATE == 1 then
+            start()
+            __DARKLUA_FLATTEN_STATE = 2
+        elseif __DARKLUA_FLATTEN_STATE == 2 then
+            update()
+            __DARKLUA_FLATTEN_STATE = 3
+        elseif __DARKLUA_FLATTEN_STATE == 3 then
+            stop()
+            __DARKLUA_FLATTEN_STATE = 0
+        end
+    end
+end
+"#,
+    function_expression(
+        "return function(...) local a, b = ... print(a) return b end"
+    ) => r#"
+return function(...)
+    local a, b
+    local __DARKLUA_FLATTEN_STATE = 1
+    while __DARKLUA_FLATTEN_STATE ~= 0 do
+        if __DARKLUA_FLATTEN_STATE == 1 then
+            a, b = ...
+            __DARKLUA_FLATTEN_STATE = 2
+        elseif __DARKLUA_FLATTEN_STATE == 2 then
+            print(a)
+            __DARKLUA_FLATTEN_STATE = 3
+        elseif __DARKLUA_FLATTEN_STATE == 3 then
+            return b
+        end
+    end
+end
+"#,
+    hoist_local_function(
+        "local function f() local function g(n) return n end local x = g(1) return x end"
+    ) => r#"
+local function f()
+    local g, x
+    local __DARKLUA_FLATTEN_STATE = 1
+    while __DARKLUA_FLATTEN_STATE ~= 0 do
+        if __DARKLUA_FLATTEN_STATE == 1 then
+            g = function(n) return n end
+            __DARKLUA_FLATTEN_STATE = 2
+        elseif __DARKLUA_FLATTEN_STATE == 2 then
+            x = g(1)
+            __DARKLUA_FLATTEN_STATE = 3
+        elseif __DARKLUA_FLATTEN_STATE == 3 then
+            return x
+        end
+    end
+end
+"#,
+    hoist_local_without_value(
+        "local function f() local x if check() then x = 1 end return x end"
+    ) => r#"
+local function f()
+    local x
+    local __DARKLUA_FLATTEN_STATE = 1
+    while __DARKLUA_FLATTEN_STATE ~= 0 do
+        if __DARKLUA_FLATTEN_STATE == 1 then
+            if check() then x = 1 end
+            __DARKLUA_FLATTEN_STATE = 2
+        elseif __DARKLUA_FLATTEN_STATE == 2 then
+            return x
+        end
+    end
+end
+"#,
+    return_and_break_inside_segments(
+        "local function find(list) for i, v in list do if v then break end end if #list == 0 then return nil end return list[1] end"
+    ) => r#"
+local function find(list)
+    local __DARKLUA_FLATTEN_STATE = 1
+    while __DARKLUA_FLATTEN_STATE ~= 0 do
+        if __DARKLUA_FLATTEN_STATE == 1 then
+            for i, v in list do if v then break end end
+            __DARKLUA_FLATTEN_STATE = 2
+        elseif __DARKLUA_FLATTEN_STATE == 2 then
+            if #list == 0 then return nil end
+            __DARKLUA_FLATTEN_STATE = 3
+        elseif __DARKLUA_FLATTEN_STATE == 3 then
+            return list[1]
+        end
+    end
+end
+"#,
+);
+
+test_rule!(
+    flatten_control_flow_with_minimum_statements,
+    FlattenControlFlow::default().with_minimum_statements(2),
+    two_statements("local function f() a() b() end") => r#"
+local function f()
+    local __DARKLUA_FLATTEN_STATE = 1
+    while __DARKLUA_FLATTEN_STATE ~= 0 do
+        if __DARKLUA_FLATTEN_STATE == 1 then
+            a()
+            __DARKLUA_FLATTEN_STATE = 2
+        elseif __DARKLUA_FLATTEN_STATE == 2 then
+            b()
+            __DARKLUA_FLATTEN_STATE = 0
+        end
+    end
+end
+"#,
+);
+
+test_rule_without_effects!(
+    FlattenControlFlow::default(),
+    top_level_statements("a() b() c()"),
+    function_below_minimum_statements("local function f() a() return b() end"),
+    redeclared_local("local function f() local x = 1 print(x) local x = 2 print(x) end"),
+    local_used_before_declaration("local function f() print(x) local x = 1 print(x) end"),
+    local_used_in_closure_before_declaration(
+        "local function f() local function g() return h() end local function h() return 1 end return g() end"
+    ),
+    local_used_in_own_value("local function f(x) local x = x or 1 print(x) return x end"),
+    function_with_label("local function f() a() ::skip:: b() c() end"),
+    function_with_goto("local function f() a() b() goto done end"),
+    function_with_type_declaration("local function f() type T = number local x: T = 1 print(x) end"),
+    already_flattened(
+        "local function f() local x local __DARKLUA_FLATTEN_STATE = 1 while __DARKLUA_FLATTEN_STATE ~= 0 do if __DARKLUA_FLATTEN_STATE == 1 then x = a() __DARKLUA_FLATTEN_STATE = 0 end end end"
+    ),
+);
+
+fn flatten(code: &str, rule: &FlattenControlFlow) -> String {
+    let resources = Resources::from_memory();
+    let parser = Parser::default();
+    let mut block = parser.parse(code).expect("unable to parse code");
+
+    let context = ContextBuilder::new("test.lua", &resources, code).build();
+    rule.process(&mut block, &context)
+        .expect("rule should succeed");
+
+    let mut generator = ReadableLuaGenerator::default();
+    generator.write_block(&block);
+    let output = generator.into_string();
+
+    parser
+        .parse(&output)
+        .unwrap_or_else(|error| panic!("unable to parse flattened code: {:?}\n{}", error, output));
+
+    output
+}
+
+const CODE: &str = "local function f(a) local b = a + 1 print(b) if b > 2 then return b end local c = b * 2 print(c) return c end";
+
+#[test]
+fn same_seed_produces_same_output() {
+    let rule = FlattenControlFlow::default().with_seed(42);
+
+    pretty_assertions::assert_eq!(flatten(CODE, &rule), flatten(CODE, &rule));
+}
+
+#[test]
+fn seeded_output_has_one_branch_per_statement() {
+    let output = flatten(CODE, &FlattenControlFlow::default().with_seed(42));
+
+    assert_eq!(output.matches("__DARKLUA_FLATTEN_STATE ==").count(), 6);
+    assert_eq!(
+        output.matches("while __DARKLUA_FLATTEN_STATE ~= 0").count(),
+        1
+    );
+    assert_eq!(output.matches("local b, c").count(), 1);
+}
+
+#[test]
+fn different_seeds_produce_different_states() {
+    let output = flatten(CODE, &FlattenControlFlow::default().with_seed(1));
+    let other_output = flatten(CODE, &FlattenControlFlow::default().with_seed(2));
+
+    assert_ne!(output, other_output);
+}
+
+#[test]
+fn flattening_twice_does_not_change_the_dispatcher() {
+    for rule in [
+        FlattenControlFlow::default(),
+        FlattenControlFlow::default().with_seed(7),
+    ] {
+        let output = flatten(CODE, &rule);
+
+        pretty_assertions::assert_eq!(flatten(&output, &rule), output);
+    }
+}
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'flatten_control_flow',
+        minimum_statements: 5,
+        seed: 42,
+    }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'flatten_control_flow'").unwrap();
+}
@@ -0,0 +1,60 @@
+use darklua_core::rules::{HoistRequires, Rule};
+
+test_rule!(
+    hoist_requires,
+    HoistRequires::default(),
+    require_after_unrelated_code("local value = 1 local Util = require('util') return Util.get(value)")
+        => "local Util = require('util') local value = 1 return Util.get(value)",
+    requires_keep_their_relative_order("print('a') local A = require('a') print('b') local B = require('b')")
+        => "local A = require('a') local B = require('b') print('a') print('b')",
+    require_with_string_call("print('a') local A = require 'a'")
+        => "local A = require 'a' print('a')",
+    require_with_constant_concatenation("print('a') local A = require('packages/' .. 'a')")
+        => "local A = require('packages/' .. 'a') print('a')",
+    require_moved_after_requires_at_the_top("local A = require('a') print(A) local B = require('b')")
+        => "local A = require('a') local B = require('b') print(A)",
+);
+
+test_rule_without_effects!(
+    HoistRequires::default(),
+    requires_already_at_the_top("local A = require('a') local B = require('b') return A, B"),
+    require_referencing_a_local("local name = 'util' local Util = require(name)"),
+    require_referencing_a_global("print('a') local Util = require(script.Parent.Util)"),
+    require_variable_used_before("print(Util) local Util = require('util')"),
+    require_variable_declared_before("local Util print('a') local Util = require('util')"),
+    require_variable_used_in_function_before(
+        "local function get() return Util end local Util = require('util')"
+    ),
+    require_function_declared_before(
+        "local require = load print('a') local Util = require('util')"
+    ),
+    require_function_assigned_before("require = load print('a') local Util = require('util')"),
+    require_after_unhoisted_require(
+        "local name = 'a' local A = require(name) local B = require('b')"
+    ),
+    require_after_function_using_require(
+        "local function load() return require('a') end local B = require('b')"
+    ),
+    require_inside_function(
+        "local value = 1 local function load() local Util = require('util') end"
+    ),
+    require_inside_conditional("local value = 1 if value then local Util = require('util') end"),
+    require_with_multiple_variables("local value = 1 local A, B = require('a'), require('b')"),
+    require_with_type("local value = 1 local Util: any = require('util')"),
+    require_method_call("local value = 1 local Util = require:get('util')"),
+);
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'hoist_requires',
+    }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'hoist_requires'").unwrap();
+}
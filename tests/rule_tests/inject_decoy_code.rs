@@ -0,0 +1,296 @@
+use darklua_core::{
+    generator::{DenseLuaGenerator, LuaGenerator},
+    nodes::{Block, Expression, LocalAssignStatement, Statement},
+    process::{DefaultVisitor, NodeProcessor, NodeVisitor},
+    rules::{ComputeExpression, ContextBuilder, InjectDecoyCode, Rule},
+    Parser, Resources,
+};
+
+const DECOY_PREFIX: &str = "__DARKLUA_DECOY";
+
+test_rule_without_effects!(
+    InjectDecoyCode::default().with_density(0),
+    zero_density("local a = 1 print(a) return a"),
+);
+
+test_rule_without_effects!(
+    InjectDecoyCode::default().with_density(100),
+    block_with_label("::start:: print('hello') goto start"),
+    empty_file(""),
+);
+
+test_rule_without_effects!(
+    InjectDecoyCode::default()
+        .with_density(100)
+        .with_maximum_growth(0),
+    no_growth_allowed("local a = 1 print(a) return a"),
+);
+
+const CODE: &str = r#"
+local Module = {}
+
+function Module.sum(list)
+    local total = 0
+    for _, value in ipairs(list) do
+        if value > 0 then
+            total = total + value
+        end
+    end
+    return total
+end
+
+local function greet(name)
+    print('hello ' .. name)
+    return #name
+end
+
+while Module.running do
+    Module.update()
+end
+
+return Module
+"#;
+
+fn generate(block: &Block) -> String {
+    let mut generator = DenseLuaGenerator::default();
+    generator.write_block(block);
+    generator.into_string()
+}
+
+fn inject(code: &str, rule: &InjectDecoyCode) -> String {
+    let resources = Resources::from_memory();
+    let parser = Parser::default();
+    let mut block = parser.parse(code).expect("unable to parse code");
+
+    let context = ContextBuilder::new("test.lua", &resources, code).build();
+    rule.process(&mut block, &context)
+        .expect("rule should succeed");
+
+    let output = generate(&block);
+
+    parser.parse(&output).unwrap_or_else(|error| {
+        panic!("unable to parse code with decoys: {:?}\n{}", error, output)
+    });
+
+    output
+}
+
+fn is_decoy_identifier(expression: &Expression) -> bool {
+    matches!(expression, Expression::Identifier(identifier) if identifier.get_name().starts_with(DECOY_PREFIX))
+}
+
+fn is_decoy(statement: &Statement) -> bool {
+    match statement {
+        Statement::LocalAssign(assign) => assign
+            .iter_variables()
+            .any(|variable| variable.get_name().starts_with(DECOY_PREFIX)),
+        Statement::LocalFunction(function) => function.get_name().starts_with(DECOY_PREFIX),
+        Statement::If(if_statement) => if_statement.get_branches().iter().any(|branch| {
+            matches!(branch.get_condition(), Expression::Binary(binary) if is_decoy_identifier(binary.left()))
+        }),
+        _ => false,
+    }
+}
+
+struct DecoyRemover;
+
+impl NodeProcessor for DecoyRemover {
+    fn process_block(&mut self, block: &mut Block) {
+        block.filter_statements(|statement| !is_decoy(statement));
+    }
+}
+
+fn remove_decoys(code: &str) -> String {
+    let mut block = Parser::default().parse(code).expect("unable to parse code");
+    DefaultVisitor::visit_block(&mut block, &mut DecoyRemover);
+    generate(&block)
+}
+
+#[test]
+fn same_seed_produces_same_output() {
+    let rule = InjectDecoyCode::default().with_seed(42).with_density(50);
+
+    pretty_assertions::assert_eq!(inject(CODE, &rule), inject(CODE, &rule));
+}
+
+#[test]
+fn different_seeds_produce_different_output() {
+    let output = inject(
+        CODE,
+        &InjectDecoyCode::default().with_seed(1).with_density(50),
+    );
+    let other_output = inject(
+        CODE,
+        &InjectDecoyCode::default().with_seed(2).with_density(50),
+    );
+
+    assert_ne!(output, other_output);
+}
+
+#[test]
+fn inserts_decoys() {
+    let output = inject(CODE, &InjectDecoyCode::default().with_density(100));
+
+    assert!(
+        output.contains(DECOY_PREFIX),
+        "no decoy inserted:\n{}",
+        output
+    );
+}
+
+#[test]
+fn removing_decoys_yields_original_program() {
+    let original = remove_decoys(CODE);
+
+    for seed in 0..10 {
+        let rule = InjectDecoyCode::default()
+            .with_seed(seed)
+            .with_density(100)
+            .with_maximum_growth(200);
+        let output = inject(CODE, &rule);
+
+        assert_ne!(output, original);
+        pretty_assertions::assert_eq!(remove_decoys(&output), original);
+    }
+}
+
+#[test]
+fn decoys_do_not_use_existing_names() {
+    let code = "local __DARKLUA_DECOY1 = 1 print(__DARKLUA_DECOY1) print(__DARKLUA_DECOY1)";
+    let output = inject(code, &InjectDecoyCode::default().with_density(100));
+
+    let block = Parser::default().parse(&output).unwrap();
+    let declarations = block
+        .iter_statements()
+        .filter(|statement| match statement {
+            Statement::LocalAssign(assign) => assign
+                .iter_variables()
+                .any(|variable| variable.get_name() == "__DARKLUA_DECOY1"),
+            Statement::LocalFunction(function) => function.get_name() == "__DARKLUA_DECOY1",
+            _ => false,
+        })
+        .count();
+
+    assert_eq!(declarations, 1, "decoy name collision:\n{}", output);
+}
+
+#[test]
+fn growth_is_limited() {
+    let code: String = (0..100).map(|i| format!("print({})\n", i)).collect();
+
+    let output = inject(
+        &code,
+        &InjectDecoyCode::default()
+            .with_density(500)
+            .with_maximum_growth(20),
+    );
+
+    let inserted = Parser::default().parse(&output).unwrap().statements_len() - 100;
+
+    assert!(inserted > 0, "no decoy inserted:\n{}", output);
+    assert!(
+        inserted <= 20,
+        "expected at most 20 inserted statements, got {}",
+        inserted
+    );
+}
+
+#[derive(Default)]
+struct ConstantDecoyCounter {
+    constant_decoys: usize,
+}
+
+impl NodeProcessor for ConstantDecoyCounter {
+    fn process_local_assign_statement(&mut self, assign: &mut LocalAssignStatement) {
+        let is_decoy = assign
+            .iter_variables()
+            .any(|variable| variable.get_name().starts_with(DECOY_PREFIX));
+
+        if is_decoy && matches!(assign.iter_values().next(), Some(Expression::Number(_))) {
+            self.constant_decoys += 1;
+        }
+    }
+}
+
+#[test]
+fn decoys_are_not_computed_ahead_of_time() {
+    for seed in 0..10 {
+        let rule = InjectDecoyCode::default()
+            .with_seed(seed)
+            .with_density(100)
+            .with_maximum_growth(200);
+        let output = inject(CODE, &rule);
+
+        let resources = Resources::from_memory();
+        let mut block = Parser::default().parse(&output).unwrap();
+        let context = ContextBuilder::new("test.lua", &resources, &output).build();
+        ComputeExpression::default()
+            .process(&mut block, &context)
+            .expect("rule should succeed");
+
+        let mut counter = ConstantDecoyCounter::default();
+        DefaultVisitor::visit_block(&mut block, &mut counter);
+
+        assert_eq!(
+            counter.constant_decoys,
+            0,
+            "decoys were computed:\n{}",
+            generate(&block)
+        );
+    }
+}
+
+#[test]
+fn decoys_count_existing_locals_of_function() {
+    let locals: Vec<_> = (0..196).map(|i| format!("v{}", i)).collect();
+    let mut code = format!("local {} = nil\n", locals.join(", "));
+    for i in 0..100 {
+        code.push_str(&format!("print({})\n", i));
+    }
+
+    let output = inject(
+        &code,
+        &InjectDecoyCode::default()
+            .with_density(500)
+            .with_maximum_growth(1000),
+    );
+
+    let declarations = Parser::default()
+        .parse(&output)
+        .unwrap()
+        .iter_statements()
+        .filter(|statement| match statement {
+            Statement::LocalAssign(assign) => assign
+                .iter_variables()
+                .any(|variable| variable.get_name().starts_with(DECOY_PREFIX)),
+            Statement::LocalFunction(function) => function.get_name().starts_with(DECOY_PREFIX),
+            _ => false,
+        })
+        .count();
+
+    assert!(declarations > 0, "no decoy inserted:\n{}", output);
+    assert!(
+        declarations <= 4,
+        "expected at most 4 decoys, got {}:\n{}",
+        declarations,
+        output
+    );
+}
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'inject_decoy_code',
+        seed: 7,
+        density: 25,
+        maximum_growth: 100,
+    }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'inject_decoy_code'").unwrap();
+}
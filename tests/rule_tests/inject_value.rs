@@ -1,4 +1,7 @@
-use darklua_core::rules::{InjectGlobalValue, Rule};
+use darklua_core::{
+    rules::{ContextBuilder, InjectGlobalValue, Rule, RuleProcessResult},
+    Parser, Resources,
+};
 
 test_rule!(
     inject_global_nil,
@@ -183,6 +186,93 @@ test_rule!(
     inject_negative_integer("return _G.num") => "return 1E49",
 );
 
+test_rule!(
+    inject_global_as_local,
+    InjectGlobalValue::boolean("DEV", false).as_local(),
+    declare_local_before_usage("if DEV then print('dev') end")
+        => "local DEV = false if DEV then print('dev') end",
+    declare_local_for_upvalue("local function isDev() return DEV end return isDev")
+        => "local DEV = false local function isDev() return DEV end return isDev",
+    declare_local_for_nested_function_usage("return { run = function() return function() return DEV end end }")
+        => "local DEV = false return { run = function() return function() return DEV end end }",
+    does_not_declare_for_global_table_access("return _G.DEV") => "return _G.DEV",
+    does_not_declare_without_usage("return value") => "return value",
+    does_not_declare_when_shadowed("local function f(DEV) return DEV end")
+        => "local function f(DEV) return DEV end",
+    does_not_declare_when_local_in_nested_scope("do local DEV = true print(DEV) end")
+        => "do local DEV = true print(DEV) end",
+);
+
+test_rule!(
+    inject_global_as_local_in_bundle,
+    InjectGlobalValue::boolean("DEV", true).as_local(),
+    declare_once_at_bundle_start(
+        "local M M = {} do function M.a() return DEV end function M.b() return DEV end end return M.a()"
+    ) => "local DEV = true local M M = {} do function M.a() return DEV end function M.b() return DEV end end return M.a()",
+);
+
+test_rule!(
+    inject_global_as_local_in_each_bundled_module,
+    InjectGlobalValue::boolean("DEV", true).as_local().in_each_bundled_module(),
+    declare_in_each_module_using_value(
+        "local M M = {} do function M.a() return DEV end function M.b() return 1 end end return M.a()"
+    ) => "local M M = {} do function M.a() local DEV = true return DEV end function M.b() return 1 end end return M.a()",
+    declare_in_entry_code(
+        "local M M = {} do function M.a() return 1 end end return M.a() or DEV"
+    ) => "local M M = {} do function M.a() return 1 end end local DEV = true return M.a() or DEV",
+    declare_at_start_without_bundle("return DEV") => "local DEV = true return DEV",
+);
+
+fn process_code(rule: &dyn Rule, code: &str) -> RuleProcessResult {
+    let mut block = Parser::default().parse(code).expect("unable to parse code");
+
+    let resources = Resources::from_memory();
+    let context = ContextBuilder::new("src/test.lua", &resources, code).build();
+
+    rule.process(&mut block, &context)
+}
+
+#[test]
+fn inject_as_local_errors_when_local_is_already_declared() {
+    let rule = InjectGlobalValue::boolean("DEV", false).as_local();
+
+    assert!(process_code(&rule, "local DEV = true return DEV").is_err());
+}
+
+#[test]
+fn inject_as_local_errors_when_local_function_is_already_declared() {
+    let rule = InjectGlobalValue::boolean("DEV", false).as_local();
+
+    assert!(process_code(&rule, "local function DEV() end return DEV").is_err());
+}
+
+#[test]
+fn inject_as_local_errors_when_declared_in_bundled_module() {
+    let rule = InjectGlobalValue::boolean("DEV", false)
+        .as_local()
+        .in_each_bundled_module();
+
+    assert!(process_code(
+        &rule,
+        "local M M = {} do function M.a() local DEV = 1 return DEV end end return M.a()"
+    )
+    .is_err());
+}
+
+#[test]
+fn deserialize_from_object_notation_as_local() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'inject_global_value',
+        identifier: 'DEV',
+        value: false,
+        as_local: true,
+        bundle_scope: 'module',
+    }"#,
+    )
+    .unwrap();
+}
+
 #[test]
 fn deserialize_number_value_too_large() {
     let err = json5::from_str::<Box<dyn Rule>>(
@@ -0,0 +1,72 @@
+use darklua_core::rules::{InlineTrivialFunctions, Rule};
+
+test_rule!(
+    inline_trivial_functions,
+    InlineTrivialFunctions::default(),
+    local_function_returning_true("local function isEnabled() return true end local value = isEnabled()")
+        => "local function isEnabled() return true end local value = true",
+    local_function_returning_nil("local function noop() return nil end return noop()")
+        => "local function noop() return nil end return nil",
+    local_function_returning_negative_number("local function min() return -1 end print(min())")
+        => "local function min() return -1 end print(-1)",
+    local_function_returning_string("local function getName() return 'darklua' end local name = getName()")
+        => "local function getName() return 'darklua' end local name = 'darklua'",
+    call_with_literal_arguments("local function version(a, b) return 2 end local v = version(1, 'two')")
+        => "local function version(a, b) return 2 end local v = 2",
+    call_with_identifier_arguments("local function version(...) return 2 end local a = 1 local v = version(a)")
+        => "local function version(...) return 2 end local a = 1 local v = 2",
+    call_in_nested_function("local function isDebug() return false end local function log() if isDebug() then print('debug') end end")
+        => "local function isDebug() return false end local function log() if false then print('debug') end end",
+    call_used_as_prefix("local function getName() return 'name' end local upper = getName():upper()")
+        => "local function getName() return 'name' end local upper = ('name'):upper()",
+    module_function("local Module = {} function Module.getVersion() return 3 end local version = Module.getVersion()")
+        => "local Module = {} function Module.getVersion() return 3 end local version = 3",
+    nested_module_function("local Module = { config = {} } function Module.config.isStrict() return true end return Module.config.isStrict()")
+        => "local Module = { config = {} } function Module.config.isStrict() return true end return true",
+    function_read_as_value("local Module = {} function Module.get() return 1 end local get = Module.get local value = Module.get()")
+        => "local Module = {} function Module.get() return 1 end local get = Module.get local value = 1",
+);
+
+test_rule_without_effects!(
+    InlineTrivialFunctions::default(),
+    reassigned_local_function("local function isEnabled() return true end isEnabled = function() return false end local value = isEnabled()"),
+    redefined_module_function("local Module = {} function Module.get() return 1 end function Module.get() return 2 end local value = Module.get()"),
+    reassigned_module_function("local Module = {} function Module.get() return 1 end Module.get = nil local value = Module.get()"),
+    reassigned_module_table("local Module = {} function Module.get() return 1 end Module = other local value = Module.get()"),
+    module_table_written_with_index("local Module = {} function Module.get() return 1 end Module[key] = nil local value = Module.get()"),
+    call_with_side_effect_argument("local function version(a) return 2 end local v = version(compute())"),
+    call_with_field_argument("local function version(a) return 2 end local v = version(t.field)"),
+    method_call("local Module = {} function Module.get() return 1 end local value = Module:get()"),
+    method_definition("local Module = {} function Module:get() return 1 end local value = Module.get()"),
+    call_through_variable("local function isEnabled() return true end local alias = isEnabled local value = alias()"),
+    call_before_definition("local value = isEnabled() local function isEnabled() return true end"),
+    shadowed_by_parameter("local function isEnabled() return true end local function check(isEnabled) return isEnabled() end"),
+    shadowed_by_local("local function isEnabled() return true end do local isEnabled = check return isEnabled() end"),
+    global_function("function isEnabled() return true end local value = isEnabled()"),
+    global_module_function("function Module.get() return 1 end local value = Module.get()"),
+    function_returning_identifier("local function get() return value end local result = get()"),
+    function_returning_multiple_values("local function get() return 1, 2 end local a, b = get()"),
+    function_with_statements("local function get() print('get') return 1 end local result = get()"),
+    nested_local_function("do local function get() return 1 end local result = get() end"),
+    call_statement("local function noop() return nil end noop()"),
+    module_table_aliased("local Config = {} function Config.get() return 1 end local C = Config C.get = nil local value = Config.get()"),
+    module_table_passed_as_argument("local Config = {} function Config.get() return 1 end mutate(Config) local value = Config.get()"),
+    module_table_returned("local Config = {} function Config.get() return 1 end local function getConfig() return Config end local c = getConfig() c.get = nil local value = Config.get()"),
+    module_table_passed_to_method("local Config = {} function Config.get() return 1 end function Config:reset() self.get = nil end Config:reset() local value = Config.get()"),
+    nested_module_table_aliased("local Module = { config = {} } function Module.config.isStrict() return true end local config = Module.config config.isStrict = nil return Module.config.isStrict()"),
+);
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'inline_trivial_functions',
+    }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'inline_trivial_functions'").unwrap();
+}
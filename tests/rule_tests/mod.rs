@@ -51,12 +51,9 @@ macro_rules! test_rule_with_generator {
             if $compare_with_tokens {
                 pretty_assertions::assert_eq!($output, lua_code,);
             } else {
-                pretty_assertions::assert_eq!(
-                    $crate::utils::parse_input(&lua_code),
-                    expect_block,
-                    "\nexpected code:\n{}\nbut received:\n{}",
-                    $output,
-                    lua_code
+                $crate::utils::assert_blocks_eq(
+                    &$crate::utils::parse_input(&lua_code),
+                    &expect_block,
                 );
             }
         }
@@ -311,33 +308,60 @@ macro_rules! test_rule {
 
 macro_rules! test_rule_without_effects {
     ($rule:expr, $($name:ident ($input:literal)),* $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                use darklua_core::rules::Rule;
+
+                let mut block = $crate::utils::parse_input($input);
+                let expect_block = block.clone();
+                let resources = darklua_core::Resources::from_memory();
+                let context = darklua_core::rules::ContextBuilder::new(".", &resources, $input).build();
+
+                $rule.process(&mut block, &context)
+                    .expect("rule should succeed");
+
+                $crate::utils::assert_blocks_eq(&block, &expect_block);
+            }
+        )*
+    };
+}
+
+/// Like `test_rule!`, but the input is parsed with its tokens and the result is compared
+/// to the expected code using `Block::eq_ignore_tokens`, so rules do not have to produce
+/// the exact same tokens as the expected code.
+macro_rules! test_rule_no_tokens {
+    ($rule:expr, $($name:ident ($input:literal) => $output:literal),* $(,)?) => {
         $(
             #[test]
             fn $name() {
                 use darklua_core::{
                     rules::Rule,
-                    generator::{LuaGenerator, TokenBasedLuaGenerator},
+                    generator::{LuaGenerator, ReadableLuaGenerator},
                 };
 
-                let mut block = $crate::utils::parse_input($input);
-                let expect_block = block.clone();
+                let parser = darklua_core::Parser::default().preserve_tokens();
+                let mut block = parser.parse($input).unwrap_or_else(|error| {
+                    panic!("could not parse content: {:?}\ncontent:\n{}", error, $input)
+                });
+                let expect_block = parser.parse($output).expect("unable to parse expected code");
+
                 let resources = darklua_core::Resources::from_memory();
                 let context = darklua_core::rules::ContextBuilder::new(".", &resources, $input).build();
 
                 $rule.process(&mut block, &context)
                     .expect("rule should succeed");
 
-                let mut generator = TokenBasedLuaGenerator::new($input);
-                generator.write_block(&block);
-                let lua_code = generator.into_string();
+                if !block.eq_ignore_tokens(&expect_block) {
+                    let mut generator = ReadableLuaGenerator::default();
+                    generator.write_block(&block);
 
-                pretty_assertions::assert_eq!(
-                    block,
-                    expect_block,
-                    "\nexpected code:\n{}\nbut received:\n{}",
-                    $input,
-                    lua_code,
-                );
+                    panic!(
+                        "\nexpected code:\n{}\nbut received:\n{}",
+                        $output,
+                        generator.into_string(),
+                    );
+                }
             }
         )*
     };
@@ -415,15 +439,62 @@ macro_rules! test_rule_snapshot {
     };
 }
 
+/// Tests a rule that reads other files, using the in-memory files given inline. The files
+/// and paths are used to build a `RuleTestContext` for every test case, and the processed
+/// code is compared with the expected code.
+macro_rules! test_rule_with_context {
+    (
+        $rule_name:ident,
+        $rule:expr,
+        current_path = $current_path:literal,
+        $( project_location = $project_location:literal, )?
+        files = { $( $file_path:literal => $file_content:expr ),* $(,)? },
+        $($name:ident ($input:literal) => $output:literal),* $(,)?
+    ) => {
+        mod $rule_name {
+            use super::*;
+
+            fn create_context() -> darklua_core::test_utils::RuleTestContext {
+                darklua_core::test_utils::RuleTestContext::new($current_path)
+                    $( .with_project_location($project_location) )?
+                    $( .with_file($file_path, $file_content) )*
+            }
+
+        $(
+            #[test]
+            fn $name() {
+                let block = create_context()
+                    .process(&$rule, $input)
+                    .expect("rule should succeed");
+
+                $crate::utils::assert_blocks_eq(&block, &$crate::utils::parse_input($output));
+            }
+        )*
+
+        }
+    };
+}
+
 mod append_text_comment;
+mod cache_field_access;
+mod cache_services;
 mod compute_expression;
 mod convert_index_to_field;
 mod convert_require;
+mod convert_to_compound_assignment;
+mod external_scope_rule;
 mod filter_early_return;
+mod flatten_control_flow;
 mod group_local_assignment;
+mod hoist_requires;
+mod inject_decoy_code;
 mod inject_value;
+mod inline_trivial_functions;
 mod no_local_function;
+mod normalize_unpack;
+mod pool_strings;
 mod remove_assertions;
+mod remove_attributes;
 mod remove_call_parens;
 mod remove_comments;
 mod remove_compound_assignment;
@@ -431,12 +502,49 @@ mod remove_continue;
 mod remove_debug_profiling;
 mod remove_empty_do;
 mod remove_floor_division;
+mod remove_goto;
 mod remove_if_expression;
 mod remove_interpolated_string;
 mod remove_method_definition;
 mod remove_nil_declaration;
+mod remove_test_blocks;
+mod remove_trailing_nil_arguments;
+mod remove_type_assertions;
+mod remove_type_export;
 mod remove_types;
 mod remove_unused_if_branch;
 mod remove_unused_variable;
 mod remove_unused_while;
 mod rename_variables;
+mod replace_calls;
+mod simplify_nil_defaults;
+mod simplify_stdlib_idioms;
+mod simplify_string_format;
+mod split_large_constructs;
+mod unroll_loops;
+mod wrap_in_pcall;
+
+#[test]
+fn assert_blocks_eq_shows_generated_code() {
+    let received = crate::utils::parse_input("local a = 1 return a");
+    let expected = crate::utils::parse_input("local a = 2 return a");
+
+    let payload = std::panic::catch_unwind(|| {
+        crate::utils::assert_blocks_eq(&received, &expected);
+    })
+    .unwrap_err();
+
+    let message = payload
+        .downcast_ref::<String>()
+        .expect("panic payload should be a string");
+
+    assert!(message.contains("local a = 1"), "message:\n{}", message);
+    assert!(message.contains("local a = 2"), "message:\n{}", message);
+}
+
+#[test]
+fn assert_blocks_eq_passes_on_equal_blocks() {
+    let block = crate::utils::parse_input("return function() end");
+
+    crate::utils::assert_blocks_eq(&block, &block.clone());
+}
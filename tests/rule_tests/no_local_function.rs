@@ -8,12 +8,43 @@ test_rule!(
     empty_variadic_function("local function foo(...) end") => "local foo = function(...) end",
     empty_variadic_function_with_arguments("local function foo(a, b, c, ...) end") => "local foo = function(a, b, c, ...) end",
     function_with_block("local function foo() return true end") => "local foo = function() return true end",
-    name_in_parameters("local function foo(foo) return foo end") => "local foo = function(foo) return foo end"
+    name_in_parameters("local function foo(foo) return foo end") => "local foo = function(foo) return foo end",
+    name_shadowed_by_inner_local("local function foo() local foo = 1 return foo end")
+        => "local foo = function() local foo = 1 return foo end",
+    name_shadowed_by_nested_function_parameter("local function foo() return function(foo) return foo end end")
+        => "local foo = function() return function(foo) return foo end end",
+    name_shadowed_in_nested_function("local function foo() return function() local foo return foo end end")
+        => "local foo = function() return function() local foo return foo end end",
+    nested_local_function("local function foo() local function bar() end return bar end")
+        => "local foo = function() local bar = function() end return bar end",
 );
 
 test_rule_without_effects!(
     ConvertLocalFunctionToAssign::default(),
-    two_local_using_the_other("local function foo() foo() end")
+    two_local_using_the_other("local function foo() foo() end"),
+    direct_recursion(
+        "local function fact(n) if n == 0 then return 1 end return n * fact(n - 1) end"
+    ),
+    recursion_from_nested_closure("local function foo() return function() return foo() end end"),
+    recursion_before_shadowing_local("local function foo() foo() local foo = 1 end"),
+    recursion_from_nested_block("local function foo() do return foo end end"),
+);
+
+test_rule!(
+    convert_local_function_to_assign_forward_declaring_recursive_functions,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'convert_local_function_to_assign',
+        recursive_functions: 'forward_declare',
+    }"#
+    )
+    .unwrap(),
+    direct_recursion("local function fact(n) if n == 0 then return 1 end return n * fact(n - 1) end")
+        => "local fact fact = function(n) if n == 0 then return 1 end return n * fact(n - 1) end",
+    recursion_from_nested_closure("local function foo() return function() return foo() end end")
+        => "local foo foo = function() return function() return foo() end end",
+    non_recursive_function("local function foo() end") => "local foo = function() end",
+    nested_recursive_function("do local function foo() foo() end end") => "do local foo foo = function() foo() end end",
 );
 
 #[test]
@@ -0,0 +1,84 @@
+use darklua_core::rules::{NormalizeUnpack, Rule};
+
+test_rule!(
+    normalize_unpack_compat,
+    NormalizeUnpack::default(),
+    call_global_unpack("return unpack(list)")
+        => "local unpack = table.unpack or unpack return unpack(list)",
+    call_table_unpack("return table.unpack(list)")
+        => "local unpack = table.unpack or unpack return unpack(list)",
+    unpack_as_value("local u = unpack")
+        => "local unpack = table.unpack or unpack local u = unpack",
+    table_unpack_as_value("local u = table.unpack")
+        => "local unpack = table.unpack or unpack local u = unpack",
+);
+
+test_rule_without_effects!(
+    NormalizeUnpack::default(),
+    without_unpack("return select('#', ...)"),
+    shadowed_unpack("local function unpack() end return unpack(list)"),
+    shadowed_table("local table = {} return table.unpack(list)"),
+    table_unpack_with_shadowed_unpack("local unpack return table.unpack(list)"),
+    already_defined_unpack("local unpack = table.unpack or unpack return unpack(list)"),
+);
+
+test_rule!(
+    normalize_unpack_lua51,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'normalize_unpack',
+        target: 'lua51',
+    }"#,
+    )
+    .unwrap(),
+    call_table_unpack("return table.unpack(list)") => "return unpack(list)",
+    call_table_unpack_with_indices("return table.unpack(list, 1, 2)") => "return unpack(list, 1, 2)",
+    table_unpack_as_value("local u = table.unpack") => "local u = unpack",
+    table_unpack_in_table("return { fn = table.unpack }") => "return { fn = unpack }",
+);
+
+test_rule_without_effects!(
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'normalize_unpack',
+        target: 'lua51',
+    }"#,
+    )
+    .unwrap(),
+    call_global_unpack("return unpack(list)"),
+    table_unpack_with_shadowed_unpack("local unpack = nil return table.unpack(list)"),
+    shadowed_table("local table = {} return table.unpack(list)"),
+    assign_table_unpack("table.unpack = unpack"),
+);
+
+test_rule!(
+    normalize_unpack_lua52,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'normalize_unpack',
+        target: 'lua52',
+    }"#,
+    )
+    .unwrap(),
+    call_global_unpack("return unpack(list)") => "return table.unpack(list)",
+    unpack_as_value("local u = unpack") => "local u = table.unpack",
+    unpack_as_argument("return pcall(unpack, list)") => "return pcall(table.unpack, list)",
+    unpack_in_nested_function("local function f(t) return unpack(t) end")
+        => "local function f(t) return table.unpack(t) end",
+    unpack_after_shadowed_scope("do local unpack end return unpack(list)")
+        => "do local unpack end return table.unpack(list)",
+);
+
+test_rule_without_effects!(
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'normalize_unpack',
+        target: 'lua52',
+    }"#,
+    )
+    .unwrap(),
+    call_table_unpack("return table.unpack(list)"),
+    shadowed_unpack("local unpack = function() end return unpack(list)"),
+    shadowed_unpack_parameter("local function f(unpack) return unpack(list) end"),
+    unpack_with_shadowed_table("local table = {} return unpack(list)"),
+);
@@ -0,0 +1,212 @@
+use darklua_core::{
+    generator::{DenseLuaGenerator, LuaGenerator, TokenBasedLuaGenerator},
+    rules::{ContextBuilder, PoolStrings, Rule},
+    Parser, Resources,
+};
+
+const URL: &str = "https://example.com/api/v1/players/event";
+
+test_rule!(
+    pool_strings,
+    PoolStrings::default(),
+    string_in_calls(
+        "print('https://example.com/api/v1/players/event') print('https://example.com/api/v1/players/event') print('https://example.com/api/v1/players/event')"
+    ) => "local __DARKLUA_STR1 = 'https://example.com/api/v1/players/event' print(__DARKLUA_STR1) print(__DARKLUA_STR1) print(__DARKLUA_STR1)",
+    string_call_arguments(
+        "fire 'https://example.com/api/v1/players/event' fire 'https://example.com/api/v1/players/event' fire 'https://example.com/api/v1/players/event'"
+    ) => "local __DARKLUA_STR1 = 'https://example.com/api/v1/players/event' fire(__DARKLUA_STR1) fire(__DARKLUA_STR1) fire(__DARKLUA_STR1)",
+    string_in_table_index_keys(
+        "return { ['https://example.com/api/v1/players/event'] = 1, a = 'https://example.com/api/v1/players/event', b = 'https://example.com/api/v1/players/event' }"
+    ) => "local __DARKLUA_STR1 = 'https://example.com/api/v1/players/event' return { [__DARKLUA_STR1] = 1, a = __DARKLUA_STR1, b = __DARKLUA_STR1 }",
+    avoid_existing_identifier(
+        "local __DARKLUA_STR1 = 1 return 'https://example.com/api/v1/players/event', 'https://example.com/api/v1/players/event', 'https://example.com/api/v1/players/event'"
+    ) => "local __DARKLUA_STR2 = 'https://example.com/api/v1/players/event' local __DARKLUA_STR1 = 1 return __DARKLUA_STR2, __DARKLUA_STR2, __DARKLUA_STR2",
+    avoid_nested_identifier(
+        "local function f(__DARKLUA_STR1) return 'https://example.com/api/v1/players/event' end return 'https://example.com/api/v1/players/event', 'https://example.com/api/v1/players/event'"
+    ) => "local __DARKLUA_STR2 = 'https://example.com/api/v1/players/event' local function f(__DARKLUA_STR1) return __DARKLUA_STR2 end return __DARKLUA_STR2, __DARKLUA_STR2",
+    multiple_strings_in_order(
+        "return 'https://example.com/api/v1/players/event', 'https://example.com/api/v1/players/items', 'https://example.com/api/v1/players/event', 'https://example.com/api/v1/players/items', 'https://example.com/api/v1/players/event', 'https://example.com/api/v1/players/items'"
+    ) => "local __DARKLUA_STR1, __DARKLUA_STR2 = 'https://example.com/api/v1/players/event', 'https://example.com/api/v1/players/items' return __DARKLUA_STR1, __DARKLUA_STR2, __DARKLUA_STR1, __DARKLUA_STR2, __DARKLUA_STR1, __DARKLUA_STR2",
+    keep_require_paths(
+        "local a = require('./modules/players/PlayerEventTriggered') local b = require './modules/players/PlayerEventTriggered' return './modules/players/PlayerEventTriggered', './modules/players/PlayerEventTriggered', './modules/players/PlayerEventTriggered'"
+    ) => "local __DARKLUA_STR1 = './modules/players/PlayerEventTriggered' local a = require('./modules/players/PlayerEventTriggered') local b = require './modules/players/PlayerEventTriggered' return __DARKLUA_STR1, __DARKLUA_STR1, __DARKLUA_STR1",
+);
+
+test_rule_without_effects!(
+    PoolStrings::default(),
+    string_with_two_occurrences("return 'https://example.com/api/v1/players/event', 'https://example.com/api/v1/players/event'"),
+    short_string("return 'event', 'event', 'event', 'event'"),
+    shorthand_table_keys("return { PlayerEventTriggered = 1 }, { PlayerEventTriggered = 2 }, { PlayerEventTriggered = 3 }"),
+    strings_in_require_calls("require('./modules/players/PlayerEventTriggered') require('./modules/players/PlayerEventTriggered') require('./modules/players/PlayerEventTriggered')"),
+);
+
+test_rule!(
+    pool_strings_with_thresholds,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'pool_strings',
+        minimum_occurrences: 2,
+        minimum_length: 4,
+    }"#,
+    )
+    .unwrap(),
+    long_string_repeated_twice(
+        "return 'https://example.com/api/v1/players/event/settings/advanced', 'https://example.com/api/v1/players/event/settings/advanced'"
+    ) => "local __DARKLUA_STR1 = 'https://example.com/api/v1/players/event/settings/advanced' return __DARKLUA_STR1, __DARKLUA_STR1",
+);
+
+test_rule_without_effects!(
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'pool_strings',
+        minimum_occurrences: 2,
+        minimum_length: 4,
+    }"#,
+    )
+    .unwrap(),
+    short_string_not_reducing_size(
+        "return 'event', 'event', 'event', 'event', 'event', 'event', 'event', 'event'"
+    ),
+);
+
+fn generate_dense(block: &darklua_core::nodes::Block, code: &str) -> String {
+    let mut generator = DenseLuaGenerator::default();
+    generator.write_block(block);
+    let output = generator.into_string();
+    assert!(!output.is_empty(), "unable to generate code for:\n{}", code);
+    output
+}
+
+#[test]
+fn pooling_repeated_string_reduces_code_size() {
+    let code: String = (0..10)
+        .map(|i| format!("fire('{}', {})\n", URL, i))
+        .collect();
+
+    let resources = Resources::from_memory();
+    let parser = Parser::default();
+    let mut block = parser.parse(&code).expect("unable to parse code");
+    let original_output = generate_dense(&block, &code);
+
+    let context = ContextBuilder::new("test.lua", &resources, &code).build();
+    PoolStrings::default()
+        .process(&mut block, &context)
+        .expect("rule should succeed");
+
+    let output = generate_dense(&block, &code);
+
+    assert!(
+        output.len() < original_output.len(),
+        "expected pooled code to be shorter than the original:\n{}\n{}",
+        output,
+        original_output
+    );
+    assert_eq!(output.matches(URL).count(), 1);
+    parser
+        .parse(&output)
+        .unwrap_or_else(|error| panic!("unable to parse pooled code: {:?}\n{}", error, output));
+}
+
+#[test]
+fn pooled_strings_are_declared_after_file_header() {
+    let code = format!(
+        "--!strict\n-- the module header\nprint('{0}')\nprint('{0}')\nprint('{0}')\n",
+        URL
+    );
+
+    let resources = Resources::from_memory();
+    let mut block = Parser::default()
+        .preserve_tokens()
+        .parse(&code)
+        .expect("unable to parse code");
+
+    let context = ContextBuilder::new("test.lua", &resources, &code).build();
+    PoolStrings::default()
+        .process(&mut block, &context)
+        .expect("rule should succeed");
+
+    let mut generator = TokenBasedLuaGenerator::new(&code);
+    generator.write_block(&block);
+    let output = generator.into_string();
+
+    assert!(
+        output.starts_with("--!strict\n-- the module header\nlocal __DARKLUA_STR1"),
+        "unexpected output: {}",
+        output
+    );
+}
+
+#[test]
+fn pooled_strings_count_existing_chunk_locals() {
+    let locals: Vec<_> = (0..190).map(|i| format!("v{}", i)).collect();
+    let mut code = format!("local {} = nil\n", locals.join(", "));
+    for i in 0..20 {
+        for _ in 0..3 {
+            code.push_str(&format!("print('{}/{}')\n", URL, i));
+        }
+    }
+
+    let resources = Resources::from_memory();
+    let mut block = Parser::default()
+        .parse(&code)
+        .expect("unable to parse code");
+
+    let context = ContextBuilder::new("test.lua", &resources, &code).build();
+    PoolStrings::default()
+        .process(&mut block, &context)
+        .expect("rule should succeed");
+
+    let output = generate_dense(&block, &code);
+
+    assert!(output.contains("__DARKLUA_STR10"), "{}", output);
+    assert!(!output.contains("__DARKLUA_STR11"), "{}", output);
+}
+
+fn pool_strings(code: &str) -> String {
+    let resources = Resources::from_memory();
+    let mut block = Parser::default().parse(code).expect("unable to parse code");
+
+    let context = ContextBuilder::new("test.lua", &resources, code).build();
+    PoolStrings::default()
+        .process(&mut block, &context)
+        .expect("rule should succeed");
+
+    generate_dense(&block, code)
+}
+
+#[test]
+fn pooled_strings_count_locals_of_nested_blocks() {
+    let locals: Vec<_> = (0..186).map(|i| format!("v{}", i)).collect();
+    let mut code = format!("for i = 1, 10 do local {} = nil end\n", locals.join(", "));
+    for i in 0..20 {
+        for _ in 0..3 {
+            code.push_str(&format!("print('{}/{}')\n", URL, i));
+        }
+    }
+
+    let output = pool_strings(&code);
+
+    assert!(output.contains("__DARKLUA_STR10"), "{}", output);
+    assert!(!output.contains("__DARKLUA_STR11"), "{}", output);
+}
+
+#[test]
+fn pooled_strings_used_in_functions_respect_upvalue_limit() {
+    let upvalues: Vec<_> = (0..55).map(|i| format!("u{}", i)).collect();
+    let mut code = format!(
+        "local {} = nil\nlocal function f()\nprint({})\n",
+        upvalues.join(", "),
+        upvalues.join(", ")
+    );
+    for i in 0..20 {
+        for _ in 0..3 {
+            code.push_str(&format!("print('{}/{}')\n", URL, i));
+        }
+    }
+    code.push_str("end\n");
+
+    let output = pool_strings(&code);
+
+    assert!(output.contains("__DARKLUA_STR5"), "{}", output);
+    assert!(!output.contains("__DARKLUA_STR6"), "{}", output);
+}
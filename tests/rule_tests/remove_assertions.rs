@@ -32,6 +32,35 @@ test_rule!(
     remove_variable_condition_with_function_call_message("assert(condition, formatter(condition))") => "do end",
 );
 
+test_rule!(
+    remove_assertions_preserve_side_effects,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'remove_assertions',
+        preserve_side_effects: true,
+    }"#,
+    )
+    .unwrap(),
+    remove_pure_assertion("assert(isValid, 'value is invalid')") => "do end",
+    remove_pure_assertion_with_table_message("assert(condition, { code = 1 })") => "do end",
+    keep_function_call_condition("assert(doThing(), 'msg')") => "doThing()",
+    keep_method_call_condition("assert(object:validate())") => "object:validate()",
+    keep_function_call_message("assert(condition, format('%s', name))") => "format('%s', name)",
+    keep_condition_and_message("assert(doThing(), format(name))") => "do doThing() format(name) end",
+);
+
+test_rule!(
+    remove_assertions_without_preserved_side_effects,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'remove_assertions',
+        preserve_side_effects: false,
+    }"#,
+    )
+    .unwrap(),
+    remove_function_call_condition("assert(doThing(), 'msg')") => "do end",
+);
+
 test_rule_without_effects!(
     RemoveAssertions::default(),
     assert_function_used("local function assert() end assert('label')"),
@@ -0,0 +1,44 @@
+use darklua_core::rules::{RemoveAttributes, Rule};
+
+test_rule!(
+    remove_attributes,
+    RemoveAttributes::default(),
+    function_statement("@native function foo() end") => "function foo() end",
+    local_function("@native local function foo() end") => "local function foo() end",
+    multiple_attributes("@native @checked local function foo() end") => "local function foo() end",
+    nested_function("local function foo() @native local function bar() end end")
+        => "local function foo() local function bar() end end",
+);
+
+test_rule!(
+    remove_only_checked_attributes,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'remove_attributes',
+        only: ['checked'],
+    }"#,
+    )
+    .unwrap(),
+    keep_native_attribute("@native @checked function foo() end") => "@native function foo() end",
+    remove_checked_attribute("@checked local function foo() end") => "local function foo() end",
+);
+
+test_rule_without_effects!(
+    RemoveAttributes::default(),
+    function_without_attributes("local function foo() end")
+);
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'remove_attributes',
+    }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'remove_attributes'").unwrap();
+}
@@ -44,6 +44,8 @@ test_remove_comments_rule!(
     empty_do("do end -- comment") => "do end ",
     before_empty_do("-- comment\ndo end") => "\ndo end",
     comment_after_semicolon("print('hello');-- bye") => "print('hello');",
+    before_function_attribute("-- comment\n@native function foo() end") => "\n@native function foo() end",
+    after_local_function_attribute("@native -- comment\nlocal function foo() end") => "@native \nlocal function foo() end",
 );
 
 test_remove_comments_rule!(
@@ -70,3 +72,47 @@ fn deserialize_from_object_notation() {
 fn deserialize_from_string() {
     json5::from_str::<Box<dyn Rule>>("'remove_comments'").unwrap();
 }
+
+test_remove_comments_rule!(
+    json5::from_str::<Box<dyn Rule>>(r#"{
+        rule: 'remove_comments',
+        keep: ['selene:', '^--!'],
+    }"#,
+    )
+    .unwrap(),
+    keep_matching_line_comment_before_statement("--!strict\n-- comment\nlocal a = 1")
+        => "--!strict\n\nlocal a = 1",
+    keep_matching_trailing_comment("local a = 1 -- comment\nlocal b = call() -- selene: allow(unused_variable)\nlocal c = 3 -- comment")
+        => "local a = 1 \nlocal b = call() -- selene: allow(unused_variable)\nlocal c = 3 ",
+    keep_matching_block_comment("local a = --[[ selene: allow(shadowing) ]] 1 --[[ comment ]]")
+        => "local a = --[[ selene: allow(shadowing) ]] 1 ",
+    remove_non_matching_license("-- license\nreturn nil") => "\nreturn nil",
+);
+
+test_remove_comments_rule!(
+    json5::from_str::<Box<dyn Rule>>(r#"{
+        rule: 'remove_comments',
+        keep_first_comment: true,
+    }"#,
+    )
+    .unwrap(),
+    keep_license_line_comments("-- Copyright (c) 2024\n-- MIT License\nlocal a = 1 -- comment")
+        => "-- Copyright (c) 2024\n-- MIT License\nlocal a = 1 ",
+    keep_license_block_comment("--[[\n  Copyright (c) 2024\n]]\n\n-- comment\nreturn nil")
+        => "--[[\n  Copyright (c) 2024\n]]\n\n\nreturn nil",
+    keep_license_before_empty_line("-- MIT License\n\n-- comment\nreturn nil")
+        => "-- MIT License\n\n\nreturn nil",
+    remove_comments_after_code("return nil -- comment") => "return nil ",
+);
+
+test_remove_comments_rule!(
+    json5::from_str::<Box<dyn Rule>>(r#"{
+        rule: 'remove_comments',
+        keep_first_comment: true,
+        except: ['^--!'],
+    }"#,
+    )
+    .unwrap(),
+    keep_license_and_matching_comments("-- MIT License\n\n--!native\n-- comment\nlocal a = 1 -- comment")
+        => "-- MIT License\n\n--!native\n\nlocal a = 1 ",
+);
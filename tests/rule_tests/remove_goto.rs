@@ -0,0 +1,152 @@
+use darklua_core::{
+    rules::{ContextBuilder, RemoveGoto, Rule},
+    Parser, Resources,
+};
+
+test_rule!(
+    remove_goto,
+    RemoveGoto::default(),
+    remove_unused_label("::done:: print('a')") => "print('a')",
+    goto_continue_in_numeric_for(
+        "for i = 1, 10 do if i % 2 == 0 then goto continue end print(i) ::continue:: end"
+    ) => "for i = 1, 10 do if i % 2 == 0 then continue end print(i) end",
+    goto_continue_in_while(
+        "while running() do if skip() then goto continue end step() ::continue:: end"
+    ) => "while running() do if skip() then continue end step() end",
+    goto_continue_in_nested_do(
+        "for _, v in list do do print(v) goto continue end ::continue:: end"
+    ) => "for _, v in list do do print(v) continue end end",
+    goto_continue_before_label(
+        "while true do step() goto continue ::continue:: end"
+    ) => "while true do step() end",
+    goto_continue_of_inner_loop(
+        "for i = 1, 3 do for j = 1, 3 do if j == i then goto next end print(i, j) ::next:: end end"
+    ) => "for i = 1, 3 do for j = 1, 3 do if j == i then continue end print(i, j) end end",
+    forward_goto_skips_statements(
+        "do if done then goto exit end print('a') print('b') ::exit:: end"
+    ) => "do if not done then print('a') print('b') end end",
+    forward_goto_with_negated_condition(
+        "if not ready then goto skip end start() ::skip:: finish()"
+    ) => "if ready then start() end finish()",
+    forward_goto_with_statements_in_branch(
+        "if failed then log() goto skip end run() ::skip::"
+    ) => "if failed then log() else run() end",
+    two_forward_gotos_to_same_label(
+        "if a then goto done end print(1) if b then goto done end print(2) ::done:: print(3)"
+    ) => "if not a then print(1) if not b then print(2) end end print(3)",
+    unconditional_forward_goto_removes_unreachable_code(
+        "print(1) goto done print(2) ::done:: print(3)"
+    ) => "print(1) print(3)",
+    forward_goto_in_function(
+        "local function f(a) if a then goto done end print(a) ::done:: end"
+    ) => "local function f(a) if not a then print(a) end end",
+);
+
+test_rule!(
+    remove_goto_with_repeat_strategy,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'remove_goto',
+        strategy: 'repeat',
+    }"#,
+    )
+    .unwrap(),
+    goto_continue_in_numeric_for(
+        "for i = 1, 10 do if i % 2 == 0 then goto continue end print(i) ::continue:: end"
+    ) => "for i = 1, 10 do if not (i % 2 == 0) then print(i) end end",
+    goto_continue_in_nested_if(
+        "for _, v in list do if v then if v.skip then goto continue end print(v) end ::continue:: end"
+    ) => "for _, v in list do repeat if v then if v.skip then break end print(v) end until true end",
+);
+
+test_rule_without_effects!(
+    RemoveGoto::default(),
+    loop_with_continue("for i = 1, 10 do if i == 1 then continue end print(i) end"),
+);
+
+fn process_error(code: &str, rule: &dyn Rule) -> String {
+    let resources = Resources::from_memory();
+    let mut block = Parser::default().parse(code).expect("unable to parse code");
+    let context = ContextBuilder::new("test.lua", &resources, code).build();
+
+    rule.process(&mut block, &context)
+        .expect_err("rule should fail")
+        .to_string()
+}
+
+#[test]
+fn backward_goto_is_an_error() {
+    assert_eq!(
+        process_error(
+            "::top:: if retry() then goto top end",
+            &RemoveGoto::default()
+        ),
+        "unable to remove `goto top`: jumping backward is not supported"
+    );
+}
+
+#[test]
+fn goto_out_of_nested_loop_is_an_error() {
+    assert_eq!(
+        process_error(
+            "for i = 1, 3 do for j = 1, 3 do if j == i then goto continue end end ::continue:: end",
+            &RemoveGoto::default()
+        ),
+        "unable to remove `goto continue`: jumping out of a nested loop is not supported"
+    );
+}
+
+#[test]
+fn goto_into_nested_block_label_is_an_error() {
+    assert_eq!(
+        process_error(
+            "do if a then goto done end end print(1) ::done:: print(2)",
+            &RemoveGoto::default()
+        ),
+        "unable to remove `goto done`: the label must be at the end of a loop or after the goto in the same block"
+    );
+}
+
+#[test]
+fn goto_into_local_scope_is_an_error() {
+    assert_eq!(
+        process_error(
+            "if a then goto done end local b = 1 ::done:: print(b)",
+            &RemoveGoto::default()
+        ),
+        "unable to remove `goto done`: it jumps into the scope of local `b`"
+    );
+}
+
+#[test]
+fn goto_without_label_is_an_error() {
+    assert_eq!(
+        process_error("goto missing", &RemoveGoto::default()),
+        "no visible label `missing` for goto"
+    );
+}
+
+#[test]
+fn goto_to_label_in_outer_function_is_an_error() {
+    assert_eq!(
+        process_error(
+            "local function f() goto done end ::done::",
+            &RemoveGoto::default()
+        ),
+        "no visible label `done` for goto"
+    );
+}
+
+#[test]
+fn repeat_strategy_with_break_in_loop_is_an_error() {
+    let rule =
+        json5::from_str::<Box<dyn Rule>>("{ rule: 'remove_goto', strategy: 'repeat' }").unwrap();
+
+    assert_eq!(
+        process_error(
+            "while true do if a then break end if b then if c then goto continue end end ::continue:: end",
+            rule.as_ref()
+        ),
+        "unable to remove `goto continue`: the loop contains a break statement"
+    );
+}
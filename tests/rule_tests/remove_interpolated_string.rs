@@ -22,6 +22,9 @@ test_rule!(
         => "local __DARKLUA_STR_FMT, __DARKLUA_TO_STR = string.format, tostring local string, tostring return __DARKLUA_STR_FMT('%%%s', __DARKLUA_TO_STR(object))",
     two_strings_with_variable_shadowing_tostring("local tostring local a, b = `{object}`, `{var}`")
     => "local __DARKLUA_TO_STR = tostring local tostring local a, b = __DARKLUA_TO_STR(object), __DARKLUA_TO_STR(var)",
+    constant_number_segment("return `value: {1 + 2}`") => "return 'value: 3'",
+    constant_concat_segment("return `<{'a' .. 'b'}>`") => "return '<ab>'",
+    constant_segment_with_percent("return `{50}%`") => "return '50%'",
 );
 
 test_rule!(
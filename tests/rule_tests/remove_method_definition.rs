@@ -8,9 +8,22 @@ test_rule!(
     name_with_field_and_method("function foo.bar:baz() end") => "function foo.bar.baz(self) end",
     with_arguments("function foo:bar(a, b, c) end") => "function foo.bar(self, a, b, c) end",
     variadic_function("function foo:bar(...) end") => "function foo.bar(self, ...) end",
-    variadic_with_arguments("function foo:bar(a, b, c, ...) end") => "function foo.bar(self, a, b, c, ...) end"
+    variadic_with_arguments("function foo:bar(a, b, c, ...) end") => "function foo.bar(self, a, b, c, ...) end",
+    with_attribute("@native function foo:bar() end") => "@native function foo.bar(self) end",
+    with_multiple_attributes("@native @checked function foo:bar() end") => "@native @checked function foo.bar(self) end"
 );
 
+mod parsed_with_tokens {
+    use super::*;
+
+    test_rule_no_tokens!(
+        RemoveMethodDefinition::default(),
+        name_with_method("function foo:bar() end") => "function foo.bar(self) end",
+        with_arguments_and_comment("-- method\nfunction foo:bar(a, b)\n\treturn a\nend")
+            => "function foo.bar(self, a, b) return a end",
+    );
+}
+
 #[test]
 fn deserialize_from_object_notation() {
     json5::from_str::<Box<dyn Rule>>(
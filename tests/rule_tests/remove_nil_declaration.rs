@@ -13,6 +13,11 @@ test_rule!(
     assign_field_expression_and_nil("local a, b = object.prop, nil") => "local a, b = (object.prop)",
     assign_index_expression_and_nil("local a, b = object[key], nil") => "local a, b = (object[key])",
     assign_call_and_nil_and_nil("local a, b, c = call(), nil, nil") => "local a, b, c = (call())",
+    // the last call is adjusted to one value, otherwise its extra values would be assigned
+    // to the variables that were initialized with `nil`
+    assign_two_calls_and_nil("local a, b, c = first(), second(), nil") => "local a, b, c = first(), (second())",
+    assign_method_call_and_nil_and_nil("local a, b, c = object:method(), nil, nil")
+        => "local a, b, c = (object:method())",
     // we can re-order variables that gets assigned to `nil`
     assign_to_nil_and_true("local a, b = nil, true") => "local b, a = true",
     assign_to_nil_and_nil_and_true("local a, b, c = nil, nil, true") => "local c, a, b = true",
@@ -27,6 +32,10 @@ test_rule_without_effects!(
     assign_to_true("local a = true"),
     assign_to_nil_and_extra_call("local a = nil, call()"),
     assign_to_nil_and_extract_varargs("local a, b, c = nil, ..."),
+    // assigning `nil` clears the variable, so assignments are not modified
+    assignment_with_trailing_nil("a, b = value, nil"),
+    assignment_with_call_and_nil("a, b = call(), nil"),
+    assignment_to_nil("a = nil"),
 );
 
 #[test]
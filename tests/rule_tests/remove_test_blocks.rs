@@ -0,0 +1,50 @@
+use darklua_core::rules::{RemoveTestBlocks, Rule};
+
+test_rule!(
+    remove_test_blocks,
+    RemoveTestBlocks::default(),
+    remove_describe("describe('module', function() end)") => "",
+    remove_it("it('works', function() assert(true) end)") => "",
+    remove_test("test('works', function() end)") => "",
+    remove_before_each("beforeEach(function() reset() end)") => "",
+    remove_after_all("afterAll(function() cleanup() end)") => "",
+    remove_nested_describe_and_it(
+        "describe('module', function() beforeEach(function() end) describe('function', function() it('works', function() end) end) end)"
+    ) => "",
+    keep_surrounding_statements("local module = {} describe('module', function() it('works', function() end) end) return module")
+        => "local module = {} return module",
+    remove_test_blocks_nested_in_function("local function run() describe('module', function() end) print('done') end")
+        => "local function run() print('done') end",
+    remove_test_blocks_after_shadowed_local_in_other_scope("do local it = print end it('works', function() end)")
+        => "do local it = print end",
+);
+
+test_rule!(
+    remove_configured_test_functions,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'remove_test_blocks',
+        functions: ['suite', 'spec'],
+    }"#,
+    )
+    .unwrap(),
+    remove_suite("suite('module', function() spec('works', function() end) end)") => "",
+    keep_describe("describe('module', function() end)") => "describe('module', function() end)",
+);
+
+test_rule_without_effects!(
+    RemoveTestBlocks::default(),
+    shadowed_it_local("local it = print it('works', function() end)"),
+    shadowed_it_local_function("local function it(name, callback) end it('works', function() end)"),
+    shadowed_it_parameter("local function run(it) it('works', function() end) end"),
+    shadowed_it_in_parent_scope("local it = print do it('works', function() end) end"),
+    shadowed_it_after_other_statements(
+        "describe = nil local it = print it('works', function() end)"
+    ),
+    call_without_function_argument("describe('module', callback)"),
+    call_with_function_not_last("describe(function() end, 'module')"),
+    call_with_string_argument("it 'works'"),
+    method_call("suite:describe('module', function() end)"),
+    field_call("test.describe('module', function() end)"),
+    expression_call("local result = describe('module', function() end)"),
+);
@@ -0,0 +1,56 @@
+use darklua_core::rules::{RemoveTrailingNilArguments, Rule};
+
+test_rule!(
+    remove_trailing_nil_arguments,
+    RemoveTrailingNilArguments::default(),
+    single_nil("f(nil)") => "f()",
+    multiple_nils("f(nil, nil)") => "f()",
+    value_then_nils("callback(value, nil, nil)") => "callback(value)",
+    only_last_nil_removed("f(a, nil, b, nil)") => "f(a, nil, b)",
+    method_call("object:method(a, nil)") => "object:method(a)",
+    method_call_with_single_nil("object:method(nil)") => "object:method()",
+    field_call("module.f(a, nil)") => "module.f(a)",
+    nested_call("f(g(a, nil), nil)") => "f(g(a))",
+    call_in_expression("local value = f(a, nil)") => "local value = f(a)",
+    call_in_function("local function run() return f(nil) end") => "local function run() return f() end",
+);
+
+test_rule_without_effects!(
+    RemoveTrailingNilArguments::default(),
+    call_without_arguments("f()"),
+    nil_before_value("f(nil, a)"),
+    string_call_sugar("f 'nil'"),
+    table_call_sugar("f { nil }"),
+    parenthesized_nil("f((nil))"),
+    nil_variable("local none = nil f(none)"),
+    vararg_argument("f(a, ...)"),
+);
+
+test_rule!(
+    remove_trailing_nil_arguments_keep_single_nil,
+    RemoveTrailingNilArguments::default().keep_single_nil(),
+    multiple_nils("f(nil, nil)") => "f(nil)",
+    value_then_nils("f(a, nil, nil)") => "f(a)",
+);
+
+test_rule_without_effects!(
+    RemoveTrailingNilArguments::default().keep_single_nil(),
+    keep_single_nil("f(nil)"),
+    keep_single_nil_in_method_call("object:method(nil)"),
+);
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'remove_trailing_nil_arguments',
+        keep_single_nil: true,
+    }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'remove_trailing_nil_arguments'").unwrap();
+}
@@ -0,0 +1,45 @@
+use darklua_core::rules::{RemoveTypeAssertions, Rule};
+
+test_rule!(
+    remove_type_assertions,
+    RemoveTypeAssertions::default(),
+    remove_assertion_of_identifier("return value :: string") => "return value",
+    remove_assertion_of_table("return {} :: any") => "return {}",
+    remove_assertion_in_binary_operand("return value :: number + 1") => "return value + 1",
+    keep_local_annotation("local value: number = other :: number") => "local value: number = other",
+    keep_function_annotations("local function f(a: number): any return a :: any end")
+        => "local function f(a: number): any return a end",
+    keep_parentheses_of_field_prefix("return (value :: any).field") => "return (value).field",
+    keep_parentheses_of_method_call_prefix("return (value :: any):method()")
+        => "return (value):method()",
+    truncate_call_in_tail_position_of_return("return call() :: any") => "return (call())",
+    truncate_call_in_tail_position_of_assignment("local a, b = 1, call() :: any")
+        => "local a, b = 1, (call())",
+    truncate_call_in_tail_position_of_arguments("print(call() :: any)") => "print((call()))",
+    truncate_call_in_tail_position_of_table("return { call() :: any }") => "return { (call()) }",
+    truncate_variadic_arguments("return ... :: any") => "return (...)",
+    keep_single_parentheses_around_call("return (call() :: any)") => "return (call())",
+    remove_nested_assertions("return call() :: any :: number") => "return (call())",
+);
+
+test_rule_without_effects!(
+    RemoveTypeAssertions::default(),
+    type_declaration("type T = string | number"),
+    exported_type_declaration("export type T = { number }"),
+    local_annotation("local value: number = 1"),
+);
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'remove_type_assertions',
+    }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'remove_type_assertions'").unwrap();
+}
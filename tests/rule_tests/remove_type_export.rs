@@ -0,0 +1,34 @@
+use darklua_core::rules::{RemoveTypeExport, Rule};
+
+test_rule!(
+    remove_type_export,
+    RemoveTypeExport::default(),
+    exported_type_declaration("export type T = string") => "type T = string",
+    exported_generic_type_declaration("export type Array<T> = { T }") => "type Array<T> = { T }",
+    exported_type_function("export type function Same(t) return t end")
+        => "type function Same(t) return t end",
+    keep_other_statements("export type T = string\nlocal value: T = 'hello'")
+        => "type T = string\nlocal value: T = 'hello'",
+);
+
+test_rule_without_effects!(
+    RemoveTypeExport::default(),
+    type_declaration("type T = string | number"),
+    type_function("type function Same(t) return t end"),
+    local_annotation("local value: number = 1"),
+);
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'remove_type_export',
+    }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'remove_type_export'").unwrap();
+}
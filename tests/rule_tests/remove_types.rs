@@ -49,6 +49,11 @@ test_rule!(
         => "return value",
     remove_types_in_type_cast_of_table("return {} :: any")
         => "return {}",
+    remove_type_function("type function Pair(a, b) return types.newtable() end") => "",
+    remove_exported_type_function("export type function Same(t) return t end") => "",
+    remove_type_function_keeps_other_statements(
+        "type function Same(t) return t end\nlocal value = 1"
+    ) => "local value = 1",
 );
 
 #[test]
@@ -1,4 +1,8 @@
-use darklua_core::rules::{RemoveUnusedIfBranch, Rule};
+use darklua_core::{
+    generator::{DenseLuaGenerator, LuaGenerator, TokenBasedLuaGenerator},
+    rules::{ContextBuilder, RemoveComments, RemoveUnusedIfBranch, Rule},
+    Parser, Resources,
+};
 
 test_rule!(
     remove_unused_if_branch,
@@ -45,6 +49,69 @@ test_rule!(
     ) => "return if var then 'first' else 'third'",
 );
 
+test_rule_with_tokens!(
+    remove_unused_if_branch_preserving_comments,
+    RemoveUnusedIfBranch::default().preserve_comments(),
+    removed_statement_before_return("if false then\n\t-- explain\n\tprint('debug')\nend\nreturn 1")
+        => "--[[\nremoved `if false` branch:\nexplain\n]]\nreturn 1",
+    removed_statement_at_end_of_file("if false then\n\tlocal function f()\n\t\t-- inner\n\tend\nend")
+        => "--[[\nremoved `if false` branch:\ninner\n]]\n",
+    removed_elseif_branch("if x then\n\tone()\nelseif false then\n\t-- skipped\n\ttwo()\nend")
+        => "--[[\nremoved `elseif false` branch:\nskipped\n]]\nif x then\n\tone()\nend",
+    comments_in_code_order("if x then\n\ta()\nelseif false then\n\t-- first\n\tb() -- second\nelse\n\tc()\nend")
+        => "--[[\nremoved `elseif false` branch:\nfirst\nsecond\n]]\nif x then\n\ta()\nelse\n\tc()\nend",
+    removed_branches_after_truthy_branch("if x then\n\ta()\nelseif true then\n\tb()\nelse\n\t-- never\n\tc()\nend")
+        => "--[[\nremoved `else` branch:\nnever\n]]\nif x then\n\ta()\nelse\n\tb()\nend",
+    comment_with_closing_brackets("if false then\n\t-- see t[a[1]]\n\tcall()\nend\nreturn")
+        => "--[=[\nremoved `if false` branch:\nsee t[a[1]]\n]=]\nreturn",
+    removed_branch_without_comments("if false then call() end return") => "return",
+);
+
+fn process_with_rules(code: &str, rules: &[Box<dyn Rule>], generator: &mut impl LuaGenerator) {
+    let mut block = Parser::default()
+        .preserve_tokens()
+        .parse(code)
+        .expect("unable to parse code");
+
+    let resources = Resources::from_memory();
+    let context = ContextBuilder::new("src/test.lua", &resources, code).build();
+
+    for rule in rules {
+        rule.process(&mut block, &context)
+            .expect("rule should succeed");
+    }
+
+    generator.write_block(&block);
+}
+
+#[test]
+fn preserved_comments_are_removed_by_remove_comments() {
+    let code = "if false then\n\t-- explain\n\tprint('debug')\nend\nreturn 1";
+    let rules: Vec<Box<dyn Rule>> = vec![
+        Box::new(RemoveUnusedIfBranch::default().preserve_comments()),
+        Box::new(RemoveComments::default()),
+    ];
+
+    let mut generator = TokenBasedLuaGenerator::new(code);
+    process_with_rules(code, &rules, &mut generator);
+    pretty_assertions::assert_eq!(generator.into_string(), "\n\n\n\nreturn 1");
+
+    let mut generator = DenseLuaGenerator::default();
+    process_with_rules(code, &rules, &mut generator);
+    pretty_assertions::assert_eq!(generator.into_string(), "return 1");
+}
+
+#[test]
+fn deserialize_from_object_notation_preserving_comments() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'remove_unused_if_branch',
+        preserve_comments: true,
+    }"#,
+    )
+    .unwrap();
+}
+
 #[test]
 fn deserialize_from_object_notation() {
     json5::from_str::<Box<dyn Rule>>(
@@ -84,6 +84,8 @@ test_rule!(
 test_rule_without_effects!(
     RemoveUnusedVariable::default(),
     keep_returning_local_function("local function foo() end return foo"),
+    keep_decoy_local("local __DARKLUA_DECOY1 = 7 * 3 % 4"),
+    keep_decoy_local_function("local function __DARKLUA_DECOY1() end"),
     keep_used_local_function("local function foo() end foo()"),
     keep_not_initialized_variable("local foo return foo"),
     keep_previous_identifiers_for_tuple_extraction("local a, b, c = ... return c"),
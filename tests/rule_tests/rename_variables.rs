@@ -102,6 +102,45 @@ test_rule_without_effects!(
     does_not_rename_functions("local function foo() end return foo()"),
 );
 
+test_rule!(
+    rename_variables_and_file_globals,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'rename_variables',
+        include_file_globals: true,
+    }"#,
+    ).unwrap(),
+    global_function_defined_and_used("function Foo() return 1 end return Foo()")
+        => "function a() return 1 end return a()",
+    global_function_with_parameters("function Foo(value) return value end return Foo(1)")
+        => "function a(b) return b end return a(1)",
+    global_function_shadowed_by_parameter("function Foo() end local function g(Foo) return Foo end return Foo")
+        => "function a() end local function g(b) return b end return a",
+    global_function_avoids_external_global_names("function Foo() return a end")
+        => "function b() return a end",
+    global_function_kept_with_dynamic_global_table_index("function Foo() end local name = 'Foo' return _G[name]")
+        => "function Foo() end local a = 'Foo' return _G[a]",
+);
+
+test_rule_without_effects!(
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'rename_variables',
+        include_file_globals: true,
+    }"#,
+    )
+    .unwrap(),
+    global_function_read_before_definition(
+        "local function call() return Foo() end function Foo() end return call()"
+    ),
+    global_function_indexed_from_global_table("function Foo() end return _G['Foo']"),
+    global_function_field_of_global_table("function Foo() end return _G.Foo"),
+    global_function_defined_in_nested_block("do function Foo() end end return Foo()"),
+    global_function_with_getfenv("function Foo() end return getfenv()"),
+    global_function_from_default_globals("function print() end print()"),
+    global_variable_without_function_definition("Foo = 1 return Foo"),
+);
+
 #[test]
 fn deserialize_with_special_empty_globals() {
     json5::from_str::<Box<dyn Rule>>(
@@ -0,0 +1,104 @@
+use darklua_core::rules::Rule;
+
+test_rule!(
+    replace_calls,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'replace_calls',
+        mapping: {
+            'utils.deepCopy': 'table.clone',
+            'game.Workspace': 'workspace',
+            'wait': 'task.wait',
+        },
+    }"#,
+    )
+    .unwrap(),
+    call_field("return utils.deepCopy(value)") => "return table.clone(value)",
+    call_identifier("wait(1)") => "task.wait(1)",
+    call_in_nested_function("local function copy(value) return utils.deepCopy(value) end")
+        => "local function copy(value) return table.clone(value) end",
+    method_call_on_prefix("game.Workspace:FindFirstChild('Part')")
+        => "workspace:FindFirstChild('Part')",
+    field_of_prefix("local part = game.Workspace.Part") => "local part = workspace.Part",
+    call_with_longer_path("return utils.deepCopy.inner(value)")
+        => "return table.clone.inner(value)",
+    call_after_shadowing_scope("do local utils = {} end return utils.deepCopy(value)")
+        => "do local utils = {} end return table.clone(value)",
+);
+
+test_rule_without_effects!(
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'replace_calls',
+        mapping: {
+            'utils.deepCopy': 'table.clone',
+            'game.Workspace': 'workspace',
+            'wait': 'task.wait',
+        },
+    }"#,
+    )
+    .unwrap(),
+    reference_to_field("local copy = utils.deepCopy"),
+    reference_to_identifier("return wait"),
+    prefix_reference("local workspace = game.Workspace"),
+    shadowed_root("local utils = require('./utils') return utils.deepCopy(value)"),
+    shadowed_root_parameter("local function f(wait) wait(1) end"),
+    shadowed_replacement_root("local table = {} return utils.deepCopy(value)"),
+    call_with_other_field("return utils.shallowCopy(value)"),
+    assign_to_field("utils.deepCopy = function() end"),
+);
+
+test_rule!(
+    replace_calls_and_references,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'replace_calls',
+        mapping: {
+            'utils.deepCopy': 'table.clone',
+            'game.Workspace': 'workspace',
+            'wait': 'task.wait',
+        },
+        replace_references: true,
+    }"#,
+    )
+    .unwrap(),
+    call_field("return utils.deepCopy(value)") => "return table.clone(value)",
+    reference_to_field("local copy = utils.deepCopy") => "local copy = table.clone",
+    reference_to_identifier("return wait") => "return task.wait",
+    reference_as_argument("return pcall(utils.deepCopy, value)")
+        => "return pcall(table.clone, value)",
+    reference_in_table("return { copy = utils.deepCopy }") => "return { copy = table.clone }",
+    prefix_reference("local root = game.Workspace") => "local root = workspace",
+);
+
+test_rule_without_effects!(
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'replace_calls',
+        mapping: {
+            'utils.deepCopy': 'table.clone',
+            'wait': 'task.wait',
+        },
+        replace_references: true,
+    }"#,
+    )
+    .unwrap(),
+    shadowed_reference("local utils = {} local copy = utils.deepCopy"),
+    shadowed_identifier_reference("local wait = nil return wait"),
+);
+
+test_rule_without_effects!(
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'replace_calls',
+        mapping: {
+            'utils.deepCopy': 'table.clone',
+        },
+        replace_references: true,
+        warn_only: true,
+    }"#,
+    )
+    .unwrap(),
+    call_field_with_warning("return utils.deepCopy(value)"),
+    reference_with_warning("local copy = utils.deepCopy"),
+);
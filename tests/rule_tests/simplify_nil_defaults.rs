@@ -0,0 +1,74 @@
+use darklua_core::rules::{Rule, SimplifyNilDefaults};
+
+test_rule!(
+    simplify_nil_defaults_if_expression,
+    SimplifyNilDefaults::default(),
+    equal_nil("if x == nil then x = 1 end") => "x = if x == nil then 1 else x",
+    nil_equal("if nil == x then x = 1 end") => "x = if x == nil then 1 else x",
+    not_equal_nil_with_else("if x ~= nil then else x = {} end") => "x = if x == nil then {} else x",
+    // a variable that is `false` keeps its value
+    false_value_is_kept("local x = false if x == nil then x = true end")
+        => "local x = false x = if x == nil then true else x",
+    default_with_call("if options == nil then options = getDefaults() end")
+        => "options = if options == nil then getDefaults() else options",
+    nested_in_function("local function f(x) if x == nil then x = 'default' end return x end")
+        => "local function f(x) x = if x == nil then 'default' else x return x end",
+);
+
+test_rule!(
+    simplify_nil_defaults_or,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+            rule: 'simplify_nil_defaults',
+            style: 'or',
+            assume_no_false: true,
+        }"#
+    )
+    .unwrap(),
+    equal_nil("if x == nil then x = 1 end") => "x = x or 1",
+    not_equal_nil_with_else("if x ~= nil then else x = {} end") => "x = x or {}",
+    // with `assume_no_false`, a variable that is `false` is replaced by the default value
+    false_value_is_replaced("local x = false if x == nil then x = true end")
+        => "local x = false x = x or true",
+    default_with_binary_expression("if x == nil then x = a and b end") => "x = x or a and b",
+);
+
+test_rule_without_effects!(
+    SimplifyNilDefaults::default(),
+    assign_other_variable("if x == nil then y = 1 end"),
+    multiple_statements("if x == nil then x = 1 print(x) end"),
+    compare_with_false("if x == false then x = 1 end"),
+    not_equal_nil_without_else("if x ~= nil then x = 1 end"),
+    with_else_block("if x == nil then x = 1 else x = 2 end"),
+    with_elseif_branch("if x == nil then x = 1 elseif y then x = 2 end"),
+    assign_field("if t.x == nil then t.x = 1 end"),
+    multiple_assignment("if x == nil then x, y = 1, 2 end"),
+);
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'simplify_nil_defaults',
+        style: 'if_expression',
+    }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'simplify_nil_defaults'").unwrap();
+}
+
+#[test]
+fn deserialize_or_style_without_assume_no_false_fails() {
+    let result = json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'simplify_nil_defaults',
+        style: 'or',
+    }"#,
+    );
+
+    assert!(result.is_err());
+}
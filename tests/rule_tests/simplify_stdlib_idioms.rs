@@ -0,0 +1,91 @@
+use darklua_core::rules::{Rule, SimplifyStdlibIdioms};
+
+test_rule!(
+    simplify_stdlib_idioms,
+    SimplifyStdlibIdioms::default(),
+    string_len("return string.len(value)") => "return #value",
+    string_len_with_string_call("return string.len 'abc'") => "return #'abc'",
+    string_len_of_concatenation("return string.len(a .. b)") => "return #(a .. b)",
+    string_len_of_call("return string.len(f())") => "return #f()",
+    table_getn("return table.getn(list)") => "return #list",
+    table_getn_with_table_call("return table.getn { 1, 2 }") => "return #{ 1, 2 }",
+    table_getn_of_field("return table.getn(self.items) + 1") => "return #self.items + 1",
+    math_pow("return math.pow(a, b)") => "return a ^ b",
+    math_pow_with_binary_operands("return math.pow(a + 1, b * 2)") => "return (a + 1) ^ (b * 2)",
+    math_pow_with_negative_base("return math.pow(-2, n)") => "return (-2) ^ n",
+    math_pow_in_binary_expression("return 2 * math.pow(a, b)") => "return 2 * a ^ b",
+    string_format("return string.format('%s', value)") => "return tostring(value)",
+    string_format_of_call("return string.format('%s', f())") => "return tostring(f())",
+    string_format_as_prefix("return string.format('%s', value):upper()") => "return tostring(value):upper()",
+    length_as_prefix("return string.len(a):format()") => "return (#a):format()",
+    nested_idioms("return math.pow(string.len(a), 2)") => "return (#a) ^ 2",
+    shadowed_variable_in_another_scope("do local string = {} end return string.len(a)") => "do local string = {} end return #a",
+);
+
+test_rule!(
+    simplify_stdlib_idioms_without_string_len,
+    SimplifyStdlibIdioms::default().with_string_len(false),
+    table_getn("return string.len(s), table.getn(t)") => "return string.len(s), #t",
+);
+
+test_rule!(
+    simplify_stdlib_idioms_without_table_getn,
+    SimplifyStdlibIdioms::default().with_table_getn(false),
+    string_len("return string.len(s), table.getn(t)") => "return #s, table.getn(t)",
+);
+
+test_rule!(
+    simplify_stdlib_idioms_without_math_pow,
+    SimplifyStdlibIdioms::default().with_math_pow(false),
+    string_format("return math.pow(a, b), string.format('%s', c)") => "return math.pow(a, b), tostring(c)",
+);
+
+test_rule!(
+    simplify_stdlib_idioms_without_string_format,
+    SimplifyStdlibIdioms::default().with_string_format(false),
+    math_pow("return math.pow(a, b), string.format('%s', c)") => "return a ^ b, string.format('%s', c)",
+);
+
+test_rule_without_effects!(
+    SimplifyStdlibIdioms::default(),
+    shadowed_string_library(
+        "local string = require('string') return string.len(a), string.format('%s', b)"
+    ),
+    shadowed_string_library_parameter("local function f(string) return string.len(a) end"),
+    shadowed_table_library("local table = {} return table.getn(t)"),
+    shadowed_math_library("local math = {} return math.pow(a, b)"),
+    shadowed_tostring("local tostring = f return string.format('%s', value)"),
+    string_len_without_argument("return string.len()"),
+    string_len_with_extra_argument("return string.len(a, b)"),
+    table_getn_with_variadic_arguments("return table.getn(...)"),
+    math_pow_with_single_argument("return math.pow(f())"),
+    math_pow_with_extra_argument("return math.pow(a, b, c)"),
+    string_format_with_other_format("return string.format('%d', value)"),
+    string_format_with_literal("return string.format('%s!', value)"),
+    string_format_without_value("return string.format('%s')"),
+    string_format_with_extra_value("return string.format('%s', a, b)"),
+    string_format_with_variable_format("return string.format(format, value)"),
+    string_sub("return string.sub(s, 2)"),
+    method_call("return string:len(a)"),
+    other_library_function("return string.upper(a), math.floor(a)"),
+    field_of_another_table("return str.len(a), utils.pow(a, b)"),
+);
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'simplify_stdlib_idioms',
+        string_len: false,
+        table_getn: false,
+        math_pow: true,
+        string_format: false,
+    }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'simplify_stdlib_idioms'").unwrap();
+}
@@ -0,0 +1,57 @@
+use darklua_core::rules::{Rule, SimplifyStringFormat};
+
+test_rule!(
+    simplify_string_format,
+    SimplifyStringFormat::default(),
+    two_arguments("return string.format('%s/%s', a, b)") => "return tostring(a) .. '/' .. tostring(b)",
+    single_argument("return string.format('%s', value)") => "return tostring(value)",
+    escaped_percent("return string.format('%s%% done', progress)") => "return tostring(progress) .. '% done'",
+    trailing_literal("return string.format('Hello %s!', name)") => "return 'Hello ' .. tostring(name) .. '!'",
+    adjacent_specifiers("return string.format('%s%s', a, b)") => "return tostring(a) .. tostring(b)",
+    string_literal_argument("return string.format('%s-%s', 'id', b)") => "return 'id' .. '-' .. tostring(b)",
+    no_specifier("return string.format('100%%')") => "return '100%'",
+    string_call_argument("return string.format 'no specifier'") => "return 'no specifier'",
+    empty_format("return string.format('')") => "return ''",
+    call_prefix("return string.format('%s.%s', a, b):upper()") => "return (tostring(a) .. '.' .. tostring(b)):upper()",
+    nested_format("return string.format('[%s]', string.format('%s', a))") => "return '[' .. tostring(tostring(a)) .. ']'",
+);
+
+test_rule!(
+    simplify_string_format_assume_strings,
+    SimplifyStringFormat::default().with_strings_assumed(),
+    two_arguments("return string.format('%s/%s', a, b)") => "return a .. '/' .. b",
+    single_call_argument("return string.format('%s', f())") => "return (f())",
+    shadowed_tostring("local tostring = f return string.format('%s!', a)") => "local tostring = f return a .. '!'",
+);
+
+test_rule_without_effects!(
+    SimplifyStringFormat::default(),
+    missing_argument("return string.format('%s/%s', a)"),
+    extra_argument("return string.format('%s', a, b)"),
+    integer_specifier("return string.format('%d', a)"),
+    quoted_specifier("return string.format('%q', a)"),
+    width_flag("return string.format('%5s', a)"),
+    precision_flag("return string.format('%.3s', a)"),
+    trailing_percent("return string.format('100%', a)"),
+    format_variable("return string.format(format, a)"),
+    variable_arguments("return string.format('%s %s', ...)"),
+    shadowed_string_library("local string = {} return string.format('%s', a)"),
+    shadowed_tostring("local tostring = f return string.format('%s', a)"),
+    method_call("return ('%s'):format(a)"),
+);
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'simplify_string_format',
+        assume_strings: true,
+    }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'simplify_string_format'").unwrap();
+}
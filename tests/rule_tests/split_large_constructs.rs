@@ -0,0 +1,88 @@
+use darklua_core::rules::{Rule, SplitLargeConstructs};
+
+test_rule!(
+    split_large_constructs,
+    SplitLargeConstructs::default().with_max_entries(2),
+    mixed_table_in_local(
+        "local t = { 1, a = 2, 'x', ['k'] = 3, 4, b = 5 }"
+    ) => "local t = { 1, a = 2 } t[2] = 'x' t['k'] = 3 t[3] = 4 t.b = 5",
+    array_after_hash_entries("local t = { a = 1, b = 2, 'x', 'y' }")
+        => "local t = { a = 1, b = 2 } t[1] = 'x' t[2] = 'y'",
+    keys_without_array_values("local t = { [1] = 'a', [2] = 'b', [3] = 'c', [key] = 'd' }")
+        => "local t = { [1] = 'a', [2] = 'b' } t[3] = 'c' t[key] = 'd'",
+    nil_values_keep_their_index("local t = { 1, nil, 3, nil }")
+        => "local t = { 1, nil } t[3] = 3 t[4] = nil",
+    call_before_last_entry("local t = { 1, 2, f(), 4 }")
+        => "local t = { 1, 2 } t[3] = f() t[4] = 4",
+    call_kept_as_last_entry("local t = { 1, f(), 3 }")
+        => "local t = { 1, (f()) } t[3] = 3",
+    variadic_arguments_kept_as_last_entry("local t = { 1, ..., 3 }")
+        => "local t = { 1, (...) } t[3] = 3",
+    returned_table("return { 1, 2, 3 }")
+        => "local __DARKLUA_TABLE = { 1, 2 } __DARKLUA_TABLE[3] = 3 return __DARKLUA_TABLE",
+    returned_table_in_function("local function get() return { a = 1, b = 2, c = 3 } end")
+        => "local function get() local __DARKLUA_TABLE = { a = 1, b = 2 } __DARKLUA_TABLE.c = 3 return __DARKLUA_TABLE end",
+    returned_table_with_used_variable_name("local __DARKLUA_TABLE = 0 return { 1, 2, 3 }")
+        => "local __DARKLUA_TABLE = 0 local __DARKLUA_TABLE1 = { 1, 2 } __DARKLUA_TABLE1[3] = 3 return __DARKLUA_TABLE1",
+    assigned_table("data = { a = 1, b = 2, c = 3 }")
+        => "local __DARKLUA_TABLE = { a = 1, b = 2 } __DARKLUA_TABLE.c = 3 data = __DARKLUA_TABLE",
+    local_referenced_by_moved_entries("local t = { 1, 2, t }")
+        => "local __DARKLUA_TABLE = { 1, 2 } __DARKLUA_TABLE[3] = t local t = __DARKLUA_TABLE",
+    local_referenced_by_kept_entries("local t = { t, 2, 3 }")
+        => "local t = { t, 2 } t[3] = 3",
+    split_in_multiple_chunks("local t = { 1, 2, 3, 4, 5 }")
+        => "local t = { 1, 2 } t[3] = 3 t[4] = 4 t[5] = 5",
+);
+
+test_rule!(
+    split_large_constructs_with_chunk_statements,
+    SplitLargeConstructs::default().with_max_chunk_statements(2),
+    locals_not_used_after_their_group(
+        "local a = 1 print(a) local b = 2 print(b) return"
+    ) => "do local a = 1 print(a) end do local b = 2 print(b) end return",
+    local_used_after_maximum_group("local a = 1 print(a) local b = 2 print(b) print(b)")
+        => "do local a = 1 print(a) end local b = 2 print(b) print(b)",
+    statements_without_locals_are_not_grouped("print(1) local a = 1 print(a) print(2)")
+        => "print(1) do local a = 1 print(a) end print(2)",
+    type_declaration_stays_in_block(
+        "type A = number local a: A = 1 print(a) local b = 2 print(b)"
+    ) => "type A = number do local a: A = 1 print(a) end do local b = 2 print(b) end",
+);
+
+test_rule_without_effects!(
+    SplitLargeConstructs::default().with_max_entries(2),
+    table_below_threshold("local t = { 1, a = 2 }"),
+    returned_table_below_threshold("return { a = 1, b = 2 }"),
+    table_ending_with_call("local t = { 1, 2, f() }"),
+    table_ending_with_variadic_arguments("local t = { 1, 2, ... }"),
+    number_key_with_array_values("local t = { 1, [2] = 'x', 3 }"),
+    expression_key_with_array_values("local t = { 1, [key] = 'x', 3 }"),
+    multiple_local_variables("local a, b = { 1, 2, 3 }, 0"),
+    assign_to_field("t.field = { 1, 2, 3 }"),
+    return_multiple_values("return { 1, 2, 3 }, 0"),
+    table_in_call_arguments("call({ 1, 2, 3 })"),
+);
+
+test_rule_without_effects!(
+    SplitLargeConstructs::default().with_max_chunk_statements(2),
+    statements_below_threshold("local a = 1 print(a)"),
+    local_used_after_every_group("local a = 1 print(a) print(a) print(a)"),
+    local_used_by_return("local a = 1 print(a) print(a) return a"),
+);
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+            rule: 'split_large_constructs',
+            max_entries: 500,
+            max_chunk_statements: 100,
+        }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'split_large_constructs'").unwrap();
+}
@@ -0,0 +1,111 @@
+use darklua_core::rules::{Rule, UnrollLoops};
+
+test_rule!(
+    unroll_loops,
+    UnrollLoops::default(),
+    loop_with_default_step("for i = 1, 4 do t[i] = 0 end")
+        => "t[1] = 0 t[2] = 0 t[3] = 0 t[4] = 0",
+    loop_with_positive_step("for i = 1, 7, 3 do print(i) end")
+        => "print(1) print(4) print(7)",
+    loop_with_negative_step("for i = 3, 1, -1 do print(i) end")
+        => "print(3) print(2) print(1)",
+    loop_with_negative_step_and_negative_end("for i = 2, -4, -3 do print(i) end")
+        => "print(2) print(-1) print(-4)",
+    loop_with_negative_start("for i = -1, 1 do print(i) end")
+        => "print(-1) print(0) print(1)",
+    loop_with_step_not_reaching_end("for i = 1, 6, 2 do print(i) end")
+        => "print(1) print(3) print(5)",
+    loop_without_iterations("for i = 1, 0 do print(i) end")
+        => "",
+    loop_with_negative_step_without_iterations("for i = 1, 3, -1 do print(i) end")
+        => "",
+    loop_with_single_iteration("for i = 5, 5 do print(i) end")
+        => "print(5)",
+    loop_with_max_iterations("for i = 1, 8 do f(i) end")
+        => "f(1) f(2) f(3) f(4) f(5) f(6) f(7) f(8)",
+    loop_with_multiple_statements("for i = 1, 2 do a(i) b(i) end")
+        => "a(1) b(1) a(2) b(2)",
+    loop_variable_used_as_prefix("for i = 1, 2 do print(i.x) end")
+        => "print((1).x) print((2).x)",
+    loop_with_local_variable("for i = 1, 2 do local value = i * 2 print(value) end")
+        => "do local value = 1 * 2 print(value) end do local value = 2 * 2 print(value) end",
+    loop_with_local_function("for i = 1, 2 do local function f() return 0 end f() end")
+        => "do local function f() return 0 end f() end do local function f() return 0 end f() end",
+    loop_with_return("for i = 1, 2 do print(i) return end")
+        => "do print(1) return end do print(2) return end",
+    loop_variable_shadowed_by_local("for i = 1, 2 do print(i) local i = 0 print(i) end")
+        => "do print(1) local i = 0 print(i) end do print(2) local i = 0 print(i) end",
+    loop_variable_shadowed_then_assigned("for i = 1, 2 do print(i) local i = 0 i = i + 1 end")
+        => "do print(1) local i = 0 i = i + 1 end do print(2) local i = 0 i = i + 1 end",
+    loop_variable_shadowed_by_nested_loop("for i = 1, 2 do for i = 1, 10 do print(i) end end")
+        => "for i = 1, 10 do print(i) end for i = 1, 10 do print(i) end",
+    nested_loops("for i = 1, 2 do for j = 1, 2 do t[i][j] = 0 end end")
+        => "t[1][1] = 0 t[1][2] = 0 t[2][1] = 0 t[2][2] = 0",
+    break_in_nested_loop("for i = 1, 2 do for j = 1, 10 do if j == i then break end end end")
+        => "for j = 1, 10 do if j == 1 then break end end for j = 1, 10 do if j == 2 then break end end",
+    loop_in_function("local function reset(t) for i = 1, 3 do t[i] = nil end end")
+        => "local function reset(t) t[1] = nil t[2] = nil t[3] = nil end",
+    closure_not_using_loop_variable("for i = 1, 2 do t[i] = function() return 0 end end")
+        => "t[1] = function() return 0 end t[2] = function() return 0 end",
+);
+
+test_rule_without_effects!(
+    UnrollLoops::default(),
+    loop_with_break("for i = 1, 3 do print(i) break end"),
+    loop_with_break_in_if("for i = 1, 3 do if t[i] then break end end"),
+    loop_with_break_in_do("for i = 1, 3 do do break end end"),
+    loop_with_continue("for i = 1, 3 do if t[i] then continue end print(i) end"),
+    loop_variable_captured_by_function_expression(
+        "for i = 1, 3 do t[i] = function() return i end end"
+    ),
+    loop_variable_captured_by_local_function(
+        "for i = 1, 3 do local function get() return i end t[i] = get end"
+    ),
+    loop_variable_captured_by_function_statement(
+        "for i = 1, 3 do function t.get() return i end end"
+    ),
+    loop_variable_assigned("for i = 1, 3 do i = i + 1 print(i) end"),
+    loop_variable_compound_assigned("for i = 1, 3 do i += 1 print(i) end"),
+    too_many_iterations("for i = 1, 9 do print(i) end"),
+    too_many_body_statements("for i = 1, 2 do a(i) b(i) c(i) d(i) e(i) end"),
+    identifier_start("for i = start, 3 do print(i) end"),
+    identifier_end("for i = 1, #t do print(t[i]) end"),
+    identifier_step("for i = 1, 3, step do print(i) end"),
+    decimal_step("for i = 1, 2, 0.5 do print(i) end"),
+    decimal_start("for i = 0.5, 2 do print(i) end"),
+    zero_step("for i = 1, 3, 0 do print(i) end"),
+    generic_for("for i, value in ipairs({ 1, 2 }) do print(value) end"),
+);
+
+test_rule!(
+    unroll_loops_with_thresholds,
+    UnrollLoops::default()
+        .with_max_iterations(2)
+        .with_max_body_statements(1),
+    loop_within_thresholds("for i = 1, 2 do print(i) end") => "print(1) print(2)",
+);
+
+test_rule_without_effects!(
+    UnrollLoops::default()
+        .with_max_iterations(2)
+        .with_max_body_statements(1),
+    loop_with_too_many_iterations("for i = 1, 3 do print(i) end"),
+    loop_with_too_many_body_statements("for i = 1, 2 do a(i) b(i) end"),
+);
+
+#[test]
+fn deserialize_from_object_notation() {
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'unroll_loops',
+        max_iterations: 4,
+        max_body_statements: 2,
+    }"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn deserialize_from_string() {
+    json5::from_str::<Box<dyn Rule>>("'unroll_loops'").unwrap();
+}
@@ -0,0 +1,50 @@
+use darklua_core::rules::{Rule, WrapInPcall};
+
+test_rule!(
+    wrap_in_pcall,
+    WrapInPcall::new("__reportError"),
+    wrap_statements("print('hello')") => "local __DARKLUA_PCALL_OK, __DARKLUA_PCALL_ERROR = pcall(function() print('hello') end) \
+        if not __DARKLUA_PCALL_OK then __reportError(__DARKLUA_PCALL_ERROR) end",
+    wrap_module_returning_value("local module = {} return module") => "local function __DARKLUA_PCALL_RESULT(__DARKLUA_PCALL_OK, ...) \
+            if __DARKLUA_PCALL_OK then return ... end \
+            __reportError((...)) \
+        end \
+        return __DARKLUA_PCALL_RESULT(pcall(function() local module = {} return module end))",
+    wrap_module_returning_multiple_values("local a, b = 1, 2 return a, b") => "local function __DARKLUA_PCALL_RESULT(__DARKLUA_PCALL_OK, ...) \
+            if __DARKLUA_PCALL_OK then return ... end \
+            __reportError((...)) \
+        end \
+        return __DARKLUA_PCALL_RESULT(pcall(function() local a, b = 1, 2 return a, b end))",
+    wrap_return_nested_in_if("if condition then return true end print('done')") => "local function __DARKLUA_PCALL_RESULT(__DARKLUA_PCALL_OK, ...) \
+            if __DARKLUA_PCALL_OK then return ... end \
+            __reportError((...)) \
+        end \
+        return __DARKLUA_PCALL_RESULT(pcall(function() if condition then return true end print('done') end))",
+    forward_variable_arguments("local name = ... return name") => "local function __DARKLUA_PCALL_RESULT(__DARKLUA_PCALL_OK, ...) \
+            if __DARKLUA_PCALL_OK then return ... end \
+            __reportError((...)) \
+        end \
+        return __DARKLUA_PCALL_RESULT(pcall(function(...) local name = ... return name end, ...))",
+    forward_variable_arguments_without_return("print(...)") => "local __DARKLUA_PCALL_OK, __DARKLUA_PCALL_ERROR = pcall(function(...) print(...) end, ...) \
+        if not __DARKLUA_PCALL_OK then __reportError(__DARKLUA_PCALL_ERROR) end",
+    do_not_forward_variable_arguments_of_nested_function("local function run(...) return ... end run()") => "local __DARKLUA_PCALL_OK, __DARKLUA_PCALL_ERROR = pcall(function() local function run(...) return ... end run() end) \
+        if not __DARKLUA_PCALL_OK then __reportError(__DARKLUA_PCALL_ERROR) end",
+    keep_exported_type_outside("export type Value = string local value: Value = 'a'") => "export type Value = string \
+        local __DARKLUA_PCALL_OK, __DARKLUA_PCALL_ERROR = pcall(function() local value: Value = 'a' end) \
+        if not __DARKLUA_PCALL_OK then __reportError(__DARKLUA_PCALL_ERROR) end",
+);
+
+test_rule!(
+    wrap_in_pcall_with_field_handler,
+    json5::from_str::<Box<dyn Rule>>(
+        r#"{
+        rule: 'wrap_in_pcall',
+        handler: 'Logger.error',
+    }"#,
+    )
+    .unwrap(),
+    call_field_handler("print('hello')") => "local __DARKLUA_PCALL_OK, __DARKLUA_PCALL_ERROR = pcall(function() print('hello') end) \
+        if not __DARKLUA_PCALL_OK then Logger.error(__DARKLUA_PCALL_ERROR) end",
+);
+
+test_rule_without_effects!(WrapInPcall::new("__reportError"), empty_file(""));
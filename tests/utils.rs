@@ -3,6 +3,7 @@ use std::path::Path;
 use std::time::{Duration, Instant};
 
 use anstyle::{AnsiColor, Style};
+use darklua_core::generator::generate_for_diagnostics;
 use darklua_core::nodes::Block;
 use darklua_core::{Parser, ParserError, Resources};
 use log::Level;
@@ -11,7 +12,7 @@ use log::Level;
 pub fn parse_input(input: &str) -> Block {
     match Parser::default().parse(input) {
         Ok(block) => block,
-        Err(error) => panic!("could not parse content: {:?}\ncontent:\n{}", error, input),
+        Err(error) => panic!("could not parse content:\n{}\ncontent:\n{}", error, input),
     }
 }
 
@@ -20,6 +21,33 @@ pub fn try_parse_input(input: &str) -> Result<Block, ParserError> {
     Parser::default().parse(input)
 }
 
+/// Asserts that two blocks are equal. When they are not, both blocks are generated with
+/// the readable generator and the panic message shows a line diff of the code. The debug
+/// form of the blocks is only shown if the generated code is identical or can't be
+/// generated.
+#[track_caller]
+#[allow(dead_code)]
+pub fn assert_blocks_eq(received: &Block, expected: &Block) {
+    if received == expected {
+        return;
+    }
+
+    match (
+        generate_for_diagnostics(received),
+        generate_for_diagnostics(expected),
+    ) {
+        (Some(received_code), Some(expected_code)) if received_code != expected_code => {
+            panic!(
+                "blocks are not equal\nexpected code:\n{}\nbut received:\n{}\ndiff:\n{}",
+                expected_code,
+                received_code,
+                pretty_assertions::StrComparison::new(&expected_code, &received_code),
+            );
+        }
+        _ => pretty_assertions::assert_eq!(received, expected),
+    }
+}
+
 #[allow(dead_code)]
 pub fn setup_logger(level_filter: log::LevelFilter) {
     env_logger::Builder::new()
@@ -78,6 +106,29 @@ pub fn snapshot_file_process_file_errors(
     });
 }
 
+/// Processes the options and returns the sorted paths of the files written in their output
+/// location, using `/` as the separator.
+#[track_caller]
+#[allow(dead_code)]
+pub fn process_outputs(resources: &Resources, options: darklua_core::Options) -> Vec<String> {
+    let output = options
+        .output()
+        .expect("options should have an output")
+        .to_path_buf();
+
+    darklua_core::process(resources, options)
+        .unwrap()
+        .result()
+        .unwrap();
+
+    let mut outputs: Vec<_> = resources
+        .collect_work(output)
+        .map(|path| path.display().to_string().replace('\\', "/"))
+        .collect();
+    outputs.sort();
+    outputs
+}
+
 #[allow(dead_code)]
 pub fn run_for_minimum_time<F: Fn()>(duration: Duration, func: F) {
     let start = Instant::now();